@@ -10,6 +10,12 @@ fn benchmark_event_serialization(c: &mut Criterion) {
         topic: "test-topic".to_string(),
         payload: json!({"test": "data", "number": 42}),
         published_at: chrono::Utc::now(),
+        partition_key: None,
+        headers: Default::default(),
+        metadata: Default::default(),
+        attributes: Default::default(),
+        schema_valid: None,
+        schema_version: None,
     };
 
     c.bench_function("event_serialization", |b| {
@@ -28,6 +34,12 @@ fn benchmark_event_deserialization(c: &mut Criterion) {
         topic: "test-topic".to_string(),
         payload: json!({"test": "data", "number": 42}),
         published_at: chrono::Utc::now(),
+        partition_key: None,
+        headers: Default::default(),
+        metadata: Default::default(),
+        attributes: Default::default(),
+        schema_valid: None,
+        schema_version: None,
     };
     
     let serialized = serde_json::to_string(&event).unwrap();
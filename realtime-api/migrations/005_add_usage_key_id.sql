@@ -0,0 +1,4 @@
+-- Attribute usage to the API key that generated it (NULL for JWT/system usage)
+ALTER TABLE usage_records ADD COLUMN IF NOT EXISTS key_id VARCHAR(36);
+
+CREATE INDEX IF NOT EXISTS idx_usage_records_tenant_key_metric ON usage_records(tenant_id, key_id, metric);
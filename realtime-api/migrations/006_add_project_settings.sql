@@ -0,0 +1,2 @@
+-- Project-level feature settings (e.g. schema auto-registration)
+ALTER TABLE projects ADD COLUMN IF NOT EXISTS settings JSONB NOT NULL DEFAULT '{}';
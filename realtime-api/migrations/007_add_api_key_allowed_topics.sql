@@ -0,0 +1,2 @@
+-- Restrict an API key to topics matching NATS-style patterns (empty = all topics)
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS allowed_topics JSONB NOT NULL DEFAULT '[]';
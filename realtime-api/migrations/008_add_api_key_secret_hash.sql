@@ -0,0 +1,4 @@
+-- Argon2id hash used to verify API keys. key_hash becomes an indexed SHA-256
+-- lookup prefix; rows where this is NULL still hold the full legacy digest and
+-- are re-hashed on their next successful use
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS secret_hash VARCHAR(255);
@@ -0,0 +1,2 @@
+-- Keyset pagination over a tenant's events orders by (published_at, id)
+CREATE INDEX IF NOT EXISTS idx_events_tenant_published_at_id ON events(tenant_id, published_at DESC, id DESC);
@@ -0,0 +1,15 @@
+-- Versioned JSON Schemas registered per project topic; the highest version is active
+CREATE TABLE IF NOT EXISTS topic_schemas (
+    tenant_id VARCHAR(36) NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    project_id VARCHAR(36) NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    topic VARCHAR(255) NOT NULL,
+    version INTEGER NOT NULL,
+    json_schema JSONB NOT NULL,
+    mode VARCHAR(16) NOT NULL DEFAULT 'enforce',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (project_id, topic, version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_topic_schemas_tenant_project ON topic_schemas(tenant_id, project_id);
+
+ALTER TABLE topic_schemas ENABLE ROW LEVEL SECURITY;
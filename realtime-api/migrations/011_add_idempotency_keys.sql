@@ -0,0 +1,17 @@
+-- Producer-supplied idempotency keys, scoped per tenant/project, mapping to the
+-- event first published with that key. Rows expire after the configured TTL.
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    tenant_id VARCHAR(36) NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    project_id VARCHAR(36) NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    idempotency_key VARCHAR(255) NOT NULL,
+    event_id VARCHAR(36) NOT NULL,
+    -- NULL while the first publish is still in flight
+    sequence BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (tenant_id, project_id, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_expires_at ON idempotency_keys(expires_at);
+
+ALTER TABLE idempotency_keys ENABLE ROW LEVEL SECURITY;
@@ -0,0 +1,25 @@
+-- Usage is accumulated into one row per tenant/project/metric/window/key instead
+-- of one row per connection or event. Fold existing rows into the oldest row of
+-- each window before enforcing uniqueness.
+UPDATE usage_records u
+SET quantity = totals.quantity
+FROM (
+    SELECT MIN(id) AS id, SUM(quantity) AS quantity
+    FROM usage_records
+    GROUP BY tenant_id, project_id, metric, window_start, COALESCE(key_id, '')
+    HAVING COUNT(*) > 1
+) totals
+WHERE u.id = totals.id;
+
+DELETE FROM usage_records u
+USING usage_records kept
+WHERE u.tenant_id = kept.tenant_id
+  AND u.project_id = kept.project_id
+  AND u.metric = kept.metric
+  AND u.window_start = kept.window_start
+  AND COALESCE(u.key_id, '') = COALESCE(kept.key_id, '')
+  AND u.id > kept.id;
+
+-- NULL key_id (JWT/system usage) shares one row per window
+CREATE UNIQUE INDEX IF NOT EXISTS idx_usage_records_window_unique
+    ON usage_records(tenant_id, project_id, metric, window_start, (COALESCE(key_id, '')));
@@ -0,0 +1,3 @@
+-- Last four characters of the raw key, so operators can tell which physical key
+-- a row belongs to. NULL for keys created before this was recorded.
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS key_last4 VARCHAR(4);
@@ -0,0 +1,4 @@
+-- When and from where each API key was last used, recorded at most once per
+-- minute per key, so operators can find stale keys to rotate
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS last_used_at TIMESTAMPTZ;
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS last_used_ip INET;
@@ -0,0 +1,17 @@
+-- HTTP endpoints that receive a project's published events, signed with a
+-- per-webhook secret; topic_filter uses the same wildcards as subscriptions
+CREATE TABLE IF NOT EXISTS webhooks (
+    id VARCHAR(36) PRIMARY KEY,
+    tenant_id VARCHAR(36) NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    project_id VARCHAR(36) NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    secret VARCHAR(255) NOT NULL,
+    topic_filter VARCHAR(255) NOT NULL DEFAULT '>',
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_webhooks_tenant_project ON webhooks(tenant_id, project_id) WHERE active;
+
+ALTER TABLE webhooks ENABLE ROW LEVEL SECURITY;
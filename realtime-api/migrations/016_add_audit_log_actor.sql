@@ -0,0 +1,6 @@
+-- Record who performed each admin action and what it targeted, so the audit
+-- log can be queried per tenant by time range and action
+ALTER TABLE audit_logs ADD COLUMN IF NOT EXISTS actor_api_key_id VARCHAR(36);
+ALTER TABLE audit_logs ADD COLUMN IF NOT EXISTS target VARCHAR(255);
+
+CREATE INDEX IF NOT EXISTS idx_audit_logs_tenant_created ON audit_logs(tenant_id, created_at DESC);
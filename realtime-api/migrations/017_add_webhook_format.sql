@@ -0,0 +1,2 @@
+-- Encoding of events POSTed to a webhook: native event JSON or a CloudEvents envelope
+ALTER TABLE webhooks ADD COLUMN IF NOT EXISTS format VARCHAR(32) NOT NULL DEFAULT 'native';
@@ -0,0 +1,21 @@
+-- Usage totals of closed billing cycles, kept after the cycle's usage records
+-- are cleared for the next cycle
+CREATE TABLE IF NOT EXISTS usage_history (
+    id VARCHAR(36) PRIMARY KEY,
+    tenant_id VARCHAR(36) NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    project_id VARCHAR(36) NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    metric usage_metric NOT NULL,
+    quantity BIGINT NOT NULL,
+    cycle_start TIMESTAMPTZ NOT NULL,
+    cycle_end TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_history_tenant_cycle ON usage_history(tenant_id, cycle_start DESC);
+
+-- Start of the tenant's current billing cycle; NULL until the rollover job first runs
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS usage_cycle_start TIMESTAMPTZ;
+
+-- Why a suspended tenant was suspended, so usage suspensions can be lifted
+-- when a new cycle starts without lifting payment suspensions
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS suspension_reason VARCHAR(255);
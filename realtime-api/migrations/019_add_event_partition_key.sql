@@ -0,0 +1,2 @@
+-- Optional ordering key; events sharing a key are delivered in publish order
+ALTER TABLE events ADD COLUMN IF NOT EXISTS partition_key VARCHAR(255);
@@ -0,0 +1,2 @@
+-- Soft delete for projects; archived projects are hidden and reject new connections
+ALTER TABLE projects ADD COLUMN IF NOT EXISTS archived_at TIMESTAMPTZ;
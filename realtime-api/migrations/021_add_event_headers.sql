@@ -0,0 +1,2 @@
+-- Producer metadata delivered alongside the payload
+ALTER TABLE events ADD COLUMN IF NOT EXISTS headers JSONB NOT NULL DEFAULT '{}';
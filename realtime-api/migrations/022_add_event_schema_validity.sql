@@ -0,0 +1,3 @@
+-- Schema validation outcome recorded at publish time; NULL for older events
+ALTER TABLE events ADD COLUMN IF NOT EXISTS schema_valid BOOLEAN;
+ALTER TABLE events ADD COLUMN IF NOT EXISTS schema_version INTEGER;
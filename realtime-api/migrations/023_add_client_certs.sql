@@ -0,0 +1,21 @@
+-- Client certificates accepted for mTLS authentication in place of an API
+-- key, matched by public key fingerprint or, for CA-issued identities, subject
+CREATE TABLE IF NOT EXISTS client_certs (
+    id VARCHAR(36) PRIMARY KEY,
+    tenant_id VARCHAR(36) NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    project_id VARCHAR(36) NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    spki_sha256 CHAR(64),
+    subject TEXT,
+    scopes JSONB NOT NULL,
+    rate_limit_per_sec INTEGER NOT NULL DEFAULT 100,
+    allowed_topics JSONB NOT NULL DEFAULT '[]',
+    is_active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (spki_sha256 IS NOT NULL OR subject IS NOT NULL)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_client_certs_spki ON client_certs(spki_sha256) WHERE is_active AND spki_sha256 IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_client_certs_subject ON client_certs(subject) WHERE is_active AND spki_sha256 IS NULL;
+
+ALTER TABLE client_certs ENABLE ROW LEVEL SECURITY;
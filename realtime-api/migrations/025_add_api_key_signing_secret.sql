@@ -0,0 +1,4 @@
+-- Secret for HMAC-signed requests made with an API key. Unlike the key itself
+-- it has to be readable to recompute signatures; NULL for keys created before
+-- request signing, which can only be used as bearer keys
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS signing_secret VARCHAR(255);
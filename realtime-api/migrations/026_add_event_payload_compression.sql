@@ -0,0 +1,9 @@
+-- Large payloads are stored zstd-compressed in payload_zstd with payload left
+-- NULL; payload_compressed says which column holds an event's JSON
+ALTER TABLE events ALTER COLUMN payload DROP NOT NULL;
+ALTER TABLE events ADD COLUMN IF NOT EXISTS payload_compressed BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE events ADD COLUMN IF NOT EXISTS payload_zstd BYTEA;
+
+ALTER TABLE events ADD CONSTRAINT events_payload_stored CHECK (
+    CASE WHEN payload_compressed THEN payload_zstd IS NOT NULL ELSE payload IS NOT NULL END
+);
@@ -0,0 +1,8 @@
+-- Signing secrets are now stored encrypted. Secrets written in plain text
+-- before are dropped; rotating such a key issues it a new one
+UPDATE api_keys SET signing_secret = NULL WHERE signing_secret LIKE 'rtsk\_%';
+ALTER TABLE api_keys ALTER COLUMN signing_secret TYPE TEXT;
+
+-- Signing secret replaced by the last rotation, accepted until
+-- previous_expires_at like the previous key secret
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS previous_signing_secret TEXT;
@@ -0,0 +1,3 @@
+-- Producer metadata and attributes, kept and delivered with the event
+ALTER TABLE events ADD COLUMN IF NOT EXISTS metadata JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE events ADD COLUMN IF NOT EXISTS attributes JSONB NOT NULL DEFAULT '{}';
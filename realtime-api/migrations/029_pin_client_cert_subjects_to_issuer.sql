@@ -0,0 +1,13 @@
+-- Any trusted CA can issue a certificate with a given subject, so subject
+-- registrations are pinned to the issuer that was registered with them.
+-- Existing subject-only rows have no issuer and stop matching.
+ALTER TABLE client_certs ADD COLUMN IF NOT EXISTS issuer TEXT;
+
+UPDATE client_certs SET is_active = false, updated_at = NOW()
+WHERE spki_sha256 IS NULL AND issuer IS NULL;
+
+ALTER TABLE client_certs ADD CONSTRAINT client_certs_subject_issuer
+    CHECK (spki_sha256 IS NOT NULL OR (subject IS NOT NULL AND issuer IS NOT NULL)) NOT VALID;
+
+DROP INDEX IF EXISTS idx_client_certs_subject;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_client_certs_issuer_subject ON client_certs(issuer, subject) WHERE is_active AND spki_sha256 IS NULL;
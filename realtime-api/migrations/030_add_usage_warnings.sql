@@ -0,0 +1,10 @@
+-- Soft-limit usage warnings already fired, one per tenant, metric, threshold
+-- and quota window, so every instance warns at most once per window
+CREATE TABLE IF NOT EXISTS usage_warnings (
+    tenant_id VARCHAR(36) NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    metric usage_metric NOT NULL,
+    threshold_percent INTEGER NOT NULL,
+    window_start TIMESTAMPTZ NOT NULL,
+    fired_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant_id, metric, threshold_percent, window_start)
+);
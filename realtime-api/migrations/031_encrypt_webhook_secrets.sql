@@ -0,0 +1,3 @@
+-- Webhook secrets are now stored encrypted, which no longer fits 255 characters.
+-- Secrets written in plain text before are encrypted when the server starts
+ALTER TABLE webhooks ALTER COLUMN secret TYPE TEXT;
@@ -38,7 +38,7 @@ pub struct Alert {
 }
 
 /// Alerting service for sending notifications about errors and performance issues
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct AlertingService {
     config: ObservabilityConfig,
     client: Client,
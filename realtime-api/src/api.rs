@@ -1,21 +1,37 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
     Extension,
 };
+use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use tracing::{error, info, warn};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 use crate::alerting::AlertingService;
+use crate::api_error::{require_any_scope, require_scope, ApiError};
 use crate::auth::{AuthContext, AuthService};
+use crate::circuit_breaker::circuit_open;
+use crate::cloudevents::DeliveryFormat;
+use crate::config::{BillingConfig, CorsConfig, GraphQLConfig, HttpConfig};
 use crate::database::Database;
-use crate::event_service::{EventService, PublishResult};
-use crate::models::{Event, Scope, Tenant, UsageMetric, Permission, UserRole};
-use crate::observability::Metrics;
+use crate::event_service::{BatchItem, BatchItemError, DeadLetterReplay, EventService, PublishResult};
+use crate::nats::{DeadLetter, EventCursor, ReplayRequest};
+use crate::models::{
+    ApiKey, BillingPlan, Event, EventBuildError, EventPageCursor, MetadataLimits, Permission, Project, ProjectLimits, ProjectLimitsUpdate, Scope, Tenant, UsageMetric, UsageRecord, UserRole,
+    AuditLog, AuditQuery, TenantStatus, Webhook, WebhookUpdate,
+};
+use crate::observability::{
+    tenant_log_levels, Metrics, SlaSummary, DEFAULT_SLA_WINDOW, MAX_SLA_WINDOW,
+};
+use crate::request_id::current_request_id;
+use crate::schema_validator::{CompatibilityMode, SchemaMode, SchemaValidator, ValidationMode};
+use crate::stripe_webhook::{StripeWebhookEvent, STRIPE_SIGNATURE_HEADER};
+use crate::usage_warnings::usage_window_start;
 
 /// Application state shared across handlers
 #[derive(Clone)]
@@ -25,51 +41,279 @@ pub struct AppState {
     pub auth_service: AuthService,
     pub metrics: Metrics,
     pub alerting: AlertingService,
+    pub max_page_size: i64,
+    pub metadata_limits: MetadataLimits,
+    /// Serve Prometheus metrics at `GET /metrics`
+    pub metrics_enabled: bool,
+    pub billing: BillingConfig,
+    /// Browser origins allowed to call the API
+    pub cors: CorsConfig,
+    /// Depth and complexity limits applied to GraphQL queries
+    pub graphql: GraphQLConfig,
+    /// Request body cap and REST request timeout
+    pub http: HttpConfig,
+}
+
+impl AppState {
+    /// Suspend a tenant, terminating its WebSocket and SSE connections and
+    /// recording an audit entry attributed to `actor_api_key_id`. Returns the
+    /// terminated connection ids.
+    pub async fn suspend_tenant(
+        &self,
+        tenant_id: &str,
+        reason: &str,
+        actor_api_key_id: Option<&str>,
+    ) -> anyhow::Result<Vec<String>> {
+        crate::kill_switch::suspend_tenant(&self.database, tenant_id, reason, actor_api_key_id)
+            .await
+    }
+
+    /// Return a suspended tenant to `Active`
+    pub async fn unsuspend_tenant(
+        &self,
+        tenant_id: &str,
+        reason: &str,
+        actor_api_key_id: Option<&str>,
+    ) -> anyhow::Result<()> {
+        crate::kill_switch::unsuspend_tenant(&self.database, tenant_id, reason, actor_api_key_id)
+            .await
+    }
+}
+
+/// Default number of items returned by list endpoints when no `limit` is given
+pub const DEFAULT_PAGE_SIZE: i64 = 100;
+
+/// Default ceiling on the page size a client can request
+pub const DEFAULT_MAX_PAGE_SIZE: i64 = 1000;
+
+/// Header carrying a producer-chosen key that makes `POST /events` retries safe
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Resolve a requested page size against the default and the configured ceiling
+pub fn clamp_page_size(requested: Option<i64>, max_page_size: i64) -> i64 {
+    let max_page_size = max_page_size.max(1);
+    match requested {
+        Some(limit) if limit > 0 => limit.min(max_page_size),
+        _ => DEFAULT_PAGE_SIZE.min(max_page_size),
+    }
+}
+
+/// Envelope for list responses, reporting the effective page size
+#[derive(Debug, Serialize, ToSchema)]
+#[aliases(
+    EventPageResponse = PageResponse<Event>,
+    AuditLogPageResponse = PageResponse<AuditLog>,
+    DeadLetterPageResponse = PageResponse<DeadLetter>
+)]
+pub struct PageResponse<T> {
+    pub items: Vec<T>,
+    pub limit: i64,
+    pub max_page_size: i64,
+    /// Cursor for the next page, present when more items remain
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Query parameters for list endpoints
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListQuery {
+    pub limit: Option<i64>,
+    /// Opaque cursor returned as `next_cursor` by the previous page
+    pub cursor: Option<String>,
+}
+
+/// Query parameters for replaying persisted events
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReplayQuery {
+    /// JetStream sequence to start from, e.g. one returned by a publish
+    pub from_sequence: Option<u64>,
+    /// Replay events published at or after this time
+    pub from_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+    pub topic: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Query parameters for listing the audit log
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AuditLogQuery {
+    /// Only list entries for this action, e.g. `api_key_revoked`
+    pub action: Option<String>,
+    /// Only list entries recorded at or after this time
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only list entries recorded at or before this time
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    pub limit: Option<i64>,
+}
+
+/// Query parameters for listing dead-lettered events
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeadLetterQuery {
+    pub project_id: Option<String>,
+    /// Only list events dead-lettered at or after this time (default: the last 24 hours)
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    pub limit: Option<i64>,
+}
+
+/// Response for replaying a dead-lettered event
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReplayDeadLetterResponse {
+    pub id: u64,
+    pub event_id: String,
+    /// Stream sequence of the re-published event; absent for a webhook or Kafka redelivery
+    pub sequence: Option<u64>,
 }
 
 /// Request payload for publishing events
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct PublishEventRequest {
     pub topic: String,
     pub payload: Value,
+    /// Events with the same key are delivered and replayed in the order they
+    /// were accepted; different keys may be processed in parallel, one
+    /// `.p{n}` partition subject each
+    #[serde(default)]
+    pub partition_key: Option<String>,
+    /// Delivered with the event to subscribers and webhooks; `ce-*` names are reserved
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// Stored and delivered with the event, within the configured limits
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// Stored and delivered with the event, within the configured limits
+    #[serde(default)]
+    pub attributes: HashMap<String, String>,
 }
 
 /// Response for successful event publishing
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PublishEventResponse {
     pub event_id: String,
     pub sequence: u64,
     pub published_at: String,
 }
 
+/// Request payload for publishing several events in one round trip
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PublishBatchRequest {
+    pub events: Vec<PublishEventRequest>,
+}
+
+/// Outcome for one event of a batch publish
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum BatchEventResult {
+    Published { event_id: String, sequence: u64 },
+    Failed(ErrorResponse),
+}
+
+/// Response for a batch publish, with one result per event in request order
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PublishBatchResponse {
+    pub results: Vec<BatchEventResult>,
+}
+
 /// Request payload for creating API keys
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateApiKeyRequest {
     pub name: String,
     pub scopes: Vec<String>,
     pub rate_limit_per_sec: Option<i32>,
     pub expires_in_days: Option<i64>,
+    /// NATS-style topic patterns (`*`, `>`) the key is limited to; empty allows all
+    #[serde(default)]
+    pub allowed_topics: Vec<String>,
 }
 
 /// Response for API key creation
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CreateApiKeyResponse {
     pub id: String,
     pub key: String,
     pub scopes: Vec<String>,
     pub rate_limit_per_sec: i32,
+    pub allowed_topics: Vec<String>,
+    pub expires_at: Option<String>,
+    /// Secret for HMAC-signed requests made with this key; only returned here
+    pub signing_secret: Option<String>,
+}
+
+/// Response for API key rotation
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RotateApiKeyResponse {
+    pub id: String,
+    /// The new raw key; only returned here
+    pub key: String,
+    /// The new secret for HMAC-signed requests; only returned here
+    pub signing_secret: Option<String>,
+    /// When the previous key and signing secret stop being accepted
+    pub previous_key_expires_at: Option<String>,
+}
+
+/// API key as listed to admins; never includes the raw key or its hashes
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiKeySummary {
+    pub id: String,
+    pub project_id: String,
+    /// Prefix and last four characters of the raw key, e.g. `rtp_...x7Qz`
+    pub key_hint: Option<String>,
+    pub scopes: Vec<String>,
+    pub rate_limit_per_sec: i32,
+    pub allowed_topics: Vec<String>,
+    pub is_active: bool,
     pub expires_at: Option<String>,
+    pub last_used_at: Option<String>,
+    pub last_used_ip: Option<String>,
+    /// Unused for longer than the configured stale threshold
+    pub stale: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+impl From<ApiKey> for ApiKeySummary {
+    fn from(api_key: ApiKey) -> Self {
+        let key_hint = api_key.key_hint();
+        Self {
+            id: api_key.id,
+            project_id: api_key.project_id,
+            key_hint,
+            scopes: api_key.scopes.iter().map(|s| scope_name(s).to_string()).collect(),
+            rate_limit_per_sec: api_key.rate_limit_per_sec,
+            allowed_topics: api_key.allowed_topics,
+            is_active: api_key.is_active,
+            expires_at: api_key.expires_at.map(|dt| dt.to_rfc3339()),
+            last_used_at: api_key.last_used_at.map(|dt| dt.to_rfc3339()),
+            last_used_ip: api_key.last_used_ip,
+            stale: false,
+            created_at: api_key.created_at.to_rfc3339(),
+            updated_at: api_key.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+// Scope names as accepted by `CreateApiKeyRequest`
+pub(crate) fn scope_name(scope: &Scope) -> &'static str {
+    match scope {
+        Scope::EventsPublish => "events:publish",
+        Scope::EventsSubscribe => "events:subscribe",
+        Scope::AdminRead => "admin:read",
+        Scope::AdminWrite => "admin:write",
+        Scope::BillingRead => "billing:read",
+    }
 }
 
 /// Request payload for creating tenants
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateTenantRequest {
     pub name: String,
     pub plan: String,
 }
 
 /// Response for tenant creation
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CreateTenantResponse {
     pub id: String,
     pub name: String,
@@ -77,29 +321,132 @@ pub struct CreateTenantResponse {
     pub created_at: String,
 }
 
+/// Request payload for onboarding a tenant
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct OnboardRequest {
+    pub tenant_name: String,
+    pub plan: String,
+    pub project_name: Option<String>,
+}
+
+/// Response for onboarding; the raw API key is only ever returned here
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OnboardResponse {
+    pub tenant: CreateTenantResponse,
+    pub project: Project,
+    pub api_key: CreateApiKeyResponse,
+}
+
+/// Query parameters for schema registration
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RegisterSchemaQuery {
+    /// Compatibility the new version must keep with the active one
+    #[serde(default)]
+    pub compat: CompatibilityMode,
+}
+
+/// Response for schema registration
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RegisterSchemaResponse {
+    pub project_id: String,
+    pub topic: String,
+    pub version: u32,
+}
+
+/// Effective schema for a topic
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TopicSchemaResponse {
+    pub project_id: String,
+    pub topic: String,
+    pub version: u32,
+    pub mode: SchemaMode,
+    pub schema: Value,
+}
+
+/// Request to override the log level for a tenant
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TenantLogLevelRequest {
+    pub level: String,
+}
+
 /// Query parameters for usage reporting
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct UsageQuery {
     pub metric: Option<String>,
     pub start_date: Option<String>,
     pub end_date: Option<String>,
 }
 
-/// Usage report response
+/// Format of a raw usage export
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+/// Query parameters for exporting raw usage records
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UsageExportQuery {
+    /// Only export windows starting at or after this time
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only export windows starting at or before this time
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub format: UsageExportFormat,
+}
+
+/// Header row of a CSV usage export
+pub const USAGE_EXPORT_CSV_HEADER: &str = "tenant_id,project_id,metric,quantity,window_start";
+
+/// One usage record in a JSON usage export
 #[derive(Debug, Serialize)]
+struct UsageExportRow<'a> {
+    tenant_id: &'a str,
+    project_id: &'a str,
+    metric: &'static str,
+    quantity: i64,
+    window_start: chrono::DateTime<chrono::Utc>,
+}
+
+impl<'a> From<&'a UsageRecord> for UsageExportRow<'a> {
+    fn from(record: &'a UsageRecord) -> Self {
+        Self {
+            tenant_id: &record.tenant_id,
+            project_id: &record.project_id,
+            metric: record.metric.as_str(),
+            quantity: record.quantity,
+            window_start: record.window_start,
+        }
+    }
+}
+
+/// Usage report response
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UsageReportResponse {
     pub tenant_id: String,
     pub metrics: HashMap<String, i64>,
     pub period: String,
 }
 
+/// Usage attributed to a single API key
+#[derive(Debug, Serialize, ToSchema)]
+pub struct KeyUsageResponse {
+    pub key_id: String,
+    pub metrics: HashMap<String, i64>,
+}
+
 /// Error response structure
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ErrorResponse {
     pub error: ErrorDetail,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ErrorDetail {
     pub code: String,
     pub message: String,
@@ -114,18 +461,40 @@ impl ErrorResponse {
                 code: code.to_string(),
                 message: message.to_string(),
                 details,
-                request_id: Uuid::new_v4().to_string(),
+                request_id: current_request_id().unwrap_or_else(|| Uuid::new_v4().to_string()),
             },
         }
     }
 }
 
 /// POST /events - Publish an event
+///
+/// With an `Idempotency-Key` header, repeating the request with the same key
+/// within the idempotency TTL (24 hours by default) returns the original
+/// `event_id` and `sequence` instead of publishing again.
+#[utoipa::path(
+    post,
+    path = "/events",
+    tag = "events",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Key that makes retries of this request publish once")),
+    request_body = PublishEventRequest,
+    responses(
+        (status = 200, description = "Event published, or the original result for a repeated idempotency key", body = PublishEventResponse),
+        (status = 400, description = "Invalid event: VALIDATION_FAILED, INVALID_TOPIC, INVALID_EVENT, INVALID_HEADER, RESERVED_HEADER, METADATA_LIMIT_EXCEEDED, INVALID_IDEMPOTENCY_KEY", body = ErrorResponse),
+        (status = 403, description = "Missing scope or topic not allowed for the key: INSUFFICIENT_SCOPE, TOPIC_NOT_ALLOWED", body = ErrorResponse),
+        (status = 409, description = "Idempotency key reused with a different request: IDEMPOTENCY_KEY_IN_USE", body = ErrorResponse),
+        (status = 413, description = "Payload over the project's max_payload_size: PAYLOAD_TOO_LARGE", body = ErrorResponse),
+        (status = 429, description = "The key or the project as a whole is publishing too fast; see Retry-After: RATE_LIMIT_EXCEEDED, PROJECT_RATE_LIMIT_EXCEEDED", body = ErrorResponse),
+        (status = 500, description = "Publishing failed: PUBLISH_FAILED", body = ErrorResponse),
+        (status = 503, description = "NATS or the database is failing and requests fail fast until it recovers; see Retry-After: DEPENDENCY_UNAVAILABLE", body = ErrorResponse),
+    )
+)]
 pub async fn publish_event(
     State(state): State<AppState>,
     Extension(auth): Extension<AuthContext>,
+    headers: HeaderMap,
     Json(request): Json<PublishEventRequest>,
-) -> Result<Json<PublishEventResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<PublishEventResponse>, ApiError> {
     use crate::observability::add_correlation_id;
     
     // Add correlation ID for tracing
@@ -135,7 +504,7 @@ pub async fn publish_event(
     let start_time = std::time::Instant::now();
     
     // Check if the API key has publish permissions
-    if !auth.scopes.contains(&Scope::EventsPublish) {
+    if let Err(e) = require_scope(&auth, Scope::EventsPublish) {
         state.metrics.record_auth_operation("scope_check", false);
         state.alerting.alert_error(
             "Insufficient Permissions",
@@ -152,84 +521,126 @@ pub async fn publish_event(
             "Insufficient permissions for event publishing: tenant={}, scopes={:?}",
             auth.tenant_id, auth.scopes
         );
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(ErrorResponse::new(
-                "INSUFFICIENT_SCOPE",
-                "API key lacks events:publish permission",
-                Some(json!({
-                    "required_scope": "events:publish",
-                    "available_scopes": auth.scopes,
-                    "correlation_id": correlation_id
-                })),
-            )),
-        ));
+        return Err(e.with_details(json!({"correlation_id": correlation_id})));
     }
 
     state.metrics.record_auth_operation("scope_check", true);
 
-    // Validate topic name
-    if request.topic.is_empty() || request.topic.len() > 255 {
-        state.metrics.record_error("validation_error", "invalid_topic");
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse::new(
-                "INVALID_TOPIC",
-                "Topic name must be between 1 and 255 characters",
-                Some(json!({
+    if !auth.allows_topic(&request.topic) {
+        warn!(
+            correlation_id = correlation_id,
+            "API key may not publish to topic: tenant={}, topic={}",
+            auth.tenant_id, request.topic
+        );
+        return Err(ApiError::forbidden(
+            "TOPIC_NOT_ALLOWED",
+            format!("API key may not publish to topic {}", request.topic),
+        )
+        .with_details(json!({
+            "allowed_topics": auth.allowed_topics,
+            "correlation_id": correlation_id
+        })));
+    }
+
+    // Reject producers attaching unbounded metadata or attributes
+    if let Err(e) = state
+        .metadata_limits
+        .check(&request.metadata, &request.attributes)
+    {
+        state
+            .metrics
+            .record_error("validation_error", "metadata_limit_exceeded");
+        return Err(
+            ApiError::validation("METADATA_LIMIT_EXCEEDED", e.to_string()).with_details(json!({
+                "limits": state.metadata_limits,
+                "correlation_id": correlation_id
+            })),
+        );
+    }
+
+    // Build the event, enforcing topic format and the platform's payload size;
+    // the project's own limit is enforced when publishing
+    let event = match Event::builder()
+        .tenant_id(auth.tenant_id.clone())
+        .project_id(auth.project_id.clone())
+        .topic(request.topic.clone())
+        .payload(request.payload)
+        .partition_key(request.partition_key)
+        .headers(request.headers)
+        .metadata(request.metadata)
+        .attributes(request.attributes)
+        .build()
+    {
+        Ok(event) => event,
+        Err(EventBuildError::PayloadTooLarge { size, limit }) => {
+            state.metrics.record_error("validation_error", "payload_too_large");
+            return Err(ApiError::payload_too_large(size, limit).with_details(json!({
+                "project_id": auth.project_id,
+                "correlation_id": correlation_id
+            })));
+        }
+        Err(EventBuildError::InvalidTopic(msg)) => {
+            state
+                .metrics
+                .record_error("validation_error", "invalid_topic");
+            return Err(
+                ApiError::validation("INVALID_TOPIC", msg).with_details(json!({
                     "topic": request.topic,
                     "length": request.topic.len(),
                     "correlation_id": correlation_id
                 })),
-            )),
-        ));
-    }
-
-    // Validate payload size (1MB limit)
-    let payload_size = serde_json::to_string(&request.payload)
-        .map_err(|e| {
-            state.metrics.record_error("serialization_error", "invalid_payload");
-            error!(correlation_id = correlation_id, "Failed to serialize payload: {}", e);
-            (
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse::new(
-                    "INVALID_PAYLOAD",
-                    "Payload must be valid JSON",
-                    Some(json!({
-                        "error": e.to_string(),
-                        "correlation_id": correlation_id
-                    })),
-                )),
+            );
+        }
+        Err(EventBuildError::ReservedHeader(header)) => {
+            state
+                .metrics
+                .record_error("validation_error", "reserved_header");
+            return Err(ApiError::validation(
+                "RESERVED_HEADER",
+                format!(
+                    "Header '{}' is set by the platform and can't be overridden",
+                    header
+                ),
             )
-        })?
-        .len();
-
-    if payload_size > 1024 * 1024 {
-        state.metrics.record_error("validation_error", "payload_too_large");
-        return Err((
-            StatusCode::PAYLOAD_TOO_LARGE,
-            Json(ErrorResponse::new(
-                "PAYLOAD_TOO_LARGE",
-                "Payload exceeds 1MB limit",
-                Some(json!({
-                    "size": payload_size,
-                    "limit": 1024 * 1024,
-                    "correlation_id": correlation_id
-                })),
-            )),
-        ));
-    }
+            .with_details(json!({
+                "header": header,
+                "correlation_id": correlation_id
+            })));
+        }
+        Err(EventBuildError::InvalidHeader(msg)) => {
+            state
+                .metrics
+                .record_error("validation_error", "invalid_header");
+            return Err(ApiError::validation("INVALID_HEADER", msg)
+                .with_details(json!({"correlation_id": correlation_id})));
+        }
+        Err(e) => {
+            state
+                .metrics
+                .record_error("validation_error", "invalid_event");
+            return Err(ApiError::validation("INVALID_EVENT", e.to_string())
+                .with_details(json!({"correlation_id": correlation_id})));
+        }
+    };
 
-    // Publish the event
-    let event = Event::new(
-        auth.tenant_id.clone(),
-        auth.project_id.clone(),
-        request.topic.clone(),
-        request.payload,
-    );
+    let idempotency_key = match headers.get(IDEMPOTENCY_KEY_HEADER).map(|v| v.to_str()) {
+        None => None,
+        Some(Ok(key)) => Some(key),
+        Some(Err(_)) => {
+            return Err(ApiError::validation(
+                "INVALID_IDEMPOTENCY_KEY",
+                "Idempotency key must contain only visible ASCII characters",
+            )
+            .with_details(json!({"correlation_id": correlation_id})));
+        }
+    };
 
-    match state.event_service.publish_event(&event).await {
-        Ok(PublishResult::Success) => {
+    match state
+        .event_service
+        .publish_event_idempotent(&event, auth.api_key_id(), idempotency_key)
+        .await
+    {
+        Ok(PublishResult::Success { sequence }) => {
             // Record successful event publication
             state.metrics.record_event_published(&auth.tenant_id, &request.topic);
             
@@ -244,280 +655,1971 @@ pub async fn publish_event(
 
             Ok(Json(PublishEventResponse {
                 event_id: event.id,
-                sequence: 0, // Placeholder - would come from NATS in real implementation
+                sequence,
+                published_at: event.published_at.to_rfc3339(),
+            }))
+        }
+        Ok(PublishResult::Deduplicated {
+            original_event_id,
+            sequence,
+        }) => {
+            state.metrics.record_event_deduplicated(&auth.tenant_id, &request.topic);
+
+            let duration = start_time.elapsed().as_secs_f64();
+            state.metrics.record_api_request("POST", "/events", duration);
+
+            info!(
+                correlation_id = correlation_id,
+                "Event deduplicated: event_id={}, original_event_id={}, topic={}",
+                event.id, original_event_id, request.topic
+            );
+
+            Ok(Json(PublishEventResponse {
+                event_id: original_event_id,
+                sequence,
+                published_at: event.published_at.to_rfc3339(),
+            }))
+        }
+        Ok(PublishResult::Replayed { event_id, sequence }) => {
+            state.metrics.record_event_deduplicated(&auth.tenant_id, &request.topic);
+
+            let duration = start_time.elapsed().as_secs_f64();
+            state.metrics.record_api_request("POST", "/events", duration);
+
+            info!(
+                correlation_id = correlation_id,
+                "Idempotent publish replayed: event_id={}, sequence={}, topic={}",
+                event_id, sequence, request.topic
+            );
+
+            Ok(Json(PublishEventResponse {
+                event_id,
+                sequence,
                 published_at: event.published_at.to_rfc3339(),
             }))
         }
+        Ok(PublishResult::IdempotencyKeyInUse) => Err(ApiError::conflict(
+            "IDEMPOTENCY_KEY_IN_USE",
+            "A request with this idempotency key is still being processed",
+        )
+        .with_details(json!({"correlation_id": correlation_id}))),
+        Ok(PublishResult::RateLimited(status)) => {
+            state
+                .metrics
+                .record_error("rate_limit_error", "project_rate_limit_exceeded");
+            Err(ApiError::project_rate_limited(status)
+                .with_details(json!({"correlation_id": correlation_id})))
+        }
+        Ok(PublishResult::PayloadTooLarge { size, limit }) => {
+            state.metrics.record_error("validation_error", "payload_too_large");
+            Err(ApiError::payload_too_large(size, limit).with_details(json!({
+                "project_id": auth.project_id,
+                "correlation_id": correlation_id
+            })))
+        }
         Ok(PublishResult::ValidationFailed(msg)) => {
-            state.metrics.record_error("validation_error", "event_validation_failed");
-            warn!(correlation_id = correlation_id, "Event validation failed: {}", msg);
-            Err((
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse::new(
-                    "VALIDATION_FAILED", 
-                    &msg, 
-                    Some(json!({"correlation_id": correlation_id}))
-                )),
-            ))
+            state
+                .metrics
+                .record_error("validation_error", "event_validation_failed");
+            warn!(
+                correlation_id = correlation_id,
+                "Event validation failed: {}", msg
+            );
+            Err(ApiError::validation("VALIDATION_FAILED", msg)
+                .with_details(json!({"correlation_id": correlation_id})))
         }
         Err(e) => {
-            state.metrics.record_error("publish_error", "event_publish_failed");
-            state.alerting.alert_error(
-                "Event Publishing Failed",
-                &e.to_string(),
-                json!({
-                    "tenant_id": auth.tenant_id,
-                    "topic": request.topic,
-                    "correlation_id": correlation_id
-                })
-            ).await;
-            
-            error!(correlation_id = correlation_id, "Failed to publish event: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse::new(
-                    "PUBLISH_FAILED",
-                    "Failed to publish event",
-                    Some(json!({
-                        "error": e.to_string(),
+            // The breaker alerted when it opened; each refused publish doesn't
+            if let Some(open) = circuit_open(&e) {
+                state
+                    .metrics
+                    .record_error("publish_error", "dependency_unavailable");
+                warn!(
+                    correlation_id = correlation_id,
+                    "Publish refused while {} is unavailable", open.dependency
+                );
+                return Err(ApiError::circuit_open(open)
+                    .with_details(json!({"correlation_id": correlation_id})));
+            }
+
+            state
+                .metrics
+                .record_error("publish_error", "event_publish_failed");
+            state
+                .alerting
+                .alert_error(
+                    "Event Publishing Failed",
+                    &e.to_string(),
+                    json!({
+                        "tenant_id": auth.tenant_id,
+                        "topic": request.topic,
                         "correlation_id": correlation_id
-                    })),
-                )),
-            ))
+                    }),
+                )
+                .await;
+
+            error!(
+                correlation_id = correlation_id,
+                "Failed to publish event: {}", e
+            );
+            Err(
+                ApiError::from_error("PUBLISH_FAILED", "Failed to publish event", e)
+                    .with_details(json!({"correlation_id": correlation_id})),
+            )
         }
     }
 }
 
-/// POST /admin/tenants - Create a new tenant (admin only)
-pub async fn create_tenant(
+/// POST /events/batch - Publish several events, reporting success or failure per event
+#[utoipa::path(
+    post,
+    path = "/events/batch",
+    tag = "events",
+    request_body = PublishBatchRequest,
+    responses(
+        (status = 200, description = "Per-event publish results", body = PublishBatchResponse),
+        (status = 400, description = "Empty or oversized batch: INVALID_BATCH_SIZE", body = ErrorResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 500, description = "Publishing failed: PUBLISH_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn publish_events_batch(
     State(state): State<AppState>,
     Extension(auth): Extension<AuthContext>,
-    Json(request): Json<CreateTenantRequest>,
-) -> Result<Json<CreateTenantResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // Check admin permissions
-    if !auth.scopes.contains(&Scope::AdminWrite) {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(ErrorResponse::new(
-                "INSUFFICIENT_SCOPE",
-                "Admin write permission required",
-                None,
-            )),
-        ));
+    Json(request): Json<PublishBatchRequest>,
+) -> Result<Json<PublishBatchResponse>, ApiError> {
+    let start_time = std::time::Instant::now();
+
+    if let Err(e) = require_scope(&auth, Scope::EventsPublish) {
+        state.metrics.record_auth_operation("scope_check", false);
+        return Err(e);
     }
+    state.metrics.record_auth_operation("scope_check", true);
 
-    // Validate tenant name
-    if request.name.is_empty() || request.name.len() > 255 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse::new(
-                "INVALID_TENANT_NAME",
-                "Tenant name must be between 1 and 255 characters",
-                None,
-            )),
-        ));
+    if let Err(e) = state.event_service.check_batch_size(request.events.len()) {
+        return Err(
+            ApiError::validation("INVALID_BATCH_SIZE", e.to_string()).with_details(json!({
+                "size": e.size,
+                "max_batch_size": e.max_batch_size
+            })),
+        );
     }
 
-    // Parse billing plan
-    let plan = match request.plan.as_str() {
-        "free" => crate::models::BillingPlan::Free {
+    // Events that fail local checks keep their slot so results line up with the request
+    let prepared: Vec<Result<Event, ErrorResponse>> = request
+        .events
+        .into_iter()
+        .map(|item| build_batch_event(&state, &auth, item))
+        .collect();
+    let events: Vec<Event> = prepared
+        .iter()
+        .filter_map(|prepared| prepared.as_ref().ok().cloned())
+        .collect();
+
+    let publish_results = match state
+        .event_service
+        .publish_batch(&events, auth.api_key_id())
+        .await
+    {
+        Ok(results) => results,
+        Err(e) => {
+            error!("Failed to publish event batch: {}", e);
+            return Err(ApiError::from_error(
+                "PUBLISH_FAILED",
+                "Failed to publish event batch",
+                e,
+            ));
+        }
+    };
+    let mut published = events.into_iter().zip(publish_results);
+
+    let results = prepared
+        .into_iter()
+        .map(|prepared| {
+            if let Err(error) = prepared {
+                return BatchEventResult::Failed(error);
+            }
+            let (event, result) = published
+                .next()
+                .expect("one publish result per prepared event");
+            match result {
+                Ok(PublishResult::Success { sequence }) => {
+                    state.metrics.record_event_published(&auth.tenant_id, &event.topic);
+                    BatchEventResult::Published {
+                        event_id: event.id,
+                        sequence,
+                    }
+                }
+                Ok(PublishResult::Deduplicated {
+                    original_event_id,
+                    sequence,
+                }) => {
+                    state.metrics.record_event_deduplicated(&auth.tenant_id, &event.topic);
+                    BatchEventResult::Published {
+                        event_id: original_event_id,
+                        sequence,
+                    }
+                }
+                // Batches carry no idempotency keys
+                Ok(PublishResult::Replayed { event_id, sequence }) => {
+                    BatchEventResult::Published { event_id, sequence }
+                }
+                Ok(PublishResult::IdempotencyKeyInUse) => BatchEventResult::Failed(
+                    ErrorResponse::new("IDEMPOTENCY_KEY_IN_USE", "Idempotency key in use", None),
+                ),
+                Ok(PublishResult::RateLimited(status)) => {
+                    state
+                        .metrics
+                        .record_error("rate_limit_error", "project_rate_limit_exceeded");
+                    BatchEventResult::Failed(ErrorResponse::new(
+                        "PROJECT_RATE_LIMIT_EXCEEDED",
+                        "Project publish rate limit exceeded",
+                        Some(json!({
+                            "limit": status.limit,
+                            "retry_after_seconds": status.retry_after_secs,
+                        })),
+                    ))
+                }
+                Ok(PublishResult::PayloadTooLarge { size, limit }) => {
+                    state.metrics.record_error("validation_error", "payload_too_large");
+                    BatchEventResult::Failed(payload_too_large_error(size, limit))
+                }
+                Ok(PublishResult::ValidationFailed(msg)) => {
+                    state.metrics.record_error("validation_error", "event_validation_failed");
+                    BatchEventResult::Failed(ErrorResponse::new("VALIDATION_FAILED", &msg, None))
+                }
+                Err(e) => {
+                    state.metrics.record_error("publish_error", "event_publish_failed");
+                    error!("Failed to publish batched event {}: {}", event.id, e);
+                    BatchEventResult::Failed(ErrorResponse::new(
+                        "PUBLISH_FAILED",
+                        "Failed to publish event",
+                        Some(json!({"error": e.to_string()})),
+                    ))
+                }
+            }
+        })
+        .collect();
+
+    state.metrics.record_api_request(
+        "POST",
+        "/events/batch",
+        start_time.elapsed().as_secs_f64(),
+    );
+
+    Ok(Json(PublishBatchResponse { results }))
+}
+
+// Batch result for a payload over the project's or the platform's limit
+fn payload_too_large_error(size: usize, limit: usize) -> ErrorResponse {
+    ErrorResponse::new(
+        "PAYLOAD_TOO_LARGE",
+        &format!("Payload of {} bytes exceeds the {} byte limit", size, limit),
+        Some(json!({"size": size, "limit": limit})),
+    )
+}
+
+// Apply the single-event publish checks to one item of a batch
+fn build_batch_event(
+    state: &AppState,
+    auth: &AuthContext,
+    item: PublishEventRequest,
+) -> Result<Event, ErrorResponse> {
+    let item = BatchItem {
+        topic: item.topic,
+        payload: item.payload,
+        partition_key: item.partition_key,
+        headers: item.headers,
+        metadata: item.metadata,
+        attributes: item.attributes,
+    };
+
+    let error = match state.event_service.prepare_batch_event(auth, item) {
+        Ok(event) => return Ok(event),
+        Err(error) => error,
+    };
+    Err(match error {
+        BatchItemError::TopicNotAllowed(_) => {
+            ErrorResponse::new("TOPIC_NOT_ALLOWED", &error.to_string(), None)
+        }
+        BatchItemError::MetadataLimit(e) => {
+            state.metrics.record_error("validation_error", "metadata_limit_exceeded");
+            ErrorResponse::new("METADATA_LIMIT_EXCEEDED", &e.to_string(), None)
+        }
+        BatchItemError::InvalidEvent(EventBuildError::PayloadTooLarge { size, limit }) => {
+            state.metrics.record_error("validation_error", "payload_too_large");
+            payload_too_large_error(size, limit)
+        }
+        BatchItemError::InvalidEvent(EventBuildError::InvalidTopic(msg)) => {
+            state.metrics.record_error("validation_error", "invalid_topic");
+            ErrorResponse::new("INVALID_TOPIC", &msg, None)
+        }
+        BatchItemError::InvalidEvent(EventBuildError::ReservedHeader(header)) => {
+            state.metrics.record_error("validation_error", "reserved_header");
+            ErrorResponse::new(
+                "RESERVED_HEADER",
+                &format!("Header '{}' is set by the platform and can't be overridden", header),
+                Some(json!({"header": header})),
+            )
+        }
+        BatchItemError::InvalidEvent(EventBuildError::InvalidHeader(msg)) => {
+            state.metrics.record_error("validation_error", "invalid_header");
+            ErrorResponse::new("INVALID_HEADER", &msg, None)
+        }
+        BatchItemError::InvalidEvent(e) => {
+            state.metrics.record_error("validation_error", "invalid_event");
+            ErrorResponse::new("INVALID_EVENT", &e.to_string(), None)
+        }
+    })
+}
+
+/// Map a plan name to its default billing plan
+pub fn parse_billing_plan(plan: &str) -> Option<BillingPlan> {
+    match plan {
+        "free" => Some(BillingPlan::Free {
             monthly_events: 10000,
-        },
-        "pro" => crate::models::BillingPlan::Pro {
+        }),
+        "pro" => Some(BillingPlan::Pro {
             monthly_events: 100000,
             price_per_event: 0.001,
-        },
-        "enterprise" => crate::models::BillingPlan::Enterprise { unlimited: true },
-        _ => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse::new(
-                    "INVALID_PLAN",
-                    "Plan must be one of: free, pro, enterprise",
-                    None,
-                )),
-            ))
-        }
+        }),
+        "enterprise" => Some(BillingPlan::Enterprise { unlimited: true }),
+        _ => None,
+    }
+}
+
+/// Usage that already exceeds a tenant's new plan; reported rather than enforced
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct PlanLimitWarning {
+    pub code: String,
+    pub metric: String,
+    pub usage: i64,
+    pub limit: i64,
+}
+
+/// Change a tenant's plan, returning the updated tenant (or `None` if it doesn't
+/// exist) and a warning for each limit its usage this cycle already exceeds.
+/// Its projects' limits move to the new plan's. The tenant is never suspended
+/// here; enforcement stays with billing. The change is audited against `actor`.
+pub async fn change_tenant_plan(
+    database: &Database,
+    actor: &AuthContext,
+    tenant_id: &str,
+    plan: BillingPlan,
+) -> anyhow::Result<Option<(Tenant, Vec<PlanLimitWarning>)>> {
+    let Some(mut tenant) = database.get_tenant(tenant_id).await? else {
+        return Ok(None);
     };
+    if !database.update_tenant_plan(tenant_id, &plan).await? {
+        return Ok(None);
+    }
 
-    // Create the tenant
-    let tenant = Tenant::new(request.name, plan);
+    for project in database.get_projects_for_tenant(tenant_id).await? {
+        let limits = project.limits.for_plan_change(&tenant.plan, &plan);
+        database
+            .update_project_limits(tenant_id, &project.id, &limits)
+            .await?;
+    }
 
-    match state.database.create_tenant(&tenant).await {
-        Ok(_) => {
-            info!("Created tenant: {} ({})", tenant.id, tenant.name);
-            Ok(Json(CreateTenantResponse {
-                id: tenant.id,
-                name: tenant.name,
-                status: format!("{:?}", tenant.status).to_lowercase(),
-                created_at: tenant.created_at.to_rfc3339(),
-            }))
-        }
-        Err(e) => {
-            error!("Failed to create tenant: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse::new(
-                    "TENANT_CREATION_FAILED",
-                    "Failed to create tenant",
-                    Some(json!({"error": e.to_string()})),
-                )),
-            ))
+    let mut warnings = Vec::new();
+    if let Some(limit) = plan.monthly_event_limit() {
+        // Tenants the rollover job hasn't reached yet count the calendar month
+        let since = match database.get_usage_cycle_start(tenant_id).await? {
+            Some(cycle_start) => cycle_start,
+            None => usage_window_start(chrono::Utc::now()),
+        };
+        let usage = database
+            .get_usage_for_tenant_since(tenant_id, UsageMetric::EventsPublished, since)
+            .await?;
+        if usage > limit {
+            warn!(
+                "Tenant {} moved to a plan allowing {} events with {} published this cycle",
+                tenant_id, limit, usage
+            );
+            warnings.push(PlanLimitWarning {
+                code: "USAGE_EXCEEDS_PLAN".to_string(),
+                metric: "events_published".to_string(),
+                usage,
+                limit,
+            });
         }
     }
+
+    crate::audit::record(
+        database,
+        actor,
+        tenant_id,
+        crate::audit::TENANT_PLAN_CHANGED,
+        Some(tenant_id),
+        json!({"from": tenant.plan, "to": plan}),
+    )
+    .await;
+
+    tenant.plan = plan;
+    tenant.updated_at = chrono::Utc::now();
+    Ok(Some((tenant, warnings)))
+}
+
+/// Request body for `PATCH /admin/tenants/{tenant_id}/plan`
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateTenantPlanRequest {
+    /// One of free, pro, enterprise
+    pub plan: String,
+}
+
+/// Response for a plan change
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UpdateTenantPlanResponse {
+    pub tenant_id: String,
+    pub plan: BillingPlan,
+    pub warnings: Vec<PlanLimitWarning>,
+}
+
+/// PATCH /admin/tenants/{tenant_id}/plan - Upgrade or downgrade a tenant's plan
+#[utoipa::path(
+    patch,
+    path = "/admin/tenants/{tenant_id}/plan",
+    tag = "admin",
+    params(("tenant_id" = String, Path, description = "Tenant id")),
+    request_body = UpdateTenantPlanRequest,
+    responses(
+        (status = 200, description = "New plan, with a warning for each limit current usage exceeds", body = UpdateTenantPlanResponse),
+        (status = 400, description = "Unknown plan: INVALID_PLAN", body = ErrorResponse),
+        (status = 403, description = "Missing scope or not a platform admin: INSUFFICIENT_SCOPE, PLATFORM_ADMIN_REQUIRED", body = ErrorResponse),
+        (status = 404, description = "No such tenant: TENANT_NOT_FOUND", body = ErrorResponse),
+        (status = 500, description = "Tenant update failed: TENANT_PLAN_UPDATE_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn update_tenant_plan(
+    Extension(auth_context): Extension<AuthContext>,
+    Path(tenant_id): Path<String>,
+    State(state): State<AppState>,
+    Json(request): Json<UpdateTenantPlanRequest>,
+) -> Result<Json<UpdateTenantPlanResponse>, ApiError> {
+    // Tenants change plans through billing; only the platform operator may
+    // set one directly
+    require_scope(&auth_context, Scope::AdminWrite)?;
+    if !state.auth_service.is_platform_admin(&auth_context) {
+        return Err(ApiError::forbidden(
+            "PLATFORM_ADMIN_REQUIRED",
+            "Only platform admin keys may change a tenant's plan",
+        ));
+    }
+
+    let Some(plan) = parse_billing_plan(&request.plan) else {
+        return Err(ApiError::validation(
+            "INVALID_PLAN",
+            "Plan must be one of: free, pro, enterprise",
+        ));
+    };
+
+    match change_tenant_plan(&state.database, &auth_context, &tenant_id, plan).await {
+        Ok(Some((tenant, warnings))) => Ok(Json(UpdateTenantPlanResponse {
+            tenant_id: tenant.id,
+            plan: tenant.plan,
+            warnings,
+        })),
+        Ok(None) => Err(ApiError::not_found("TENANT_NOT_FOUND", "Tenant not found")
+            .with_details(json!({"tenant_id": tenant_id}))),
+        Err(e) => {
+            error!("Failed to change plan of tenant {}: {}", tenant_id, e);
+            Err(ApiError::from_error(
+                "TENANT_PLAN_UPDATE_FAILED",
+                "Failed to change tenant plan",
+                e,
+            ))
+        }
+    }
+}
+
+/// A tenant as seen by its own admins
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TenantResponse {
+    pub id: String,
+    pub name: String,
+    pub plan: BillingPlan,
+    pub status: TenantStatus,
+    pub stripe_customer_id: Option<String>,
+}
+
+/// GET /admin/tenants/{tenant_id} - Read the caller's own tenant, e.g. to poll
+/// whether it has been suspended. Unlike other endpoints, this one still
+/// accepts credentials of a suspended or past-due tenant.
+#[utoipa::path(
+    get,
+    path = "/admin/tenants/{tenant_id}",
+    tag = "admin",
+    params(("tenant_id" = String, Path, description = "Tenant id")),
+    responses(
+        (status = 200, description = "The tenant", body = TenantResponse),
+        (status = 403, description = "Missing scope or another tenant: INSUFFICIENT_SCOPE, TENANT_ACCESS_DENIED", body = ErrorResponse),
+        (status = 404, description = "No such tenant: TENANT_NOT_FOUND", body = ErrorResponse),
+        (status = 500, description = "Tenant lookup failed: TENANT_LOOKUP_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn get_tenant(
+    Extension(auth_context): Extension<AuthContext>,
+    Path(tenant_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<TenantResponse>, ApiError> {
+    require_scope(&auth_context, Scope::AdminRead)?;
+
+    if auth_context.tenant_id != tenant_id {
+        warn!("Cross-tenant access attempt: {} -> {}", auth_context.tenant_id, tenant_id);
+        return Err(ApiError::forbidden(
+            "TENANT_ACCESS_DENIED",
+            "API key may only read its own tenant",
+        )
+        .with_details(json!({"tenant_id": tenant_id})));
+    }
+
+    match state.database.get_tenant(&tenant_id).await {
+        Ok(Some(tenant)) => Ok(Json(TenantResponse {
+            id: tenant.id,
+            name: tenant.name,
+            plan: tenant.plan,
+            status: tenant.status,
+            stripe_customer_id: tenant.stripe_customer_id,
+        })),
+        Ok(None) => Err(ApiError::not_found("TENANT_NOT_FOUND", "Tenant not found")
+            .with_details(json!({"tenant_id": tenant_id}))),
+        Err(e) => Err(tenant_suspension_error(
+            "TENANT_LOOKUP_FAILED",
+            "load",
+            &tenant_id,
+            e,
+        )),
+    }
+}
+
+/// POST /admin/tenants - Create a new tenant (admin only)
+#[utoipa::path(
+    post,
+    path = "/admin/tenants",
+    tag = "admin",
+    request_body = CreateTenantRequest,
+    responses(
+        (status = 200, description = "Tenant created", body = CreateTenantResponse),
+        (status = 400, description = "Invalid tenant: INVALID_TENANT_NAME, INVALID_PLAN", body = ErrorResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 500, description = "Creation failed: TENANT_CREATION_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn create_tenant(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Json(request): Json<CreateTenantRequest>,
+) -> Result<Json<CreateTenantResponse>, ApiError> {
+    // Check admin permissions
+    require_scope(&auth, Scope::AdminWrite)?;
+
+    // Validate tenant name
+    if request.name.is_empty() || request.name.len() > 255 {
+        return Err(ApiError::validation(
+            "INVALID_TENANT_NAME",
+            "Tenant name must be between 1 and 255 characters",
+        ));
+    }
+
+    // Parse billing plan
+    let plan = match parse_billing_plan(&request.plan) {
+        Some(plan) => plan,
+        None => {
+            return Err(ApiError::validation(
+                "INVALID_PLAN",
+                "Plan must be one of: free, pro, enterprise",
+            ))
+        }
+    };
+
+    // Create the tenant
+    let tenant = Tenant::new(request.name, plan);
+
+    match state.database.create_tenant(&tenant).await {
+        Ok(_) => {
+            info!("Created tenant: {} ({})", tenant.id, tenant.name);
+            crate::audit::record(
+                &state.database,
+                &auth,
+                &tenant.id,
+                crate::audit::TENANT_CREATED,
+                Some(&tenant.id),
+                json!({"name": tenant.name, "plan": tenant.plan}),
+            )
+            .await;
+            Ok(Json(CreateTenantResponse {
+                id: tenant.id,
+                name: tenant.name,
+                status: format!("{:?}", tenant.status).to_lowercase(),
+                created_at: tenant.created_at.to_rfc3339(),
+            }))
+        }
+        Err(e) => {
+            error!("Failed to create tenant: {}", e);
+            Err(ApiError::from_error(
+                "TENANT_CREATION_FAILED",
+                "Failed to create tenant",
+                e,
+            ))
+        }
+    }
+}
+
+/// POST /admin/onboard - Create a tenant, default project and admin API key atomically
+#[utoipa::path(
+    post,
+    path = "/admin/onboard",
+    tag = "admin",
+    request_body = OnboardRequest,
+    responses(
+        (status = 200, description = "Tenant, default project and admin API key created", body = OnboardResponse),
+        (status = 400, description = "Invalid tenant: INVALID_TENANT_NAME, INVALID_PLAN", body = ErrorResponse),
+        (status = 403, description = "Missing scope or not a platform admin: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 500, description = "Onboarding failed: ONBOARDING_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn onboard_tenant(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Json(request): Json<OnboardRequest>,
+) -> Result<Json<OnboardResponse>, ApiError> {
+    // Onboarding hands out a full-scope key for a tenant on any plan, so it is
+    // reserved for the platform operator
+    require_scope(&auth, Scope::AdminWrite)?;
+    if !state.auth_service.is_platform_admin(&auth) {
+        return Err(ApiError::forbidden(
+            "INSUFFICIENT_SCOPE",
+            "Only platform admin keys may onboard tenants",
+        ));
+    }
+
+    if request.tenant_name.is_empty() || request.tenant_name.len() > 255 {
+        return Err(ApiError::validation(
+            "INVALID_TENANT_NAME",
+            "Tenant name must be between 1 and 255 characters",
+        ));
+    }
+
+    let plan = match parse_billing_plan(&request.plan) {
+        Some(plan) => plan,
+        None => {
+            return Err(ApiError::validation(
+                "INVALID_PLAN",
+                "Plan must be one of: free, pro, enterprise",
+            ))
+        }
+    };
+
+    let tenant = Tenant::new(request.tenant_name, plan);
+
+    let mut project = Project::new(
+        tenant.id.clone(),
+        request.project_name.unwrap_or_else(|| "default".to_string()),
+    );
+    project.limits = ProjectLimits::for_plan(&tenant.plan);
+
+    let scopes = vec![
+        Scope::EventsPublish,
+        Scope::EventsSubscribe,
+        Scope::AdminRead,
+        Scope::AdminWrite,
+        Scope::BillingRead,
+    ];
+    let (raw_key, api_key) =
+        match AuthService::new_api_key(tenant.id.clone(), project.id.clone(), scopes, 100, None) {
+            Ok((raw_key, api_key)) => {
+                (raw_key, state.auth_service.with_new_signing_secret(api_key))
+            }
+            Err(e) => {
+                error!("Failed to generate onboarding API key: {}", e);
+                return Err(ApiError::internal(
+                    "ONBOARDING_FAILED",
+                    "Failed to onboard tenant",
+                ));
+            }
+        };
+
+    match state
+        .database
+        .onboard_tenant(&tenant, &project, &api_key)
+        .await
+    {
+        Ok(_) => {
+            info!(
+                "Onboarded tenant: {} ({}) with project: {}",
+                tenant.id, tenant.name, project.id
+            );
+            crate::audit::record(
+                &state.database,
+                &auth,
+                &tenant.id,
+                crate::audit::TENANT_ONBOARDED,
+                Some(&tenant.id),
+                json!({
+                    "name": tenant.name,
+                    "plan": tenant.plan,
+                    "project_id": project.id,
+                    "api_key_id": api_key.id,
+                }),
+            )
+            .await;
+            let signing_secret = state.auth_service.signing_secret(&api_key);
+            Ok(Json(OnboardResponse {
+                tenant: CreateTenantResponse {
+                    id: tenant.id,
+                    name: tenant.name,
+                    status: format!("{:?}", tenant.status).to_lowercase(),
+                    created_at: tenant.created_at.to_rfc3339(),
+                },
+                project,
+                api_key: CreateApiKeyResponse {
+                    id: api_key.id,
+                    key: raw_key,
+                    scopes: vec![
+                        "events:publish".to_string(),
+                        "events:subscribe".to_string(),
+                        "admin:read".to_string(),
+                        "admin:write".to_string(),
+                        "billing:read".to_string(),
+                    ],
+                    rate_limit_per_sec: api_key.rate_limit_per_sec,
+                    allowed_topics: api_key.allowed_topics,
+                    expires_at: None,
+                    signing_secret,
+                },
+            }))
+        }
+        Err(e) => {
+            error!("Failed to onboard tenant: {}", e);
+            Err(ApiError::from_error(
+                "ONBOARDING_FAILED",
+                "Failed to onboard tenant",
+                e,
+            ))
+        }
+    }
+}
+
+/// POST /admin/api-keys - Create a new API key
+#[utoipa::path(
+    post,
+    path = "/admin/api-keys",
+    tag = "admin",
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 200, description = "API key created; the raw key is only returned here", body = CreateApiKeyResponse),
+        (status = 400, description = "Unknown scope: INVALID_SCOPE", body = ErrorResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 500, description = "Creation failed: API_KEY_CREATION_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Result<Json<CreateApiKeyResponse>, ApiError> {
+    // Check admin permissions
+    require_scope(&auth, Scope::AdminWrite)?;
+
+    // Parse scopes
+    let mut scopes = Vec::new();
+    for scope_str in &request.scopes {
+        let scope = match scope_str.as_str() {
+            "events:publish" => Scope::EventsPublish,
+            "events:subscribe" => Scope::EventsSubscribe,
+            "admin:read" => Scope::AdminRead,
+            "admin:write" => Scope::AdminWrite,
+            "billing:read" => Scope::BillingRead,
+            _ => {
+                return Err(ApiError::validation(
+                    "INVALID_SCOPE",
+                    format!("Invalid scope: {}", scope_str),
+                )
+                .with_details(json!({
+                    "valid_scopes": [
+                        "events:publish",
+                        "events:subscribe",
+                        "admin:read",
+                        "admin:write",
+                        "billing:read"
+                    ]
+                })))
+            }
+        };
+        scopes.push(scope);
+    }
+
+    let rate_limit = request.rate_limit_per_sec.unwrap_or(100);
+    let expires_at = request
+        .expires_in_days
+        .map(|days| chrono::Utc::now() + chrono::Duration::days(days));
+
+    // Create the API key
+    match state
+        .auth_service
+        .create_api_key(
+            auth.tenant_id.clone(),
+            auth.project_id.clone(),
+            scopes.clone(),
+            rate_limit,
+            request.allowed_topics.clone(),
+            expires_at,
+        )
+        .await
+    {
+        Ok((raw_key, api_key)) => {
+            info!(
+                "Created API key: {} for tenant: {}",
+                api_key.id, auth.tenant_id
+            );
+            crate::audit::record(
+                &state.database,
+                &auth,
+                &auth.tenant_id,
+                crate::audit::API_KEY_CREATED,
+                Some(&api_key.id),
+                json!({"project_id": api_key.project_id, "scopes": api_key.scopes}),
+            )
+            .await;
+            let signing_secret = state.auth_service.signing_secret(&api_key);
+            Ok(Json(CreateApiKeyResponse {
+                id: api_key.id,
+                key: raw_key,
+                scopes: request.scopes,
+                rate_limit_per_sec: rate_limit,
+                allowed_topics: api_key.allowed_topics,
+                expires_at: expires_at.map(|dt| dt.to_rfc3339()),
+                signing_secret,
+            }))
+        }
+        Err(e) => {
+            error!("Failed to create API key: {}", e);
+            Err(ApiError::from_error(
+                "API_KEY_CREATION_FAILED",
+                "Failed to create API key",
+                e,
+            ))
+        }
+    }
+}
+
+/// GET /admin/projects/{project_id}/api-keys - List a project's API keys
+#[utoipa::path(
+    get,
+    path = "/admin/projects/{project_id}/api-keys",
+    tag = "admin",
+    params(("project_id" = String, Path, description = "Project id")),
+    responses(
+        (status = 200, description = "The project's API keys, without secrets", body = Vec<ApiKeySummary>),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 404, description = "No such project for the tenant: PROJECT_NOT_FOUND", body = ErrorResponse),
+        (status = 500, description = "Listing failed: PROJECT_LOOKUP_FAILED, API_KEY_LIST_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn list_project_api_keys(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(project_id): Path<String>,
+) -> Result<Json<Vec<ApiKeySummary>>, ApiError> {
+    require_scope(&auth, Scope::AdminRead)?;
+
+    // Only projects belonging to the caller's tenant are visible
+    match state
+        .database
+        .get_project_with_tenant(&auth.tenant_id, &project_id)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Err(ApiError::not_found(
+                "PROJECT_NOT_FOUND",
+                "Project not found",
+            ));
+        }
+        Err(e) => {
+            error!("Failed to load project {}: {}", project_id, e);
+            return Err(ApiError::from_error(
+                "PROJECT_LOOKUP_FAILED",
+                "Failed to load project",
+                e,
+            ));
+        }
+    }
+
+    match state
+        .database
+        .get_api_keys_for_project(&project_id, None)
+        .await
+    {
+        Ok(api_keys) => Ok(Json(
+            api_keys
+                .into_iter()
+                .filter(|api_key| api_key.tenant_id == auth.tenant_id)
+                .map(|api_key| ApiKeySummary {
+                    stale: state.auth_service.is_stale_api_key(&api_key),
+                    ..ApiKeySummary::from(api_key)
+                })
+                .collect(),
+        )),
+        Err(e) => {
+            error!("Failed to list API keys for project {}: {}", project_id, e);
+            Err(ApiError::from_error(
+                "API_KEY_LIST_FAILED",
+                "Failed to list API keys",
+                e,
+            ))
+        }
+    }
+}
+
+/// Why a project's limits could not be updated
+#[derive(Debug, thiserror::Error)]
+pub enum ProjectLimitsError {
+    #[error("{0}")]
+    Invalid(String),
+    #[error("Project not found")]
+    NotFound,
+    #[error("Project is archived")]
+    Archived,
+    #[error(transparent)]
+    Database(#[from] anyhow::Error),
+}
+
+/// Apply a partial limits update to one of the tenant's projects and push the
+/// new connection limit to the WebSocket and SSE managers, draining any
+/// connections above it. Limits above the tenant's plan are lowered to the
+/// plan's. Archived projects can't be changed. Returns the updated project.
+pub async fn update_project_limits(
+    database: &Database,
+    tenant_id: &str,
+    project_id: &str,
+    update: &ProjectLimitsUpdate,
+) -> Result<Project, ProjectLimitsError> {
+    let mut project = database
+        .get_project_with_tenant(tenant_id, project_id)
+        .await?
+        .ok_or(ProjectLimitsError::NotFound)?;
+    if project.is_archived() {
+        return Err(ProjectLimitsError::Archived);
+    }
+    let tenant = database
+        .get_tenant(tenant_id)
+        .await?
+        .ok_or(ProjectLimitsError::NotFound)?;
+
+    let limits = update
+        .apply_to(&project.limits)
+        .capped_to_plan(&tenant.plan);
+    limits.validate().map_err(ProjectLimitsError::Invalid)?;
+    if !database
+        .update_project_limits(tenant_id, project_id, &limits)
+        .await?
+    {
+        return Err(ProjectLimitsError::NotFound);
+    }
+
+    crate::drain::apply_project_connection_limit(tenant_id, project_id, limits.max_connections);
+    project.limits = limits;
+    project.updated_at = chrono::Utc::now();
+    Ok(project)
+}
+
+/// Request body for `PATCH /admin/projects/{project_id}`
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct UpdateProjectRequest {
+    pub limits: Option<ProjectLimitsUpdate>,
+    /// Whether schema violations block publishing, are only recorded, or aren't checked
+    pub validation_mode: Option<ValidationMode>,
+}
+
+/// PATCH /admin/projects/{project_id} - Change a project's limits or schema validation mode.
+/// Limits above the tenant's plan are lowered to the plan's.
+#[utoipa::path(
+    patch,
+    path = "/admin/projects/{project_id}",
+    tag = "admin",
+    params(("project_id" = String, Path, description = "Project id")),
+    request_body = UpdateProjectRequest,
+    responses(
+        (status = 200, description = "The updated project", body = Project),
+        (status = 400, description = "Invalid limits: INVALID_LIMITS", body = ErrorResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 404, description = "No such project for the tenant: PROJECT_NOT_FOUND", body = ErrorResponse),
+        (status = 409, description = "The project is archived: PROJECT_ARCHIVED", body = ErrorResponse),
+        (status = 500, description = "Update failed: PROJECT_UPDATE_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn update_project(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(project_id): Path<String>,
+    Json(request): Json<UpdateProjectRequest>,
+) -> Result<Json<Project>, ApiError> {
+    require_scope(&auth, Scope::AdminWrite)?;
+
+    let update = request.limits.unwrap_or_default();
+    let updated = async {
+        let mut project =
+            update_project_limits(&state.database, &auth.tenant_id, &project_id, &update).await?;
+        if let Some(validation_mode) = request.validation_mode {
+            project.settings.validation_mode = validation_mode;
+            if !state
+                .database
+                .update_project_settings(&auth.tenant_id, &project_id, &project.settings)
+                .await?
+            {
+                return Err(ProjectLimitsError::NotFound);
+            }
+        }
+        Ok::<_, ProjectLimitsError>(project)
+    }
+    .await;
+
+    match updated {
+        Ok(project) => Ok(Json(project)),
+        Err(ProjectLimitsError::Invalid(message)) => {
+            Err(ApiError::validation("INVALID_LIMITS", message))
+        }
+        Err(ProjectLimitsError::NotFound) => Err(ApiError::not_found(
+            "PROJECT_NOT_FOUND",
+            "Project not found",
+        )),
+        Err(ProjectLimitsError::Archived) => Err(ApiError::conflict(
+            "PROJECT_ARCHIVED",
+            "Archived projects can't be changed",
+        )),
+        Err(ProjectLimitsError::Database(e)) => {
+            error!("Failed to update limits of project {}: {}", project_id, e);
+            Err(ApiError::from_error(
+                "PROJECT_UPDATE_FAILED",
+                "Failed to update project",
+                e,
+            ))
+        }
+    }
+}
+
+/// DELETE /admin/projects/{project_id} - Archive a project and close its connections
+#[utoipa::path(
+    delete,
+    path = "/admin/projects/{project_id}",
+    tag = "admin",
+    params(("project_id" = String, Path, description = "Project id")),
+    responses(
+        (status = 204, description = "Project archived and its connections closed"),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 404, description = "No such active project for the tenant: PROJECT_NOT_FOUND", body = ErrorResponse),
+        (status = 500, description = "Archiving failed: PROJECT_ARCHIVE_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn archive_project(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(project_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    require_scope(&auth, Scope::AdminWrite)?;
+
+    match crate::project_archive::archive_project(&state.database, &auth.tenant_id, &project_id)
+        .await
+    {
+        Ok(Some(terminated)) => {
+            crate::audit::record(
+                &state.database,
+                &auth,
+                &auth.tenant_id,
+                crate::audit::PROJECT_ARCHIVED,
+                Some(&project_id),
+                json!({ "terminated_connections": terminated }),
+            )
+            .await;
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Ok(None) => Err(ApiError::not_found(
+            "PROJECT_NOT_FOUND",
+            "Project not found",
+        )),
+        Err(e) => {
+            error!("Failed to archive project {}: {}", project_id, e);
+            Err(ApiError::from_error(
+                "PROJECT_ARCHIVE_FAILED",
+                "Failed to archive project",
+                e,
+            ))
+        }
+    }
+}
+
+/// DELETE /admin/api-keys/{key_id} - Revoke an API key
+#[utoipa::path(
+    delete,
+    path = "/admin/api-keys/{key_id}",
+    tag = "admin",
+    params(("key_id" = String, Path, description = "API key id")),
+    responses(
+        (status = 204, description = "API key revoked"),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 500, description = "Revocation failed: API_KEY_REVOCATION_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(key_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    // Check admin permissions
+    require_scope(&auth, Scope::AdminWrite)?;
+
+    match state
+        .auth_service
+        .revoke_api_key(&auth.tenant_id, &key_id)
+        .await
+    {
+        Ok(_) => {
+            info!("Revoked API key: {} for tenant: {}", key_id, auth.tenant_id);
+            crate::audit::record(
+                &state.database,
+                &auth,
+                &auth.tenant_id,
+                crate::audit::API_KEY_REVOKED,
+                Some(&key_id),
+                json!({}),
+            )
+            .await;
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Err(e) => {
+            error!("Failed to revoke API key: {}", e);
+            Err(ApiError::from_error(
+                "API_KEY_REVOCATION_FAILED",
+                "Failed to revoke API key",
+                e,
+            ))
+        }
+    }
+}
+
+/// POST /admin/api-keys/{key_id}/rotate - Give an API key a new secret
+#[utoipa::path(
+    post,
+    path = "/admin/api-keys/{key_id}/rotate",
+    tag = "admin",
+    params(("key_id" = String, Path, description = "API key id")),
+    responses(
+        (status = 200, description = "API key rotated; the previous key works until previous_key_expires_at. Rotating again before then cuts off the key it replaced immediately", body = RotateApiKeyResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 404, description = "No such active API key for the tenant: API_KEY_NOT_FOUND", body = ErrorResponse),
+        (status = 500, description = "Rotation failed: API_KEY_ROTATION_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn rotate_api_key(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(key_id): Path<String>,
+) -> Result<Json<RotateApiKeyResponse>, ApiError> {
+    require_scope(&auth, Scope::AdminWrite)?;
+
+    match state
+        .auth_service
+        .rotate_api_key(&auth.tenant_id, &key_id)
+        .await
+    {
+        Ok(Some((raw_key, api_key))) => {
+            let previous_key_expires_at = api_key.previous_expires_at.map(|dt| dt.to_rfc3339());
+            crate::audit::record(
+                &state.database,
+                &auth,
+                &auth.tenant_id,
+                crate::audit::API_KEY_ROTATED,
+                Some(&api_key.id),
+                json!({"previous_key_expires_at": previous_key_expires_at}),
+            )
+            .await;
+            let signing_secret = state.auth_service.signing_secret(&api_key);
+            Ok(Json(RotateApiKeyResponse {
+                id: api_key.id,
+                key: raw_key,
+                signing_secret,
+                previous_key_expires_at,
+            }))
+        }
+        Ok(None) => Err(ApiError::not_found(
+            "API_KEY_NOT_FOUND",
+            "API key not found",
+        )),
+        Err(e) => {
+            error!("Failed to rotate API key {}: {}", key_id, e);
+            Err(ApiError::from_error(
+                "API_KEY_ROTATION_FAILED",
+                "Failed to rotate API key",
+                e,
+            ))
+        }
+    }
+}
+
+/// GET /events/{id} - Fetch one of the authenticated tenant's events
+#[utoipa::path(
+    get,
+    path = "/events/{event_id}",
+    tag = "events",
+    params(("event_id" = String, Path, description = "Event id")),
+    responses(
+        (status = 200, description = "The event", body = Event),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 404, description = "No such event for the tenant: EVENT_NOT_FOUND", body = ErrorResponse),
+        (status = 500, description = "Lookup failed: EVENT_LOOKUP_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn get_event(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(event_id): Path<String>,
+) -> Result<Json<Event>, ApiError> {
+    require_scope(&auth, Scope::EventsSubscribe)?;
+
+    match state.database.get_event(&auth.tenant_id, &event_id).await {
+        // Events on topics the key can't read look the same as missing ones
+        Ok(Some(event)) if auth.allows_topic(&event.topic) => Ok(Json(event)),
+        Ok(_) => Err(ApiError::not_found("EVENT_NOT_FOUND", "Event not found")
+            .with_details(json!({"event_id": event_id}))),
+        Err(e) => {
+            error!("Failed to load event {}: {}", event_id, e);
+            Err(ApiError::from_error(
+                "EVENT_LOOKUP_FAILED",
+                "Failed to load event",
+                e,
+            ))
+        }
+    }
+}
+
+/// GET /events - List recent events for the authenticated tenant
+#[utoipa::path(
+    get,
+    path = "/events",
+    tag = "events",
+    params(ListQuery),
+    responses(
+        (status = 200, description = "A page of events, newest first", body = EventPageResponse),
+        (status = 400, description = "Invalid cursor: INVALID_CURSOR", body = ErrorResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 500, description = "Listing failed: EVENT_LIST_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn list_events(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Query(query): Query<ListQuery>,
+) -> Result<Json<PageResponse<Event>>, ApiError> {
+    require_any_scope(&auth, &[Scope::EventsSubscribe, Scope::AdminRead])?;
+
+    let limit = clamp_page_size(query.limit, state.max_page_size);
+    let before = match query.cursor.as_deref().map(EventPageCursor::decode) {
+        None => None,
+        Some(Some(cursor)) => Some(cursor.into_key()),
+        Some(None) => {
+            return Err(ApiError::validation(
+                "INVALID_CURSOR",
+                "Invalid page cursor",
+            ))
+        }
+    };
+
+    match state
+        .database
+        .get_events_for_tenant_paged(&auth.tenant_id, before, limit)
+        .await
+    {
+        Ok(page) => Ok(Json(PageResponse {
+            next_cursor: page.has_more.then(|| page.end_cursor()).flatten(),
+            items: page.events,
+            limit,
+            max_page_size: state.max_page_size,
+        })),
+        Err(e) => {
+            error!("Failed to list events: {}", e);
+            Err(ApiError::from_error(
+                "EVENT_LIST_FAILED",
+                "Failed to list events",
+                e,
+            ))
+        }
+    }
+}
+
+/// GET /events/replay - Replay persisted events from a sequence or timestamp
+#[utoipa::path(
+    get,
+    path = "/events/replay",
+    tag = "events",
+    params(ReplayQuery),
+    responses(
+        (status = 200, description = "A page of replayed events, oldest first", body = EventPageResponse),
+        (status = 400, description = "Invalid replay cursor: INVALID_REPLAY_CURSOR", body = ErrorResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 500, description = "Replay failed: EVENT_REPLAY_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn replay_events(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Query(query): Query<ReplayQuery>,
+) -> Result<Json<PageResponse<Event>>, ApiError> {
+    require_scope(&auth, Scope::EventsSubscribe)?;
+
+    if query.from_sequence.is_some() && query.from_timestamp.is_some() {
+        return Err(ApiError::validation(
+            "INVALID_REPLAY_CURSOR",
+            "Specify either from_sequence or from_timestamp, not both",
+        ));
+    }
+
+    let limit = clamp_page_size(query.limit, state.max_page_size);
+    let request = ReplayRequest {
+        tenant_id: auth.tenant_id.clone(),
+        project_id: auth.project_id.clone(),
+        topic: query.topic,
+        cursor: query.from_sequence.map(EventCursor::at_sequence),
+        from_timestamp: query.from_timestamp,
+        limit: Some(limit as usize),
+        transform: None,
+    };
+
+    match state.event_service.replay(request).await {
+        Ok(events) => Ok(Json(PageResponse {
+            items: events.collect().await,
+            limit,
+            max_page_size: state.max_page_size,
+            next_cursor: None,
+        })),
+        Err(e) => {
+            error!("Failed to replay events: {}", e);
+            Err(ApiError::from_error(
+                "EVENT_REPLAY_FAILED",
+                "Failed to replay events",
+                e,
+            ))
+        }
+    }
+}
+
+/// GET /admin/audit - List the tenant's audit log, newest first
+#[utoipa::path(
+    get,
+    path = "/admin/audit",
+    tag = "admin",
+    params(AuditLogQuery),
+    responses(
+        (status = 200, description = "A page of audit log entries, newest first", body = AuditLogPageResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 500, description = "Listing failed: AUDIT_LOG_LIST_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn list_audit_logs(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<PageResponse<AuditLog>>, ApiError> {
+    require_scope(&auth, Scope::AdminRead)?;
+
+    let limit = clamp_page_size(query.limit, state.max_page_size);
+    let filter = AuditQuery {
+        action: query.action,
+        from: query.from,
+        to: query.to,
+    };
+
+    match state
+        .database
+        .list_audit_logs(&auth.tenant_id, &filter, limit)
+        .await
+    {
+        Ok(entries) => Ok(Json(PageResponse {
+            items: entries,
+            limit,
+            max_page_size: state.max_page_size,
+            next_cursor: None,
+        })),
+        Err(e) => {
+            error!("Failed to list audit log: {}", e);
+            Err(ApiError::from_error(
+                "AUDIT_LOG_LIST_FAILED",
+                "Failed to list audit log",
+                e,
+            ))
+        }
+    }
+}
+
+/// GET /admin/dlq - List recently dead-lettered events for the tenant
+#[utoipa::path(
+    get,
+    path = "/admin/dlq",
+    tag = "admin",
+    params(DeadLetterQuery),
+    responses(
+        (status = 200, description = "A page of dead-lettered events", body = DeadLetterPageResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 500, description = "Listing failed: DEAD_LETTER_LIST_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn list_dead_letters(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Query(query): Query<DeadLetterQuery>,
+) -> Result<Json<PageResponse<DeadLetter>>, ApiError> {
+    require_scope(&auth, Scope::AdminRead)?;
+
+    let limit = clamp_page_size(query.limit, state.max_page_size);
+    let since = query
+        .since
+        .unwrap_or_else(|| chrono::Utc::now() - chrono::Duration::hours(24));
+
+    match state
+        .event_service
+        .list_dead_letters(&auth.tenant_id, query.project_id.as_deref(), since, limit as usize)
+        .await
+    {
+        Ok(dead_letters) => Ok(Json(PageResponse {
+            items: dead_letters,
+            limit,
+            max_page_size: state.max_page_size,
+            next_cursor: None,
+        })),
+        Err(e) => {
+            error!("Failed to list dead letters: {}", e);
+            Err(ApiError::from_error(
+                "DEAD_LETTER_LIST_FAILED",
+                "Failed to list dead-lettered events",
+                e,
+            ))
+        }
+    }
+}
+
+/// POST /admin/dlq/{id}/replay - Re-publish a dead-lettered event
+#[utoipa::path(
+    post,
+    path = "/admin/dlq/{id}/replay",
+    tag = "admin",
+    params(("id" = u64, Path, description = "Dead letter stream sequence")),
+    responses(
+        (status = 200, description = "Dead letter re-published, or redelivered to the webhook, Kafka sink or subscription it failed for", body = ReplayDeadLetterResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 404, description = "No such dead letter for the tenant: DEAD_LETTER_NOT_FOUND", body = ErrorResponse),
+        (status = 409, description = "Idempotency key reused with a different request: IDEMPOTENCY_KEY_IN_USE", body = ErrorResponse),
+        (status = 422, description = "Event no longer passes validation, or its webhook or Kafka sink still fails; the dead letter is kept. A VALIDATION_FAILED error's details name the event's new dead letter as dead_letter_id: VALIDATION_FAILED, DEAD_LETTER_REDELIVERY_FAILED", body = ErrorResponse),
+        (status = 500, description = "Replay failed: DEAD_LETTER_REPLAY_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn replay_dead_letter(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<u64>,
+) -> Result<Json<ReplayDeadLetterResponse>, ApiError> {
+    require_scope(&auth, Scope::AdminWrite)?;
+
+    let (dead_letter, result) = match state
+        .event_service
+        .replay_dead_letter(&auth.tenant_id, id)
+        .await
+    {
+        Ok(Some(replayed)) => replayed,
+        Ok(None) => {
+            return Err(ApiError::not_found(
+                "DEAD_LETTER_NOT_FOUND",
+                "Dead-lettered event not found",
+            )
+            .with_details(json!({"id": id})))
+        }
+        Err(e) => {
+            error!("Failed to replay dead letter {}: {}", id, e);
+            return Err(ApiError::from_error(
+                "DEAD_LETTER_REPLAY_FAILED",
+                "Failed to replay dead-lettered event",
+                e,
+            ));
+        }
+    };
+
+    let (result, dead_letter_id) = match result {
+        DeadLetterReplay::Published(result) => (result, None),
+        DeadLetterReplay::Rejected {
+            result,
+            dead_letter_id,
+        } => (result, dead_letter_id),
+        DeadLetterReplay::Redelivered { .. } => {
+            return Ok(Json(ReplayDeadLetterResponse {
+                id,
+                event_id: dead_letter.event.id,
+                sequence: None,
+            }))
+        }
+        DeadLetterReplay::Requeued { sequence } => {
+            return Ok(Json(ReplayDeadLetterResponse {
+                id,
+                event_id: dead_letter.event.id,
+                sequence: Some(sequence),
+            }))
+        }
+        DeadLetterReplay::Failed(reason) => {
+            return Err(ApiError::unprocessable("DEAD_LETTER_REDELIVERY_FAILED", reason)
+                .with_details(json!({"id": id, "event_id": dead_letter.event.id})))
+        }
+    };
+    let (event_id, sequence) = match result {
+        PublishResult::Success { sequence } => (dead_letter.event.id, Some(sequence)),
+        PublishResult::Replayed { event_id, sequence } => (event_id, Some(sequence)),
+        PublishResult::Deduplicated {
+            original_event_id,
+            sequence,
+        } => (original_event_id, Some(sequence)),
+        // The dead letter is kept; one failing schema validation again is
+        // also dead-lettered anew with the new reason
+        PublishResult::ValidationFailed(msg) => {
+            return Err(ApiError::unprocessable("VALIDATION_FAILED", msg).with_details(json!({
+                "id": id,
+                "event_id": dead_letter.event.id,
+                "dead_letter_id": dead_letter_id,
+            })))
+        }
+        PublishResult::IdempotencyKeyInUse => {
+            return Err(ApiError::conflict(
+                "IDEMPOTENCY_KEY_IN_USE",
+                "A request with this idempotency key is still being processed",
+            )
+            .with_details(json!({"id": id})))
+        }
+        PublishResult::RateLimited(status) => {
+            return Err(ApiError::project_rate_limited(status).with_details(json!({"id": id})))
+        }
+        PublishResult::PayloadTooLarge { size, limit } => {
+            return Err(ApiError::payload_too_large(size, limit)
+                .with_details(json!({"id": id, "event_id": dead_letter.event.id})))
+        }
+    };
+
+    Ok(Json(ReplayDeadLetterResponse {
+        id,
+        event_id,
+        sequence,
+    }))
+}
+
+/// PUT /admin/topics/{topic}/schema - Register a new schema version for a topic
+#[utoipa::path(
+    put,
+    path = "/admin/topics/{topic}/schema",
+    tag = "schemas",
+    params(("topic" = String, Path, description = "Topic"), RegisterSchemaQuery),
+    request_body = Value,
+    responses(
+        (status = 200, description = "Schema version registered", body = RegisterSchemaResponse),
+        (status = 400, description = "Invalid topic or schema: INVALID_TOPIC, INVALID_SCHEMA", body = ErrorResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 409, description = "Schema breaks the requested compatibility: INCOMPATIBLE_SCHEMA", body = ErrorResponse),
+        (status = 500, description = "Registration failed: SCHEMA_REGISTRATION_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn register_topic_schema(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(topic): Path<String>,
+    Query(query): Query<RegisterSchemaQuery>,
+    Json(schema): Json<Value>,
+) -> Result<Json<RegisterSchemaResponse>, ApiError> {
+    require_scope(&auth, Scope::AdminWrite)?;
+
+    if let Err(e) = crate::schema_validator::validate_topic_format(&topic) {
+        return Err(ApiError::validation("INVALID_TOPIC", e));
+    }
+
+    if let Err(e) = SchemaValidator::check_schema_document(&schema) {
+        return Err(
+            ApiError::validation("INVALID_SCHEMA", "Document is not a valid JSON Schema")
+                .with_details(json!({"error": e})),
+        );
+    }
+
+    let schema_validator = state.event_service.schema_validator();
+
+    if query.compat != CompatibilityMode::None {
+        if let Err(e) = schema_validator.sync_topic(&auth.project_id, &topic).await {
+            warn!("Failed to load schema for topic {}: {}", topic, e);
+        }
+
+        if let Some(active) = schema_validator.get_schema(&auth.project_id, &topic) {
+            let result = query.compat.check(&active.schema, &schema);
+            if !result.is_compatible() {
+                return Err(ApiError::conflict(
+                    "INCOMPATIBLE_SCHEMA",
+                    "Schema change breaks compatibility with the active version",
+                )
+                .with_details(json!({
+                    "compat": query.compat,
+                    "active_version": active.version,
+                    "breaking_changes": result.breaking_changes,
+                })));
+            }
+        }
+    }
+
+    // Registering bumps the version and invalidates cached compilations
+    let version = match schema_validator
+        .store_schema(
+            &auth.tenant_id,
+            &auth.project_id,
+            &topic,
+            schema,
+            SchemaMode::Enforce,
+        )
+        .await
+    {
+        Ok(version) => version,
+        Err(e) => {
+            error!("Failed to register schema for topic {}: {}", topic, e);
+            return Err(ApiError::from_error(
+                "SCHEMA_REGISTRATION_FAILED",
+                "Failed to register schema",
+                e,
+            ));
+        }
+    };
+
+    info!(
+        "Registered schema v{} for topic {} in project {}",
+        version, topic, auth.project_id
+    );
+
+    Ok(Json(RegisterSchemaResponse {
+        project_id: auth.project_id,
+        topic,
+        version,
+    }))
+}
+
+/// GET /projects/{project_id}/topics/{topic}/schema - Download the effective schema for a topic
+#[utoipa::path(
+    get,
+    path = "/projects/{project_id}/topics/{topic}/schema",
+    tag = "schemas",
+    params(("project_id" = String, Path, description = "Project id"), ("topic" = String, Path, description = "Topic")),
+    responses(
+        (status = 200, description = "The effective schema for the topic", body = TopicSchemaResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 404, description = "No such project or schema: PROJECT_NOT_FOUND, SCHEMA_NOT_FOUND", body = ErrorResponse),
+        (status = 500, description = "Lookup failed: PROJECT_LOOKUP_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn get_topic_schema(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path((project_id, topic)): Path<(String, String)>,
+) -> Result<Json<TopicSchemaResponse>, ApiError> {
+    require_scope(&auth, Scope::EventsSubscribe)?;
+
+    // Only projects belonging to the caller's tenant are visible
+    match state
+        .database
+        .get_project_with_tenant(&auth.tenant_id, &project_id)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return Err(ApiError::not_found(
+                "PROJECT_NOT_FOUND",
+                "Project not found",
+            ));
+        }
+        Err(e) => {
+            error!("Failed to load project {}: {}", project_id, e);
+            return Err(ApiError::from_error(
+                "PROJECT_LOOKUP_FAILED",
+                "Failed to load project",
+                e,
+            ));
+        }
+    }
+
+    let schema_validator = state.event_service.schema_validator();
+    if let Err(e) = schema_validator.sync_topic(&project_id, &topic).await {
+        warn!("Failed to load schema for topic {}: {}", topic, e);
+    }
+
+    match schema_validator.get_schema(&project_id, &topic) {
+        Some(active) => Ok(Json(TopicSchemaResponse {
+            project_id,
+            topic,
+            version: active.version,
+            mode: active.mode,
+            schema: active.schema,
+        })),
+        None => Err(
+            ApiError::not_found("SCHEMA_NOT_FOUND", "No schema registered for topic")
+                .with_details(json!({"topic": topic})),
+        ),
+    }
+}
+
+/// Request body for registering a webhook
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    /// Topics delivered, with `*` and `>` wildcards; defaults to every topic
+    pub topic_filter: Option<String>,
+    /// Signing secret; generated when omitted
+    pub secret: Option<String>,
+    /// `native` (default) or `cloudevents`
+    #[serde(default)]
+    pub format: DeliveryFormat,
+}
+
+/// A newly registered webhook, including the secret that signs its deliveries
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreateWebhookResponse {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+/// Webhooks registered for the caller's project
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookListResponse {
+    pub webhooks: Vec<Webhook>,
+}
+
+fn webhook_not_found(webhook_id: &str) -> ApiError {
+    ApiError::not_found("WEBHOOK_NOT_FOUND", "Webhook not found")
+        .with_details(json!({"webhook_id": webhook_id}))
+}
+
+fn webhook_storage_error(e: anyhow::Error) -> ApiError {
+    error!("Webhook storage failed: {}", e);
+    ApiError::from_error("WEBHOOK_STORAGE_FAILED", "Failed to access webhooks", e)
+}
+
+fn validate_webhook_fields(
+    state: &AppState,
+    url: Option<&str>,
+    topic_filter: Option<&str>,
+) -> Result<(), ApiError> {
+    let allow_private = state.event_service.allows_private_webhook_addresses();
+    if let Some(Err(e)) = url.map(|u| crate::webhooks::validate_webhook_url(u, allow_private)) {
+        return Err(ApiError::validation("INVALID_WEBHOOK_URL", e));
+    }
+    if let Some(Err(e)) = topic_filter.map(crate::webhooks::validate_topic_filter) {
+        return Err(ApiError::validation("INVALID_TOPIC_FILTER", e));
+    }
+    Ok(())
+}
+
+/// POST /admin/webhooks - Register a webhook for the caller's project
+#[utoipa::path(
+    post,
+    path = "/admin/webhooks",
+    tag = "webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook registered; the signing secret is only returned here", body = CreateWebhookResponse),
+        (status = 400, description = "Invalid webhook: INVALID_WEBHOOK_URL, INVALID_TOPIC_FILTER, INVALID_WEBHOOK_SECRET", body = ErrorResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 500, description = "Storage failed: WEBHOOK_STORAGE_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn create_webhook(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Json(request): Json<CreateWebhookRequest>,
+) -> Result<Json<CreateWebhookResponse>, ApiError> {
+    require_scope(&auth, Scope::AdminWrite)?;
+
+    let topic_filter = request.topic_filter.unwrap_or_else(|| ">".to_string());
+    validate_webhook_fields(&state, Some(&request.url), Some(&topic_filter))?;
+
+    let secret = match request.secret {
+        Some(secret) if secret.trim().is_empty() => {
+            return Err(ApiError::validation(
+                "INVALID_WEBHOOK_SECRET",
+                "Webhook secret cannot be empty",
+            ));
+        }
+        Some(secret) => secret,
+        None => crate::webhooks::generate_secret(),
+    };
+
+    let id = Uuid::new_v4().to_string();
+    let now = chrono::Utc::now();
+    let webhook = Webhook {
+        secret: state.auth_service.signing_cipher().seal(&id, &secret),
+        id,
+        tenant_id: auth.tenant_id.clone(),
+        project_id: auth.project_id.clone(),
+        url: request.url,
+        topic_filter,
+        format: request.format,
+        active: true,
+        created_at: now,
+        updated_at: now,
+    };
+    state
+        .database
+        .create_webhook(&webhook)
+        .await
+        .map_err(webhook_storage_error)?;
+
+    info!(
+        "Registered webhook {} for project {}",
+        webhook.id, webhook.project_id
+    );
+    Ok(Json(CreateWebhookResponse { webhook, secret }))
 }
 
-/// POST /admin/api-keys - Create a new API key
-pub async fn create_api_key(
+/// GET /admin/webhooks - List the caller's project's webhooks
+#[utoipa::path(
+    get,
+    path = "/admin/webhooks",
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "The project's webhooks", body = WebhookListResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 500, description = "Storage failed: WEBHOOK_STORAGE_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn list_webhooks(
     State(state): State<AppState>,
     Extension(auth): Extension<AuthContext>,
-    Json(request): Json<CreateApiKeyRequest>,
-) -> Result<Json<CreateApiKeyResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // Check admin permissions
-    if !auth.scopes.contains(&Scope::AdminWrite) {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(ErrorResponse::new(
-                "INSUFFICIENT_SCOPE",
-                "Admin write permission required",
-                None,
-            )),
-        ));
-    }
+) -> Result<Json<WebhookListResponse>, ApiError> {
+    require_any_scope(&auth, &[Scope::AdminRead, Scope::AdminWrite])?;
 
-    // Parse scopes
-    let mut scopes = Vec::new();
-    for scope_str in &request.scopes {
-        let scope = match scope_str.as_str() {
-            "events:publish" => Scope::EventsPublish,
-            "events:subscribe" => Scope::EventsSubscribe,
-            "admin:read" => Scope::AdminRead,
-            "admin:write" => Scope::AdminWrite,
-            "billing:read" => Scope::BillingRead,
-            _ => {
-                return Err((
-                    StatusCode::BAD_REQUEST,
-                    Json(ErrorResponse::new(
-                        "INVALID_SCOPE",
-                        &format!("Invalid scope: {}", scope_str),
-                        Some(json!({
-                            "valid_scopes": [
-                                "events:publish",
-                                "events:subscribe",
-                                "admin:read",
-                                "admin:write",
-                                "billing:read"
-                            ]
-                        })),
-                    )),
-                ))
-            }
-        };
-        scopes.push(scope);
-    }
+    let webhooks = state
+        .database
+        .list_webhooks(&auth.tenant_id, &auth.project_id, false)
+        .await
+        .map_err(webhook_storage_error)?;
+    Ok(Json(WebhookListResponse { webhooks }))
+}
 
-    let rate_limit = request.rate_limit_per_sec.unwrap_or(100);
-    let expires_at = request
-        .expires_in_days
-        .map(|days| chrono::Utc::now() + chrono::Duration::days(days));
+/// GET /admin/webhooks/{webhook_id} - Fetch one of the tenant's webhooks
+#[utoipa::path(
+    get,
+    path = "/admin/webhooks/{webhook_id}",
+    tag = "webhooks",
+    params(("webhook_id" = String, Path, description = "Webhook id")),
+    responses(
+        (status = 200, description = "The webhook", body = Webhook),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 404, description = "No such webhook for the tenant: WEBHOOK_NOT_FOUND", body = ErrorResponse),
+        (status = 500, description = "Storage failed: WEBHOOK_STORAGE_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn get_webhook(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(webhook_id): Path<String>,
+) -> Result<Json<Webhook>, ApiError> {
+    require_any_scope(&auth, &[Scope::AdminRead, Scope::AdminWrite])?;
 
-    // Create the API key
     match state
-        .auth_service
-        .create_api_key(
-            auth.tenant_id.clone(),
-            auth.project_id.clone(),
-            scopes.clone(),
-            rate_limit,
-            expires_at,
-        )
+        .database
+        .get_webhook(&auth.tenant_id, &webhook_id)
         .await
+        .map_err(webhook_storage_error)?
     {
-        Ok((raw_key, api_key)) => {
-            info!(
-                "Created API key: {} for tenant: {}",
-                api_key.id, auth.tenant_id
-            );
-            Ok(Json(CreateApiKeyResponse {
-                id: api_key.id,
-                key: raw_key,
-                scopes: request.scopes,
-                rate_limit_per_sec: rate_limit,
-                expires_at: expires_at.map(|dt| dt.to_rfc3339()),
-            }))
-        }
-        Err(e) => {
-            error!("Failed to create API key: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse::new(
-                    "API_KEY_CREATION_FAILED",
-                    "Failed to create API key",
-                    Some(json!({"error": e.to_string()})),
-                )),
-            ))
-        }
+        Some(webhook) => Ok(Json(webhook)),
+        None => Err(webhook_not_found(&webhook_id)),
     }
 }
 
-/// DELETE /admin/api-keys/{key_id} - Revoke an API key
-pub async fn revoke_api_key(
+/// PATCH /admin/webhooks/{webhook_id} - Change a webhook's URL, topic filter or active flag
+#[utoipa::path(
+    patch,
+    path = "/admin/webhooks/{webhook_id}",
+    tag = "webhooks",
+    params(("webhook_id" = String, Path, description = "Webhook id")),
+    request_body = WebhookUpdate,
+    responses(
+        (status = 200, description = "The updated webhook", body = Webhook),
+        (status = 400, description = "Invalid webhook: INVALID_WEBHOOK_URL, INVALID_TOPIC_FILTER", body = ErrorResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 404, description = "No such webhook for the tenant: WEBHOOK_NOT_FOUND", body = ErrorResponse),
+        (status = 500, description = "Storage failed: WEBHOOK_STORAGE_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn update_webhook(
     State(state): State<AppState>,
     Extension(auth): Extension<AuthContext>,
-    Path(key_id): Path<String>,
-) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
-    // Check admin permissions
-    if !auth.scopes.contains(&Scope::AdminWrite) {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(ErrorResponse::new(
-                "INSUFFICIENT_SCOPE",
-                "Admin write permission required",
-                None,
-            )),
-        ));
-    }
+    Path(webhook_id): Path<String>,
+    Json(update): Json<WebhookUpdate>,
+) -> Result<Json<Webhook>, ApiError> {
+    require_scope(&auth, Scope::AdminWrite)?;
+
+    validate_webhook_fields(&state, update.url.as_deref(), update.topic_filter.as_deref())?;
 
     match state
-        .auth_service
-        .revoke_api_key(&auth.tenant_id, &key_id)
+        .database
+        .update_webhook(&auth.tenant_id, &webhook_id, &update)
         .await
+        .map_err(webhook_storage_error)?
     {
-        Ok(_) => {
-            info!("Revoked API key: {} for tenant: {}", key_id, auth.tenant_id);
-            Ok(StatusCode::NO_CONTENT)
-        }
-        Err(e) => {
-            error!("Failed to revoke API key: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse::new(
-                    "API_KEY_REVOCATION_FAILED",
-                    "Failed to revoke API key",
-                    Some(json!({"error": e.to_string()})),
-                )),
-            ))
+        Some(webhook) => {
+            info!("Updated webhook {} for tenant {}", webhook_id, auth.tenant_id);
+            Ok(Json(webhook))
         }
+        None => Err(webhook_not_found(&webhook_id)),
+    }
+}
+
+/// DELETE /admin/webhooks/{webhook_id} - Remove a webhook
+#[utoipa::path(
+    delete,
+    path = "/admin/webhooks/{webhook_id}",
+    tag = "webhooks",
+    params(("webhook_id" = String, Path, description = "Webhook id")),
+    responses(
+        (status = 204, description = "Webhook removed"),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 404, description = "No such webhook for the tenant: WEBHOOK_NOT_FOUND", body = ErrorResponse),
+        (status = 500, description = "Storage failed: WEBHOOK_STORAGE_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn delete_webhook(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(webhook_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    require_scope(&auth, Scope::AdminWrite)?;
+
+    if state
+        .database
+        .delete_webhook(&auth.tenant_id, &webhook_id)
+        .await
+        .map_err(webhook_storage_error)?
+    {
+        info!("Deleted webhook {} for tenant {}", webhook_id, auth.tenant_id);
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(webhook_not_found(&webhook_id))
     }
 }
 
 /// GET /billing/usage - Get usage report for tenant
+#[utoipa::path(
+    get,
+    path = "/billing/usage",
+    tag = "billing",
+    params(UsageQuery),
+    responses(
+        (status = 200, description = "Usage report for the tenant", body = UsageReportResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+    )
+)]
 pub async fn get_usage_report(
     State(state): State<AppState>,
     Extension(auth): Extension<AuthContext>,
     Query(_query): Query<UsageQuery>,
-) -> Result<Json<UsageReportResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<UsageReportResponse>, ApiError> {
     // Check billing read permissions
-    if !auth.scopes.contains(&Scope::BillingRead) && !auth.scopes.contains(&Scope::AdminRead) {
-        return Err((
-            StatusCode::FORBIDDEN,
-            Json(ErrorResponse::new(
-                "INSUFFICIENT_SCOPE",
-                "Billing read or admin read permission required",
-                None,
-            )),
-        ));
-    }
+    require_any_scope(&auth, &[Scope::BillingRead, Scope::AdminRead])?;
 
     let mut metrics = HashMap::new();
 
@@ -558,55 +2660,329 @@ pub async fn get_usage_report(
     }))
 }
 
-/// POST /billing/stripe-webhook - Handle Stripe webhooks
-pub async fn handle_stripe_webhook(
-    State(_state): State<AppState>,
-    body: String,
-) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
-    // TODO: Implement Stripe webhook signature verification
-    // TODO: Handle different webhook event types (payment_succeeded, payment_failed, etc.)
-
-    info!("Received Stripe webhook: {}", body.len());
-
-    // For now, just acknowledge receipt
-    // In a real implementation, this would:
-    // 1. Verify the webhook signature
-    // 2. Parse the webhook payload
-    // 3. Handle different event types (payment success/failure, subscription changes)
-    // 4. Update tenant status accordingly
-    // 5. Trigger kill switch if payment fails
-
-    Ok(StatusCode::OK)
-}
-
-/// Health check endpoint
-pub async fn health_check(
-    State(state): State<AppState>,
-) -> Result<Json<Value>, (StatusCode, Json<ErrorResponse>)> {
-    let is_healthy = state.event_service.is_healthy();
-
-    if is_healthy {
-        Ok(Json(json!({
-            "status": "healthy",
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-            "services": {
-                "database": "healthy",
-                "nats": "healthy",
-                "event_service": "healthy"
+/// GET /admin/usage/export - Export the tenant's raw usage records as CSV or JSON.
+///
+/// Records are streamed from a database cursor so large exports aren't held
+/// in memory.
+#[utoipa::path(
+    get,
+    path = "/admin/usage/export",
+    tag = "billing",
+    params(UsageExportQuery),
+    responses(
+        (status = 200, description = "Raw usage records", content(
+            ("text/csv" = String),
+            ("application/json" = Vec<Value>),
+        )),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+    )
+)]
+pub async fn export_usage(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Query(query): Query<UsageExportQuery>,
+) -> Result<Response, ApiError> {
+    require_scope(&auth, Scope::BillingRead)?;
+
+    let format = query.format;
+    let mut records = Box::pin(state.database.stream_usage_records(
+        auth.tenant_id.clone(),
+        query.from,
+        query.to,
+    ));
+    let body = async_stream::stream! {
+        let mut first = true;
+        yield Ok(match format {
+            UsageExportFormat::Csv => format!("{}\n", USAGE_EXPORT_CSV_HEADER),
+            UsageExportFormat::Json => "[".to_string(),
+        });
+
+        while let Some(record) = records.next().await {
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    // Headers are already sent, so the only signal left is an aborted body
+                    error!("Failed to read usage records for tenant {}: {}", auth.tenant_id, e);
+                    yield Err(std::io::Error::other(e.to_string()));
+                    return;
+                }
+            };
+
+            yield Ok(match format {
+                UsageExportFormat::Csv => format!(
+                    "{},{},{},{},{}\n",
+                    record.tenant_id,
+                    record.project_id,
+                    record.metric.as_str(),
+                    record.quantity,
+                    record.window_start.to_rfc3339(),
+                ),
+                UsageExportFormat::Json => {
+                    let row = serde_json::to_string(&UsageExportRow::from(&record))
+                        .unwrap_or_default();
+                    if first {
+                        first = false;
+                        row
+                    } else {
+                        format!(",{}", row)
+                    }
+                }
+            });
+        }
+
+        if format == UsageExportFormat::Json {
+            yield Ok("]".to_string());
+        }
+    };
+
+    let content_type = match format {
+        UsageExportFormat::Csv => "text/csv; charset=utf-8",
+        UsageExportFormat::Json => "application/json",
+    };
+    Ok((
+        [(header::CONTENT_TYPE, content_type)],
+        axum::body::Body::from_stream(body),
+    )
+        .into_response())
+}
+
+/// GET /admin/api-keys/{key_id}/usage - Get usage attributed to one API key
+#[utoipa::path(
+    get,
+    path = "/admin/api-keys/{key_id}/usage",
+    tag = "admin",
+    params(("key_id" = String, Path, description = "API key id")),
+    responses(
+        (status = 200, description = "Usage attributed to the key", body = KeyUsageResponse),
+        (status = 403, description = "Missing scope: INSUFFICIENT_SCOPE", body = ErrorResponse),
+        (status = 500, description = "Usage query failed: USAGE_QUERY_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn get_api_key_usage(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthContext>,
+    Path(key_id): Path<String>,
+) -> Result<Json<KeyUsageResponse>, ApiError> {
+    require_scope(&auth, Scope::AdminRead)?;
+
+    let mut metrics = HashMap::new();
+
+    let usage_metrics = vec![
+        UsageMetric::EventsPublished,
+        UsageMetric::EventsDelivered,
+        UsageMetric::WebSocketMinutes,
+        UsageMetric::ApiRequests,
+    ];
+
+    // Scoped to the caller's tenant, so other tenants' keys always report zero
+    for metric in usage_metrics {
+        match state
+            .database
+            .get_usage_for_key(&auth.tenant_id, &key_id, metric.clone())
+            .await
+        {
+            Ok(usage) => {
+                let metric_name = match metric {
+                    UsageMetric::EventsPublished => "events_published",
+                    UsageMetric::EventsDelivered => "events_delivered",
+                    UsageMetric::WebSocketMinutes => "websocket_minutes",
+                    UsageMetric::ApiRequests => "api_requests",
+                };
+                metrics.insert(metric_name.to_string(), usage);
             }
-        })))
-    } else {
-        Err((
-            StatusCode::SERVICE_UNAVAILABLE,
-            Json(ErrorResponse::new(
-                "SERVICE_UNHEALTHY",
-                "One or more services are unhealthy",
-                None,
-            )),
-        ))
+            Err(e) => {
+                error!("Failed to get key usage for metric {:?}: {}", metric, e);
+                return Err(ApiError::from_error(
+                    "USAGE_QUERY_FAILED",
+                    "Failed to get API key usage",
+                    e,
+                ));
+            }
+        }
+    }
+
+    Ok(Json(KeyUsageResponse { key_id, metrics }))
+}
+
+/// POST /webhooks/stripe - Apply Stripe payment lifecycle events to tenants
+///
+/// `invoice.payment_failed` moves the tenant to past due, or suspends it once the
+/// invoice has failed `suspend_after_failed_payments` times; `invoice.paid`
+/// reactivates it and `customer.subscription.deleted` suspends it. A tenant
+/// suspended for any reason but failed payments stays suspended.
+#[utoipa::path(
+    post,
+    path = "/webhooks/stripe",
+    tag = "billing",
+    params(("Stripe-Signature" = String, Header, description = "Stripe webhook signature")),
+    request_body = String,
+    responses(
+        (status = 200, description = "Event applied"),
+        (status = 400, description = "Bad signature or payload: INVALID_SIGNATURE, INVALID_PAYLOAD", body = ErrorResponse),
+        (status = 500, description = "Applying the event failed: WEBHOOK_FAILED", body = ErrorResponse),
+        (status = 503, description = "No webhook secret configured: WEBHOOK_NOT_CONFIGURED", body = ErrorResponse),
+    )
+)]
+pub async fn handle_stripe_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+) -> Result<StatusCode, ApiError> {
+    let secret = match &state.billing.stripe_webhook_secret {
+        Some(secret) => secret,
+        None => {
+            warn!("Rejecting Stripe webhook: no webhook secret configured");
+            return Err(ApiError::unavailable(
+                "WEBHOOK_NOT_CONFIGURED",
+                "Stripe webhooks are not configured",
+            ));
+        }
+    };
+
+    let signature = headers
+        .get(STRIPE_SIGNATURE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if let Err(e) = crate::stripe_webhook::verify_signature(
+        &body,
+        signature,
+        secret,
+        chrono::Utc::now().timestamp(),
+    ) {
+        warn!("Rejecting Stripe webhook: {}", e);
+        return Err(ApiError::validation("INVALID_SIGNATURE", e.to_string()));
+    }
+
+    let (event_id, event) = match StripeWebhookEvent::parse(&body) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            return Err(ApiError::validation("INVALID_PAYLOAD", e.to_string()));
+        }
+    };
+    info!("Received Stripe webhook {}: {:?}", event_id, event);
+
+    match crate::stripe_webhook::apply_event(
+        &state.database,
+        &event,
+        state.billing.suspend_after_failed_payments,
+    )
+    .await
+    {
+        Ok(_) => Ok(StatusCode::OK),
+        Err(e) => {
+            // A non-2xx response makes Stripe retry the delivery
+            error!("Failed to apply Stripe webhook {}: {}", event_id, e);
+            Err(ApiError::from_error(
+                "WEBHOOK_FAILED",
+                "Failed to process webhook",
+                e,
+            ))
+        }
+    }
+}
+
+/// How long a dependency may take to answer a health check
+pub const HEALTH_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Reachability of each dependency, `"ok"` or `"unavailable"`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub struct DependencyHealth {
+    pub database: String,
+    pub nats: String,
+}
+
+impl DependencyHealth {
+    /// Whether every dependency is reachable
+    pub fn is_ok(&self) -> bool {
+        self.database == "ok" && self.nats == "ok"
+    }
+}
+
+fn health_status(healthy: bool) -> String {
+    if healthy { "ok" } else { "unavailable" }.to_string()
+}
+
+/// Check the database with `SELECT 1` and NATS with its connection state
+pub async fn check_dependencies(state: &AppState) -> DependencyHealth {
+    let database = match tokio::time::timeout(HEALTH_CHECK_TIMEOUT, state.database.ping()).await {
+        Ok(Ok(())) => true,
+        Ok(Err(e)) => {
+            warn!("Health check: database unavailable: {}", e);
+            false
+        }
+        Err(_) => {
+            warn!("Health check: database did not answer within {:?}", HEALTH_CHECK_TIMEOUT);
+            false
+        }
+    };
+    let nats = state.event_service.is_healthy();
+    if !nats {
+        warn!("Health check: NATS disconnected");
+    }
+
+    DependencyHealth {
+        database: health_status(database),
+        nats: health_status(nats),
     }
 }
+
+/// Health check endpoint: `200` when the database and NATS are reachable,
+/// `503` naming the failing component otherwise
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses(
+        (status = 200, description = "Database and NATS reachable", body = DependencyHealth),
+        (status = 503, description = "A dependency is unavailable", body = DependencyHealth),
+    )
+)]
+pub async fn health_check(State(state): State<AppState>) -> (StatusCode, Json<DependencyHealth>) {
+    let health = check_dependencies(&state).await;
+    let status = if health.is_ok() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(health))
+}
+
+/// Liveness probe: the process is up and serving requests
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "health",
+    responses(
+        (status = 200, description = "The process is serving requests", body = Value),
+    )
+)]
+pub async fn liveness_check() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+/// Readiness probe: dependencies are reachable, so traffic can be routed here
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "Ready for traffic", body = DependencyHealth),
+        (status = 503, description = "A dependency is unavailable", body = DependencyHealth),
+    )
+)]
+pub async fn readiness_check(state: State<AppState>) -> (StatusCode, Json<DependencyHealth>) {
+    health_check(state).await
+}
+
 /// Get usage limits for the authenticated tenant
+#[utoipa::path(
+    get,
+    path = "/billing/limits",
+    tag = "billing",
+    responses(
+        (status = 200, description = "The tenant's usage limits", body = Value),
+    )
+)]
 pub async fn get_usage_limits(
     Extension(auth_context): Extension<AuthContext>,
     State(state): State<AppState>,
@@ -628,61 +3004,154 @@ pub async fn get_usage_limits(
     Ok(Json(limits))
 }
 
-/// Suspend a tenant (admin only)
+/// Request body for suspending or reactivating a tenant
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct TenantSuspensionRequest {
+    pub reason: Option<String>,
+}
+
+/// POST /admin/tenants/{tenant_id}/suspend - Suspend a tenant and terminate its
+/// WebSocket and SSE connections
+#[utoipa::path(
+    post,
+    path = "/admin/tenants/{tenant_id}/suspend",
+    tag = "admin",
+    params(("tenant_id" = String, Path, description = "Tenant id")),
+    request_body = Option<TenantSuspensionRequest>,
+    responses(
+        (status = 200, description = "Tenant suspended and its connections terminated", body = Value),
+        (status = 403, description = "Missing scope or another tenant: INSUFFICIENT_SCOPE, TENANT_ACCESS_DENIED", body = ErrorResponse),
+        (status = 404, description = "No such tenant: TENANT_NOT_FOUND", body = ErrorResponse),
+        (status = 500, description = "Tenant update failed: TENANT_LOOKUP_FAILED, TENANT_SUSPENSION_FAILED", body = ErrorResponse),
+    )
+)]
 pub async fn suspend_tenant(
     Extension(auth_context): Extension<AuthContext>,
     Path(tenant_id): Path<String>,
     State(state): State<AppState>,
-) -> Result<Json<Value>, StatusCode> {
-    // Check if user has admin permissions
-    if state
-        .auth_service
-        .check_scope(&auth_context, &Scope::AdminWrite)
-        .is_err()
-    {
-        return Err(StatusCode::FORBIDDEN);
-    }
+    request: Option<Json<TenantSuspensionRequest>>,
+) -> Result<Json<Value>, ApiError> {
+    check_tenant_admin(&auth_context, &state, &tenant_id).await?;
 
-    // This would integrate with the billing service to suspend the tenant
-    info!(tenant_id = %tenant_id, "Suspending tenant");
+    let reason = request
+        .and_then(|Json(request)| request.reason)
+        .unwrap_or_else(|| "Suspended by administrator".to_string());
 
-    let response = json!({
+    let terminated = state
+        .suspend_tenant(&tenant_id, &reason, auth_context.api_key_id())
+        .await
+        .map_err(|e| {
+            tenant_suspension_error("TENANT_SUSPENSION_FAILED", "suspend", &tenant_id, e)
+        })?;
+
+    Ok(Json(json!({
         "tenant_id": tenant_id,
         "status": "suspended",
+        "reason": reason,
+        "terminated_connections": terminated,
         "suspended_at": chrono::Utc::now().to_rfc3339()
-    });
-
-    Ok(Json(response))
+    })))
 }
 
-/// Unsuspend a tenant (admin only)
+/// POST /admin/tenants/{tenant_id}/unsuspend - Return a suspended tenant to active
+#[utoipa::path(
+    post,
+    path = "/admin/tenants/{tenant_id}/unsuspend",
+    tag = "admin",
+    params(("tenant_id" = String, Path, description = "Tenant id")),
+    request_body = Option<TenantSuspensionRequest>,
+    responses(
+        (status = 200, description = "Tenant reactivated", body = Value),
+        (status = 403, description = "Missing scope or another tenant: INSUFFICIENT_SCOPE, TENANT_ACCESS_DENIED", body = ErrorResponse),
+        (status = 404, description = "No such tenant: TENANT_NOT_FOUND", body = ErrorResponse),
+        (status = 500, description = "Tenant update failed: TENANT_LOOKUP_FAILED, TENANT_SUSPENSION_FAILED", body = ErrorResponse),
+    )
+)]
 pub async fn unsuspend_tenant(
     Extension(auth_context): Extension<AuthContext>,
     Path(tenant_id): Path<String>,
     State(state): State<AppState>,
-) -> Result<Json<Value>, StatusCode> {
-    // Check if user has admin permissions
-    if state
-        .auth_service
-        .check_scope(&auth_context, &Scope::AdminWrite)
-        .is_err()
-    {
-        return Err(StatusCode::FORBIDDEN);
-    }
+    request: Option<Json<TenantSuspensionRequest>>,
+) -> Result<Json<Value>, ApiError> {
+    check_tenant_admin(&auth_context, &state, &tenant_id).await?;
 
-    // This would integrate with the billing service to unsuspend the tenant
-    info!(tenant_id = %tenant_id, "Unsuspending tenant");
+    let reason = request
+        .and_then(|Json(request)| request.reason)
+        .unwrap_or_else(|| "Unsuspended by administrator".to_string());
+
+    state
+        .unsuspend_tenant(&tenant_id, &reason, auth_context.api_key_id())
+        .await
+        .map_err(|e| {
+            tenant_suspension_error("TENANT_SUSPENSION_FAILED", "unsuspend", &tenant_id, e)
+        })?;
 
-    let response = json!({
+    Ok(Json(json!({
         "tenant_id": tenant_id,
         "status": "active",
+        "reason": reason,
         "unsuspended_at": chrono::Utc::now().to_rfc3339()
-    });
+    })))
+}
+
+// Require AdminWrite on the tenant itself, or platform admin credentials,
+// and an existing tenant
+async fn check_tenant_admin(
+    auth_context: &AuthContext,
+    state: &AppState,
+    tenant_id: &str,
+) -> Result<(), ApiError> {
+    require_scope(auth_context, Scope::AdminWrite)?;
+    if auth_context.tenant_id != tenant_id && !state.auth_service.is_platform_admin(auth_context)
+    {
+        return Err(ApiError::forbidden(
+            "TENANT_ACCESS_DENIED",
+            "API key may only manage its own tenant",
+        )
+        .with_details(json!({"tenant_id": tenant_id})));
+    }
+
+    require_tenant(state, tenant_id).await
+}
 
-    Ok(Json(response))
+// Require the tenant to exist
+async fn require_tenant(state: &AppState, tenant_id: &str) -> Result<(), ApiError> {
+    match state.database.get_tenant(tenant_id).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(ApiError::not_found("TENANT_NOT_FOUND", "Tenant not found")
+            .with_details(json!({"tenant_id": tenant_id}))),
+        Err(e) => Err(tenant_suspension_error(
+            "TENANT_LOOKUP_FAILED",
+            "load",
+            tenant_id,
+            e,
+        )),
+    }
+}
+
+fn tenant_suspension_error(
+    code: &'static str,
+    action: &str,
+    tenant_id: &str,
+    e: anyhow::Error,
+) -> ApiError {
+    error!("Failed to {} tenant {}: {}", action, tenant_id, e);
+    ApiError::from_error(code, format!("Failed to {} tenant", action), e)
 }
 
 /// Admin endpoint to manage user roles (requires ManageUsers permission)
+#[utoipa::path(
+    put,
+    path = "/admin/tenants/{tenant_id}/users/{user_id}/role",
+    tag = "admin",
+    params(("tenant_id" = String, Path, description = "Tenant id"), ("user_id" = String, Path, description = "User id")),
+    request_body = UpdateUserRoleRequest,
+    responses(
+        (status = 200, description = "Role changed", body = Value),
+        (status = 403, description = "Cross-tenant request or missing ManageUsers permission"),
+        (status = 404, description = "No such user in the tenant"),
+    )
+)]
 pub async fn update_user_role(
     State(state): State<AppState>,
     Extension(auth_context): Extension<AuthContext>,
@@ -749,6 +3218,16 @@ pub async fn update_user_role(
 }
 
 /// Admin endpoint to list users in a tenant (requires ViewAuditLogs permission)
+#[utoipa::path(
+    get,
+    path = "/admin/tenants/{tenant_id}/users",
+    tag = "admin",
+    params(("tenant_id" = String, Path, description = "Tenant id")),
+    responses(
+        (status = 200, description = "The tenant's users", body = Value),
+        (status = 403, description = "Cross-tenant request or missing ViewAuditLogs permission"),
+    )
+)]
 pub async fn list_tenant_users(
     State(state): State<AppState>,
     Extension(auth_context): Extension<AuthContext>,
@@ -787,6 +3266,18 @@ pub async fn list_tenant_users(
 }
 
 /// Admin endpoint to deactivate a user (requires ManageUsers permission)
+#[utoipa::path(
+    post,
+    path = "/admin/tenants/{tenant_id}/users/{user_id}/deactivate",
+    tag = "admin",
+    params(("tenant_id" = String, Path, description = "Tenant id"), ("user_id" = String, Path, description = "User id")),
+    responses(
+        (status = 200, description = "User deactivated", body = Value),
+        (status = 400, description = "Users cannot deactivate themselves"),
+        (status = 403, description = "Cross-tenant request or missing ManageUsers permission"),
+        (status = 404, description = "No such user in the tenant"),
+    )
+)]
 pub async fn deactivate_user(
     State(state): State<AppState>,
     Extension(auth_context): Extension<AuthContext>,
@@ -831,33 +3322,179 @@ pub async fn deactivate_user(
     })))
 }
 
-/// GET /metrics - Prometheus metrics endpoint
-pub async fn metrics_handler(
+/// Query parameters for the SLA summary
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SlaQuery {
+    /// Seconds the summary covers, up to an hour (default: 300)
+    pub window_secs: Option<u64>,
+}
+
+/// GET /admin/sla - SLA and error-budget summary computed from the metrics registry
+#[utoipa::path(
+    get,
+    path = "/admin/sla",
+    tag = "admin",
+    params(SlaQuery),
+    responses(
+        (status = 200, description = "SLA and error-budget summary over the window", body = SlaSummary),
+        (status = 400, description = "Window is zero or longer than an hour: INVALID_SLA_WINDOW", body = ErrorResponse),
+        (status = 403, description = "Missing scope or not a platform admin: INSUFFICIENT_SCOPE, PLATFORM_ADMIN_REQUIRED", body = ErrorResponse),
+    )
+)]
+pub async fn get_sla_summary(
+    Extension(auth_context): Extension<AuthContext>,
+    State(state): State<AppState>,
+    Query(query): Query<SlaQuery>,
+) -> Result<Json<SlaSummary>, ApiError> {
+    // The summary covers every tenant's traffic
+    require_scope(&auth_context, Scope::AdminRead)?;
+    if !state.auth_service.is_platform_admin(&auth_context) {
+        return Err(ApiError::forbidden(
+            "PLATFORM_ADMIN_REQUIRED",
+            "Only platform admin keys may read the SLA summary",
+        ));
+    }
+
+    let window = query
+        .window_secs
+        .map_or(DEFAULT_SLA_WINDOW, std::time::Duration::from_secs);
+    if window.is_zero() || window > MAX_SLA_WINDOW {
+        return Err(ApiError::validation(
+            "INVALID_SLA_WINDOW",
+            format!(
+                "window_secs must be between 1 and {}",
+                MAX_SLA_WINDOW.as_secs()
+            ),
+        ));
+    }
+
+    Ok(Json(state.metrics.sla_summary(window)))
+}
+
+/// PUT /admin/tenants/{tenant_id}/log-level - Override log verbosity for one tenant
+#[utoipa::path(
+    put,
+    path = "/admin/tenants/{tenant_id}/log-level",
+    tag = "admin",
+    params(("tenant_id" = String, Path, description = "Tenant id")),
+    request_body = TenantLogLevelRequest,
+    responses(
+        (status = 200, description = "Log level override set", body = Value),
+        (status = 400, description = "Unknown log level: INVALID_LOG_LEVEL", body = ErrorResponse),
+        (status = 403, description = "Missing scope or not a platform admin: INSUFFICIENT_SCOPE, PLATFORM_ADMIN_REQUIRED", body = ErrorResponse),
+        (status = 404, description = "No such tenant: TENANT_NOT_FOUND", body = ErrorResponse),
+    )
+)]
+pub async fn set_tenant_log_level(
+    Extension(auth_context): Extension<AuthContext>,
+    State(state): State<AppState>,
+    Path(tenant_id): Path<String>,
+    Json(request): Json<TenantLogLevelRequest>,
+) -> Result<Json<Value>, ApiError> {
+    check_log_level_admin(&auth_context, &state, &tenant_id).await?;
+
+    let level = request
+        .level
+        .parse::<tracing::level_filters::LevelFilter>()
+        .map_err(|_| {
+            ApiError::validation(
+                "INVALID_LOG_LEVEL",
+                "Log level must be one of: off, error, warn, info, debug, trace",
+            )
+            .with_details(json!({"level": request.level}))
+        })?;
+
+    tenant_log_levels().set(&tenant_id, level);
+    info!(tenant_id = %tenant_id, level = %level, "Set tenant log level override");
+
+    Ok(Json(json!({
+        "tenant_id": tenant_id,
+        "level": level.to_string()
+    })))
+}
+
+/// DELETE /admin/tenants/{tenant_id}/log-level - Remove a tenant's log level override
+#[utoipa::path(
+    delete,
+    path = "/admin/tenants/{tenant_id}/log-level",
+    tag = "admin",
+    params(("tenant_id" = String, Path, description = "Tenant id")),
+    responses(
+        (status = 204, description = "Log level override removed"),
+        (status = 403, description = "Missing scope or not a platform admin: INSUFFICIENT_SCOPE, PLATFORM_ADMIN_REQUIRED", body = ErrorResponse),
+        (status = 404, description = "No such tenant or no override set for it: TENANT_NOT_FOUND, LOG_LEVEL_OVERRIDE_NOT_FOUND", body = ErrorResponse),
+    )
+)]
+pub async fn clear_tenant_log_level(
+    Extension(auth_context): Extension<AuthContext>,
     State(state): State<AppState>,
-) -> Result<String, (StatusCode, Json<ErrorResponse>)> {
+    Path(tenant_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    check_log_level_admin(&auth_context, &state, &tenant_id).await?;
+
+    if tenant_log_levels().clear(&tenant_id) {
+        info!(tenant_id = %tenant_id, "Cleared tenant log level override");
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ApiError::not_found(
+            "LOG_LEVEL_OVERRIDE_NOT_FOUND",
+            "No log level override set for tenant",
+        ))
+    }
+}
+
+// Log level overrides can flood the logs and put a tenant's payloads in
+// them, so only the platform operator sets or clears them, for existing tenants
+async fn check_log_level_admin(
+    auth_context: &AuthContext,
+    state: &AppState,
+    tenant_id: &str,
+) -> Result<(), ApiError> {
+    require_scope(auth_context, Scope::AdminWrite)?;
+    if !state.auth_service.is_platform_admin(auth_context) {
+        return Err(ApiError::forbidden(
+            "PLATFORM_ADMIN_REQUIRED",
+            "Only platform admin keys may change a tenant's log level",
+        ));
+    }
+    require_tenant(state, tenant_id).await
+}
+
+/// GET /metrics - Prometheus metrics endpoint
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "health",
+    responses(
+        (status = 200, description = "Prometheus text exposition", body = String, content_type = "text/plain"),
+        (status = 500, description = "Encoding failed: METRICS_ENCODING_FAILED", body = ErrorResponse),
+    )
+)]
+pub async fn metrics_handler(State(state): State<AppState>) -> Result<Response, ApiError> {
     use prometheus::Encoder;
     
     let encoder = prometheus::TextEncoder::new();
     let metric_families = state.metrics.registry.gather();
     
     match encoder.encode_to_string(&metric_families) {
-        Ok(metrics_text) => Ok(metrics_text),
+        Ok(metrics_text) => Ok((
+            [(header::CONTENT_TYPE, encoder.format_type().to_string())],
+            metrics_text,
+        )
+            .into_response()),
         Err(e) => {
             error!("Failed to encode metrics: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse::new(
-                    "METRICS_ENCODING_FAILED",
-                    "Failed to encode metrics",
-                    Some(json!({"error": e.to_string()})),
-                )),
-            ))
+            Err(
+                ApiError::internal("METRICS_ENCODING_FAILED", "Failed to encode metrics")
+                    .with_details(json!({"error": e.to_string()})),
+            )
         }
     }
 }
 
 /// Request payload for updating user roles
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateUserRoleRequest {
     pub role: UserRole,
 }
\ No newline at end of file
@@ -0,0 +1,388 @@
+use axum::{
+    http::{header::RETRY_AFTER, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Value};
+
+use crate::api::{scope_name, ErrorResponse};
+use crate::auth::AuthContext;
+use crate::circuit_breaker::CircuitOpen;
+use crate::db_retry::is_pool_exhausted;
+use crate::models::Scope;
+use crate::rate_limit::{RateLimitStatus, OVERLOADED_RETRY_AFTER_SECS};
+
+/// An error returned by a REST handler. Each kind maps to one status code and
+/// renders as an `ErrorResponse`, which always carries a `request_id`.
+#[derive(Debug)]
+pub enum ApiError {
+    /// 403: the API key holds none of the scopes the endpoint accepts
+    InsufficientScope {
+        required: Vec<Scope>,
+        available: Vec<Scope>,
+        details: Option<Value>,
+    },
+    /// 400: the request is malformed or breaks a rule
+    Validation {
+        code: &'static str,
+        message: String,
+        details: Option<Value>,
+    },
+    /// 403: the caller may use the endpoint but not for this request
+    Forbidden {
+        code: &'static str,
+        message: String,
+        details: Option<Value>,
+    },
+    /// 404
+    NotFound {
+        code: &'static str,
+        message: String,
+        details: Option<Value>,
+    },
+    /// 409: the request clashes with the current state
+    Conflict {
+        code: &'static str,
+        message: String,
+        details: Option<Value>,
+    },
+    /// 413: the payload is over the size limit
+    PayloadTooLarge {
+        size: usize,
+        limit: usize,
+        details: Option<Value>,
+    },
+    /// 422: well-formed but rejected by a schema
+    Unprocessable {
+        code: &'static str,
+        message: String,
+        details: Option<Value>,
+    },
+    /// 429: the caller is over a rate limit
+    RateLimited {
+        code: &'static str,
+        message: String,
+        status: RateLimitStatus,
+        details: Option<Value>,
+    },
+    /// 500: something failed on our side
+    Internal {
+        code: &'static str,
+        message: String,
+        details: Option<Value>,
+    },
+    /// 503: a feature or dependency isn't available
+    Unavailable {
+        code: &'static str,
+        message: String,
+        /// Sent as `Retry-After` when the dependency is expected back
+        retry_after_secs: Option<u64>,
+        details: Option<Value>,
+    },
+}
+
+impl ApiError {
+    pub fn validation(code: &'static str, message: impl Into<String>) -> Self {
+        Self::Validation {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn forbidden(code: &'static str, message: impl Into<String>) -> Self {
+        Self::Forbidden {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn not_found(code: &'static str, message: impl Into<String>) -> Self {
+        Self::NotFound {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn conflict(code: &'static str, message: impl Into<String>) -> Self {
+        Self::Conflict {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn payload_too_large(size: usize, limit: usize) -> Self {
+        Self::PayloadTooLarge {
+            size,
+            limit,
+            details: None,
+        }
+    }
+
+    pub fn unprocessable(code: &'static str, message: impl Into<String>) -> Self {
+        Self::Unprocessable {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn internal(code: &'static str, message: impl Into<String>) -> Self {
+        Self::Internal {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn unavailable(code: &'static str, message: impl Into<String>) -> Self {
+        Self::Unavailable {
+            code,
+            message: message.into(),
+            retry_after_secs: None,
+            details: None,
+        }
+    }
+
+    /// 500 for an unexpected failure, with the error in the details. A timeout
+    /// waiting for a pooled database connection is an overload rather than a
+    /// failure, so it becomes a 503 asking the client to retry instead.
+    pub fn from_error(
+        code: &'static str,
+        message: impl Into<String>,
+        error: impl Into<anyhow::Error>,
+    ) -> Self {
+        let error = error.into();
+        if is_pool_exhausted(&error) {
+            return Self::overloaded();
+        }
+        Self::internal(code, message).with_details(json!({ "error": error.to_string() }))
+    }
+
+    /// 503 for a request the service can't take on right now, such as when no
+    /// database connection could be acquired
+    pub fn overloaded() -> Self {
+        Self::Unavailable {
+            code: "SERVICE_OVERLOADED",
+            message: "Service temporarily overloaded".to_string(),
+            retry_after_secs: Some(OVERLOADED_RETRY_AFTER_SECS),
+            details: None,
+        }
+    }
+
+    /// 503 for a dependency whose circuit breaker is open, telling the client
+    /// when it will next be tried
+    pub fn circuit_open(open: &CircuitOpen) -> Self {
+        Self::Unavailable {
+            code: "DEPENDENCY_UNAVAILABLE",
+            message: format!("{} is temporarily unavailable", open.dependency),
+            retry_after_secs: Some(open.retry_after_secs()),
+            details: Some(json!({ "dependency": open.dependency })),
+        }
+    }
+
+    /// 429 for a project publishing faster than its `max_events_per_sec`,
+    /// whichever of its keys the events come from
+    pub fn project_rate_limited(status: RateLimitStatus) -> Self {
+        Self::RateLimited {
+            code: "PROJECT_RATE_LIMIT_EXCEEDED",
+            message: "Project publish rate limit exceeded".to_string(),
+            status,
+            details: None,
+        }
+    }
+
+    /// Attach details to the error body. Object details are merged into any
+    /// already attached, so context such as a correlation id can be added on
+    /// top of an error's own details.
+    pub fn with_details(mut self, extra: Value) -> Self {
+        let details = match &mut self {
+            Self::InsufficientScope { details, .. }
+            | Self::Validation { details, .. }
+            | Self::Forbidden { details, .. }
+            | Self::NotFound { details, .. }
+            | Self::Conflict { details, .. }
+            | Self::PayloadTooLarge { details, .. }
+            | Self::Unprocessable { details, .. }
+            | Self::RateLimited { details, .. }
+            | Self::Internal { details, .. }
+            | Self::Unavailable { details, .. } => details,
+        };
+        match (details, extra) {
+            (Some(Value::Object(existing)), Value::Object(extra)) => existing.extend(extra),
+            (details, extra) => *details = Some(extra),
+        }
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::InsufficientScope { .. } | Self::Forbidden { .. } => StatusCode::FORBIDDEN,
+            Self::Validation { .. } => StatusCode::BAD_REQUEST,
+            Self::NotFound { .. } => StatusCode::NOT_FOUND,
+            Self::Conflict { .. } => StatusCode::CONFLICT,
+            Self::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Unprocessable { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            Self::Internal { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Unavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// The machine-readable code in the error body
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InsufficientScope { .. } => "INSUFFICIENT_SCOPE",
+            Self::PayloadTooLarge { .. } => "PAYLOAD_TOO_LARGE",
+            Self::Validation { code, .. }
+            | Self::Forbidden { code, .. }
+            | Self::NotFound { code, .. }
+            | Self::Conflict { code, .. }
+            | Self::Unprocessable { code, .. }
+            | Self::RateLimited { code, .. }
+            | Self::Internal { code, .. }
+            | Self::Unavailable { code, .. } => code,
+        }
+    }
+
+    /// The status and body this error renders as
+    pub fn into_parts(self) -> (StatusCode, Json<ErrorResponse>) {
+        let status = self.status();
+        let code = self.code();
+        let body = match self {
+            Self::InsufficientScope {
+                required,
+                available,
+                details,
+            } => {
+                let required: Vec<&str> = required.iter().map(scope_name).collect();
+                let available: Vec<&str> = available.iter().map(scope_name).collect();
+                ErrorResponse::new(
+                    code,
+                    &format!("API key lacks {} permission", required.join(" or ")),
+                    Some(merge_details(
+                        json!({
+                            "required_scopes": required,
+                            "available_scopes": available,
+                        }),
+                        details,
+                    )),
+                )
+            }
+            Self::PayloadTooLarge {
+                size,
+                limit,
+                details,
+            } => ErrorResponse::new(
+                code,
+                &format!("Payload of {} bytes exceeds the {} byte limit", size, limit),
+                Some(merge_details(
+                    json!({ "size": size, "limit": limit }),
+                    details,
+                )),
+            ),
+            Self::Validation {
+                message, details, ..
+            }
+            | Self::Forbidden {
+                message, details, ..
+            }
+            | Self::NotFound {
+                message, details, ..
+            }
+            | Self::Conflict {
+                message, details, ..
+            }
+            | Self::Unprocessable {
+                message, details, ..
+            }
+            | Self::Internal {
+                message, details, ..
+            } => ErrorResponse::new(code, &message, details),
+            Self::RateLimited {
+                message,
+                status,
+                details,
+                ..
+            } => ErrorResponse::new(
+                code,
+                &message,
+                Some(merge_details(
+                    json!({
+                        "limit": status.limit,
+                        "retry_after_seconds": status.retry_after_secs,
+                    }),
+                    details,
+                )),
+            ),
+            Self::Unavailable {
+                message,
+                retry_after_secs: Some(secs),
+                details,
+                ..
+            } => ErrorResponse::new(
+                code,
+                &message,
+                Some(merge_details(json!({ "retry_after_seconds": secs }), details)),
+            ),
+            Self::Unavailable {
+                message, details, ..
+            } => ErrorResponse::new(code, &message, details),
+        };
+        (status, Json(body))
+    }
+}
+
+/// `base` with any object `details` added on top
+fn merge_details(mut base: Value, details: Option<Value>) -> Value {
+    if let (Some(base), Some(Value::Object(details))) = (base.as_object_mut(), details) {
+        base.extend(details);
+    }
+    base
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let rate_limit = match &self {
+            Self::RateLimited { status, .. } => Some(*status),
+            _ => None,
+        };
+        let retry_after = match &self {
+            Self::Unavailable {
+                retry_after_secs, ..
+            } => *retry_after_secs,
+            _ => None,
+        };
+        let mut response = self.into_parts().into_response();
+        if let Some(status) = rate_limit {
+            status.apply_headers(response.headers_mut());
+        }
+        if let Some(secs) = retry_after {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response
+    }
+}
+
+/// Require the key to hold `scope`
+pub fn require_scope(auth: &AuthContext, scope: Scope) -> Result<(), ApiError> {
+    require_any_scope(auth, &[scope])
+}
+
+/// Require the key to hold at least one of `scopes`
+pub fn require_any_scope(auth: &AuthContext, scopes: &[Scope]) -> Result<(), ApiError> {
+    if scopes.iter().any(|scope| auth.scopes.contains(scope)) {
+        Ok(())
+    } else {
+        Err(ApiError::InsufficientScope {
+            required: scopes.to_vec(),
+            available: auth.scopes.clone(),
+            details: None,
+        })
+    }
+}
@@ -0,0 +1,48 @@
+use serde_json::Value;
+use tracing::error;
+
+use crate::auth::AuthContext;
+use crate::database::Database;
+
+/// Audit action recorded when a tenant is created
+pub const TENANT_CREATED: &str = "tenant_created";
+
+/// Audit action recorded when a tenant is onboarded with its project and key
+pub const TENANT_ONBOARDED: &str = "tenant_onboarded";
+
+/// Audit action recorded when a tenant moves to another billing plan
+pub const TENANT_PLAN_CHANGED: &str = "tenant_plan_changed";
+
+/// Audit action recorded when a project is archived
+pub const PROJECT_ARCHIVED: &str = "project_archived";
+
+/// Audit action recorded when an API key is created
+pub const API_KEY_CREATED: &str = "api_key_created";
+
+/// Audit action recorded when an API key is revoked
+pub const API_KEY_REVOKED: &str = "api_key_revoked";
+
+/// Audit action recorded when an API key is given a new secret
+pub const API_KEY_ROTATED: &str = "api_key_rotated";
+
+/// Record an admin action that has already taken effect. The audit entry is
+/// written to `tenant_id`'s log and attributed to the caller's API key;
+/// failures are logged rather than returned so they don't mask the action.
+pub async fn record(
+    database: &Database,
+    auth: &AuthContext,
+    tenant_id: &str,
+    action: &str,
+    target: Option<&str>,
+    details: Value,
+) {
+    if let Err(e) = database
+        .record_audit(tenant_id, auth.api_key_id(), action, target, &details)
+        .await
+    {
+        error!(
+            "Failed to record audit action {} for tenant {}: {}",
+            action, tenant_id, e
+        );
+    }
+}
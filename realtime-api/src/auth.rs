@@ -1,20 +1,38 @@
 use anyhow::Result;
 use axum::{
-    extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    extract::{ConnectInfo, Request, State},
+    http::{header::AUTHORIZATION, HeaderMap, Method, StatusCode},
     middleware::Next,
     response::Response,
 };
-use bcrypt::{hash, verify, DEFAULT_COST};
+use argon2::password_hash::{
+    rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString,
+};
+use argon2::Argon2;
 use chrono::{DateTime, Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{encode, EncodingKey, Header};
+use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Instant;
 use thiserror::Error;
-use tracing::{debug, error, info, warn};
-
-use crate::models::{ApiKey, Scope, UserRole, Permission};
+use tracing::{debug, error, info, info_span, warn, Instrument};
+
+use crate::circuit_breaker::{circuit_open, circuit_open_response};
+use crate::config::JwtConfig;
+use crate::db_retry::is_pool_exhausted;
+use crate::jwt::{JwtVerifier, DEFAULT_JWT_ISSUER};
+use crate::models::{topic_allowed, topic_pattern_covers, ApiKey, Scope, UserRole, Permission};
+use crate::mtls::ClientCertificate;
+use crate::request_signing::{
+    generate_signing_secret, verify_request, HmacAuthorization, SigningSecretCipher,
+    MAX_SIGNATURE_SKEW_SECS,
+};
+use crate::rate_limit::{
+    acquire_or_allow, overloaded_response, rate_limit_fail_open_counter, RateLimitStatus,
+    RateLimitStore, RateLimiter,
+};
 use crate::Database;
 
 /// Authentication errors
@@ -30,21 +48,45 @@ pub enum AuthError {
         available: Vec<String>,
     },
     #[error("Rate limit exceeded")]
-    RateLimitExceeded,
+    RateLimitExceeded(RateLimitStatus),
     #[error("Invalid JWT token")]
     InvalidJwt,
     #[error("Tenant suspended")]
     TenantSuspended,
     #[error("Missing authorization header")]
     MissingAuth,
+    #[error("Unknown client certificate")]
+    UnknownClientCertificate,
+    #[error("Invalid request signature")]
+    InvalidSignature,
+    #[error("Request signature timestamp outside the allowed window")]
+    ExpiredSignature,
+    #[error("Request signature was already used")]
+    ReplayedSignature,
     #[error("Database error: {0}")]
     Database(#[from] anyhow::Error),
-    #[error("Bcrypt error: {0}")]
-    Bcrypt(#[from] bcrypt::BcryptError),
+    #[error("Hashing error: {0}")]
+    Hashing(#[from] argon2::password_hash::Error),
     #[error("JWT error: {0}")]
     Jwt(#[from] jsonwebtoken::errors::Error),
 }
 
+/// Length of the SHA-256 hex prefix used to look up API keys
+pub const API_KEY_LOOKUP_PREFIX_LEN: usize = 16;
+
+/// Prefix of every generated API key
+pub const API_KEY_PREFIX: &str = "rtp_";
+
+/// Minimum time between recorded uses of the same API key
+pub const API_KEY_LAST_USED_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Days without use after which an API key is listed as stale, by default
+pub const DEFAULT_STALE_API_KEY_DAYS: i64 = 90;
+
+/// How long a rotated API key's previous secret keeps working, by default
+pub const DEFAULT_API_KEY_ROTATION_GRACE: std::time::Duration =
+    std::time::Duration::from_secs(24 * 60 * 60);
+
 /// JWT Claims structure
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
@@ -55,6 +97,9 @@ pub struct Claims {
     pub exp: i64,    // Expiration time
     pub iat: i64,    // Issued at
     pub iss: String, // Issuer
+    /// Audience: a string or an array of strings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<serde_json::Value>,
 }
 
 /// Authentication context extracted from requests
@@ -64,23 +109,58 @@ pub struct AuthContext {
     pub project_id: String,
     pub scopes: Vec<Scope>,
     pub rate_limit_per_sec: i32,
+    /// Topic patterns the credentials are restricted to; empty allows all topics
+    pub allowed_topics: Vec<String>,
     pub auth_type: AuthType,
     pub user_id: Option<String>,
     pub user_role: Option<UserRole>,
 }
 
+impl AuthContext {
+    /// The API key id when authenticated with an API key
+    pub fn api_key_id(&self) -> Option<&str> {
+        match &self.auth_type {
+            AuthType::ApiKey { key_id } => Some(key_id),
+            AuthType::Jwt { .. } | AuthType::ClientCert { .. } => None,
+        }
+    }
+
+    /// Check if the credentials may publish or subscribe to a topic
+    pub fn allows_topic(&self, topic: &str) -> bool {
+        topic_allowed(&self.allowed_topics, topic)
+    }
+
+    /// Check a subscription's topics against the allowed patterns. Topic-restricted
+    /// credentials must name their topics, since an empty list means "everything".
+    pub fn check_subscribe_topics(&self, topics: &[String]) -> Result<(), String> {
+        if self.allowed_topics.is_empty() {
+            return Ok(());
+        }
+        if topics.is_empty() {
+            return Err(
+                "This API key is restricted to specific topics; subscribe to them explicitly"
+                    .to_string(),
+            );
+        }
+        let covered = |topic: &String| {
+            self.allowed_topics
+                .iter()
+                .any(|pattern| topic_pattern_covers(pattern, topic))
+        };
+        match topics.iter().find(|topic| !covered(topic)) {
+            Some(topic) => Err(format!("This API key may not access topic {}", topic)),
+            None => Ok(()),
+        }
+    }
+}
+
 /// Type of authentication used
 #[derive(Debug, Clone)]
 pub enum AuthType {
     ApiKey { key_id: String },
     Jwt { user_id: String },
-}
-
-/// Rate limiting tracker
-#[derive(Debug, Clone)]
-struct RateLimitEntry {
-    count: u32,
-    window_start: DateTime<Utc>,
+    /// mTLS with a certificate registered in `client_certs`
+    ClientCert { cert_id: String },
 }
 
 /// Authentication service
@@ -88,7 +168,18 @@ struct RateLimitEntry {
 pub struct AuthService {
     database: Database,
     jwt_secret: String,
-    rate_limits: Arc<Mutex<HashMap<String, RateLimitEntry>>>,
+    jwt_verifier: JwtVerifier,
+    rate_limiter: Arc<dyn RateLimitStore>,
+    // When this instance last recorded a use of each key, to skip redundant writes
+    last_used_recorded: Arc<DashMap<String, Instant>>,
+    stale_key_after: Duration,
+    rotation_grace_period: std::time::Duration,
+    platform_admin_tenant_id: Option<String>,
+    // Peers whose forwarding headers name the real client
+    trusted_proxies: Vec<IpAddr>,
+    // Authenticate credentials of suspended and past-due tenants too
+    allow_inactive_tenants: bool,
+    signing_cipher: SigningSecretCipher,
 }
 
 impl AuthService {
@@ -96,11 +187,101 @@ impl AuthService {
     pub fn new(database: Database, jwt_secret: String) -> Self {
         Self {
             database,
+            jwt_verifier: JwtVerifier::new(&jwt_secret),
             jwt_secret,
-            rate_limits: Arc::new(Mutex::new(HashMap::new())),
+            rate_limiter: Arc::new(RateLimiter::new()),
+            last_used_recorded: Arc::new(DashMap::new()),
+            stale_key_after: Duration::days(DEFAULT_STALE_API_KEY_DAYS),
+            rotation_grace_period: DEFAULT_API_KEY_ROTATION_GRACE,
+            platform_admin_tenant_id: None,
+            trusted_proxies: Vec::new(),
+            allow_inactive_tenants: false,
+            signing_cipher: SigningSecretCipher::new(&jwt_secret),
         }
     }
 
+    /// Encrypt stored signing secrets under a key derived from `key` instead
+    /// of the JWT secret
+    pub fn with_signing_secret_key(mut self, key: Option<&str>) -> Self {
+        if let Some(key) = key {
+            self.signing_cipher = SigningSecretCipher::new(key);
+        }
+        self
+    }
+
+    /// The cipher stored signing secrets are encrypted with
+    pub fn signing_cipher(&self) -> &SigningSecretCipher {
+        &self.signing_cipher
+    }
+
+    /// Keep rate limit buckets in `store`, e.g. Redis shared by all instances
+    pub fn with_rate_limit_store(mut self, store: Arc<dyn RateLimitStore>) -> Self {
+        self.rate_limiter = store;
+        self
+    }
+
+    /// Check JWT issuer and audience and verify tokens naming a `kid` with
+    /// the configured JWK set or JWKS endpoint
+    pub fn with_jwt_config(mut self, config: &JwtConfig) -> Self {
+        self.jwt_verifier = JwtVerifier::from_config(&self.jwt_secret, config);
+        self
+    }
+
+    /// Flag API keys unused for this many days as stale
+    pub fn with_stale_key_days(mut self, days: i64) -> Self {
+        self.stale_key_after = Duration::days(days);
+        self
+    }
+
+    /// Keep a rotated API key's previous secret valid for `grace_period`
+    pub fn with_rotation_grace_period(mut self, grace_period: std::time::Duration) -> Self {
+        self.rotation_grace_period = grace_period;
+        self
+    }
+
+    /// Let admin:write credentials of `tenant_id` manage every tenant
+    pub fn with_platform_admin_tenant(mut self, tenant_id: Option<String>) -> Self {
+        self.platform_admin_tenant_id = tenant_id;
+        self
+    }
+
+    /// Believe `X-Forwarded-For` and `X-Real-IP` on requests from these peers
+    pub fn with_trusted_proxies(mut self, proxies: Vec<IpAddr>) -> Self {
+        self.trusted_proxies = proxies;
+        self
+    }
+
+    /// Address of the client behind a request from `peer`, see [`client_ip`]
+    pub fn client_ip(&self, headers: &HeaderMap, peer: Option<SocketAddr>) -> Option<IpAddr> {
+        client_ip(headers, peer, &self.trusted_proxies)
+    }
+
+    /// This service, but authenticating credentials whose tenant is suspended
+    /// or past due, for the few requests such a tenant may still make
+    pub fn allowing_inactive_tenants(&self) -> Self {
+        Self {
+            allow_inactive_tenants: true,
+            ..self.clone()
+        }
+    }
+
+    // Whether a tenant's credentials may be used in its current status
+    fn tenant_allowed(&self, tenant: &crate::models::Tenant) -> bool {
+        tenant.is_active() || self.allow_inactive_tenants
+    }
+
+    /// Whether the credentials belong to the platform operator rather than a
+    /// customer tenant, allowing them to act on other tenants
+    pub fn is_platform_admin(&self, auth_context: &AuthContext) -> bool {
+        auth_context.scopes.contains(&Scope::AdminWrite)
+            && self.platform_admin_tenant_id.as_deref() == Some(auth_context.tenant_id.as_str())
+    }
+
+    /// Whether an API key has gone unused for longer than the stale threshold
+    pub fn is_stale_api_key(&self, api_key: &ApiKey) -> bool {
+        api_key.is_stale(self.stale_key_after)
+    }
+
     /// Generate a secure API key
     pub fn generate_api_key() -> String {
         use rand::Rng;
@@ -115,32 +296,44 @@ impl AuthService {
             })
             .collect();
 
-        format!("rtp_{}", key) // Prefix for realtime platform
+        format!("{}{}", API_KEY_PREFIX, key)
     }
 
-    /// Hash an API key for database lookup (using SHA-256)
+    /// Indexed lookup value for an API key: a truncated SHA-256 prefix that
+    /// finds the row in one query but is not enough to verify the key
     pub fn hash_api_key_for_lookup(key: &str) -> String {
+        let mut digest = Self::legacy_lookup_hash(key);
+        digest.truncate(API_KEY_LOOKUP_PREFIX_LEN);
+        digest
+    }
+
+    // Full SHA-256 digest stored as the lookup value before Argon2id hashing
+    fn legacy_lookup_hash(key: &str) -> String {
         use sha2::{Digest, Sha256};
         let mut hasher = Sha256::new();
         hasher.update(key.as_bytes());
         format!("{:x}", hasher.finalize())
     }
 
-    /// Hash an API key for secure storage (using bcrypt)
+    /// Hash an API key for secure storage (using Argon2id)
     pub fn hash_api_key_for_storage(key: &str) -> Result<String, AuthError> {
-        let hash = hash(key, DEFAULT_COST)?;
-        Ok(hash)
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = Argon2::default().hash_password(key.as_bytes(), &salt)?;
+        Ok(hash.to_string())
     }
 
-    /// Verify an API key against its bcrypt hash
-    pub fn verify_api_key(key: &str, bcrypt_hash: &str) -> Result<bool, AuthError> {
-        let is_valid = verify(key, bcrypt_hash)?;
-        Ok(is_valid)
+    /// Verify an API key against its Argon2id hash
+    pub fn verify_api_key(key: &str, stored_hash: &str) -> Result<bool, AuthError> {
+        let parsed_hash = PasswordHash::new(stored_hash)?;
+        match Argon2::default().verify_password(key.as_bytes(), &parsed_hash) {
+            Ok(()) => Ok(true),
+            Err(argon2::password_hash::Error::Password) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
     }
 
-    /// Create a new API key in the database
-    pub async fn create_api_key(
-        &self,
+    /// Generate a raw API key and its unsaved record (only the lookup hash is kept)
+    pub fn new_api_key(
         tenant_id: String,
         project_id: String,
         scopes: Vec<Scope>,
@@ -148,21 +341,40 @@ impl AuthService {
         expires_at: Option<DateTime<Utc>>,
     ) -> Result<(String, ApiKey), AuthError> {
         let raw_key = Self::generate_api_key();
-        // Use SHA-256 hash for database lookup
+        // SHA-256 prefix for database lookup, Argon2id hash for verification
         let lookup_hash = Self::hash_api_key_for_lookup(&raw_key);
+        let secret_hash = Self::hash_api_key_for_storage(&raw_key)?;
 
-        let api_key = ApiKey::new(
+        let mut api_key = ApiKey::new(
             tenant_id,
             project_id,
             lookup_hash,
             scopes,
             rate_limit_per_sec,
-        );
+        )
+        .with_secret_hash(secret_hash);
+        api_key.key_last4 = Some(raw_key[raw_key.len() - 4..].to_string());
 
         // Set expiration if provided
-        let mut api_key = api_key;
         api_key.expires_at = expires_at;
 
+        Ok((raw_key, api_key))
+    }
+
+    /// Create a new API key in the database
+    pub async fn create_api_key(
+        &self,
+        tenant_id: String,
+        project_id: String,
+        scopes: Vec<Scope>,
+        rate_limit_per_sec: i32,
+        allowed_topics: Vec<String>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(String, ApiKey), AuthError> {
+        let (raw_key, api_key) =
+            Self::new_api_key(tenant_id, project_id, scopes, rate_limit_per_sec, expires_at)?;
+        let api_key = self.with_new_signing_secret(api_key.with_allowed_topics(allowed_topics));
+
         self.database.create_api_key(&api_key).await?;
 
         info!(
@@ -172,18 +384,206 @@ impl AuthService {
         Ok((raw_key, api_key))
     }
 
+    /// Give an unsaved API key a new signing secret, encrypted for storage
+    pub fn with_new_signing_secret(&self, api_key: ApiKey) -> ApiKey {
+        let signing_secret = self
+            .signing_cipher
+            .seal(&api_key.id, &generate_signing_secret());
+        api_key.with_signing_secret(signing_secret)
+    }
+
+    /// The plain signing secret of an API key, to hand to its owner once
+    pub fn signing_secret(&self, api_key: &ApiKey) -> Option<String> {
+        let sealed = api_key.signing_secret.as_deref()?;
+        self.signing_cipher.open(&api_key.id, sealed)
+    }
+
+    /// Give one of the tenant's API keys a new secret and signing secret and
+    /// return the new raw key. The old secrets keep working for the rotation
+    /// grace period so clients can switch over; None if the tenant has no
+    /// such active key.
+    ///
+    /// Only one previous secret is kept: rotating again within the grace
+    /// period cuts off the oldest secret immediately and starts a new grace
+    /// period for the one being replaced. This is what makes a second rotation
+    /// useful when a freshly issued secret leaks.
+    pub async fn rotate_api_key(
+        &self,
+        tenant_id: &str,
+        key_id: &str,
+    ) -> Result<Option<(String, ApiKey)>, AuthError> {
+        let raw_key = Self::generate_api_key();
+        let lookup_hash = Self::hash_api_key_for_lookup(&raw_key);
+        let secret_hash = Self::hash_api_key_for_storage(&raw_key)?;
+        let key_last4 = &raw_key[raw_key.len() - 4..];
+        let signing_secret = self.signing_cipher.seal(key_id, &generate_signing_secret());
+        let previous_expires_at =
+            Utc::now() + Duration::seconds(self.rotation_grace_period.as_secs() as i64);
+
+        let Some(api_key) = self
+            .database
+            .rotate_api_key(
+                tenant_id,
+                key_id,
+                &lookup_hash,
+                &secret_hash,
+                key_last4,
+                &signing_secret,
+                previous_expires_at,
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        info!(
+            "Rotated API key {} for tenant {}; previous secret valid until {}",
+            key_id, tenant_id, previous_expires_at
+        );
+        Ok(Some((raw_key, api_key)))
+    }
+
     /// Validate an API key and return authentication context
     pub async fn validate_api_key(&self, key: &str) -> Result<AuthContext, AuthError> {
-        // Use SHA-256 hash for database lookup
+        self.validate_api_key_from(key, None).await
+    }
+
+    /// Validate an API key presented by `client_ip`, recording the use on the key
+    pub async fn validate_api_key_from(
+        &self,
+        key: &str,
+        client_ip: Option<IpAddr>,
+    ) -> Result<AuthContext, AuthError> {
+        // Fast step: find the row by its indexed lookup prefix, falling back to
+        // the full SHA-256 digest stored for keys created before Argon2id
         let lookup_hash = Self::hash_api_key_for_lookup(key);
+        let legacy_hash = Self::legacy_lookup_hash(key);
 
-        // Get API key from database by lookup hash
         let api_key = self
             .database
-            .get_api_key_by_hash(&lookup_hash)
+            .get_api_key_by_lookup(&lookup_hash, &legacy_hash)
             .await?
             .ok_or(AuthError::InvalidApiKey)?;
 
+        // A rotated key is found by its previous secret during the grace period
+        let rotated_out = api_key.key_hash != lookup_hash && api_key.key_hash != legacy_hash;
+        let (stored_lookup, stored_secret) = if rotated_out {
+            (api_key.previous_key_hash.as_deref(), api_key.previous_secret_hash.clone())
+        } else {
+            (Some(api_key.key_hash.as_str()), api_key.secret_hash.clone())
+        };
+
+        // Slow step: verify against the Argon2id hash off the async runtime
+        match stored_secret {
+            Some(secret_hash) => {
+                let raw_key = key.to_string();
+                let is_valid = tokio::task::spawn_blocking(move || {
+                    Self::verify_api_key(&raw_key, &secret_hash)
+                })
+                .await
+                .map_err(|e| AuthError::Database(e.into()))??;
+                if !is_valid {
+                    return Err(AuthError::InvalidApiKey);
+                }
+            }
+            None if stored_lookup == Some(legacy_hash.as_str()) => {
+                // Only the current secret is re-hashed; a previous one just expires
+                if !rotated_out {
+                    self.upgrade_legacy_api_key(&api_key.id, key).await;
+                }
+            }
+            None => return Err(AuthError::InvalidApiKey),
+        }
+
+        self.api_key_context(api_key, client_ip).await
+    }
+
+    /// Validate a request signed with an API key's signing secret. The
+    /// signature covers the method, path, timestamp and body; timestamps
+    /// outside the skew window are rejected so captured requests can't be
+    /// replayed later, and each signature is accepted only once within it.
+    /// The key's scopes apply as for a bearer key.
+    pub async fn validate_signed_request(
+        &self,
+        authorization: &HmacAuthorization,
+        method: &str,
+        path: &str,
+        body: &[u8],
+        client_ip: Option<IpAddr>,
+    ) -> Result<AuthContext, AuthError> {
+        if !authorization.is_fresh(Utc::now().timestamp()) {
+            return Err(AuthError::ExpiredSignature);
+        }
+
+        let api_key = self
+            .database
+            .get_signing_api_key(&authorization.key_id)
+            .await?
+            .ok_or(AuthError::InvalidSignature)?;
+
+        // A rotated key's previous signing secret is accepted during the grace period
+        let previous_live = api_key
+            .previous_expires_at
+            .is_some_and(|expires_at| expires_at > Utc::now());
+        let previous = api_key.previous_signing_secret.as_deref().filter(|_| previous_live);
+        let verified = api_key
+            .signing_secret
+            .as_deref()
+            .into_iter()
+            .chain(previous)
+            .filter_map(|sealed| self.signing_cipher.open(&api_key.id, sealed))
+            .any(|secret| {
+                verify_request(
+                    &secret,
+                    method,
+                    path,
+                    authorization.timestamp,
+                    body,
+                    &authorization.signature,
+                )
+            });
+        if !verified {
+            return Err(AuthError::InvalidSignature);
+        }
+        self.record_signature(authorization).await?;
+
+        self.api_key_context(api_key, client_ip).await
+    }
+
+    // Remember a verified signature for as long as its timestamp stays fresh,
+    // through the rate limit store so every instance sees it, and reject
+    // repeats. If the store is unreachable the request is let through, as
+    // its rate limit check would be.
+    async fn record_signature(&self, authorization: &HmacAuthorization) -> Result<(), AuthError> {
+        let fresh_for = std::time::Duration::from_secs(2 * MAX_SIGNATURE_SKEW_SECS as u64);
+        let identifier = format!(
+            "signature:{}:{}",
+            authorization.key_id, authorization.signature
+        );
+        match self.rate_limiter.record_once(&identifier, fresh_for).await {
+            Ok(true) => Ok(()),
+            Ok(false) => {
+                warn!(
+                    "Replayed request signature for key {}",
+                    authorization.key_id
+                );
+                Err(AuthError::ReplayedSignature)
+            }
+            Err(e) => {
+                rate_limit_fail_open_counter().inc();
+                error!("Rate limit store unavailable, skipping replay check: {}", e);
+                Ok(())
+            }
+        }
+    }
+
+    // Checks shared by every way of presenting an API key once it has been
+    // verified: expiry, tenant status, use tracking and the rate limit
+    async fn api_key_context(
+        &self,
+        api_key: ApiKey,
+        client_ip: Option<IpAddr>,
+    ) -> Result<AuthContext, AuthError> {
         // Verify the key is still valid
         if !api_key.is_valid() {
             if !api_key.is_active {
@@ -200,25 +600,121 @@ impl AuthService {
             .await?
             .ok_or(AuthError::InvalidApiKey)?;
 
-        if !tenant.is_active() {
+        if !self.tenant_allowed(&tenant) {
             return Err(AuthError::TenantSuspended);
         }
 
-        // Check rate limits
-        self.check_rate_limit(&api_key.id, api_key.rate_limit_per_sec as u32)
+        // Check rate limits before recording the use, so a throttled flood
+        // doesn't write to the database
+        self.check_rate_limit(&api_key.id, api_key.rate_limit_per_sec.max(0) as u32)
             .await?;
 
+        self.record_api_key_use(&api_key.id, client_ip).await;
+
         Ok(AuthContext {
             tenant_id: api_key.tenant_id,
             project_id: api_key.project_id,
             scopes: api_key.scopes,
             rate_limit_per_sec: api_key.rate_limit_per_sec,
+            allowed_topics: api_key.allowed_topics,
             auth_type: AuthType::ApiKey { key_id: api_key.id },
             user_id: None,
             user_role: None,
         })
     }
 
+    /// Authenticate with a client certificate verified during the TLS
+    /// handshake, using the tenant, project and scopes it was registered with
+    pub async fn validate_client_certificate(
+        &self,
+        certificate: &ClientCertificate,
+    ) -> Result<AuthContext, AuthError> {
+        let client_cert = self
+            .database
+            .get_client_cert(
+                &certificate.spki_sha256,
+                &certificate.subject,
+                &certificate.issuer,
+            )
+            .await?
+            .ok_or(AuthError::UnknownClientCertificate)?;
+
+        let tenant = self
+            .database
+            .get_tenant(&client_cert.tenant_id)
+            .await?
+            .ok_or(AuthError::UnknownClientCertificate)?;
+
+        if !self.tenant_allowed(&tenant) {
+            return Err(AuthError::TenantSuspended);
+        }
+
+        self.check_rate_limit(&client_cert.id, client_cert.rate_limit_per_sec.max(0) as u32)
+            .await?;
+
+        Ok(AuthContext {
+            tenant_id: client_cert.tenant_id,
+            project_id: client_cert.project_id,
+            scopes: client_cert.scopes,
+            rate_limit_per_sec: client_cert.rate_limit_per_sec,
+            allowed_topics: client_cert.allowed_topics,
+            auth_type: AuthType::ClientCert {
+                cert_id: client_cert.id,
+            },
+            user_id: None,
+            user_role: None,
+        })
+    }
+
+    // Record when and from where a key was used, at most once per
+    // API_KEY_LAST_USED_INTERVAL. The database applies the same interval so
+    // several instances don't each write on every request. Failures are logged.
+    async fn record_api_key_use(&self, key_id: &str, client_ip: Option<IpAddr>) {
+        let now = Instant::now();
+        if let Some(recorded) = self.last_used_recorded.get(key_id) {
+            if now.duration_since(*recorded) < API_KEY_LAST_USED_INTERVAL {
+                return;
+            }
+        }
+        self.last_used_recorded.insert(key_id.to_string(), now);
+
+        let client_ip = client_ip.map(|ip| ip.to_string());
+        if let Err(e) = self
+            .database
+            .touch_api_key(key_id, client_ip.as_deref(), API_KEY_LAST_USED_INTERVAL)
+            .await
+        {
+            warn!("Failed to record use of API key {}: {}", key_id, e);
+        }
+    }
+
+    // Re-hash a legacy key with Argon2id after it has been presented successfully.
+    // Failures are logged and retried on the next use rather than failing the request.
+    async fn upgrade_legacy_api_key(&self, key_id: &str, key: &str) {
+        let raw_key = key.to_string();
+        let secret_hash =
+            match tokio::task::spawn_blocking(move || Self::hash_api_key_for_storage(&raw_key))
+                .await
+                .map_err(|e| AuthError::Database(e.into()))
+                .and_then(|result| result)
+            {
+                Ok(secret_hash) => secret_hash,
+                Err(e) => {
+                    warn!("Failed to re-hash legacy API key {}: {}", key_id, e);
+                    return;
+                }
+            };
+
+        match self
+            .database
+            .upgrade_api_key_hash(key_id, &Self::hash_api_key_for_lookup(key), &secret_hash)
+            .await
+        {
+            Ok(_) => info!("Upgraded API key {} to Argon2id", key_id),
+            Err(e) => warn!("Failed to store upgraded hash for API key {}: {}", key_id, e),
+        }
+    }
+
     /// Generate a JWT token
     pub fn generate_jwt(
         &self,
@@ -238,7 +734,12 @@ impl AuthService {
             scopes: scopes.iter().map(|s| format!("{:?}", s)).collect(),
             exp: exp.timestamp(),
             iat: now.timestamp(),
-            iss: "realtime-platform".to_string(),
+            iss: self
+                .jwt_verifier
+                .issuer()
+                .unwrap_or(DEFAULT_JWT_ISSUER)
+                .to_string(),
+            aud: self.jwt_verifier.audience().map(|aud| serde_json::json!(aud)),
         };
 
         let token = encode(
@@ -252,13 +753,7 @@ impl AuthService {
 
     /// Validate a JWT token and return authentication context
     pub async fn validate_jwt(&self, token: &str) -> Result<AuthContext, AuthError> {
-        let token_data = decode::<Claims>(
-            token,
-            &DecodingKey::from_secret(self.jwt_secret.as_ref()),
-            &Validation::default(),
-        )?;
-
-        let claims = token_data.claims;
+        let claims = self.jwt_verifier.verify(token).await?;
 
         // Check if token is expired
         let now = Utc::now().timestamp();
@@ -273,7 +768,7 @@ impl AuthService {
             .await?
             .ok_or(AuthError::InvalidJwt)?;
 
-        if !tenant.is_active() {
+        if !self.tenant_allowed(&tenant) {
             return Err(AuthError::TenantSuspended);
         }
 
@@ -296,6 +791,7 @@ impl AuthService {
             project_id: claims.project_id,
             scopes,
             rate_limit_per_sec: 1000, // Default rate limit for JWT tokens
+            allowed_topics: Vec::new(),
             auth_type: AuthType::Jwt {
                 user_id: claims.sub.clone(),
             },
@@ -316,47 +812,37 @@ impl AuthService {
         }
     }
 
-    /// Check rate limits for a given identifier
+    /// Take a token from the identifier's rate limit bucket
     async fn check_rate_limit(
         &self,
         identifier: &str,
         limit_per_sec: u32,
     ) -> Result<(), AuthError> {
-        let now = Utc::now();
-        let mut rate_limits = self.rate_limits.lock().unwrap();
-
-        let entry = rate_limits
-            .entry(identifier.to_string())
-            .or_insert(RateLimitEntry {
-                count: 0,
-                window_start: now,
-            });
-
-        // Reset window if more than 1 second has passed
-        if now.signed_duration_since(entry.window_start).num_seconds() >= 1 {
-            entry.count = 0;
-            entry.window_start = now;
-        }
-
-        // Check if limit is exceeded
-        if entry.count >= limit_per_sec {
+        let status = acquire_or_allow(self.rate_limiter.as_ref(), identifier, limit_per_sec).await;
+        if !status.allowed {
             warn!(
                 "Rate limit exceeded for {}: {} requests/sec",
-                identifier, entry.count
+                identifier, limit_per_sec
             );
-            return Err(AuthError::RateLimitExceeded);
+            return Err(AuthError::RateLimitExceeded(status));
         }
 
-        // Increment counter
-        entry.count += 1;
-
         debug!(
-            "Rate limit check passed for {}: {}/{} requests",
-            identifier, entry.count, limit_per_sec
+            "Rate limit check passed for {}: {}/{} remaining",
+            identifier, status.remaining, limit_per_sec
         );
         Ok(())
     }
 
+    /// Current rate limit state for an authenticated API key, without consuming a token
+    pub async fn rate_limit_status(&self, auth: &AuthContext) -> Option<RateLimitStatus> {
+        let key_id = auth.api_key_id()?;
+        self.rate_limiter
+            .peek(key_id, auth.rate_limit_per_sec.max(0) as u32)
+            .await
+            .ok()
+    }
+
     /// Revoke an API key
     pub async fn revoke_api_key(&self, tenant_id: &str, key_id: &str) -> Result<(), AuthError> {
         self.database.revoke_api_key(tenant_id, key_id).await?;
@@ -364,13 +850,9 @@ impl AuthService {
         Ok(())
     }
 
-    /// Clean up expired rate limit entries (should be called periodically)
+    /// Clean up idle rate limit buckets (should be called periodically)
     pub fn cleanup_rate_limits(&self) {
-        let now = Utc::now();
-        let mut rate_limits = self.rate_limits.lock().unwrap();
-
-        rate_limits
-            .retain(|_, entry| now.signed_duration_since(entry.window_start).num_seconds() < 60);
+        self.rate_limiter.cleanup(std::time::Duration::from_secs(60));
     }
 
     /// Check if user has required permission based on their role
@@ -449,52 +931,202 @@ pub fn extract_auth_header(headers: &HeaderMap) -> Result<String, AuthError> {
     }
 }
 
+/// Where a WebSocket handshake's credential came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialSource {
+    Header,
+    /// `?access_token=`, for browser clients that can't set handshake headers
+    Query,
+}
+
+/// Credential for a WebSocket handshake: the `Authorization` header, falling
+/// back to an `access_token` query parameter. The header wins when both are sent.
+pub fn extract_websocket_credential(
+    headers: &HeaderMap,
+    access_token: Option<&str>,
+) -> Result<(String, CredentialSource), AuthError> {
+    match extract_auth_header(headers) {
+        Ok(value) => Ok((value, CredentialSource::Header)),
+        Err(e) => access_token
+            .filter(|token| !token.is_empty())
+            .map(|token| (token.to_string(), CredentialSource::Query))
+            .ok_or(e),
+    }
+}
+
+/// Address of the client behind a request. When the peer is one of
+/// `trusted_proxies` this is the first `X-Forwarded-For` hop, then
+/// `X-Real-IP`; otherwise, or without those headers, the peer address of the
+/// connection, as any client can set them.
+pub fn client_ip(
+    headers: &HeaderMap,
+    peer: Option<SocketAddr>,
+    trusted_proxies: &[IpAddr],
+) -> Option<IpAddr> {
+    let peer = peer.map(|addr| addr.ip());
+    if !peer.is_some_and(|ip| trusted_proxies.contains(&ip)) {
+        return peer;
+    }
+
+    let forwarded = headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .and_then(|hop| hop.trim().parse().ok());
+    let real_ip = || {
+        headers
+            .get("x-real-ip")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse().ok())
+    };
+
+    forwarded.or_else(real_ip).or(peer)
+}
+
+/// `GET /admin/tenants/{id}`, which a suspended or past-due tenant may still
+/// call to read its status
+fn is_tenant_status_read(request: &Request) -> bool {
+    let segments: Vec<&str> = request.uri().path().trim_matches('/').split('/').collect();
+    request.method() == Method::GET
+        && matches!(segments.as_slice(), ["admin", "tenants", id] if !id.is_empty())
+}
+
 /// Middleware for API key authentication
 pub async fn api_key_auth_middleware(
     State(auth_service): State<AuthService>,
-    mut request: Request,
+    request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
+    let auth_service = if is_tenant_status_read(&request) {
+        auth_service.allowing_inactive_tenants()
+    } else {
+        auth_service
+    };
     let headers = request.headers();
-
-    match extract_auth_header(headers) {
-        Ok(auth_value) => {
-            // Try to validate as API key first
-            match auth_service.validate_api_key(&auth_value).await {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| *addr);
+
+    // An Authorization header takes precedence; without one, a client
+    // certificate verified in the TLS handshake can authenticate the request
+    if !headers.contains_key(AUTHORIZATION) {
+        if let Some(certificate) = request.extensions().get::<ClientCertificate>().cloned() {
+            return match auth_service.validate_client_certificate(&certificate).await {
                 Ok(auth_context) => {
-                    // Insert auth context into request extensions
-                    request.extensions_mut().insert(auth_context);
-                    Ok(next.run(request).await)
-                }
-                Err(AuthError::InvalidApiKey) => {
-                    // Try JWT validation as fallback
-                    match auth_service.validate_jwt(&auth_value).await {
-                        Ok(auth_context) => {
-                            request.extensions_mut().insert(auth_context);
-                            Ok(next.run(request).await)
-                        }
-                        Err(e) => {
-                            error!("Authentication failed: {}", e);
-                            Err(StatusCode::UNAUTHORIZED)
-                        }
-                    }
-                }
-                Err(AuthError::RateLimitExceeded) => {
-                    warn!("Rate limit exceeded");
-                    Err(StatusCode::TOO_MANY_REQUESTS)
-                }
-                Err(AuthError::TenantSuspended) => {
-                    warn!("Tenant suspended");
-                    Err(StatusCode::FORBIDDEN)
-                }
-                Err(e) => {
-                    error!("Authentication error: {}", e);
-                    Err(StatusCode::UNAUTHORIZED)
+                    Ok(run_authenticated(&auth_service, auth_context, request, next).await)
                 }
+                Err(e) => auth_failure_response(
+                    e,
+                    &format!("Client certificate authentication for {}", certificate.subject),
+                ),
+            };
+        }
+    }
+
+    // A signed request is checked against its body, so the body is read here
+    // and handed on to the handler unchanged
+    let hmac_authorization = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(HmacAuthorization::parse);
+    if let Some(authorization) = hmac_authorization {
+        let client_ip = auth_service.client_ip(headers, peer);
+        let method = request.method().to_string();
+        let path = request
+            .uri()
+            .path_and_query()
+            .map(|path| path.as_str().to_string())
+            .unwrap_or_else(|| request.uri().path().to_string());
+        let (parts, body) = request.into_parts();
+        let body = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to read signed request body: {}", e);
+                return Err(StatusCode::PAYLOAD_TOO_LARGE);
+            }
+        };
+        let result = auth_service
+            .validate_signed_request(&authorization, &method, &path, &body, client_ip)
+            .await;
+        let request = Request::from_parts(parts, axum::body::Body::from(body));
+
+        return match result {
+            Ok(auth_context) => {
+                Ok(run_authenticated(&auth_service, auth_context, request, next).await)
             }
+            Err(e) => auth_failure_response(
+                e,
+                &format!("Signed request authentication for key {}", authorization.key_id),
+            ),
+        };
+    }
+
+    let Ok(auth_value) = extract_auth_header(headers) else {
+        error!("Missing or invalid authorization header");
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+
+    // Try to validate as API key first, falling back to a JWT
+    let result = match auth_service
+        .validate_api_key_from(&auth_value, auth_service.client_ip(headers, peer))
+        .await
+    {
+        Err(AuthError::InvalidApiKey) => auth_service.validate_jwt(&auth_value).await,
+        result => result,
+    };
+    match result {
+        Ok(auth_context) => Ok(run_authenticated(&auth_service, auth_context, request, next).await),
+        Err(e) => auth_failure_response(e, "Authentication"),
+    }
+}
+
+// Run the request as `auth_context` inside a span carrying the tenant id;
+// handlers fill in the correlation id. Responses to API key requests carry
+// the key's rate limit headers.
+async fn run_authenticated(
+    auth_service: &AuthService,
+    auth_context: AuthContext,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let span = info_span!(
+        "tenant_request",
+        tenant_id = %auth_context.tenant_id,
+        correlation_id = tracing::field::Empty
+    );
+    let rate_limit = auth_service.rate_limit_status(&auth_context).await;
+    request.extensions_mut().insert(auth_context);
+
+    let mut response = next.run(request).instrument(span).await;
+    if let Some(status) = rate_limit {
+        status.apply_headers(response.headers_mut());
+    }
+    response
+}
+
+// Response to credentials that didn't authenticate; `attempt` describes
+// what was tried for the log
+fn auth_failure_response(error: AuthError, attempt: &str) -> Result<Response, StatusCode> {
+    match error {
+        AuthError::RateLimitExceeded(status) => {
+            warn!("Rate limit exceeded");
+            Ok(status.into_response())
+        }
+        AuthError::TenantSuspended => {
+            warn!("Tenant suspended");
+            Err(StatusCode::FORBIDDEN)
         }
-        Err(_) => {
-            error!("Missing or invalid authorization header");
+        AuthError::Database(e) if circuit_open(&e).is_some() => {
+            warn!("Database unavailable during authentication: {}", e);
+            Ok(circuit_open_response(&e))
+        }
+        AuthError::Database(e) if is_pool_exhausted(&e) => {
+            warn!("Database pool exhausted during authentication: {}", e);
+            Ok(overloaded_response())
+        }
+        e => {
+            warn!("{} failed: {}", attempt, e);
             Err(StatusCode::UNAUTHORIZED)
         }
     }
@@ -531,6 +1163,64 @@ pub fn require_scope(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::topic_pattern_matches;
+
+    #[test]
+    fn test_client_ip_prefers_forwarded_headers_from_trusted_proxies() {
+        let peer: SocketAddr = "10.0.0.5:40000".parse().unwrap();
+        let trusted = [peer.ip()];
+        let mut headers = HeaderMap::new();
+        assert_eq!(client_ip(&headers, Some(peer), &trusted), Some(peer.ip()));
+        assert_eq!(client_ip(&headers, None, &trusted), None);
+
+        headers.insert("x-real-ip", "198.51.100.9".parse().unwrap());
+        assert_eq!(
+            client_ip(&headers, Some(peer), &trusted),
+            "198.51.100.9".parse().ok()
+        );
+
+        headers.insert("x-forwarded-for", "203.0.113.7, 10.0.0.1".parse().unwrap());
+        assert_eq!(
+            client_ip(&headers, Some(peer), &trusted),
+            "203.0.113.7".parse().ok()
+        );
+    }
+
+    #[test]
+    fn test_client_ip_ignores_forwarded_headers_from_untrusted_peers() {
+        let peer: SocketAddr = "203.0.113.50:40000".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-real-ip", "198.51.100.9".parse().unwrap());
+        headers.insert("x-forwarded-for", "203.0.113.7".parse().unwrap());
+
+        assert_eq!(client_ip(&headers, Some(peer), &[]), Some(peer.ip()));
+        let other_proxy: IpAddr = "10.0.0.5".parse().unwrap();
+        assert_eq!(
+            client_ip(&headers, Some(peer), &[other_proxy]),
+            Some(peer.ip())
+        );
+        assert_eq!(client_ip(&headers, None, &[other_proxy]), None);
+    }
+
+    #[test]
+    fn test_websocket_credential_prefers_header() {
+        let mut headers = HeaderMap::new();
+        assert!(matches!(
+            extract_websocket_credential(&headers, None),
+            Err(AuthError::MissingAuth)
+        ));
+        assert!(extract_websocket_credential(&headers, Some("")).is_err());
+        assert_eq!(
+            extract_websocket_credential(&headers, Some("rtp_query")).unwrap(),
+            ("rtp_query".to_string(), CredentialSource::Query)
+        );
+
+        headers.insert("authorization", "ApiKey rtp_header".parse().unwrap());
+        assert_eq!(
+            extract_websocket_credential(&headers, Some("rtp_query")).unwrap(),
+            ("rtp_header".to_string(), CredentialSource::Header)
+        );
+    }
 
     #[test]
     fn test_generate_api_key() {
@@ -545,7 +1235,7 @@ mod tests {
 
         // Test lookup hash
         let lookup_hash = AuthService::hash_api_key_for_lookup(key);
-        assert_eq!(lookup_hash.len(), 64); // SHA-256 produces 64 character hex string
+        assert_eq!(lookup_hash.len(), API_KEY_LOOKUP_PREFIX_LEN);
 
         // Test storage hash
         let storage_hash = AuthService::hash_api_key_for_storage(key).unwrap();
@@ -553,6 +1243,50 @@ mod tests {
         assert!(!AuthService::verify_api_key("wrong_key", &storage_hash).unwrap());
     }
 
+    #[test]
+    fn test_lookup_prefix_is_stable_and_truncated() {
+        let key = AuthService::generate_api_key();
+
+        let lookup_hash = AuthService::hash_api_key_for_lookup(&key);
+        assert_eq!(lookup_hash, AuthService::hash_api_key_for_lookup(&key));
+        // The prefix finds the row but is not the full digest kept for legacy keys
+        let legacy_hash = AuthService::legacy_lookup_hash(&key);
+        assert_eq!(legacy_hash.len(), 64);
+        assert!(legacy_hash.starts_with(&lookup_hash));
+        assert_ne!(
+            lookup_hash,
+            AuthService::hash_api_key_for_lookup(&AuthService::generate_api_key())
+        );
+    }
+
+    #[test]
+    fn test_storage_hash_is_salted_argon2id() {
+        let key = AuthService::generate_api_key();
+
+        let first = AuthService::hash_api_key_for_storage(&key).unwrap();
+        let second = AuthService::hash_api_key_for_storage(&key).unwrap();
+        assert!(first.starts_with("$argon2id$"));
+        assert_ne!(first, second);
+        assert!(AuthService::verify_api_key(&key, &second).unwrap());
+    }
+
+    #[test]
+    fn test_new_api_key_stores_prefix_and_secret_hash() {
+        let (raw_key, api_key) = AuthService::new_api_key(
+            "tenant_123".to_string(),
+            "project_456".to_string(),
+            vec![Scope::EventsPublish],
+            100,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(api_key.key_hash, AuthService::hash_api_key_for_lookup(&raw_key));
+        let secret_hash = api_key.secret_hash.expect("secret hash should be set");
+        assert!(AuthService::verify_api_key(&raw_key, &secret_hash).unwrap());
+        assert!(!AuthService::verify_api_key("rtp_wrong", &secret_hash).unwrap());
+    }
+
     #[test]
     fn test_scope_checking_logic() {
         let scopes = [Scope::EventsPublish, Scope::EventsSubscribe];
@@ -563,4 +1297,45 @@ mod tests {
         assert!(!scopes.contains(&Scope::AdminWrite));
         assert!(!scopes.contains(&Scope::BillingRead));
     }
+
+    #[test]
+    fn test_topic_pattern_matching() {
+        assert!(topic_pattern_matches("orders.*", "orders.created"));
+        assert!(!topic_pattern_matches("orders.*", "orders.created.v2"));
+        assert!(!topic_pattern_matches("orders.*", "billing.created"));
+        assert!(topic_pattern_matches("orders.>", "orders.created.v2"));
+        assert!(!topic_pattern_matches("orders.>", "orders"));
+        assert!(topic_pattern_matches("orders.created", "orders.created"));
+        assert!(topic_allowed(&[], "anything.at.all"));
+    }
+
+    #[test]
+    fn test_restricted_context_checks_subscriptions() {
+        let auth = AuthContext {
+            tenant_id: "tenant_123".to_string(),
+            project_id: "project_456".to_string(),
+            scopes: vec![Scope::EventsSubscribe],
+            rate_limit_per_sec: 100,
+            allowed_topics: vec!["orders.*".to_string()],
+            auth_type: AuthType::ApiKey {
+                key_id: "key_1".to_string(),
+            },
+            user_id: None,
+            user_role: None,
+        };
+
+        assert!(auth
+            .check_subscribe_topics(&["orders.created".to_string()])
+            .is_ok());
+        assert!(auth
+            .check_subscribe_topics(&["billing.invoiced".to_string()])
+            .is_err());
+        // An empty subscription would receive every topic
+        assert!(auth.check_subscribe_topics(&[]).is_err());
+
+        // Wildcard subscriptions must stay within the allowed patterns
+        assert!(auth.check_subscribe_topics(&["orders.*".to_string()]).is_ok());
+        assert!(auth.check_subscribe_topics(&["orders.>".to_string()]).is_err());
+        assert!(auth.check_subscribe_topics(&["*.created".to_string()]).is_err());
+    }
 }
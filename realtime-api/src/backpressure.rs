@@ -0,0 +1,189 @@
+use prometheus::{CounterVec, Opts};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::event_service::{EventService, SendError};
+use crate::models::Event;
+use crate::nats::SubscriptionConfig;
+
+/// Delivery buffer per connection when the project doesn't set one
+pub const DEFAULT_DELIVERY_BUFFER_SIZE: i32 = 1000;
+
+/// Close code sent after a connection fell behind and events were dropped
+pub const LAGGED_CLOSE_CODE: u16 = 4408;
+
+/// Close reason sent after a connection fell behind and events were dropped
+pub const LAGGED_CLOSE_REASON: &str = "consumer_lagged";
+
+/// Error code reported to a client whose connection dropped events
+pub const EVENTS_DROPPED_CODE: &str = "EVENTS_DROPPED";
+
+// Whether lagging connections are closed so the client reconnects and replays,
+// and how many events have been dropped per transport
+lazy_static::lazy_static! {
+    static ref CLOSE_LAGGED_CONNECTIONS: AtomicBool = AtomicBool::new(false);
+    static ref LAGGED_EVENTS_DROPPED: CounterVec = CounterVec::new(
+        Opts::new(
+            "realtime_lagged_events_dropped_total",
+            "Events dropped because a connection's delivery buffer overflowed"
+        ),
+        &["transport"]
+    )
+    .expect("valid metric definition");
+}
+
+/// Enable or disable closing connections after they drop events
+pub fn set_close_lagged_connections(enabled: bool) {
+    CLOSE_LAGGED_CONNECTIONS.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether connections are closed after they drop events
+pub fn close_lagged_connections_enabled() -> bool {
+    CLOSE_LAGGED_CONNECTIONS.load(Ordering::Relaxed)
+}
+
+/// Counter of events dropped by lagging connections, labelled by transport
+pub fn lagged_events_dropped_counter() -> &'static CounterVec {
+    &LAGGED_EVENTS_DROPPED
+}
+
+/// Capacity of a connection's delivery channel for a project's configured buffer size
+pub fn delivery_buffer_capacity(delivery_buffer_size: i32) -> usize {
+    delivery_buffer_size.max(1) as usize
+}
+
+/// Next item from a connection's delivery channel
+#[derive(Debug, Clone, PartialEq)]
+pub enum Delivery<T> {
+    Message(T),
+    /// The consumer fell behind and this many messages were overwritten
+    Lagged(u64),
+    Closed,
+}
+
+/// Receive the next delivery for a connection, surfacing overflow instead of
+/// silently skipping the overwritten messages
+pub async fn next_delivery<T: Clone>(
+    receiver: &mut broadcast::Receiver<T>,
+    transport: &str,
+    connection_id: &str,
+) -> Delivery<T> {
+    match receiver.recv().await {
+        Ok(message) => Delivery::Message(message),
+        Err(RecvError::Lagged(dropped)) => {
+            warn!(
+                "{} connection {} lagged and dropped {} events",
+                transport, connection_id, dropped
+            );
+            LAGGED_EVENTS_DROPPED
+                .with_label_values(&[transport])
+                .inc_by(dropped as f64);
+            Delivery::Lagged(dropped)
+        }
+        Err(RecvError::Closed) => Delivery::Closed,
+    }
+}
+
+/// Queue a message on a connection's delivery channel only while it has room,
+/// so it can't overwrite messages the client hasn't read yet. A full channel
+/// is a transient failure; one without receivers means the client has gone.
+pub fn send_if_room<T>(
+    sender: &broadcast::Sender<T>,
+    capacity: usize,
+    message: T,
+) -> Result<(), SendError> {
+    if sender.receiver_count() == 0 {
+        return Err(SendError::Disconnected);
+    }
+    if sender.len() >= capacity {
+        return Err(SendError::Transient(format!(
+            "delivery buffer of {} messages is full",
+            capacity
+        )));
+    }
+    sender
+        .send(message)
+        .map(|_| ())
+        .map_err(|_| SendError::Disconnected)
+}
+
+/// Feed an acked connection's channel from its own consumer. An event is
+/// acked once it's queued for the client, and redelivered if the channel is
+/// full. The task ends once the connection's receiver is dropped.
+pub fn spawn_acked_delivery<T, M>(
+    event_service: EventService,
+    config: SubscriptionConfig,
+    sender: broadcast::Sender<T>,
+    capacity: usize,
+    to_message: M,
+) -> JoinHandle<()>
+where
+    T: Send + 'static,
+    M: Fn(&Event) -> T + Send + 'static,
+{
+    tokio::spawn(async move {
+        let delivered = event_service
+            .deliver_subscription(&config, |event| {
+                let sent = send_if_room(&sender, capacity, to_message(&event));
+                async move { sent }
+            })
+            .await;
+        if let Err(e) = delivered {
+            warn!(
+                "Acked delivery for subscription {} stopped: {}",
+                config.consumer_name, e
+            );
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_overflow_is_reported_before_remaining_messages() {
+        let (sender, mut receiver) = broadcast::channel(4);
+        for i in 0..10 {
+            sender.send(i).unwrap();
+        }
+
+        let before = lagged_events_dropped_counter()
+            .with_label_values(&["test"])
+            .get();
+        assert_eq!(next_delivery(&mut receiver, "test", "c").await, Delivery::Lagged(6));
+        assert_eq!(
+            lagged_events_dropped_counter().with_label_values(&["test"]).get() - before,
+            6.0
+        );
+
+        // The newest messages are still delivered after the notification
+        for i in 6..10 {
+            assert_eq!(next_delivery(&mut receiver, "test", "c").await, Delivery::Message(i));
+        }
+
+        drop(sender);
+        assert_eq!(next_delivery(&mut receiver, "test", "c").await, Delivery::Closed);
+    }
+
+    #[test]
+    fn test_send_if_room_never_overwrites() {
+        let (sender, mut receiver) = broadcast::channel(2);
+        assert_eq!(send_if_room(&sender, 2, 1), Ok(()));
+        assert_eq!(send_if_room(&sender, 2, 2), Ok(()));
+        assert!(matches!(
+            send_if_room(&sender, 2, 3),
+            Err(SendError::Transient(_))
+        ));
+
+        // Reading makes room again
+        assert_eq!(receiver.try_recv(), Ok(1));
+        assert_eq!(send_if_room(&sender, 2, 3), Ok(()));
+
+        // A client that has gone is reported even while its buffer is full
+        drop(receiver);
+        assert_eq!(send_if_room(&sender, 2, 4), Err(SendError::Disconnected));
+    }
+}
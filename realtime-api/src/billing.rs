@@ -146,11 +146,12 @@ impl BillingService {
         sqlx::query(
             r#"
             UPDATE tenants
-            SET status = 'suspended', updated_at = NOW()
+            SET status = 'suspended', suspension_reason = $2, updated_at = NOW()
             WHERE id = $1
             "#,
         )
         .bind(tenant_id)
+        .bind(crate::usage_cycle::USAGE_LIMIT_SUSPENSION_REASON)
         .execute(&self.db)
         .await?;
 
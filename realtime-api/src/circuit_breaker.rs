@@ -0,0 +1,385 @@
+use anyhow::Result;
+use axum::response::{IntoResponse, Response};
+use prometheus::{IntGaugeVec, Opts};
+use serde_json::json;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::alerting::AlertingService;
+use crate::api_error::ApiError;
+use crate::config::CircuitBreakerConfig;
+use crate::rate_limit::overloaded_response;
+
+/// Consecutive failures that open a breaker when none are configured
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+
+/// How long an open breaker fails fast before letting a probe through when
+/// none is configured, in seconds
+pub const DEFAULT_OPEN_SECS: u64 = 30;
+
+/// Dependency label of the breaker around Postgres
+pub const DATABASE_DEPENDENCY: &str = "database";
+
+/// Dependency label of the breaker around NATS publishes
+pub const NATS_DEPENDENCY: &str = "nats";
+
+// Callers turned away while a probe is already in flight retry after this
+const PROBE_IN_FLIGHT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+// Current state of each breaker, by dependency
+lazy_static::lazy_static! {
+    static ref CIRCUIT_BREAKER_STATE: IntGaugeVec = IntGaugeVec::new(
+        Opts::new(
+            "realtime_circuit_breaker_state",
+            "Circuit breaker state by dependency: 0 closed, 1 half-open, 2 open"
+        ),
+        &["dependency"]
+    )
+    .expect("valid metric definition");
+}
+
+/// Gauge of each circuit breaker's state, labelled by dependency
+pub fn circuit_breaker_state_gauge() -> &'static IntGaugeVec {
+    &CIRCUIT_BREAKER_STATE
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    /// Calls go through; failures are counted
+    Closed,
+    /// Calls fail fast until the open period ends
+    Open,
+    /// One probe call is let through to decide whether to close again
+    HalfOpen,
+}
+
+impl BreakerState {
+    fn gauge_value(self) -> i64 {
+        match self {
+            BreakerState::Closed => 0,
+            BreakerState::HalfOpen => 1,
+            BreakerState::Open => 2,
+        }
+    }
+}
+
+/// A call refused without being attempted because its dependency's breaker is open
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{dependency} is unavailable; circuit breaker is open")]
+pub struct CircuitOpen {
+    pub dependency: &'static str,
+    /// When the breaker will next let a probe through
+    pub retry_after: Duration,
+}
+
+impl CircuitOpen {
+    /// `retry_after` rounded up to whole seconds, at least one
+    pub fn retry_after_secs(&self) -> u64 {
+        let secs = self.retry_after.as_secs();
+        if self.retry_after.subsec_nanos() > 0 {
+            secs + 1
+        } else {
+            secs.max(1)
+        }
+    }
+}
+
+/// The open breaker behind an error, if any error it wraps is a `CircuitOpen`
+pub fn circuit_open(error: &anyhow::Error) -> Option<&CircuitOpen> {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<CircuitOpen>())
+}
+
+/// `503` with a `Retry-After` header for when the open breaker behind `error`
+/// will next probe; other errors get the generic overload response
+pub fn circuit_open_response(error: &anyhow::Error) -> Response {
+    match circuit_open(error) {
+        Some(open) => ApiError::circuit_open(open).into_response(),
+        None => overloaded_response(),
+    }
+}
+
+#[derive(Debug)]
+struct BreakerInner {
+    state: BreakerState,
+    consecutive_failures: u32,
+    opened_at: Instant,
+    probe_in_flight: bool,
+}
+
+/// Stops calling a degraded dependency after repeated failures.
+///
+/// Closed, calls go through and consecutive failures are counted; reaching
+/// the threshold opens the breaker. Open, calls fail fast with `CircuitOpen`
+/// instead of waiting on timeouts. Once the open period has passed the
+/// breaker is half-open and lets a single probe through: success closes it,
+/// failure opens it for another period.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    dependency: &'static str,
+    failure_threshold: u32,
+    open_duration: Duration,
+    inner: Mutex<BreakerInner>,
+    alerting: Option<AlertingService>,
+}
+
+impl CircuitBreaker {
+    pub fn new(dependency: &'static str, config: &CircuitBreakerConfig) -> Self {
+        CIRCUIT_BREAKER_STATE
+            .with_label_values(&[dependency])
+            .set(BreakerState::Closed.gauge_value());
+        Self {
+            dependency,
+            failure_threshold: config.failure_threshold.max(1),
+            open_duration: Duration::from_secs(config.open_secs),
+            inner: Mutex::new(BreakerInner {
+                state: BreakerState::Closed,
+                consecutive_failures: 0,
+                opened_at: Instant::now(),
+                probe_in_flight: false,
+            }),
+            alerting: None,
+        }
+    }
+
+    /// Send an alert each time the breaker opens
+    pub fn with_alerting(mut self, alerting: AlertingService) -> Self {
+        self.alerting = Some(alerting);
+        self
+    }
+
+    pub fn dependency(&self) -> &'static str {
+        self.dependency
+    }
+
+    pub fn state(&self) -> BreakerState {
+        self.inner.lock().unwrap().state
+    }
+
+    /// Run `operation` unless the breaker is open. Errors for which `trips`
+    /// returns true count as failures of the dependency; any other outcome,
+    /// including errors such as a constraint violation, shows it is
+    /// answering and counts as a success.
+    pub async fn call<T, F, Fut>(
+        &self,
+        operation: F,
+        trips: fn(&anyhow::Error) -> bool,
+    ) -> Result<T>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let probe = ProbeGuard {
+            breaker: self,
+            armed: self.admit()?,
+        };
+        let result = operation().await;
+        probe.disarm();
+
+        match &result {
+            Err(e) if trips(e) => self.record_failure(),
+            _ => self.record_success(),
+        }
+        result
+    }
+
+    // Whether a call may go ahead, and if so whether it is the half-open probe
+    fn admit(&self) -> Result<bool, CircuitOpen> {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.state {
+            BreakerState::Closed => Ok(false),
+            BreakerState::Open => {
+                let elapsed = inner.opened_at.elapsed();
+                if elapsed < self.open_duration {
+                    return Err(self.open_error(self.open_duration - elapsed));
+                }
+                inner.state = BreakerState::HalfOpen;
+                inner.probe_in_flight = true;
+                self.set_gauge(BreakerState::HalfOpen);
+                info!(dependency = self.dependency, "Circuit breaker half-open; probing");
+                Ok(true)
+            }
+            BreakerState::HalfOpen if inner.probe_in_flight => {
+                Err(self.open_error(PROBE_IN_FLIGHT_RETRY_AFTER))
+            }
+            BreakerState::HalfOpen => {
+                inner.probe_in_flight = true;
+                Ok(true)
+            }
+        }
+    }
+
+    fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures = 0;
+        inner.probe_in_flight = false;
+        if inner.state != BreakerState::Closed {
+            inner.state = BreakerState::Closed;
+            self.set_gauge(BreakerState::Closed);
+            info!(dependency = self.dependency, "Circuit breaker closed");
+        }
+    }
+
+    fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+        inner.probe_in_flight = false;
+        let opens = match inner.state {
+            BreakerState::Closed => inner.consecutive_failures >= self.failure_threshold,
+            BreakerState::HalfOpen => true,
+            // A call admitted before the breaker opened; it is already open
+            BreakerState::Open => false,
+        };
+        if !opens {
+            return;
+        }
+
+        inner.state = BreakerState::Open;
+        inner.opened_at = Instant::now();
+        let failures = inner.consecutive_failures;
+        drop(inner);
+
+        self.set_gauge(BreakerState::Open);
+        warn!(
+            dependency = self.dependency,
+            failures,
+            open_secs = self.open_duration.as_secs(),
+            "Circuit breaker opened; failing fast"
+        );
+        if let Some(alerting) = self.alerting.clone() {
+            let dependency = self.dependency;
+            let open_secs = self.open_duration.as_secs();
+            tokio::spawn(async move {
+                alerting
+                    .alert_error(
+                        "Circuit Breaker Open",
+                        &format!(
+                            "{} failed {} times in a row; requests fail fast for {}s",
+                            dependency, failures, open_secs
+                        ),
+                        json!({
+                            "dependency": dependency,
+                            "consecutive_failures": failures,
+                            "open_secs": open_secs,
+                        }),
+                    )
+                    .await;
+            });
+        }
+    }
+
+    fn open_error(&self, retry_after: Duration) -> CircuitOpen {
+        CircuitOpen {
+            dependency: self.dependency,
+            retry_after,
+        }
+    }
+
+    fn set_gauge(&self, state: BreakerState) {
+        CIRCUIT_BREAKER_STATE
+            .with_label_values(&[self.dependency])
+            .set(state.gauge_value());
+    }
+}
+
+// Frees the half-open probe slot if the probe is dropped before finishing,
+// so a cancelled request doesn't leave the breaker refusing every call
+struct ProbeGuard<'a> {
+    breaker: &'a CircuitBreaker,
+    armed: bool,
+}
+
+impl ProbeGuard<'_> {
+    fn disarm(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for ProbeGuard<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            self.breaker.inner.lock().unwrap().probe_in_flight = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(open_secs: u64) -> CircuitBreaker {
+        CircuitBreaker::new(
+            "test",
+            &CircuitBreakerConfig {
+                failure_threshold: 3,
+                open_secs,
+            },
+        )
+    }
+
+    fn always(_: &anyhow::Error) -> bool {
+        true
+    }
+
+    async fn fail(breaker: &CircuitBreaker) -> Result<()> {
+        breaker
+            .call(|| async { Err(anyhow::anyhow!("connection refused")) }, always)
+            .await
+    }
+
+    #[test]
+    fn test_retry_after_rounds_up() {
+        let open = |millis| CircuitOpen {
+            dependency: "test",
+            retry_after: Duration::from_millis(millis),
+        };
+        assert_eq!(open(0).retry_after_secs(), 1);
+        assert_eq!(open(1500).retry_after_secs(), 2);
+        assert_eq!(open(30_000).retry_after_secs(), 30);
+    }
+
+    #[tokio::test]
+    async fn test_errors_that_dont_trip_keep_breaker_closed() {
+        let breaker = breaker(30);
+        for _ in 0..5 {
+            let result: Result<()> = breaker
+                .call(|| async { Err(anyhow::anyhow!("duplicate key")) }, |_| false)
+                .await;
+            assert!(result.is_err());
+        }
+        assert_eq!(breaker.state(), BreakerState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_success_resets_failure_count() {
+        let breaker = breaker(30);
+        fail(&breaker).await.unwrap_err();
+        fail(&breaker).await.unwrap_err();
+        breaker.call(|| async { Ok(()) }, always).await.unwrap();
+        fail(&breaker).await.unwrap_err();
+        fail(&breaker).await.unwrap_err();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_probe_frees_the_slot() {
+        let breaker = breaker(0);
+        for _ in 0..3 {
+            fail(&breaker).await.unwrap_err();
+        }
+
+        // Timed out mid-probe, as when a client disconnects mid-request
+        let stalled = tokio::time::timeout(
+            Duration::from_millis(10),
+            breaker.call(std::future::pending::<Result<()>>, always),
+        )
+        .await;
+        assert!(stalled.is_err());
+
+        breaker.call(|| async { Ok(()) }, always).await.unwrap();
+        assert_eq!(breaker.state(), BreakerState::Closed);
+    }
+}
@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::models::Event;
+
+/// CloudEvents specification version emitted in envelopes
+pub const CLOUDEVENTS_SPEC_VERSION: &str = "1.0";
+
+/// Content type of a CloudEvent sent in structured mode over HTTP
+pub const CLOUDEVENTS_CONTENT_TYPE: &str = "application/cloudevents+json";
+
+/// How events are encoded when delivered to a subscriber
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryFormat {
+    /// The platform's own event JSON
+    #[default]
+    Native,
+    /// CNCF CloudEvents v1.0 JSON envelope
+    CloudEvents,
+}
+
+impl DeliveryFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryFormat::Native => "native",
+            DeliveryFormat::CloudEvents => "cloudevents",
+        }
+    }
+}
+
+impl fmt::Display for DeliveryFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DeliveryFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "native" => Ok(DeliveryFormat::Native),
+            "cloudevents" => Ok(DeliveryFormat::CloudEvents),
+            other => Err(format!(
+                "Unknown delivery format '{}', expected native or cloudevents",
+                other
+            )),
+        }
+    }
+}
+
+/// Attributes of the envelope itself, which a producer header can't replace
+const CONTEXT_ATTRIBUTES: [&str; 14] = [
+    "specversion",
+    "id",
+    "source",
+    "type",
+    "time",
+    "datacontenttype",
+    "dataschema",
+    "subject",
+    "data",
+    "data_base64",
+    "tenantid",
+    "projectid",
+    "sequence",
+    "partitionkey",
+];
+
+/// Longest extension attribute name the spec recommends
+pub const MAX_EXTENSION_NAME_LENGTH: usize = 20;
+
+/// Extension attribute name for a producer header: lowercased, keeping only
+/// the ASCII letters and digits the spec allows, so `X-Trace-Id` becomes
+/// `xtraceid`. `None` when nothing is left, it is longer than
+/// `MAX_EXTENSION_NAME_LENGTH` or it names a context attribute.
+pub fn extension_name(header: &str) -> Option<String> {
+    let name: String = header
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    let usable = !name.is_empty()
+        && name.len() <= MAX_EXTENSION_NAME_LENGTH
+        && !CONTEXT_ATTRIBUTES.contains(&name.as_str());
+    usable.then_some(name)
+}
+
+/// `source` attribute identifying the project an event was published to
+pub fn event_source(tenant_id: &str, project_id: &str) -> String {
+    format!("/tenants/{}/projects/{}", tenant_id, project_id)
+}
+
+/// An event wrapped in a CloudEvents v1.0 structured-mode JSON envelope. The
+/// topic becomes `type`, the payload `data`, and the tenant and project are
+/// carried as the `tenantid` and `projectid` extension attributes. An event's
+/// partition key becomes the `partitionkey` extension of the CloudEvents
+/// Partitioning spec, and each producer header an extension named by
+/// [`extension_name`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CloudEvent {
+    pub specversion: String,
+    pub id: String,
+    pub source: String,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// RFC 3339 publish time
+    pub time: String,
+    pub datacontenttype: String,
+    pub data: serde_json::Value,
+    pub tenantid: String,
+    pub projectid: String,
+    /// Stream sequence as a string, per the CloudEvents sequence extension
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<String>,
+    /// Key the event was ordered by, per the CloudEvents partitioning extension
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partitionkey: Option<String>,
+    /// Producer headers as extension attributes
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, String>,
+}
+
+impl CloudEvent {
+    pub fn new(
+        tenant_id: &str,
+        project_id: &str,
+        id: &str,
+        topic: &str,
+        data: &serde_json::Value,
+        time: &str,
+    ) -> Self {
+        Self {
+            specversion: CLOUDEVENTS_SPEC_VERSION.to_string(),
+            id: id.to_string(),
+            source: event_source(tenant_id, project_id),
+            event_type: topic.to_string(),
+            time: time.to_string(),
+            datacontenttype: "application/json".to_string(),
+            data: data.clone(),
+            tenantid: tenant_id.to_string(),
+            projectid: project_id.to_string(),
+            sequence: None,
+            partitionkey: None,
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_sequence(mut self, sequence: Option<u64>) -> Self {
+        self.sequence = sequence.map(|sequence| sequence.to_string());
+        self
+    }
+
+    pub fn with_partition_key(mut self, partition_key: Option<&str>) -> Self {
+        self.partitionkey = partition_key.map(str::to_string);
+        self
+    }
+
+    /// Carry producer headers as extension attributes. Headers that map to
+    /// the same name keep the value of the first in sorted order.
+    pub fn with_headers(mut self, headers: &HashMap<String, String>) -> Self {
+        let mut names: Vec<&String> = headers.keys().collect();
+        names.sort();
+        for header in names {
+            if let Some(name) = extension_name(header) {
+                self.extensions
+                    .entry(name)
+                    .or_insert_with(|| headers[header].clone());
+            }
+        }
+        self
+    }
+}
+
+impl From<&Event> for CloudEvent {
+    fn from(event: &Event) -> Self {
+        CloudEvent::new(
+            &event.tenant_id,
+            &event.project_id,
+            &event.id,
+            &event.topic,
+            &event.payload,
+            &event.published_at.to_rfc3339(),
+        )
+        .with_partition_key(event.partition_key.as_deref())
+        .with_headers(&event.headers)
+    }
+}
+
+/// A connection's delivery format, with the tenant and project a CloudEvents
+/// envelope names
+#[derive(Debug, Clone)]
+pub struct EventEncoding {
+    pub format: DeliveryFormat,
+    pub tenant_id: String,
+    pub project_id: String,
+}
+
+impl EventEncoding {
+    pub fn new(format: DeliveryFormat, tenant_id: &str, project_id: &str) -> Self {
+        Self {
+            format,
+            tenant_id: tenant_id.to_string(),
+            project_id: project_id.to_string(),
+        }
+    }
+
+    /// The envelope for an event delivery, or `None` when the connection
+    /// receives the native format. Producer headers are added with
+    /// [`CloudEvent::with_headers`].
+    pub fn cloud_event(
+        &self,
+        id: &str,
+        topic: &str,
+        payload: &serde_json::Value,
+        published_at: &str,
+        sequence: Option<u64>,
+        partition_key: Option<&str>,
+    ) -> Option<CloudEvent> {
+        match self.format {
+            DeliveryFormat::Native => None,
+            DeliveryFormat::CloudEvents => Some(
+                CloudEvent::new(
+                    &self.tenant_id,
+                    &self.project_id,
+                    id,
+                    topic,
+                    payload,
+                    published_at,
+                )
+                .with_sequence(sequence)
+                .with_partition_key(partition_key),
+            ),
+        }
+    }
+}
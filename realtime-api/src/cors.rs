@@ -0,0 +1,146 @@
+use axum::http::{HeaderName, HeaderValue, Method};
+use std::time::Duration;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
+
+use crate::config::CorsConfig;
+
+/// Response headers browsers may read cross-origin, so dashboards can show rate limits
+const EXPOSED_HEADERS: [&str; 3] = ["x-ratelimit-limit", "x-ratelimit-remaining", "retry-after"];
+
+fn is_wildcard(values: &[String]) -> bool {
+    values.iter().any(|v| v == "*")
+}
+
+/// Build the CORS layer for `config`. With no allowed origins, browsers are
+/// refused cross-origin access; entries that aren't valid header values are
+/// skipped (`CorsConfig::validate` rejects them at startup).
+pub fn cors_layer(config: &CorsConfig) -> CorsLayer {
+    let origins = if is_wildcard(&config.allowed_origins) {
+        AllowOrigin::from(Any)
+    } else {
+        AllowOrigin::list(
+            config
+                .allowed_origins
+                .iter()
+                .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        )
+    };
+
+    let methods = if is_wildcard(&config.allowed_methods) {
+        AllowMethods::from(Any)
+    } else {
+        AllowMethods::list(
+            config
+                .allowed_methods
+                .iter()
+                .filter_map(|method| Method::from_bytes(method.to_uppercase().as_bytes()).ok()),
+        )
+    };
+
+    let headers = if is_wildcard(&config.allowed_headers) {
+        AllowHeaders::from(Any)
+    } else {
+        AllowHeaders::list(
+            config
+                .allowed_headers
+                .iter()
+                .filter_map(|header| HeaderName::from_bytes(header.as_bytes()).ok()),
+        )
+    };
+
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .allow_credentials(config.allow_credentials)
+        .expose_headers(EXPOSED_HEADERS.map(HeaderName::from_static))
+        .max_age(Duration::from_secs(config.max_age_secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use axum::routing::get;
+    use axum::Router;
+    use tower::Service;
+
+    fn app(config: &CorsConfig) -> Router {
+        Router::new()
+            .route("/events", get(|| async { "ok" }))
+            .layer(cors_layer(config))
+    }
+
+    fn preflight(origin: &str) -> Request<Body> {
+        Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/events")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "authorization")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    fn allowlist() -> CorsConfig {
+        CorsConfig {
+            allowed_origins: vec!["https://dashboard.example.com".to_string()],
+            allow_credentials: true,
+            ..CorsConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_allowed_origin_passes_preflight() {
+        let response = app(&allowlist())
+            .call(preflight("https://dashboard.example.com"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers();
+        assert_eq!(
+            headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+            "https://dashboard.example.com"
+        );
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert_eq!(headers[header::ACCESS_CONTROL_MAX_AGE], "600");
+        assert!(headers[header::ACCESS_CONTROL_ALLOW_METHODS]
+            .to_str()
+            .unwrap()
+            .contains("POST"));
+    }
+
+    #[tokio::test]
+    async fn test_disallowed_origin_gets_no_cors_headers() {
+        let response = app(&allowlist())
+            .call(preflight("https://evil.example.com"))
+            .await
+            .unwrap();
+        assert!(response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+
+        // The default policy allows no origins at all
+        let response = app(&CorsConfig::default())
+            .call(preflight("https://dashboard.example.com"))
+            .await
+            .unwrap();
+        assert!(response.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_simple_request_exposes_rate_limit_headers() {
+        let request = Request::builder()
+            .uri("/events")
+            .header(header::ORIGIN, "https://dashboard.example.com")
+            .body(Body::empty())
+            .unwrap();
+        let response = app(&allowlist()).call(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::ACCESS_CONTROL_EXPOSE_HEADERS]
+            .to_str()
+            .unwrap()
+            .contains("x-ratelimit-remaining"));
+    }
+}
@@ -1,30 +1,74 @@
 use anyhow::Result;
-use sqlx::{PgPool, Row};
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, TryStreamExt};
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{PgExecutor, PgPool, Postgres, QueryBuilder, Row};
+use std::sync::Arc;
 use std::time::Duration;
 use tracing::{info, warn};
 
+use crate::circuit_breaker::{CircuitBreaker, DATABASE_DEPENDENCY};
+use crate::config::{CircuitBreakerConfig, DatabaseConfig};
+use crate::db_retry::{is_unavailable, DbRetryPolicy};
 use crate::models::*;
+use crate::request_signing::{SigningSecretCipher, SEALED_SECRET_PREFIX};
+
+/// zstd level for compressed event payloads; favours speed on the publish path
+const PAYLOAD_ZSTD_LEVEL: i32 = 3;
+// Attempts to claim the next topic schema version before giving up
+const TOPIC_SCHEMA_VERSION_ATTEMPTS: u32 = 5;
 
 /// Database connection pool and operations
 #[derive(Debug, Clone)]
 pub struct Database {
     pool: PgPool,
+    /// Applied to idempotent reads that fail with a transient error
+    retry: DbRetryPolicy,
+    /// Event payloads whose JSON is longer than this are stored compressed; 0 never compresses
+    payload_compression_threshold: usize,
+    /// Fails the queries on the publish and authentication paths fast while
+    /// the database is unreachable
+    breaker: Arc<CircuitBreaker>,
 }
 
 impl Database {
-    /// Create a new database connection with the given URL
-    pub async fn new(database_url: &str) -> Result<Self> {
-        let pool = sqlx::postgres::PgPoolOptions::new()
-            .max_connections(20)
-            .min_connections(5)
-            .acquire_timeout(Duration::from_secs(30))
-            .idle_timeout(Duration::from_secs(600))
-            .max_lifetime(Duration::from_secs(1800))
-            .connect(database_url)
-            .await?;
+    /// Create a connection pool sized and timed by `config`
+    pub async fn new(config: &DatabaseConfig) -> Result<Self> {
+        let pool = Self::pool_options(config).connect(&config.url).await?;
+
+        info!(
+            max_connections = config.max_connections,
+            "Database connection pool established"
+        );
+        Ok(Self {
+            pool,
+            retry: DbRetryPolicy::from(config),
+            payload_compression_threshold: config.payload_compression_threshold_bytes,
+            breaker: Arc::new(CircuitBreaker::new(
+                DATABASE_DEPENDENCY,
+                &CircuitBreakerConfig::default(),
+            )),
+        })
+    }
+
+    /// Guard hot queries with `breaker` instead of one with default settings
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = Arc::new(breaker);
+        self
+    }
 
-        info!("Database connection pool established");
-        Ok(Self { pool })
+    /// The breaker guarding hot queries
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+
+    fn pool_options(config: &DatabaseConfig) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .min_connections(config.min_connections())
+            .acquire_timeout(Duration::from_secs(config.acquire_timeout_secs))
+            .idle_timeout(Duration::from_secs(config.idle_timeout_secs))
+            .max_lifetime(Duration::from_secs(config.max_lifetime_secs))
     }
 
     /// Get a reference to the connection pool
@@ -32,6 +76,12 @@ impl Database {
         &self.pool
     }
 
+    /// Check the database is reachable with a trivial query
+    pub async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
     /// Run database migrations
     pub async fn migrate(&self) -> Result<()> {
         sqlx::migrate!("./migrations").run(&self.pool).await?;
@@ -49,6 +99,13 @@ impl Database {
 
     // Tenant CRUD operations
     pub async fn create_tenant(&self, tenant: &Tenant) -> Result<()> {
+        Self::insert_tenant(&self.pool, tenant).await?;
+
+        info!("Created tenant: {}", tenant.id);
+        Ok(())
+    }
+
+    async fn insert_tenant<'e, E: PgExecutor<'e>>(executor: E, tenant: &Tenant) -> Result<()> {
         let status_str = match &tenant.status {
             TenantStatus::Active => "active",
             TenantStatus::Trial => "trial",
@@ -69,44 +126,85 @@ impl Database {
         .bind(&tenant.stripe_customer_id)
         .bind(tenant.created_at)
         .bind(tenant.updated_at)
-        .execute(&self.pool)
+        .execute(executor)
         .await?;
 
-        info!("Created tenant: {}", tenant.id);
         Ok(())
     }
 
     pub async fn get_tenant(&self, tenant_id: &str) -> Result<Option<Tenant>> {
+        let row = self
+            .breaker
+            .call(
+                || async {
+                    Ok(self
+                        .retry
+                        .run(|| {
+                            sqlx::query(
+                                "SELECT id, name, plan, status, stripe_customer_id, created_at, updated_at FROM tenants WHERE id = $1"
+                            )
+                            .bind(tenant_id)
+                            .fetch_optional(&self.pool)
+                        })
+                        .await?)
+                },
+                is_unavailable,
+            )
+            .await?;
+
+        row.as_ref().map(Self::tenant_from_row).transpose()
+    }
+
+    /// Get the tenant billed under a Stripe customer id
+    pub async fn get_tenant_by_stripe_customer(
+        &self,
+        stripe_customer_id: &str,
+    ) -> Result<Option<Tenant>> {
         let row = sqlx::query(
-            "SELECT id, name, plan, status, stripe_customer_id, created_at, updated_at FROM tenants WHERE id = $1"
+            "SELECT id, name, plan, status, stripe_customer_id, created_at, updated_at FROM tenants WHERE stripe_customer_id = $1"
         )
-        .bind(tenant_id)
+        .bind(stripe_customer_id)
         .fetch_optional(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            let plan: BillingPlan = serde_json::from_value(row.get("plan"))?;
-            let status_str: String = row.get("status");
-            let status = match status_str.as_str() {
-                "active" => TenantStatus::Active,
-                "trial" => TenantStatus::Trial,
-                "past_due" => TenantStatus::PastDue,
-                "suspended" => TenantStatus::Suspended,
-                _ => TenantStatus::Trial,
-            };
+        row.as_ref().map(Self::tenant_from_row).transpose()
+    }
 
-            Ok(Some(Tenant {
-                id: row.get("id"),
-                name: row.get("name"),
-                plan,
-                status,
-                stripe_customer_id: row.get("stripe_customer_id"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            }))
-        } else {
-            Ok(None)
+    fn tenant_from_row(row: &sqlx::postgres::PgRow) -> Result<Tenant> {
+        let plan: BillingPlan = serde_json::from_value(row.get("plan"))?;
+        let status_str: String = row.get("status");
+        let status = match status_str.as_str() {
+            "active" => TenantStatus::Active,
+            "trial" => TenantStatus::Trial,
+            "past_due" => TenantStatus::PastDue,
+            "suspended" => TenantStatus::Suspended,
+            _ => TenantStatus::Trial,
+        };
+
+        Ok(Tenant {
+            id: row.get("id"),
+            name: row.get("name"),
+            plan,
+            status,
+            stripe_customer_id: row.get("stripe_customer_id"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        })
+    }
+
+    /// Change a tenant's billing plan. Returns false when there is no such tenant.
+    pub async fn update_tenant_plan(&self, tenant_id: &str, plan: &BillingPlan) -> Result<bool> {
+        let result = sqlx::query("UPDATE tenants SET plan = $1, updated_at = NOW() WHERE id = $2")
+            .bind(serde_json::to_value(plan)?)
+            .bind(tenant_id)
+            .execute(&self.pool)
+            .await?;
+
+        let updated = result.rows_affected() > 0;
+        if updated {
+            info!("Updated tenant {} plan to {:?}", tenant_id, plan);
         }
+        Ok(updated)
     }
 
     pub async fn update_tenant_status(&self, tenant_id: &str, status: TenantStatus) -> Result<()> {
@@ -137,131 +235,227 @@ impl Database {
 
     // Project CRUD operations
     pub async fn create_project(&self, project: &Project) -> Result<()> {
+        Self::insert_project(&self.pool, project).await?;
+
+        info!(
+            "Created project: {} for tenant: {}",
+            project.id, project.tenant_id
+        );
+        Ok(())
+    }
+
+    async fn insert_project<'e, E: PgExecutor<'e>>(executor: E, project: &Project) -> Result<()> {
         sqlx::query(
             r#"
-            INSERT INTO projects (id, tenant_id, name, limits, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            INSERT INTO projects (id, tenant_id, name, limits, settings, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
             "#,
         )
         .bind(&project.id)
         .bind(&project.tenant_id)
         .bind(&project.name)
         .bind(serde_json::to_value(&project.limits)?)
+        .bind(serde_json::to_value(&project.settings)?)
         .bind(project.created_at)
         .bind(project.updated_at)
-        .execute(&self.pool)
+        .execute(executor)
         .await?;
 
-        info!(
-            "Created project: {} for tenant: {}",
-            project.id, project.tenant_id
-        );
         Ok(())
     }
 
-    pub async fn get_project(&self, project_id: &str) -> Result<Option<Project>> {
-        let row = sqlx::query(
-            "SELECT id, tenant_id, name, limits, created_at, updated_at FROM projects WHERE id = $1"
+    pub async fn get_project_with_tenant(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+    ) -> Result<Option<Project>> {
+        let row = self
+            .retry
+            .run(|| {
+                sqlx::query(
+                    "SELECT id, tenant_id, name, limits, settings, created_at, updated_at, archived_at FROM projects WHERE id = $1 AND tenant_id = $2"
+                )
+                .bind(project_id)
+                .bind(tenant_id)
+                .fetch_optional(&self.pool)
+            })
+            .await?;
+
+        row.as_ref().map(Self::project_from_row).transpose()
+    }
+
+    /// Replace a project's limits. Returns false when the tenant has no such
+    /// project or it is archived.
+    pub async fn update_project_limits(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        limits: &ProjectLimits,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE projects SET limits = $1, updated_at = NOW() WHERE id = $2 AND tenant_id = $3 AND archived_at IS NULL",
         )
+        .bind(serde_json::to_value(limits)?)
         .bind(project_id)
-        .fetch_optional(&self.pool)
+        .bind(tenant_id)
+        .execute(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            let limits: ProjectLimits = serde_json::from_value(row.get("limits"))?;
-            Ok(Some(Project {
-                id: row.get("id"),
-                tenant_id: row.get("tenant_id"),
-                name: row.get("name"),
-                limits,
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            }))
-        } else {
-            Ok(None)
+        let updated = result.rows_affected() > 0;
+        if updated {
+            info!("Updated limits of project {} to {:?}", project_id, limits);
         }
+        Ok(updated)
     }
 
-    pub async fn get_project_with_tenant(
+    /// Replace a project's settings. Returns false when the tenant has no such
+    /// project or it is archived.
+    pub async fn update_project_settings(
         &self,
         tenant_id: &str,
         project_id: &str,
-    ) -> Result<Option<Project>> {
-        let row = sqlx::query(
-            "SELECT id, tenant_id, name, limits, created_at, updated_at FROM projects WHERE id = $1 AND tenant_id = $2"
+        settings: &ProjectSettings,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE projects SET settings = $1, updated_at = NOW() WHERE id = $2 AND tenant_id = $3 AND archived_at IS NULL",
         )
+        .bind(serde_json::to_value(settings)?)
         .bind(project_id)
         .bind(tenant_id)
-        .fetch_optional(&self.pool)
+        .execute(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            let limits: ProjectLimits = serde_json::from_value(row.get("limits"))?;
-            Ok(Some(Project {
-                id: row.get("id"),
-                tenant_id: row.get("tenant_id"),
-                name: row.get("name"),
-                limits,
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            }))
-        } else {
-            Ok(None)
+        let updated = result.rows_affected() > 0;
+        if updated {
+            info!("Updated settings of project {} to {:?}", project_id, settings);
+        }
+        Ok(updated)
+    }
+
+    /// Archive (soft delete) a project. Returns false when the tenant has no
+    /// such project or it is already archived.
+    pub async fn archive_project(&self, tenant_id: &str, project_id: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE projects SET archived_at = NOW(), updated_at = NOW() WHERE id = $1 AND tenant_id = $2 AND archived_at IS NULL",
+        )
+        .bind(project_id)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+
+        let archived = result.rows_affected() > 0;
+        if archived {
+            info!("Archived project {} of tenant {}", project_id, tenant_id);
         }
+        Ok(archived)
     }
 
     pub async fn get_projects_for_tenant(&self, tenant_id: &str) -> Result<Vec<Project>> {
-        self.list_projects_for_tenant(tenant_id).await
+        self.list_projects_for_tenant(tenant_id, false).await
     }
 
-    pub async fn list_projects_for_tenant(&self, tenant_id: &str) -> Result<Vec<Project>> {
+    /// Projects of a tenant, oldest first. Archived projects are only
+    /// returned when `include_archived` is set.
+    pub async fn list_projects_for_tenant(
+        &self,
+        tenant_id: &str,
+        include_archived: bool,
+    ) -> Result<Vec<Project>> {
         let rows = sqlx::query(
-            "SELECT id, tenant_id, name, limits, created_at, updated_at FROM projects WHERE tenant_id = $1 ORDER BY created_at"
+            "SELECT id, tenant_id, name, limits, settings, created_at, updated_at, archived_at FROM projects WHERE tenant_id = $1 AND ($2 OR archived_at IS NULL) ORDER BY created_at"
         )
         .bind(tenant_id)
+        .bind(include_archived)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut projects = Vec::new();
-        for row in rows {
-            let limits: ProjectLimits = serde_json::from_value(row.get("limits"))?;
-            projects.push(Project {
-                id: row.get("id"),
-                tenant_id: row.get("tenant_id"),
-                name: row.get("name"),
-                limits,
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            });
-        }
+        rows.iter().map(Self::project_from_row).collect()
+    }
 
-        Ok(projects)
+    /// Projects of several tenants in one query, for batched GraphQL lookups
+    pub async fn get_projects_for_tenants(&self, tenant_ids: &[String]) -> Result<Vec<Project>> {
+        let rows = sqlx::query(
+            "SELECT id, tenant_id, name, limits, settings, created_at, updated_at, archived_at FROM projects WHERE tenant_id = ANY($1) AND archived_at IS NULL ORDER BY created_at"
+        )
+        .bind(tenant_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::project_from_row).collect()
+    }
+
+    fn project_from_row(row: &sqlx::postgres::PgRow) -> Result<Project> {
+        let limits: ProjectLimits = serde_json::from_value(row.get("limits"))?;
+        let settings: ProjectSettings = serde_json::from_value(row.get("settings"))?;
+        Ok(Project {
+            id: row.get("id"),
+            tenant_id: row.get("tenant_id"),
+            name: row.get("name"),
+            limits,
+            settings,
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+            archived_at: row.get("archived_at"),
+        })
     }
 
     // API Key CRUD operations
     pub async fn create_api_key(&self, api_key: &ApiKey) -> Result<()> {
+        Self::insert_api_key(&self.pool, api_key).await?;
+
+        info!(
+            "Created API key: {} for tenant: {}",
+            api_key.id, api_key.tenant_id
+        );
+        Ok(())
+    }
+
+    async fn insert_api_key<'e, E: PgExecutor<'e>>(executor: E, api_key: &ApiKey) -> Result<()> {
         sqlx::query(
             r#"
-            INSERT INTO api_keys (id, tenant_id, project_id, key_hash, scopes, rate_limit_per_sec, is_active, expires_at, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            INSERT INTO api_keys (id, tenant_id, project_id, key_hash, key_last4, secret_hash, scopes, rate_limit_per_sec, allowed_topics, is_active, expires_at, signing_secret, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
             "#
         )
         .bind(&api_key.id)
         .bind(&api_key.tenant_id)
         .bind(&api_key.project_id)
         .bind(&api_key.key_hash)
+        .bind(&api_key.key_last4)
+        .bind(&api_key.secret_hash)
         .bind(serde_json::to_value(&api_key.scopes)?)
         .bind(api_key.rate_limit_per_sec)
+        .bind(serde_json::to_value(&api_key.allowed_topics)?)
         .bind(api_key.is_active)
         .bind(api_key.expires_at)
+        .bind(&api_key.signing_secret)
         .bind(api_key.created_at)
         .bind(api_key.updated_at)
-        .execute(&self.pool)
+        .execute(executor)
         .await?;
 
+        Ok(())
+    }
+
+    /// Create a tenant, its first project and an initial API key in one transaction.
+    /// Nothing is persisted if any insert fails.
+    pub async fn onboard_tenant(
+        &self,
+        tenant: &Tenant,
+        project: &Project,
+        api_key: &ApiKey,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        Self::insert_tenant(&mut *tx, tenant).await?;
+        Self::insert_project(&mut *tx, project).await?;
+        Self::insert_api_key(&mut *tx, api_key).await?;
+
+        tx.commit().await?;
+
         info!(
-            "Created API key: {} for tenant: {}",
-            api_key.id, api_key.tenant_id
+            "Onboarded tenant: {} with project: {} and API key: {}",
+            tenant.id, project.id, api_key.id
         );
         Ok(())
     }
@@ -269,7 +463,7 @@ impl Database {
     pub async fn get_api_key_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>> {
         let row = sqlx::query(
             r#"
-            SELECT id, tenant_id, project_id, key_hash, scopes, rate_limit_per_sec, is_active, expires_at, created_at, updated_at
+            SELECT id, tenant_id, project_id, key_hash, key_last4, secret_hash, scopes, rate_limit_per_sec, allowed_topics, is_active, expires_at, last_used_at, host(last_used_ip) AS last_used_ip, signing_secret, previous_signing_secret, previous_key_hash, previous_secret_hash, previous_expires_at, created_at, updated_at
             FROM api_keys 
             WHERE key_hash = $1 AND is_active = true
             "#
@@ -278,133 +472,1031 @@ impl Database {
         .fetch_optional(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            let scopes: Vec<Scope> = serde_json::from_value(row.get("scopes"))?;
-            Ok(Some(ApiKey {
-                id: row.get("id"),
-                tenant_id: row.get("tenant_id"),
-                project_id: row.get("project_id"),
-                key_hash: row.get("key_hash"),
-                scopes,
-                rate_limit_per_sec: row.get("rate_limit_per_sec"),
-                is_active: row.get("is_active"),
-                expires_at: row.get("expires_at"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            }))
-        } else {
-            Ok(None)
-        }
+        row.as_ref().map(Self::api_key_from_row).transpose()
+    }
+
+    /// Find an active API key by its lookup prefix or, for keys not yet
+    /// re-hashed with Argon2id, by the full legacy SHA-256 digest. A rotated
+    /// key is also found by its previous secret until that secret expires.
+    /// Keys of archived projects are never returned, so they stop authenticating.
+    pub async fn get_api_key_by_lookup(
+        &self,
+        lookup_hash: &str,
+        legacy_hash: &str,
+    ) -> Result<Option<ApiKey>> {
+        let query = sqlx::query(
+            r#"
+            SELECT id, tenant_id, project_id, key_hash, key_last4, secret_hash, scopes, rate_limit_per_sec, allowed_topics, is_active, expires_at, last_used_at, host(last_used_ip) AS last_used_ip, signing_secret, previous_signing_secret, previous_key_hash, previous_secret_hash, previous_expires_at, created_at, updated_at
+            FROM api_keys 
+            WHERE (key_hash IN ($1, $2)
+                   OR (previous_key_hash IN ($1, $2) AND previous_expires_at > NOW()))
+              AND is_active = true
+              AND NOT EXISTS (
+                  SELECT 1 FROM projects p
+                  WHERE p.id = api_keys.project_id AND p.archived_at IS NOT NULL
+              )
+            "#
+        )
+        .bind(lookup_hash)
+        .bind(legacy_hash);
+        let row = self
+            .breaker
+            .call(
+                || async { Ok(query.fetch_optional(&self.pool).await?) },
+                is_unavailable,
+            )
+            .await?;
+
+        row.as_ref().map(Self::api_key_from_row).transpose()
+    }
+
+    /// Find an active API key by id for verifying a signed request. Keys of
+    /// archived projects are never returned, as with `get_api_key_by_lookup`.
+    pub async fn get_signing_api_key(&self, key_id: &str) -> Result<Option<ApiKey>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, tenant_id, project_id, key_hash, key_last4, secret_hash, scopes, rate_limit_per_sec, allowed_topics, is_active, expires_at, last_used_at, host(last_used_ip) AS last_used_ip, signing_secret, previous_signing_secret, previous_key_hash, previous_secret_hash, previous_expires_at, created_at, updated_at
+            FROM api_keys
+            WHERE id = $1 AND is_active = true AND signing_secret IS NOT NULL
+              AND NOT EXISTS (
+                  SELECT 1 FROM projects p
+                  WHERE p.id = api_keys.project_id AND p.archived_at IS NOT NULL
+              )
+            "#
+        )
+        .bind(key_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(Self::api_key_from_row).transpose()
+    }
+
+    /// Replace a legacy SHA-256 key hash with a lookup prefix and Argon2id hash
+    pub async fn upgrade_api_key_hash(
+        &self,
+        key_id: &str,
+        lookup_hash: &str,
+        secret_hash: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE api_keys SET key_hash = $2, secret_hash = $3, updated_at = NOW() WHERE id = $1 AND secret_hash IS NULL"
+        )
+        .bind(key_id)
+        .bind(lookup_hash)
+        .bind(secret_hash)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Give an active API key a new secret and signing secret, keeping its
+    /// current ones as the previous secrets until `previous_expires_at`.
+    /// Any previous secrets still in their grace period are overwritten.
+    /// Returns None if the tenant has no such active key.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn rotate_api_key(
+        &self,
+        tenant_id: &str,
+        key_id: &str,
+        lookup_hash: &str,
+        secret_hash: &str,
+        key_last4: &str,
+        signing_secret: &str,
+        previous_expires_at: DateTime<Utc>,
+    ) -> Result<Option<ApiKey>> {
+        let row = sqlx::query(
+            r#"
+            UPDATE api_keys
+            SET previous_key_hash = key_hash,
+                previous_secret_hash = secret_hash,
+                previous_signing_secret = signing_secret,
+                previous_expires_at = $3,
+                key_hash = $4,
+                secret_hash = $5,
+                key_last4 = $6,
+                signing_secret = $7,
+                updated_at = NOW()
+            WHERE id = $1 AND tenant_id = $2 AND is_active = true
+            RETURNING id, tenant_id, project_id, key_hash, key_last4, secret_hash, scopes, rate_limit_per_sec, allowed_topics, is_active, expires_at, last_used_at, host(last_used_ip) AS last_used_ip, signing_secret, previous_signing_secret, previous_key_hash, previous_secret_hash, previous_expires_at, created_at, updated_at
+            "#
+        )
+        .bind(key_id)
+        .bind(tenant_id)
+        .bind(previous_expires_at)
+        .bind(lookup_hash)
+        .bind(secret_hash)
+        .bind(key_last4)
+        .bind(signing_secret)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(Self::api_key_from_row).transpose()
+    }
+
+    /// Forget previous secrets of rotated API keys whose grace period has
+    /// ended and return how many were cleared
+    pub async fn clear_expired_api_key_secrets(&self) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE api_keys
+            SET previous_key_hash = NULL, previous_secret_hash = NULL, previous_signing_secret = NULL, previous_expires_at = NULL, updated_at = NOW()
+            WHERE previous_expires_at <= NOW()
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    fn api_key_from_row(row: &sqlx::postgres::PgRow) -> Result<ApiKey> {
+        let scopes: Vec<Scope> = serde_json::from_value(row.get("scopes"))?;
+        let allowed_topics: Vec<String> = serde_json::from_value(row.get("allowed_topics"))?;
+        Ok(ApiKey {
+            id: row.get("id"),
+            tenant_id: row.get("tenant_id"),
+            project_id: row.get("project_id"),
+            key_hash: row.get("key_hash"),
+            key_last4: row.get("key_last4"),
+            secret_hash: row.get("secret_hash"),
+            scopes,
+            rate_limit_per_sec: row.get("rate_limit_per_sec"),
+            allowed_topics,
+            is_active: row.get("is_active"),
+            expires_at: row.get("expires_at"),
+            last_used_at: row.get("last_used_at"),
+            last_used_ip: row.get("last_used_ip"),
+            signing_secret: row.get("signing_secret"),
+            previous_signing_secret: row.get("previous_signing_secret"),
+            previous_key_hash: row.get("previous_key_hash"),
+            previous_secret_hash: row.get("previous_secret_hash"),
+            previous_expires_at: row.get("previous_expires_at"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        })
+    }
+
+    pub async fn create_client_cert(&self, client_cert: &ClientCert) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO client_certs (id, tenant_id, project_id, spki_sha256, subject, issuer, scopes, rate_limit_per_sec, allowed_topics, is_active, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            "#
+        )
+        .bind(&client_cert.id)
+        .bind(&client_cert.tenant_id)
+        .bind(&client_cert.project_id)
+        .bind(&client_cert.spki_sha256)
+        .bind(&client_cert.subject)
+        .bind(&client_cert.issuer)
+        .bind(serde_json::to_value(&client_cert.scopes)?)
+        .bind(client_cert.rate_limit_per_sec)
+        .bind(serde_json::to_value(&client_cert.allowed_topics)?)
+        .bind(client_cert.is_active)
+        .bind(client_cert.created_at)
+        .bind(client_cert.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        info!(
+            "Registered client certificate: {} for tenant: {}",
+            client_cert.id, client_cert.tenant_id
+        );
+        Ok(())
+    }
+
+    /// Find the active client certificate registered for a presented
+    /// certificate's SPKI fingerprint, or else for its subject from the same
+    /// issuer. As with API keys, certificates of archived projects are never
+    /// returned.
+    pub async fn get_client_cert(
+        &self,
+        spki_sha256: &str,
+        subject: &str,
+        issuer: &str,
+    ) -> Result<Option<ClientCert>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, tenant_id, project_id, spki_sha256, subject, issuer, scopes, rate_limit_per_sec, allowed_topics, is_active, created_at, updated_at
+            FROM client_certs
+            WHERE is_active = true
+              AND (spki_sha256 = $1 OR (spki_sha256 IS NULL AND subject = $2 AND issuer = $3))
+              AND NOT EXISTS (
+                  SELECT 1 FROM projects p
+                  WHERE p.id = client_certs.project_id AND p.archived_at IS NOT NULL
+              )
+            ORDER BY spki_sha256 IS NULL
+            LIMIT 1
+            "#
+        )
+        .bind(spki_sha256)
+        .bind(subject)
+        .bind(issuer)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            Ok(ClientCert {
+                id: row.get("id"),
+                tenant_id: row.get("tenant_id"),
+                project_id: row.get("project_id"),
+                spki_sha256: row.get("spki_sha256"),
+                subject: row.get("subject"),
+                issuer: row.get("issuer"),
+                scopes: serde_json::from_value(row.get("scopes"))?,
+                rate_limit_per_sec: row.get("rate_limit_per_sec"),
+                allowed_topics: serde_json::from_value(row.get("allowed_topics"))?,
+                is_active: row.get("is_active"),
+                created_at: row.get("created_at"),
+                updated_at: row.get("updated_at"),
+            })
+        })
+        .transpose()
+    }
+
+    /// Record a use of an API key unless one was recorded within `min_interval`.
+    /// Returns whether the row was updated.
+    pub async fn touch_api_key(
+        &self,
+        key_id: &str,
+        client_ip: Option<&str>,
+        min_interval: std::time::Duration,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE api_keys SET last_used_at = NOW(), last_used_ip = $2::inet
+            WHERE id = $1 AND (last_used_at IS NULL OR last_used_at <= NOW() - make_interval(secs => $3))
+            "#
+        )
+        .bind(key_id)
+        .bind(client_ip)
+        .bind(min_interval.as_secs_f64())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn revoke_api_key(&self, tenant_id: &str, key_id: &str) -> Result<()> {
+        let result = sqlx::query(
+            "UPDATE api_keys SET is_active = false, updated_at = NOW() WHERE id = $1 AND tenant_id = $2"
+        )
+        .bind(key_id)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+
+        let rows_affected = result.rows_affected();
+
+        if rows_affected == 0 {
+            warn!(
+                "No API key found with id: {} for tenant: {}",
+                key_id, tenant_id
+            );
+        } else {
+            info!("Revoked API key: {} for tenant: {}", key_id, tenant_id);
+        }
+
+        Ok(())
+    }
+
+    // Event operations
+    pub async fn create_event(&self, event: &Event) -> Result<()> {
+        // Large payloads go into payload_zstd instead of the jsonb column
+        let compressed = Self::compress_payload(&event.payload, self.payload_compression_threshold)?;
+        let payload = compressed.is_none().then_some(&event.payload);
+
+        let query = sqlx::query(
+            r#"
+            INSERT INTO events (id, tenant_id, project_id, topic, payload, payload_compressed, payload_zstd, published_at, partition_key, headers, metadata, attributes, schema_valid, schema_version)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            "#,
+        )
+        .bind(&event.id)
+        .bind(&event.tenant_id)
+        .bind(&event.project_id)
+        .bind(&event.topic)
+        .bind(payload)
+        .bind(compressed.is_some())
+        .bind(&compressed)
+        .bind(event.published_at)
+        .bind(&event.partition_key)
+        .bind(sqlx::types::Json(&event.headers))
+        .bind(sqlx::types::Json(&event.metadata))
+        .bind(sqlx::types::Json(&event.attributes))
+        .bind(event.schema_valid)
+        .bind(event.schema_version);
+        self.breaker
+            .call(
+                || async {
+                    query.execute(&self.pool).await?;
+                    Ok(())
+                },
+                is_unavailable,
+            )
+            .await
+    }
+
+    /// The payload's JSON zstd-compressed, if it is longer than `threshold`
+    /// bytes; None when it should be stored as plain jsonb
+    fn compress_payload(payload: &serde_json::Value, threshold: usize) -> Result<Option<Vec<u8>>> {
+        if threshold == 0 {
+            return Ok(None);
+        }
+        let json = serde_json::to_vec(payload)?;
+        if json.len() <= threshold {
+            return Ok(None);
+        }
+        Ok(Some(zstd::encode_all(json.as_slice(), PAYLOAD_ZSTD_LEVEL)?))
+    }
+
+    /// Inverse of [`Database::compress_payload`]
+    fn decompress_payload(compressed: &[u8]) -> Result<serde_json::Value> {
+        let json = zstd::decode_all(compressed)?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+
+    /// Get one of a tenant's events by id; events of other tenants are never returned
+    pub async fn get_event(&self, tenant_id: &str, event_id: &str) -> Result<Option<Event>> {
+        let row = sqlx::query(
+            "SELECT id, tenant_id, project_id, topic, payload, payload_compressed, payload_zstd, published_at, partition_key, headers, metadata, attributes, schema_valid, schema_version FROM events WHERE tenant_id = $1 AND id = $2"
+        )
+        .bind(tenant_id)
+        .bind(event_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(Self::event_from_row).transpose()
+    }
+
+    pub async fn get_events_for_tenant(&self, tenant_id: &str, limit: i64) -> Result<Vec<Event>> {
+        let rows = self
+            .retry
+            .run(|| {
+                sqlx::query(
+                    "SELECT id, tenant_id, project_id, topic, payload, payload_compressed, payload_zstd, published_at, partition_key, headers, metadata, attributes, schema_valid, schema_version FROM events WHERE tenant_id = $1 ORDER BY published_at DESC LIMIT $2"
+                )
+                .bind(tenant_id)
+                .bind(limit)
+                .fetch_all(&self.pool)
+            })
+            .await?;
+
+        rows.iter().map(Self::event_from_row).collect()
+    }
+
+    /// Page backward in time through a tenant's events using keyset pagination
+    /// on `(published_at, id)`. `before` is the key of the last event already
+    /// seen; events are returned newest first.
+    pub async fn get_events_for_tenant_paged(
+        &self,
+        tenant_id: &str,
+        before: Option<(DateTime<Utc>, String)>,
+        limit: i64,
+    ) -> Result<EventPage> {
+        self.query_events_paged(tenant_id, &EventQuery::default(), before, limit)
+            .await
+    }
+
+    /// List a tenant's events matching an optional topic and published-at
+    /// range (`from` inclusive, `to` inclusive), newest first
+    pub async fn query_events(
+        &self,
+        tenant_id: &str,
+        topic: Option<&str>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> Result<Vec<Event>> {
+        let query = EventQuery {
+            topic: topic.map(str::to_string),
+            from,
+            to,
+        };
+        let page = self.query_events_paged(tenant_id, &query, None, limit).await?;
+        Ok(page.events)
+    }
+
+    /// Filtered variant of [`Database::get_events_for_tenant_paged`]
+    pub async fn query_events_paged(
+        &self,
+        tenant_id: &str,
+        query: &EventQuery,
+        before: Option<(DateTime<Utc>, String)>,
+        limit: i64,
+    ) -> Result<EventPage> {
+        let mut builder = Self::filtered_events_query(tenant_id, query);
+        if let Some((published_at, id)) = before {
+            builder
+                .push(" AND (published_at, id) < (")
+                .push_bind(published_at)
+                .push(", ")
+                .push_bind(id)
+                .push(")");
+        }
+        builder
+            .push(" ORDER BY published_at DESC, id DESC LIMIT ")
+            .push_bind(limit + 1);
+
+        let rows = builder.build().fetch_all(&self.pool).await?;
+        let events = rows.iter().map(Self::event_from_row).collect::<Result<_>>()?;
+        Ok(Self::event_page(events, limit))
+    }
+
+    /// Page forward in time: the `limit` matching events immediately newer than
+    /// `after`, returned newest first
+    pub async fn query_events_paged_after(
+        &self,
+        tenant_id: &str,
+        query: &EventQuery,
+        after: (DateTime<Utc>, String),
+        limit: i64,
+    ) -> Result<EventPage> {
+        let mut builder = Self::filtered_events_query(tenant_id, query);
+        builder
+            .push(" AND (published_at, id) > (")
+            .push_bind(after.0)
+            .push(", ")
+            .push_bind(after.1)
+            .push(") ORDER BY published_at ASC, id ASC LIMIT ")
+            .push_bind(limit + 1);
+
+        let rows = builder.build().fetch_all(&self.pool).await?;
+        let events = rows.iter().map(Self::event_from_row).collect::<Result<_>>()?;
+        let mut page = Self::event_page(events, limit);
+        page.events.reverse();
+        Ok(page)
+    }
+
+    // SELECT over a tenant's events with the query's filters as bound parameters
+    fn filtered_events_query<'a>(
+        tenant_id: &'a str,
+        query: &'a EventQuery,
+    ) -> QueryBuilder<'a, Postgres> {
+        let mut builder = QueryBuilder::new(
+            "SELECT id, tenant_id, project_id, topic, payload, payload_compressed, payload_zstd, published_at, partition_key, headers, metadata, attributes, schema_valid, schema_version FROM events WHERE tenant_id = ",
+        );
+        builder.push_bind(tenant_id);
+        if let Some(topic) = &query.topic {
+            builder.push(" AND topic = ").push_bind(topic);
+        }
+        if let Some(from) = query.from {
+            builder.push(" AND published_at >= ").push_bind(from);
+        }
+        if let Some(to) = query.to {
+            builder.push(" AND published_at <= ").push_bind(to);
+        }
+        builder
+    }
+
+    // Trim a `limit + 1` fetch down to a page, noting whether anything was cut
+    fn event_page(mut events: Vec<Event>, limit: i64) -> EventPage {
+        let limit = limit.max(0) as usize;
+        let has_more = events.len() > limit;
+        events.truncate(limit);
+        EventPage { events, has_more }
+    }
+
+    fn event_from_row(row: &sqlx::postgres::PgRow) -> Result<Event> {
+        let payload = if row.get::<bool, _>("payload_compressed") {
+            Self::decompress_payload(row.get::<&[u8], _>("payload_zstd"))?
+        } else {
+            row.get("payload")
+        };
+
+        Ok(Event {
+            id: row.get("id"),
+            tenant_id: row.get("tenant_id"),
+            project_id: row.get("project_id"),
+            topic: row.get("topic"),
+            payload,
+            published_at: row.get("published_at"),
+            partition_key: row.get("partition_key"),
+            headers: row
+                .get::<sqlx::types::Json<std::collections::HashMap<String, String>>, _>("headers")
+                .0,
+            metadata: row
+                .get::<sqlx::types::Json<std::collections::HashMap<String, String>>, _>("metadata")
+                .0,
+            attributes: row
+                .get::<sqlx::types::Json<std::collections::HashMap<String, String>>, _>(
+                    "attributes",
+                )
+                .0,
+            schema_valid: row.get("schema_valid"),
+            schema_version: row.get("schema_version"),
+        })
+    }
+
+    /// A project's API keys, newest first, at most `limit` of them when given
+    pub async fn get_api_keys_for_project(
+        &self,
+        project_id: &str,
+        limit: Option<i64>,
+    ) -> Result<Vec<ApiKey>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, tenant_id, project_id, key_hash, key_last4, secret_hash, scopes, rate_limit_per_sec, allowed_topics, is_active, expires_at, last_used_at, host(last_used_ip) AS last_used_ip, signing_secret, previous_signing_secret, previous_key_hash, previous_secret_hash, previous_expires_at, created_at, updated_at
+            FROM api_keys 
+            WHERE project_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#
+        )
+        .bind(project_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::api_key_from_row).collect()
+    }
+
+    /// API keys of several projects in one query, for batched GraphQL lookups
+    pub async fn get_api_keys_for_projects(&self, project_ids: &[String]) -> Result<Vec<ApiKey>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, tenant_id, project_id, key_hash, key_last4, secret_hash, scopes, rate_limit_per_sec, allowed_topics, is_active, expires_at, last_used_at, host(last_used_ip) AS last_used_ip, signing_secret, previous_signing_secret, previous_key_hash, previous_secret_hash, previous_expires_at, created_at, updated_at
+            FROM api_keys
+            WHERE project_id = ANY($1)
+            ORDER BY created_at DESC
+            "#
+        )
+        .bind(project_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::api_key_from_row).collect()
+    }
+
+    /// Store the next version of a topic's schema and return its version number.
+    /// Concurrent registrations that claim the same version collide on the
+    /// primary key; the loser retries with the version after the winner's.
+    pub async fn create_topic_schema(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        topic: &str,
+        json_schema: &serde_json::Value,
+        mode: &str,
+    ) -> Result<i32> {
+        let mut attempt = 1;
+        loop {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO topic_schemas (tenant_id, project_id, topic, version, json_schema, mode, created_at)
+                SELECT $1, $2, $3, COALESCE(MAX(version), 0) + 1, $4, $5, NOW()
+                FROM topic_schemas
+                WHERE project_id = $2 AND topic = $3
+                RETURNING version
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(project_id)
+            .bind(topic)
+            .bind(json_schema)
+            .bind(mode)
+            .fetch_one(&self.pool)
+            .await;
+
+            match result {
+                Ok(row) => return Ok(row.get("version")),
+                Err(sqlx::Error::Database(e))
+                    if e.is_unique_violation() && attempt < TOPIC_SCHEMA_VERSION_ATTEMPTS =>
+                {
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Store version 1 of a topic's schema unless the topic already has one.
+    /// Returns `None` when another writer registered it first.
+    pub async fn create_first_topic_schema(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        topic: &str,
+        json_schema: &serde_json::Value,
+        mode: &str,
+    ) -> Result<Option<i32>> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO topic_schemas (tenant_id, project_id, topic, version, json_schema, mode, created_at)
+            VALUES ($1, $2, $3, 1, $4, $5, NOW())
+            ON CONFLICT (project_id, topic, version) DO NOTHING
+            RETURNING version
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(project_id)
+        .bind(topic)
+        .bind(json_schema)
+        .bind(mode)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| row.get("version")))
+    }
+
+    /// Get the highest registered version of a topic's schema
+    pub async fn get_active_topic_schema(
+        &self,
+        project_id: &str,
+        topic: &str,
+    ) -> Result<Option<TopicSchemaRecord>> {
+        let row = sqlx::query(
+            r#"
+            SELECT tenant_id, project_id, topic, version, json_schema, mode, created_at
+            FROM topic_schemas
+            WHERE project_id = $1 AND topic = $2
+            ORDER BY version DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(project_id)
+        .bind(topic)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.as_ref().map(Self::topic_schema_from_row))
+    }
+
+    /// List the active schema of every topic in a tenant's project
+    pub async fn list_topic_schemas(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+    ) -> Result<Vec<TopicSchemaRecord>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT ON (topic) tenant_id, project_id, topic, version, json_schema, mode, created_at
+            FROM topic_schemas
+            WHERE tenant_id = $1 AND project_id = $2
+            ORDER BY topic, version DESC
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(project_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(Self::topic_schema_from_row).collect())
+    }
+
+    /// Remove every version of a topic's schema; returns whether any existed
+    pub async fn delete_topic_schema(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        topic: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            "DELETE FROM topic_schemas WHERE tenant_id = $1 AND project_id = $2 AND topic = $3",
+        )
+        .bind(tenant_id)
+        .bind(project_id)
+        .bind(topic)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Register a webhook
+    pub async fn create_webhook(&self, webhook: &Webhook) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO webhooks (id, tenant_id, project_id, url, secret, topic_filter, format, active, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(&webhook.id)
+        .bind(&webhook.tenant_id)
+        .bind(&webhook.project_id)
+        .bind(&webhook.url)
+        .bind(&webhook.secret)
+        .bind(&webhook.topic_filter)
+        .bind(webhook.format.as_str())
+        .bind(webhook.active)
+        .bind(webhook.created_at)
+        .bind(webhook.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Get a tenant's webhook by id
+    pub async fn get_webhook(&self, tenant_id: &str, webhook_id: &str) -> Result<Option<Webhook>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, tenant_id, project_id, url, secret, topic_filter, format, active, created_at, updated_at
+            FROM webhooks
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(webhook_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.as_ref().map(Self::webhook_from_row))
+    }
+
+    /// List a project's webhooks, oldest first; `active_only` skips disabled ones
+    pub async fn list_webhooks(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        active_only: bool,
+    ) -> Result<Vec<Webhook>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, tenant_id, project_id, url, secret, topic_filter, format, active, created_at, updated_at
+            FROM webhooks
+            WHERE tenant_id = $1 AND project_id = $2 AND (active OR NOT $3)
+            ORDER BY created_at, id
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(project_id)
+        .bind(active_only)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.iter().map(Self::webhook_from_row).collect())
+    }
+
+    /// Apply changes to a tenant's webhook; returns `None` when it doesn't exist
+    pub async fn update_webhook(
+        &self,
+        tenant_id: &str,
+        webhook_id: &str,
+        update: &WebhookUpdate,
+    ) -> Result<Option<Webhook>> {
+        let row = sqlx::query(
+            r#"
+            UPDATE webhooks
+            SET url = COALESCE($3, url),
+                topic_filter = COALESCE($4, topic_filter),
+                active = COALESCE($5, active),
+                format = COALESCE($6, format),
+                updated_at = NOW()
+            WHERE tenant_id = $1 AND id = $2
+            RETURNING id, tenant_id, project_id, url, secret, topic_filter, format, active, created_at, updated_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(webhook_id)
+        .bind(&update.url)
+        .bind(&update.topic_filter)
+        .bind(update.active)
+        .bind(update.format.map(|format| format.as_str()))
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.as_ref().map(Self::webhook_from_row))
+    }
+
+    /// Delete a tenant's webhook; returns whether it existed
+    pub async fn delete_webhook(&self, tenant_id: &str, webhook_id: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM webhooks WHERE tenant_id = $1 AND id = $2")
+            .bind(tenant_id)
+            .bind(webhook_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Encrypt webhook secrets still stored in plain text; returns how many
+    /// were encrypted
+    pub async fn seal_plaintext_webhook_secrets(
+        &self,
+        cipher: &SigningSecretCipher,
+    ) -> Result<u64> {
+        let rows = sqlx::query("SELECT id, secret FROM webhooks WHERE secret NOT LIKE $1")
+            .bind(format!("{}%", SEALED_SECRET_PREFIX))
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut sealed = 0;
+        for row in rows {
+            let id: String = row.get("id");
+            let secret: String = row.get("secret");
+            // Compare the secret so a concurrent sweep can't seal it twice
+            let result =
+                sqlx::query("UPDATE webhooks SET secret = $2 WHERE id = $1 AND secret = $3")
+                    .bind(&id)
+                    .bind(cipher.seal(&id, &secret))
+                    .bind(&secret)
+                    .execute(&self.pool)
+                    .await?;
+            sealed += result.rows_affected();
+        }
+
+        Ok(sealed)
+    }
+
+    fn webhook_from_row(row: &sqlx::postgres::PgRow) -> Webhook {
+        Webhook {
+            id: row.get("id"),
+            tenant_id: row.get("tenant_id"),
+            project_id: row.get("project_id"),
+            url: row.get("url"),
+            secret: row.get("secret"),
+            topic_filter: row.get("topic_filter"),
+            format: row
+                .get::<String, _>("format")
+                .parse()
+                .unwrap_or_default(),
+            active: row.get("active"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        }
+    }
+
+    /// Claim an idempotency key for an event about to be published. Returns
+    /// `None` when the key is now held by `event_id`, or the existing record when
+    /// an unexpired claim already exists. A claim whose publish hasn't completed
+    /// within `lease` of being made, e.g. because the instance holding it
+    /// crashed, no longer blocks the key.
+    pub async fn claim_idempotency_key(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        idempotency_key: &str,
+        event_id: &str,
+        ttl: Duration,
+        lease: Duration,
+    ) -> Result<Option<IdempotencyRecord>> {
+        // An expired claim, or an abandoned in-flight one, no longer blocks the key
+        sqlx::query(
+            r#"
+            DELETE FROM idempotency_keys
+            WHERE tenant_id = $1 AND project_id = $2 AND idempotency_key = $3
+              AND (expires_at <= NOW()
+                   OR (sequence IS NULL AND created_at <= NOW() - $4 * INTERVAL '1 second'))
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(project_id)
+        .bind(idempotency_key)
+        .bind(lease.as_secs_f64())
+        .execute(&self.pool)
+        .await?;
+
+        let claimed = sqlx::query(
+            r#"
+            INSERT INTO idempotency_keys (tenant_id, project_id, idempotency_key, event_id, created_at, expires_at)
+            VALUES ($1, $2, $3, $4, NOW(), NOW() + $5 * INTERVAL '1 second')
+            ON CONFLICT (tenant_id, project_id, idempotency_key) DO NOTHING
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(project_id)
+        .bind(idempotency_key)
+        .bind(event_id)
+        .bind(ttl.as_secs() as f64)
+        .execute(&self.pool)
+        .await?
+        .rows_affected()
+            > 0;
+        if claimed {
+            return Ok(None);
+        }
+
+        let row = sqlx::query(
+            r#"
+            SELECT event_id, sequence FROM idempotency_keys
+            WHERE tenant_id = $1 AND project_id = $2 AND idempotency_key = $3
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(project_id)
+        .bind(idempotency_key)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(Some(IdempotencyRecord {
+            event_id: row.get("event_id"),
+            sequence: row.get::<Option<i64>, _>("sequence").map(|sequence| sequence as u64),
+        }))
     }
 
-    pub async fn revoke_api_key(&self, tenant_id: &str, key_id: &str) -> Result<()> {
-        let result = sqlx::query(
-            "UPDATE api_keys SET is_active = false, updated_at = NOW() WHERE id = $1 AND tenant_id = $2"
+    /// Record the event and stream sequence published under a key claimed by
+    /// `claimed_event_id`. The published event differs from the claiming one
+    /// when JetStream recognised a retry of an earlier, abandoned publish.
+    pub async fn complete_idempotency_key(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        idempotency_key: &str,
+        claimed_event_id: &str,
+        published_event_id: &str,
+        sequence: u64,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE idempotency_keys SET event_id = $5, sequence = $6
+            WHERE tenant_id = $1 AND project_id = $2 AND idempotency_key = $3
+              AND event_id = $4
+            "#,
         )
-        .bind(key_id)
         .bind(tenant_id)
+        .bind(project_id)
+        .bind(idempotency_key)
+        .bind(claimed_event_id)
+        .bind(published_event_id)
+        .bind(sequence as i64)
         .execute(&self.pool)
         .await?;
 
-        let rows_affected = result.rows_affected();
-
-        if rows_affected == 0 {
-            warn!(
-                "No API key found with id: {} for tenant: {}",
-                key_id, tenant_id
-            );
-        } else {
-            info!("Revoked API key: {} for tenant: {}", key_id, tenant_id);
-        }
-
         Ok(())
     }
 
-    // Event operations
-    pub async fn create_event(&self, event: &Event) -> Result<()> {
+    /// Give up `event_id`'s claim on a key after a failed publish so the
+    /// producer can retry; a claim taken over after its lease is left alone
+    pub async fn release_idempotency_key(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        idempotency_key: &str,
+        event_id: &str,
+    ) -> Result<()> {
         sqlx::query(
             r#"
-            INSERT INTO events (id, tenant_id, project_id, topic, payload, published_at)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            DELETE FROM idempotency_keys
+            WHERE tenant_id = $1 AND project_id = $2 AND idempotency_key = $3
+              AND event_id = $4 AND sequence IS NULL
             "#,
         )
-        .bind(&event.id)
-        .bind(&event.tenant_id)
-        .bind(&event.project_id)
-        .bind(&event.topic)
-        .bind(&event.payload)
-        .bind(event.published_at)
+        .bind(tenant_id)
+        .bind(project_id)
+        .bind(idempotency_key)
+        .bind(event_id)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
-    pub async fn get_events_for_tenant(&self, tenant_id: &str, limit: i64) -> Result<Vec<Event>> {
-        let rows = sqlx::query(
-            "SELECT id, tenant_id, project_id, topic, payload, published_at FROM events WHERE tenant_id = $1 ORDER BY published_at DESC LIMIT $2"
-        )
-        .bind(tenant_id)
-        .bind(limit)
-        .fetch_all(&self.pool)
-        .await?;
+    /// Remove expired idempotency keys, returning how many were deleted
+    pub async fn delete_expired_idempotency_keys(&self) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM idempotency_keys WHERE expires_at <= NOW()")
+            .execute(&self.pool)
+            .await?;
 
-        let mut events = Vec::new();
-        for row in rows {
-            events.push(Event {
-                id: row.get("id"),
-                tenant_id: row.get("tenant_id"),
-                project_id: row.get("project_id"),
-                topic: row.get("topic"),
-                payload: row.get("payload"),
-                published_at: row.get("published_at"),
-            });
-        }
+        Ok(result.rows_affected())
+    }
 
-        Ok(events)
+    fn topic_schema_from_row(row: &sqlx::postgres::PgRow) -> TopicSchemaRecord {
+        TopicSchemaRecord {
+            tenant_id: row.get("tenant_id"),
+            project_id: row.get("project_id"),
+            topic: row.get("topic"),
+            version: row.get("version"),
+            json_schema: row.get("json_schema"),
+            mode: row.get("mode"),
+            created_at: row.get("created_at"),
+        }
     }
 
-    pub async fn get_api_keys_for_project(&self, project_id: &str) -> Result<Vec<ApiKey>> {
-        let rows = sqlx::query(
-            r#"
-            SELECT id, tenant_id, project_id, key_hash, scopes, rate_limit_per_sec, is_active, expires_at, created_at, updated_at
-            FROM api_keys 
-            WHERE project_id = $1
-            ORDER BY created_at DESC
-            "#
-        )
-        .bind(project_id)
-        .fetch_all(&self.pool)
-        .await?;
+    fn usage_record_from_row(row: &sqlx::postgres::PgRow) -> UsageRecord {
+        let metric_str: String = row.get("metric");
+        let metric = match metric_str.as_str() {
+            "events_published" => UsageMetric::EventsPublished,
+            "events_delivered" => UsageMetric::EventsDelivered,
+            "web_socket_minutes" => UsageMetric::WebSocketMinutes,
+            "api_requests" => UsageMetric::ApiRequests,
+            _ => UsageMetric::ApiRequests,
+        };
 
-        let mut api_keys = Vec::new();
-        for row in rows {
-            let scopes: Vec<Scope> = serde_json::from_value(row.get("scopes"))?;
-            api_keys.push(ApiKey {
-                id: row.get("id"),
-                tenant_id: row.get("tenant_id"),
-                project_id: row.get("project_id"),
-                key_hash: row.get("key_hash"),
-                scopes,
-                rate_limit_per_sec: row.get("rate_limit_per_sec"),
-                is_active: row.get("is_active"),
-                expires_at: row.get("expires_at"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            });
+        UsageRecord {
+            id: row.get("id"),
+            tenant_id: row.get("tenant_id"),
+            project_id: row.get("project_id"),
+            metric,
+            quantity: row.get("quantity"),
+            window_start: row.get("window_start"),
+            created_at: row.get("created_at"),
+            key_id: row.get("key_id"),
         }
-
-        Ok(api_keys)
     }
 
+    /// A project's usage records in a window, newest first, at most `limit`
+    /// of them when given
     pub async fn get_usage_records(
         &self,
         project_id: &str,
         from_date: Option<chrono::DateTime<chrono::Utc>>,
         to_date: Option<chrono::DateTime<chrono::Utc>>,
+        limit: Option<i64>,
     ) -> Result<Vec<UsageRecord>> {
         let mut query = String::from(
-            "SELECT id, tenant_id, project_id, metric, quantity, window_start, created_at FROM usage_records WHERE project_id = $1"
+            "SELECT id, tenant_id, project_id, metric, quantity, window_start, created_at, key_id FROM usage_records WHERE project_id = $1"
         );
 
         let mut bind_count = 2;
@@ -414,8 +1506,12 @@ impl Database {
         }
         if to_date.is_some() {
             query.push_str(&format!(" AND window_start <= ${}", bind_count));
+            bind_count += 1;
         }
-        query.push_str(" ORDER BY window_start DESC");
+        query.push_str(&format!(
+            " ORDER BY window_start DESC LIMIT ${}",
+            bind_count
+        ));
 
         let mut query_builder = sqlx::query(&query).bind(project_id);
 
@@ -425,74 +1521,159 @@ impl Database {
         if let Some(to) = to_date {
             query_builder = query_builder.bind(to);
         }
+        query_builder = query_builder.bind(limit);
 
         let rows = query_builder.fetch_all(&self.pool).await?;
 
-        let mut usage_records = Vec::new();
-        for row in rows {
-            let metric_str: String = row.get("metric");
-            let metric = match metric_str.as_str() {
-                "events_published" => UsageMetric::EventsPublished,
-                "events_delivered" => UsageMetric::EventsDelivered,
-                "web_socket_minutes" => UsageMetric::WebSocketMinutes,
-                "api_requests" => UsageMetric::ApiRequests,
-                _ => UsageMetric::ApiRequests,
-            };
+        Ok(rows.iter().map(Self::usage_record_from_row).collect())
+    }
 
-            usage_records.push(UsageRecord {
-                id: row.get("id"),
-                tenant_id: row.get("tenant_id"),
-                project_id: row.get("project_id"),
-                metric,
-                quantity: row.get("quantity"),
-                window_start: row.get("window_start"),
-                created_at: row.get("created_at"),
-            });
+    /// Every usage record of a tenant, across all its projects, read from a
+    /// cursor so an export never holds them all in memory
+    pub fn stream_usage_records(
+        &self,
+        tenant_id: String,
+        from_date: Option<chrono::DateTime<chrono::Utc>>,
+        to_date: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> impl Stream<Item = Result<UsageRecord>> + Send + 'static {
+        let pool = self.pool.clone();
+        async_stream::try_stream! {
+            let mut rows = sqlx::query(
+                r#"
+                SELECT id, tenant_id, project_id, metric, quantity, window_start, created_at, key_id
+                FROM usage_records
+                WHERE tenant_id = $1
+                  AND ($2::timestamptz IS NULL OR window_start >= $2)
+                  AND ($3::timestamptz IS NULL OR window_start <= $3)
+                ORDER BY project_id, window_start DESC
+                "#,
+            )
+            .bind(&tenant_id)
+            .bind(from_date)
+            .bind(to_date)
+            .fetch(&pool);
+
+            while let Some(row) = rows.try_next().await? {
+                yield Self::usage_record_from_row(&row);
+            }
         }
-
-        Ok(usage_records)
     }
 
     // Usage tracking operations
-    pub async fn create_usage_record(&self, usage: &UsageRecord) -> Result<()> {
-        let metric_str = match &usage.metric {
-            UsageMetric::EventsPublished => "events_published",
-            UsageMetric::EventsDelivered => "events_delivered",
-            UsageMetric::WebSocketMinutes => "web_socket_minutes",
-            UsageMetric::ApiRequests => "api_requests",
-        };
+    /// Add `quantity` to the usage of a metric in the window starting at
+    /// `window_start`, creating the window's row on first use. Concurrent
+    /// increments to the same window accumulate into one row.
+    pub async fn increment_usage(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        metric: UsageMetric,
+        quantity: i64,
+        window_start: DateTime<Utc>,
+        key_id: Option<&str>,
+    ) -> Result<()> {
+        let metric_str = metric.as_str();
 
         sqlx::query(
             r#"
-            INSERT INTO usage_records (id, tenant_id, project_id, metric, quantity, window_start, created_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            INSERT INTO usage_records (id, tenant_id, project_id, metric, quantity, window_start, created_at, key_id)
+            VALUES ($1, $2, $3, $4, $5, $6, NOW(), $7)
+            ON CONFLICT (tenant_id, project_id, metric, window_start, (COALESCE(key_id, '')))
+            DO UPDATE SET quantity = usage_records.quantity + EXCLUDED.quantity
             "#
         )
-        .bind(&usage.id)
-        .bind(&usage.tenant_id)
-        .bind(&usage.project_id)
+        .bind(uuid::Uuid::new_v4().to_string())
+        .bind(tenant_id)
+        .bind(project_id)
         .bind(metric_str)
-        .bind(usage.quantity)
-        .bind(usage.window_start)
-        .bind(usage.created_at)
+        .bind(quantity)
+        .bind(window_start)
+        .bind(key_id)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
+    /// Total of a metric for a tenant in its current billing cycle, or across
+    /// all windows before the rollover job has started one
     pub async fn get_usage_for_tenant(&self, tenant_id: &str, metric: UsageMetric) -> Result<i64> {
-        let metric_str = match metric {
-            UsageMetric::EventsPublished => "events_published",
-            UsageMetric::EventsDelivered => "events_delivered",
-            UsageMetric::WebSocketMinutes => "web_socket_minutes",
-            UsageMetric::ApiRequests => "api_requests",
-        };
+        let metric_str = metric.as_str();
+
+        let row = sqlx::query(
+            "SELECT COALESCE(SUM(quantity), 0) as total FROM usage_records WHERE tenant_id = $1 AND metric = $2 AND window_start >= COALESCE((SELECT usage_cycle_start FROM tenants WHERE id = $1), '-infinity')"
+        )
+        .bind(tenant_id)
+        .bind(metric_str)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let total: i64 = row.get("total");
+        Ok(total)
+    }
+
+    /// Total of a metric for a tenant across windows starting at or after `since`
+    pub async fn get_usage_for_tenant_since(
+        &self,
+        tenant_id: &str,
+        metric: UsageMetric,
+        since: DateTime<Utc>,
+    ) -> Result<i64> {
+        let metric_str = metric.as_str();
+
+        let row = sqlx::query(
+            "SELECT COALESCE(SUM(quantity), 0) as total FROM usage_records WHERE tenant_id = $1 AND metric = $2 AND window_start >= $3"
+        )
+        .bind(tenant_id)
+        .bind(metric_str)
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let total: i64 = row.get("total");
+        Ok(total)
+    }
+
+    /// Record that a usage warning fired for a tenant's quota window. Returns
+    /// `false` when it had already fired, on this or another instance.
+    pub async fn record_usage_warning(
+        &self,
+        tenant_id: &str,
+        metric: UsageMetric,
+        threshold_percent: u32,
+        window_start: DateTime<Utc>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO usage_warnings (tenant_id, metric, threshold_percent, window_start)
+            VALUES ($1, $2::usage_metric, $3, $4)
+            ON CONFLICT (tenant_id, metric, threshold_percent, window_start) DO NOTHING
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(metric.as_str())
+        .bind(threshold_percent as i32)
+        .bind(window_start)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Total of a metric for one API key in its tenant's current billing cycle
+    pub async fn get_usage_for_key(
+        &self,
+        tenant_id: &str,
+        key_id: &str,
+        metric: UsageMetric,
+    ) -> Result<i64> {
+        let metric_str = metric.as_str();
 
         let row = sqlx::query(
-            "SELECT COALESCE(SUM(quantity), 0) as total FROM usage_records WHERE tenant_id = $1 AND metric = $2"
+            "SELECT COALESCE(SUM(quantity), 0) as total FROM usage_records WHERE tenant_id = $1 AND key_id = $2 AND metric = $3 AND window_start >= COALESCE((SELECT usage_cycle_start FROM tenants WHERE id = $1), '-infinity')"
         )
         .bind(tenant_id)
+        .bind(key_id)
         .bind(metric_str)
         .fetch_one(&self.pool)
         .await?;
@@ -501,6 +1682,173 @@ impl Database {
         Ok(total)
     }
 
+    /// Every tenant's billing cycle start and suspension reason
+    pub async fn list_usage_cycles(&self) -> Result<Vec<UsageCycleState>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, created_at, usage_cycle_start,
+                   CASE WHEN status = 'suspended' THEN suspension_reason END AS suspension_reason
+            FROM tenants
+            ORDER BY created_at
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| UsageCycleState {
+                tenant_id: row.get("id"),
+                created_at: row.get("created_at"),
+                usage_cycle_start: row.get("usage_cycle_start"),
+                suspension_reason: row.get("suspension_reason"),
+            })
+            .collect())
+    }
+
+    /// Start of a tenant's current billing cycle, if the rollover job has set one
+    pub async fn get_usage_cycle_start(&self, tenant_id: &str) -> Result<Option<DateTime<Utc>>> {
+        let row = sqlx::query("SELECT usage_cycle_start FROM tenants WHERE id = $1")
+            .bind(tenant_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.and_then(|row| row.get("usage_cycle_start")))
+    }
+
+    /// Start a tenant's first billing cycle at `cycle_start`, archiving the
+    /// usage recorded before it as a cycle running from signup. Does nothing if
+    /// the tenant already has a cycle. Returns the number of totals archived.
+    pub async fn start_usage_cycle(&self, tenant_id: &str, cycle_start: DateTime<Utc>) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let started = sqlx::query(
+            "UPDATE tenants SET usage_cycle_start = $1 WHERE id = $2 AND usage_cycle_start IS NULL RETURNING created_at",
+        )
+        .bind(cycle_start)
+        .bind(tenant_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(row) = started else {
+            return Ok(0);
+        };
+        let created_at: DateTime<Utc> = row.get("created_at");
+
+        let archived = sqlx::query(
+            r#"
+            INSERT INTO usage_history (id, tenant_id, project_id, metric, quantity, cycle_start, cycle_end)
+            SELECT gen_random_uuid()::text, tenant_id, project_id, metric, SUM(quantity), LEAST($2, $3), $3
+            FROM usage_records
+            WHERE tenant_id = $1 AND window_start < $3
+            GROUP BY tenant_id, project_id, metric
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(created_at)
+        .bind(cycle_start)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+        Ok(archived)
+    }
+
+    /// Close a tenant's billing cycle: total its usage records in
+    /// `[cycle_start, cycle_end)` per project and metric into usage history and
+    /// start the next cycle at `cycle_end`. The records themselves are kept for
+    /// exports and reports. Returns the number of totals archived.
+    pub async fn roll_over_usage(
+        &self,
+        tenant_id: &str,
+        cycle_start: DateTime<Utc>,
+        cycle_end: DateTime<Utc>,
+    ) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let archived = sqlx::query(
+            r#"
+            INSERT INTO usage_history (id, tenant_id, project_id, metric, quantity, cycle_start, cycle_end)
+            SELECT gen_random_uuid()::text, tenant_id, project_id, metric, SUM(quantity), $2, $3
+            FROM usage_records
+            WHERE tenant_id = $1 AND window_start >= $2 AND window_start < $3
+            GROUP BY tenant_id, project_id, metric
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(cycle_start)
+        .bind(cycle_end)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        sqlx::query("UPDATE tenants SET usage_cycle_start = $1 WHERE id = $2")
+            .bind(cycle_end)
+            .bind(tenant_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(archived)
+    }
+
+    /// Set a tenant's status together with why it is suspended, or clear the
+    /// reason with `None`, in a single statement
+    pub async fn set_tenant_suspension(
+        &self,
+        tenant_id: &str,
+        status: TenantStatus,
+        reason: Option<&str>,
+    ) -> Result<()> {
+        let status_str = match status {
+            TenantStatus::Active => "active",
+            TenantStatus::Trial => "trial",
+            TenantStatus::PastDue => "past_due",
+            TenantStatus::Suspended => "suspended",
+        };
+
+        sqlx::query(
+            "UPDATE tenants SET status = $1, suspension_reason = $2, updated_at = NOW() WHERE id = $3",
+        )
+        .bind(status_str)
+        .bind(reason)
+        .bind(tenant_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Move a tenant to a status set by a billing event, clearing any
+    /// suspension reason. A suspended tenant is left alone unless it was
+    /// suspended for `lifts_reason`, so billing events never undo an admin's
+    /// suspension. Returns whether the tenant's status was changed.
+    pub async fn update_billing_status(
+        &self,
+        tenant_id: &str,
+        status: TenantStatus,
+        lifts_reason: Option<&str>,
+    ) -> Result<bool> {
+        let status_str = match status {
+            TenantStatus::Active => "active",
+            TenantStatus::Trial => "trial",
+            TenantStatus::PastDue => "past_due",
+            TenantStatus::Suspended => "suspended",
+        };
+
+        let result = sqlx::query(
+            r#"
+            UPDATE tenants SET status = $1, suspension_reason = NULL, updated_at = NOW()
+            WHERE id = $2 AND (status <> 'suspended' OR suspension_reason = $3)
+            "#,
+        )
+        .bind(status_str)
+        .bind(tenant_id)
+        .bind(lifts_reason)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     // RBAC operations
     pub async fn create_user(&self, user: &User) -> Result<()> {
         let role_str = match &user.role {
@@ -716,66 +2064,86 @@ impl Database {
         Ok(())
     }
 
-    // Audit logging operations
-    pub async fn create_audit_log(
+    /// Record an admin action against a tenant. `actor_api_key_id` is the key
+    /// that performed it, or `None` for actions taken by the platform itself.
+    pub async fn record_audit(
         &self,
         tenant_id: &str,
-        operation: &str,
-        details: &str,
-        performed_by: &str,
+        actor_api_key_id: Option<&str>,
+        action: &str,
+        target: Option<&str>,
+        details: &serde_json::Value,
     ) -> Result<()> {
-        let audit_id = uuid::Uuid::new_v4().to_string();
-        
         sqlx::query(
             r#"
-            INSERT INTO audit_logs (id, tenant_id, operation, details, performed_by, created_at)
-            VALUES ($1, $2, $3, $4, $5, NOW())
+            INSERT INTO audit_logs (id, tenant_id, actor_api_key_id, action, target, details, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, NOW())
             "#,
         )
-        .bind(&audit_id)
+        .bind(uuid::Uuid::new_v4().to_string())
         .bind(tenant_id)
-        .bind(operation)
+        .bind(actor_api_key_id)
+        .bind(action)
+        .bind(target)
         .bind(details)
-        .bind(performed_by)
         .execute(&self.pool)
         .await?;
 
-        info!("Created audit log: {} for tenant: {}", audit_id, tenant_id);
         Ok(())
     }
 
-    pub async fn get_audit_logs_for_tenant(
-        &self,
-        tenant_id: &str,
-        limit: Option<i64>,
-    ) -> Result<Vec<AuditLog>> {
-        let limit = limit.unwrap_or(100);
-        
+    /// Actions recorded for a tenant via `record_audit`, newest first
+    pub async fn get_audit_actions(&self, tenant_id: &str) -> Result<Vec<String>> {
         let rows = sqlx::query(
-            "SELECT id, tenant_id, operation, details, performed_by, created_at 
-             FROM audit_logs 
-             WHERE tenant_id = $1 
-             ORDER BY created_at DESC 
-             LIMIT $2"
+            "SELECT action FROM audit_logs WHERE tenant_id = $1 ORDER BY created_at DESC",
         )
         .bind(tenant_id)
-        .bind(limit)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut audit_logs = Vec::new();
-        for row in rows {
-            audit_logs.push(AuditLog {
+        Ok(rows.iter().map(|row| row.get("action")).collect())
+    }
+
+    /// A tenant's audit log entries matching `query`, newest first
+    pub async fn list_audit_logs(
+        &self,
+        tenant_id: &str,
+        query: &AuditQuery,
+        limit: i64,
+    ) -> Result<Vec<AuditLog>> {
+        let mut builder = QueryBuilder::<Postgres>::new(
+            "SELECT id, tenant_id, actor_api_key_id, action, target, details, created_at \
+             FROM audit_logs WHERE tenant_id = ",
+        );
+        builder.push_bind(tenant_id);
+        if let Some(action) = &query.action {
+            builder.push(" AND action = ").push_bind(action);
+        }
+        if let Some(from) = query.from {
+            builder.push(" AND created_at >= ").push_bind(from);
+        }
+        if let Some(to) = query.to {
+            builder.push(" AND created_at <= ").push_bind(to);
+        }
+        builder
+            .push(" ORDER BY created_at DESC, id DESC LIMIT ")
+            .push_bind(limit);
+
+        let rows = builder.build().fetch_all(&self.pool).await?;
+        Ok(rows
+            .iter()
+            .map(|row| AuditLog {
                 id: row.get("id"),
                 tenant_id: row.get("tenant_id"),
-                operation: row.get("operation"),
-                details: row.get("details"),
-                performed_by: row.get("performed_by"),
+                actor_api_key_id: row.get("actor_api_key_id"),
+                action: row.get("action"),
+                target: row.get("target"),
+                details: row
+                    .get::<Option<serde_json::Value>, _>("details")
+                    .unwrap_or_default(),
                 created_at: row.get("created_at"),
-            });
-        }
-
-        Ok(audit_logs)
+            })
+            .collect())
     }
 }
 
@@ -804,5 +2172,37 @@ mod tests {
             "SELECT * FROM events"
         ));
     }
-}
 
+    #[test]
+    fn test_pool_options_follow_config() {
+        let mut config = DatabaseConfig::new("postgresql://localhost/realtime_test");
+        config.max_connections = 7;
+        config.acquire_timeout_secs = 5;
+
+        let options = Database::pool_options(&config);
+        assert_eq!(options.get_max_connections(), 7);
+        assert_eq!(options.get_min_connections(), 1);
+        assert_eq!(options.get_acquire_timeout(), Duration::from_secs(5));
+
+        config.max_connections = 40;
+        assert_eq!(Database::pool_options(&config).get_min_connections(), 10);
+    }
+
+    #[test]
+    fn test_payload_compression_threshold() {
+        let small = serde_json::json!({"id": 1});
+        let large = serde_json::json!({"items": vec!["repeated value"; 200]});
+
+        assert!(Database::compress_payload(&large, 0).unwrap().is_none());
+        assert!(Database::compress_payload(&small, 64).unwrap().is_none());
+
+        let compressed = Database::compress_payload(&large, 64).unwrap().unwrap();
+        assert!(compressed.len() < serde_json::to_vec(&large).unwrap().len());
+        assert_eq!(Database::decompress_payload(&compressed).unwrap(), large);
+    }
+
+    #[test]
+    fn test_corrupt_compressed_payload_is_an_error() {
+        assert!(Database::decompress_payload(b"not zstd").is_err());
+    }
+}
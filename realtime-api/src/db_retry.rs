@@ -0,0 +1,146 @@
+use prometheus::Counter;
+use std::future::Future;
+use std::time::Duration;
+use tracing::warn;
+
+use crate::config::DatabaseConfig;
+
+/// Attempts made for a database operation when none are configured
+pub const DEFAULT_DB_MAX_ATTEMPTS: u32 = 3;
+
+/// Backoff before the first retry when none is configured, in milliseconds
+pub const DEFAULT_DB_RETRY_BACKOFF_MS: u64 = 50;
+
+// Longest wait between attempts, however many have failed
+const MAX_DB_RETRY_BACKOFF: Duration = Duration::from_secs(2);
+
+// How many database operations have been retried after a transient error
+lazy_static::lazy_static! {
+    static ref DATABASE_RETRIES: Counter = Counter::new(
+        "realtime_database_retries_total",
+        "Database operations retried after a transient error"
+    )
+    .expect("valid metric definition");
+}
+
+/// Counter of database operations retried after a transient error
+pub fn database_retries_counter() -> &'static Counter {
+    &DATABASE_RETRIES
+}
+
+/// Whether an error is likely to clear up on its own: a dropped connection,
+/// an exhausted pool, or a deadlock or serialization failure the server
+/// rolled back
+pub fn is_transient(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Io(_) | sqlx::Error::PoolTimedOut => true,
+        sqlx::Error::Database(db_error) => db_error.code().is_some_and(|code| {
+            // connection_exception class, serialization_failure, deadlock_detected,
+            // too_many_connections, admin_shutdown, cannot_connect_now
+            code.starts_with("08")
+                || matches!(
+                    code.as_ref(),
+                    "40001" | "40P01" | "53300" | "57P01" | "57P03"
+                )
+        }),
+        _ => false,
+    }
+}
+
+// Whether retrying an error can help. A pool timeout is transient but already
+// waited the pool's full acquire timeout; retrying would multiply that wait.
+fn is_retryable(error: &sqlx::Error) -> bool {
+    is_transient(error) && !matches!(error, sqlx::Error::PoolTimedOut)
+}
+
+/// Whether an error, or any error it wraps, is a timeout waiting for a pooled
+/// connection: the database is reachable but every connection is busy
+pub fn is_pool_exhausted(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        matches!(
+            cause.downcast_ref::<sqlx::Error>(),
+            Some(sqlx::Error::PoolTimedOut)
+        )
+    })
+}
+
+/// Whether an error, or any error it wraps, means the database couldn't be
+/// reached or couldn't serve the query, rather than that it rejected it
+pub fn is_unavailable(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .any(|cause| cause.downcast_ref::<sqlx::Error>().is_some_and(is_transient))
+}
+
+/// How many times and how patiently transient database errors are retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DbRetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+}
+
+impl Default for DbRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_DB_MAX_ATTEMPTS,
+            initial_backoff: Duration::from_millis(DEFAULT_DB_RETRY_BACKOFF_MS),
+        }
+    }
+}
+
+impl From<&DatabaseConfig> for DbRetryPolicy {
+    fn from(config: &DatabaseConfig) -> Self {
+        Self {
+            max_attempts: config.max_retry_attempts.max(1),
+            initial_backoff: Duration::from_millis(config.retry_backoff_ms),
+        }
+    }
+}
+
+impl DbRetryPolicy {
+    /// Wait after `attempt` (1-based) failed attempts: doubling from the
+    /// initial backoff, capped, with up to half of it randomized so callers
+    /// that failed together don't retry together
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        use rand::Rng;
+
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(factor)
+            .min(MAX_DB_RETRY_BACKOFF);
+        let jitter = rand::thread_rng().gen_range(0.0..=0.5);
+        backoff.mul_f64(1.0 - jitter)
+    }
+
+    /// Run `operation`, retrying transient errors until it succeeds, fails
+    /// with a permanent error, or runs out of attempts. Pool timeouts are
+    /// returned at once rather than queued for another connection again.
+    ///
+    /// Only wrap operations that are safe to repeat: reads, and writes guarded
+    /// by a key or condition that makes a second application a no-op. A write
+    /// whose first attempt may have committed before the error must not be
+    /// retried.
+    pub async fn run<T, F, Fut>(&self, mut operation: F) -> Result<T, sqlx::Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Err(error) if attempt < self.max_attempts && is_retryable(&error) => {
+                    let backoff = self.backoff(attempt);
+                    warn!(
+                        "Transient database error on attempt {}, retrying in {:?}: {}",
+                        attempt, backoff, error
+                    );
+                    DATABASE_RETRIES.inc();
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
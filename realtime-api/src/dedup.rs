@@ -0,0 +1,272 @@
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::models::Event;
+
+/// Events remembered per topic; beyond this the oldest are forgotten early
+const MAX_ENTRIES_PER_TOPIC: usize = 100_000;
+
+/// Content-based event deduplication with per-topic windows
+#[derive(Debug)]
+pub struct EventDeduplicator {
+    windows: HashMap<String, Duration>,
+    max_entries_per_topic: usize,
+    // Acked events per topic
+    seen: Mutex<HashMap<String, SeenEvents>>,
+}
+
+/// Events acked on one topic whose window has not elapsed yet
+#[derive(Debug, Default)]
+struct SeenEvents {
+    // Keyed by content hash: the original event id, its stream sequence and
+    // when its window ends
+    events: HashMap<String, (String, u64, Instant)>,
+    // Content hashes in the order their windows end, which is the order they
+    // were recorded since a topic has a single window
+    expiries: VecDeque<(Instant, String)>,
+}
+
+impl SeenEvents {
+    /// Drop the events whose window has ended
+    fn expire(&mut self, now: Instant) {
+        while self
+            .expiries
+            .front()
+            .is_some_and(|(expires_at, _)| *expires_at <= now)
+        {
+            self.forget_oldest();
+        }
+    }
+
+    fn forget_oldest(&mut self) {
+        if let Some((_, hash)) = self.expiries.pop_front() {
+            self.events.remove(&hash);
+        }
+    }
+}
+
+impl Default for EventDeduplicator {
+    fn default() -> Self {
+        Self::new(HashMap::new())
+    }
+}
+
+impl EventDeduplicator {
+    /// Create a deduplicator from a map of topic -> window in seconds
+    pub fn new(dedup_window_secs: HashMap<String, u64>) -> Self {
+        let windows = dedup_window_secs
+            .into_iter()
+            .filter(|(_, secs)| *secs > 0)
+            .map(|(topic, secs)| (topic, Duration::from_secs(secs)))
+            .collect();
+
+        Self {
+            windows,
+            max_entries_per_topic: MAX_ENTRIES_PER_TOPIC,
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the dedup window configured for a topic, if any
+    pub fn window_for(&self, topic: &str) -> Option<Duration> {
+        self.windows.get(topic).copied()
+    }
+
+    /// Hash the tenant, project, topic and payload of an event
+    pub fn content_hash(event: &Event) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(event.tenant_id.as_bytes());
+        hasher.update([0]);
+        hasher.update(event.project_id.as_bytes());
+        hasher.update([0]);
+        hasher.update(event.topic.as_bytes());
+        hasher.update([0]);
+        hasher.update(event.payload.to_string().as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Check whether an identical event was published within the topic's window.
+    /// Returns the original event's id and stream sequence for duplicates.
+    pub fn check(&self, event: &Event) -> Option<(String, u64)> {
+        self.check_at(event, Instant::now())
+    }
+
+    /// Remember an event once JetStream has acked it at `sequence`, so events
+    /// that never made it to the stream are not treated as originals
+    pub fn record(&self, event: &Event, sequence: u64) {
+        self.record_at(event, sequence, Instant::now())
+    }
+
+    fn check_at(&self, event: &Event, now: Instant) -> Option<(String, u64)> {
+        self.window_for(&event.topic)?;
+        let hash = Self::content_hash(event);
+        let mut seen = self.seen.lock().unwrap();
+        let topic_seen = seen.get_mut(&event.topic)?;
+        topic_seen.expire(now);
+        topic_seen
+            .events
+            .get(&hash)
+            .map(|(original_id, sequence, _)| (original_id.clone(), *sequence))
+    }
+
+    fn record_at(&self, event: &Event, sequence: u64, now: Instant) {
+        let Some(window) = self.window_for(&event.topic) else {
+            return;
+        };
+        let hash = Self::content_hash(event);
+        let mut seen = self.seen.lock().unwrap();
+        let topic_seen = seen.entry(event.topic.clone()).or_default();
+        topic_seen.expire(now);
+        // A concurrent identical publish may have been acked first; keep it
+        if topic_seen.events.contains_key(&hash) {
+            return;
+        }
+        if topic_seen.events.len() >= self.max_entries_per_topic {
+            topic_seen.forget_oldest();
+        }
+
+        let expires_at = now + window;
+        topic_seen.expiries.push_back((expires_at, hash.clone()));
+        topic_seen
+            .events
+            .insert(hash, (event.id.clone(), sequence, expires_at));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn deduplicator() -> EventDeduplicator {
+        let mut windows = HashMap::new();
+        windows.insert("orders.created".to_string(), 60);
+        EventDeduplicator::new(windows)
+    }
+
+    fn event(topic: &str, payload: serde_json::Value) -> Event {
+        Event::new(
+            "tenant_123".to_string(),
+            "project_456".to_string(),
+            topic.to_string(),
+            payload,
+        )
+    }
+
+    #[test]
+    fn test_identical_events_within_window_are_suppressed() {
+        let dedup = deduplicator();
+        let now = Instant::now();
+        let first = event("orders.created", json!({"order_id": 1}));
+        let second = event("orders.created", json!({"order_id": 1}));
+
+        assert_eq!(dedup.check_at(&first, now), None);
+        dedup.record_at(&first, 7, now);
+        assert_eq!(
+            dedup.check_at(&second, now + Duration::from_secs(10)),
+            Some((first.id.clone(), 7))
+        );
+    }
+
+    #[test]
+    fn test_unacked_event_is_not_an_original() {
+        let dedup = deduplicator();
+        let now = Instant::now();
+        let failed = event("orders.created", json!({"order_id": 1}));
+        let retry = event("orders.created", json!({"order_id": 1}));
+
+        // The first publish was never acked, so it was never recorded
+        assert_eq!(dedup.check_at(&failed, now), None);
+        assert_eq!(dedup.check_at(&retry, now + Duration::from_secs(1)), None);
+        dedup.record_at(&retry, 3, now + Duration::from_secs(1));
+        assert_eq!(
+            dedup.check_at(&failed, now + Duration::from_secs(2)),
+            Some((retry.id.clone(), 3))
+        );
+    }
+
+    #[test]
+    fn test_different_payload_is_not_suppressed() {
+        let dedup = deduplicator();
+        let now = Instant::now();
+
+        dedup.record_at(&event("orders.created", json!({"order_id": 1})), 1, now);
+        assert_eq!(
+            dedup.check_at(&event("orders.created", json!({"order_id": 2})), now),
+            None
+        );
+    }
+
+    #[test]
+    fn test_identical_event_after_window_is_accepted() {
+        let dedup = deduplicator();
+        let now = Instant::now();
+
+        dedup.record_at(&event("orders.created", json!({"order_id": 1})), 1, now);
+        assert_eq!(
+            dedup.check_at(
+                &event("orders.created", json!({"order_id": 1})),
+                now + Duration::from_secs(61)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_topics_without_window_are_never_deduplicated() {
+        let dedup = deduplicator();
+        let now = Instant::now();
+
+        dedup.record_at(&event("user.created", json!({"id": 1})), 1, now);
+        assert_eq!(
+            dedup.check_at(&event("user.created", json!({"id": 1})), now),
+            None
+        );
+    }
+
+    #[test]
+    fn test_oldest_events_are_forgotten_beyond_the_entry_cap() {
+        let mut dedup = deduplicator();
+        dedup.max_entries_per_topic = 2;
+        let now = Instant::now();
+
+        for order_id in 1..=3 {
+            dedup.record_at(
+                &event("orders.created", json!({"order_id": order_id})),
+                order_id,
+                now,
+            );
+        }
+        assert_eq!(
+            dedup.check_at(&event("orders.created", json!({"order_id": 1})), now),
+            None
+        );
+        assert!(dedup
+            .check_at(&event("orders.created", json!({"order_id": 3})), now)
+            .is_some());
+    }
+
+    #[test]
+    fn test_expired_events_are_dropped() {
+        let dedup = deduplicator();
+        let now = Instant::now();
+
+        dedup.record_at(&event("orders.created", json!({"order_id": 1})), 1, now);
+        dedup.record_at(
+            &event("orders.created", json!({"order_id": 2})),
+            2,
+            now + Duration::from_secs(30),
+        );
+        dedup.check_at(
+            &event("orders.created", json!({"order_id": 3})),
+            now + Duration::from_secs(61),
+        );
+
+        let seen = dedup.seen.lock().unwrap();
+        let topic_seen = &seen["orders.created"];
+        assert_eq!(topic_seen.events.len(), 1);
+        assert_eq!(topic_seen.expiries.len(), 1);
+    }
+}
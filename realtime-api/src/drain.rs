@@ -0,0 +1,238 @@
+use chrono::{DateTime, Utc};
+use prometheus::{CounterVec, Opts};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::info;
+
+use crate::sse::{sse_manager, SSEManager};
+use crate::websocket::{websocket_manager, WebSocketManager};
+
+/// Close code sent to connections drained after a limit decrease (policy violation)
+pub const CONNECTION_LIMIT_CLOSE_CODE: u16 = 1008;
+
+/// Close reason sent to connections drained after a limit decrease
+pub const CONNECTION_LIMIT_CLOSE_REASON: &str = "connection_limit_reduced";
+
+// Whether connections whose delivery channel has closed are removed on broadcast,
+// and how many have been removed per transport
+lazy_static::lazy_static! {
+    static ref PRUNE_CLOSED_CONNECTIONS: AtomicBool = AtomicBool::new(true);
+    static ref CLOSED_CONNECTIONS_PRUNED: CounterVec = CounterVec::new(
+        Opts::new(
+            "realtime_closed_connections_pruned_total",
+            "Connections removed after all receivers of their delivery channel dropped"
+        ),
+        &["transport"]
+    )
+    .expect("valid metric definition");
+}
+
+/// Enable or disable removing connections with closed delivery channels on broadcast
+pub fn set_prune_closed_connections(enabled: bool) {
+    PRUNE_CLOSED_CONNECTIONS.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether closed connections are removed on broadcast
+pub fn prune_closed_connections_enabled() -> bool {
+    PRUNE_CLOSED_CONNECTIONS.load(Ordering::Relaxed)
+}
+
+/// Counter of pruned closed connections, labelled by transport
+pub fn closed_connections_pruned_counter() -> &'static CounterVec {
+    &CLOSED_CONNECTIONS_PRUNED
+}
+
+/// Record that a closed connection was removed from a manager
+pub fn record_closed_connection_pruned(transport: &str) {
+    CLOSED_CONNECTIONS_PRUNED.with_label_values(&[transport]).inc();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Transport {
+    WebSocket,
+    Sse,
+}
+
+/// Close the oldest connections of a project, across both transports,
+/// until at most `max_connections` remain. Returns the drained connection ids.
+pub fn drain_to_limit(
+    websockets: &WebSocketManager,
+    sse: &SSEManager,
+    tenant_id: &str,
+    project_id: &str,
+    max_connections: i32,
+) -> Vec<String> {
+    let mut connections: Vec<(DateTime<Utc>, String, Transport)> = websockets
+        .project_connections(tenant_id, project_id)
+        .into_iter()
+        .map(|(id, created_at)| (created_at, id, Transport::WebSocket))
+        .chain(
+            sse.project_connections(tenant_id, project_id)
+                .into_iter()
+                .map(|(id, created_at)| (created_at, id, Transport::Sse)),
+        )
+        .collect();
+
+    let excess = connections
+        .len()
+        .saturating_sub(max_connections.max(0) as usize);
+    if excess == 0 {
+        return Vec::new();
+    }
+
+    // Oldest first
+    connections.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut websocket_ids = Vec::new();
+    let mut sse_ids = Vec::new();
+    for (_, id, transport) in connections.into_iter().take(excess) {
+        match transport {
+            Transport::WebSocket => websocket_ids.push(id),
+            Transport::Sse => sse_ids.push(id),
+        }
+    }
+
+    websockets.close_connections(
+        &websocket_ids,
+        CONNECTION_LIMIT_CLOSE_CODE,
+        CONNECTION_LIMIT_CLOSE_REASON,
+    );
+    sse.close_connections(&sse_ids, CONNECTION_LIMIT_CLOSE_CODE, CONNECTION_LIMIT_CLOSE_REASON);
+
+    websocket_ids.into_iter().chain(sse_ids).collect()
+}
+
+/// Apply a new project connection limit, draining any connections above it
+pub fn apply_project_connection_limit(
+    tenant_id: &str,
+    project_id: &str,
+    max_connections: i32,
+) -> Vec<String> {
+    websocket_manager().set_connection_limit(project_id.to_string(), max_connections);
+    sse_manager().set_connection_limit(project_id.to_string(), max_connections);
+
+    let drained = drain_to_limit(
+        websocket_manager(),
+        sse_manager(),
+        tenant_id,
+        project_id,
+        max_connections,
+    );
+
+    if !drained.is_empty() {
+        info!(
+            "Drained {} connections for tenant/project {}/{} to new limit {}",
+            drained.len(),
+            tenant_id,
+            project_id,
+            max_connections
+        );
+    }
+
+    drained
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sse::{SSEConnection, SSEMessage};
+    use crate::websocket::{WebSocketConnection, WebSocketMessage};
+    use tokio::sync::broadcast;
+
+    fn ws_connection(
+        id: &str,
+        project_id: &str,
+        created_at: DateTime<Utc>,
+    ) -> (WebSocketConnection, broadcast::Receiver<WebSocketMessage>) {
+        let (sender, receiver) = broadcast::channel(10);
+        let connection = WebSocketConnection {
+            id: id.to_string(),
+            tenant_id: "tenant_1".to_string(),
+            project_id: project_id.to_string(),
+            subscribed_topics: vec![],
+            sender,
+            created_at,
+            acked: false,
+        };
+        (connection, receiver)
+    }
+
+    fn sse_connection(
+        id: &str,
+        created_at: DateTime<Utc>,
+    ) -> (SSEConnection, broadcast::Receiver<SSEMessage>) {
+        let (sender, receiver) = broadcast::channel(10);
+        let connection = SSEConnection {
+            id: id.to_string(),
+            tenant_id: "tenant_1".to_string(),
+            project_id: "project_1".to_string(),
+            subscribed_topics: vec![],
+            sender,
+            created_at,
+            acked: false,
+        };
+        (connection, receiver)
+    }
+
+    #[test]
+    fn test_lowering_limit_drains_oldest_connections() {
+        let websockets = WebSocketManager::new();
+        let sse = SSEManager::new();
+        let start = Utc::now();
+        let at = |secs: i64| start + chrono::Duration::seconds(secs);
+
+        let (ws_0, mut ws_0_rx) = ws_connection("ws_0", "project_1", at(0));
+        let (sse_1, mut sse_1_rx) = sse_connection("sse_1", at(1));
+        let (ws_2, _ws_2_rx) = ws_connection("ws_2", "project_1", at(2));
+        let (sse_3, mut sse_3_rx) = sse_connection("sse_3", at(3));
+        let (ws_4, mut ws_4_rx) = ws_connection("ws_4", "project_1", at(4));
+        let (other, _other_rx) = ws_connection("other", "project_2", at(-10));
+
+        websockets.add_connection(ws_0).unwrap();
+        sse.add_connection(sse_1).unwrap();
+        websockets.add_connection(ws_2).unwrap();
+        sse.add_connection(sse_3).unwrap();
+        websockets.add_connection(ws_4).unwrap();
+        websockets.add_connection(other).unwrap();
+
+        let mut drained = drain_to_limit(&websockets, &sse, "tenant_1", "project_1", 2);
+        drained.sort();
+        assert_eq!(drained, vec!["sse_1", "ws_0", "ws_2"]);
+
+        // Survivors are the newest connections of each transport
+        let remaining_ws: Vec<String> = websockets
+            .project_connections("tenant_1", "project_1")
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(remaining_ws, vec!["ws_4"]);
+        assert_eq!(sse.project_connections("tenant_1", "project_1").len(), 1);
+
+        // Other projects are untouched
+        assert_eq!(websockets.project_connections("tenant_1", "project_2").len(), 1);
+
+        // Drained connections receive a structured close, survivors do not
+        match ws_0_rx.try_recv().unwrap() {
+            WebSocketMessage::Close { code, reason } => {
+                assert_eq!(code, CONNECTION_LIMIT_CLOSE_CODE);
+                assert_eq!(reason, CONNECTION_LIMIT_CLOSE_REASON);
+            }
+            other => panic!("Expected close message, got {:?}", other),
+        }
+        assert!(matches!(sse_1_rx.try_recv().unwrap(), SSEMessage::Close { .. }));
+        assert!(ws_4_rx.try_recv().is_err());
+        assert!(sse_3_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_limit_above_current_count_drains_nothing() {
+        let websockets = WebSocketManager::new();
+        let sse = SSEManager::new();
+
+        let (ws, mut ws_rx) = ws_connection("ws_0", "project_1", Utc::now());
+        websockets.add_connection(ws).unwrap();
+
+        assert!(drain_to_limit(&websockets, &sse, "tenant_1", "project_1", 5).is_empty());
+        assert_eq!(websockets.project_connections("tenant_1", "project_1").len(), 1);
+        assert!(ws_rx.try_recv().is_err());
+    }
+}
@@ -1,12 +1,114 @@
 use anyhow::{anyhow, Result};
+use futures_util::{Future, Stream, StreamExt};
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::broadcast;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
 use tracing::{error, info, warn};
 
+use crate::alerting::AlertingService;
+use crate::auth::AuthContext;
+use crate::circuit_breaker::circuit_open;
+use crate::config::RedactionConfig;
 use crate::database::Database;
-use crate::models::{Event, UsageMetric, UsageRecord};
-use crate::nats::{NatsClient, ReplayRequest, SubscriptionConfig};
-use crate::schema_validator::SchemaValidator;
+use crate::dedup::EventDeduplicator;
+#[cfg(feature = "kafka")]
+use crate::kafka_sink::{spawn_sink_worker, KafkaSink};
+use crate::models::{
+    Event, EventBuildError, MetadataLimitError, MetadataLimits, ProjectSettings, Tenant,
+    UsageMetric, UsageRecord,
+};
+use crate::nats::{
+    redelivery_delay, DeadLetter, DeadLetterKind, NatsClient, ReplayRequest, SubscriptionConfig,
+};
+use crate::rate_limit::{acquire_or_allow, RateLimitStatus, RateLimitStore, RateLimiter};
+use crate::redaction::PayloadRedactor;
+use crate::schema_validator::{
+    record_schema_violation, SchemaCheck, SchemaValidationError, SchemaValidator, ValidationMode,
+};
+use crate::snapshot::SnapshotStore;
+use crate::transform::TransformPipeline;
+use crate::usage_warnings::{usage_window_start, UsageWarnings, USAGE_WARNING_TOPIC};
+use crate::webhooks::{spawn_delivery_worker, webhook_events_dropped_counter, WebhookDispatcher};
+
+/// Reserved topic whose events are fanned straight back to subscribers for connectivity checks
+pub const ECHO_TOPIC: &str = "__echo";
+
+/// Default cap on the number of events accepted by one batch publish
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 100;
+
+/// Default time an idempotency key keeps returning the event first published with it
+pub const DEFAULT_IDEMPOTENCY_KEY_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Default time an in-flight idempotency claim blocks retries before another
+/// publish may take it over; comfortably longer than a JetStream publish
+pub const DEFAULT_IDEMPOTENCY_CLAIM_LEASE: Duration = Duration::from_secs(30);
+
+/// Usage records buffered per subscriber before a slow subscriber is dropped
+pub const USAGE_UPDATES_CAPACITY: usize = 1000;
+
+/// Prefix of the rate limit identifiers of project-wide publish budgets,
+/// keeping them apart from the per-key buckets in a shared store
+pub const PROJECT_RATE_LIMIT_PREFIX: &str = "project:";
+
+/// Longest accepted `Idempotency-Key`
+pub const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+/// Check that an idempotency key is 1-255 visible ASCII characters
+pub fn validate_idempotency_key(key: &str) -> Result<(), String> {
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+        return Err(format!(
+            "Idempotency key must be between 1 and {} characters",
+            MAX_IDEMPOTENCY_KEY_LENGTH
+        ));
+    }
+    if !key.bytes().all(|b| b.is_ascii_graphic()) {
+        return Err("Idempotency key must contain only visible ASCII characters".to_string());
+    }
+    Ok(())
+}
+
+/// Why `send` couldn't deliver an event to a subscriber
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SendError {
+    /// Worth retrying, such as a full outbound buffer; the event is redelivered
+    #[error("{0}")]
+    Transient(String),
+    /// The subscriber has gone away; delivery stops
+    #[error("subscriber disconnected")]
+    Disconnected,
+}
+
+/// A batch publish with no events or more than the batch size limit
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("A batch must contain between 1 and {max_batch_size} events")]
+pub struct InvalidBatchSize {
+    pub size: usize,
+    pub max_batch_size: usize,
+}
+
+/// One event of a batch publish, as submitted over REST or GraphQL
+#[derive(Debug, Clone, Default)]
+pub struct BatchItem {
+    pub topic: String,
+    pub payload: serde_json::Value,
+    pub partition_key: Option<String>,
+    pub headers: HashMap<String, String>,
+    pub metadata: HashMap<String, String>,
+    pub attributes: HashMap<String, String>,
+}
+
+/// Why one event of a batch was rejected before it was published
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum BatchItemError {
+    #[error("API key may not publish to topic {0}")]
+    TopicNotAllowed(String),
+    #[error(transparent)]
+    MetadataLimit(#[from] MetadataLimitError),
+    #[error(transparent)]
+    InvalidEvent(#[from] EventBuildError),
+}
 
 /// Event publishing service with tenant/project scoping
 #[derive(Debug, Clone)]
@@ -14,13 +116,76 @@ pub struct EventService {
     database: Database,
     nats_client: NatsClient,
     schema_validator: Arc<SchemaValidator>,
+    deduplicator: Arc<EventDeduplicator>,
+    snapshots: Arc<SnapshotStore>,
+    echo_enabled: bool,
+    max_batch_size: usize,
+    metadata_limits: MetadataLimits,
+    idempotency_ttl: Duration,
+    idempotency_claim_lease: Duration,
+    // Masks payload values quoted in validation logs and errors
+    redactor: Arc<PayloadRedactor>,
+    // Token buckets capping each project's publish rate across all its keys
+    project_rate_limiter: Arc<dyn RateLimitStore>,
+    // Live fan-out of published events to in-process subscribers (e.g. GraphQL)
+    live_events: broadcast::Sender<Event>,
+    // Live fan-out of recorded usage to billing subscribers
+    usage_updates: broadcast::Sender<UsageRecord>,
+    // Queue feeding the webhook delivery worker, when webhooks are enabled
+    webhook_queue: Option<mpsc::Sender<Event>>,
+    // Delivers replayed webhook dead letters, when webhooks are enabled
+    webhook_dispatcher: Option<WebhookDispatcher>,
+    // Queue feeding the Kafka sink worker, when the sink is enabled
+    kafka_queue: Option<mpsc::Sender<Event>>,
+    // Writes replayed Kafka dead letters, when the sink is enabled
+    #[cfg(feature = "kafka")]
+    kafka_sink: Option<KafkaSink>,
+    // Soft-limit thresholds and where their alerts go, when enabled
+    usage_warnings: Option<(Arc<UsageWarnings>, AlertingService)>,
 }
 
 /// Event publishing result
 #[derive(Debug)]
 pub enum PublishResult {
-    Success,
+    /// Persisted to JetStream at the given stream sequence
+    Success { sequence: u64 },
     ValidationFailed(String),
+    /// An identical event was published within the topic's dedup window, at
+    /// the given stream sequence
+    Deduplicated {
+        original_event_id: String,
+        sequence: u64,
+    },
+    /// An event was already published with the same idempotency key
+    Replayed { event_id: String, sequence: u64 },
+    /// The first publish with the same idempotency key has not finished yet
+    IdempotencyKeyInUse,
+    /// The project is publishing faster than its `max_events_per_sec`
+    RateLimited(RateLimitStatus),
+    /// The serialized payload is over the project's `max_payload_size`
+    PayloadTooLarge { size: usize, limit: usize },
+}
+
+/// Outcome of retrying a dead-lettered event
+#[derive(Debug)]
+pub enum DeadLetterReplay {
+    /// Re-published to the event stream
+    Published(PublishResult),
+    /// Delivered to the webhook or Kafka sink it failed for, after `attempts`
+    /// attempts
+    Redelivered { attempts: u32 },
+    /// Re-published at `sequence` for only the subscription it failed for
+    Requeued { sequence: u64 },
+    /// Failed again; the dead letter is kept
+    Failed(String),
+    /// Re-published but not accepted, e.g. because it still fails
+    /// validation; the dead letter is kept. An event that failed schema
+    /// validation again is also dead-lettered anew as `dead_letter_id` with
+    /// the new reason.
+    Rejected {
+        result: PublishResult,
+        dead_letter_id: Option<u64>,
+    },
 }
 
 /// Event subscription handle
@@ -33,6 +198,47 @@ pub struct EventSubscription {
     pub receiver: broadcast::Receiver<Event>,
 }
 
+impl EventSubscription {
+    /// Whether an event belongs to this subscription's tenant, project and topics
+    pub fn matches(&self, event: &Event) -> bool {
+        subscription_matches(&self.tenant_id, &self.project_id, &self.topics, event)
+    }
+
+    /// Stream the subscription's matching events; dropping the stream ends the subscription
+    pub fn into_stream(self) -> impl Stream<Item = Event> + Send {
+        let EventSubscription {
+            consumer_name,
+            tenant_id,
+            project_id,
+            topics,
+            receiver,
+        } = self;
+
+        BroadcastStream::new(receiver).filter_map(move |received| {
+            let event = match received {
+                Ok(event) if subscription_matches(&tenant_id, &project_id, &topics, &event) => {
+                    Some(event)
+                }
+                Ok(_) => None,
+                Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                    warn!(
+                        "Subscription {} lagged and skipped {} events",
+                        consumer_name, skipped
+                    );
+                    None
+                }
+            };
+            futures_util::future::ready(event)
+        })
+    }
+}
+
+fn subscription_matches(tenant_id: &str, project_id: &str, topics: &[String], event: &Event) -> bool {
+    event.tenant_id == tenant_id
+        && event.project_id == project_id
+        && crate::websocket::topic_matches(topics, &event.topic)
+}
+
 impl EventService {
     /// Create a new event service
     pub fn new(
@@ -44,11 +250,206 @@ impl EventService {
             database,
             nats_client,
             schema_validator: Arc::new(schema_validator),
+            deduplicator: Arc::new(EventDeduplicator::default()),
+            snapshots: Arc::new(SnapshotStore::default()),
+            echo_enabled: false,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            metadata_limits: MetadataLimits::default(),
+            idempotency_ttl: DEFAULT_IDEMPOTENCY_KEY_TTL,
+            idempotency_claim_lease: DEFAULT_IDEMPOTENCY_CLAIM_LEASE,
+            redactor: Arc::new(PayloadRedactor::default()),
+            project_rate_limiter: Arc::new(RateLimiter::new()),
+            live_events: broadcast::channel(1000).0,
+            usage_updates: broadcast::channel(USAGE_UPDATES_CAPACITY).0,
+            webhook_queue: None,
+            webhook_dispatcher: None,
+            kafka_queue: None,
+            #[cfg(feature = "kafka")]
+            kafka_sink: None,
+            usage_warnings: None,
+        }
+    }
+
+    /// Deliver published events to registered webhooks from a background worker
+    pub fn with_webhooks(mut self, dispatcher: WebhookDispatcher) -> Self {
+        self.webhook_queue = Some(spawn_delivery_worker(
+            dispatcher.clone(),
+            self.database.clone(),
+            self.nats_client.clone(),
+        ));
+        self.webhook_dispatcher = Some(dispatcher);
+        self
+    }
+
+    /// Whether webhooks may be registered for loopback and private addresses
+    pub fn allows_private_webhook_addresses(&self) -> bool {
+        self.webhook_dispatcher
+            .as_ref()
+            .is_some_and(|dispatcher| dispatcher.allows_private_addresses())
+    }
+
+    /// Copy published events to Kafka from a background worker
+    #[cfg(feature = "kafka")]
+    pub fn with_kafka_sink(mut self, sink: KafkaSink) -> Self {
+        self.kafka_queue = Some(spawn_sink_worker(sink.clone(), self.nats_client.clone()));
+        self.kafka_sink = Some(sink);
+        self
+    }
+
+    /// Warn tenants once per month as their published events cross each of
+    /// `thresholds` (percentages of the plan's monthly events)
+    pub fn with_usage_warnings(mut self, thresholds: Vec<u32>, alerting: AlertingService) -> Self {
+        if !thresholds.is_empty() {
+            self.usage_warnings = Some((Arc::new(UsageWarnings::new(thresholds)), alerting));
+        }
+        self
+    }
+
+    /// Enable content-based deduplication for the given topic windows (seconds)
+    pub fn with_dedup_windows(mut self, dedup_window_secs: HashMap<String, u64>) -> Self {
+        self.deduplicator = Arc::new(EventDeduplicator::new(dedup_window_secs));
+        self
+    }
+
+    /// Enable snapshot-on-subscribe for topics (topic -> partition key field),
+    /// keeping at most `max_keys_per_topic` keys per topic
+    pub fn with_snapshot_topics(
+        mut self,
+        key_fields: HashMap<String, String>,
+        max_keys_per_topic: usize,
+    ) -> Self {
+        self.snapshots = Arc::new(SnapshotStore::new(key_fields, max_keys_per_topic));
+        self
+    }
+
+    /// Enable the reserved echo topic
+    pub fn with_echo_topic(mut self, enabled: bool) -> Self {
+        self.echo_enabled = enabled;
+        self
+    }
+
+    /// Cap the number of events accepted by [`EventService::publish_batch`]
+    pub fn with_max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = max_batch_size;
+        self
+    }
+
+    /// Maximum number of events accepted in one batch
+    pub fn max_batch_size(&self) -> usize {
+        self.max_batch_size
+    }
+
+    /// Cap the metadata and attributes accepted on each event of a batch
+    pub fn with_metadata_limits(mut self, metadata_limits: MetadataLimits) -> Self {
+        self.metadata_limits = metadata_limits;
+        self
+    }
+
+    /// Check that a batch holds between 1 and `max_batch_size` events
+    pub fn check_batch_size(&self, size: usize) -> Result<(), InvalidBatchSize> {
+        if size == 0 || size > self.max_batch_size {
+            return Err(InvalidBatchSize {
+                size,
+                max_batch_size: self.max_batch_size,
+            });
         }
+        Ok(())
+    }
+
+    /// Check one event of a batch against the caller's topic grants and the
+    /// metadata limits, and build it. REST and GraphQL batches share these checks.
+    pub fn prepare_batch_event(
+        &self,
+        auth: &AuthContext,
+        item: BatchItem,
+    ) -> Result<Event, BatchItemError> {
+        if !auth.allows_topic(&item.topic) {
+            return Err(BatchItemError::TopicNotAllowed(item.topic));
+        }
+        self.metadata_limits
+            .check(&item.metadata, &item.attributes)?;
+
+        let event = Event::builder()
+            .tenant_id(auth.tenant_id.clone())
+            .project_id(auth.project_id.clone())
+            .topic(item.topic)
+            .payload(item.payload)
+            .partition_key(item.partition_key)
+            .headers(item.headers)
+            .metadata(item.metadata)
+            .attributes(item.attributes)
+            .build()?;
+        Ok(event)
+    }
+
+    /// Set how long an idempotency key keeps returning its original event
+    pub fn with_idempotency_ttl(mut self, idempotency_ttl: Duration) -> Self {
+        self.idempotency_ttl = idempotency_ttl;
+        self
+    }
+
+    /// Set how long an unfinished publish holds its idempotency key
+    pub fn with_idempotency_claim_lease(mut self, lease: Duration) -> Self {
+        self.idempotency_claim_lease = lease;
+        self
+    }
+
+    /// Mask payload values configured in `redaction` wherever a validation
+    /// failure is logged or returned
+    pub fn with_redaction(mut self, redaction: &RedactionConfig) -> Self {
+        self.redactor = Arc::new(PayloadRedactor::new(redaction));
+        self
+    }
+
+    /// Keep project publish budgets in `store`, e.g. Redis shared by all instances
+    pub fn with_rate_limit_store(mut self, store: Arc<dyn RateLimitStore>) -> Self {
+        self.project_rate_limiter = store;
+        self
     }
 
     /// Publish an event with validation and persistence
     pub async fn publish_event(&self, event: &Event) -> Result<PublishResult> {
+        self.publish_event_with_key(event, None).await
+    }
+
+    /// Publish an event, attributing its usage to the given API key
+    pub async fn publish_event_with_key(
+        &self,
+        event: &Event,
+        key_id: Option<&str>,
+    ) -> Result<PublishResult> {
+        self.publish_event_idempotent(event, key_id, None).await
+    }
+
+    /// Publish an event under an optional producer-supplied idempotency key.
+    ///
+    /// The key is scoped to the event's tenant and project. A repeat within the
+    /// idempotency TTL returns the original event id and sequence instead of
+    /// publishing again; the key is also sent as `Nats-Msg-Id` so JetStream drops
+    /// repeats within its own duplicate window.
+    pub async fn publish_event_idempotent(
+        &self,
+        event: &Event,
+        key_id: Option<&str>,
+        idempotency_key: Option<&str>,
+    ) -> Result<PublishResult> {
+        self.publish_checked(event, key_id, idempotency_key, &mut None)
+            .await
+    }
+
+    // The body of `publish_event_idempotent`, also setting `dead_letter_id`
+    // when the event fails schema validation and is dead-lettered
+    async fn publish_checked(
+        &self,
+        event: &Event,
+        key_id: Option<&str>,
+        idempotency_key: Option<&str>,
+        dead_letter_id: &mut Option<u64>,
+    ) -> Result<PublishResult> {
+        if let Some(Err(e)) = idempotency_key.map(validate_idempotency_key) {
+            return Ok(PublishResult::ValidationFailed(e));
+        }
+
         // Validate tenant and project exist and are active
         let tenant = self
             .database
@@ -63,26 +464,205 @@ impl EventService {
             )));
         }
 
-        let _project = self
+        let project = self
             .database
             .get_project_with_tenant(&event.tenant_id, &event.project_id)
             .await?
             .ok_or_else(|| anyhow!("Project not found: {}", event.project_id))?;
 
-        // Validate event payload against topic schema
-        if let Err(e) = self
-            .schema_validator
-            .validate_event_payload(&event.topic, &event.payload)
-        {
-            warn!("Event validation failed for topic {}: {}", event.topic, e);
+        // API keys of archived projects no longer authenticate, but JWTs name
+        // the project themselves
+        if project.is_archived() {
             return Ok(PublishResult::ValidationFailed(format!(
-                "Event validation failed: {}",
-                e
+                "Project is archived: {}",
+                event.project_id
             )));
         }
 
+        let payload_size = event.payload.to_string().len();
+        let max_payload_size = project.limits.max_payload_size.max(0) as usize;
+        if payload_size > max_payload_size {
+            return Ok(PublishResult::PayloadTooLarge {
+                size: payload_size,
+                limit: max_payload_size,
+            });
+        }
+
+        if event.topic == ECHO_TOPIC {
+            return self.publish_echo(event).await;
+        }
+
+        // One budget for the whole project, however many keys publish to it
+        let rate = acquire_or_allow(
+            self.project_rate_limiter.as_ref(),
+            &format!("{}{}", PROJECT_RATE_LIMIT_PREFIX, project.id),
+            project.limits.max_events_per_sec.max(0) as u32,
+        )
+        .await;
+        if !rate.allowed {
+            warn!(
+                "Project {} exceeded its publish rate of {} events/sec",
+                project.id, rate.limit
+            );
+            return Ok(PublishResult::RateLimited(rate));
+        }
+
+        let schema_check = match self.check_schema(event, &project.settings).await {
+            Ok(schema_check) => schema_check,
+            Err(reason) => {
+                *dead_letter_id = self
+                    .dead_letter(event, DeadLetterKind::Validation, &reason)
+                    .await;
+                return Ok(PublishResult::ValidationFailed(reason));
+            }
+        };
+
+        // The outcome is stored and delivered with the event
+        let mut validated = event.clone();
+        if let Some((schema_valid, schema_version)) = schema_check {
+            validated.schema_valid = Some(schema_valid);
+            validated.schema_version = schema_version;
+        }
+        let event = &validated;
+
+        // Suppress identical payloads within the topic's dedup window
+        if let Some((original_event_id, sequence)) = self.deduplicator.check(event) {
+            info!(
+                "Deduplicated event {} on topic {} (original: {})",
+                event.id, event.topic, original_event_id
+            );
+            return Ok(PublishResult::Deduplicated {
+                original_event_id,
+                sequence,
+            });
+        }
+
+        // Producers retrying with the same key get the original event back
+        let msg_id = match idempotency_key {
+            Some(idempotency_key) => {
+                if let Some(existing) = self
+                    .database
+                    .claim_idempotency_key(
+                        &event.tenant_id,
+                        &event.project_id,
+                        idempotency_key,
+                        &event.id,
+                        self.idempotency_ttl,
+                        self.idempotency_claim_lease,
+                    )
+                    .await?
+                {
+                    info!(
+                        "Idempotency key {} already used by event {}",
+                        idempotency_key, existing.event_id
+                    );
+                    return Ok(match existing.sequence {
+                        Some(sequence) => PublishResult::Replayed {
+                            event_id: existing.event_id,
+                            sequence,
+                        },
+                        None => PublishResult::IdempotencyKeyInUse,
+                    });
+                }
+                Some(format!(
+                    "{}.{}.{}",
+                    event.tenant_id, event.project_id, idempotency_key
+                ))
+            }
+            None => None,
+        };
+
+        let tier = crate::observability::tenant_tier(&tenant.plan);
+        crate::observability::record_payload_size(tier, payload_size);
+
         // Publish to NATS JetStream first (for durability)
-        let _sequence = self.nats_client.publish_event(event).await?;
+        let publish_started = std::time::Instant::now();
+        let published = self
+            .nats_client
+            .publish_event_with_msg_id(event, msg_id.as_deref())
+            .await;
+        crate::observability::record_publish_latency(
+            tier,
+            &event.topic,
+            publish_started.elapsed().as_secs_f64(),
+        );
+        let ack = match published {
+            Ok(ack) => ack,
+            Err(e) => {
+                // Refused by an open breaker: nothing was attempted, and NATS
+                // is the dead-letter store too
+                if circuit_open(&e).is_none() {
+                    self.dead_letter(event, DeadLetterKind::Delivery, &e.to_string())
+                        .await;
+                }
+                if let Some(idempotency_key) = idempotency_key {
+                    if let Err(release_error) = self
+                        .database
+                        .release_idempotency_key(
+                            &event.tenant_id,
+                            &event.project_id,
+                            idempotency_key,
+                            &event.id,
+                        )
+                        .await
+                    {
+                        warn!(
+                            "Failed to release idempotency key {}: {}",
+                            idempotency_key, release_error
+                        );
+                    }
+                }
+                return Err(e);
+            }
+        };
+        let sequence = ack.sequence;
+        self.deduplicator.record(event, sequence);
+
+        // JetStream already stored this message from an earlier attempt whose
+        // outcome was lost, so it must not be persisted or delivered twice; the
+        // producer gets that attempt's event back
+        let published_id = if ack.duplicate {
+            match self
+                .nats_client
+                .event_at_sequence(&event.tenant_id, sequence)
+                .await
+            {
+                Ok(original) => original.id,
+                Err(e) => {
+                    warn!(
+                        "Failed to look up the original of duplicate event {}: {}",
+                        event.id, e
+                    );
+                    event.id.clone()
+                }
+            }
+        } else {
+            event.id.clone()
+        };
+
+        if let Some(idempotency_key) = idempotency_key {
+            if let Err(e) = self
+                .database
+                .complete_idempotency_key(
+                    &event.tenant_id,
+                    &event.project_id,
+                    idempotency_key,
+                    &event.id,
+                    &published_id,
+                    sequence,
+                )
+                .await
+            {
+                error!("Failed to record idempotency key {}: {}", idempotency_key, e);
+            }
+        }
+
+        if ack.duplicate {
+            return Ok(PublishResult::Replayed {
+                event_id: published_id,
+                sequence,
+            });
+        }
 
         // Store event metadata in PostgreSQL
         if let Err(e) = self.database.create_event(event).await {
@@ -91,12 +671,35 @@ impl EventService {
             // but we log the error for monitoring
         }
 
+        // Compact into the latest state for snapshot topics before live fan-out
+        self.snapshots.record(event);
+
+        // No in-process subscribers is fine
+        let _ = self.live_events.send(event.clone());
+
         // Broadcast to WebSocket connections
-        if let Err(e) = crate::websocket::broadcast_event_to_websockets(event).await {
+        if let Err(e) = crate::websocket::broadcast_event_to_websockets(event, Some(sequence)).await {
             warn!("Failed to broadcast event to WebSocket connections: {}", e);
             // Don't fail the publish for WebSocket broadcast errors
         }
 
+        // Webhook deliveries run in the background and never delay the publish
+        if let Some(queue) = &self.webhook_queue {
+            if queue.try_send(event.clone()).is_err() {
+                webhook_events_dropped_counter().inc();
+                warn!("Webhook queue is full; skipping webhooks for event {}", event.id);
+            }
+        }
+
+        // Likewise the Kafka sink, which JetStream's ack above has already cleared
+        if let Some(queue) = &self.kafka_queue {
+            if queue.try_send(event.clone()).is_err() {
+                warn!("Kafka queue is full; dead-lettering event {}", event.id);
+                self.dead_letter(event, DeadLetterKind::Kafka, "Kafka sink queue is full")
+                    .await;
+            }
+        }
+
         // Track usage metrics
         let usage_record = UsageRecord::new(
             event.tenant_id.clone(),
@@ -108,11 +711,14 @@ impl EventService {
                 .and_hms_opt(0, 0, 0)
                 .unwrap()
                 .and_utc(),
-        );
+        )
+        .with_key_id(key_id.map(str::to_string));
 
-        if let Err(e) = self.database.create_usage_record(&usage_record).await {
+        if let Err(e) = self.record_usage(&usage_record).await {
             error!("Failed to track usage metrics: {}", e);
             // Don't fail the publish for usage tracking errors
+        } else {
+            self.check_usage_warnings(&tenant, &event.project_id).await;
         }
 
         info!(
@@ -120,7 +726,454 @@ impl EventService {
             event.id, event.topic, event.tenant_id, event.project_id
         );
 
-        Ok(PublishResult::Success)
+        Ok(PublishResult::Success { sequence })
+    }
+
+    /// Alert and emit a `usage.warning` event for every soft-limit threshold
+    /// the tenant's events this window have crossed for the first time. The
+    /// total is re-read every `USAGE_REFRESH_INTERVAL`, and a threshold fires
+    /// on whichever instance records it first.
+    async fn check_usage_warnings(&self, tenant: &Tenant, project_id: &str) {
+        let Some((warnings, alerting)) = &self.usage_warnings else {
+            return;
+        };
+        let Some(quota) = tenant.plan.monthly_event_limit() else {
+            return;
+        };
+
+        let now = std::time::Instant::now();
+        let (window_start, used) = match warnings.count(&tenant.id, 1, now) {
+            Some(usage) => usage,
+            None => match self.read_usage(&tenant.id).await {
+                Ok((window_start, used)) => {
+                    warnings.refresh(&tenant.id, window_start, used, now);
+                    (window_start, used)
+                }
+                Err(e) => {
+                    warn!("Failed to check usage warnings for tenant {}: {}", tenant.id, e);
+                    return;
+                }
+            },
+        };
+
+        for threshold in warnings.crossed(&tenant.id, used, quota) {
+            // Only the first instance to record a threshold in a window fires it
+            let first = self
+                .database
+                .record_usage_warning(
+                    &tenant.id,
+                    UsageMetric::EventsPublished,
+                    threshold,
+                    window_start,
+                )
+                .await;
+            match first {
+                Ok(first) => {
+                    warnings.mark_fired(&tenant.id, threshold);
+                    if !first {
+                        continue;
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to record usage warning for tenant {}: {}", tenant.id, e);
+                    continue;
+                }
+            }
+            let details = serde_json::json!({
+                "metric": UsageMetric::EventsPublished.as_str(),
+                "threshold_percent": threshold,
+                "used": used,
+                "quota": quota,
+                "window_start": window_start,
+            });
+            warn!(
+                "Tenant {} has used {}% of its monthly events ({}/{})",
+                tenant.id, threshold, used, quota
+            );
+            alerting
+                .alert_billing(
+                    &tenant.id,
+                    &format!("{}% of monthly events used", threshold),
+                    details.clone(),
+                )
+                .await;
+
+            let warning = Event::new(
+                tenant.id.clone(),
+                project_id.to_string(),
+                USAGE_WARNING_TOPIC.to_string(),
+                details,
+            );
+            if let Err(e) = self.publish_system_event(&warning).await {
+                warn!("Failed to publish usage warning for tenant {}: {}", tenant.id, e);
+            }
+        }
+    }
+
+    /// The tenant's current usage window and the events published in it.
+    /// Tenants whose cycle has been started by the rollover job use it;
+    /// others fall back to the calendar month
+    async fn read_usage(&self, tenant_id: &str) -> Result<(chrono::DateTime<chrono::Utc>, i64)> {
+        let window_start = match self.database.get_usage_cycle_start(tenant_id).await? {
+            Some(cycle_start) => cycle_start,
+            None => usage_window_start(chrono::Utc::now()),
+        };
+        let used = self
+            .database
+            .get_usage_for_tenant_since(tenant_id, UsageMetric::EventsPublished, window_start)
+            .await?;
+        Ok((window_start, used))
+    }
+
+    /// Publish a platform-generated event to the tenant's stream and live
+    /// subscribers, bypassing validation and usage tracking
+    async fn publish_system_event(&self, event: &Event) -> Result<()> {
+        let ack = self.nats_client.publish_event_with_msg_id(event, None).await?;
+        let _ = self.live_events.send(event.clone());
+        crate::websocket::broadcast_event_to_websockets(event, Some(ack.sequence)).await?;
+        Ok(())
+    }
+
+    /// Move a failed event to its tenant/project's dead-letter subject,
+    /// returning its dead letter id. Failing to dead-letter is logged rather
+    /// than masking the original failure.
+    pub async fn dead_letter(
+        &self,
+        event: &Event,
+        kind: DeadLetterKind,
+        reason: &str,
+    ) -> Option<u64> {
+        match self.nats_client.publish_dead_letter(event, kind, reason).await {
+            Ok(id) => Some(id),
+            Err(e) => {
+                error!("Failed to dead-letter event {}: {}", event.id, e);
+                None
+            }
+        }
+    }
+
+    /// Dead-lettered events for a tenant (optionally one project) since `since`
+    pub async fn list_dead_letters(
+        &self,
+        tenant_id: &str,
+        project_id: Option<&str>,
+        since: chrono::DateTime<chrono::Utc>,
+        limit: usize,
+    ) -> Result<Vec<DeadLetter>> {
+        self.nats_client
+            .list_dead_letters(tenant_id, project_id, since, limit)
+            .await
+    }
+
+    /// Retry a dead-lettered event, e.g. after fixing its topic schema.
+    /// Returns `None` when the tenant has no dead letter with this id.
+    ///
+    /// A webhook, Kafka or subscription dead letter goes only to the webhook,
+    /// sink or subscription it failed for, and is removed only once that
+    /// succeeds. Any other is re-published and removed once the publish is
+    /// accepted; one that is rejected, e.g. by validation, is kept.
+    pub async fn replay_dead_letter(
+        &self,
+        tenant_id: &str,
+        id: u64,
+    ) -> Result<Option<(DeadLetter, DeadLetterReplay)>> {
+        let dead_letter = match self.nats_client.get_dead_letter(tenant_id, id).await? {
+            Some(dead_letter) => dead_letter,
+            None => return Ok(None),
+        };
+
+        let result = match dead_letter.kind {
+            DeadLetterKind::Webhook => self.redeliver_webhook(&dead_letter).await?,
+            DeadLetterKind::Kafka => self.redeliver_kafka(&dead_letter).await,
+            DeadLetterKind::Subscription => self.redeliver_subscription(&dead_letter).await?,
+            DeadLetterKind::Validation | DeadLetterKind::Delivery => {
+                let mut dead_letter_id = None;
+                let result = self
+                    .publish_checked(&dead_letter.event, None, None, &mut dead_letter_id)
+                    .await?;
+                match result {
+                    PublishResult::Success { .. }
+                    | PublishResult::Replayed { .. }
+                    | PublishResult::Deduplicated { .. } => DeadLetterReplay::Published(result),
+                    result => DeadLetterReplay::Rejected {
+                        result,
+                        dead_letter_id,
+                    },
+                }
+            }
+        };
+        if matches!(
+            result,
+            DeadLetterReplay::Failed(_) | DeadLetterReplay::Rejected { .. }
+        ) {
+            return Ok(Some((dead_letter, result)));
+        }
+        self.nats_client.delete_dead_letter(id).await?;
+
+        info!(
+            "Replayed dead letter {} (event {}) for tenant {}",
+            id, dead_letter.event.id, tenant_id
+        );
+        Ok(Some((dead_letter, result)))
+    }
+
+    /// Deliver a webhook dead letter to the webhook it failed for, and no other
+    async fn redeliver_webhook(&self, dead_letter: &DeadLetter) -> Result<DeadLetterReplay> {
+        let Some(dispatcher) = &self.webhook_dispatcher else {
+            return Ok(DeadLetterReplay::Failed("Webhooks are disabled".to_string()));
+        };
+        let Some(webhook_id) = &dead_letter.target else {
+            return Ok(DeadLetterReplay::Failed(
+                "Dead letter doesn't name the webhook it failed for".to_string(),
+            ));
+        };
+        let webhook = match self
+            .database
+            .get_webhook(&dead_letter.event.tenant_id, webhook_id)
+            .await?
+        {
+            Some(webhook) if webhook.active => webhook,
+            Some(_) => {
+                return Ok(DeadLetterReplay::Failed(format!(
+                    "Webhook {} is disabled",
+                    webhook_id
+                )))
+            }
+            None => {
+                return Ok(DeadLetterReplay::Failed(format!(
+                    "Webhook {} no longer exists",
+                    webhook_id
+                )))
+            }
+        };
+
+        Ok(match dispatcher.deliver(&webhook, &dead_letter.event).await {
+            Ok(attempts) => DeadLetterReplay::Redelivered { attempts },
+            Err(e) => DeadLetterReplay::Failed(e.to_string()),
+        })
+    }
+
+    /// Write a Kafka dead letter to the Kafka sink, and nowhere else
+    #[cfg(feature = "kafka")]
+    async fn redeliver_kafka(&self, dead_letter: &DeadLetter) -> DeadLetterReplay {
+        let Some(sink) = &self.kafka_sink else {
+            return DeadLetterReplay::Failed("The Kafka sink is disabled".to_string());
+        };
+        match sink.send(&dead_letter.event).await {
+            Ok(attempts) => DeadLetterReplay::Redelivered { attempts },
+            Err(e) => DeadLetterReplay::Failed(e.to_string()),
+        }
+    }
+
+    #[cfg(not(feature = "kafka"))]
+    async fn redeliver_kafka(&self, _dead_letter: &DeadLetter) -> DeadLetterReplay {
+        DeadLetterReplay::Failed("This build has no Kafka sink".to_string())
+    }
+
+    /// Re-publish a subscription dead letter for the subscription it failed
+    /// for; other subscriptions, webhooks and the Kafka sink don't see it
+    async fn redeliver_subscription(&self, dead_letter: &DeadLetter) -> Result<DeadLetterReplay> {
+        let Some(consumer_name) = &dead_letter.target else {
+            return Ok(DeadLetterReplay::Failed(
+                "Dead letter doesn't name the subscription it failed for".to_string(),
+            ));
+        };
+        let ack = self
+            .nats_client
+            .redeliver_to_subscription(&dead_letter.event, consumer_name)
+            .await?;
+        Ok(DeadLetterReplay::Requeued {
+            sequence: ack.sequence,
+        })
+    }
+
+    /// Add a usage record to its window's total and announce it to live usage subscribers
+    pub async fn record_usage(&self, usage: &UsageRecord) -> Result<()> {
+        self.database
+            .increment_usage(
+                &usage.tenant_id,
+                &usage.project_id,
+                usage.metric.clone(),
+                usage.quantity,
+                usage.window_start,
+                usage.key_id.as_deref(),
+            )
+            .await?;
+        // No usage subscribers is fine
+        let _ = self.usage_updates.send(usage.clone());
+        Ok(())
+    }
+
+    /// Stream usage records as they are stored for a tenant. A subscriber that
+    /// falls more than [`USAGE_UPDATES_CAPACITY`] records behind is dropped, so
+    /// usage tracking never waits on it.
+    pub fn subscribe_usage(&self, tenant_id: &str) -> impl Stream<Item = UsageRecord> + Send {
+        let tenant_id = tenant_id.to_string();
+
+        BroadcastStream::new(self.usage_updates.subscribe())
+            .take_while(|received| {
+                let lagged = matches!(received, Err(BroadcastStreamRecvError::Lagged(_)));
+                if lagged {
+                    warn!("Usage subscriber fell behind and was dropped");
+                }
+                futures_util::future::ready(!lagged)
+            })
+            .filter_map(move |received| {
+                let usage = received.ok().filter(|usage| usage.tenant_id == tenant_id);
+                futures_util::future::ready(usage)
+            })
+    }
+
+    /// Check an event against its topic schema under the project's validation
+    /// mode. Gives whether the payload conformed and the schema version it was
+    /// checked against, `None` when validation is off, or why it was rejected.
+    async fn check_schema(
+        &self,
+        event: &Event,
+        settings: &ProjectSettings,
+    ) -> std::result::Result<Option<(bool, Option<i32>)>, String> {
+        if settings.validation_mode == ValidationMode::Off {
+            return Ok(None);
+        }
+
+        // Pick up schemas registered or changed through the registry
+        if let Err(e) = self
+            .schema_validator
+            .sync_topic(&event.project_id, &event.topic)
+            .await
+        {
+            warn!("Failed to load schema for topic {}: {}", event.topic, e);
+        }
+
+        let active_version = || {
+            self.schema_validator
+                .get_schema(&event.project_id, &event.topic)
+                .map(|schema| schema.version as i32)
+        };
+        // Violation messages quote payload values, so mask sensitive ones
+        let redacted = |e: &dyn std::fmt::Display| {
+            self.redactor
+                .redact_message(&event.topic, &event.payload, &e.to_string())
+        };
+        match self.schema_validator.validate_project_event(
+            &event.project_id,
+            &event.topic,
+            &event.payload,
+        ) {
+            Ok(SchemaCheck::Valid) => Ok(Some((true, active_version()))),
+            Ok(SchemaCheck::Unregistered) => {
+                // Zero-config projects adopt the first event's shape as the topic schema
+                if !settings.auto_register_schema_from_first_event {
+                    return Ok(Some((true, None)));
+                }
+                match self
+                    .schema_validator
+                    .store_inferred_schema(
+                        &event.tenant_id,
+                        &event.project_id,
+                        &event.topic,
+                        &event.payload,
+                    )
+                    .await
+                {
+                    Ok(version) => {
+                        info!(
+                            "Auto-registered schema v{} for topic {} in project {}",
+                            version, event.topic, event.project_id
+                        );
+                        Ok(Some((true, Some(version as i32))))
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Failed to auto-register schema for topic {}: {}",
+                            event.topic, e
+                        );
+                        Ok(Some((true, None)))
+                    }
+                }
+            }
+            Ok(SchemaCheck::Warning(e)) => {
+                warn!(
+                    "Event {} violates warn-mode schema for topic {}: {}",
+                    event.id,
+                    event.topic,
+                    redacted(&e)
+                );
+                record_schema_violation(true);
+                Ok(Some((false, active_version())))
+            }
+            Err(e)
+                if settings.validation_mode == ValidationMode::Advisory
+                    && e.is::<SchemaValidationError>() =>
+            {
+                warn!(
+                    "Event {} violates schema for topic {} (advisory): {}",
+                    event.id,
+                    event.topic,
+                    redacted(&e)
+                );
+                record_schema_violation(true);
+                Ok(Some((false, active_version())))
+            }
+            Err(e) => {
+                if e.is::<SchemaValidationError>() {
+                    record_schema_violation(false);
+                }
+                let message = redacted(&e);
+                warn!("Event validation failed for topic {}: {}", event.topic, message);
+                Err(format!("Event validation failed: {}", message))
+            }
+        }
+    }
+
+    /// Publish a batch of events in order. Each event is validated against its
+    /// topic schema and published to JetStream on its own, so one failure does
+    /// not affect the others; results are returned in input order.
+    pub async fn publish_batch(
+        &self,
+        events: &[Event],
+        key_id: Option<&str>,
+    ) -> Result<Vec<Result<PublishResult>>> {
+        if events.len() > self.max_batch_size {
+            return Err(anyhow!(
+                "Batch of {} events exceeds the limit of {}",
+                events.len(),
+                self.max_batch_size
+            ));
+        }
+
+        let mut results = Vec::with_capacity(events.len());
+        for event in events {
+            results.push(self.publish_event_with_key(event, key_id).await);
+        }
+        Ok(results)
+    }
+
+    /// Fan an echo event back to the tenant/project's live subscribers only;
+    /// skips schema validation, persistence and usage tracking
+    async fn publish_echo(&self, event: &Event) -> Result<PublishResult> {
+        if !self.echo_enabled {
+            return Ok(PublishResult::ValidationFailed(format!(
+                "Topic {} is reserved and not enabled",
+                ECHO_TOPIC
+            )));
+        }
+
+        if let Err(e) = crate::websocket::broadcast_event_to_websockets(event, None).await {
+            warn!("Failed to broadcast echo event to WebSocket connections: {}", e);
+        }
+        if let Err(e) = crate::sse::broadcast_event_to_sse(event, None).await {
+            warn!("Failed to broadcast echo event to SSE connections: {}", e);
+        }
+        let _ = self.live_events.send(event.clone());
+
+        info!(
+            "Echoed event {} for tenant/project: {}/{}",
+            event.id, event.tenant_id, event.project_id
+        );
+
+        // Echo events are never persisted, so there is no stream sequence
+        Ok(PublishResult::Success { sequence: 0 })
     }
 
     /// Publish an event with validation and persistence (legacy method)
@@ -131,12 +1184,16 @@ impl EventService {
         topic: &str,
         payload: serde_json::Value,
     ) -> Result<PublishResult> {
-        let event = Event::new(
-            tenant_id.to_string(),
-            project_id.to_string(),
-            topic.to_string(),
-            payload,
-        );
+        let event = match Event::builder()
+            .tenant_id(tenant_id)
+            .project_id(project_id)
+            .topic(topic)
+            .payload(payload)
+            .build()
+        {
+            Ok(event) => event,
+            Err(e) => return Ok(PublishResult::ValidationFailed(e.to_string())),
+        };
 
         self.publish_event(&event).await
     }
@@ -165,11 +1222,9 @@ impl EventService {
             .await?
             .ok_or_else(|| anyhow!("Project not found: {}", project_id))?;
 
-        // Create a broadcast channel for real-time events
-        let (_sender, receiver) = broadcast::channel(1000);
-
-        // For now, we'll create a simple subscription
-        // In a real implementation, this would connect to NATS consumers
+        // Receive live events published through this service; filtering to the
+        // subscription's tenant, project and topics happens on the consumer side
+        let receiver = self.live_events.subscribe();
         let consumer_name = format!("graphql_{}_{}", tenant_id, project_id);
 
         info!(
@@ -219,6 +1274,10 @@ impl EventService {
             topics: topics.clone(),
             consumer_name: consumer_name.clone(),
             durable,
+            max_ack_pending: None,
+            explicit_ack: durable,
+            ack_wait: None,
+            max_deliver: None,
         };
 
         // Create the consumer in NATS
@@ -241,7 +1300,77 @@ impl EventService {
         })
     }
 
-    /// Replay events with cursor support
+    /// Deliver an acking subscription's events through `send`, acking each
+    /// only once `send` succeeds.
+    ///
+    /// An event `send` fails to deliver for a transient reason is nacked and
+    /// redelivered after a backoff; once the subscription's `max_deliver`
+    /// deliveries have all failed it is dead-lettered instead. Returns when
+    /// the subscriber disconnects, leaving the event in flight for the next
+    /// consumer of the subscription.
+    pub async fn deliver_subscription<F, Fut>(
+        &self,
+        config: &SubscriptionConfig,
+        mut send: F,
+    ) -> Result<()>
+    where
+        F: FnMut(Event) -> Fut,
+        Fut: Future<Output = Result<(), SendError>>,
+    {
+        let max_deliver = config.max_deliver();
+        let deliveries = self.nats_client.subscription_deliveries(config).await?;
+        tokio::pin!(deliveries);
+
+        while let Some(delivery) = deliveries.next().await {
+            let delivery = delivery?;
+            let settled = match send(delivery.event.clone()).await {
+                Ok(()) => delivery.ack().await,
+                Err(SendError::Disconnected) => {
+                    // Don't make the next consumer wait out ack_wait
+                    if let Err(e) = delivery.nack(Duration::ZERO).await {
+                        warn!("{}", e);
+                    }
+                    return Ok(());
+                }
+                Err(SendError::Transient(reason))
+                    if max_deliver > 0 && delivery.delivered >= max_deliver =>
+                {
+                    warn!(
+                        "Dead-lettering event {} for subscription {} after {} deliveries: {}",
+                        delivery.event.id, config.consumer_name, delivery.delivered, reason
+                    );
+                    if let Err(e) = self
+                        .nats_client
+                        .publish_targeted_dead_letter(
+                            &delivery.event,
+                            DeadLetterKind::Subscription,
+                            Some(&config.consumer_name),
+                            &reason,
+                        )
+                        .await
+                    {
+                        error!("Failed to dead-letter event {}: {}", delivery.event.id, e);
+                    }
+                    delivery.term().await
+                }
+                Err(SendError::Transient(reason)) => {
+                    let delay = redelivery_delay(delivery.delivered);
+                    warn!(
+                        "Delivery {} of event {} to subscription {} failed, redelivering in {:?}: {}",
+                        delivery.delivered, delivery.event.id, config.consumer_name, delay, reason
+                    );
+                    delivery.nack(delay).await
+                }
+            };
+            // An unsettled event is redelivered once ack_wait runs out
+            if let Err(e) = settled {
+                warn!("{}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Replay events with cursor support, optionally upgrading them with a transform
     pub async fn replay_events(
         &self,
         tenant_id: &str,
@@ -249,6 +1378,7 @@ impl EventService {
         topic: Option<String>,
         cursor: Option<crate::nats::EventCursor>,
         limit: Option<usize>,
+        transform: Option<TransformPipeline>,
     ) -> Result<Vec<(Event, crate::nats::EventCursor)>> {
         // Validate tenant and project
         let tenant = self
@@ -273,7 +1403,9 @@ impl EventService {
             project_id: project_id.to_string(),
             topic,
             cursor,
+            from_timestamp: None,
             limit,
+            transform,
         };
 
         // Get events from NATS
@@ -289,9 +1421,46 @@ impl EventService {
         Ok(events)
     }
 
+    /// Stream persisted events for a tenant/project from a sequence or start time
+    pub async fn replay(&self, request: ReplayRequest) -> Result<impl Stream<Item = Event>> {
+        Ok(self
+            .replay_with_cursors(request)
+            .await?
+            .map(|(event, _)| event))
+    }
+
+    /// Like [`EventService::replay`], pairing each event with its stream cursor
+    pub async fn replay_with_cursors(
+        &self,
+        request: ReplayRequest,
+    ) -> Result<impl Stream<Item = (Event, crate::nats::EventCursor)>> {
+        let tenant = self
+            .database
+            .get_tenant(&request.tenant_id)
+            .await?
+            .ok_or_else(|| anyhow!("Tenant not found: {}", request.tenant_id))?;
+
+        if !tenant.is_active() {
+            return Err(anyhow!("Tenant is not active: {}", request.tenant_id));
+        }
+
+        self.database
+            .get_project_with_tenant(&request.tenant_id, &request.project_id)
+            .await?
+            .ok_or_else(|| anyhow!("Project not found: {}", request.project_id))?;
+
+        self.nats_client.replay_with_cursors(request).await
+    }
+
+    /// First and last sequence currently retained in the stream holding the
+    /// tenant's events
+    pub async fn stream_sequence_bounds(&self, tenant_id: &str) -> Result<(u64, u64)> {
+        self.nats_client.sequence_bounds(tenant_id).await
+    }
+
     /// Delete a subscription
-    pub async fn delete_subscription(&self, consumer_name: &str) -> Result<()> {
-        self.nats_client.delete_consumer(consumer_name).await?;
+    pub async fn delete_subscription(&self, tenant_id: &str, consumer_name: &str) -> Result<()> {
+        self.nats_client.delete_consumer(tenant_id, consumer_name).await?;
         info!("Deleted subscription: {}", consumer_name);
         Ok(())
     }
@@ -313,6 +1482,16 @@ impl EventService {
         &self.nats_client
     }
 
+    /// Get the schema validator shared with this service
+    pub fn schema_validator(&self) -> &SchemaValidator {
+        &self.schema_validator
+    }
+
+    /// Get the snapshot store for stateful topics
+    pub fn snapshots(&self) -> &SnapshotStore {
+        &self.snapshots
+    }
+
     /// Get the database connection
     pub fn database(&self) -> &Database {
         &self.database
@@ -325,11 +1504,17 @@ mod tests {
 
     #[test]
     fn test_publish_result() {
-        let result = PublishResult::Success;
+        let result = PublishResult::Success { sequence: 42 };
 
         match result {
-            PublishResult::Success => { /* Success case handled */ },
+            PublishResult::Success { sequence } => assert_eq!(sequence, 42),
             PublishResult::ValidationFailed(_) => panic!("Validation should not fail in tests"),
+            PublishResult::Deduplicated { .. } => panic!("Event should not be deduplicated in tests"),
+            PublishResult::Replayed { .. } | PublishResult::IdempotencyKeyInUse => {
+                panic!("Event should not be replayed in tests")
+            }
+            PublishResult::RateLimited(_) => panic!("Event should not be rate limited in tests"),
+            PublishResult::PayloadTooLarge { .. } => panic!("Payload should fit in tests"),
         }
     }
 
@@ -347,4 +1532,53 @@ mod tests {
         assert_eq!(subscription.consumer_name, "websocket_consumer");
         assert_eq!(subscription.topics.len(), 2);
     }
+
+    fn event(tenant_id: &str, project_id: &str, topic: &str) -> Event {
+        Event::new(
+            tenant_id.to_string(),
+            project_id.to_string(),
+            topic.to_string(),
+            serde_json::json!({"topic": topic}),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_subscription_stream_filters_tenant_project_and_topics() {
+        let (tx, rx) = broadcast::channel(100);
+        let subscription = EventSubscription {
+            consumer_name: "graphql_tenant_123_project_456".to_string(),
+            tenant_id: "tenant_123".to_string(),
+            project_id: "project_456".to_string(),
+            topics: vec!["user.*".to_string()],
+            receiver: rx,
+        };
+        let stream = subscription.into_stream();
+
+        tx.send(event("tenant_999", "project_456", "user.created")).unwrap();
+        tx.send(event("tenant_123", "project_999", "user.created")).unwrap();
+        tx.send(event("tenant_123", "project_456", "order.created")).unwrap();
+        tx.send(event("tenant_123", "project_456", "user.created")).unwrap();
+        tx.send(event("tenant_123", "project_456", "user.deleted")).unwrap();
+        drop(tx);
+
+        let delivered: Vec<String> = stream.map(|e| e.topic).collect().await;
+        assert_eq!(delivered, vec!["user.created", "user.deleted"]);
+    }
+
+    #[tokio::test]
+    async fn test_dropping_subscription_stream_releases_receiver() {
+        let (tx, rx) = broadcast::channel::<Event>(100);
+        let subscription = EventSubscription {
+            consumer_name: "graphql_tenant_123_project_456".to_string(),
+            tenant_id: "tenant_123".to_string(),
+            project_id: "project_456".to_string(),
+            topics: vec![],
+            receiver: rx,
+        };
+
+        let stream = subscription.into_stream();
+        assert!(tx.receiver_count() > 0);
+        drop(stream);
+        assert_eq!(tx.receiver_count(), 0);
+    }
 }
@@ -1,22 +1,37 @@
+use async_graphql::dataloader::DataLoader;
+use async_graphql::extensions::{Extension, ExtensionContext, ExtensionFactory, NextValidation};
 use async_graphql::{
-    Context, Enum, Error, ErrorExtensions, FieldResult, InputObject, Object, Schema, SimpleObject,
-    Subscription, Union, ID,
+    ComplexObject, Context, Enum, Error, ErrorExtensions, FieldResult, InputObject, Object, Schema,
+    ServerError, SimpleObject, Subscription, Union, ValidationResult, ID,
 };
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
 use axum::extract::ws::WebSocketUpgrade;
+use axum::extract::State;
+use axum::Extension;
 use chrono::{DateTime, Utc};
-use futures_util::Stream;
+use futures_util::{future, Stream, StreamExt};
+use std::collections::HashMap;
 use std::fmt;
 use std::pin::Pin;
+use std::time::Duration;
 use tracing::info;
 
+use crate::api::{
+    change_tenant_plan, clamp_page_size, update_project_limits, AppState, PlanLimitWarning,
+    ProjectLimitsError, DEFAULT_MAX_PAGE_SIZE,
+};
+use crate::audit;
 use crate::auth::{AuthContext, AuthError, AuthService};
+use crate::config::GraphQLConfig;
 use crate::database::Database;
-use crate::event_service::{EventService, PublishResult};
+use crate::event_service::{BatchItem, EventService, PublishResult};
+use crate::graphql_loaders::{ApiKeysByProject, ProjectsByTenant};
+use crate::graphql_ws::{serve_graphql_ws, GRAPHQL_TRANSPORT_WS_PROTOCOL};
 use crate::models::{
-    ApiKey, BillingPlan, Event, Project, ProjectLimits, Scope, Tenant, TenantStatus, UsageMetric,
-    UsageRecord,
+    ApiKey, BillingPlan, Event, EventPageCursor, EventQuery, Project, ProjectLimits,
+    ProjectLimitsUpdate, Scope, Tenant, TenantStatus, UsageMetric, UsageRecord,
 };
+use crate::schema_validator::{validate_topic_format, SchemaMode, SchemaValidator, TopicSchema};
 
 /// GraphQL Schema type
 pub type ApiSchema = Schema<QueryRoot, MutationRoot, SubscriptionRoot>;
@@ -28,6 +43,8 @@ pub enum GraphQLError {
     Forbidden,
     NotFound,
     ValidationError(String),
+    /// Over a rate limit; retry after this many seconds
+    RateLimited(u64),
     InternalError(String),
 }
 
@@ -38,6 +55,7 @@ impl fmt::Display for GraphQLError {
             GraphQLError::Forbidden => write!(f, "Forbidden"),
             GraphQLError::NotFound => write!(f, "Not found"),
             GraphQLError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
+            GraphQLError::RateLimited(_) => write!(f, "Rate limit exceeded"),
             GraphQLError::InternalError(msg) => write!(f, "Internal error: {}", msg),
         }
     }
@@ -59,6 +77,11 @@ impl ErrorExtensions for GraphQLError {
                 .extend_with(|_, e| {
                     e.set("code", "VALIDATION_ERROR");
                 }),
+            GraphQLError::RateLimited(retry_after_secs) => Error::new("Rate limit exceeded")
+                .extend_with(|_, e| {
+                    e.set("code", "RATE_LIMITED");
+                    e.set("retry_after_seconds", *retry_after_secs);
+                }),
             GraphQLError::InternalError(msg) => Error::new(format!("Internal error: {}", msg))
                 .extend_with(|_, e| {
                     e.set("code", "INTERNAL_ERROR");
@@ -76,7 +99,7 @@ impl From<AuthError> for GraphQLError {
             AuthError::InsufficientScope { .. } | AuthError::TenantSuspended => {
                 GraphQLError::Forbidden
             }
-            AuthError::RateLimitExceeded => GraphQLError::Forbidden,
+            AuthError::RateLimitExceeded(_) => GraphQLError::Forbidden,
             _ => GraphQLError::InternalError(err.to_string()),
         }
     }
@@ -90,6 +113,7 @@ impl From<anyhow::Error> for GraphQLError {
 
 /// GraphQL representation of Tenant
 #[derive(SimpleObject, Clone)]
+#[graphql(complex)]
 pub struct GqlTenant {
     pub id: ID,
     pub name: String,
@@ -116,6 +140,7 @@ impl From<Tenant> for GqlTenant {
 
 /// GraphQL representation of Project
 #[derive(SimpleObject, Clone)]
+#[graphql(complex)]
 pub struct GqlProject {
     pub id: ID,
     pub tenant_id: ID,
@@ -123,6 +148,7 @@ pub struct GqlProject {
     pub limits: GqlProjectLimits,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 impl From<Project> for GqlProject {
@@ -134,6 +160,113 @@ impl From<Project> for GqlProject {
             limits: project.limits.into(),
             created_at: project.created_at,
             updated_at: project.updated_at,
+            archived_at: project.archived_at,
+        }
+    }
+}
+
+#[ComplexObject]
+impl GqlTenant {
+    /// The tenant's projects; lookups across tenants in one query are batched
+    async fn projects(&self, ctx: &Context<'_>) -> FieldResult<Vec<GqlProject>> {
+        let auth = get_auth_context(ctx)?;
+        if self.id.as_str() != auth.tenant_id {
+            return Err(GraphQLError::Forbidden.extend());
+        }
+
+        let projects = ctx
+            .data::<DataLoader<ProjectsByTenant>>()?
+            .load_one(self.id.to_string())
+            .await
+            .map_err(|e| GraphQLError::InternalError(e.to_string()).extend())?
+            .unwrap_or_default();
+
+        Ok(projects.into_iter().map(Into::into).collect())
+    }
+}
+
+#[ComplexObject]
+impl GqlProject {
+    /// The project's API keys (admin read required); lookups across projects
+    /// in one query are batched
+    async fn api_keys(&self, ctx: &Context<'_>) -> FieldResult<Vec<GqlApiKey>> {
+        let auth = get_auth_context(ctx)?;
+        check_scope(&auth, &Scope::AdminRead)?;
+        if self.tenant_id.as_str() != auth.tenant_id {
+            return Err(GraphQLError::Forbidden.extend());
+        }
+
+        let api_keys = ctx
+            .data::<DataLoader<ApiKeysByProject>>()?
+            .load_one(self.id.to_string())
+            .await
+            .map_err(|e| GraphQLError::InternalError(e.to_string()).extend())?
+            .unwrap_or_default();
+
+        Ok(api_keys.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Pagination state for cursor-paged lists
+#[derive(SimpleObject, Clone)]
+pub struct GqlPageInfo {
+    pub has_next_page: bool,
+    pub has_previous_page: bool,
+    pub start_cursor: Option<String>,
+    pub end_cursor: Option<String>,
+}
+
+/// A page of events, newest first
+#[derive(SimpleObject, Clone)]
+pub struct GqlEventPage {
+    pub events: Vec<GqlEvent>,
+    pub page_info: GqlPageInfo,
+}
+
+/// Outcome for one event of a batch publish: an id and sequence, or an error
+#[derive(SimpleObject, Clone)]
+pub struct GqlBatchPublishResult {
+    pub event_id: Option<ID>,
+    pub sequence: Option<u64>,
+    pub error: Option<String>,
+}
+
+impl GqlBatchPublishResult {
+    fn published(event_id: String, sequence: u64) -> Self {
+        Self {
+            event_id: Some(ID(event_id)),
+            sequence: Some(sequence),
+            error: None,
+        }
+    }
+
+    fn failed(error: String) -> Self {
+        Self {
+            event_id: None,
+            sequence: None,
+            error: Some(error),
+        }
+    }
+}
+
+/// Active schema registered for a topic
+#[derive(SimpleObject, Clone)]
+pub struct GqlTopicSchema {
+    pub project_id: ID,
+    pub topic: String,
+    pub version: u32,
+    pub mode: String,
+    pub schema: String, // JSON string
+}
+
+impl GqlTopicSchema {
+    fn new(project_id: String, topic: String, schema: TopicSchema) -> Self {
+        Self {
+            project_id: ID(project_id),
+            topic,
+            version: schema.version,
+            mode: schema.mode.as_str().to_string(),
+            schema: schema.schema.to_string(),
         }
     }
 }
@@ -147,6 +280,21 @@ pub struct GqlEvent {
     pub topic: String,
     pub payload: String, // JSON as string for GraphQL
     pub published_at: DateTime<Utc>,
+    pub partition_key: Option<String>,
+    /// Producer headers, as a JSON object of strings
+    pub headers: HashMap<String, String>,
+    /// Producer metadata, as a JSON object of strings
+    pub metadata: HashMap<String, String>,
+    /// Event attributes, as a JSON object of strings
+    pub attributes: HashMap<String, String>,
+    /// Whether the payload conformed to the topic schema when published, even
+    /// if the schema only warns; null when validation was off or for events
+    /// published before this was recorded
+    pub schema_valid: Option<bool>,
+    /// Version of the topic schema the payload was checked against
+    pub schema_version: Option<i32>,
+    /// JetStream sequence, set when returned from a publish
+    pub sequence: Option<u64>,
 }
 
 impl From<Event> for GqlEvent {
@@ -158,6 +306,13 @@ impl From<Event> for GqlEvent {
             topic: event.topic,
             payload: event.payload.to_string(),
             published_at: event.published_at,
+            partition_key: event.partition_key,
+            headers: event.headers,
+            metadata: event.metadata,
+            attributes: event.attributes,
+            schema_valid: event.schema_valid,
+            schema_version: event.schema_version,
+            sequence: None,
         }
     }
 }
@@ -168,24 +323,32 @@ pub struct GqlApiKey {
     pub id: ID,
     pub tenant_id: ID,
     pub project_id: ID,
+    /// Prefix and last four characters of the raw key
+    pub key_hint: Option<String>,
     pub scopes: Vec<GqlScope>,
     pub rate_limit_per_sec: i32,
+    pub allowed_topics: Vec<String>,
     pub is_active: bool,
     pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
 impl From<ApiKey> for GqlApiKey {
     fn from(api_key: ApiKey) -> Self {
+        let key_hint = api_key.key_hint();
         Self {
             id: ID(api_key.id),
             tenant_id: ID(api_key.tenant_id),
             project_id: ID(api_key.project_id),
+            key_hint,
             scopes: api_key.scopes.into_iter().map(Into::into).collect(),
             rate_limit_per_sec: api_key.rate_limit_per_sec,
+            allowed_topics: api_key.allowed_topics,
             is_active: api_key.is_active,
             expires_at: api_key.expires_at,
+            last_used_at: api_key.last_used_at,
             created_at: api_key.created_at,
             updated_at: api_key.updated_at,
         }
@@ -296,6 +459,9 @@ pub struct GqlProjectLimits {
     pub max_connections: i32,
     pub max_events_per_sec: i32,
     pub max_payload_size: i32,
+    pub delivery_buffer_size: i32,
+    pub idle_timeout_secs: i32,
+    pub max_subscriptions_per_connection: i32,
 }
 
 impl From<ProjectLimits> for GqlProjectLimits {
@@ -304,6 +470,9 @@ impl From<ProjectLimits> for GqlProjectLimits {
             max_connections: limits.max_connections,
             max_events_per_sec: limits.max_events_per_sec,
             max_payload_size: limits.max_payload_size,
+            delivery_buffer_size: limits.delivery_buffer_size,
+            idle_timeout_secs: limits.idle_timeout_secs,
+            max_subscriptions_per_connection: limits.max_subscriptions_per_connection,
         }
     }
 }
@@ -356,6 +525,12 @@ pub struct GqlEnterprisePlan {
 pub struct EventInput {
     pub topic: String,
     pub payload: String, // JSON as string
+    /// Events with the same key are delivered and replayed in the order they
+    /// were accepted; different keys may be processed in parallel, one
+    /// `.p{n}` partition subject each
+    pub partition_key: Option<String>,
+    /// Producer metadata delivered alongside the payload; `ce-*` names are reserved
+    pub headers: Option<HashMap<String, String>>,
 }
 
 #[derive(InputObject)]
@@ -363,6 +538,8 @@ pub struct CreateApiKeyInput {
     pub project_id: ID,
     pub scopes: Vec<GqlScope>,
     pub rate_limit_per_sec: i32,
+    /// NATS-style topic patterns the key is limited to; omitted allows all topics
+    pub allowed_topics: Option<Vec<String>>,
     pub expires_at: Option<DateTime<Utc>>,
 }
 
@@ -380,6 +557,56 @@ pub struct CreateBillingPlanInput {
     pub unlimited: Option<bool>,
 }
 
+// Convert a plan input to a BillingPlan, filling in the plan type's defaults
+fn billing_plan_from_input(input: CreateBillingPlanInput) -> FieldResult<BillingPlan> {
+    match input.plan_type.as_str() {
+        "free" => Ok(BillingPlan::Free {
+            monthly_events: input.monthly_events.unwrap_or(10000),
+        }),
+        "pro" => Ok(BillingPlan::Pro {
+            monthly_events: input.monthly_events.unwrap_or(100000),
+            price_per_event: input.price_per_event.unwrap_or(0.001),
+        }),
+        "enterprise" => Ok(BillingPlan::Enterprise {
+            unlimited: input.unlimited.unwrap_or(true),
+        }),
+        _ => Err(GraphQLError::ValidationError("Invalid plan type".to_string()).extend()),
+    }
+}
+
+#[derive(InputObject)]
+pub struct UpdateTenantPlanInput {
+    pub tenant_id: ID,
+    pub plan: CreateBillingPlanInput,
+}
+
+/// Usage that already exceeds a tenant's new plan
+#[derive(SimpleObject, Clone)]
+pub struct GqlPlanLimitWarning {
+    pub code: String,
+    pub metric: String,
+    pub usage: i64,
+    pub limit: i64,
+}
+
+impl From<PlanLimitWarning> for GqlPlanLimitWarning {
+    fn from(warning: PlanLimitWarning) -> Self {
+        Self {
+            code: warning.code,
+            metric: warning.metric,
+            usage: warning.usage,
+            limit: warning.limit,
+        }
+    }
+}
+
+/// Result of a plan change
+#[derive(SimpleObject, Clone)]
+pub struct GqlTenantPlanChange {
+    pub tenant: GqlTenant,
+    pub warnings: Vec<GqlPlanLimitWarning>,
+}
+
 #[derive(InputObject)]
 pub struct CreateProjectInput {
     pub name: String,
@@ -391,6 +618,33 @@ pub struct CreateProjectLimitsInput {
     pub max_connections: i32,
     pub max_events_per_sec: i32,
     pub max_payload_size: i32,
+    pub delivery_buffer_size: Option<i32>,
+    pub idle_timeout_secs: Option<i32>,
+    pub max_subscriptions_per_connection: Option<i32>,
+}
+
+/// Limits to change on a project; omitted fields keep their value
+#[derive(InputObject)]
+pub struct UpdateProjectLimitsInput {
+    pub max_connections: Option<i32>,
+    pub max_events_per_sec: Option<i32>,
+    pub max_payload_size: Option<i32>,
+    pub delivery_buffer_size: Option<i32>,
+    pub idle_timeout_secs: Option<i32>,
+    pub max_subscriptions_per_connection: Option<i32>,
+}
+
+impl From<UpdateProjectLimitsInput> for ProjectLimitsUpdate {
+    fn from(input: UpdateProjectLimitsInput) -> Self {
+        Self {
+            max_connections: input.max_connections,
+            max_events_per_sec: input.max_events_per_sec,
+            max_payload_size: input.max_payload_size,
+            delivery_buffer_size: input.delivery_buffer_size,
+            idle_timeout_secs: input.idle_timeout_secs,
+            max_subscriptions_per_connection: input.max_subscriptions_per_connection,
+        }
+    }
 }
 
 /// Filter types for queries
@@ -400,6 +654,34 @@ pub struct EventFilter {
     pub from_date: Option<DateTime<Utc>>,
     pub to_date: Option<DateTime<Utc>>,
     pub limit: Option<i32>,
+    /// Return older events following this `endCursor`
+    pub after: Option<String>,
+    /// Return newer events preceding this `startCursor`
+    pub before: Option<String>,
+}
+
+// Decode an opaque event cursor from a filter into its keyset position
+fn decode_event_cursor(token: Option<&str>) -> FieldResult<Option<(DateTime<Utc>, String)>> {
+    token
+        .map(|token| {
+            EventPageCursor::decode(token)
+                .map(EventPageCursor::into_key)
+                .ok_or_else(|| GraphQLError::ValidationError("Invalid cursor".to_string()).extend())
+        })
+        .transpose()
+}
+
+/// Ceiling on page sizes requested through list queries
+#[derive(Debug, Clone, Copy)]
+pub struct PageSizeLimit(pub i64);
+
+// A list query's requested page size, clamped like the REST lists
+fn page_size(ctx: &Context<'_>, requested: Option<i32>) -> i64 {
+    let max_page_size = ctx
+        .data_opt::<PageSizeLimit>()
+        .map(|l| l.0)
+        .unwrap_or(DEFAULT_MAX_PAGE_SIZE);
+    clamp_page_size(requested.map(i64::from), max_page_size)
 }
 
 /// Query root
@@ -412,18 +694,75 @@ impl QueryRoot {
         &self,
         ctx: &Context<'_>,
         filter: Option<EventFilter>,
-    ) -> FieldResult<Vec<GqlEvent>> {
+    ) -> FieldResult<GqlEventPage> {
         let auth = get_auth_context(ctx)?;
         let database = ctx.data::<Database>()?;
 
         // Enforce tenant isolation - only return events for the authenticated tenant
-        let limit = filter.as_ref().and_then(|f| f.limit).unwrap_or(100) as i64;
-        let events = database
-            .get_events_for_tenant(&auth.tenant_id, limit)
+        let limit = page_size(ctx, filter.as_ref().and_then(|f| f.limit));
+
+        let after = decode_event_cursor(filter.as_ref().and_then(|f| f.after.as_deref()))?;
+        let before = decode_event_cursor(filter.as_ref().and_then(|f| f.before.as_deref()))?;
+        let query = filter
+            .map(|f| EventQuery {
+                topic: f.topic,
+                from: f.from_date,
+                to: f.to_date,
+            })
+            .unwrap_or_default();
+
+        let (page, has_next_page, has_previous_page) = match (after, before) {
+            (Some(_), Some(_)) => {
+                return Err(GraphQLError::ValidationError(
+                    "Only one of after and before may be given".to_string(),
+                )
+                .extend())
+            }
+            // Walking toward newer events: the cursor itself lies after this page
+            (None, Some(before)) => {
+                let page = database
+                    .query_events_paged_after(&auth.tenant_id, &query, before, limit)
+                    .await
+                    .map_err(GraphQLError::from)?;
+                let has_previous_page = page.has_more;
+                (page, true, has_previous_page)
+            }
+            (after, None) => {
+                let has_previous_page = after.is_some();
+                let page = database
+                    .query_events_paged(&auth.tenant_id, &query, after, limit)
+                    .await
+                    .map_err(GraphQLError::from)?;
+                let has_next_page = page.has_more;
+                (page, has_next_page, has_previous_page)
+            }
+        };
+
+        Ok(GqlEventPage {
+            page_info: GqlPageInfo {
+                has_next_page,
+                has_previous_page,
+                start_cursor: page.start_cursor(),
+                end_cursor: page.end_cursor(),
+            },
+            events: page.events.into_iter().map(Into::into).collect(),
+        })
+    }
+
+    /// Get one of the authenticated tenant's events by id
+    async fn event(&self, ctx: &Context<'_>, id: ID) -> FieldResult<Option<GqlEvent>> {
+        let auth = get_auth_context(ctx)?;
+        check_scope(&auth, &Scope::EventsSubscribe)?;
+
+        let database = ctx.data::<Database>()?;
+        let event = database
+            .get_event(&auth.tenant_id, &id)
             .await
             .map_err(GraphQLError::from)?;
 
-        Ok(events.into_iter().map(Into::into).collect())
+        Ok(event
+            .filter(|event| auth.allows_topic(&event.topic))
+            .map(Into::into))
     }
 
     /// Get tenants (admin only)
@@ -445,11 +784,13 @@ impl QueryRoot {
         }
     }
 
-    /// Get projects for a tenant
+    /// Get projects for a tenant; archived projects are left out unless
+    /// `include_archived` is set
     async fn projects(
         &self,
         ctx: &Context<'_>,
         tenant_id: Option<ID>,
+        include_archived: Option<bool>,
     ) -> FieldResult<Vec<GqlProject>> {
         let auth = get_auth_context(ctx)?;
         let database = ctx.data::<Database>()?;
@@ -464,41 +805,40 @@ impl QueryRoot {
         }
 
         let projects = database
-            .get_projects_for_tenant(&target_tenant_id)
+            .list_projects_for_tenant(&target_tenant_id, include_archived.unwrap_or(false))
             .await
             .map_err(GraphQLError::from)?;
 
         Ok(projects.into_iter().map(Into::into).collect())
     }
 
-    /// Get API keys for a project (admin read required)
+    /// Get API keys for a project, newest first and at most `limit` of them
+    /// (admin read required)
     #[allow(clippy::unnecessary_lazy_evaluations, clippy::unnecessary_to_owned)]
-    async fn api_keys(&self, ctx: &Context<'_>, project_id: ID) -> FieldResult<Vec<GqlApiKey>> {
+    async fn api_keys(
+        &self,
+        ctx: &Context<'_>,
+        project_id: ID,
+        limit: Option<i32>,
+    ) -> FieldResult<Vec<GqlApiKey>> {
         let auth = get_auth_context(ctx)?;
         check_scope(&auth, &Scope::AdminRead)?;
 
         let database = ctx.data::<Database>()?;
 
         // Verify project belongs to authenticated tenant
-        let project = database
-            .get_project(&project_id.to_string())
-            .await
-            .map_err(GraphQLError::from)?
-            .ok_or_else(|| GraphQLError::NotFound)?;
-
-        if project.tenant_id != auth.tenant_id {
-            return Err(GraphQLError::Forbidden.extend());
-        }
+        tenant_project(database, &auth, &project_id.to_string()).await?;
 
         let api_keys = database
-            .get_api_keys_for_project(&project_id.to_string())
+            .get_api_keys_for_project(&project_id.to_string(), Some(page_size(ctx, limit)))
             .await
             .map_err(GraphQLError::from)?;
 
         Ok(api_keys.into_iter().map(Into::into).collect())
     }
 
-    /// Get usage records for a project (billing read required)
+    /// Get usage records for a project, newest first and at most `limit` of
+    /// them (billing read required)
     #[allow(clippy::unnecessary_lazy_evaluations, clippy::unnecessary_to_owned)]
     async fn usage_records(
         &self,
@@ -506,6 +846,7 @@ impl QueryRoot {
         project_id: ID,
         from_date: Option<DateTime<Utc>>,
         to_date: Option<DateTime<Utc>>,
+        limit: Option<i32>,
     ) -> FieldResult<Vec<GqlUsageRecord>> {
         let auth = get_auth_context(ctx)?;
         check_scope(&auth, &Scope::BillingRead)?;
@@ -513,23 +854,43 @@ impl QueryRoot {
         let database = ctx.data::<Database>()?;
 
         // Verify project belongs to authenticated tenant
-        let project = database
-            .get_project(&project_id.to_string())
-            .await
-            .map_err(GraphQLError::from)?
-            .ok_or_else(|| GraphQLError::NotFound)?;
-
-        if project.tenant_id != auth.tenant_id {
-            return Err(GraphQLError::Forbidden.extend());
-        }
+        tenant_project(database, &auth, &project_id.to_string()).await?;
 
+        let limit = page_size(ctx, limit);
         let usage_records = database
-            .get_usage_records(&project_id.to_string(), from_date, to_date)
+            .get_usage_records(&project_id.to_string(), from_date, to_date, Some(limit))
             .await
             .map_err(GraphQLError::from)?;
 
         Ok(usage_records.into_iter().map(Into::into).collect())
     }
+
+    /// Get the active schema for a topic in a project
+    async fn topic_schema(
+        &self,
+        ctx: &Context<'_>,
+        project_id: ID,
+        topic: String,
+    ) -> FieldResult<Option<GqlTopicSchema>> {
+        let auth = get_auth_context(ctx)?;
+        check_scope(&auth, &Scope::EventsSubscribe)?;
+
+        let database = ctx.data::<Database>()?;
+        let event_service = ctx.data::<EventService>()?;
+
+        // Verify project belongs to authenticated tenant
+        tenant_project(database, &auth, &project_id.to_string()).await?;
+
+        let schema_validator = event_service.schema_validator();
+        schema_validator
+            .sync_topic(&project_id, &topic)
+            .await
+            .map_err(GraphQLError::from)?;
+
+        Ok(schema_validator
+            .get_schema(&project_id, &topic)
+            .map(|schema| GqlTopicSchema::new(project_id.to_string(), topic, schema)))
+    }
 }
 
 /// Mutation root
@@ -537,10 +898,19 @@ pub struct MutationRoot;
 
 #[Object]
 impl MutationRoot {
-    /// Publish an event
-    async fn publish_event(&self, ctx: &Context<'_>, input: EventInput) -> FieldResult<GqlEvent> {
+    /// Publish an event; repeating a call with the same `idempotencyKey` returns
+    /// the originally published event instead of publishing again
+    async fn publish_event(
+        &self,
+        ctx: &Context<'_>,
+        input: EventInput,
+        idempotency_key: Option<String>,
+    ) -> FieldResult<GqlEvent> {
         let auth = get_auth_context(ctx)?;
         check_scope(&auth, &Scope::EventsPublish)?;
+        if !auth.allows_topic(&input.topic) {
+            return Err(GraphQLError::Forbidden.extend());
+        }
 
         let event_service = ctx.data::<EventService>()?;
 
@@ -549,17 +919,59 @@ impl MutationRoot {
             .map_err(|e| GraphQLError::ValidationError(format!("Invalid JSON payload: {}", e)))?;
 
         // Create and publish event
-        let event = Event::new(
-            auth.tenant_id.clone(),
-            auth.project_id.clone(),
-            input.topic,
-            payload,
-        );
-
-        match event_service.publish_event(&event).await {
-            Ok(PublishResult::Success) => {
+        let event = Event::builder()
+            .tenant_id(auth.tenant_id.clone())
+            .project_id(auth.project_id.clone())
+            .topic(input.topic)
+            .payload(payload)
+            .partition_key(input.partition_key)
+            .headers(input.headers.unwrap_or_default())
+            .build()
+            .map_err(|e| GraphQLError::ValidationError(e.to_string()))?;
+
+        match event_service
+            .publish_event_idempotent(&event, auth.api_key_id(), idempotency_key.as_deref())
+            .await
+        {
+            Ok(PublishResult::Success { sequence }) => {
                 info!("Event published via GraphQL: {}", event.id);
-                Ok(event.into())
+                let mut event: GqlEvent = event.into();
+                event.sequence = Some(sequence);
+                Ok(event)
+            }
+            Ok(PublishResult::Deduplicated {
+                original_event_id,
+                sequence,
+            }) => {
+                info!(
+                    "Event deduplicated via GraphQL: {} (original: {})",
+                    event.id, original_event_id
+                );
+                let mut event: GqlEvent = event.into();
+                event.id = ID(original_event_id);
+                event.sequence = Some(sequence);
+                Ok(event)
+            }
+            Ok(PublishResult::Replayed { event_id, sequence }) => {
+                info!("Idempotent publish replayed via GraphQL: {}", event_id);
+                let mut event: GqlEvent = event.into();
+                event.id = ID(event_id);
+                event.sequence = Some(sequence);
+                Ok(event)
+            }
+            Ok(PublishResult::IdempotencyKeyInUse) => Err(GraphQLError::ValidationError(
+                "A request with this idempotency key is still being processed".to_string(),
+            )
+            .extend()),
+            Ok(PublishResult::RateLimited(status)) => {
+                Err(GraphQLError::RateLimited(status.retry_after_secs).extend())
+            }
+            Ok(PublishResult::PayloadTooLarge { size, limit }) => {
+                Err(GraphQLError::ValidationError(format!(
+                    "Payload of {} bytes exceeds the {} byte limit",
+                    size, limit
+                ))
+                .extend())
             }
             Ok(PublishResult::ValidationFailed(msg)) => {
                 Err(GraphQLError::ValidationError(msg).extend())
@@ -568,6 +980,136 @@ impl MutationRoot {
         }
     }
 
+    /// Publish several events; each one succeeds or fails on its own
+    async fn publish_events(
+        &self,
+        ctx: &Context<'_>,
+        inputs: Vec<EventInput>,
+    ) -> FieldResult<Vec<GqlBatchPublishResult>> {
+        let auth = get_auth_context(ctx)?;
+        check_scope(&auth, &Scope::EventsPublish)?;
+
+        let event_service = ctx.data::<EventService>()?;
+        event_service
+            .check_batch_size(inputs.len())
+            .map_err(|e| GraphQLError::ValidationError(e.to_string()).extend())?;
+
+        // Events that fail local checks keep their slot so results line up with the inputs
+        let prepared: Vec<Result<Event, String>> = inputs
+            .into_iter()
+            .map(|input| {
+                let payload: serde_json::Value = serde_json::from_str(&input.payload)
+                    .map_err(|e| format!("Invalid JSON payload: {}", e))?;
+                let item = BatchItem {
+                    topic: input.topic,
+                    payload,
+                    partition_key: input.partition_key,
+                    headers: input.headers.unwrap_or_default(),
+                    ..BatchItem::default()
+                };
+                event_service
+                    .prepare_batch_event(&auth, item)
+                    .map_err(|e| e.to_string())
+            })
+            .collect();
+        let events: Vec<Event> = prepared
+            .iter()
+            .filter_map(|prepared| prepared.as_ref().ok().cloned())
+            .collect();
+
+        let results = event_service
+            .publish_batch(&events, auth.api_key_id())
+            .await
+            .map_err(|e| GraphQLError::InternalError(e.to_string()).extend())?;
+        let mut published = results.into_iter().zip(events);
+
+        Ok(prepared
+            .into_iter()
+            .map(|prepared| {
+                if let Err(error) = prepared {
+                    return GqlBatchPublishResult::failed(error);
+                }
+                let (result, event) = published
+                    .next()
+                    .expect("one publish result per prepared event");
+                match result {
+                    Ok(PublishResult::Success { sequence }) => {
+                        GqlBatchPublishResult::published(event.id, sequence)
+                    }
+                    Ok(PublishResult::Deduplicated {
+                        original_event_id,
+                        sequence,
+                    }) => GqlBatchPublishResult::published(original_event_id, sequence),
+                    // Batches carry no idempotency keys
+                    Ok(PublishResult::Replayed { event_id, sequence }) => {
+                        GqlBatchPublishResult::published(event_id, sequence)
+                    }
+                    Ok(PublishResult::IdempotencyKeyInUse) => {
+                        GqlBatchPublishResult::failed("Idempotency key in use".to_string())
+                    }
+                    Ok(PublishResult::RateLimited(_)) => GqlBatchPublishResult::failed(
+                        "Project publish rate limit exceeded".to_string(),
+                    ),
+                    Ok(PublishResult::PayloadTooLarge { size, limit }) => {
+                        GqlBatchPublishResult::failed(format!(
+                            "Payload of {} bytes exceeds the {} byte limit",
+                            size, limit
+                        ))
+                    }
+                    Ok(PublishResult::ValidationFailed(msg)) => GqlBatchPublishResult::failed(msg),
+                    Err(e) => GqlBatchPublishResult::failed(e.to_string()),
+                }
+            })
+            .collect())
+    }
+
+    /// Register a new schema version for a topic (admin write required)
+    async fn register_topic_schema(
+        &self,
+        ctx: &Context<'_>,
+        topic: String,
+        schema: String,
+    ) -> FieldResult<GqlTopicSchema> {
+        let auth = get_auth_context(ctx)?;
+        check_scope(&auth, &Scope::AdminWrite)?;
+
+        validate_topic_format(&topic).map_err(|e| GraphQLError::ValidationError(e).extend())?;
+
+        let schema: serde_json::Value = serde_json::from_str(&schema)
+            .map_err(|e| GraphQLError::ValidationError(format!("Invalid JSON schema: {}", e)))?;
+        SchemaValidator::check_schema_document(&schema).map_err(|e| {
+            GraphQLError::ValidationError(format!("Document is not a valid JSON Schema: {}", e))
+        })?;
+
+        let event_service = ctx.data::<EventService>()?;
+        let version = event_service
+            .schema_validator()
+            .store_schema(
+                &auth.tenant_id,
+                &auth.project_id,
+                &topic,
+                schema.clone(),
+                SchemaMode::Enforce,
+            )
+            .await
+            .map_err(GraphQLError::from)?;
+
+        info!(
+            "Registered schema v{} for topic {} in project {} via GraphQL",
+            version, topic, auth.project_id
+        );
+
+        Ok(GqlTopicSchema::new(
+            auth.project_id.clone(),
+            topic,
+            TopicSchema {
+                version,
+                schema,
+                mode: SchemaMode::Enforce,
+            },
+        ))
+    }
+
     /// Create a new API key (admin write required)
     #[allow(clippy::unnecessary_lazy_evaluations, clippy::unnecessary_to_owned)]
     async fn create_api_key(
@@ -582,15 +1124,7 @@ impl MutationRoot {
         let database = ctx.data::<Database>()?;
 
         // Verify project belongs to authenticated tenant
-        let project = database
-            .get_project(&input.project_id.to_string())
-            .await
-            .map_err(GraphQLError::from)?
-            .ok_or_else(|| GraphQLError::NotFound)?;
-
-        if project.tenant_id != auth.tenant_id {
-            return Err(GraphQLError::Forbidden.extend());
-        }
+        tenant_project(database, &auth, &input.project_id.to_string()).await?;
 
         let scopes: Vec<Scope> = input.scopes.into_iter().map(Into::into).collect();
 
@@ -600,11 +1134,22 @@ impl MutationRoot {
                 input.project_id.to_string(),
                 scopes,
                 input.rate_limit_per_sec,
+                input.allowed_topics.unwrap_or_default(),
                 input.expires_at,
             )
             .await
             .map_err(GraphQLError::from)?;
 
+        audit::record(
+            database,
+            &auth,
+            &auth.tenant_id,
+            audit::API_KEY_CREATED,
+            Some(&api_key.id),
+            serde_json::json!({"project_id": api_key.project_id, "scopes": api_key.scopes}),
+        )
+        .await;
+
         Ok(api_key.into())
     }
 
@@ -619,32 +1164,54 @@ impl MutationRoot {
 
         let database = ctx.data::<Database>()?;
 
-        // Convert input to BillingPlan
-        let plan = match input.plan.plan_type.as_str() {
-            "free" => BillingPlan::Free {
-                monthly_events: input.plan.monthly_events.unwrap_or(10000),
-            },
-            "pro" => BillingPlan::Pro {
-                monthly_events: input.plan.monthly_events.unwrap_or(100000),
-                price_per_event: input.plan.price_per_event.unwrap_or(0.001),
-            },
-            "enterprise" => BillingPlan::Enterprise {
-                unlimited: input.plan.unlimited.unwrap_or(true),
-            },
-            _ => {
-                return Err(GraphQLError::ValidationError("Invalid plan type".to_string()).extend())
-            }
-        };
-
+        let plan = billing_plan_from_input(input.plan)?;
         let tenant = Tenant::new(input.name, plan);
         database
             .create_tenant(&tenant)
             .await
             .map_err(GraphQLError::from)?;
 
+        audit::record(
+            database,
+            &auth,
+            &tenant.id,
+            audit::TENANT_CREATED,
+            Some(&tenant.id),
+            serde_json::json!({"name": tenant.name, "plan": tenant.plan}),
+        )
+        .await;
+
         Ok(tenant.into())
     }
 
+    /// Upgrade or downgrade a tenant's plan. Usage already past the new plan's
+    /// limits is reported as warnings; the tenant is not suspended. Only
+    /// platform admin credentials may do this; tenants change plans through
+    /// billing.
+    async fn update_tenant_plan(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateTenantPlanInput,
+    ) -> FieldResult<GqlTenantPlanChange> {
+        let auth = get_auth_context(ctx)?;
+        check_scope(&auth, &Scope::AdminWrite)?;
+        if !ctx.data::<AuthService>()?.is_platform_admin(&auth) {
+            return Err(GraphQLError::Forbidden.extend());
+        }
+
+        let database = ctx.data::<Database>()?;
+        let plan = billing_plan_from_input(input.plan)?;
+        let (tenant, warnings) = change_tenant_plan(database, &auth, &input.tenant_id, plan)
+            .await
+            .map_err(GraphQLError::from)?
+            .ok_or_else(|| GraphQLError::NotFound.extend())?;
+
+        Ok(GqlTenantPlanChange {
+            tenant: tenant.into(),
+            warnings: warnings.into_iter().map(Into::into).collect(),
+        })
+    }
+
     /// Create a new project
     async fn create_project(
         &self,
@@ -664,6 +1231,15 @@ impl MutationRoot {
                 max_connections: limits_input.max_connections,
                 max_events_per_sec: limits_input.max_events_per_sec,
                 max_payload_size: limits_input.max_payload_size,
+                delivery_buffer_size: limits_input
+                    .delivery_buffer_size
+                    .unwrap_or(project.limits.delivery_buffer_size),
+                idle_timeout_secs: limits_input
+                    .idle_timeout_secs
+                    .unwrap_or(project.limits.idle_timeout_secs),
+                max_subscriptions_per_connection: limits_input
+                    .max_subscriptions_per_connection
+                    .unwrap_or(project.limits.max_subscriptions_per_connection),
             };
         }
 
@@ -675,6 +1251,64 @@ impl MutationRoot {
         Ok(project.into())
     }
 
+    /// Change a project's limits; a lower connection limit also drains
+    /// connections above it (admin write required)
+    async fn update_project_limits(
+        &self,
+        ctx: &Context<'_>,
+        project_id: ID,
+        limits: UpdateProjectLimitsInput,
+    ) -> FieldResult<GqlProject> {
+        let auth = get_auth_context(ctx)?;
+        check_scope(&auth, &Scope::AdminWrite)?;
+
+        let database = ctx.data::<Database>()?;
+        let project =
+            update_project_limits(database, &auth.tenant_id, &project_id, &limits.into())
+                .await
+                .map_err(|e| match e {
+                    ProjectLimitsError::Invalid(message) => {
+                        GraphQLError::ValidationError(message).extend()
+                    }
+                    // Other tenants' projects are indistinguishable from missing ones
+                    ProjectLimitsError::NotFound => GraphQLError::Forbidden.extend(),
+                    ProjectLimitsError::Archived => GraphQLError::ValidationError(
+                        "Archived projects can't be changed".to_string(),
+                    )
+                    .extend(),
+                    ProjectLimitsError::Database(e) => GraphQLError::from(e).extend(),
+                })?;
+
+        Ok(project.into())
+    }
+
+    /// Archive a project, closing its WebSocket and SSE connections; its API
+    /// keys stop authenticating (admin write required)
+    async fn archive_project(&self, ctx: &Context<'_>, id: ID) -> FieldResult<bool> {
+        let auth = get_auth_context(ctx)?;
+        check_scope(&auth, &Scope::AdminWrite)?;
+
+        let database = ctx.data::<Database>()?;
+        let terminated =
+            crate::project_archive::archive_project(database, &auth.tenant_id, &id)
+                .await
+                .map_err(GraphQLError::from)?
+                // Other tenants' projects are indistinguishable from missing ones
+                .ok_or_else(|| GraphQLError::Forbidden.extend())?;
+
+        audit::record(
+            database,
+            &auth,
+            &auth.tenant_id,
+            audit::PROJECT_ARCHIVED,
+            Some(id.as_str()),
+            serde_json::json!({ "terminated_connections": terminated }),
+        )
+        .await;
+
+        Ok(true)
+    }
+
     /// Revoke an API key (admin write required)
     #[allow(clippy::unnecessary_to_owned)]
     async fn revoke_api_key(&self, ctx: &Context<'_>, key_id: ID) -> FieldResult<bool> {
@@ -682,12 +1316,23 @@ impl MutationRoot {
         check_scope(&auth, &Scope::AdminWrite)?;
 
         let auth_service = ctx.data::<AuthService>()?;
+        let database = ctx.data::<Database>()?;
 
         auth_service
             .revoke_api_key(&auth.tenant_id, &key_id.to_string())
             .await
             .map_err(GraphQLError::from)?;
 
+        audit::record(
+            database,
+            &auth,
+            &auth.tenant_id,
+            audit::API_KEY_REVOKED,
+            Some(&key_id.to_string()),
+            serde_json::json!({}),
+        )
+        .await;
+
         Ok(true)
     }
 }
@@ -705,17 +1350,21 @@ impl SubscriptionRoot {
     ) -> FieldResult<Pin<Box<dyn Stream<Item = GqlEvent> + Send>>> {
         let auth = get_auth_context(ctx)?;
         check_scope(&auth, &Scope::EventsSubscribe)?;
+        if auth.check_subscribe_topics(&topics).is_err() {
+            return Err(GraphQLError::Forbidden.extend());
+        }
 
         let event_service = ctx.data::<EventService>()?;
 
-        // Create subscription with tenant isolation
-        let _subscription = event_service
+        // Create subscription with tenant isolation; the stream only yields events
+        // for the caller's tenant/project and requested topics
+        let subscription = event_service
             .subscribe_to_topics(&auth.tenant_id, &auth.project_id, topics)
             .await
             .map_err(GraphQLError::from)?;
 
-        // For now, return a simple empty stream since we don't have real event streaming yet
-        let stream = tokio_stream::empty::<GqlEvent>();
+        // Dropping the stream when the client disconnects releases the receiver
+        let stream = subscription.into_stream().map(GqlEvent::from);
         Ok(Box::pin(stream))
     }
 
@@ -737,9 +1386,10 @@ impl SubscriptionRoot {
             return Err(GraphQLError::Forbidden.extend());
         }
 
-        // For now, return an empty stream as usage updates would require
-        // a separate event system for billing events
-        let stream = tokio_stream::empty::<GqlUsageRecord>();
+        let event_service = ctx.data::<EventService>()?;
+        let stream = event_service
+            .subscribe_usage(&target_tenant_id)
+            .map(GqlUsageRecord::from);
         Ok(Box::pin(stream))
     }
 }
@@ -750,6 +1400,20 @@ fn get_auth_context(ctx: &Context<'_>) -> FieldResult<AuthContext> {
         .map_err(|_| GraphQLError::Unauthorized.extend())
 }
 
+/// Fetch a project scoped to the authenticated tenant; projects owned by other
+/// tenants are indistinguishable from missing ones
+async fn tenant_project(
+    database: &Database,
+    auth: &AuthContext,
+    project_id: &str,
+) -> FieldResult<Project> {
+    database
+        .get_project_with_tenant(&auth.tenant_id, project_id)
+        .await
+        .map_err(GraphQLError::from)?
+        .ok_or_else(|| GraphQLError::Forbidden.extend())
+}
+
 fn check_scope(auth: &AuthContext, required_scope: &Scope) -> FieldResult<()> {
     if auth.scopes.contains(required_scope) {
         Ok(())
@@ -758,16 +1422,82 @@ fn check_scope(auth: &AuthContext, required_scope: &Scope) -> FieldResult<()> {
     }
 }
 
-/// Create the GraphQL schema
+/// Extension code of queries rejected for exceeding the depth or complexity limit
+pub const QUERY_TOO_COMPLEX_CODE: &str = "QUERY_TOO_COMPLEX";
+
+/// Rejects queries nested deeper or scoring higher than the configured limits
+/// once validation has measured them, with `QUERY_TOO_COMPLEX`
+#[derive(Debug, Clone, Copy)]
+struct QueryLimits {
+    max_depth: usize,
+    max_complexity: usize,
+}
+
+impl ExtensionFactory for QueryLimits {
+    fn create(&self) -> std::sync::Arc<dyn Extension> {
+        std::sync::Arc::new(*self)
+    }
+}
+
+#[async_trait::async_trait]
+impl Extension for QueryLimits {
+    async fn validation(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        next: NextValidation<'_>,
+    ) -> Result<ValidationResult, Vec<ServerError>> {
+        let result = next.run(ctx).await?;
+        if result.depth > self.max_depth {
+            return Err(vec![query_too_complex(format!(
+                "Query is nested {} levels deep; the limit is {}",
+                result.depth, self.max_depth
+            ))]);
+        }
+        if result.complexity > self.max_complexity {
+            return Err(vec![query_too_complex(format!(
+                "Query complexity {} exceeds the limit of {}",
+                result.complexity, self.max_complexity
+            ))]);
+        }
+        Ok(result)
+    }
+}
+
+fn query_too_complex(message: String) -> ServerError {
+    let mut error = ServerError::new(message, None);
+    error
+        .extensions
+        .get_or_insert_with(Default::default)
+        .set("code", QUERY_TOO_COMPLEX_CODE);
+    error
+}
+
+/// Create the GraphQL schema, rejecting queries past the configured limits
+/// during validation, before any resolver runs
 pub fn create_schema(
     database: Database,
     event_service: EventService,
     auth_service: AuthService,
+    max_page_size: i64,
+    limits: &GraphQLConfig,
 ) -> ApiSchema {
     Schema::build(QueryRoot, MutationRoot, SubscriptionRoot)
+        .data(DataLoader::new(
+            ProjectsByTenant::new(database.clone()),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            ApiKeysByProject::new(database.clone()),
+            tokio::spawn,
+        ))
         .data(database)
         .data(event_service)
         .data(auth_service)
+        .data(PageSizeLimit(max_page_size))
+        .extension(QueryLimits {
+            max_depth: limits.max_depth,
+            max_complexity: limits.max_complexity,
+        })
         .finish()
 }
 
@@ -781,23 +1511,51 @@ pub async fn graphql_handler(
     schema.execute(request).await.into()
 }
 
-/// GraphQL subscription handler
+/// GraphQL subscription handler speaking `graphql-transport-ws`. Clients
+/// authenticate in their `connection_init` payload rather than the handshake,
+/// since browsers can't set headers on WebSocket requests.
+#[utoipa::path(
+    get,
+    path = "/graphql/ws",
+    tag = "graphql",
+    responses(
+        (status = 101, description = "Switching to the graphql-transport-ws protocol; an invalid connection_init credential closes the socket with 4401"),
+    )
+)]
 pub async fn graphql_subscription_handler(
-    auth_context: AuthContext,
-    _schema: axum::extract::State<ApiSchema>,
+    State(state): State<AppState>,
+    Extension(schema): Extension<ApiSchema>,
     ws: WebSocketUpgrade,
 ) -> axum::response::Response {
-    ws.on_upgrade(move |_socket| async move {
-        // For now, just log the connection
-        // In a real implementation, this would handle GraphQL subscriptions over WebSocket
-        info!(
-            "GraphQL WebSocket connection established for tenant: {}",
-            auth_context.tenant_id
-        );
-    })
+    let init_timeout = Duration::from_secs(state.graphql.connection_init_timeout_secs);
+    let auth_service = state.auth_service;
+    let database = state.database;
+    ws.protocols([GRAPHQL_TRANSPORT_WS_PROTOCOL])
+        .on_upgrade(move |socket| async move {
+            let (outgoing, incoming) = socket.split();
+            // A read error ends the connection like a close frame does
+            let incoming = incoming
+                .take_while(|message| future::ready(message.is_ok()))
+                .filter_map(|message| future::ready(message.ok()));
+            serve_graphql_ws(
+                schema,
+                auth_service,
+                database,
+                init_timeout,
+                incoming,
+                outgoing,
+            )
+            .await;
+        })
 }
 
 /// GraphQL playground handler (development only)
+#[utoipa::path(
+    get,
+    path = "/graphql/playground",
+    tag = "graphql",
+    responses((status = 200, description = "GraphQL playground page", content_type = "text/html", body = String))
+)]
 pub async fn graphql_playground() -> axum::response::Html<&'static str> {
     axum::response::Html(
         r#"
@@ -0,0 +1,106 @@
+use async_graphql::dataloader::Loader;
+use prometheus::{CounterVec, Opts};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::database::Database;
+use crate::models::{ApiKey, Project};
+
+// Database round trips issued by each DataLoader; one per batch of keys
+lazy_static::lazy_static! {
+    static ref LOADER_BATCHES: CounterVec = CounterVec::new(
+        Opts::new(
+            "realtime_graphql_loader_batches_total",
+            "Batched database lookups issued by GraphQL DataLoaders"
+        ),
+        &["loader"]
+    )
+    .expect("valid metric definition");
+}
+
+/// Counter of DataLoader batches, labelled by loader
+pub fn loader_batches_counter() -> &'static CounterVec {
+    &LOADER_BATCHES
+}
+
+fn record_batch(loader: &str) {
+    LOADER_BATCHES.with_label_values(&[loader]).inc();
+}
+
+/// Loads every project of a batch of tenants in one query
+pub struct ProjectsByTenant {
+    database: Database,
+}
+
+impl ProjectsByTenant {
+    pub const NAME: &'static str = "projects_by_tenant";
+
+    pub fn new(database: Database) -> Self {
+        Self { database }
+    }
+}
+
+impl Loader<String> for ProjectsByTenant {
+    type Value = Vec<Project>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(
+        &self,
+        tenant_ids: &[String],
+    ) -> Result<HashMap<String, Vec<Project>>, Self::Error> {
+        record_batch(Self::NAME);
+        let projects = self
+            .database
+            .get_projects_for_tenants(tenant_ids)
+            .await
+            .map_err(Arc::new)?;
+
+        let mut by_tenant: HashMap<String, Vec<Project>> = HashMap::new();
+        for project in projects {
+            by_tenant
+                .entry(project.tenant_id.clone())
+                .or_default()
+                .push(project);
+        }
+        Ok(by_tenant)
+    }
+}
+
+/// Loads every API key of a batch of projects in one query
+pub struct ApiKeysByProject {
+    database: Database,
+}
+
+impl ApiKeysByProject {
+    pub const NAME: &'static str = "api_keys_by_project";
+
+    pub fn new(database: Database) -> Self {
+        Self { database }
+    }
+}
+
+impl Loader<String> for ApiKeysByProject {
+    type Value = Vec<ApiKey>;
+    type Error = Arc<anyhow::Error>;
+
+    async fn load(
+        &self,
+        project_ids: &[String],
+    ) -> Result<HashMap<String, Vec<ApiKey>>, Self::Error> {
+        record_batch(Self::NAME);
+        let api_keys = self
+            .database
+            .get_api_keys_for_projects(project_ids)
+            .await
+            .map_err(Arc::new)?;
+
+        let mut by_project: HashMap<String, Vec<ApiKey>> = HashMap::new();
+        for api_key in api_keys {
+            by_project
+                .entry(api_key.project_id.clone())
+                .or_default()
+                .push(api_key);
+        }
+        Ok(by_project)
+    }
+}
@@ -0,0 +1,499 @@
+use async_graphql::{Request, Response, ServerError, Value as GqlValue};
+use axum::extract::ws::{CloseFrame, Message};
+use futures_util::{pin_mut, Sink, SinkExt, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::AbortHandle;
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
+
+use crate::auth::{AuthContext, AuthError, AuthService};
+use crate::database::Database;
+use crate::graphql::ApiSchema;
+use crate::idle::{
+    idle_timeout, record_idle_connection_closed, websocket_ping_interval, ActivityTracker,
+    DEFAULT_IDLE_TIMEOUT_SECS, IDLE_CLOSE_CODE, IDLE_CLOSE_REASON,
+};
+use crate::websocket::{
+    DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION, UNAUTHORIZED_CLOSE_CODE, UNAUTHORIZED_CLOSE_REASON,
+};
+
+/// WebSocket subprotocol clients negotiate for GraphQL subscriptions
+pub const GRAPHQL_TRANSPORT_WS_PROTOCOL: &str = "graphql-transport-ws";
+
+/// Close code for a message that isn't valid `graphql-transport-ws`
+pub const INVALID_MESSAGE_CLOSE_CODE: u16 = 4400;
+
+/// Close code for a client that didn't send `connection_init` in time
+pub const INIT_TIMEOUT_CLOSE_CODE: u16 = 4408;
+
+/// Close code for a `subscribe` reusing the id of an operation still running
+pub const SUBSCRIBER_EXISTS_CLOSE_CODE: u16 = 4409;
+
+/// Close code for a second `connection_init` on the same socket
+pub const TOO_MANY_INIT_CLOSE_CODE: u16 = 4429;
+
+/// Extension code of the `error` answering a `subscribe` over the project's
+/// `max_subscriptions_per_connection`
+pub const SUBSCRIPTION_LIMIT_CODE: &str = "SUBSCRIPTION_LIMIT_EXCEEDED";
+
+// Server messages operations may queue before the socket catches up
+const OUTBOUND_BUFFER: usize = 64;
+
+/// `graphql-transport-ws` message sent by the client
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    ConnectionInit {
+        #[serde(default)]
+        payload: Option<Value>,
+    },
+    Ping {
+        #[serde(default)]
+        payload: Option<Value>,
+    },
+    Pong {
+        #[serde(default)]
+        payload: Option<Value>,
+    },
+    Subscribe {
+        id: String,
+        payload: Request,
+    },
+    Complete {
+        id: String,
+    },
+}
+
+/// `graphql-transport-ws` message sent by the server
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    ConnectionAck,
+    Ping,
+    Pong,
+    Next {
+        id: String,
+        payload: Response,
+    },
+    /// The operation failed before producing a result; no `complete` follows
+    Error {
+        id: String,
+        payload: Vec<ServerError>,
+    },
+    Complete {
+        id: String,
+    },
+}
+
+impl ServerMessage {
+    fn operation_id(&self) -> Option<&str> {
+        match self {
+            ServerMessage::Next { id, .. }
+            | ServerMessage::Error { id, .. }
+            | ServerMessage::Complete { id } => Some(id),
+            _ => None,
+        }
+    }
+
+    fn ends_operation(&self) -> bool {
+        matches!(self, ServerMessage::Error { .. } | ServerMessage::Complete { .. })
+    }
+}
+
+/// API key or JWT carried in a `connection_init` payload, either as an
+/// `authorization` value like the HTTP header or as a bare `access_token`
+pub fn connection_init_credential(payload: Option<&Value>) -> Option<String> {
+    let payload = payload?;
+    let credential = match payload.get("authorization").and_then(Value::as_str) {
+        Some(authorization) => authorization
+            .strip_prefix("Bearer ")
+            .or_else(|| authorization.strip_prefix("ApiKey "))
+            .unwrap_or(authorization),
+        None => payload.get("access_token").and_then(Value::as_str)?,
+    };
+    Some(credential.trim().to_string()).filter(|credential| !credential.is_empty())
+}
+
+/// Serve one `graphql-transport-ws` connection until either side closes it.
+///
+/// The client has `init_timeout` to send `connection_init` with its API key or
+/// JWT in the payload; a missing or rejected credential closes the socket with
+/// `4401`. Once acknowledged, each `subscribe` runs against `schema` with the
+/// client's auth context, streaming `next` messages until it ends with
+/// `complete` or `error` or the client completes it.
+///
+/// The project's limits apply as on `/ws`: a `subscribe` beyond
+/// `max_subscriptions_per_connection` running operations is answered with an
+/// `error`, the server sends `ping` every half idle timeout, and a connection
+/// that shows no activity for `idle_timeout_secs` is closed with `4410`.
+pub async fn serve_graphql_ws<I, O>(
+    schema: ApiSchema,
+    auth_service: AuthService,
+    database: Database,
+    init_timeout: Duration,
+    incoming: I,
+    outgoing: O,
+) where
+    I: Stream<Item = Message>,
+    O: Sink<Message>,
+{
+    pin_mut!(incoming);
+    pin_mut!(outgoing);
+
+    let Some(auth_context) =
+        wait_for_init(&auth_service, init_timeout, &mut incoming, &mut outgoing).await
+    else {
+        return;
+    };
+    if outgoing
+        .send(encode(&ServerMessage::ConnectionAck))
+        .await
+        .is_err()
+    {
+        return;
+    }
+    info!(
+        "GraphQL WebSocket connection established for tenant: {}",
+        auth_context.tenant_id
+    );
+
+    let project = database
+        .get_project_with_tenant(&auth_context.tenant_id, &auth_context.project_id)
+        .await
+        .ok()
+        .flatten();
+    let max_operations = project
+        .as_ref()
+        .map_or(DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION, |p| {
+            p.limits.max_subscriptions_per_connection
+        })
+        .max(1) as usize;
+    let idle_timeout = idle_timeout(
+        project
+            .as_ref()
+            .map_or(DEFAULT_IDLE_TIMEOUT_SECS, |p| p.limits.idle_timeout_secs),
+    );
+
+    let (sender, mut receiver) = mpsc::channel(OUTBOUND_BUFFER);
+    let mut operations: HashMap<String, AbortHandle> = HashMap::new();
+
+    // Passive subscribers send nothing, so the server pings them instead. Any
+    // message received, pongs included, and any message sent counts as activity.
+    let ping_interval = websocket_ping_interval(idle_timeout);
+    let mut ping = tokio::time::interval_at(Instant::now() + ping_interval, ping_interval);
+    let activity = ActivityTracker::new();
+
+    loop {
+        tokio::select! {
+            message = incoming.next() => {
+                activity.touch();
+                let message = match message.map(parse) {
+                    None | Some(Parsed::Closed) => break,
+                    Some(Parsed::Ignore) => continue,
+                    Some(Parsed::Invalid) => {
+                        close(&mut outgoing, INVALID_MESSAGE_CLOSE_CODE, "Invalid message received").await;
+                        break;
+                    }
+                    Some(Parsed::Message(message)) => message,
+                };
+                match message {
+                    ClientMessage::ConnectionInit { .. } => {
+                        close(&mut outgoing, TOO_MANY_INIT_CLOSE_CODE, "Too many initialisation requests").await;
+                        break;
+                    }
+                    ClientMessage::Ping { .. } => {
+                        if outgoing.send(encode(&ServerMessage::Pong)).await.is_err() {
+                            break;
+                        }
+                    }
+                    ClientMessage::Pong { .. } => {}
+                    ClientMessage::Subscribe { id, payload } => {
+                        if operations.contains_key(&id) {
+                            let reason = format!("Subscriber for {} already exists", id);
+                            close(&mut outgoing, SUBSCRIBER_EXISTS_CLOSE_CODE, reason).await;
+                            break;
+                        }
+                        if operations.len() >= max_operations {
+                            let error = subscription_limit_error(id, max_operations);
+                            if outgoing.send(encode(&error)).await.is_err() {
+                                break;
+                            }
+                            continue;
+                        }
+                        let responses = schema.execute_stream(payload.data(auth_context.clone()));
+                        let task = tokio::spawn(run_operation(id.clone(), responses, sender.clone()));
+                        operations.insert(id, task.abort_handle());
+                    }
+                    ClientMessage::Complete { id } => {
+                        if let Some(task) = operations.remove(&id) {
+                            task.abort();
+                        }
+                    }
+                }
+            }
+            Some(message) = receiver.recv() => {
+                // A completed operation may have left messages in the queue
+                let Some(id) = message.operation_id() else { continue };
+                if !operations.contains_key(id) {
+                    continue;
+                }
+                if message.ends_operation() {
+                    operations.remove(id);
+                }
+                if outgoing.send(encode(&message)).await.is_err() {
+                    break;
+                }
+                activity.touch();
+            }
+            _ = ping.tick() => {
+                if outgoing.send(encode(&ServerMessage::Ping)).await.is_err() {
+                    break;
+                }
+            }
+            _ = tokio::time::sleep(activity.remaining(idle_timeout)) => {
+                info!(
+                    "GraphQL WebSocket connection for tenant {} idle for {:?}, closing",
+                    auth_context.tenant_id, idle_timeout
+                );
+                close(&mut outgoing, IDLE_CLOSE_CODE, IDLE_CLOSE_REASON).await;
+                record_idle_connection_closed("graphql_ws");
+                break;
+            }
+        }
+    }
+
+    // Dropping a subscription's stream releases its event receiver
+    for task in operations.values() {
+        task.abort();
+    }
+    debug!(
+        "GraphQL WebSocket connection closed for tenant: {}",
+        auth_context.tenant_id
+    );
+}
+
+// Wait for `connection_init` and authenticate it, closing the socket when it
+// doesn't arrive in time or carries no valid credential
+async fn wait_for_init<I, O>(
+    auth_service: &AuthService,
+    init_timeout: Duration,
+    incoming: &mut I,
+    outgoing: &mut O,
+) -> Option<AuthContext>
+where
+    I: Stream<Item = Message> + Unpin,
+    O: Sink<Message> + Unpin,
+{
+    let deadline = Instant::now() + init_timeout;
+    loop {
+        let message = match tokio::time::timeout_at(deadline, incoming.next()).await {
+            Ok(Some(message)) => message,
+            Ok(None) => return None,
+            Err(_) => {
+                debug!("GraphQL WebSocket client sent no connection_init in time");
+                close(outgoing, INIT_TIMEOUT_CLOSE_CODE, "Connection initialisation timeout").await;
+                return None;
+            }
+        };
+        match parse(message) {
+            Parsed::Message(ClientMessage::ConnectionInit { payload }) => {
+                let auth = match connection_init_credential(payload.as_ref()) {
+                    Some(credential) => authenticate(auth_service, &credential).await,
+                    None => Err(AuthError::MissingAuth),
+                };
+                return match auth {
+                    Ok(auth_context) => Some(auth_context),
+                    Err(e) => {
+                        warn!("GraphQL WebSocket authentication failed: {}", e);
+                        close(outgoing, UNAUTHORIZED_CLOSE_CODE, UNAUTHORIZED_CLOSE_REASON).await;
+                        None
+                    }
+                };
+            }
+            Parsed::Message(ClientMessage::Ping { .. }) => {
+                if outgoing.send(encode(&ServerMessage::Pong)).await.is_err() {
+                    return None;
+                }
+            }
+            // Operations may only start once the connection is acknowledged
+            Parsed::Message(ClientMessage::Subscribe { .. }) => {
+                close(outgoing, UNAUTHORIZED_CLOSE_CODE, UNAUTHORIZED_CLOSE_REASON).await;
+                return None;
+            }
+            Parsed::Message(ClientMessage::Pong { .. } | ClientMessage::Complete { .. })
+            | Parsed::Ignore => {}
+            Parsed::Closed => return None,
+            Parsed::Invalid => {
+                close(outgoing, INVALID_MESSAGE_CLOSE_CODE, "Invalid message received").await;
+                return None;
+            }
+        }
+    }
+}
+
+// An API key, falling back to a JWT like the HTTP middleware
+async fn authenticate(
+    auth_service: &AuthService,
+    credential: &str,
+) -> Result<AuthContext, AuthError> {
+    match auth_service.validate_api_key(credential).await {
+        Err(AuthError::InvalidApiKey) => auth_service.validate_jwt(credential).await,
+        result => result,
+    }
+}
+
+// Forward an operation's results as `next` messages and finish with `complete`.
+// A result with errors and no data means the operation failed, which is
+// reported with `error` instead.
+async fn run_operation(
+    id: String,
+    responses: impl Stream<Item = Response>,
+    sender: mpsc::Sender<ServerMessage>,
+) {
+    pin_mut!(responses);
+    while let Some(response) = responses.next().await {
+        if response.is_err() && response.data == GqlValue::Null {
+            let _ = sender
+                .send(ServerMessage::Error {
+                    id,
+                    payload: response.errors,
+                })
+                .await;
+            return;
+        }
+        let next = ServerMessage::Next {
+            id: id.clone(),
+            payload: response,
+        };
+        if sender.send(next).await.is_err() {
+            return;
+        }
+    }
+    let _ = sender.send(ServerMessage::Complete { id }).await;
+}
+
+// `error` for a `subscribe` over the connection's operation limit
+fn subscription_limit_error(id: String, max_operations: usize) -> ServerMessage {
+    let mut error = ServerError::new(
+        format!(
+            "Subscription limit of {} per connection reached",
+            max_operations
+        ),
+        None,
+    );
+    error
+        .extensions
+        .get_or_insert_with(Default::default)
+        .set("code", SUBSCRIPTION_LIMIT_CODE);
+    ServerMessage::Error {
+        id,
+        payload: vec![error],
+    }
+}
+
+enum Parsed {
+    Message(ClientMessage),
+    /// WebSocket-level ping and pong frames
+    Ignore,
+    Closed,
+    Invalid,
+}
+
+fn parse(message: Message) -> Parsed {
+    let parsed = match message {
+        Message::Text(text) => serde_json::from_str(&text),
+        Message::Binary(bytes) => serde_json::from_slice(&bytes),
+        Message::Ping(_) | Message::Pong(_) => return Parsed::Ignore,
+        Message::Close(_) => return Parsed::Closed,
+    };
+    match parsed {
+        Ok(message) => Parsed::Message(message),
+        Err(e) => {
+            debug!("Invalid graphql-transport-ws message: {}", e);
+            Parsed::Invalid
+        }
+    }
+}
+
+fn encode(message: &ServerMessage) -> Message {
+    Message::Text(serde_json::to_string(message).expect("server messages serialize to JSON"))
+}
+
+async fn close<O>(outgoing: &mut O, code: u16, reason: impl Into<String>)
+where
+    O: Sink<Message> + Unpin,
+{
+    let _ = outgoing
+        .send(Message::Close(Some(CloseFrame {
+            code,
+            reason: reason.into().into(),
+        })))
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_credential_from_connection_init_payload() {
+        let credential = |payload: Value| connection_init_credential(Some(&payload));
+
+        assert_eq!(
+            credential(json!({"authorization": "Bearer rtk_live_abc"})),
+            Some("rtk_live_abc".to_string())
+        );
+        assert_eq!(
+            credential(json!({"authorization": "ApiKey rtk_live_abc"})),
+            Some("rtk_live_abc".to_string())
+        );
+        assert_eq!(
+            credential(json!({"access_token": "eyJhbGciOi"})),
+            Some("eyJhbGciOi".to_string())
+        );
+        assert_eq!(credential(json!({"authorization": "Bearer "})), None);
+        assert_eq!(credential(json!({})), None);
+        assert_eq!(connection_init_credential(None), None);
+    }
+
+    #[test]
+    fn test_message_wire_format() {
+        let message: ClientMessage = serde_json::from_str(
+            r#"{"type":"subscribe","id":"1","payload":{"query":"subscription { eventStream(topics: [\"a.b\"]) { id } }"}}"#,
+        )
+        .unwrap();
+        assert!(matches!(message, ClientMessage::Subscribe { ref id, .. } if id == "1"));
+
+        let message: ClientMessage = serde_json::from_str(r#"{"type":"connection_init"}"#).unwrap();
+        assert!(matches!(message, ClientMessage::ConnectionInit { payload: None }));
+
+        assert_eq!(
+            serde_json::to_value(ServerMessage::ConnectionAck).unwrap(),
+            json!({"type": "connection_ack"})
+        );
+        assert_eq!(
+            serde_json::to_value(ServerMessage::Complete { id: "1".to_string() }).unwrap(),
+            json!({"type": "complete", "id": "1"})
+        );
+    }
+
+    #[test]
+    fn test_subscription_limit_error_carries_code() {
+        let error = serde_json::to_value(subscription_limit_error("7".to_string(), 2)).unwrap();
+        assert_eq!(error["type"], "error");
+        assert_eq!(error["id"], "7");
+        assert_eq!(
+            error["payload"][0]["message"],
+            "Subscription limit of 2 per connection reached"
+        );
+        assert_eq!(
+            error["payload"][0]["extensions"]["code"],
+            SUBSCRIPTION_LIMIT_CODE
+        );
+    }
+}
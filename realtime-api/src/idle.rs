@@ -0,0 +1,127 @@
+use prometheus::{CounterVec, Opts};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Idle timeout for connections when the project doesn't set one
+pub const DEFAULT_IDLE_TIMEOUT_SECS: i32 = 300;
+
+/// Close code sent to a connection closed for inactivity
+pub const IDLE_CLOSE_CODE: u16 = 4410;
+
+/// Close reason sent to a connection closed for inactivity
+pub const IDLE_CLOSE_REASON: &str = "idle_timeout";
+
+/// Longest gap between SSE keep-alive frames
+pub const SSE_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
+// How many connections have been closed for inactivity per transport
+lazy_static::lazy_static! {
+    static ref IDLE_CONNECTIONS_CLOSED: CounterVec = CounterVec::new(
+        Opts::new(
+            "realtime_idle_connections_closed_total",
+            "Connections closed after their idle timeout elapsed"
+        ),
+        &["transport"]
+    )
+    .expect("valid metric definition");
+}
+
+/// Counter of connections closed for inactivity, labelled by transport
+pub fn idle_connections_closed_counter() -> &'static CounterVec {
+    &IDLE_CONNECTIONS_CLOSED
+}
+
+/// Record that a connection was closed for inactivity
+pub fn record_idle_connection_closed(transport: &str) {
+    IDLE_CONNECTIONS_CLOSED
+        .with_label_values(&[transport])
+        .inc();
+}
+
+/// Idle timeout for a project's configured number of seconds
+pub fn idle_timeout(idle_timeout_secs: i32) -> Duration {
+    Duration::from_secs(idle_timeout_secs.max(1) as u64)
+}
+
+/// Interval between SSE keep-alive frames: often enough that a live client
+/// confirms it's reading at least twice per idle timeout
+pub fn sse_keep_alive_interval(idle_timeout: Duration) -> Duration {
+    (idle_timeout / 2).min(SSE_KEEP_ALIVE_INTERVAL)
+}
+
+/// Interval between server pings on a WebSocket: a live client answers each
+/// with a pong, so passive subscribers show activity twice per idle timeout
+pub fn websocket_ping_interval(idle_timeout: Duration) -> Duration {
+    idle_timeout / 2
+}
+
+/// When a connection last showed signs of life
+#[derive(Debug, Clone)]
+pub struct ActivityTracker {
+    last_activity: Arc<Mutex<Instant>>,
+}
+
+impl Default for ActivityTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ActivityTracker {
+    pub fn new() -> Self {
+        Self {
+            last_activity: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Record activity now
+    pub fn touch(&self) {
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+
+    /// Time since the last activity
+    pub fn idle_for(&self) -> Duration {
+        self.last_activity.lock().unwrap().elapsed()
+    }
+
+    /// Time left before the connection has been idle for `timeout`
+    pub fn remaining(&self, timeout: Duration) -> Duration {
+        timeout.saturating_sub(self.idle_for())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keep_alive_fits_twice_in_idle_timeout() {
+        assert_eq!(
+            sse_keep_alive_interval(idle_timeout(300)),
+            SSE_KEEP_ALIVE_INTERVAL
+        );
+        assert_eq!(
+            sse_keep_alive_interval(idle_timeout(20)),
+            Duration::from_secs(10)
+        );
+        assert_eq!(idle_timeout(0), Duration::from_secs(1));
+        assert_eq!(
+            websocket_ping_interval(idle_timeout(300)),
+            Duration::from_secs(150)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_activity_resets_the_remaining_time() {
+        let timeout = Duration::from_secs(10);
+        let activity = ActivityTracker::new();
+
+        tokio::time::advance(Duration::from_secs(4)).await;
+        assert_eq!(activity.remaining(timeout), Duration::from_secs(6));
+        activity.touch();
+        assert_eq!(activity.remaining(timeout), timeout);
+        tokio::time::advance(Duration::from_secs(12)).await;
+        assert_eq!(activity.remaining(timeout), Duration::ZERO);
+    }
+}
@@ -0,0 +1,412 @@
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::auth::{AuthError, Claims};
+use crate::config::JwtConfig;
+
+/// Issuer put in tokens this service signs when none is configured
+pub const DEFAULT_JWT_ISSUER: &str = "realtime-platform";
+
+/// Shortest wait between JWKS fetches prompted by an unknown `kid`, so
+/// tokens with made-up key ids can't be used to hammer the JWKS endpoint
+pub const MIN_JWKS_REFETCH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long one JWKS fetch may take
+const JWKS_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A key tokens naming its `kid` are verified with
+#[derive(Clone)]
+struct VerificationKey {
+    key: DecodingKey,
+    /// Algorithm the JWK is pinned to with `alg`, if any
+    algorithm: Option<Algorithm>,
+}
+
+impl VerificationKey {
+    fn from_jwk(jwk: &Jwk) -> jsonwebtoken::errors::Result<Self> {
+        let algorithm = jwk
+            .common
+            .key_algorithm
+            .map(|alg| Algorithm::from_str(&alg.to_string()))
+            .transpose()?;
+        Ok(Self {
+            key: DecodingKey::from_jwk(jwk)?,
+            algorithm,
+        })
+    }
+}
+
+/// Problems with keys in a JWK set that would stop them verifying tokens
+pub fn unusable_keys(jwks: &JwkSet) -> Vec<String> {
+    jwks.keys
+        .iter()
+        .filter_map(|jwk| match (&jwk.common.key_id, VerificationKey::from_jwk(jwk)) {
+            (None, _) => Some("a key has no kid".to_string()),
+            (Some(kid), Err(e)) => Some(format!("key {} is unusable: {}", kid, e)),
+            (Some(_), Ok(_)) => None,
+        })
+        .collect()
+}
+
+/// The usable keys of a JWK set by `kid`
+fn keys_by_id(jwks: &JwkSet) -> HashMap<String, VerificationKey> {
+    jwks.keys
+        .iter()
+        .filter_map(|jwk| {
+            let kid = jwk.common.key_id.clone()?;
+            match VerificationKey::from_jwk(jwk) {
+                Ok(key) => Some((kid, key)),
+                Err(e) => {
+                    warn!("Skipping unusable JWT verification key {}: {}", kid, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Keys fetched from the JWKS URL
+#[derive(Default)]
+struct RemoteKeys {
+    keys: HashMap<String, VerificationKey>,
+    /// Last fetch attempt, successful or not
+    fetched_at: Option<Instant>,
+}
+
+impl RemoteKeys {
+    fn needs_refresh(&self, kid: &str, refresh_interval: Duration) -> bool {
+        match self.fetched_at {
+            None => true,
+            Some(at) if at.elapsed() >= refresh_interval => true,
+            Some(at) => {
+                !self.keys.contains_key(kid)
+                    && at.elapsed() >= MIN_JWKS_REFETCH_INTERVAL.min(refresh_interval)
+            }
+        }
+    }
+}
+
+/// Checks JWT signatures and registered claims. Tokens without a `kid` are
+/// HS256-signed with `jwt_secret`; tokens with one are verified with that key
+/// from the configured JWK set or, failing that, the JWKS URL. Fetched keys
+/// are cached for the refresh interval, so a key removed from the JWKS stops
+/// verifying tokens once the cache next refreshes.
+#[derive(Clone)]
+pub struct JwtVerifier {
+    secret: DecodingKey,
+    issuer: Option<String>,
+    audience: Option<String>,
+    static_keys: Arc<HashMap<String, VerificationKey>>,
+    jwks_url: Option<String>,
+    refresh_interval: Duration,
+    remote: Arc<RwLock<RemoteKeys>>,
+    client: reqwest::Client,
+}
+
+impl std::fmt::Debug for JwtVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtVerifier")
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .field("static_keys", &self.static_keys.keys().collect::<Vec<_>>())
+            .field("jwks_url", &self.jwks_url)
+            .field("refresh_interval", &self.refresh_interval)
+            .finish()
+    }
+}
+
+impl JwtVerifier {
+    /// Verify kid-less HS256 tokens with `secret`, checking no issuer or audience
+    pub fn new(secret: &str) -> Self {
+        Self::from_config(secret, &JwtConfig::default())
+    }
+
+    pub fn from_config(secret: &str, config: &JwtConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(JWKS_FETCH_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Self {
+            secret: DecodingKey::from_secret(secret.as_bytes()),
+            issuer: config.issuer.clone(),
+            audience: config.audience.clone(),
+            static_keys: Arc::new(config.jwks.as_ref().map(keys_by_id).unwrap_or_default()),
+            jwks_url: config.jwks_url.clone(),
+            refresh_interval: Duration::from_secs(config.jwks_refresh_secs),
+            remote: Arc::new(RwLock::new(RemoteKeys::default())),
+            client,
+        }
+    }
+
+    /// The `iss` tokens must carry, if one is configured
+    pub fn issuer(&self) -> Option<&str> {
+        self.issuer.as_deref()
+    }
+
+    /// The `aud` tokens must carry, if one is configured
+    pub fn audience(&self) -> Option<&str> {
+        self.audience.as_deref()
+    }
+
+    /// Verify a token's signature, expiry, issuer and audience
+    pub async fn verify(&self, token: &str) -> Result<Claims, AuthError> {
+        let header = decode_header(token).map_err(rejected)?;
+        let (key, algorithm) = match &header.kid {
+            None => (self.secret.clone(), Algorithm::HS256),
+            Some(kid) => {
+                let key = self.key(kid).await.ok_or_else(|| {
+                    debug!("JWT rejected: unknown kid {}", kid);
+                    AuthError::InvalidJwt
+                })?;
+                (key.key, key.algorithm.unwrap_or(header.alg))
+            }
+        };
+
+        let mut validation = Validation::new(algorithm);
+        let mut required = vec!["exp"];
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+            required.push("iss");
+        }
+        match &self.audience {
+            Some(audience) => {
+                validation.set_audience(&[audience]);
+                required.push("aud");
+            }
+            None => validation.validate_aud = false,
+        }
+        validation.set_required_spec_claims(&required);
+
+        decode::<Claims>(token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(rejected)
+    }
+
+    async fn key(&self, kid: &str) -> Option<VerificationKey> {
+        if let Some(key) = self.static_keys.get(kid) {
+            return Some(key.clone());
+        }
+        let url = self.jwks_url.as_ref()?;
+
+        {
+            let remote = self.remote.read().await;
+            if !remote.needs_refresh(kid, self.refresh_interval) {
+                return remote.keys.get(kid).cloned();
+            }
+        }
+
+        let mut remote = self.remote.write().await;
+        // Another request may have refreshed while this one waited
+        if remote.needs_refresh(kid, self.refresh_interval) {
+            remote.fetched_at = Some(Instant::now());
+            match self.fetch(url).await {
+                Ok(jwks) => {
+                    remote.keys = keys_by_id(&jwks);
+                    info!("Loaded {} JWT verification keys from {}", remote.keys.len(), url);
+                }
+                // Keep the previous keys so an unreachable JWKS endpoint
+                // doesn't lock everyone out
+                Err(e) => warn!("Failed to fetch JWKS from {}: {}", url, e),
+            }
+        }
+        remote.keys.get(kid).cloned()
+    }
+
+    async fn fetch(&self, url: &str) -> reqwest::Result<JwkSet> {
+        self.client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+}
+
+fn rejected(e: jsonwebtoken::errors::Error) -> AuthError {
+    debug!("JWT rejected: {}", e);
+    AuthError::InvalidJwt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Json, Router};
+    use chrono::Utc;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use std::sync::Mutex;
+
+    const SECRET: &str = "default-secret-at-least-32-characters";
+
+    fn claims(iss: &str, aud: Option<&str>, exp_offset_secs: i64) -> Claims {
+        let now = Utc::now().timestamp();
+        Claims {
+            sub: "user_1".to_string(),
+            tenant_id: "tenant_1".to_string(),
+            project_id: "project_1".to_string(),
+            scopes: vec!["EventsPublish".to_string()],
+            exp: now + exp_offset_secs,
+            iat: now,
+            iss: iss.to_string(),
+            aud: aud.map(|aud| serde_json::json!(aud)),
+        }
+    }
+
+    fn sign(claims: &Claims, kid: Option<&str>, secret: &str) -> String {
+        let header = Header {
+            kid: kid.map(str::to_string),
+            ..Header::default()
+        };
+        encode(&header, claims, &EncodingKey::from_secret(secret.as_bytes())).unwrap()
+    }
+
+    fn jwks(keys: &[(&str, &str)]) -> JwkSet {
+        use base64::Engine;
+        let keys: Vec<_> = keys
+            .iter()
+            .map(|(kid, secret)| {
+                serde_json::json!({
+                    "kty": "oct",
+                    "alg": "HS256",
+                    "kid": kid,
+                    "k": base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(secret),
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({ "keys": keys })).unwrap()
+    }
+
+    fn config() -> JwtConfig {
+        JwtConfig {
+            issuer: Some("https://auth.example.com".to_string()),
+            audience: Some("realtime-api".to_string()),
+            ..JwtConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_issuer_and_audience_are_checked() {
+        let verifier = JwtVerifier::from_config(SECRET, &config());
+
+        let valid = claims("https://auth.example.com", Some("realtime-api"), 3600);
+        let verified = verifier.verify(&sign(&valid, None, SECRET)).await.unwrap();
+        assert_eq!(verified.tenant_id, "tenant_1");
+
+        for invalid in [
+            claims("https://evil.example.com", Some("realtime-api"), 3600),
+            claims("https://auth.example.com", Some("another-api"), 3600),
+            claims("https://auth.example.com", None, 3600),
+        ] {
+            assert!(matches!(
+                verifier.verify(&sign(&invalid, None, SECRET)).await,
+                Err(AuthError::InvalidJwt)
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_issuer_is_optional_by_default() {
+        let verifier = JwtVerifier::new(SECRET);
+        let token = sign(&claims("anyone", Some("anything"), 3600), None, SECRET);
+        assert!(verifier.verify(&token).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_expired_token_is_rejected() {
+        let verifier = JwtVerifier::from_config(SECRET, &config());
+        // Past the default 60 second leeway
+        let expired = claims("https://auth.example.com", Some("realtime-api"), -120);
+        assert!(matches!(
+            verifier.verify(&sign(&expired, None, SECRET)).await,
+            Err(AuthError::InvalidJwt)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tokens_verify_with_the_key_named_by_kid() {
+        let config = JwtConfig {
+            jwks: Some(jwks(&[
+                ("2024-01", "key-2024-01-secret-32-characters!"),
+                ("2024-02", "key-2024-02-secret-32-characters!"),
+            ])),
+            ..config()
+        };
+        let verifier = JwtVerifier::from_config(SECRET, &config);
+        let valid = claims("https://auth.example.com", Some("realtime-api"), 3600);
+
+        for (kid, secret) in [
+            ("2024-01", "key-2024-01-secret-32-characters!"),
+            ("2024-02", "key-2024-02-secret-32-characters!"),
+        ] {
+            assert!(verifier.verify(&sign(&valid, Some(kid), secret)).await.is_ok());
+        }
+
+        // Signed with the wrong key for its kid, or naming no known key
+        let wrong_key = sign(&valid, Some("2024-01"), "key-2024-02-secret-32-characters!");
+        assert!(verifier.verify(&wrong_key).await.is_err());
+        let unknown = sign(&valid, Some("2023-12"), "key-2023-12-secret-32-characters!");
+        assert!(verifier.verify(&unknown).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rotated_out_jwks_key_is_rejected_after_refresh() {
+        let served = Arc::new(Mutex::new(jwks(&[("old", "old-key-secret-at-least-32-chars!")])));
+        let app = Router::new().route(
+            "/.well-known/jwks.json",
+            get({
+                let served = served.clone();
+                move || {
+                    let jwks = served.lock().unwrap().clone();
+                    async move { Json(jwks) }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let config = JwtConfig {
+            jwks_url: Some(format!("http://{}/.well-known/jwks.json", address)),
+            jwks_refresh_secs: 1,
+            ..config()
+        };
+        let verifier = JwtVerifier::from_config(SECRET, &config);
+        let valid = claims("https://auth.example.com", Some("realtime-api"), 3600);
+        let old_token = sign(&valid, Some("old"), "old-key-secret-at-least-32-chars!");
+        let new_token = sign(&valid, Some("new"), "new-key-secret-at-least-32-chars!");
+
+        assert!(verifier.verify(&old_token).await.is_ok());
+
+        // Rotate: publish the new key alongside the old one, then drop the old one
+        *served.lock().unwrap() = jwks(&[
+            ("old", "old-key-secret-at-least-32-chars!"),
+            ("new", "new-key-secret-at-least-32-chars!"),
+        ]);
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert!(verifier.verify(&new_token).await.is_ok());
+        assert!(verifier.verify(&old_token).await.is_ok());
+
+        *served.lock().unwrap() = jwks(&[("new", "new-key-secret-at-least-32-chars!")]);
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert!(matches!(
+            verifier.verify(&old_token).await,
+            Err(AuthError::InvalidJwt)
+        ));
+        assert!(verifier.verify(&new_token).await.is_ok());
+    }
+
+    #[test]
+    fn test_unusable_keys_are_reported() {
+        assert!(unusable_keys(&jwks(&[("a", "secret")])).is_empty());
+
+        let mut set = jwks(&[("a", "secret")]);
+        set.keys[0].common.key_id = None;
+        assert_eq!(unusable_keys(&set), vec!["a key has no kid".to_string()]);
+    }
+}
@@ -0,0 +1,219 @@
+use anyhow::Result;
+use rdkafka::config::ClientConfig;
+use rdkafka::message::{Header, OwnedHeaders};
+use rdkafka::producer::{FutureProducer, FutureRecord};
+use rdkafka::util::Timeout;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use crate::config::KafkaConfig;
+use crate::models::Event;
+use crate::nats::{DeadLetterKind, NatsClient};
+use crate::webhooks::RetryPolicy;
+
+/// Published events waiting to be written to Kafka before new ones are dead-lettered
+pub const KAFKA_QUEUE_CAPACITY: usize = 10_000;
+
+/// Longest topic name Kafka accepts
+pub const MAX_KAFKA_TOPIC_LENGTH: usize = 249;
+
+/// Kafka topic for `event`: the template with `{tenant_id}`, `{project_id}`
+/// and `{topic}` substituted, and any character Kafka doesn't allow in topic
+/// names replaced by `_`
+pub fn kafka_topic(template: &str, event: &Event) -> String {
+    template
+        .replace("{tenant_id}", &event.tenant_id)
+        .replace("{project_id}", &event.project_id)
+        .replace("{topic}", &event.topic)
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '_' | '-' => c,
+            _ => '_',
+        })
+        .take(MAX_KAFKA_TOPIC_LENGTH)
+        .collect()
+}
+
+/// Message key deciding the Kafka partition. Events sharing a partition key
+/// keep their order; without one, events of the same topic do.
+pub fn message_key(event: &Event) -> &str {
+    event.partition_key.as_deref().unwrap_or(&event.topic)
+}
+
+impl From<&KafkaConfig> for RetryPolicy {
+    fn from(config: &KafkaConfig) -> Self {
+        Self {
+            max_attempts: config.max_attempts.max(1),
+            initial_backoff: Duration::from_millis(config.initial_backoff_ms),
+            max_backoff: Duration::from_millis(config.max_backoff_ms),
+        }
+    }
+}
+
+/// Why an event was never written to Kafka
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum KafkaSinkError {
+    #[error("Failed to encode event: {0}")]
+    Encode(String),
+    #[error("Kafka produce failed after {attempts} attempts: {message}")]
+    Produce { message: String, attempts: u32 },
+}
+
+/// Writes published events to Kafka, retrying failures with exponential backoff
+#[derive(Clone)]
+pub struct KafkaSink {
+    producer: FutureProducer,
+    topic_template: String,
+    policy: RetryPolicy,
+}
+
+impl KafkaSink {
+    pub fn from_config(config: &KafkaConfig) -> Result<Self> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", &config.brokers)
+            .set("message.timeout.ms", config.message_timeout_ms.to_string())
+            // The producer's own retries must not duplicate or reorder messages
+            .set("enable.idempotence", "true")
+            .create()?;
+        Ok(Self {
+            producer,
+            topic_template: config.topic_template.clone(),
+            policy: config.into(),
+        })
+    }
+
+    pub fn topic_for(&self, event: &Event) -> String {
+        kafka_topic(&self.topic_template, event)
+    }
+
+    /// Write `event` as native JSON, with its headers and identifiers as Kafka
+    /// headers, until the broker acknowledges it or the attempts run out.
+    /// Returns the number of attempts made.
+    pub async fn send(&self, event: &Event) -> Result<u32, KafkaSinkError> {
+        let body = serde_json::to_vec(event).map_err(|e| KafkaSinkError::Encode(e.to_string()))?;
+        let topic = self.topic_for(event);
+        let key = message_key(event);
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let record = FutureRecord::to(&topic)
+                .key(key)
+                .payload(&body)
+                .headers(kafka_headers(event));
+
+            let error = match self.producer.send(record, Timeout::Never).await {
+                Ok(_) => return Ok(attempt),
+                Err((e, _)) => KafkaSinkError::Produce {
+                    message: e.to_string(),
+                    attempts: attempt,
+                },
+            };
+
+            if attempt >= self.policy.max_attempts {
+                return Err(error);
+            }
+            let backoff = self.policy.backoff(attempt);
+            warn!(
+                "Writing event {} to Kafka topic {} failed ({}); retrying in {:?}",
+                event.id, topic, error, backoff
+            );
+            tokio::time::sleep(backoff).await;
+        }
+    }
+}
+
+/// The event's own headers plus the identifiers consumers route on. The
+/// identifiers are reserved header names, so producers can't shadow them.
+fn kafka_headers(event: &Event) -> OwnedHeaders {
+    let identifiers = [
+        ("event_id", event.id.as_str()),
+        ("tenant_id", event.tenant_id.as_str()),
+        ("project_id", event.project_id.as_str()),
+        ("topic", event.topic.as_str()),
+    ];
+    identifiers
+        .into_iter()
+        .chain(
+            event
+                .headers
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str())),
+        )
+        .fold(OwnedHeaders::new(), |headers, (key, value)| {
+            headers.insert(Header {
+                key,
+                value: Some(value),
+            })
+        })
+}
+
+/// Start the worker that writes published events to Kafka. Events are sent
+/// one at a time so Kafka receives them in publish order; events that exhaust
+/// their attempts are dead-lettered.
+pub fn spawn_sink_worker(sink: KafkaSink, nats_client: NatsClient) -> mpsc::Sender<Event> {
+    let (sender, mut receiver) = mpsc::channel::<Event>(KAFKA_QUEUE_CAPACITY);
+
+    tokio::spawn(async move {
+        while let Some(event) = receiver.recv().await {
+            match sink.send(&event).await {
+                Ok(attempts) => {
+                    debug!("Wrote event {} to Kafka in {} attempts", event.id, attempts)
+                }
+                Err(e) => {
+                    warn!("Giving up on writing event {} to Kafka: {}", event.id, e);
+                    let reason = format!("Kafka topic {}: {}", sink.topic_for(&event), e);
+                    if let Err(e) = nats_client
+                        .publish_dead_letter(&event, DeadLetterKind::Kafka, &reason)
+                        .await
+                    {
+                        warn!("Failed to dead-letter event {}: {}", event.id, e);
+                    }
+                }
+            }
+        }
+    });
+
+    sender
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(topic: &str) -> Event {
+        Event::new(
+            "tenant_1".to_string(),
+            "proj:2".to_string(),
+            topic.to_string(),
+            json!({}),
+        )
+    }
+
+    #[test]
+    fn test_kafka_topic_substitutes_and_sanitizes() {
+        let event = event("orders.created");
+        assert_eq!(
+            kafka_topic("realtime.{tenant_id}.{project_id}", &event),
+            "realtime.tenant_1.proj_2"
+        );
+        assert_eq!(
+            kafka_topic("{project_id}-{topic}", &event),
+            "proj_2-orders.created"
+        );
+        assert_eq!(kafka_topic("events", &event), "events");
+
+        let long = format!("{}{{topic}}", "x".repeat(300));
+        assert_eq!(kafka_topic(&long, &event).len(), MAX_KAFKA_TOPIC_LENGTH);
+    }
+
+    #[test]
+    fn test_message_key_prefers_partition_key() {
+        let mut event = event("orders.created");
+        assert_eq!(message_key(&event), "orders.created");
+        event.partition_key = Some("customer-7".to_string());
+        assert_eq!(message_key(&event), "customer-7");
+    }
+}
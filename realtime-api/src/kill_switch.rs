@@ -0,0 +1,78 @@
+use anyhow::Result;
+use serde_json::json;
+use tracing::info;
+
+use crate::database::Database;
+use crate::models::TenantStatus;
+
+/// Audit action recorded when a tenant is suspended
+pub const SUSPEND_AUDIT_ACTION: &str = "kill_switch_activated";
+
+/// Audit action recorded when a suspended tenant is reactivated
+pub const UNSUSPEND_AUDIT_ACTION: &str = "kill_switch_deactivated";
+
+/// Suspend a tenant and terminate its WebSocket and SSE connections.
+/// `actor_api_key_id` is recorded in the audit log; `None` for suspensions
+/// made by the platform, e.g. on a failed payment. Returns the ids of the
+/// terminated connections.
+pub async fn suspend_tenant(
+    database: &Database,
+    tenant_id: &str,
+    reason: &str,
+    actor_api_key_id: Option<&str>,
+) -> Result<Vec<String>> {
+    // Flip the status first so reconnect attempts are rejected
+    database
+        .set_tenant_suspension(tenant_id, TenantStatus::Suspended, Some(reason))
+        .await?;
+
+    let mut terminated = crate::websocket::terminate_tenant_websocket_connections(tenant_id).await;
+    terminated.extend(crate::sse::terminate_tenant_sse_connections(tenant_id).await);
+
+    database
+        .record_audit(
+            tenant_id,
+            actor_api_key_id,
+            SUSPEND_AUDIT_ACTION,
+            Some(tenant_id),
+            &json!({
+                "reason": reason,
+                "terminated_connections": terminated,
+            }),
+        )
+        .await?;
+
+    info!(
+        tenant_id = %tenant_id,
+        reason = %reason,
+        "Suspended tenant and terminated {} connections",
+        terminated.len()
+    );
+
+    Ok(terminated)
+}
+
+/// Reactivate a suspended tenant
+pub async fn unsuspend_tenant(
+    database: &Database,
+    tenant_id: &str,
+    reason: &str,
+    actor_api_key_id: Option<&str>,
+) -> Result<()> {
+    database
+        .set_tenant_suspension(tenant_id, TenantStatus::Active, None)
+        .await?;
+
+    database
+        .record_audit(
+            tenant_id,
+            actor_api_key_id,
+            UNSUSPEND_AUDIT_ACTION,
+            Some(tenant_id),
+            &json!({ "reason": reason }),
+        )
+        .await?;
+
+    info!(tenant_id = %tenant_id, reason = %reason, "Unsuspended tenant");
+    Ok(())
+}
@@ -1,20 +1,57 @@
 // Library module for shared functionality and testing
 pub mod alerting;
 pub mod api;
+pub mod api_error;
+pub mod audit;
 pub mod auth;
+pub mod backpressure;
 pub mod billing;
+pub mod circuit_breaker;
+pub mod cloudevents;
 pub mod config;
+pub mod cors;
 pub mod database;
+pub mod db_retry;
+pub mod dedup;
+pub mod drain;
 pub mod event_service;
 pub mod graphql;
+pub mod graphql_loaders;
+pub mod graphql_ws;
+pub mod idle;
+pub mod jwt;
+#[cfg(feature = "kafka")]
+pub mod kafka_sink;
+pub mod kill_switch;
 pub mod models;
+pub mod mtls;
 pub mod nats;
 pub mod observability;
+pub mod openapi;
+pub mod ordering;
+pub mod project_archive;
+pub mod rate_limit;
 pub mod rbac;
+pub mod redaction;
+pub mod request_id;
+pub mod request_limits;
+pub mod request_signing;
 pub mod routes;
 pub mod schema_validator;
+pub mod secrets;
+pub mod snapshot;
+pub mod shutdown;
 pub mod sse;
+pub mod stripe_webhook;
+pub mod transform;
+pub mod usage_cycle;
+pub mod usage_warnings;
+pub mod webhooks;
 pub mod websocket;
+pub mod ws_compression;
+pub mod ws_framing;
+pub mod ws_rate_limit;
+pub mod ws_resume;
 
 pub use alerting::{Alert, AlertSeverity, AlertingService};
 pub use billing::BillingService;
@@ -23,7 +60,8 @@ pub use api::{AppState, ErrorResponse, PublishEventRequest, PublishEventResponse
 pub use auth::*;
 pub use config::Config;
 pub use database::Database;
-pub use event_service::{EventService, EventSubscription, PublishResult};
+pub use dedup::EventDeduplicator;
+pub use event_service::{EventService, EventSubscription, PublishResult, ECHO_TOPIC};
 pub use graphql::{
     create_schema, graphql_handler, graphql_playground, graphql_subscription_handler, ApiSchema,
 };
@@ -32,12 +70,15 @@ pub use nats::{EventCursor, NatsClient, ReplayRequest, SubscriptionConfig};
 pub use observability::{init_observability, init_tracing, shutdown_tracing, Metrics, add_correlation_id};
 pub use routes::create_router;
 pub use schema_validator::{
-    validate_api_key_security, validate_event_structure, validate_tenant_isolation, SchemaValidator,
+    validate_api_key_security, validate_event_structure, validate_tenant_isolation, SchemaInvalidation,
+    SchemaCheck, SchemaMode, SchemaValidator, TopicSchema, ValidationMode,
 };
 pub use sse::{
-    broadcast_event_to_sse, get_sse_stats, sse_handler, terminate_tenant_sse_connections,
+    broadcast_event_to_sse, get_sse_stats, sse_handler, sse_subscribe_handler,
+    terminate_tenant_sse_connections,
     SSEConnectionParams, SSEMessage,
 };
+pub use transform::{TransformPipeline, TransformStep};
 pub use websocket::{
     broadcast_event_to_websockets, get_websocket_stats, terminate_tenant_websocket_connections,
     WebSocketConnectionParams, WebSocketMessage,
@@ -1,26 +1,67 @@
 use anyhow::Result;
-use tracing::{info, instrument};
+use std::future::{Future, IntoFuture};
+use std::pin::Pin;
+use std::time::Duration;
+use tracing::{info, instrument, warn};
 
 mod alerting;
 mod api;
+mod api_error;
+mod audit;
 mod auth;
+mod backpressure;
 mod billing;
+mod circuit_breaker;
+mod cloudevents;
 mod config;
+mod cors;
 mod database;
+mod db_retry;
+mod dedup;
+mod drain;
 mod event_service;
 mod graphql;
+mod graphql_loaders;
+mod graphql_ws;
+mod idle;
+mod jwt;
+#[cfg(feature = "kafka")]
+mod kafka_sink;
+mod kill_switch;
 mod models;
+mod mtls;
 mod nats;
 mod observability;
+mod openapi;
+mod ordering;
+mod project_archive;
+mod rate_limit;
 mod rbac;
+mod redaction;
+mod request_id;
+mod request_limits;
+mod request_signing;
 mod routes;
 mod schema_validator;
+mod secrets;
+mod snapshot;
+mod shutdown;
 mod sse;
+mod stripe_webhook;
+mod transform;
+mod usage_cycle;
+mod usage_warnings;
+mod webhooks;
 mod websocket;
+mod ws_compression;
+mod ws_framing;
+mod ws_rate_limit;
+mod ws_resume;
 
 use alerting::AlertingService;
 use api::AppState;
 use auth::AuthService;
+use circuit_breaker::CircuitBreaker;
 use config::Config;
 use database::Database;
 use event_service::EventService;
@@ -32,12 +73,33 @@ use schema_validator::SchemaValidator;
 #[tokio::main]
 #[instrument]
 async fn main() -> Result<()> {
-    // Load configuration
-    let config = Config::from_env()?;
+    // Load configuration, from a file when CONFIG_FILE points at one
+    let config = match std::env::var("CONFIG_FILE") {
+        Ok(path) => Config::from_file(path)?,
+        Err(_) => Config::from_env()?,
+    };
+    if let Err(errors) = config.validate() {
+        // Logging isn't set up yet, so report straight to stderr
+        eprintln!("Invalid configuration:");
+        for error in &errors {
+            eprintln!("  - {}", error);
+        }
+        std::process::exit(1);
+    }
 
     // Initialize comprehensive observability (tracing, metrics, alerting)
     info!("Initializing observability...");
     let metrics = init_observability(&config).await?;
+
+    // Sample the SLA counters so /admin/sla can report a recent window
+    let sla_metrics = metrics.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(observability::SLA_SAMPLE_INTERVAL);
+        loop {
+            interval.tick().await;
+            sla_metrics.sample_sla();
+        }
+    });
     
     // Initialize alerting service
     let alerting = AlertingService::new(config.observability.clone());
@@ -47,7 +109,10 @@ async fn main() -> Result<()> {
 
     // Initialize database connection
     info!("Connecting to database...");
-    let database = Database::new(&config.database.url).await?;
+    let database = Database::new(&config.database).await?.with_circuit_breaker(
+        CircuitBreaker::new(circuit_breaker::DATABASE_DEPENDENCY, &config.circuit_breaker)
+            .with_alerting(alerting.clone()),
+    );
 
     // Run database migrations
     database.migrate().await?;
@@ -55,17 +120,134 @@ async fn main() -> Result<()> {
 
     // Initialize NATS connection
     info!("Connecting to NATS...");
-    let nats_client = NatsClient::new(&config.nats.url, config.nats.stream_name.clone()).await?;
+    let nats_client = NatsClient::new(&config.nats.url, config.nats.stream_name.clone())
+        .await?
+        .with_max_ack_pending(config.nats.max_ack_pending)
+        .with_publish_buffer_size(config.nats.publish_buffer_size)
+        .with_publish_buffer_timeout(Duration::from_millis(
+            config.nats.publish_buffer_timeout_ms,
+        ))
+        .with_tenant_streams(&config.nats.tenant_streams)
+        .with_circuit_breaker(
+            CircuitBreaker::new(circuit_breaker::NATS_DEPENDENCY, &config.circuit_breaker)
+                .with_alerting(alerting.clone()),
+        );
     info!("NATS connection established");
 
     // Initialize schema validator
-    let schema_validator = SchemaValidator::new();
+    let schema_validator = SchemaValidator::new().with_store(database.clone());
 
-    // Initialize event service
-    let event_service = EventService::new(database.clone(), nats_client, schema_validator);
+    // Per-key and per-project rate limits share one store
+    let rate_limit_store = rate_limit::rate_limit_store(&config.rate_limit).await?;
 
     // Initialize auth service
-    let auth_service = AuthService::new(database.clone(), config.jwt_secret.clone());
+    let auth_service = AuthService::new(database.clone(), config.jwt_secret.clone())
+        .with_jwt_config(&config.jwt)
+        .with_stale_key_days(config.server.stale_api_key_days)
+        .with_rotation_grace_period(Duration::from_secs(config.server.api_key_rotation_grace_secs))
+        .with_platform_admin_tenant(config.server.platform_admin_tenant_id.clone())
+        .with_trusted_proxies(config.server.trusted_proxies.clone())
+        .with_signing_secret_key(config.signing_secret_key.as_deref())
+        .with_rate_limit_store(rate_limit_store.clone());
+
+    // Webhook secrets registered before they were stored encrypted
+    let sealed = database
+        .seal_plaintext_webhook_secrets(auth_service.signing_cipher())
+        .await?;
+    if sealed > 0 {
+        info!("Encrypted {} stored webhook secrets", sealed);
+    }
+
+    // Initialize event service
+    let event_service = EventService::new(database.clone(), nats_client, schema_validator)
+        .with_dedup_windows(config.events.dedup_window_secs.clone())
+        .with_snapshot_topics(
+            config.events.snapshot_key_fields.clone(),
+            config.events.snapshot_max_keys_per_topic,
+        )
+        .with_echo_topic(config.events.echo_topic_enabled)
+        .with_max_batch_size(config.events.max_batch_size)
+        .with_metadata_limits(config.events.metadata_limits.clone())
+        .with_idempotency_ttl(Duration::from_secs(config.events.idempotency_key_ttl_secs))
+        .with_redaction(&config.events.redaction)
+        .with_rate_limit_store(rate_limit_store.clone())
+        .with_usage_warnings(config.billing.usage_warning_thresholds.clone(), alerting.clone())
+        .with_webhooks(webhooks::WebhookDispatcher::from_config(
+            &config.webhooks,
+            auth_service.signing_cipher().clone(),
+        ));
+    // Config validation has rejected kafka.enabled in builds without the sink
+    #[cfg(feature = "kafka")]
+    let event_service = if config.kafka.enabled {
+        info!("Writing published events to Kafka at {}", config.kafka.brokers);
+        event_service.with_kafka_sink(kafka_sink::KafkaSink::from_config(&config.kafka)?)
+    } else {
+        event_service
+    };
+    ordering::ordering_verifier().set_enabled(config.events.verify_ordering);
+    drain::set_prune_closed_connections(config.events.prune_closed_connections);
+    backpressure::set_close_lagged_connections(config.events.close_lagged_connections);
+    ws_rate_limit::set_inbound_rate_limit(
+        config.websocket.inbound_messages_per_sec,
+        config.websocket.close_on_inbound_rate_limit,
+    );
+    ws_compression::set_compression(
+        config.websocket.compression_threshold_bytes,
+        config.websocket.compression_level,
+    );
+
+    // Sweep expired idempotency keys; expired keys are also replaced on reuse
+    let sweeper_database = database.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+        loop {
+            interval.tick().await;
+            match sweeper_database.delete_expired_idempotency_keys().await {
+                Ok(0) => {}
+                Ok(deleted) => info!("Deleted {} expired idempotency keys", deleted),
+                Err(e) => warn!("Failed to delete expired idempotency keys: {}", e),
+            }
+        }
+    });
+
+    // Forget rotated API key secrets once their grace period has ended; lookups
+    // already ignore them after that, this just removes the stored hashes
+    let rotation_database = database.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(5 * 60));
+        loop {
+            interval.tick().await;
+            match rotation_database.clear_expired_api_key_secrets().await {
+                Ok(0) => {}
+                Ok(cleared) => info!("Deactivated {} rotated API key secrets", cleared),
+                Err(e) => warn!("Failed to deactivate rotated API key secrets: {}", e),
+            }
+        }
+    });
+
+    // Close billing cycles that have ended, archiving their usage
+    let usage_cycle_database = database.clone();
+    let usage_cycle_anchor = config.billing.usage_cycle_anchor;
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(60 * 60));
+        loop {
+            interval.tick().await;
+            match usage_cycle::roll_over_due_cycles(
+                &usage_cycle_database,
+                usage_cycle_anchor,
+                chrono::Utc::now(),
+            )
+            .await
+            {
+                Ok(0) => {}
+                Ok(closed) => info!("Rolled over {} usage cycles", closed),
+                Err(e) => warn!("Failed to roll over usage cycles: {}", e),
+            }
+        }
+    });
+
+    // Kept for flushing buffered publishes during shutdown
+    let shutdown_event_service = event_service.clone();
 
     // Create application state
     let app_state = AppState {
@@ -74,6 +256,13 @@ async fn main() -> Result<()> {
         auth_service,
         metrics,
         alerting,
+        max_page_size: config.server.max_page_size,
+        metadata_limits: config.events.metadata_limits.clone(),
+        metrics_enabled: config.observability.metrics_endpoint.is_some(),
+        billing: config.billing.clone(),
+        cors: config.cors.clone(),
+        graphql: config.graphql.clone(),
+        http: config.http.clone(),
     };
 
     // Create the router
@@ -90,10 +279,35 @@ async fn main() -> Result<()> {
 
     info!("Realtime API server started successfully");
 
-    // Start the server
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    // Start the server; on shutdown, streaming clients are told to reconnect and
+    // in-flight requests and NATS publishes get the grace period to finish
+    let (shutdown_started, shutdown_started_rx) = tokio::sync::oneshot::channel();
+    let on_shutdown = shutdown::on_shutdown_signal(shutdown_signal(), shutdown_started);
+    let server: Pin<Box<dyn Future<Output = std::io::Result<()>> + Send>> = if config.tls.enabled() {
+        if config.tls.client_auth_enabled() {
+            info!("Serving HTTPS; client certificates are accepted for authentication");
+        } else {
+            info!("Serving HTTPS");
+        }
+        let acceptor = mtls::tls_acceptor(&config.tls)?;
+        Box::pin(mtls::serve_tls(listener, acceptor, app, on_shutdown))
+    } else {
+        Box::pin(
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+            )
+            .with_graceful_shutdown(on_shutdown)
+            .into_future(),
+        )
+    };
+    shutdown::serve_with_grace_period(
+        server,
+        shutdown_started_rx,
+        Duration::from_secs(config.server.shutdown_grace_period_secs),
+        &shutdown_event_service,
+    )
+    .await?;
 
     info!("Server shut down gracefully");
     Ok(())
@@ -1,8 +1,14 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use sqlx::FromRow;
+use thiserror::Error;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::cloudevents::DeliveryFormat;
+use crate::schema_validator::ValidationMode;
+
 /// Tenant represents an organization or customer account with isolated resources
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Tenant {
@@ -16,14 +22,19 @@ pub struct Tenant {
 }
 
 /// Project represents a subdivision within a tenant for organizing applications
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Project {
     pub id: String,
     pub tenant_id: String,
     pub name: String,
     pub limits: ProjectLimits,
+    pub settings: ProjectSettings,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Set when the project has been archived (soft deleted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[sqlx(default)]
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 /// API key for authentication with specific scopes and rate limits
@@ -32,17 +43,73 @@ pub struct ApiKey {
     pub id: String,
     pub tenant_id: String,
     pub project_id: String,
+    /// Indexed SHA-256 lookup value (a prefix, or the full digest for legacy keys)
     pub key_hash: String,
+    /// Last four characters of the raw key; None for keys created before they were kept
+    #[serde(default)]
+    pub key_last4: Option<String>,
+    /// Argon2id hash used to verify the key; None until a legacy key is re-hashed
+    #[serde(default, skip_serializing)]
+    pub secret_hash: Option<String>,
     pub scopes: Vec<Scope>,
     pub rate_limit_per_sec: i32,
+    /// NATS-style topic patterns this key may use; empty allows every topic
+    #[serde(default)]
+    pub allowed_topics: Vec<String>,
     pub is_active: bool,
     pub expires_at: Option<DateTime<Utc>>,
+    /// Last successful validation, recorded at most once per minute
+    #[serde(default)]
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// Client address of the last recorded use
+    #[serde(default)]
+    pub last_used_ip: Option<String>,
+    /// Secret for HMAC-signed requests, encrypted as `SigningSecretCipher`
+    /// seals it; None for keys without one
+    #[serde(default, skip_serializing)]
+    pub signing_secret: Option<String>,
+    /// Encrypted signing secret replaced by the last rotation
+    #[serde(default, skip_serializing)]
+    pub previous_signing_secret: Option<String>,
+    /// Lookup value of the secret replaced by the last rotation
+    #[serde(default, skip_serializing)]
+    pub previous_key_hash: Option<String>,
+    /// Argon2id hash of the secret replaced by the last rotation
+    #[serde(default, skip_serializing)]
+    pub previous_secret_hash: Option<String>,
+    /// When the replaced secret stops being accepted
+    #[serde(default)]
+    pub previous_expires_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-/// Event represents a message published to a specific topic
+/// Client certificate registered for mTLS authentication, matched by the
+/// SHA-256 fingerprint of its public key or, failing that, by its subject
+/// and issuer
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ClientCert {
+    pub id: String,
+    pub tenant_id: String,
+    pub project_id: String,
+    /// Lowercase hex SHA-256 of the certificate's SubjectPublicKeyInfo
+    pub spki_sha256: Option<String>,
+    /// Distinguished name in RFC 4514 form, e.g. `CN=billing,O=Acme`
+    pub subject: Option<String>,
+    /// Distinguished name of the CA a subject registration is pinned to
+    pub issuer: Option<String>,
+    pub scopes: Vec<Scope>,
+    pub rate_limit_per_sec: i32,
+    /// NATS-style topic patterns the certificate may use; empty allows every topic
+    #[serde(default)]
+    pub allowed_topics: Vec<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Event represents a message published to a specific topic
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Event {
     pub id: String,
     pub tenant_id: String,
@@ -50,6 +117,36 @@ pub struct Event {
     pub topic: String,
     pub payload: serde_json::Value,
     pub published_at: DateTime<Utc>,
+    /// Ordering key: events sharing a key are stored and delivered in the
+    /// order they were accepted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[sqlx(default)]
+    pub partition_key: Option<String>,
+    /// Producer metadata kept apart from the payload (trace id, schema
+    /// version, source), delivered alongside the event
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[sqlx(default, json)]
+    pub headers: HashMap<String, String>,
+    /// Producer metadata about the event, within the configured
+    /// `MetadataLimits`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[sqlx(default, json)]
+    pub metadata: HashMap<String, String>,
+    /// Attributes describing the event, within the configured `MetadataLimits`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[sqlx(default, json)]
+    pub attributes: HashMap<String, String>,
+    /// Whether the payload conformed to its topic's schema when published,
+    /// including warn-mode schemas that let violations through. Topics without
+    /// a schema count as conforming; `None` when validation was off or for
+    /// events published before this was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[sqlx(default)]
+    pub schema_valid: Option<bool>,
+    /// Version of the topic schema the payload was checked against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[sqlx(default)]
+    pub schema_version: Option<i32>,
 }
 
 /// Usage record for tracking resource consumption
@@ -62,11 +159,14 @@ pub struct UsageRecord {
     pub quantity: i64,
     pub window_start: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
+    /// API key that generated the usage, if any
+    pub key_id: Option<String>,
 }
 
 /// Tenant status enumeration
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "tenant_status", rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
 pub enum TenantStatus {
     Active,
     Trial,
@@ -95,8 +195,20 @@ pub enum UsageMetric {
     ApiRequests,
 }
 
+impl UsageMetric {
+    /// Name stored in the database and used in exports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UsageMetric::EventsPublished => "events_published",
+            UsageMetric::EventsDelivered => "events_delivered",
+            UsageMetric::WebSocketMinutes => "web_socket_minutes",
+            UsageMetric::ApiRequests => "api_requests",
+        }
+    }
+}
+
 /// User role enumeration for RBAC
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "user_role", rename_all = "snake_case")]
 pub enum UserRole {
     Owner,
@@ -143,7 +255,7 @@ pub struct RolePermission {
 }
 
 /// Billing plan configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub enum BillingPlan {
     Free {
         monthly_events: i64,
@@ -157,12 +269,56 @@ pub enum BillingPlan {
     },
 }
 
+impl BillingPlan {
+    /// Events a tenant may publish per month, or `None` when unlimited
+    pub fn monthly_event_limit(&self) -> Option<i64> {
+        match self {
+            BillingPlan::Free { monthly_events } | BillingPlan::Pro { monthly_events, .. } => {
+                Some(*monthly_events)
+            }
+            BillingPlan::Enterprise { .. } => None,
+        }
+    }
+}
+
 /// Project limits configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ProjectLimits {
     pub max_connections: i32,
     pub max_events_per_sec: i32,
     pub max_payload_size: i32,
+    /// Undelivered messages buffered per connection before the oldest are dropped
+    #[serde(default = "default_delivery_buffer_size")]
+    pub delivery_buffer_size: i32,
+    /// Seconds a WebSocket or SSE connection may stay idle before it's closed
+    #[serde(default = "default_idle_timeout_secs")]
+    pub idle_timeout_secs: i32,
+    /// Distinct topics one WebSocket connection may subscribe to
+    #[serde(default = "default_max_subscriptions_per_connection")]
+    pub max_subscriptions_per_connection: i32,
+}
+
+fn default_delivery_buffer_size() -> i32 {
+    crate::backpressure::DEFAULT_DELIVERY_BUFFER_SIZE
+}
+
+fn default_idle_timeout_secs() -> i32 {
+    crate::idle::DEFAULT_IDLE_TIMEOUT_SECS
+}
+
+fn default_max_subscriptions_per_connection() -> i32 {
+    crate::websocket::DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION
+}
+
+/// Project-level feature settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct ProjectSettings {
+    /// Infer and register a warn-mode schema from the first event on a new topic
+    pub auto_register_schema_from_first_event: bool,
+    /// Whether schema violations block publishing, are only recorded, or
+    /// aren't checked at all
+    pub validation_mode: ValidationMode,
 }
 
 impl Default for ProjectLimits {
@@ -171,6 +327,145 @@ impl Default for ProjectLimits {
             max_connections: 1000,
             max_events_per_sec: 100,
             max_payload_size: 1024 * 1024, // 1MB
+            delivery_buffer_size: default_delivery_buffer_size(),
+            idle_timeout_secs: default_idle_timeout_secs(),
+            max_subscriptions_per_connection: default_max_subscriptions_per_connection(),
+        }
+    }
+}
+
+impl ProjectLimits {
+    /// Default limits for a project created under the given billing plan
+    pub fn for_plan(plan: &BillingPlan) -> Self {
+        match plan {
+            BillingPlan::Free { .. } => Self {
+                max_connections: 100,
+                max_events_per_sec: 10,
+                max_payload_size: 256 * 1024, // 256KB
+                delivery_buffer_size: 256,
+                idle_timeout_secs: default_idle_timeout_secs(),
+                max_subscriptions_per_connection: 50,
+            },
+            BillingPlan::Pro { .. } => Self::default(),
+            BillingPlan::Enterprise { .. } => Self {
+                max_connections: 10000,
+                max_events_per_sec: 1000,
+                max_payload_size: 1024 * 1024, // 1MB
+                delivery_buffer_size: 10000,
+                idle_timeout_secs: default_idle_timeout_secs(),
+                max_subscriptions_per_connection: 1000,
+            },
+        }
+    }
+
+    /// These limits once the tenant moves from plan `from` to `to`. A limit
+    /// still at the old plan's default takes the new plan's; any other is
+    /// capped at the new plan's.
+    pub fn for_plan_change(&self, from: &BillingPlan, to: &BillingPlan) -> Self {
+        let (old, new) = (Self::for_plan(from), Self::for_plan(to));
+        let rebase = |current: i32, old: i32, new: i32| {
+            if current == old {
+                new
+            } else {
+                current.min(new)
+            }
+        };
+        Self {
+            max_connections: rebase(
+                self.max_connections,
+                old.max_connections,
+                new.max_connections,
+            ),
+            max_events_per_sec: rebase(
+                self.max_events_per_sec,
+                old.max_events_per_sec,
+                new.max_events_per_sec,
+            ),
+            max_payload_size: rebase(
+                self.max_payload_size,
+                old.max_payload_size,
+                new.max_payload_size,
+            ),
+            delivery_buffer_size: rebase(
+                self.delivery_buffer_size,
+                old.delivery_buffer_size,
+                new.delivery_buffer_size,
+            ),
+            idle_timeout_secs: rebase(
+                self.idle_timeout_secs,
+                old.idle_timeout_secs,
+                new.idle_timeout_secs,
+            ),
+            max_subscriptions_per_connection: rebase(
+                self.max_subscriptions_per_connection,
+                old.max_subscriptions_per_connection,
+                new.max_subscriptions_per_connection,
+            ),
+        }
+    }
+
+    /// These limits with each lowered to at most the plan's, the most a
+    /// project on that plan may set
+    pub fn capped_to_plan(&self, plan: &BillingPlan) -> Self {
+        let ceiling = Self::for_plan(plan);
+        Self {
+            max_connections: self.max_connections.min(ceiling.max_connections),
+            max_events_per_sec: self.max_events_per_sec.min(ceiling.max_events_per_sec),
+            max_payload_size: self.max_payload_size.min(ceiling.max_payload_size),
+            delivery_buffer_size: self.delivery_buffer_size.min(ceiling.delivery_buffer_size),
+            idle_timeout_secs: self.idle_timeout_secs.min(ceiling.idle_timeout_secs),
+            max_subscriptions_per_connection: self
+                .max_subscriptions_per_connection
+                .min(ceiling.max_subscriptions_per_connection),
+        }
+    }
+
+    /// Every limit must be positive
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("max_connections", self.max_connections),
+            ("max_events_per_sec", self.max_events_per_sec),
+            ("max_payload_size", self.max_payload_size),
+            ("delivery_buffer_size", self.delivery_buffer_size),
+            ("idle_timeout_secs", self.idle_timeout_secs),
+            (
+                "max_subscriptions_per_connection",
+                self.max_subscriptions_per_connection,
+            ),
+        ] {
+            if value <= 0 {
+                return Err(format!("{} must be positive", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Partial update of a project's limits; omitted fields keep their value
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ProjectLimitsUpdate {
+    pub max_connections: Option<i32>,
+    pub max_events_per_sec: Option<i32>,
+    pub max_payload_size: Option<i32>,
+    pub delivery_buffer_size: Option<i32>,
+    pub idle_timeout_secs: Option<i32>,
+    pub max_subscriptions_per_connection: Option<i32>,
+}
+
+impl ProjectLimitsUpdate {
+    /// The limits that result from applying this update to `limits`
+    pub fn apply_to(&self, limits: &ProjectLimits) -> ProjectLimits {
+        ProjectLimits {
+            max_connections: self.max_connections.unwrap_or(limits.max_connections),
+            max_events_per_sec: self.max_events_per_sec.unwrap_or(limits.max_events_per_sec),
+            max_payload_size: self.max_payload_size.unwrap_or(limits.max_payload_size),
+            delivery_buffer_size: self
+                .delivery_buffer_size
+                .unwrap_or(limits.delivery_buffer_size),
+            idle_timeout_secs: self.idle_timeout_secs.unwrap_or(limits.idle_timeout_secs),
+            max_subscriptions_per_connection: self
+                .max_subscriptions_per_connection
+                .unwrap_or(limits.max_subscriptions_per_connection),
         }
     }
 }
@@ -205,10 +500,17 @@ impl Project {
             tenant_id,
             name,
             limits: ProjectLimits::default(),
+            settings: ProjectSettings::default(),
             created_at: now,
             updated_at: now,
+            archived_at: None,
         }
     }
+
+    /// Whether the project has been archived
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
 }
 
 impl ApiKey {
@@ -226,15 +528,30 @@ impl ApiKey {
             tenant_id,
             project_id,
             key_hash,
+            key_last4: None,
+            secret_hash: None,
             scopes,
             rate_limit_per_sec,
+            allowed_topics: Vec::new(),
             is_active: true,
             expires_at: None,
+            last_used_at: None,
+            last_used_ip: None,
+            signing_secret: None,
+            previous_signing_secret: None,
+            previous_key_hash: None,
+            previous_secret_hash: None,
+            previous_expires_at: None,
             created_at: now,
             updated_at: now,
         }
     }
 
+    /// Whether the key hasn't been used (or, if never used, was created) within `max_idle`
+    pub fn is_stale(&self, max_idle: chrono::Duration) -> bool {
+        self.last_used_at.unwrap_or(self.created_at) < Utc::now() - max_idle
+    }
+
     /// Check if API key has a specific scope
     pub fn has_scope(&self, scope: &Scope) -> bool {
         self.scopes.contains(scope)
@@ -244,9 +561,455 @@ impl ApiKey {
     pub fn is_valid(&self) -> bool {
         self.is_active && self.expires_at.is_none_or(|exp| exp > Utc::now())
     }
+
+    /// Identify the key to operators without revealing it, e.g. `rtp_...x7Qz`
+    pub fn key_hint(&self) -> Option<String> {
+        self.key_last4
+            .as_ref()
+            .map(|last4| format!("{}...{}", crate::auth::API_KEY_PREFIX, last4))
+    }
+
+    /// Attach the Argon2id hash used to verify the key
+    pub fn with_secret_hash(mut self, secret_hash: String) -> Self {
+        self.secret_hash = Some(secret_hash);
+        self
+    }
+
+    /// Restrict the key to topics matching the given patterns
+    pub fn with_allowed_topics(mut self, allowed_topics: Vec<String>) -> Self {
+        self.allowed_topics = allowed_topics;
+        self
+    }
+
+    /// Attach the encrypted secret used to sign requests made with the key
+    pub fn with_signing_secret(mut self, signing_secret: String) -> Self {
+        self.signing_secret = Some(signing_secret);
+        self
+    }
+
+    /// Check if the key may publish or subscribe to a topic
+    pub fn allows_topic(&self, topic: &str) -> bool {
+        topic_allowed(&self.allowed_topics, topic)
+    }
+}
+
+impl ClientCert {
+    fn new(
+        tenant_id: String,
+        project_id: String,
+        spki_sha256: Option<String>,
+        issuer_and_subject: Option<(String, String)>,
+        scopes: Vec<Scope>,
+        rate_limit_per_sec: i32,
+    ) -> Self {
+        let now = Utc::now();
+        let (issuer, subject) = issuer_and_subject.unzip();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            tenant_id,
+            project_id,
+            spki_sha256,
+            subject,
+            issuer,
+            scopes,
+            rate_limit_per_sec,
+            allowed_topics: Vec::new(),
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Register the certificate whose public key has this SPKI fingerprint
+    pub fn for_fingerprint(
+        tenant_id: String,
+        project_id: String,
+        spki_sha256: String,
+        scopes: Vec<Scope>,
+        rate_limit_per_sec: i32,
+    ) -> Self {
+        let spki_sha256 = spki_sha256.to_ascii_lowercase();
+        Self::new(tenant_id, project_id, Some(spki_sha256), None, scopes, rate_limit_per_sec)
+    }
+
+    /// Register any certificate with this subject issued by this CA. Other
+    /// trusted CAs issuing the same subject don't match.
+    pub fn for_subject(
+        tenant_id: String,
+        project_id: String,
+        issuer: String,
+        subject: String,
+        scopes: Vec<Scope>,
+        rate_limit_per_sec: i32,
+    ) -> Self {
+        Self::new(
+            tenant_id,
+            project_id,
+            None,
+            Some((issuer, subject)),
+            scopes,
+            rate_limit_per_sec,
+        )
+    }
+
+    /// Restrict the certificate to topics matching the given patterns
+    pub fn with_allowed_topics(mut self, allowed_topics: Vec<String>) -> Self {
+        self.allowed_topics = allowed_topics;
+        self
+    }
+}
+
+/// Whether a topic matches a NATS-style pattern: `*` matches exactly one
+/// dot-separated token and a trailing `>` matches one or more tokens
+pub fn topic_pattern_matches(pattern: &str, topic: &str) -> bool {
+    let mut pattern_tokens = pattern.split('.');
+    let mut topic_tokens = topic.split('.');
+
+    loop {
+        match (pattern_tokens.next(), topic_tokens.next()) {
+            (Some(">"), Some(_)) => return pattern_tokens.next().is_none(),
+            (Some("*"), Some(_)) => {}
+            (Some(pattern_token), Some(topic_token)) if pattern_token == topic_token => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Whether every topic matched by `subscription` is also matched by `pattern`,
+/// so a wildcard subscription can't reach beyond a restriction. For concrete
+/// topics this is the same as [`topic_pattern_matches`].
+pub fn topic_pattern_covers(pattern: &str, subscription: &str) -> bool {
+    let mut pattern_tokens = pattern.split('.');
+    let mut subscription_tokens = subscription.split('.');
+
+    loop {
+        match (pattern_tokens.next(), subscription_tokens.next()) {
+            (Some(">"), Some(_)) => return pattern_tokens.next().is_none(),
+            (Some(_), Some(">")) => return false,
+            (Some("*"), Some(_)) => {}
+            (Some(pattern_token), Some(subscription_token))
+                if pattern_token == subscription_token => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Whether a topic is permitted by a list of patterns (an empty list allows all topics)
+pub fn topic_allowed(allowed_topics: &[String], topic: &str) -> bool {
+    allowed_topics.is_empty()
+        || allowed_topics
+            .iter()
+            .any(|pattern| topic_pattern_matches(pattern, topic))
+}
+
+/// Maximum topic length accepted by the platform
+pub const MAX_TOPIC_LENGTH: usize = 255;
+
+/// Maximum partition key length accepted by the platform
+pub const MAX_PARTITION_KEY_LENGTH: usize = 255;
+
+/// Number of partitions that partition keys are hashed into
+pub const EVENT_PARTITIONS: u32 = 16;
+
+/// Maximum number of headers on one event
+pub const MAX_EVENT_HEADERS: usize = 32;
+
+/// Maximum length of an event header name
+pub const MAX_HEADER_NAME_LENGTH: usize = 64;
+
+/// Maximum length of an event header value
+pub const MAX_HEADER_VALUE_LENGTH: usize = 1024;
+
+/// Prefix of the CloudEvents headers the platform sets on stream messages
+pub const RESERVED_HEADER_PREFIX: &str = "ce-";
+
+/// Headers the platform sets on stream messages; producers can't override them
+pub const SYSTEM_HEADERS: [&str; 8] = [
+    "tenant_id",
+    "project_id",
+    "topic",
+    "event_id",
+    "published_at",
+    "partition_key",
+    "partition",
+    "redeliver_to",
+];
+
+/// Whether a header name is set by the platform rather than the producer.
+/// Names are compared case-insensitively, as NATS and HTTP treat them.
+pub fn is_reserved_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.starts_with(RESERVED_HEADER_PREFIX)
+        || name.starts_with("nats-")
+        || SYSTEM_HEADERS.contains(&name.as_str())
+}
+
+/// Header names are HTTP tokens so they carry over to NATS and webhook requests
+fn valid_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_HEADER_NAME_LENGTH
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+}
+
+/// Default maximum serialized payload size (1MB)
+pub const DEFAULT_MAX_PAYLOAD_SIZE: usize = 1024 * 1024;
+
+/// Errors raised when an event fails its construction invariants
+#[derive(Debug, Error, PartialEq)]
+pub enum EventBuildError {
+    #[error("Tenant ID cannot be empty")]
+    EmptyTenantId,
+    #[error("Project ID cannot be empty")]
+    EmptyProjectId,
+    #[error("Invalid topic: {0}")]
+    InvalidTopic(String),
+    #[error("Payload of {size} bytes exceeds the {limit} byte limit")]
+    PayloadTooLarge { size: usize, limit: usize },
+    #[error("Invalid partition key: {0}")]
+    InvalidPartitionKey(String),
+    #[error("Invalid header: {0}")]
+    InvalidHeader(String),
+    #[error("Header '{0}' is reserved for the platform")]
+    ReservedHeader(String),
+}
+
+/// Builder that validates an event before it can be constructed
+#[derive(Debug, Clone)]
+pub struct EventBuilder {
+    tenant_id: String,
+    project_id: String,
+    topic: String,
+    payload: serde_json::Value,
+    partition_key: Option<String>,
+    headers: HashMap<String, String>,
+    metadata: HashMap<String, String>,
+    attributes: HashMap<String, String>,
+    max_payload_size: usize,
+}
+
+impl Default for EventBuilder {
+    fn default() -> Self {
+        Self {
+            tenant_id: String::new(),
+            project_id: String::new(),
+            topic: String::new(),
+            payload: serde_json::Value::Null,
+            partition_key: None,
+            headers: HashMap::new(),
+            metadata: HashMap::new(),
+            attributes: HashMap::new(),
+            max_payload_size: DEFAULT_MAX_PAYLOAD_SIZE,
+        }
+    }
+}
+
+impl EventBuilder {
+    pub fn tenant_id(mut self, tenant_id: impl Into<String>) -> Self {
+        self.tenant_id = tenant_id.into();
+        self
+    }
+
+    pub fn project_id(mut self, project_id: impl Into<String>) -> Self {
+        self.project_id = project_id.into();
+        self
+    }
+
+    pub fn topic(mut self, topic: impl Into<String>) -> Self {
+        self.topic = topic.into();
+        self
+    }
+
+    pub fn payload(mut self, payload: serde_json::Value) -> Self {
+        self.payload = payload;
+        self
+    }
+
+    /// Order the event after earlier events with the same key.
+    ///
+    /// Every subscriber and replay sees events sharing a key in the order
+    /// their publishes were acknowledged by the stream. Each key hashes into
+    /// one of [`EVENT_PARTITIONS`] partitions, published under its own
+    /// `.p{n}` subject suffix, so consumers can process partitions in
+    /// parallel without reordering any key. No order is guaranteed between
+    /// different keys.
+    pub fn partition_key(mut self, partition_key: Option<String>) -> Self {
+        self.partition_key = partition_key;
+        self
+    }
+
+    /// Attach producer metadata delivered alongside the payload
+    pub fn headers(mut self, headers: HashMap<String, String>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Attach metadata kept with the event; callers check it against the
+    /// configured `MetadataLimits`
+    pub fn metadata(mut self, metadata: HashMap<String, String>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Attach attributes kept with the event; callers check them against the
+    /// configured `MetadataLimits`
+    pub fn attributes(mut self, attributes: HashMap<String, String>) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Override the serialized payload size limit (defaults to 1MB)
+    pub fn max_payload_size(mut self, max_payload_size: usize) -> Self {
+        self.max_payload_size = max_payload_size;
+        self
+    }
+
+    /// Validate the fields and build the event
+    pub fn build(self) -> Result<Event, EventBuildError> {
+        if self.tenant_id.is_empty() {
+            return Err(EventBuildError::EmptyTenantId);
+        }
+        if self.project_id.is_empty() {
+            return Err(EventBuildError::EmptyProjectId);
+        }
+        if self.topic.len() > MAX_TOPIC_LENGTH {
+            return Err(EventBuildError::InvalidTopic(format!(
+                "Topic must be at most {} characters",
+                MAX_TOPIC_LENGTH
+            )));
+        }
+        crate::schema_validator::validate_topic_format(&self.topic)
+            .map_err(EventBuildError::InvalidTopic)?;
+
+        let size = self.payload.to_string().len();
+        if size > self.max_payload_size {
+            return Err(EventBuildError::PayloadTooLarge {
+                size,
+                limit: self.max_payload_size,
+            });
+        }
+
+        if let Some(partition_key) = &self.partition_key {
+            if partition_key.is_empty() || partition_key.len() > MAX_PARTITION_KEY_LENGTH {
+                return Err(EventBuildError::InvalidPartitionKey(format!(
+                    "Partition key must be 1 to {} characters",
+                    MAX_PARTITION_KEY_LENGTH
+                )));
+            }
+        }
+
+        if self.headers.len() > MAX_EVENT_HEADERS {
+            return Err(EventBuildError::InvalidHeader(format!(
+                "{} headers exceed the limit of {}",
+                self.headers.len(),
+                MAX_EVENT_HEADERS
+            )));
+        }
+        for (name, value) in &self.headers {
+            if is_reserved_header(name) {
+                return Err(EventBuildError::ReservedHeader(name.clone()));
+            }
+            if !valid_header_name(name) {
+                return Err(EventBuildError::InvalidHeader(format!(
+                    "Header name '{}' must be 1 to {} token characters",
+                    name, MAX_HEADER_NAME_LENGTH
+                )));
+            }
+            if value.len() > MAX_HEADER_VALUE_LENGTH || value.contains(['\r', '\n']) {
+                return Err(EventBuildError::InvalidHeader(format!(
+                    "Value of '{}' must be a single line of at most {} bytes",
+                    name, MAX_HEADER_VALUE_LENGTH
+                )));
+            }
+        }
+
+        let mut event = Event::new(self.tenant_id, self.project_id, self.topic, self.payload);
+        event.partition_key = self.partition_key;
+        event.headers = self.headers;
+        event.metadata = self.metadata;
+        event.attributes = self.attributes;
+        Ok(event)
+    }
+}
+
+/// Caps on per-event metadata and attribute cardinality
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetadataLimits {
+    pub max_metadata_keys: usize,
+    pub max_attributes: usize,
+    pub max_value_length: usize,
+}
+
+impl Default for MetadataLimits {
+    fn default() -> Self {
+        Self {
+            max_metadata_keys: 32,
+            max_attributes: 16,
+            max_value_length: 256,
+        }
+    }
+}
+
+/// Errors raised when event metadata exceeds the configured limits
+#[derive(Debug, Error, PartialEq)]
+pub enum MetadataLimitError {
+    #[error("{count} metadata keys exceed the limit of {limit}")]
+    TooManyMetadataKeys { count: usize, limit: usize },
+    #[error("{count} attributes exceed the limit of {limit}")]
+    TooManyAttributes { count: usize, limit: usize },
+    #[error("Value for '{key}' is {length} characters, exceeding the limit of {limit}")]
+    ValueTooLong {
+        key: String,
+        length: usize,
+        limit: usize,
+    },
+}
+
+impl MetadataLimits {
+    /// Check metadata and attributes against the key count and value length caps
+    pub fn check(
+        &self,
+        metadata: &HashMap<String, String>,
+        attributes: &HashMap<String, String>,
+    ) -> Result<(), MetadataLimitError> {
+        if metadata.len() > self.max_metadata_keys {
+            return Err(MetadataLimitError::TooManyMetadataKeys {
+                count: metadata.len(),
+                limit: self.max_metadata_keys,
+            });
+        }
+        if attributes.len() > self.max_attributes {
+            return Err(MetadataLimitError::TooManyAttributes {
+                count: attributes.len(),
+                limit: self.max_attributes,
+            });
+        }
+
+        for (key, value) in metadata.iter().chain(attributes.iter()) {
+            let length = value.chars().count();
+            if length > self.max_value_length {
+                return Err(MetadataLimitError::ValueTooLong {
+                    key: key.clone(),
+                    length,
+                    limit: self.max_value_length,
+                });
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Event {
+    /// Start building a validated event
+    pub fn builder() -> EventBuilder {
+        EventBuilder::default()
+    }
+
     /// Create a new event
     pub fn new(
         tenant_id: String,
@@ -261,8 +1024,170 @@ impl Event {
             topic,
             payload,
             published_at: Utc::now(),
+            partition_key: None,
+            headers: HashMap::new(),
+            metadata: HashMap::new(),
+            attributes: HashMap::new(),
+            schema_valid: None,
+            schema_version: None,
         }
     }
+
+    /// Partition the event's key hashes into, so consumers can process
+    /// partitions in parallel while keeping each key in order
+    pub fn partition(&self) -> Option<u32> {
+        self.partition_key.as_deref().map(partition_for_key)
+    }
+}
+
+/// Partition a key hashes into; stable across restarts and instances
+pub fn partition_for_key(partition_key: &str) -> u32 {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(partition_key.as_bytes());
+    u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) % EVENT_PARTITIONS
+}
+
+/// Keyset position of an event in `(published_at, id)` order, handed to
+/// clients as an opaque cursor token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventPageCursor {
+    pub published_at: DateTime<Utc>,
+    pub id: String,
+}
+
+impl EventPageCursor {
+    /// Cursor pointing at an event
+    pub fn for_event(event: &Event) -> Self {
+        Self {
+            published_at: event.published_at,
+            id: event.id.clone(),
+        }
+    }
+
+    /// Encode as an opaque URL-safe token
+    pub fn encode(&self) -> String {
+        use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+        let raw = format!(
+            "{}|{}",
+            self.published_at
+                .to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+            self.id
+        );
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    /// Decode a token produced by [`EventPageCursor::encode`]
+    pub fn decode(token: &str) -> Option<Self> {
+        use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+        let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(token).ok()?).ok()?;
+        let (published_at, id) = raw.split_once('|')?;
+        if id.is_empty() {
+            return None;
+        }
+        Some(Self {
+            published_at: DateTime::parse_from_rfc3339(published_at)
+                .ok()?
+                .with_timezone(&Utc),
+            id: id.to_string(),
+        })
+    }
+
+    /// The `(published_at, id)` pair used for keyset queries
+    pub fn into_key(self) -> (DateTime<Utc>, String) {
+        (self.published_at, self.id)
+    }
+}
+
+/// A versioned JSON Schema registered for a project topic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicSchemaRecord {
+    pub tenant_id: String,
+    pub project_id: String,
+    pub topic: String,
+    pub version: i32,
+    pub json_schema: serde_json::Value,
+    /// `enforce` or `warn`
+    pub mode: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An HTTP endpoint that receives a project's published events
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Webhook {
+    pub id: String,
+    pub tenant_id: String,
+    pub project_id: String,
+    pub url: String,
+    /// Key for the `X-Signature` HMAC; only returned when the webhook is created
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// NATS-style pattern selecting the topics delivered; `>` matches all
+    pub topic_filter: String,
+    /// Encoding of delivered events
+    #[serde(default)]
+    pub format: DeliveryFormat,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Webhook {
+    /// Whether an event on `topic` should be delivered to this webhook
+    pub fn matches(&self, topic: &str) -> bool {
+        self.active && topic_pattern_matches(&self.topic_filter, topic)
+    }
+}
+
+/// Changes to a webhook; `None` leaves a field unchanged
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct WebhookUpdate {
+    pub url: Option<String>,
+    pub topic_filter: Option<String>,
+    pub active: Option<bool>,
+    pub format: Option<DeliveryFormat>,
+}
+
+/// Event recorded for a producer-supplied idempotency key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    pub event_id: String,
+    /// JetStream sequence, or `None` while the first publish is in flight
+    pub sequence: Option<u64>,
+}
+
+/// Optional filters applied when listing a tenant's events
+#[derive(Debug, Clone, Default)]
+pub struct EventQuery {
+    /// Exact topic to match
+    pub topic: Option<String>,
+    /// Earliest `published_at`, inclusive
+    pub from: Option<DateTime<Utc>>,
+    /// Latest `published_at`, inclusive
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// One page of events, newest first
+#[derive(Debug, Clone)]
+pub struct EventPage {
+    pub events: Vec<Event>,
+    /// Whether more events exist beyond this page in the direction it was read
+    pub has_more: bool,
+}
+
+impl EventPage {
+    /// Cursor of the first (newest) event on the page
+    pub fn start_cursor(&self) -> Option<String> {
+        self.events
+            .first()
+            .map(|event| EventPageCursor::for_event(event).encode())
+    }
+
+    /// Cursor of the last (oldest) event on the page
+    pub fn end_cursor(&self) -> Option<String> {
+        self.events
+            .last()
+            .map(|event| EventPageCursor::for_event(event).encode())
+    }
 }
 
 impl UsageRecord {
@@ -282,8 +1207,15 @@ impl UsageRecord {
             quantity,
             window_start,
             created_at: Utc::now(),
+            key_id: None,
         }
     }
+
+    /// Attribute the usage to an API key
+    pub fn with_key_id(mut self, key_id: Option<String>) -> Self {
+        self.key_id = key_id;
+        self
+    }
 }
 
 impl User {
@@ -348,32 +1280,39 @@ impl RolePermission {
         }
     }
 }
-/// Audit log for tracking administrative operations
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+/// Audit log entry recording an administrative action against a tenant
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct AuditLog {
     pub id: String,
     pub tenant_id: String,
-    pub operation: String,
-    pub details: String,
-    pub performed_by: String,
+    /// API key that performed the action; `None` for system actions such as
+    /// billing-driven suspensions
+    pub actor_api_key_id: Option<String>,
+    pub action: String,
+    /// Id of the resource acted on, e.g. the revoked API key
+    pub target: Option<String>,
+    pub details: serde_json::Value,
     pub created_at: DateTime<Utc>,
 }
 
-impl AuditLog {
-    /// Create a new audit log entry
-    pub fn new(
-        tenant_id: String,
-        operation: String,
-        details: String,
-        performed_by: String,
-    ) -> Self {
-        Self {
-            id: Uuid::new_v4().to_string(),
-            tenant_id,
-            operation,
-            details,
-            performed_by,
-            created_at: Utc::now(),
-        }
-    }
-}
\ No newline at end of file
+/// Optional filters applied when listing a tenant's audit log
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    /// Exact action to match, e.g. `api_key_revoked`
+    pub action: Option<String>,
+    /// Earliest `created_at`, inclusive
+    pub from: Option<DateTime<Utc>>,
+    /// Latest `created_at`, inclusive
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// A tenant's billing cycle and suspension state, as read by the usage rollover job
+#[derive(Debug, Clone)]
+pub struct UsageCycleState {
+    pub tenant_id: String,
+    pub created_at: DateTime<Utc>,
+    /// Start of the current cycle; `None` before the first rollover run
+    pub usage_cycle_start: Option<DateTime<Utc>>,
+    /// Why the tenant is suspended; `None` when it isn't
+    pub suspension_reason: Option<String>,
+}
@@ -0,0 +1,254 @@
+use anyhow::{anyhow, Context, Result};
+use axum::extract::ConnectInfo;
+use axum::Router;
+use hyper::body::Incoming;
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto::Builder;
+use hyper_util::service::TowerToHyperService;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::future::Future;
+use std::io::BufReader;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tokio_rustls::rustls::server::AllowAnyAnonymousOrAuthenticatedClient;
+use tokio_rustls::rustls::{Certificate, PrivateKey, RootCertStore, ServerConfig};
+use tokio_rustls::TlsAcceptor;
+use tower::ServiceExt;
+use tracing::{debug, warn};
+
+use crate::config::TlsConfig;
+
+/// How long a client gets to complete the TLS handshake before the
+/// connection is dropped, so idle sockets can't hold connection tasks open
+pub const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Identity of the verified certificate a client presented in the TLS
+/// handshake, stored as a request extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientCertificate {
+    /// Lowercase hex SHA-256 of the certificate's SubjectPublicKeyInfo
+    pub spki_sha256: String,
+    /// Distinguished name, e.g. `O=Acme, CN=billing-service`
+    pub subject: String,
+    /// Distinguished name of the issuing CA
+    pub issuer: String,
+}
+
+impl ClientCertificate {
+    /// Read the identity out of a DER-encoded X.509 certificate
+    pub fn from_der(der: &[u8]) -> Result<Self> {
+        let (_, certificate) = x509_parser::parse_x509_certificate(der)
+            .map_err(|e| anyhow!("Invalid client certificate: {}", e))?;
+        Ok(Self {
+            spki_sha256: hex::encode(Sha256::digest(certificate.public_key().raw)),
+            subject: certificate.subject().to_string(),
+            issuer: certificate.issuer().to_string(),
+        })
+    }
+}
+
+fn open(path: &str) -> Result<BufReader<File>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path))?;
+    Ok(BufReader::new(file))
+}
+
+/// Certificates in a PEM file
+pub fn load_certificates(path: &str) -> Result<Vec<Certificate>> {
+    let certificates = rustls_pemfile::certs(&mut open(path)?)
+        .with_context(|| format!("Invalid PEM in {}", path))?;
+    if certificates.is_empty() {
+        anyhow::bail!("No certificates found in {}", path);
+    }
+    Ok(certificates.into_iter().map(Certificate).collect())
+}
+
+/// The first private key in a PEM file
+fn load_private_key(path: &str) -> Result<PrivateKey> {
+    let items = rustls_pemfile::read_all(&mut open(path)?)
+        .with_context(|| format!("Invalid PEM in {}", path))?;
+    items
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| anyhow!("No private key found in {}", path))
+}
+
+/// TLS acceptor for the configured certificate. With `client_ca_path` set,
+/// clients are asked for a certificate issued by one of those CAs; clients
+/// that send none can still connect and authenticate with an API key.
+pub fn tls_acceptor(config: &TlsConfig) -> Result<TlsAcceptor> {
+    let (Some(cert_path), Some(key_path)) = (&config.cert_path, &config.key_path) else {
+        anyhow::bail!("TLS requires tls.cert_path and tls.key_path");
+    };
+    let certificates = load_certificates(cert_path)?;
+    let key = load_private_key(key_path)?;
+
+    let builder = ServerConfig::builder().with_safe_defaults();
+    let builder = match &config.client_ca_path {
+        Some(ca_path) => {
+            let mut roots = RootCertStore::empty();
+            for ca in load_certificates(ca_path)? {
+                roots
+                    .add(&ca)
+                    .with_context(|| format!("Invalid CA certificate in {}", ca_path))?;
+            }
+            builder.with_client_cert_verifier(
+                AllowAnyAnonymousOrAuthenticatedClient::new(roots).boxed(),
+            )
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let mut server_config = builder
+        .with_single_cert(certificates, key)
+        .context("Invalid TLS certificate or key")?;
+    // WebSocket upgrades need HTTP/1.1
+    server_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+/// Serve `app` over TLS until `signal` resolves, then stop accepting and let
+/// open connections finish their in-flight requests. Requests carry the
+/// peer's `ConnectInfo` and, when one was verified, its `ClientCertificate`.
+pub async fn serve_tls(
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    app: Router,
+    signal: impl Future<Output = ()> + Send + 'static,
+) -> std::io::Result<()> {
+    let (shutdown, shutdown_rx) = watch::channel(());
+    let mut connections = JoinSet::new();
+    tokio::pin!(signal);
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, peer) = match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        warn!("Failed to accept connection: {}", e);
+                        continue;
+                    }
+                };
+                connections.spawn(serve_connection(
+                    stream,
+                    peer,
+                    acceptor.clone(),
+                    app.clone(),
+                    shutdown_rx.clone(),
+                ));
+            }
+            Some(_) = connections.join_next() => {}
+            _ = &mut signal => break,
+        }
+    }
+
+    drop(listener);
+    let _ = shutdown.send(());
+    while connections.join_next().await.is_some() {}
+    Ok(())
+}
+
+async fn serve_connection(
+    stream: tokio::net::TcpStream,
+    peer: SocketAddr,
+    acceptor: TlsAcceptor,
+    app: Router,
+    mut shutdown: watch::Receiver<()>,
+) {
+    let stream = match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+        Ok(Ok(stream)) => stream,
+        Ok(Err(e)) => {
+            debug!("TLS handshake with {} failed: {}", peer, e);
+            return;
+        }
+        Err(_) => {
+            debug!("TLS handshake with {} timed out", peer);
+            return;
+        }
+    };
+
+    // rustls has already checked the chain against the trusted CAs
+    let client_certificate = stream
+        .get_ref()
+        .1
+        .peer_certificates()
+        .and_then(|chain| chain.first())
+        .and_then(|leaf| match ClientCertificate::from_der(&leaf.0) {
+            Ok(certificate) => Some(certificate),
+            Err(e) => {
+                warn!("Ignoring client certificate from {}: {}", peer, e);
+                None
+            }
+        });
+
+    let service = app.map_request(move |mut request: hyper::Request<Incoming>| {
+        request.extensions_mut().insert(ConnectInfo(peer));
+        if let Some(certificate) = &client_certificate {
+            request.extensions_mut().insert(certificate.clone());
+        }
+        request
+    });
+
+    let builder = Builder::new(TokioExecutor::new());
+    let connection = builder
+        .serve_connection_with_upgrades(TokioIo::new(stream), TowerToHyperService::new(service));
+    tokio::pin!(connection);
+
+    let result = tokio::select! {
+        result = connection.as_mut() => result,
+        _ = shutdown.changed() => {
+            connection.as_mut().graceful_shutdown();
+            connection.await
+        }
+    };
+    if let Err(e) = result {
+        debug!("Connection from {} ended with an error: {}", peer, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/mtls");
+
+    #[test]
+    fn test_client_certificate_identity() {
+        let chain = load_certificates(&format!("{}/client.pem", FIXTURES)).unwrap();
+        let certificate = ClientCertificate::from_der(&chain[0].0).unwrap();
+
+        // openssl x509 -pubkey -noout | openssl pkey -pubin -outform DER | sha256sum
+        assert_eq!(
+            certificate.spki_sha256,
+            "8512d7baca865999497e29b9b89705aae7180a7ad7ce335c8d4234f827adfccf"
+        );
+        assert_eq!(certificate.subject, "O=Acme, CN=billing-service");
+        assert_eq!(certificate.issuer, "CN=Realtime Test CA");
+    }
+
+    #[test]
+    fn test_invalid_certificate_is_rejected() {
+        assert!(ClientCertificate::from_der(b"not a certificate").is_err());
+        assert!(load_certificates(&format!("{}/missing.pem", FIXTURES)).is_err());
+    }
+
+    #[test]
+    fn test_acceptor_requires_cert_and_key() {
+        let config = TlsConfig {
+            cert_path: None,
+            key_path: None,
+            client_ca_path: Some(format!("{}/ca.pem", FIXTURES)),
+        };
+        assert!(tls_acceptor(&config).is_err());
+    }
+}
@@ -1,17 +1,156 @@
 use anyhow::{anyhow, Result};
 use async_nats::jetstream::{
-    consumer::{pull::Config as ConsumerConfig, DeliverPolicy},
+    consumer::{pull::Config as ConsumerConfig, AckPolicy, DeliverPolicy, PullConsumer},
+    context::{PublishError, PublishErrorKind},
     stream::{Config as StreamConfig, RetentionPolicy, StorageType},
-    Context as JetStreamContext,
+    AckKind, Context as JetStreamContext,
 };
 use chrono::{DateTime, Utc};
-use futures_util::StreamExt;
+use futures_util::{Future, Stream, StreamExt};
+use prometheus::Counter;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use tokio::sync::{oneshot, Notify};
 use tracing::{error, info, warn};
 
-use crate::models::Event;
+use crate::circuit_breaker::{CircuitBreaker, NATS_DEPENDENCY};
+use crate::cloudevents::{event_source, CLOUDEVENTS_SPEC_VERSION};
+use crate::config::{CircuitBreakerConfig, StreamRetention, TenantStreamConfig};
+use crate::models::{topic_allowed, topic_pattern_covers, topic_pattern_matches, Event};
+use crate::transform::TransformPipeline;
+
+/// Default cap on un-acked deliveries for a durable consumer
+pub const DEFAULT_MAX_ACK_PENDING: i64 = 1000;
+
+/// Default time an acking consumer waits for an ack before redelivering
+pub const DEFAULT_ACK_WAIT: Duration = Duration::from_secs(30);
+
+/// Default deliveries of one event to an acking subscription before it is
+/// dead-lettered
+pub const DEFAULT_MAX_DELIVER: i64 = 5;
+
+/// Subject root of events in the shared stream:
+/// `events.{tenant_id}.{project_id}.{topic}`
+pub const EVENT_SUBJECT_ROOT: &str = "events";
+
+/// Subject root of events in tenants' dedicated streams, kept apart from the
+/// shared stream's because JetStream rejects streams with overlapping subjects
+pub const TENANT_EVENT_SUBJECT_ROOT: &str = "tenant";
+
+/// Window in which JetStream drops messages repeating a `Nats-Msg-Id`
+pub const DUPLICATE_WINDOW: Duration = Duration::from_secs(2 * 60);
+
+/// How long dead-lettered events are kept for inspection and replay
+pub const DEAD_LETTER_MAX_AGE: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+/// Header carrying why an event was dead-lettered
+pub const DEAD_LETTER_REASON_HEADER: &str = "dlq-reason";
+
+/// Header carrying which stage an event failed at (`validation` or `delivery`)
+pub const DEAD_LETTER_KIND_HEADER: &str = "dlq-kind";
+
+/// Header carrying when an event was dead-lettered (RFC 3339)
+pub const DEAD_LETTER_AT_HEADER: &str = "dlq-at";
+
+/// Header naming what the event failed to reach, e.g. the webhook id
+pub const DEAD_LETTER_TARGET_HEADER: &str = "dlq-target";
+
+/// Header naming the only subscription a replayed dead letter is redelivered
+/// to; other consumers and replays skip the message
+pub const REDELIVER_TO_HEADER: &str = "redeliver_to";
+
+/// Events fetched at a time from a durable consumer left on the shared stream
+const ORPHANED_CONSUMER_BATCH: usize = 100;
+
+/// How long a fetch from an orphaned consumer waits for events
+const ORPHANED_CONSUMER_WAIT: Duration = Duration::from_secs(1);
+
+/// Default cap on events held in memory while NATS is disconnected
+pub const DEFAULT_PUBLISH_BUFFER_SIZE: usize = 10_000;
+
+/// Default time a publish waits in the buffer for NATS to come back
+pub const DEFAULT_PUBLISH_BUFFER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// First reconnect delay; each further attempt doubles it
+const RECONNECT_BASE_DELAY: Duration = Duration::from_millis(100);
+
+/// Upper bound on the delay between reconnect attempts
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Wait before redelivering an event a subscriber failed to receive the
+/// first time; each further failure doubles it
+const REDELIVERY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Upper bound on the wait before a redelivery
+const REDELIVERY_MAX_DELAY: Duration = Duration::from_secs(30);
+
+lazy_static::lazy_static! {
+    static ref NATS_RECONNECT_ATTEMPTS: Counter = Counter::new(
+        "realtime_nats_reconnect_attempts_total",
+        "Attempts to re-establish a dropped NATS connection"
+    )
+    .expect("valid counter definition");
+}
+
+/// Counter of NATS reconnect attempts
+pub fn nats_reconnect_attempts_counter() -> &'static Counter {
+    &NATS_RECONNECT_ATTEMPTS
+}
+
+/// Delay before reconnect attempt `attempts`: exponential backoff from 100ms,
+/// capped at 30s
+pub fn reconnect_delay(attempts: usize) -> Duration {
+    let factor = 1u32 << attempts.min(16);
+    RECONNECT_BASE_DELAY
+        .saturating_mul(factor)
+        .min(RECONNECT_MAX_DELAY)
+}
+
+/// Delay before redelivering an event that failed on delivery `delivered`
+/// (1-based): exponential backoff from 500ms, capped at 30s
+pub fn redelivery_delay(delivered: i64) -> Duration {
+    let factor = 1u32 << (delivered.clamp(1, 17) - 1);
+    REDELIVERY_BASE_DELAY
+        .saturating_mul(factor)
+        .min(REDELIVERY_MAX_DELAY)
+}
+
+/// A publish refused because NATS is disconnected and the buffer holding
+/// publishes until it returns is full
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("NATS is disconnected and the publish buffer is full ({max_size} events)")]
+pub struct PublishBufferFull {
+    pub max_size: usize,
+}
+
+/// A buffered publish that NATS didn't come back in time to deliver; the
+/// event is dropped from the buffer
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("NATS is disconnected and did not reconnect within {timeout:?}")]
+pub struct PublishBufferTimeout {
+    pub timeout: Duration,
+}
+
+/// Whether a publish error, or any error it wraps, means NATS couldn't be
+/// reached or didn't answer in time, rather than that JetStream rejected the
+/// message. A request nobody answered is reported as `StreamNotFound`.
+pub fn is_nats_unavailable(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause.is::<PublishBufferFull>()
+            || cause.is::<PublishBufferTimeout>()
+            || cause.downcast_ref::<PublishError>().is_some_and(|e| {
+                matches!(
+                    e.kind(),
+                    PublishErrorKind::TimedOut
+                        | PublishErrorKind::BrokenPipe
+                        | PublishErrorKind::StreamNotFound
+                )
+            })
+    })
+}
 
 /// NATS JetStream client for event streaming and persistence
 #[derive(Debug, Clone)]
@@ -19,6 +158,203 @@ pub struct NatsClient {
     client: async_nats::Client,
     jetstream: JetStreamContext,
     stream_name: String,
+    max_ack_pending: i64,
+    publish_buffer: PublishBuffer,
+    /// How long a buffered publish waits for the connection to return
+    publish_buffer_timeout: Duration,
+    reconnected: Arc<Notify>,
+    /// Fails publishes fast while JetStream keeps failing to acknowledge them
+    breaker: Arc<CircuitBreaker>,
+    /// Settings for tenants' dedicated streams; `None` keeps every tenant in
+    /// the shared stream
+    tenant_streams: Option<TenantStreamConfig>,
+    /// Tenants whose dedicated stream is known to exist
+    ensured_tenant_streams: Arc<Mutex<HashSet<String>>>,
+}
+
+#[derive(Debug)]
+struct BufferedPublish {
+    event: Event,
+    /// The event serialized once, so retried flushes don't redo it
+    payload: Vec<u8>,
+    msg_id: Option<String>,
+    ack: oneshot::Sender<Result<PublishAck>>,
+}
+
+#[derive(Debug)]
+struct PublishBufferInner {
+    queue: Mutex<VecDeque<BufferedPublish>>,
+    max_size: AtomicUsize,
+}
+
+/// Bounded in-memory queue of events published while NATS is disconnected.
+///
+/// Each buffered publish resolves with its JetStream ack once the buffer is
+/// flushed after reconnecting.
+#[derive(Debug, Clone)]
+pub struct PublishBuffer {
+    inner: Arc<PublishBufferInner>,
+}
+
+impl PublishBuffer {
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            inner: Arc::new(PublishBufferInner {
+                queue: Mutex::new(VecDeque::new()),
+                max_size: AtomicUsize::new(max_size),
+            }),
+        }
+    }
+
+    pub fn set_max_size(&self, max_size: usize) {
+        self.inner.max_size.store(max_size, Ordering::Relaxed);
+    }
+
+    /// Number of events waiting for the connection to return
+    pub fn len(&self) -> usize {
+        self.inner.queue.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Queue an event and its serialized form until the next flush, failing
+    /// when the buffer is full
+    pub fn push(
+        &self,
+        event: Event,
+        payload: Vec<u8>,
+        msg_id: Option<String>,
+    ) -> Result<oneshot::Receiver<Result<PublishAck>>> {
+        let mut queue = self.inner.queue.lock().unwrap();
+        let max_size = self.inner.max_size.load(Ordering::Relaxed);
+        if queue.len() >= max_size {
+            return Err(PublishBufferFull { max_size }.into());
+        }
+
+        let (ack, receiver) = oneshot::channel();
+        queue.push_back(BufferedPublish {
+            event,
+            payload,
+            msg_id,
+            ack,
+        });
+        Ok(receiver)
+    }
+
+    /// Drop buffered events whose publisher stopped waiting for them
+    pub fn discard_abandoned(&self) {
+        self.inner
+            .queue
+            .lock()
+            .unwrap()
+            .retain(|buffered| !buffered.ack.is_closed());
+    }
+
+    /// Publish buffered events in order, returning how many were delivered.
+    /// Events whose publisher stopped waiting are dropped unpublished.
+    ///
+    /// A failed publish while `is_connected` reports the connection down again
+    /// is put back and ends the flush; any other failure is returned to that
+    /// event's publisher.
+    pub async fn flush<F, Fut>(&self, mut publish: F, is_connected: impl Fn() -> bool) -> usize
+    where
+        F: FnMut(Event, Vec<u8>, Option<String>) -> Fut,
+        Fut: Future<Output = Result<PublishAck>>,
+    {
+        let mut delivered = 0;
+        loop {
+            let Some(buffered) = self.inner.queue.lock().unwrap().pop_front() else {
+                break;
+            };
+            if buffered.ack.is_closed() {
+                continue;
+            }
+
+            match publish(
+                buffered.event.clone(),
+                buffered.payload.clone(),
+                buffered.msg_id.clone(),
+            )
+            .await
+            {
+                Ok(ack) => {
+                    delivered += 1;
+                    let _ = buffered.ack.send(Ok(ack));
+                }
+                Err(e) if !is_connected() => {
+                    warn!("NATS dropped again while flushing publish buffer: {}", e);
+                    self.inner.queue.lock().unwrap().push_front(buffered);
+                    break;
+                }
+                Err(e) => {
+                    let _ = buffered.ack.send(Err(e));
+                }
+            }
+        }
+        delivered
+    }
+}
+
+/// JetStream acknowledgement of a published event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublishAck {
+    pub sequence: u64,
+    /// The message repeated a `Nats-Msg-Id`; `sequence` is the original's
+    pub duplicate: bool,
+}
+
+/// Stage at which a dead-lettered event failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadLetterKind {
+    /// The payload did not match the topic schema
+    Validation,
+    /// JetStream did not acknowledge the publish
+    Delivery,
+    /// A webhook kept failing until its delivery attempts ran out
+    Webhook,
+    /// Writing to the Kafka sink kept failing until its attempts ran out
+    Kafka,
+    /// Sending to a subscriber kept failing until its redeliveries ran out
+    Subscription,
+}
+
+impl DeadLetterKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeadLetterKind::Validation => "validation",
+            DeadLetterKind::Delivery => "delivery",
+            DeadLetterKind::Webhook => "webhook",
+            DeadLetterKind::Kafka => "kafka",
+            DeadLetterKind::Subscription => "subscription",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "validation" => Some(DeadLetterKind::Validation),
+            "delivery" => Some(DeadLetterKind::Delivery),
+            "webhook" => Some(DeadLetterKind::Webhook),
+            "kafka" => Some(DeadLetterKind::Kafka),
+            "subscription" => Some(DeadLetterKind::Subscription),
+            _ => None,
+        }
+    }
+}
+
+/// An event held in the dead-letter stream
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct DeadLetter {
+    /// Sequence in the dead-letter stream, used to replay the event
+    pub id: u64,
+    pub kind: DeadLetterKind,
+    pub reason: String,
+    /// What the event failed to reach, e.g. the webhook id for `webhook`
+    pub target: Option<String>,
+    pub dead_lettered_at: DateTime<Utc>,
+    pub event: Event,
 }
 
 /// Event cursor for replay functionality
@@ -28,6 +364,16 @@ pub struct EventCursor {
     pub timestamp: DateTime<Utc>,
 }
 
+impl EventCursor {
+    /// Cursor at a stream sequence whose publish time isn't known to the caller
+    pub fn at_sequence(sequence: u64) -> Self {
+        Self {
+            sequence,
+            timestamp: DateTime::<Utc>::MIN_UTC,
+        }
+    }
+}
+
 /// Event replay request
 #[derive(Debug, Clone)]
 pub struct ReplayRequest {
@@ -35,7 +381,11 @@ pub struct ReplayRequest {
     pub project_id: String,
     pub topic: Option<String>,
     pub cursor: Option<EventCursor>,
+    /// Start from the first event at or after this time; ignored when a cursor is set
+    pub from_timestamp: Option<DateTime<Utc>>,
     pub limit: Option<usize>,
+    /// Optional transformation applied to each replayed event; stored events are untouched
+    pub transform: Option<TransformPipeline>,
 }
 
 /// Event subscription configuration
@@ -46,22 +396,149 @@ pub struct SubscriptionConfig {
     pub topics: Vec<String>,
     pub consumer_name: String,
     pub durable: bool,
+    /// Per-subscription override of the un-acked delivery cap (acking consumers only)
+    pub max_ack_pending: Option<i64>,
+    /// Require each delivery to be acked, redelivering it otherwise. Always
+    /// on for durable consumers.
+    pub explicit_ack: bool,
+    /// How long a delivery may go un-acked before it is redelivered;
+    /// `DEFAULT_ACK_WAIT` when unset
+    pub ack_wait: Option<Duration>,
+    /// Deliveries of one event before it is dead-lettered; `DEFAULT_MAX_DELIVER`
+    /// when unset
+    pub max_deliver: Option<i64>,
+}
+
+impl SubscriptionConfig {
+    /// An ephemeral consumer that acks each delivery, with the default ack
+    /// wait and delivery cap
+    pub fn acked(tenant_id: &str, project_id: &str, topics: Vec<String>, consumer_name: String) -> Self {
+        Self {
+            tenant_id: tenant_id.to_string(),
+            project_id: project_id.to_string(),
+            topics,
+            consumer_name,
+            durable: false,
+            max_ack_pending: None,
+            explicit_ack: true,
+            ack_wait: None,
+            max_deliver: None,
+        }
+    }
+
+    /// Whether deliveries must be acked
+    pub fn acks(&self) -> bool {
+        self.durable || self.explicit_ack
+    }
+
+    /// Deliveries of one event before it is dead-lettered
+    pub fn max_deliver(&self) -> i64 {
+        self.max_deliver.unwrap_or(DEFAULT_MAX_DELIVER)
+    }
+}
+
+/// An event delivered by an acking subscription's consumer. It is redelivered
+/// unless acked before the subscription's `ack_wait` runs out.
+pub struct SubscriptionDelivery {
+    pub event: Event,
+    /// Position of the event in the stream
+    pub sequence: u64,
+    /// How many times this event has been delivered, counting this time
+    pub delivered: i64,
+    message: async_nats::jetstream::Message,
+}
+
+impl std::fmt::Debug for SubscriptionDelivery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SubscriptionDelivery")
+            .field("event", &self.event.id)
+            .field("sequence", &self.sequence)
+            .field("delivered", &self.delivered)
+            .finish()
+    }
+}
+
+impl SubscriptionDelivery {
+    /// The subscriber received the event; it won't be delivered again
+    pub async fn ack(&self) -> Result<()> {
+        self.message
+            .ack()
+            .await
+            .map_err(|e| anyhow!("Failed to ack event {}: {}", self.event.id, e))
+    }
+
+    /// The subscriber didn't receive the event; redeliver it after `delay`
+    pub async fn nack(&self, delay: Duration) -> Result<()> {
+        self.message
+            .ack_with(AckKind::Nak(Some(delay)))
+            .await
+            .map_err(|e| anyhow!("Failed to nack event {}: {}", self.event.id, e))
+    }
+
+    /// Stop redelivering the event without it having been received
+    pub async fn term(&self) -> Result<()> {
+        self.message
+            .ack_with(AckKind::Term)
+            .await
+            .map_err(|e| anyhow!("Failed to terminate event {}: {}", self.event.id, e))
+    }
 }
 
 impl NatsClient {
-    /// Create a new NATS client and initialize JetStream
+    /// Create a new NATS client and initialize JetStream.
+    ///
+    /// A dropped connection is re-established with exponential backoff; events
+    /// published in the meantime are buffered and flushed once it returns.
     pub async fn new(nats_url: &str, stream_name: String) -> Result<Self> {
-        let client = async_nats::connect(nats_url).await?;
+        let reconnected = Arc::new(Notify::new());
+        let client = async_nats::ConnectOptions::new()
+            .reconnect_delay_callback(|attempts| {
+                nats_reconnect_attempts_counter().inc();
+                let delay = reconnect_delay(attempts);
+                warn!(attempts, ?delay, "Reconnecting to NATS");
+                delay
+            })
+            .event_callback({
+                let reconnected = reconnected.clone();
+                move |event| {
+                    let reconnected = reconnected.clone();
+                    async move {
+                        match event {
+                            async_nats::Event::Connected => {
+                                info!("NATS connection established");
+                                reconnected.notify_one();
+                            }
+                            async_nats::Event::Disconnected => {
+                                warn!("NATS connection lost; buffering publishes");
+                            }
+                            other => info!("NATS connection event: {}", other),
+                        }
+                    }
+                }
+            })
+            .connect(nats_url)
+            .await?;
         let jetstream = async_nats::jetstream::new(client.clone());
 
         let nats_client = Self {
             client,
             jetstream,
             stream_name: stream_name.clone(),
+            max_ack_pending: DEFAULT_MAX_ACK_PENDING,
+            publish_buffer: PublishBuffer::new(DEFAULT_PUBLISH_BUFFER_SIZE),
+            publish_buffer_timeout: DEFAULT_PUBLISH_BUFFER_TIMEOUT,
+            reconnected,
+            breaker: Arc::new(CircuitBreaker::new(
+                NATS_DEPENDENCY,
+                &CircuitBreakerConfig::default(),
+            )),
+            tenant_streams: None,
+            ensured_tenant_streams: Arc::new(Mutex::new(HashSet::new())),
         };
 
         // Initialize the stream
         nats_client.initialize_stream().await?;
+        tokio::spawn(nats_client.clone().flush_on_reconnect());
 
         info!(
             "NATS JetStream client initialized with stream: {}",
@@ -70,6 +547,107 @@ impl NatsClient {
         Ok(nats_client)
     }
 
+    /// Set the default maximum of in-flight un-acked events for durable consumers
+    pub fn with_max_ack_pending(mut self, max_ack_pending: i64) -> Self {
+        self.max_ack_pending = max_ack_pending;
+        self
+    }
+
+    /// Set how many events may be buffered while disconnected before publishing fails
+    pub fn with_publish_buffer_size(self, max_size: usize) -> Self {
+        self.publish_buffer.set_max_size(max_size);
+        self
+    }
+
+    /// Set how long a publish buffered while disconnected waits for the
+    /// connection to return before failing
+    pub fn with_publish_buffer_timeout(mut self, timeout: Duration) -> Self {
+        self.publish_buffer_timeout = timeout;
+        self
+    }
+
+    /// Guard publishes with `breaker` instead of one with default settings
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.breaker = Arc::new(breaker);
+        self
+    }
+
+    /// The breaker guarding publishes
+    pub fn circuit_breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+
+    /// Store each tenant's events in its own stream, created with `config`'s
+    /// retention on the tenant's first publish, if `config` is enabled
+    pub fn with_tenant_streams(mut self, config: &TenantStreamConfig) -> Self {
+        self.tenant_streams = config.enabled.then(|| config.clone());
+        self
+    }
+
+    /// Number of events waiting to be flushed once NATS reconnects
+    pub fn buffered_publishes(&self) -> usize {
+        self.publish_buffer.len()
+    }
+
+    /// Wait up to `timeout` for buffered publishes to be delivered, then flush
+    /// the client's outgoing writes. Returns whether nothing was left behind.
+    pub async fn flush_pending(&self, timeout: Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        while !self.publish_buffer.is_empty() {
+            if tokio::time::Instant::now() >= deadline {
+                warn!(
+                    "Shutting down with {} buffered events unpublished",
+                    self.publish_buffer.len()
+                );
+                return false;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        match tokio::time::timeout_at(deadline, self.client.flush()).await {
+            Ok(Ok(())) => true,
+            Ok(Err(e)) => {
+                warn!("Failed to flush NATS client: {}", e);
+                false
+            }
+            Err(_) => {
+                warn!("Timed out flushing NATS client");
+                false
+            }
+        }
+    }
+
+    // Drain the publish buffer each time the connection is (re-)established
+    async fn flush_on_reconnect(self) {
+        loop {
+            self.reconnected.notified().await;
+            if self.publish_buffer.is_empty() {
+                continue;
+            }
+
+            let client = self.client.clone();
+            let delivered = self
+                .publish_buffer
+                .flush(
+                    |event, payload, msg_id| {
+                        let nats_client = self.clone();
+                        async move {
+                            nats_client
+                                .publish_routed(&event, payload, msg_id.as_deref())
+                                .await
+                        }
+                    },
+                    || client.connection_state() == async_nats::connection::State::Connected,
+                )
+                .await;
+            info!(
+                "Flushed {} buffered events after NATS reconnect ({} still pending)",
+                delivered,
+                self.publish_buffer.len()
+            );
+        }
+    }
+
     /// Initialize the JetStream stream for events
     async fn initialize_stream(&self) -> Result<()> {
         let stream_config = StreamConfig {
@@ -80,6 +658,7 @@ impl NatsClient {
             max_age: Duration::from_secs(30 * 24 * 60 * 60), // 30 days
             max_bytes: 1024 * 1024 * 1024 * 10,              // 10GB
             max_messages: 1_000_000,
+            duplicate_window: DUPLICATE_WINDOW,
             ..Default::default()
         };
 
@@ -89,115 +668,611 @@ impl NatsClient {
                     "JetStream stream '{}' initialized successfully",
                     self.stream_name
                 );
-                Ok(())
             }
             Err(e) => {
                 error!("Failed to initialize JetStream stream: {}", e);
-                Err(anyhow!("Failed to initialize JetStream stream: {}", e))
+                return Err(anyhow!("Failed to initialize JetStream stream: {}", e));
             }
         }
+
+        let dead_letter_config = StreamConfig {
+            name: self.dead_letter_stream_name(),
+            subjects: vec!["dlq.*.*".to_string()], // dlq.{tenant_id}.{project_id}
+            retention: RetentionPolicy::Limits,
+            storage: StorageType::File,
+            max_age: DEAD_LETTER_MAX_AGE,
+            ..Default::default()
+        };
+
+        self.jetstream
+            .get_or_create_stream(dead_letter_config)
+            .await
+            .map_err(|e| anyhow!("Failed to initialize dead-letter stream: {}", e))?;
+
+        Ok(())
     }
 
     /// Publish an event to JetStream with tenant/project scoping
     pub async fn publish_event(&self, event: &Event) -> Result<u64> {
-        let subject = format!(
-            "events.{}.{}.{}",
-            event.tenant_id, event.project_id, event.topic
-        );
+        Ok(self.publish_event_with_msg_id(event, None).await?.sequence)
+    }
 
-        // Serialize the event
-        let payload = serde_json::to_vec(event)?;
+    /// Publish an event, letting JetStream drop repeats of `msg_id` within the
+    /// stream's duplicate window.
+    ///
+    /// While disconnected the event is buffered and this resolves once it has
+    /// been flushed after reconnecting, or fails with `PublishBufferTimeout`
+    /// if that takes longer than the publish buffer timeout; it fails
+    /// immediately if the buffer is full, or with `CircuitOpen` while repeated connection failures or
+    /// timeouts have opened the publish breaker.
+    pub async fn publish_event_with_msg_id(
+        &self,
+        event: &Event,
+        msg_id: Option<&str>,
+    ) -> Result<PublishAck> {
+        self.breaker
+            .call(|| self.publish_or_buffer(event, msg_id), is_nats_unavailable)
+            .await
+    }
 
-        // Add metadata headers
-        let mut headers = async_nats::HeaderMap::new();
-        headers.insert("tenant_id", event.tenant_id.as_str());
-        headers.insert("project_id", event.project_id.as_str());
-        headers.insert("topic", event.topic.as_str());
-        headers.insert("event_id", event.id.as_str());
-        headers.insert("published_at", event.published_at.to_rfc3339().as_str());
+    async fn publish_or_buffer(&self, event: &Event, msg_id: Option<&str>) -> Result<PublishAck> {
+        let payload = serde_json::to_vec(event)?;
+        if self.is_connected() {
+            return self.publish_routed(event, payload, msg_id).await;
+        }
 
-        // Publish to JetStream
         let ack = self
+            .publish_buffer
+            .push(event.clone(), payload, msg_id.map(str::to_string))?;
+        warn!(
+            "NATS disconnected; buffered event {} ({} pending)",
+            event.id,
+            self.publish_buffer.len()
+        );
+        // The connection may have returned between the check and the push
+        if self.is_connected() {
+            self.reconnected.notify_one();
+        }
+
+        match tokio::time::timeout(self.publish_buffer_timeout, ack).await {
+            Ok(ack) => {
+                ack.map_err(|_| anyhow!("Buffered publish of event {} was dropped", event.id))?
+            }
+            Err(_) => {
+                // The receiver is gone, so the event is no longer wanted
+                self.publish_buffer.discard_abandoned();
+                Err(PublishBufferTimeout {
+                    timeout: self.publish_buffer_timeout,
+                }
+                .into())
+            }
+        }
+    }
+
+    // Publish to the stream holding the event's tenant
+    async fn publish_routed(
+        &self,
+        event: &Event,
+        payload: Vec<u8>,
+        msg_id: Option<&str>,
+    ) -> Result<PublishAck> {
+        let (_, subject_root) = self.event_stream(&event.tenant_id).await?;
+        publish_to_jetstream(&self.jetstream, subject_root, event, payload, msg_id, None).await
+    }
+
+    /// Publish a copy of an event that only the subscription `consumer_name`
+    /// delivers, to retry a delivery that subscription dead-lettered
+    pub async fn redeliver_to_subscription(
+        &self,
+        event: &Event,
+        consumer_name: &str,
+    ) -> Result<PublishAck> {
+        let (_, subject_root) = self.event_stream(&event.tenant_id).await?;
+        publish_to_jetstream(
+            &self.jetstream,
+            subject_root,
+            event,
+            serde_json::to_vec(event)?,
+            None,
+            Some(consumer_name),
+        )
+        .await
+    }
+
+    /// Name of the dedicated stream holding `tenant_id`'s events
+    pub fn tenant_stream_name(&self, tenant_id: &str) -> String {
+        // Stream names can't contain whitespace, `.`, `*`, `>` or path separators
+        let tenant: String = tenant_id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+            .collect();
+        format!("{}_TENANT_{}", self.stream_name, tenant)
+    }
+
+    /// Create `tenant_id`'s dedicated stream, or bring an existing one's age
+    /// and size limits in line with `config`, returning its name. Its events
+    /// are published under `tenant.{tenant_id}.{project_id}.{topic}`.
+    pub async fn ensure_tenant_stream(
+        &self,
+        tenant_id: &str,
+        config: &TenantStreamConfig,
+    ) -> Result<String> {
+        let name = self.tenant_stream_name(tenant_id);
+        let stream_config = StreamConfig {
+            name: name.clone(),
+            subjects: vec![format!("{}.{}.*.>", TENANT_EVENT_SUBJECT_ROOT, tenant_id)],
+            retention: retention_policy(config.retention),
+            storage: StorageType::File,
+            max_age: Duration::from_secs(config.max_age_secs),
+            max_bytes: config.max_bytes,
+            duplicate_window: DUPLICATE_WINDOW,
+            ..Default::default()
+        };
+
+        let mut stream = self
             .jetstream
-            .publish_with_headers(subject, headers, payload.into())
-            .await?;
+            .get_or_create_stream(stream_config.clone())
+            .await
+            .map_err(|e| anyhow!("Failed to create stream for tenant {}: {}", tenant_id, e))?;
+        let current = &stream.info().await?.config;
+        if current.max_age != stream_config.max_age || current.max_bytes != stream_config.max_bytes {
+            // The retention policy itself can't be changed once a stream exists
+            self.jetstream
+                .update_stream(StreamConfig {
+                    retention: current.retention,
+                    ..stream_config
+                })
+                .await
+                .map_err(|e| anyhow!("Failed to update stream for tenant {}: {}", tenant_id, e))?;
+            info!("Updated retention of JetStream stream '{}'", name);
+        }
 
-        let ack_result = ack.await?;
-        let sequence = ack_result.sequence;
+        self.ensured_tenant_streams
+            .lock()
+            .unwrap()
+            .insert(tenant_id.to_string());
+        Ok(name)
+    }
 
-        info!(
-            "Published event {} to JetStream with sequence: {}",
-            event.id, sequence
-        );
+    /// The stream holding `tenant_id`'s events and the subject root they're
+    /// published under, creating the tenant's dedicated stream on first use
+    async fn event_stream(&self, tenant_id: &str) -> Result<(String, &'static str)> {
+        let Some(config) = &self.tenant_streams else {
+            return Ok((self.stream_name.clone(), EVENT_SUBJECT_ROOT));
+        };
 
-        Ok(sequence)
+        let ensured = self
+            .ensured_tenant_streams
+            .lock()
+            .unwrap()
+            .contains(tenant_id);
+        let name = if ensured {
+            self.tenant_stream_name(tenant_id)
+        } else {
+            self.ensure_tenant_stream(tenant_id, config).await?
+        };
+        Ok((name, TENANT_EVENT_SUBJECT_ROOT))
     }
 
-    /// Create a durable consumer for WebSocket/SSE delivery
-    pub async fn create_consumer(&self, config: &SubscriptionConfig) -> Result<()> {
+    /// Build the JetStream consumer configuration for a subscription.
+    ///
+    /// Acking consumers, which include every durable one, use explicit acks and
+    /// a `max_ack_pending` cap so that a client which stops acking applies
+    /// backpressure instead of being flooded with redeliveries. Un-acked events
+    /// are redelivered after `ack_wait`, up to `max_deliver` times.
+    pub fn consumer_config(
+        config: &SubscriptionConfig,
+        default_max_ack_pending: i64,
+        subject_root: &str,
+    ) -> ConsumerConfig {
         let filter_subjects: Vec<String> = if config.topics.is_empty() {
             // Subscribe to all topics for this tenant/project
             vec![format!(
-                "events.{}.{}.>",
-                config.tenant_id, config.project_id
+                "{}.{}.{}.>",
+                subject_root, config.tenant_id, config.project_id
             )]
         } else {
-            // Subscribe to specific topics
-            config
-                .topics
-                .iter()
+            // Subscribe to specific topics, including their partition subjects
+            partitioned_topic_filters(&config.topics)
+                .into_iter()
                 .map(|topic| {
                     format!(
-                        "events.{}.{}.{}",
-                        config.tenant_id, config.project_id, topic
+                        "{}.{}.{}.{}",
+                        subject_root, config.tenant_id, config.project_id, topic
                     )
                 })
                 .collect()
         };
 
+        let mut consumer_config = ConsumerConfig {
+            name: Some(config.consumer_name.clone()),
+            durable_name: if config.durable {
+                Some(config.consumer_name.clone())
+            } else {
+                None
+            },
+            deliver_policy: DeliverPolicy::New,
+            filter_subjects,
+            ..Default::default()
+        };
+
+        if config.acks() {
+            consumer_config.ack_policy = AckPolicy::Explicit;
+            consumer_config.max_ack_pending = config
+                .max_ack_pending
+                .unwrap_or(default_max_ack_pending);
+            consumer_config.ack_wait = config.ack_wait.unwrap_or(DEFAULT_ACK_WAIT);
+            consumer_config.max_deliver = config.max_deliver();
+        }
+
+        consumer_config
+    }
+
+    /// Create a durable consumer for WebSocket/SSE delivery
+    pub async fn create_consumer(&self, config: &SubscriptionConfig) -> Result<()> {
+        let (stream_name, subject_root) = self.event_stream(&config.tenant_id).await?;
+        let mut consumer_config = Self::consumer_config(config, self.max_ack_pending, subject_root);
+        if self.orphaned_consumer(config, &stream_name).await.is_some() {
+            consumer_config.deliver_policy = DeliverPolicy::All;
+        }
+
+        // Get the stream first, then create consumer
+        let stream = self.jetstream.get_stream(&stream_name).await?;
+
+        match stream.create_consumer(consumer_config).await {
+            Ok(_) => {
+                info!(
+                    "Created consumer '{}' for tenant/project: {}/{}",
+                    config.consumer_name, config.tenant_id, config.project_id
+                );
+                Ok(())
+            }
+            Err(e) => {
+                error!("Failed to create consumer: {}", e);
+                Err(anyhow!("Failed to create consumer: {}", e))
+            }
+        }
+    }
+
+    /// Stream a subscription's events from its consumer, creating the consumer
+    /// if it doesn't exist yet. Each delivery must be settled with `ack`,
+    /// `nack` or `term`; payloads that aren't events are terminated and skipped.
+    pub async fn subscription_deliveries(
+        &self,
+        config: &SubscriptionConfig,
+    ) -> Result<impl Stream<Item = Result<SubscriptionDelivery>>> {
+        if !config.acks() {
+            return Err(anyhow!(
+                "Subscription {} doesn't ack deliveries",
+                config.consumer_name
+            ));
+        }
+
+        let (stream_name, subject_root) = self.event_stream(&config.tenant_id).await?;
+        let orphaned = self.orphaned_consumer(config, &stream_name).await;
+        let mut consumer_config = Self::consumer_config(config, self.max_ack_pending, subject_root);
+        if orphaned.is_some() {
+            // Pick up from the tenant stream's first event once the shared
+            // stream's backlog is delivered
+            consumer_config.deliver_policy = DeliverPolicy::All;
+        }
+        let stream = self.jetstream.get_stream(&stream_name).await?;
+        let consumer = stream
+            .get_or_create_consumer(&config.consumer_name, consumer_config)
+            .await
+            .map_err(|e| anyhow!("Failed to create consumer: {}", e))?;
+        let messages = async_stream::stream! {
+            if let Some((shared, orphaned)) = orphaned {
+                for await message in drain_orphaned_consumer(shared, orphaned) {
+                    yield message;
+                }
+            }
+            match consumer.messages().await {
+                Ok(mut messages) => {
+                    while let Some(message) = messages.next().await {
+                        yield message.map_err(|e| anyhow!("{}", e));
+                    }
+                }
+                Err(e) => yield Err(anyhow!("Failed to pull from consumer: {}", e)),
+            }
+        };
+        let topics: Arc<[String]> = config.topics.clone().into();
+        let consumer_name: Arc<str> = config.consumer_name.as_str().into();
+
+        Ok(messages.filter_map(move |message| {
+            let topics = topics.clone();
+            let consumer_name = consumer_name.clone();
+            async move {
+                let message = match message {
+                    Ok(message) => message,
+                    Err(e) => return Some(Err(anyhow!("Error receiving message: {}", e))),
+                };
+                // Another subscription's dead letter being redelivered
+                if redelivered_to(&message).is_some_and(|target| target != &*consumer_name) {
+                    if let Err(e) = message.ack().await {
+                        warn!("Failed to ack redelivered message: {}", e);
+                    }
+                    return None;
+                }
+                let (sequence, delivered) = match message.info() {
+                    Ok(info) => (info.stream_sequence, info.delivered),
+                    Err(e) => return Some(Err(anyhow!("Message without delivery info: {}", e))),
+                };
+                match serde_json::from_slice::<Event>(&message.payload) {
+                    // Partition filters can reach topics nobody subscribed to
+                    Ok(event) if !topic_allowed(&topics, &event.topic) => {
+                        if let Err(e) = message.ack().await {
+                            warn!("Failed to ack unsubscribed message: {}", e);
+                        }
+                        None
+                    }
+                    Ok(event) => Some(Ok(SubscriptionDelivery {
+                        event,
+                        sequence,
+                        delivered,
+                        message,
+                    })),
+                    Err(e) => {
+                        error!(
+                            "Failed to deserialize event at sequence {}: {}",
+                            sequence, e
+                        );
+                        if let Err(e) = message.ack_with(AckKind::Term).await {
+                            warn!("Failed to terminate invalid message: {}", e);
+                        }
+                        None
+                    }
+                }
+            }
+        }))
+    }
+
+    /// A durable subscription's consumer left on the shared stream from before
+    /// its tenant moved to `stream_name`, with the shared stream it is on
+    async fn orphaned_consumer(
+        &self,
+        config: &SubscriptionConfig,
+        stream_name: &str,
+    ) -> Option<(async_nats::jetstream::stream::Stream, PullConsumer)> {
+        if !config.durable || stream_name == self.stream_name {
+            return None;
+        }
+        let shared = self.jetstream.get_stream(&self.stream_name).await.ok()?;
+        let consumer = shared
+            .get_consumer::<ConsumerConfig>(&config.consumer_name)
+            .await
+            .ok()?;
+        Some((shared, consumer))
+    }
+
+    /// Subject filters scoping a replay to the tenant/project (and topic, if given)
+    fn replay_subjects(request: &ReplayRequest, subject_root: &str) -> Vec<String> {
+        if let Some(topic) = &request.topic {
+            partitioned_topic_filters(std::slice::from_ref(topic))
+                .into_iter()
+                .map(|topic| {
+                    format!(
+                        "{}.{}.{}.{}",
+                        subject_root, request.tenant_id, request.project_id, topic
+                    )
+                })
+                .collect()
+        } else {
+            vec![format!(
+                "{}.{}.{}.>",
+                subject_root, request.tenant_id, request.project_id
+            )]
+        }
+    }
+
+    // Whether a replayed event is on the requested topic; partition filters
+    // can also reach other topics
+    fn replay_matches(topic: Option<&str>, event: &Event) -> bool {
+        topic.map_or(true, |topic| topic_pattern_matches(topic, &event.topic))
+    }
+
+    /// Where a replay starts: the cursor's sequence, else the start time, else the beginning
+    pub fn replay_deliver_policy(request: &ReplayRequest) -> DeliverPolicy {
+        if let Some(cursor) = &request.cursor {
+            DeliverPolicy::ByStartSequence {
+                start_sequence: cursor.sequence,
+            }
+        } else if let Some(from_timestamp) = request.from_timestamp {
+            DeliverPolicy::ByStartTime {
+                start_time: offset_date_time(from_timestamp),
+            }
+        } else {
+            DeliverPolicy::All
+        }
+    }
+
+    /// Stream up to `limit` persisted events from an ephemeral consumer.
+    ///
+    /// The stream ends once the stored events are exhausted; the consumer is
+    /// removed by the server after it goes inactive.
+    pub async fn replay(&self, request: ReplayRequest) -> Result<impl Stream<Item = Event>> {
+        Ok(self
+            .replay_with_cursors(request)
+            .await?
+            .map(|(event, _)| event))
+    }
+
+    /// Like [`NatsClient::replay`], pairing each event with its stream cursor.
+    ///
+    /// A replay from a tenant's own stream that reaches back to before the
+    /// stream was created first reads the tenant's earlier events from the
+    /// shared stream; their cursors are that stream's sequences.
+    pub async fn replay_with_cursors(
+        &self,
+        request: ReplayRequest,
+    ) -> Result<impl Stream<Item = (Event, EventCursor)>> {
+        let (stream_name, subject_root) = self.event_stream(&request.tenant_id).await?;
+        if !self.starts_in_shared_stream(&request, &stream_name).await? {
+            return Ok(self
+                .replay_from(stream_name, subject_root, request)
+                .await?
+                .left_stream());
+        }
+
+        let limit = request.limit.unwrap_or(100);
+        let remainder = Self::tenant_stream_remainder(&request, limit);
+        let shared = self
+            .replay_from(self.stream_name.clone(), EVENT_SUBJECT_ROOT, request)
+            .await?;
+        let tenant = self.replay_from(stream_name, subject_root, remainder).await?;
+        Ok(shared.chain(tenant).take(limit).right_stream())
+    }
+
+    /// Whether a replay from the tenant stream `stream_name` reaches back to
+    /// before it was created, into the shared stream. A cursor whose time
+    /// isn't known points into the shared stream when it is past the tenant
+    /// stream's end, as the shared stream's sequences, counting every tenant's
+    /// events, run far ahead of it.
+    async fn starts_in_shared_stream(
+        &self,
+        request: &ReplayRequest,
+        stream_name: &str,
+    ) -> Result<bool> {
+        if stream_name == self.stream_name {
+            return Ok(false);
+        }
+        let mut stream = self.jetstream.get_stream(stream_name).await?;
+        let info = stream.info().await?;
+        let created = DateTime::<Utc>::from_timestamp(
+            info.created.unix_timestamp(),
+            info.created.nanosecond(),
+        )
+        .unwrap_or(DateTime::<Utc>::MIN_UTC);
+
+        Ok(match (&request.cursor, request.from_timestamp) {
+            (Some(cursor), _) if cursor.timestamp == DateTime::<Utc>::MIN_UTC => {
+                cursor.sequence > info.state.last_sequence.saturating_add(1)
+            }
+            (Some(cursor), _) => cursor.timestamp < created,
+            (None, Some(from_timestamp)) => from_timestamp < created,
+            (None, None) => true,
+        })
+    }
+
+    // The tenant stream's part of a replay that started in the shared stream:
+    // everything from the tenant stream's start, up to `limit` events
+    fn tenant_stream_remainder(request: &ReplayRequest, limit: usize) -> ReplayRequest {
+        ReplayRequest {
+            cursor: None,
+            from_timestamp: None,
+            limit: Some(limit),
+            ..request.clone()
+        }
+    }
+
+    // Replay `request` from one stream, whose subjects start with `subject_root`
+    async fn replay_from(
+        &self,
+        stream_name: String,
+        subject_root: &'static str,
+        request: ReplayRequest,
+    ) -> Result<impl Stream<Item = (Event, EventCursor)>> {
         let consumer_config = ConsumerConfig {
-            name: Some(config.consumer_name.clone()),
-            durable_name: if config.durable {
-                Some(config.consumer_name.clone())
-            } else {
-                None
-            },
-            deliver_policy: DeliverPolicy::New,
-            filter_subjects,
+            deliver_policy: Self::replay_deliver_policy(&request),
+            ack_policy: AckPolicy::None,
+            filter_subjects: Self::replay_subjects(&request, subject_root),
+            inactive_threshold: Duration::from_secs(30),
             ..Default::default()
         };
 
-        // Get the stream first, then create consumer
-        let stream = self.jetstream.get_stream(&self.stream_name).await?;
+        let stream = self.jetstream.get_stream(&stream_name).await?;
+        let consumer = stream.create_consumer(consumer_config).await?;
+        let batch = consumer
+            .fetch()
+            .max_messages(request.limit.unwrap_or(100))
+            .messages()
+            .await
+            .map_err(|e| anyhow!("Failed to fetch replay batch: {}", e))?;
 
-        match stream.create_consumer(consumer_config).await {
-            Ok(_) => {
-                info!(
-                    "Created consumer '{}' for tenant/project: {}/{}",
-                    config.consumer_name, config.tenant_id, config.project_id
-                );
-                Ok(())
-            }
-            Err(e) => {
-                error!("Failed to create consumer: {}", e);
-                Err(anyhow!("Failed to create consumer: {}", e))
-            }
-        }
+        let topic = request.topic;
+        let transform = request.transform;
+        Ok(batch.filter_map(move |message| {
+            let event = match message {
+                // Redelivered dead letters copy events already in the stream
+                Ok(msg) if redelivered_to(&msg).is_some() => None,
+                Ok(msg) => match (serde_json::from_slice::<Event>(&msg.payload), msg.info()) {
+                    (Ok(event), _) if !Self::replay_matches(topic.as_deref(), &event) => None,
+                    (Ok(event), Ok(info)) => {
+                        let cursor = EventCursor {
+                            sequence: info.stream_sequence,
+                            timestamp: event.published_at,
+                        };
+                        let event = match &transform {
+                            Some(transform) => transform.apply(&event),
+                            None => event,
+                        };
+                        Some((event, cursor))
+                    }
+                    (Err(e), _) => {
+                        error!("Failed to deserialize replayed event: {}", e);
+                        None
+                    }
+                    (_, Err(e)) => {
+                        error!("Replayed message has no stream info: {}", e);
+                        None
+                    }
+                },
+                Err(e) => {
+                    error!("Error receiving replayed message: {}", e);
+                    None
+                }
+            };
+            futures_util::future::ready(event)
+        }))
     }
 
-    /// Get events for replay with cursor support
+    /// The event stored at `sequence` in the stream holding `tenant_id`'s events
+    pub async fn event_at_sequence(&self, tenant_id: &str, sequence: u64) -> Result<Event> {
+        let (stream_name, _) = self.event_stream(tenant_id).await?;
+        let stream = self.jetstream.get_stream(&stream_name).await?;
+        let raw = stream
+            .get_raw_message(sequence)
+            .await
+            .map_err(|e| anyhow!("Failed to get message at sequence {}: {}", sequence, e))?;
+        let message = async_nats::Message::try_from(raw)
+            .map_err(|e| anyhow!("Invalid message at sequence {}: {}", sequence, e))?;
+        Ok(serde_json::from_slice(&message.payload)?)
+    }
+
+    /// Get events for replay with cursor support, reading the shared stream
+    /// first like [`NatsClient::replay_with_cursors`]
     pub async fn replay_events(
         &self,
         request: &ReplayRequest,
     ) -> Result<Vec<(Event, EventCursor)>> {
-        let subject_filter = if let Some(topic) = &request.topic {
-            format!(
-                "events.{}.{}.{}",
-                request.tenant_id, request.project_id, topic
-            )
-        } else {
-            format!("events.{}.{}.>", request.tenant_id, request.project_id)
-        };
+        let (stream_name, subject_root) = self.event_stream(&request.tenant_id).await?;
+        if !self.starts_in_shared_stream(request, &stream_name).await? {
+            return self
+                .replay_events_from(&stream_name, subject_root, request)
+                .await;
+        }
+
+        let limit = request.limit.unwrap_or(100);
+        let mut events = self
+            .replay_events_from(&self.stream_name, EVENT_SUBJECT_ROOT, request)
+            .await?;
+        if events.len() < limit {
+            let remainder = Self::tenant_stream_remainder(request, limit - events.len());
+            events.extend(
+                self.replay_events_from(&stream_name, subject_root, &remainder)
+                    .await?,
+            );
+        }
+        Ok(events)
+    }
+
+    async fn replay_events_from(
+        &self,
+        stream_name: &str,
+        subject_root: &str,
+        request: &ReplayRequest,
+    ) -> Result<Vec<(Event, EventCursor)>> {
+        let filter_subjects = Self::replay_subjects(request, subject_root);
 
         // Create a temporary consumer for replay
         let consumer_name = format!(
@@ -206,22 +1281,14 @@ impl NatsClient {
             chrono::Utc::now().timestamp()
         );
 
-        let deliver_policy = if let Some(cursor) = &request.cursor {
-            DeliverPolicy::ByStartSequence {
-                start_sequence: cursor.sequence,
-            }
-        } else {
-            DeliverPolicy::All
-        };
-
         let consumer_config = ConsumerConfig {
             name: Some(consumer_name.clone()),
-            deliver_policy,
-            filter_subjects: vec![subject_filter],
+            deliver_policy: Self::replay_deliver_policy(request),
+            filter_subjects,
             ..Default::default()
         };
 
-        let stream = self.jetstream.get_stream(&self.stream_name).await?;
+        let stream = self.jetstream.get_stream(stream_name).await?;
         let consumer = stream.create_consumer(consumer_config).await?;
 
         let mut events = Vec::new();
@@ -236,11 +1303,23 @@ impl NatsClient {
                     Ok(msg) => {
                         // Deserialize the event
                         match serde_json::from_slice::<Event>(&msg.payload) {
+                            Ok(event)
+                                if redelivered_to(&msg).is_some()
+                                    || !Self::replay_matches(request.topic.as_deref(), &event) =>
+                            {
+                                if let Err(e) = msg.ack().await {
+                                    warn!("Failed to ack message: {}", e);
+                                }
+                            }
                             Ok(event) => {
                                 let cursor = EventCursor {
                                     sequence: msg.info().unwrap().stream_sequence,
                                     timestamp: event.published_at,
                                 };
+                                let event = match &request.transform {
+                                    Some(transform) => transform.apply(&event),
+                                    None => event,
+                                };
                                 events.push((event, cursor));
 
                                 // Acknowledge the message
@@ -267,7 +1346,6 @@ impl NatsClient {
         }
 
         // Clean up temporary consumer
-        let stream = self.jetstream.get_stream(&self.stream_name).await?;
         if let Err(e) = stream.delete_consumer(&consumer_name).await {
             warn!("Failed to delete temporary consumer: {}", e);
         }
@@ -282,6 +1360,27 @@ impl NatsClient {
         Ok(events)
     }
 
+    /// First and last sequence currently retained in the stream holding
+    /// `tenant_id`'s events. For a tenant stream, the last is the shared
+    /// stream's when that is further on, so that resuming from a sequence
+    /// issued before the tenant stream was created replays from the shared
+    /// stream.
+    pub async fn sequence_bounds(&self, tenant_id: &str) -> Result<(u64, u64)> {
+        let (stream_name, _) = self.event_stream(tenant_id).await?;
+        let mut stream = self.jetstream.get_stream(&stream_name).await?;
+        let info = stream.info().await?;
+        let (first, last) = (info.state.first_sequence, info.state.last_sequence);
+        if stream_name == self.stream_name {
+            return Ok((first, last));
+        }
+
+        let shared_last = match self.jetstream.get_stream(&self.stream_name).await {
+            Ok(mut shared) => shared.info().await?.state.last_sequence,
+            Err(_) => 0,
+        };
+        Ok((first, last.max(shared_last)))
+    }
+
     /// Get stream information and statistics
     pub async fn get_stream_info(&self) -> Result<HashMap<String, serde_json::Value>> {
         let mut stream = self.jetstream.get_stream(&self.stream_name).await?;
@@ -312,9 +1411,10 @@ impl NatsClient {
         Ok(stats)
     }
 
-    /// Delete a consumer
-    pub async fn delete_consumer(&self, consumer_name: &str) -> Result<()> {
-        let stream = self.jetstream.get_stream(&self.stream_name).await?;
+    /// Delete a consumer on the stream holding `tenant_id`'s events
+    pub async fn delete_consumer(&self, tenant_id: &str, consumer_name: &str) -> Result<()> {
+        let (stream_name, _) = self.event_stream(tenant_id).await?;
+        let stream = self.jetstream.get_stream(&stream_name).await?;
         stream.delete_consumer(consumer_name).await?;
         info!("Deleted consumer: {}", consumer_name);
         Ok(())
@@ -336,6 +1436,373 @@ impl NatsClient {
     }
 }
 
+/// Dead-letter stream: events that failed schema validation or were never
+/// acknowledged by JetStream, kept per tenant/project for inspection and replay
+impl NatsClient {
+    fn dead_letter_stream_name(&self) -> String {
+        format!("{}_DLQ", self.stream_name)
+    }
+
+    /// Subject dead-lettered events for a tenant/project are published to
+    pub fn dead_letter_subject(tenant_id: &str, project_id: &str) -> String {
+        format!("dlq.{}.{}", tenant_id, project_id)
+    }
+
+    /// Store a failed event with its failure reason, returning its dead-letter id
+    pub async fn publish_dead_letter(
+        &self,
+        event: &Event,
+        kind: DeadLetterKind,
+        reason: &str,
+    ) -> Result<u64> {
+        self.publish_targeted_dead_letter(event, kind, None, reason)
+            .await
+    }
+
+    /// Store an event that failed to reach `target`, such as a webhook, so a
+    /// replay can retry just that target
+    pub async fn publish_targeted_dead_letter(
+        &self,
+        event: &Event,
+        kind: DeadLetterKind,
+        target: Option<&str>,
+        reason: &str,
+    ) -> Result<u64> {
+        let payload = serde_json::to_vec(event)?;
+
+        let mut headers = async_nats::HeaderMap::new();
+        headers.insert("tenant_id", event.tenant_id.as_str());
+        headers.insert("project_id", event.project_id.as_str());
+        headers.insert("topic", event.topic.as_str());
+        headers.insert("event_id", event.id.as_str());
+        headers.insert(DEAD_LETTER_KIND_HEADER, kind.as_str());
+        // Header values can't span lines
+        headers.insert(
+            DEAD_LETTER_REASON_HEADER,
+            reason.replace(['\r', '\n'], " ").as_str(),
+        );
+        headers.insert(DEAD_LETTER_AT_HEADER, Utc::now().to_rfc3339().as_str());
+        if let Some(target) = target {
+            headers.insert(DEAD_LETTER_TARGET_HEADER, target);
+        }
+
+        let ack = self
+            .jetstream
+            .publish_with_headers(
+                Self::dead_letter_subject(&event.tenant_id, &event.project_id),
+                headers,
+                payload.into(),
+            )
+            .await?
+            .await?;
+
+        warn!(
+            "Dead-lettered event {} ({}): {}",
+            event.id,
+            kind.as_str(),
+            reason
+        );
+        Ok(ack.sequence)
+    }
+
+    /// Dead-lettered events for a tenant (optionally one project) since the
+    /// given time, oldest first
+    pub async fn list_dead_letters(
+        &self,
+        tenant_id: &str,
+        project_id: Option<&str>,
+        since: DateTime<Utc>,
+        limit: usize,
+    ) -> Result<Vec<DeadLetter>> {
+        let consumer_config = ConsumerConfig {
+            deliver_policy: DeliverPolicy::ByStartTime {
+                start_time: offset_date_time(since),
+            },
+            ack_policy: AckPolicy::None,
+            filter_subjects: vec![Self::dead_letter_subject(
+                tenant_id,
+                project_id.unwrap_or("*"),
+            )],
+            inactive_threshold: Duration::from_secs(30),
+            ..Default::default()
+        };
+
+        self.fetch_dead_letters(consumer_config, limit).await
+    }
+
+    /// A tenant's dead-lettered event by id
+    pub async fn get_dead_letter(&self, tenant_id: &str, id: u64) -> Result<Option<DeadLetter>> {
+        let consumer_config = ConsumerConfig {
+            deliver_policy: DeliverPolicy::ByStartSequence { start_sequence: id },
+            ack_policy: AckPolicy::None,
+            filter_subjects: vec![Self::dead_letter_subject(tenant_id, "*")],
+            inactive_threshold: Duration::from_secs(30),
+            ..Default::default()
+        };
+
+        // The first match at or after `id` is a later event when `id` isn't the tenant's
+        Ok(self
+            .fetch_dead_letters(consumer_config, 1)
+            .await?
+            .into_iter()
+            .find(|dead_letter| dead_letter.id == id))
+    }
+
+    /// Remove an event from the dead-letter stream
+    pub async fn delete_dead_letter(&self, id: u64) -> Result<()> {
+        let stream = self
+            .jetstream
+            .get_stream(self.dead_letter_stream_name())
+            .await?;
+        stream.delete_message(id).await?;
+        Ok(())
+    }
+
+    async fn fetch_dead_letters(
+        &self,
+        consumer_config: ConsumerConfig,
+        limit: usize,
+    ) -> Result<Vec<DeadLetter>> {
+        let stream = self
+            .jetstream
+            .get_stream(self.dead_letter_stream_name())
+            .await?;
+        let consumer = stream.create_consumer(consumer_config).await?;
+        let mut batch = consumer
+            .fetch()
+            .max_messages(limit)
+            .messages()
+            .await
+            .map_err(|e| anyhow!("Failed to fetch dead letters: {}", e))?;
+
+        let mut dead_letters = Vec::new();
+        while let Some(message) = batch.next().await {
+            let message = message.map_err(|e| anyhow!("Error receiving dead letter: {}", e))?;
+            match dead_letter_from_message(&message) {
+                Ok(dead_letter) => dead_letters.push(dead_letter),
+                Err(e) => error!("Skipping unreadable dead letter: {}", e),
+            }
+        }
+        Ok(dead_letters)
+    }
+}
+
+/// Deliver what an orphaned durable consumer still holds, ending once every
+/// event it held is acked or terminated, at which point it is deleted
+fn drain_orphaned_consumer(
+    shared: async_nats::jetstream::stream::Stream,
+    mut consumer: PullConsumer,
+) -> impl Stream<Item = Result<async_nats::jetstream::Message>> {
+    async_stream::stream! {
+        loop {
+            let batch = consumer
+                .fetch()
+                .max_messages(ORPHANED_CONSUMER_BATCH)
+                .expires(ORPHANED_CONSUMER_WAIT)
+                .messages()
+                .await;
+            let mut batch = match batch {
+                Ok(batch) => batch,
+                Err(e) => {
+                    yield Err(anyhow!("Failed to fetch from orphaned consumer: {}", e));
+                    return;
+                }
+            };
+            let mut fetched = false;
+            while let Some(message) = batch.next().await {
+                fetched = true;
+                yield message.map_err(|e| anyhow!("{}", e));
+            }
+            if fetched {
+                continue;
+            }
+            // Nacked events are still due until they're settled
+            match consumer.info().await {
+                Ok(info) if info.num_pending == 0 && info.num_ack_pending == 0 => break,
+                Ok(_) => {}
+                Err(e) => {
+                    yield Err(anyhow!("Failed to check orphaned consumer: {}", e));
+                    return;
+                }
+            }
+        }
+
+        let name = consumer.cached_info().name.clone();
+        match shared.delete_consumer(&name).await {
+            Ok(_) => info!("Drained and deleted consumer '{}' on the shared stream", name),
+            Err(e) => warn!("Failed to delete drained consumer '{}': {}", name, e),
+        }
+    }
+}
+
+/// The subscription a redelivered dead letter is meant for, if the message is one
+fn redelivered_to(message: &async_nats::jetstream::Message) -> Option<&str> {
+    message
+        .headers
+        .as_ref()
+        .and_then(|headers| headers.get(REDELIVER_TO_HEADER))
+        .map(|value| value.as_str())
+}
+
+fn dead_letter_from_message(message: &async_nats::jetstream::Message) -> Result<DeadLetter> {
+    let header = |name: &str| {
+        message
+            .headers
+            .as_ref()
+            .and_then(|headers| headers.get(name))
+            .map(|value| value.as_str().to_string())
+    };
+
+    Ok(DeadLetter {
+        id: message
+            .info()
+            .map_err(|e| anyhow!("Dead letter has no stream info: {}", e))?
+            .stream_sequence,
+        kind: header(DEAD_LETTER_KIND_HEADER)
+            .as_deref()
+            .and_then(DeadLetterKind::parse)
+            .ok_or_else(|| anyhow!("Dead letter has no failure kind"))?,
+        reason: header(DEAD_LETTER_REASON_HEADER).unwrap_or_default(),
+        target: header(DEAD_LETTER_TARGET_HEADER),
+        dead_lettered_at: header(DEAD_LETTER_AT_HEADER)
+            .and_then(|at| DateTime::parse_from_rfc3339(&at).ok())
+            .map(|at| at.with_timezone(&Utc))
+            .unwrap_or(DateTime::<Utc>::MIN_UTC),
+        event: serde_json::from_slice(&message.payload)?,
+    })
+}
+
+fn retention_policy(retention: StreamRetention) -> RetentionPolicy {
+    match retention {
+        StreamRetention::Limits => RetentionPolicy::Limits,
+        StreamRetention::Interest => RetentionPolicy::Interest,
+        StreamRetention::WorkQueue => RetentionPolicy::WorkQueue,
+    }
+}
+
+fn offset_date_time(timestamp: DateTime<Utc>) -> time::OffsetDateTime {
+    time::OffsetDateTime::from_unix_timestamp_nanos(
+        timestamp.timestamp_nanos_opt().unwrap_or_default() as i128,
+    )
+    .unwrap_or(time::OffsetDateTime::UNIX_EPOCH)
+}
+
+/// Subject an event is published to: `{subject_root}.{tenant_id}.{project_id}.{topic}`,
+/// followed by `.p{n}` for events published with a partition key, `n` being
+/// the key's partition. Consumers can filter on one partition's subject to
+/// process partitions in parallel while each key stays in order.
+pub fn event_subject(subject_root: &str, event: &Event) -> String {
+    let subject = format!(
+        "{}.{}.{}.{}",
+        subject_root, event.tenant_id, event.project_id, event.topic
+    );
+    match event.partition() {
+        Some(partition) => format!("{}.p{}", subject, partition),
+        None => subject,
+    }
+}
+
+// Topic filters matching `topics` with or without a partition suffix. A
+// trailing `>` already matches the suffix; other topics also get `{topic}.*`,
+// which can reach deeper topics, so callers re-check each event's topic.
+// Filters covered by another are dropped, as JetStream rejects overlapping
+// filter subjects.
+fn partitioned_topic_filters(topics: &[String]) -> Vec<String> {
+    let mut filters: Vec<String> = topics
+        .iter()
+        .flat_map(|topic| {
+            let suffixed = (!topic.ends_with('>')).then(|| format!("{}.*", topic));
+            std::iter::once(topic.clone()).chain(suffixed)
+        })
+        .collect();
+    filters.sort();
+    filters.dedup();
+    filters
+        .iter()
+        .filter(|filter| {
+            !filters
+                .iter()
+                .any(|other| other != *filter && topic_pattern_covers(other, filter))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Publish an event, already serialized as `payload`, to its
+/// [`event_subject`] and wait for its ack.
+///
+/// The key and its partition also travel as headers for consumers that
+/// shard work by partition.
+///
+/// The event's attributes are also set as CloudEvents binary-mode `ce-*`
+/// headers, followed by the producer's own headers, which the event builder
+/// has already checked don't collide with any header set here.
+/// `redeliver_to` restricts delivery to a single subscription.
+async fn publish_to_jetstream(
+    jetstream: &JetStreamContext,
+    subject_root: &str,
+    event: &Event,
+    payload: Vec<u8>,
+    msg_id: Option<&str>,
+    redeliver_to: Option<&str>,
+) -> Result<PublishAck> {
+    let subject = event_subject(subject_root, event);
+
+    // Add metadata headers
+    let mut headers = async_nats::HeaderMap::new();
+    headers.insert("tenant_id", event.tenant_id.as_str());
+    headers.insert("project_id", event.project_id.as_str());
+    headers.insert("topic", event.topic.as_str());
+    headers.insert("event_id", event.id.as_str());
+    headers.insert("published_at", event.published_at.to_rfc3339().as_str());
+    if let (Some(partition_key), Some(partition)) = (&event.partition_key, event.partition()) {
+        headers.insert("partition_key", partition_key.as_str());
+        headers.insert("partition", partition.to_string().as_str());
+    }
+    headers.insert("ce-specversion", CLOUDEVENTS_SPEC_VERSION);
+    headers.insert("ce-id", event.id.as_str());
+    headers.insert(
+        "ce-source",
+        event_source(&event.tenant_id, &event.project_id).as_str(),
+    );
+    headers.insert("ce-type", event.topic.as_str());
+    headers.insert("ce-time", event.published_at.to_rfc3339().as_str());
+    for (name, value) in &event.headers {
+        headers.insert(name.as_str(), value.as_str());
+    }
+    if let Some(msg_id) = msg_id {
+        headers.insert(async_nats::header::NATS_MESSAGE_ID, msg_id);
+    }
+    if let Some(consumer_name) = redeliver_to {
+        headers.insert(REDELIVER_TO_HEADER, consumer_name);
+    }
+
+    // Publish to JetStream
+    let ack = jetstream
+        .publish_with_headers(subject, headers, payload.into())
+        .await?;
+
+    let ack_result = ack.await?;
+    let sequence = ack_result.sequence;
+
+    if ack_result.duplicate {
+        info!(
+            "JetStream dropped event {} as a duplicate of sequence: {}",
+            event.id, sequence
+        );
+    } else {
+        info!(
+            "Published event {} to JetStream with sequence: {}",
+            event.id, sequence
+        );
+    }
+
+    Ok(PublishAck {
+        sequence,
+        duplicate: ack_result.duplicate,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -363,6 +1830,10 @@ mod tests {
             topics: vec!["user.created".to_string(), "user.updated".to_string()],
             consumer_name: "websocket_consumer".to_string(),
             durable: true,
+            max_ack_pending: None,
+            explicit_ack: false,
+            ack_wait: None,
+            max_deliver: None,
         };
 
         assert_eq!(config.tenant_id, "tenant_123");
@@ -370,6 +1841,75 @@ mod tests {
         assert!(config.durable);
     }
 
+    #[test]
+    fn test_durable_consumer_caps_ack_pending() {
+        let mut config = SubscriptionConfig {
+            tenant_id: "tenant_123".to_string(),
+            project_id: "project_456".to_string(),
+            topics: vec![],
+            consumer_name: "durable_consumer".to_string(),
+            durable: true,
+            max_ack_pending: None,
+            explicit_ack: false,
+            ack_wait: None,
+            max_deliver: None,
+        };
+
+        let consumer_config = NatsClient::consumer_config(&config, DEFAULT_MAX_ACK_PENDING, EVENT_SUBJECT_ROOT);
+        assert_eq!(consumer_config.max_ack_pending, DEFAULT_MAX_ACK_PENDING);
+        assert_eq!(consumer_config.ack_policy, AckPolicy::Explicit);
+
+        config.max_ack_pending = Some(5);
+        let consumer_config = NatsClient::consumer_config(&config, DEFAULT_MAX_ACK_PENDING, EVENT_SUBJECT_ROOT);
+        assert_eq!(consumer_config.max_ack_pending, 5);
+    }
+
+    #[test]
+    fn test_ephemeral_consumer_uses_server_default_ack_pending() {
+        let config = SubscriptionConfig {
+            tenant_id: "tenant_123".to_string(),
+            project_id: "project_456".to_string(),
+            topics: vec!["user.created".to_string()],
+            consumer_name: "ephemeral_consumer".to_string(),
+            durable: false,
+            max_ack_pending: Some(5),
+            explicit_ack: false,
+            ack_wait: None,
+            max_deliver: None,
+        };
+
+        let consumer_config = NatsClient::consumer_config(&config, DEFAULT_MAX_ACK_PENDING, EVENT_SUBJECT_ROOT);
+        assert_eq!(consumer_config.max_ack_pending, ConsumerConfig::default().max_ack_pending);
+        assert!(consumer_config.durable_name.is_none());
+    }
+
+    #[test]
+    fn test_acking_consumer_redelivers_until_max_deliver() {
+        let mut config = SubscriptionConfig {
+            tenant_id: "tenant_123".to_string(),
+            project_id: "project_456".to_string(),
+            topics: vec![],
+            consumer_name: "acking_consumer".to_string(),
+            durable: false,
+            max_ack_pending: None,
+            explicit_ack: true,
+            ack_wait: None,
+            max_deliver: None,
+        };
+
+        let consumer_config = NatsClient::consumer_config(&config, DEFAULT_MAX_ACK_PENDING, EVENT_SUBJECT_ROOT);
+        assert_eq!(consumer_config.ack_policy, AckPolicy::Explicit);
+        assert_eq!(consumer_config.ack_wait, DEFAULT_ACK_WAIT);
+        assert_eq!(consumer_config.max_deliver, DEFAULT_MAX_DELIVER);
+        assert!(consumer_config.durable_name.is_none());
+
+        config.ack_wait = Some(Duration::from_secs(5));
+        config.max_deliver = Some(2);
+        let consumer_config = NatsClient::consumer_config(&config, DEFAULT_MAX_ACK_PENDING, EVENT_SUBJECT_ROOT);
+        assert_eq!(consumer_config.ack_wait, Duration::from_secs(5));
+        assert_eq!(consumer_config.max_deliver, 2);
+    }
+
     #[test]
     fn test_replay_request() {
         let cursor = EventCursor {
@@ -382,7 +1922,9 @@ mod tests {
             project_id: "project_456".to_string(),
             topic: Some("user.created".to_string()),
             cursor: Some(cursor.clone()),
+            from_timestamp: None,
             limit: Some(50),
+            transform: None,
         };
 
         assert_eq!(request.tenant_id, "tenant_123");
@@ -390,4 +1932,232 @@ mod tests {
         assert_eq!(request.cursor.unwrap().sequence, 100);
         assert_eq!(request.limit, Some(50));
     }
+
+    #[test]
+    fn test_replay_deliver_policy_prefers_cursor() {
+        let mut request = ReplayRequest {
+            tenant_id: "tenant_123".to_string(),
+            project_id: "project_456".to_string(),
+            topic: None,
+            cursor: Some(EventCursor::at_sequence(42)),
+            from_timestamp: Some(Utc::now()),
+            limit: None,
+            transform: None,
+        };
+        assert!(matches!(
+            NatsClient::replay_deliver_policy(&request),
+            DeliverPolicy::ByStartSequence { start_sequence: 42 }
+        ));
+
+        request.cursor = None;
+        assert!(matches!(
+            NatsClient::replay_deliver_policy(&request),
+            DeliverPolicy::ByStartTime { .. }
+        ));
+
+        request.from_timestamp = None;
+        assert!(matches!(
+            NatsClient::replay_deliver_policy(&request),
+            DeliverPolicy::All
+        ));
+    }
+
+    #[test]
+    fn test_reconnect_delay_backs_off_exponentially() {
+        assert_eq!(reconnect_delay(0), Duration::from_millis(100));
+        assert_eq!(reconnect_delay(1), Duration::from_millis(200));
+        assert_eq!(reconnect_delay(3), Duration::from_millis(800));
+        assert_eq!(reconnect_delay(10), RECONNECT_MAX_DELAY);
+        assert_eq!(reconnect_delay(usize::MAX), RECONNECT_MAX_DELAY);
+    }
+
+    #[test]
+    fn test_redelivery_delay_backs_off_exponentially() {
+        assert_eq!(redelivery_delay(1), Duration::from_millis(500));
+        assert_eq!(redelivery_delay(2), Duration::from_secs(1));
+        assert_eq!(redelivery_delay(4), Duration::from_secs(4));
+        assert_eq!(redelivery_delay(10), REDELIVERY_MAX_DELAY);
+        assert_eq!(redelivery_delay(i64::MAX), REDELIVERY_MAX_DELAY);
+        assert_eq!(redelivery_delay(0), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_only_unavailability_trips_the_publish_breaker() {
+        for kind in [
+            PublishErrorKind::TimedOut,
+            PublishErrorKind::BrokenPipe,
+            PublishErrorKind::StreamNotFound,
+        ] {
+            let error = anyhow::Error::from(PublishError::from(kind.clone()));
+            assert!(is_nats_unavailable(&error), "{}", kind);
+            assert!(is_nats_unavailable(&error.context("publishing event")));
+        }
+
+        for kind in [
+            PublishErrorKind::WrongLastMessageId,
+            PublishErrorKind::WrongLastSequence,
+            PublishErrorKind::Other,
+        ] {
+            let error = anyhow::Error::from(PublishError::from(kind.clone()));
+            assert!(!is_nats_unavailable(&error), "{}", kind);
+        }
+        assert!(!is_nats_unavailable(&anyhow!("event serialization failed")));
+    }
+
+    #[tokio::test]
+    async fn test_buffered_events_are_delivered_after_reconnect() {
+        let event = |topic: &str| {
+            Event::new(
+                "tenant_123".to_string(),
+                "project_456".to_string(),
+                topic.to_string(),
+                serde_json::json!({}),
+            )
+        };
+
+        // Connection dropped: publishes queue up until the buffer is full
+        let buffer = PublishBuffer::new(2);
+        let first = buffer.push(event("orders.created"), vec![1], None).unwrap();
+        let second = buffer
+            .push(event("orders.paid"), vec![2], Some("key-1".to_string()))
+            .unwrap();
+        let full = buffer
+            .push(event("orders.shipped"), vec![3], None)
+            .unwrap_err();
+        assert!(is_nats_unavailable(&full));
+
+        // A flush attempt while still disconnected keeps everything queued
+        let delivered = buffer
+            .flush(
+                |_, _, _| async { Err(anyhow!("connection closed")) },
+                || false,
+            )
+            .await;
+        assert_eq!(delivered, 0);
+        assert_eq!(buffer.len(), 2);
+
+        // Reconnected: events are delivered in order with their message ids
+        let mut published = Vec::new();
+        let delivered = buffer
+            .flush(
+                |event, payload, msg_id| {
+                    published.push((event.topic, payload, msg_id));
+                    let sequence = published.len() as u64;
+                    async move {
+                        Ok(PublishAck {
+                            sequence,
+                            duplicate: false,
+                        })
+                    }
+                },
+                || true,
+            )
+            .await;
+
+        assert_eq!(delivered, 2);
+        assert!(buffer.is_empty());
+        assert_eq!(
+            published,
+            vec![
+                ("orders.created".to_string(), vec![1], None),
+                (
+                    "orders.paid".to_string(),
+                    vec![2],
+                    Some("key-1".to_string())
+                ),
+            ]
+        );
+        assert_eq!(first.await.unwrap().unwrap().sequence, 1);
+        assert_eq!(second.await.unwrap().unwrap().sequence, 2);
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_buffered_events_are_never_published() {
+        let event = |topic: &str| {
+            Event::new(
+                "tenant_123".to_string(),
+                "project_456".to_string(),
+                topic.to_string(),
+                serde_json::json!({}),
+            )
+        };
+
+        let buffer = PublishBuffer::new(3);
+        let abandoned = buffer.push(event("orders.created"), vec![], None).unwrap();
+        let waiting = buffer.push(event("orders.paid"), vec![], None).unwrap();
+        let abandoned_later = buffer.push(event("orders.shipped"), vec![], None).unwrap();
+
+        // A timed out publisher drops its receiver and discards the entry
+        drop(abandoned);
+        buffer.discard_abandoned();
+        assert_eq!(buffer.len(), 2);
+
+        // One abandoned after the discard is skipped by the flush instead
+        drop(abandoned_later);
+        let mut published = Vec::new();
+        let delivered = buffer
+            .flush(
+                |event, _, _| {
+                    published.push(event.topic);
+                    async {
+                        Ok(PublishAck {
+                            sequence: 1,
+                            duplicate: false,
+                        })
+                    }
+                },
+                || true,
+            )
+            .await;
+
+        assert_eq!(delivered, 1);
+        assert!(buffer.is_empty());
+        assert_eq!(published, vec!["orders.paid".to_string()]);
+        assert!(waiting.await.unwrap().is_ok());
+        assert!(is_nats_unavailable(&anyhow::Error::from(PublishBufferTimeout {
+            timeout: Duration::from_secs(1),
+        })));
+    }
+
+    #[test]
+    fn test_partitioned_events_get_a_partition_subject() {
+        let mut event = Event::new(
+            "tenant_123".to_string(),
+            "project_456".to_string(),
+            "orders.updated".to_string(),
+            serde_json::json!({}),
+        );
+        assert_eq!(
+            event_subject(EVENT_SUBJECT_ROOT, &event),
+            "events.tenant_123.project_456.orders.updated"
+        );
+
+        event.partition_key = Some("order_42".to_string());
+        assert_eq!(
+            event_subject(EVENT_SUBJECT_ROOT, &event),
+            format!(
+                "events.tenant_123.project_456.orders.updated.p{}",
+                crate::models::partition_for_key("order_42")
+            )
+        );
+    }
+
+    #[test]
+    fn test_topic_filters_cover_partition_subjects_without_overlap() {
+        let topics = |topics: &[&str]| -> Vec<String> {
+            partitioned_topic_filters(&topics.iter().map(|t| t.to_string()).collect::<Vec<_>>())
+        };
+
+        assert_eq!(
+            topics(&["orders.created"]),
+            vec!["orders.created", "orders.created.*"]
+        );
+        assert_eq!(topics(&["orders.>"]), vec!["orders.>"]);
+        // `orders.*` already covers `orders.created`
+        assert_eq!(
+            topics(&["orders", "orders.created"]),
+            vec!["orders", "orders.*", "orders.created.*"]
+        );
+        assert_eq!(topics(&["orders.>", "orders.created"]), vec!["orders.>"]);
+    }
 }
@@ -3,13 +3,102 @@ use axum_prometheus::PrometheusMetricLayer;
 use opentelemetry::global;
 use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::{runtime, Resource};
-use prometheus::{Counter, Histogram, Registry, Gauge};
-use std::sync::Arc;
-use tracing::{info, warn, Span};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use prometheus::{Counter, Histogram, HistogramOpts, HistogramVec, Registry, Gauge};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::subscriber::Interest;
+use tracing::{info, span, warn, Event, Level, Metadata, Span, Subscriber};
+use tracing_subscriber::fmt::format::{self, FormatEvent, FormatFields, JsonFields};
+use tracing_subscriber::fmt::{FmtContext, FormattedFields, MakeWriter};
+use tracing_subscriber::layer::{Context, Filter};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
 use uuid::Uuid;
 
-use crate::config::Config;
+use crate::config::{Config, LogFormat};
+use crate::models::BillingPlan;
+use crate::request_id::current_request_id;
+
+// Publish latency and payload size, labelled by tenant tier (the billing plan)
+// rather than tenant id so the series count stays fixed as tenants are added
+lazy_static::lazy_static! {
+    static ref PUBLISH_LATENCY: HistogramVec = HistogramVec::new(
+        HistogramOpts::new(
+            "realtime_publish_latency_seconds",
+            "Time to publish an event to JetStream and receive its acknowledgement"
+        )
+        .buckets(vec![
+            0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0,
+        ]),
+        &["tier"]
+    )
+    .expect("valid metric definition");
+    static ref PAYLOAD_SIZE: HistogramVec = HistogramVec::new(
+        HistogramOpts::new(
+            "realtime_event_payload_size_bytes",
+            "Serialized size of published event payloads"
+        )
+        // 128 B up to 2 MiB
+        .buckets(prometheus::exponential_buckets(128.0, 4.0, 8).expect("valid buckets")),
+        &["tier"]
+    )
+    .expect("valid metric definition");
+    static ref WEBSOCKET_CONNECTIONS_ACTIVE: Gauge = Gauge::new(
+        "realtime_websocket_connections_active",
+        "Number of active WebSocket connections"
+    )
+    .expect("valid metric definition");
+    static ref SSE_CONNECTIONS_ACTIVE: Gauge = Gauge::new(
+        "realtime_sse_connections_active",
+        "Number of active SSE connections"
+    )
+    .expect("valid metric definition");
+}
+
+/// Gauge of open WebSocket connections, kept in step by the global manager
+pub fn websocket_connections_gauge() -> &'static Gauge {
+    &WEBSOCKET_CONNECTIONS_ACTIVE
+}
+
+/// Gauge of open SSE connections, kept in step by the global manager
+pub fn sse_connections_gauge() -> &'static Gauge {
+    &SSE_CONNECTIONS_ACTIVE
+}
+
+/// Metric label for a tenant's billing plan
+pub fn tenant_tier(plan: &BillingPlan) -> &'static str {
+    match plan {
+        BillingPlan::Free { .. } => "free",
+        BillingPlan::Pro { .. } => "pro",
+        BillingPlan::Enterprise { .. } => "enterprise",
+    }
+}
+
+/// Histogram of JetStream publish latency, labelled by tenant tier
+pub fn publish_latency_histogram() -> &'static HistogramVec {
+    &PUBLISH_LATENCY
+}
+
+/// Histogram of serialized payload sizes, labelled by tenant tier
+pub fn payload_size_histogram() -> &'static HistogramVec {
+    &PAYLOAD_SIZE
+}
+
+/// Record how long a publish to JetStream took for a tenant of the given tier
+pub fn record_publish_latency(tier: &str, topic: &str, seconds: f64) {
+    PUBLISH_LATENCY.with_label_values(&[tier]).observe(seconds);
+    tracing::debug!(tier = tier, topic = topic, seconds = seconds, "Event publish latency");
+}
+
+/// Record the serialized payload size of an event published by a tenant of the given tier
+pub fn record_payload_size(tier: &str, bytes: usize) {
+    PAYLOAD_SIZE.with_label_values(&[tier]).observe(bytes as f64);
+}
 
 /// Metrics collector for the realtime platform
 #[derive(Clone)]
@@ -17,6 +106,7 @@ pub struct Metrics {
     pub registry: Arc<Registry>,
     pub events_published_total: Counter,
     pub events_delivered_total: Counter,
+    pub events_deduplicated_total: Counter,
     pub websocket_connections_active: Gauge,
     pub sse_connections_active: Gauge,
     pub api_requests_total: Counter,
@@ -24,6 +114,10 @@ pub struct Metrics {
     pub billing_operations_total: Counter,
     pub auth_operations_total: Counter,
     pub errors_total: Counter,
+    pub event_ordering_inversions_total: Counter,
+    // Periodic readings of the SLA counters, oldest first, so summaries can
+    // cover a recent window rather than everything since the process started
+    sla_samples: Arc<Mutex<VecDeque<(tokio::time::Instant, SlaCounters)>>>,
 }
 
 impl Metrics {
@@ -40,15 +134,14 @@ impl Metrics {
             "Total number of events delivered to clients"
         )?;
         
-        let websocket_connections_active = Gauge::new(
-            "realtime_websocket_connections_active",
-            "Number of active WebSocket connections"
+        let events_deduplicated_total = Counter::new(
+            "realtime_events_deduplicated_total",
+            "Total number of events suppressed by content deduplication"
         )?;
         
-        let sse_connections_active = Gauge::new(
-            "realtime_sse_connections_active",
-            "Number of active SSE connections"
-        )?;
+        // Shared with the global connection managers, which update them
+        let websocket_connections_active = websocket_connections_gauge().clone();
+        let sse_connections_active = sse_connections_gauge().clone();
         
         let api_requests_total = Counter::new(
             "realtime_api_requests_total",
@@ -80,6 +173,7 @@ impl Metrics {
         // Register all metrics
         registry.register(Box::new(events_published_total.clone()))?;
         registry.register(Box::new(events_delivered_total.clone()))?;
+        registry.register(Box::new(events_deduplicated_total.clone()))?;
         registry.register(Box::new(websocket_connections_active.clone()))?;
         registry.register(Box::new(sse_connections_active.clone()))?;
         registry.register(Box::new(api_requests_total.clone()))?;
@@ -88,10 +182,52 @@ impl Metrics {
         registry.register(Box::new(auth_operations_total.clone()))?;
         registry.register(Box::new(errors_total.clone()))?;
 
+        // Shared with the global ordering verifier used on delivery paths
+        let event_ordering_inversions_total = crate::ordering::ordering_verifier()
+            .inversions_counter()
+            .clone();
+        registry.register(Box::new(event_ordering_inversions_total.clone()))?;
+        registry.register(Box::new(
+            crate::drain::closed_connections_pruned_counter().clone(),
+        ))?;
+        registry.register(Box::new(
+            crate::nats::nats_reconnect_attempts_counter().clone(),
+        ))?;
+        registry.register(Box::new(
+            crate::backpressure::lagged_events_dropped_counter().clone(),
+        ))?;
+        registry.register(Box::new(
+            crate::idle::idle_connections_closed_counter().clone(),
+        ))?;
+        registry.register(Box::new(
+            crate::ws_rate_limit::inbound_messages_throttled_counter().clone(),
+        ))?;
+        registry.register(Box::new(
+            crate::graphql_loaders::loader_batches_counter().clone(),
+        ))?;
+        registry.register(Box::new(
+            crate::db_retry::database_retries_counter().clone(),
+        ))?;
+        registry.register(Box::new(
+            crate::rate_limit::rate_limit_fail_open_counter().clone(),
+        ))?;
+        registry.register(Box::new(
+            crate::schema_validator::schema_violations_counter().clone(),
+        ))?;
+        registry.register(Box::new(
+            crate::circuit_breaker::circuit_breaker_state_gauge().clone(),
+        ))?;
+        registry.register(Box::new(
+            crate::webhooks::webhook_events_dropped_counter().clone(),
+        ))?;
+        registry.register(Box::new(publish_latency_histogram().clone()))?;
+        registry.register(Box::new(payload_size_histogram().clone()))?;
+
         Ok(Self {
             registry,
             events_published_total,
             events_delivered_total,
+            events_deduplicated_total,
             websocket_connections_active,
             sse_connections_active,
             api_requests_total,
@@ -99,6 +235,8 @@ impl Metrics {
             billing_operations_total,
             auth_operations_total,
             errors_total,
+            event_ordering_inversions_total,
+            sla_samples: Arc::new(Mutex::new(VecDeque::new())),
         })
     }
     
@@ -117,6 +255,16 @@ impl Metrics {
         );
     }
     
+    /// Record JetStream publish latency for a tenant tier (see `tenant_tier`)
+    pub fn record_publish_latency(&self, tier: &str, topic: &str, seconds: f64) {
+        record_publish_latency(tier, topic, seconds);
+    }
+    
+    /// Record a published payload's serialized size for a tenant tier
+    pub fn record_payload_size(&self, tier: &str, bytes: usize) {
+        record_payload_size(tier, bytes);
+    }
+    
     /// Record an event delivery
     pub fn record_event_delivered(&self, tenant_id: &str, connection_type: &str) {
         self.events_delivered_total.inc();
@@ -127,22 +275,14 @@ impl Metrics {
         );
     }
     
-    /// Record WebSocket connection change
-    pub fn record_websocket_connection_change(&self, delta: i64) {
-        if delta > 0 {
-            self.websocket_connections_active.add(delta as f64);
-        } else {
-            self.websocket_connections_active.sub((-delta) as f64);
-        }
-    }
-    
-    /// Record SSE connection change
-    pub fn record_sse_connection_change(&self, delta: i64) {
-        if delta > 0 {
-            self.sse_connections_active.add(delta as f64);
-        } else {
-            self.sse_connections_active.sub((-delta) as f64);
-        }
+    /// Record an event suppressed by the dedup window
+    pub fn record_event_deduplicated(&self, tenant_id: &str, topic: &str) {
+        self.events_deduplicated_total.inc();
+        tracing::info!(
+            tenant_id = tenant_id,
+            topic = topic,
+            "Event deduplicated"
+        );
     }
     
     /// Record API request
@@ -188,9 +328,503 @@ impl Metrics {
     }
 }
 
-/// Add correlation ID to the current span
+/// How often the SLA counters are sampled; the resolution of SLA windows
+pub const SLA_SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Longest window an SLA summary can cover
+pub const MAX_SLA_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Window an SLA summary covers when none is requested
+pub const DEFAULT_SLA_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// Compact SLA / error-budget view derived from the Prometheus registry,
+/// covering the requested window
+#[derive(Debug, Clone, Serialize, utoipa::ToSchema)]
+pub struct SlaSummary {
+    /// Seconds actually covered, shorter than requested while the process
+    /// hasn't been sampling for the whole window
+    pub window_seconds: f64,
+    /// API requests in the window
+    pub api_requests_total: f64,
+    /// Errors in the window
+    pub errors_total: f64,
+    pub success_rate: f64,
+    pub latency_p50_seconds: Option<f64>,
+    pub latency_p99_seconds: Option<f64>,
+    /// Events published in the window
+    pub events_published_total: f64,
+    /// Events delivered in the window
+    pub events_delivered_total: f64,
+    pub delivery_success_ratio: f64,
+}
+
+/// Cumulative values of the counters and latency histogram behind the SLA
+/// summary at one point in time
+#[derive(Debug, Clone, Default)]
+struct SlaCounters {
+    api_requests_total: f64,
+    errors_total: f64,
+    events_published_total: f64,
+    events_delivered_total: f64,
+    latency_buckets: Vec<(f64, u64)>,
+    latency_count: u64,
+}
+
+impl SlaCounters {
+    /// What was recorded between `earlier` and `self`
+    fn since(&self, earlier: &SlaCounters) -> SlaCounters {
+        let latency_buckets = self
+            .latency_buckets
+            .iter()
+            .enumerate()
+            .map(|(i, &(upper_bound, count))| {
+                let before = earlier.latency_buckets.get(i).map_or(0, |bucket| bucket.1);
+                (upper_bound, count.saturating_sub(before))
+            })
+            .collect();
+        SlaCounters {
+            api_requests_total: self.api_requests_total - earlier.api_requests_total,
+            errors_total: self.errors_total - earlier.errors_total,
+            events_published_total: self.events_published_total - earlier.events_published_total,
+            events_delivered_total: self.events_delivered_total - earlier.events_delivered_total,
+            latency_buckets,
+            latency_count: self.latency_count.saturating_sub(earlier.latency_count),
+        }
+    }
+}
+
+impl Metrics {
+    /// Read the SLA counters currently held in the registry
+    fn sla_counters(&self) -> SlaCounters {
+        let mut counters = SlaCounters::default();
+
+        for family in self.registry.gather() {
+            let Some(metric) = family.get_metric().first() else {
+                continue;
+            };
+            match family.get_name() {
+                "realtime_api_requests_total" => {
+                    counters.api_requests_total = metric.get_counter().get_value()
+                }
+                "realtime_errors_total" => counters.errors_total = metric.get_counter().get_value(),
+                "realtime_events_published_total" => {
+                    counters.events_published_total = metric.get_counter().get_value()
+                }
+                "realtime_events_delivered_total" => {
+                    counters.events_delivered_total = metric.get_counter().get_value()
+                }
+                "realtime_api_request_duration_seconds" => {
+                    let histogram = metric.get_histogram();
+                    counters.latency_count = histogram.get_sample_count();
+                    counters.latency_buckets = histogram
+                        .get_bucket()
+                        .iter()
+                        .map(|b| (b.get_upper_bound(), b.get_cumulative_count()))
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+        counters
+    }
+
+    /// Record the current SLA counters, forgetting readings older than
+    /// `MAX_SLA_WINDOW`. Called every `SLA_SAMPLE_INTERVAL`.
+    pub fn sample_sla(&self) {
+        let now = tokio::time::Instant::now();
+        let counters = self.sla_counters();
+        let mut samples = self.sla_samples.lock().unwrap();
+        // Keep one reading at least as old as the longest window
+        while samples.len() > 1 && now.duration_since(samples[1].0) >= MAX_SLA_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back((now, counters));
+    }
+
+    /// Compute the SLA summary for what was recorded over the last `window`,
+    /// measured from the newest sample taken at least `window` ago. Without
+    /// one, the window reaches back to the oldest sample, or to process start.
+    pub fn sla_summary(&self, window: Duration) -> SlaSummary {
+        let now = tokio::time::Instant::now();
+        let current = self.sla_counters();
+        let (since, baseline) = {
+            let samples = self.sla_samples.lock().unwrap();
+            samples
+                .iter()
+                .rev()
+                .find(|(taken, _)| now.duration_since(*taken) >= window)
+                .or_else(|| samples.front())
+                .map(|(taken, counters)| (Some(*taken), counters.clone()))
+                .unwrap_or_default()
+        };
+        let window_seconds = since.map_or(window, |taken| now.duration_since(taken));
+        let counters = current.since(&baseline);
+
+        let success_rate = if counters.api_requests_total > 0.0 {
+            ((counters.api_requests_total - counters.errors_total) / counters.api_requests_total)
+                .clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+
+        // Fan-out can deliver one event to many clients, so cap the ratio at 1.0
+        let delivery_success_ratio = if counters.events_published_total > 0.0 {
+            (counters.events_delivered_total / counters.events_published_total).min(1.0)
+        } else {
+            1.0
+        };
+
+        SlaSummary {
+            window_seconds: window_seconds.as_secs_f64(),
+            api_requests_total: counters.api_requests_total,
+            errors_total: counters.errors_total,
+            success_rate,
+            latency_p50_seconds: histogram_quantile(
+                0.50,
+                &counters.latency_buckets,
+                counters.latency_count,
+            ),
+            latency_p99_seconds: histogram_quantile(
+                0.99,
+                &counters.latency_buckets,
+                counters.latency_count,
+            ),
+            events_published_total: counters.events_published_total,
+            events_delivered_total: counters.events_delivered_total,
+            delivery_success_ratio,
+        }
+    }
+}
+
+/// Estimate a quantile from cumulative histogram buckets, interpolating linearly
+/// within the bucket the same way Prometheus' `histogram_quantile` does
+pub fn histogram_quantile(quantile: f64, buckets: &[(f64, u64)], total: u64) -> Option<f64> {
+    if total == 0 || buckets.is_empty() {
+        return None;
+    }
+
+    let rank = quantile * total as f64;
+    let mut lower_bound = 0.0;
+    let mut lower_count = 0u64;
+
+    for &(upper_bound, cumulative_count) in buckets {
+        if cumulative_count as f64 >= rank {
+            let in_bucket = (cumulative_count - lower_count) as f64;
+            if in_bucket == 0.0 {
+                return Some(upper_bound);
+            }
+            let fraction = (rank - lower_count as f64) / in_bucket;
+            return Some(lower_bound + (upper_bound - lower_bound) * fraction);
+        }
+        lower_bound = upper_bound;
+        lower_count = cumulative_count;
+    }
+
+    // The quantile falls in the implicit +Inf bucket; report the highest finite bound
+    Some(lower_bound)
+}
+
+/// Runtime log level overrides keyed by tenant id
+#[derive(Debug, Clone, Default)]
+pub struct TenantLogLevels {
+    levels: Arc<RwLock<HashMap<String, LevelFilter>>>,
+}
+
+impl TenantLogLevels {
+    /// Raise (or lower) the log level for a single tenant
+    pub fn set(&self, tenant_id: &str, level: LevelFilter) {
+        self.levels
+            .write()
+            .unwrap()
+            .insert(tenant_id.to_string(), level);
+        // Callsites cache whether they are enabled; recompute for the new levels
+        tracing::callsite::rebuild_interest_cache();
+    }
+
+    /// Remove a tenant's override; returns whether one existed
+    pub fn clear(&self, tenant_id: &str) -> bool {
+        let existed = self.levels.write().unwrap().remove(tenant_id).is_some();
+        if existed {
+            tracing::callsite::rebuild_interest_cache();
+        }
+        existed
+    }
+
+    /// Get the override for a tenant, if any
+    pub fn get(&self, tenant_id: &str) -> Option<LevelFilter> {
+        self.levels.read().unwrap().get(tenant_id).copied()
+    }
+
+    /// The most verbose level any tenant's override enables
+    fn max_level(&self) -> Option<LevelFilter> {
+        self.levels.read().unwrap().values().copied().max()
+    }
+}
+
+// Global tenant log level overrides, shared by the log filter and admin endpoints
+lazy_static::lazy_static! {
+    static ref TENANT_LOG_LEVELS: TenantLogLevels = TenantLogLevels::default();
+}
+
+/// Get the global tenant log level overrides
+pub fn tenant_log_levels() -> &'static TenantLogLevels {
+    &TENANT_LOG_LEVELS
+}
+
+/// Tenant id recorded on a span, stored in the span's extensions
+struct SpanTenantId(String);
+
+/// Extracts a `tenant_id` field from span attributes or event fields
+#[derive(Default)]
+struct TenantIdVisitor(Option<String>);
+
+impl Visit for TenantIdVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "tenant_id" {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "tenant_id" && self.0.is_none() {
+            self.0 = Some(format!("{:?}", value).trim_matches('"').to_string());
+        }
+    }
+}
+
+/// Log filter that applies the base `EnvFilter` and additionally lets through
+/// more verbose events for tenants with an override, keyed on the `tenant_id`
+/// field of the event or any enclosing span
+pub struct TenantLogFilter {
+    base: EnvFilter,
+    overrides: TenantLogLevels,
+}
+
+impl TenantLogFilter {
+    pub fn new(base: EnvFilter, overrides: TenantLogLevels) -> Self {
+        Self { base, overrides }
+    }
+
+    fn override_allows(&self, tenant_id: &str, level: &Level) -> bool {
+        self.overrides
+            .get(tenant_id)
+            .map(|filter| *level <= filter)
+            .unwrap_or(false)
+    }
+
+    // Whether some tenant's override could enable what the base filter doesn't:
+    // events up to the most verbose override level, and the spans at those
+    // levels that record a tenant id for them
+    fn overridable(&self, meta: &Metadata<'_>) -> bool {
+        let within_override = self
+            .overrides
+            .max_level()
+            .is_some_and(|max_level| *meta.level() <= max_level);
+        within_override && (meta.is_event() || meta.fields().field("tenant_id").is_some())
+    }
+}
+
+impl<S> Filter<S> for TenantLogFilter
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn enabled(&self, meta: &Metadata<'_>, ctx: &Context<'_, S>) -> bool {
+        if Filter::<S>::enabled(&self.base, meta, ctx) {
+            return true;
+        }
+        // Keep spans so their tenant ids are recorded and defer the decision
+        // for events to `event_enabled`
+        self.overridable(meta)
+    }
+
+    fn callsite_enabled(&self, meta: &'static Metadata<'static>) -> Interest {
+        // Only callsites an override could enable are decided per event; the
+        // cache is rebuilt whenever the overrides change
+        let base = Filter::<S>::callsite_enabled(&self.base, meta);
+        if !base.is_always() && self.overridable(meta) {
+            Interest::sometimes()
+        } else {
+            base
+        }
+    }
+
+    fn event_enabled(&self, event: &tracing::Event<'_>, ctx: &Context<'_, S>) -> bool {
+        if Filter::<S>::enabled(&self.base, event.metadata(), ctx) {
+            return true;
+        }
+
+        let mut visitor = TenantIdVisitor::default();
+        event.record(&mut visitor);
+
+        let tenant_id = visitor.0.or_else(|| {
+            ctx.event_scope(event)?.find_map(|span| {
+                span.extensions()
+                    .get::<SpanTenantId>()
+                    .map(|tenant| tenant.0.clone())
+            })
+        });
+
+        tenant_id
+            .map(|tenant_id| self.override_allows(&tenant_id, event.metadata().level()))
+            .unwrap_or(false)
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        let base = Filter::<S>::max_level_hint(&self.base)?;
+        Some(base.max(self.overrides.max_level().unwrap_or(LevelFilter::OFF)))
+    }
+
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let mut visitor = TenantIdVisitor::default();
+        attrs.record(&mut visitor);
+        if let (Some(tenant_id), Some(span)) = (visitor.0, ctx.span(id)) {
+            span.extensions_mut().insert(SpanTenantId(tenant_id));
+        }
+        Filter::<S>::on_new_span(&self.base, attrs, id, ctx);
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        let mut visitor = TenantIdVisitor::default();
+        values.record(&mut visitor);
+        if let (Some(tenant_id), Some(span)) = (visitor.0, ctx.span(id)) {
+            span.extensions_mut().replace(SpanTenantId(tenant_id));
+        }
+        Filter::<S>::on_record(&self.base, id, values, ctx);
+    }
+
+    fn on_enter(&self, id: &span::Id, ctx: Context<'_, S>) {
+        Filter::<S>::on_enter(&self.base, id, ctx);
+    }
+
+    fn on_exit(&self, id: &span::Id, ctx: Context<'_, S>) {
+        Filter::<S>::on_exit(&self.base, id, ctx);
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        Filter::<S>::on_close(&self.base, id, ctx);
+    }
+}
+
+/// Build the base log filter from `RUST_LOG` or the configured level
+fn env_filter(config: &Config) -> EnvFilter {
+    EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(&config.observability.log_level))
+}
+
+/// Span fields lifted to the top level of JSON log lines
+const JSON_SPAN_FIELDS: [&str; 2] = ["correlation_id", "tenant_id"];
+
+/// Collects event fields into a JSON object
+#[derive(Default)]
+struct JsonFieldVisitor(serde_json::Map<String, serde_json::Value>);
+
+impl Visit for JsonFieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value).into());
+    }
+}
+
+/// Writes each event as a single JSON object with `timestamp`, `level`,
+/// `target`, `message` and the event's fields, plus the `correlation_id` and
+/// `tenant_id` of the innermost enclosing span that recorded them. Span fields
+/// are read back from the `JsonFields` formatter, so the layer must use it.
+struct JsonLogFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonLogFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: format::Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let meta = event.metadata();
+        let mut line = serde_json::Map::new();
+        line.insert(
+            "timestamp".to_string(),
+            chrono::Utc::now()
+                .to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+                .into(),
+        );
+        line.insert("level".to_string(), meta.level().as_str().into());
+        line.insert("target".to_string(), meta.target().into());
+
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                let extensions = span.extensions();
+                let Some(fields) = extensions.get::<FormattedFields<N>>() else {
+                    continue;
+                };
+                let Ok(serde_json::Value::Object(fields)) =
+                    serde_json::from_str::<serde_json::Value>(fields)
+                else {
+                    continue;
+                };
+                for key in JSON_SPAN_FIELDS {
+                    if let Some(value) = fields.get(key) {
+                        line.insert(key.to_string(), value.clone());
+                    }
+                }
+            }
+        }
+
+        // Event fields take precedence over span fields of the same name
+        let mut visitor = JsonFieldVisitor::default();
+        event.record(&mut visitor);
+        line.extend(visitor.0);
+
+        writeln!(writer, "{}", serde_json::Value::Object(line))
+    }
+}
+
+/// Build the stdout log layer in the configured format, writing to `writer`
+pub fn log_layer<S, W>(format: LogFormat, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a> + 'static,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    match format {
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .fmt_fields(JsonFields::new())
+            .event_format(JsonLogFormat)
+            .with_writer(writer)
+            .boxed(),
+        LogFormat::Pretty => tracing_subscriber::fmt::layer()
+            .pretty()
+            .with_writer(writer)
+            .boxed(),
+    }
+}
+
+/// Add correlation ID to the current span. Inside an HTTP request this is
+/// the request's id, so logs and error bodies agree.
 pub fn add_correlation_id() -> String {
-    let correlation_id = Uuid::new_v4().to_string();
+    let correlation_id =
+        current_request_id().unwrap_or_else(|| Uuid::new_v4().to_string());
     Span::current().record("correlation_id", &correlation_id);
     correlation_id
 }
@@ -206,19 +840,12 @@ pub async fn init_observability(config: &Config) -> Result<Metrics> {
         opentelemetry::KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
     ]);
 
-    // Set up the tracing subscriber with multiple layers
-    let env_filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new(&config.observability.log_level));
-
-    let subscriber = tracing_subscriber::registry().with(env_filter).with(
-        tracing_subscriber::fmt::layer()
-            .with_target(false)
-            .with_thread_ids(true)
-            .with_file(true)
-            .with_line_number(true)
-            .json()
-            .with_current_span(true)
-            .with_span_list(true),
+    // Set up the tracing subscriber with multiple layers; log output honours
+    // per-tenant level overrides on top of the base filter
+    let subscriber = tracing_subscriber::registry().with(
+        log_layer(config.observability.log_format, std::io::stdout).with_filter(
+            TenantLogFilter::new(env_filter(config), tenant_log_levels().clone()),
+        ),
     );
 
     // Add OpenTelemetry tracing if endpoint is configured
@@ -238,7 +865,9 @@ pub async fn init_observability(config: &Config) -> Result<Metrics> {
             .with_trace_config(opentelemetry_sdk::trace::config().with_resource(resource))
             .install_batch(runtime::Tokio)?;
 
-        let telemetry_layer = tracing_opentelemetry::layer().with_tracer(tracer);
+        let telemetry_layer = tracing_opentelemetry::layer()
+            .with_tracer(tracer)
+            .with_filter(env_filter(config));
 
         subscriber.with(telemetry_layer).try_init()?;
     } else {
@@ -258,16 +887,10 @@ pub async fn init_tracing(config: &Config) -> Result<()> {
     ]);
 
     // Set up the tracing subscriber with multiple layers
-    let env_filter = EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| EnvFilter::new(&config.observability.log_level));
-
-    let subscriber = tracing_subscriber::registry().with(env_filter).with(
-        tracing_subscriber::fmt::layer()
-            .with_target(false)
-            .with_thread_ids(true)
-            .with_file(true)
-            .with_line_number(true)
-            .json(),
+    let subscriber = tracing_subscriber::registry().with(
+        log_layer(config.observability.log_format, std::io::stdout).with_filter(
+            TenantLogFilter::new(env_filter(config), tenant_log_levels().clone()),
+        ),
     );
 
     // Add OpenTelemetry tracing if endpoint is configured
@@ -287,7 +910,9 @@ pub async fn init_tracing(config: &Config) -> Result<()> {
             .with_trace_config(opentelemetry_sdk::trace::config().with_resource(resource))
             .install_batch(runtime::Tokio)?;
 
-        let telemetry_layer = tracing_opentelemetry::layer().with_tracer(tracer);
+        let telemetry_layer = tracing_opentelemetry::layer()
+            .with_tracer(tracer)
+            .with_filter(env_filter(config));
 
         subscriber.with(telemetry_layer).try_init()?;
     } else {
@@ -302,3 +927,265 @@ pub async fn init_tracing(config: &Config) -> Result<()> {
 pub fn shutdown_tracing() {
     global::shutdown_tracer_provider();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx_eq(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_sla_summary_from_seeded_metrics() {
+        let metrics = Metrics::new().unwrap();
+
+        // 90 fast requests and 10 slow ones
+        for _ in 0..90 {
+            metrics.record_api_request("POST", "/events", 0.004);
+        }
+        for _ in 0..10 {
+            metrics.record_api_request("POST", "/events", 0.2);
+        }
+        for _ in 0..5 {
+            metrics.record_error("publish_error", "test");
+        }
+        for _ in 0..10 {
+            metrics.record_event_published("tenant", "topic");
+        }
+        for _ in 0..8 {
+            metrics.record_event_delivered("tenant", "websocket");
+        }
+
+        let summary = metrics.sla_summary(DEFAULT_SLA_WINDOW);
+
+        assert!(approx_eq(summary.api_requests_total, 100.0));
+        assert!(approx_eq(summary.errors_total, 5.0));
+        assert!(approx_eq(summary.success_rate, 0.95));
+        assert!(approx_eq(summary.delivery_success_ratio, 0.8));
+        // p50 lands in the 0.005 bucket: 0.005 * 50/90
+        assert!(approx_eq(summary.latency_p50_seconds.unwrap(), 0.005 * 50.0 / 90.0));
+        // p99 lands in the (0.1, 0.25] bucket: 0.1 + 0.15 * 9/10
+        assert!(approx_eq(summary.latency_p99_seconds.unwrap(), 0.235));
+    }
+
+    #[test]
+    fn test_sla_summary_without_traffic() {
+        let metrics = Metrics::new().unwrap();
+        let summary = metrics.sla_summary(DEFAULT_SLA_WINDOW);
+
+        assert!(approx_eq(summary.success_rate, 1.0));
+        assert!(approx_eq(summary.delivery_success_ratio, 1.0));
+        assert!(summary.latency_p50_seconds.is_none());
+        assert!(summary.latency_p99_seconds.is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_sla_summary_covers_only_the_window() {
+        let metrics = Metrics::new().unwrap();
+
+        // A bad stretch that has left the window by the time of the summary
+        for _ in 0..100 {
+            metrics.record_api_request("POST", "/events", 0.2);
+        }
+        for _ in 0..50 {
+            metrics.record_error("publish_error", "test");
+        }
+        metrics.sample_sla();
+        tokio::time::advance(Duration::from_secs(10 * 60)).await;
+        metrics.sample_sla();
+
+        for _ in 0..10 {
+            metrics.record_api_request("POST", "/events", 0.004);
+        }
+        metrics.record_error("publish_error", "test");
+        tokio::time::advance(SLA_SAMPLE_INTERVAL).await;
+
+        let summary = metrics.sla_summary(DEFAULT_SLA_WINDOW);
+        assert!(approx_eq(summary.api_requests_total, 10.0));
+        assert!(approx_eq(summary.errors_total, 1.0));
+        assert!(approx_eq(summary.success_rate, 0.9));
+        assert!(summary.latency_p99_seconds.unwrap() <= 0.005);
+        assert!(approx_eq(summary.window_seconds, 10.0 * 60.0 + 15.0));
+
+        // A window reaching past the oldest sample covers all of it
+        let summary = metrics.sla_summary(MAX_SLA_WINDOW);
+        assert!(approx_eq(summary.api_requests_total, 10.0));
+        assert!(approx_eq(summary.window_seconds, 10.0 * 60.0 + 15.0));
+    }
+
+    #[test]
+    fn test_delivery_ratio_capped_for_fan_out() {
+        let metrics = Metrics::new().unwrap();
+        metrics.record_event_published("tenant", "topic");
+        for _ in 0..3 {
+            metrics.record_event_delivered("tenant", "sse");
+        }
+
+        assert!(approx_eq(
+            metrics
+                .sla_summary(DEFAULT_SLA_WINDOW)
+                .delivery_success_ratio,
+            1.0
+        ));
+    }
+
+    #[test]
+    fn test_publish_histograms_are_exported_by_tier() {
+        let metrics = Metrics::new().unwrap();
+        let tier = tenant_tier(&BillingPlan::Enterprise { unlimited: true });
+        metrics.record_publish_latency(tier, "orders.created", 0.003);
+        metrics.record_payload_size(tier, 600);
+
+        let families = metrics.registry.gather();
+        for name in ["realtime_publish_latency_seconds", "realtime_event_payload_size_bytes"] {
+            let family = families
+                .iter()
+                .find(|family| family.get_name() == name)
+                .unwrap_or_else(|| panic!("{} should be registered", name));
+            let sample = family
+                .get_metric()
+                .iter()
+                .find(|metric| metric.get_label()[0].get_value() == "enterprise")
+                .expect("Enterprise tier should be recorded");
+            assert!(sample.get_histogram().get_sample_count() >= 1);
+        }
+    }
+
+    #[test]
+    fn test_histogram_quantile_in_overflow_bucket() {
+        let buckets = vec![(0.1, 0), (1.0, 1)];
+        assert_eq!(histogram_quantile(0.99, &buckets, 10), Some(1.0));
+    }
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl CapturedLogs {
+        fn output(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_tenant_log_override_elevates_only_target_tenant() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let overrides = TenantLogLevels::default();
+        overrides.set("tenant_a", LevelFilter::DEBUG);
+
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(move || writer.clone())
+                .with_filter(TenantLogFilter::new(EnvFilter::new("info"), overrides.clone())),
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info_span!("request", tenant_id = "tenant_a")
+                .in_scope(|| tracing::debug!("span debug for tenant a"));
+            tracing::info_span!("request", tenant_id = "tenant_b")
+                .in_scope(|| tracing::debug!("span debug for tenant b"));
+            tracing::debug!(tenant_id = "tenant_a", "field debug for tenant a");
+            tracing::debug!("debug without tenant");
+            tracing::info!("info without tenant");
+
+            overrides.clear("tenant_a");
+            tracing::debug!(tenant_id = "tenant_a", "debug after override cleared");
+        });
+
+        let output = logs.output();
+        assert!(output.contains("span debug for tenant a"));
+        assert!(output.contains("field debug for tenant a"));
+        assert!(output.contains("info without tenant"));
+        assert!(!output.contains("span debug for tenant b"));
+        assert!(!output.contains("debug without tenant"));
+        assert!(!output.contains("debug after override cleared"));
+    }
+
+    #[test]
+    fn test_override_set_after_a_callsite_was_cached_takes_effect() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let overrides = TenantLogLevels::default();
+        let filter = TenantLogFilter::new(EnvFilter::new("info"), overrides.clone());
+        assert_eq!(
+            Filter::<tracing_subscriber::Registry>::max_level_hint(&filter),
+            Some(LevelFilter::INFO)
+        );
+
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(move || writer.clone())
+                .with_filter(filter),
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            let log = |attempt: u32| {
+                tracing::debug!(tenant_id = "tenant_c", attempt, "debug for tenant c")
+            };
+            log(1);
+            overrides.set("tenant_c", LevelFilter::DEBUG);
+            log(2);
+            overrides.clear("tenant_c");
+            log(3);
+        });
+
+        let output = logs.output();
+        assert_eq!(
+            output.matches("debug for tenant c").count(),
+            1,
+            "{}",
+            output
+        );
+        assert!(output.contains("attempt=2"));
+    }
+
+    #[test]
+    fn test_json_log_lines_carry_span_correlation_and_tenant_ids() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::registry()
+            .with(log_layer(LogFormat::Json, move || writer.clone()));
+
+        let correlation_id = tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!(
+                "tenant_request",
+                tenant_id = "tenant_a",
+                correlation_id = tracing::field::Empty
+            );
+            let _guard = span.enter();
+            let correlation_id = add_correlation_id();
+            tracing::info!(topic = "orders", "event published");
+            correlation_id
+        });
+
+        let output = logs.output();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 1);
+
+        let line: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        for key in ["timestamp", "level", "target", "correlation_id", "tenant_id", "message"] {
+            assert!(line.get(key).is_some(), "missing {} in {}", key, line);
+        }
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["target"], module_path!());
+        assert_eq!(line["message"], "event published");
+        assert_eq!(line["topic"], "orders");
+        assert_eq!(line["tenant_id"], "tenant_a");
+        assert_eq!(line["correlation_id"], correlation_id.as_str());
+        assert!(chrono::DateTime::parse_from_rfc3339(line["timestamp"].as_str().unwrap()).is_ok());
+    }
+}
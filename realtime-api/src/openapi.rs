@@ -0,0 +1,265 @@
+use utoipa::openapi::response::ResponseBuilder;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::api::{
+    ApiKeySummary, AuditLogPageResponse, BatchEventResult, CreateApiKeyRequest,
+    CreateApiKeyResponse, CreateTenantRequest, CreateTenantResponse, CreateWebhookRequest,
+    CreateWebhookResponse, DeadLetterPageResponse, DependencyHealth, ErrorDetail, ErrorResponse,
+    EventPageResponse, KeyUsageResponse, OnboardRequest, OnboardResponse, PlanLimitWarning,
+    PublishBatchRequest, PublishBatchResponse, PublishEventRequest, PublishEventResponse,
+    RegisterSchemaResponse, ReplayDeadLetterResponse, RotateApiKeyResponse, TenantLogLevelRequest,
+    TenantResponse, TenantSuspensionRequest, TopicSchemaResponse, UpdateProjectRequest,
+    UpdateTenantPlanRequest, UpdateTenantPlanResponse, UpdateUserRoleRequest, UsageExportFormat,
+    UsageReportResponse, WebhookListResponse,
+};
+use crate::cloudevents::DeliveryFormat;
+use crate::models::{
+    AuditLog, BillingPlan, Event, Project, ProjectLimits, ProjectLimitsUpdate, ProjectSettings,
+    UserRole, Webhook, WebhookUpdate,
+};
+use crate::nats::{DeadLetter, DeadLetterKind};
+use crate::observability::SlaSummary;
+use crate::schema_validator::{CompatibilityMode, SchemaMode, ValidationMode};
+use crate::sse::SSESubscribeRequest;
+
+/// Path the generated spec is served from
+pub const OPENAPI_JSON_PATH: &str = "/openapi.json";
+
+/// Path Swagger UI is served from
+pub const DOCS_PATH: &str = "/docs";
+
+/// Name of the bearer security scheme carrying an API key or JWT
+pub const SECURITY_SCHEME: &str = "api_key";
+
+/// Paths served without the API key middleware; aliases inherit this from the
+/// path they copy
+const PUBLIC_PATHS: [&str; 6] = [
+    "/graphql/ws",
+    "/health",
+    "/health/live",
+    "/health/ready",
+    "/metrics",
+    "/webhooks/stripe",
+];
+
+/// Routes mounted on the same handler as a documented path, as (alias, documented path)
+const ROUTE_ALIASES: [(&str, &str); 3] = [
+    (
+        "/billing/suspend/{tenant_id}",
+        "/admin/tenants/{tenant_id}/suspend",
+    ),
+    (
+        "/billing/unsuspend/{tenant_id}",
+        "/admin/tenants/{tenant_id}/unsuspend",
+    ),
+    ("/billing/stripe-webhook", "/webhooks/stripe"),
+];
+
+/// OpenAPI 3.0 description of the REST API
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Realtime API",
+        description = "Multi-tenant event publishing and realtime delivery over WebSocket and SSE"
+    ),
+    paths(
+        crate::api::publish_event,
+        crate::api::list_events,
+        crate::api::publish_events_batch,
+        crate::api::replay_events,
+        crate::api::get_event,
+        crate::api::create_tenant,
+        crate::api::onboard_tenant,
+        crate::api::create_api_key,
+        crate::api::revoke_api_key,
+        crate::api::rotate_api_key,
+        crate::api::get_api_key_usage,
+        crate::api::update_project,
+        crate::api::archive_project,
+        crate::api::list_project_api_keys,
+        crate::api::get_sla_summary,
+        crate::api::list_audit_logs,
+        crate::api::export_usage,
+        crate::api::list_dead_letters,
+        crate::api::replay_dead_letter,
+        crate::api::create_webhook,
+        crate::api::list_webhooks,
+        crate::api::get_webhook,
+        crate::api::update_webhook,
+        crate::api::delete_webhook,
+        crate::api::register_topic_schema,
+        crate::api::get_topic_schema,
+        crate::api::get_tenant,
+        crate::api::suspend_tenant,
+        crate::api::unsuspend_tenant,
+        crate::api::update_tenant_plan,
+        crate::api::set_tenant_log_level,
+        crate::api::clear_tenant_log_level,
+        crate::api::get_usage_report,
+        crate::api::get_usage_limits,
+        crate::api::update_user_role,
+        crate::api::list_tenant_users,
+        crate::api::deactivate_user,
+        crate::api::metrics_handler,
+        crate::api::handle_stripe_webhook,
+        crate::api::health_check,
+        crate::api::liveness_check,
+        crate::api::readiness_check,
+        crate::routes::websocket_handler,
+        crate::sse::sse_handler,
+        crate::sse::sse_subscribe_handler,
+        crate::graphql::graphql_playground,
+        crate::graphql::graphql_subscription_handler,
+    ),
+    components(schemas(
+        ApiKeySummary,
+        AuditLog,
+        AuditLogPageResponse,
+        BatchEventResult,
+        BillingPlan,
+        CompatibilityMode,
+        CreateApiKeyRequest,
+        CreateApiKeyResponse,
+        CreateTenantRequest,
+        CreateTenantResponse,
+        CreateWebhookRequest,
+        CreateWebhookResponse,
+        DeadLetter,
+        DeadLetterKind,
+        DeadLetterPageResponse,
+        DeliveryFormat,
+        DependencyHealth,
+        ErrorDetail,
+        ErrorResponse,
+        Event,
+        EventPageResponse,
+        KeyUsageResponse,
+        OnboardRequest,
+        OnboardResponse,
+        PlanLimitWarning,
+        Project,
+        ProjectLimits,
+        ProjectLimitsUpdate,
+        ProjectSettings,
+        PublishBatchRequest,
+        PublishBatchResponse,
+        PublishEventRequest,
+        PublishEventResponse,
+        RegisterSchemaResponse,
+        ReplayDeadLetterResponse,
+        RotateApiKeyResponse,
+        SchemaMode,
+        SlaSummary,
+        SSESubscribeRequest,
+        TenantLogLevelRequest,
+        TenantResponse,
+        TenantSuspensionRequest,
+        TopicSchemaResponse,
+        UpdateProjectRequest,
+        UpdateTenantPlanRequest,
+        UpdateTenantPlanResponse,
+        UpdateUserRoleRequest,
+        UsageExportFormat,
+        UsageReportResponse,
+        UserRole,
+        ValidationMode,
+        Webhook,
+        WebhookListResponse,
+        WebhookUpdate,
+    )),
+    modifiers(&ApiKeySecurity, &RouteAliases),
+    security(("api_key" = [])),
+    tags(
+        (name = "events", description = "Publish, list and replay events"),
+        (name = "streaming", description = "WebSocket and Server-Sent Events delivery"),
+        (name = "admin", description = "Tenants, projects, API keys, users and operations"),
+        (name = "webhooks", description = "Outbound webhook subscriptions"),
+        (name = "schemas", description = "Per-topic JSON schema registry"),
+        (name = "billing", description = "Usage, limits and Stripe billing"),
+        (name = "health", description = "Probes and metrics"),
+        (name = "graphql", description = "GraphQL tooling"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the bearer scheme, opts the public paths out of it and documents
+/// the responses the API key middleware returns before any handler runs
+struct ApiKeySecurity;
+
+impl Modify for ApiKeySecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            SECURITY_SCHEME,
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("API key")
+                    .description(Some("An API key, or a JWT issued for a user"))
+                    .build(),
+            ),
+        );
+
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            let public = PUBLIC_PATHS.contains(&path.as_str());
+            for operation in item.operations.values_mut() {
+                if public {
+                    operation.security = Some(Vec::new());
+                    continue;
+                }
+                let responses = &mut operation.responses.responses;
+                responses.entry("401".to_string()).or_insert_with(|| {
+                    ResponseBuilder::new()
+                        .description("Missing or invalid API key or JWT")
+                        .build()
+                        .into()
+                });
+                responses.entry("429".to_string()).or_insert_with(|| {
+                    ResponseBuilder::new()
+                        .description("Rate limit exceeded; see the Retry-After header")
+                        .build()
+                        .into()
+                });
+                responses.entry("503".to_string()).or_insert_with(|| {
+                    ResponseBuilder::new()
+                        .description("Service overloaded; see the Retry-After header")
+                        .build()
+                        .into()
+                });
+            }
+        }
+    }
+}
+
+/// Copies each documented path to its alias route, prefixing the operation ids
+/// with the alias's first segment so they stay unique
+struct RouteAliases;
+
+impl Modify for RouteAliases {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        for (alias, documented) in ROUTE_ALIASES {
+            let prefix = alias
+                .trim_start_matches('/')
+                .split('/')
+                .next()
+                .unwrap_or_default();
+            let Some(mut item) = openapi.paths.paths.get(documented).cloned() else {
+                continue;
+            };
+            for operation in item.operations.values_mut() {
+                operation.operation_id = operation
+                    .operation_id
+                    .as_ref()
+                    .map(|id| format!("{}_{}", prefix, id));
+            }
+            openapi.paths.paths.insert(alias.to_string(), item);
+        }
+    }
+}
+
+/// Swagger UI at `/docs`, backed by the spec served at `/openapi.json`
+pub fn swagger_ui() -> SwaggerUi {
+    SwaggerUi::new(DOCS_PATH).url(OPENAPI_JSON_PATH, ApiDoc::openapi())
+}
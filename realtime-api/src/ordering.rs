@@ -0,0 +1,156 @@
+use prometheus::Counter;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tracing::warn;
+
+/// Debug-time checker that sequenced events reach each connection in
+/// non-decreasing sequence order
+#[derive(Debug)]
+pub struct OrderingVerifier {
+    enabled: AtomicBool,
+    // Highest sequence delivered per connection
+    last_sequence: Mutex<HashMap<String, u64>>,
+    inversions: Counter,
+}
+
+impl Default for OrderingVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderingVerifier {
+    /// Create a disabled verifier
+    pub fn new() -> Self {
+        let inversions = Counter::new(
+            "realtime_event_ordering_inversions_total",
+            "Total number of out-of-order event deliveries detected per connection",
+        )
+        .expect("valid metric definition");
+
+        Self {
+            enabled: AtomicBool::new(false),
+            last_sequence: Mutex::new(HashMap::new()),
+            inversions,
+        }
+    }
+
+    /// Turn verification on or off
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.last_sequence.lock().unwrap().clear();
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Record a delivery to a connection; returns false if it was out of order
+    pub fn observe(&self, connection_id: &str, sequence: u64) -> bool {
+        if !self.is_enabled() {
+            return true;
+        }
+
+        let mut last_sequence = self.last_sequence.lock().unwrap();
+        match last_sequence.get(connection_id) {
+            Some(&previous) if sequence < previous => {
+                self.inversions.inc();
+                warn!(
+                    connection_id = connection_id,
+                    sequence = sequence,
+                    previous = previous,
+                    "Out-of-order event delivery detected"
+                );
+                false
+            }
+            _ => {
+                last_sequence.insert(connection_id.to_string(), sequence);
+                true
+            }
+        }
+    }
+
+    /// Drop tracking state for a closed connection
+    pub fn forget(&self, connection_id: &str) {
+        self.last_sequence.lock().unwrap().remove(connection_id);
+    }
+
+    /// Number of inversions detected so far
+    pub fn inversions(&self) -> u64 {
+        self.inversions.get() as u64
+    }
+
+    /// Counter backing the inversion metric, for registration with a registry
+    pub fn inversions_counter(&self) -> &Counter {
+        &self.inversions
+    }
+}
+
+// Global verifier shared by the WebSocket and SSE delivery paths
+lazy_static::lazy_static! {
+    static ref ORDERING_VERIFIER: OrderingVerifier = OrderingVerifier::new();
+}
+
+/// Get the global ordering verifier
+pub fn ordering_verifier() -> &'static OrderingVerifier {
+    &ORDERING_VERIFIER
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled_verifier() -> OrderingVerifier {
+        let verifier = OrderingVerifier::new();
+        verifier.set_enabled(true);
+        verifier
+    }
+
+    #[test]
+    fn test_ordered_delivery_records_no_inversions() {
+        let verifier = enabled_verifier();
+
+        for sequence in [1, 2, 2, 5, 9] {
+            assert!(verifier.observe("conn_1", sequence));
+        }
+        assert_eq!(verifier.inversions(), 0);
+    }
+
+    #[test]
+    fn test_out_of_order_delivery_increments_metric() {
+        let verifier = enabled_verifier();
+
+        assert!(verifier.observe("conn_1", 10));
+        assert!(!verifier.observe("conn_1", 7));
+        assert_eq!(verifier.inversions(), 1);
+
+        // The high-water mark is kept, so later in-order events still pass
+        assert!(verifier.observe("conn_1", 11));
+        assert_eq!(verifier.inversions(), 1);
+    }
+
+    #[test]
+    fn test_connections_are_tracked_independently() {
+        let verifier = enabled_verifier();
+
+        assert!(verifier.observe("conn_1", 10));
+        assert!(verifier.observe("conn_2", 3));
+        assert_eq!(verifier.inversions(), 0);
+
+        verifier.forget("conn_1");
+        assert!(verifier.observe("conn_1", 1));
+        assert_eq!(verifier.inversions(), 0);
+    }
+
+    #[test]
+    fn test_disabled_verifier_ignores_inversions() {
+        let verifier = OrderingVerifier::new();
+
+        assert!(verifier.observe("conn_1", 10));
+        assert!(verifier.observe("conn_1", 1));
+        assert_eq!(verifier.inversions(), 0);
+    }
+}
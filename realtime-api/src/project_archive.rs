@@ -0,0 +1,66 @@
+use anyhow::Result;
+use tracing::info;
+
+use crate::database::Database;
+
+/// Close code sent to connections of a project that has been archived
+pub const PROJECT_ARCHIVED_CLOSE_CODE: u16 = 4404;
+
+/// Close reason sent to connections of a project that has been archived
+pub const PROJECT_ARCHIVED_CLOSE_REASON: &str = "project_archived";
+
+/// Archive a tenant's project and close its WebSocket and SSE connections.
+/// Returns the ids of the closed connections, or `None` when the tenant has
+/// no such project or it was already archived.
+pub async fn archive_project(
+    database: &Database,
+    tenant_id: &str,
+    project_id: &str,
+) -> Result<Option<Vec<String>>> {
+    // Archive first so the project's keys stop authenticating before the
+    // existing connections are closed and clients try to reconnect
+    if !database.archive_project(tenant_id, project_id).await? {
+        return Ok(None);
+    }
+
+    let terminated = close_project_connections(tenant_id, project_id);
+
+    info!(
+        tenant_id = %tenant_id,
+        project_id = %project_id,
+        "Archived project and closed {} connections",
+        terminated.len()
+    );
+
+    Ok(Some(terminated))
+}
+
+/// Close every WebSocket and SSE connection of a project, returning their ids
+pub fn close_project_connections(tenant_id: &str, project_id: &str) -> Vec<String> {
+    let websockets = crate::websocket::websocket_manager();
+    let mut terminated: Vec<String> = websockets
+        .project_connections(tenant_id, project_id)
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    websockets.close_connections(
+        &terminated,
+        PROJECT_ARCHIVED_CLOSE_CODE,
+        PROJECT_ARCHIVED_CLOSE_REASON,
+    );
+
+    let sse = crate::sse::sse_manager();
+    let sse_ids: Vec<String> = sse
+        .project_connections(tenant_id, project_id)
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect();
+    sse.close_connections(
+        &sse_ids,
+        PROJECT_ARCHIVED_CLOSE_CODE,
+        PROJECT_ARCHIVED_CLOSE_REASON,
+    );
+
+    terminated.extend(sse_ids);
+    terminated
+}
@@ -0,0 +1,554 @@
+use axum::{
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use async_trait::async_trait;
+use prometheus::Counter;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{error, info};
+
+use crate::api::ErrorResponse;
+use crate::config::{RateLimitBackend, RateLimitConfig};
+
+/// Outcome of a rate limit check for a single identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    pub allowed: bool,
+    /// Requests per second allowed for the identifier
+    pub limit: u32,
+    /// Whole tokens left in the bucket after this check
+    pub remaining: u32,
+    /// Seconds until a request would be admitted again (0 when allowed)
+    pub retry_after_secs: u64,
+}
+
+impl RateLimitStatus {
+    /// Add `X-RateLimit-Limit`, `X-RateLimit-Remaining` and, when throttled,
+    /// `Retry-After` headers
+    pub fn apply_headers(&self, headers: &mut HeaderMap) {
+        headers.insert("x-ratelimit-limit", HeaderValue::from(self.limit));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from(self.remaining));
+        if !self.allowed {
+            headers.insert("retry-after", HeaderValue::from(self.retry_after_secs));
+        }
+    }
+
+    /// Build the `429 Too Many Requests` response for a throttled request
+    pub fn into_response(self) -> Response {
+        let mut response = retry_after_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            "RATE_LIMIT_EXCEEDED",
+            "Rate limit exceeded",
+            self.retry_after_secs,
+            serde_json::json!({ "limit": self.limit }),
+        );
+        self.apply_headers(response.headers_mut());
+        response
+    }
+}
+
+/// Seconds clients are asked to wait when the service is overloaded, such as
+/// when no database connection could be acquired
+pub const OVERLOADED_RETRY_AFTER_SECS: u64 = 5;
+
+/// A throttling or overload response: an `ErrorResponse` whose details
+/// carry `retry_after_seconds` next to `details`, and a matching
+/// `Retry-After` header
+pub fn retry_after_response(
+    status: StatusCode,
+    code: &str,
+    message: &str,
+    retry_after_secs: u64,
+    mut details: serde_json::Value,
+) -> Response {
+    if let Some(details) = details.as_object_mut() {
+        details.insert(
+            "retry_after_seconds".to_string(),
+            serde_json::json!(retry_after_secs),
+        );
+    }
+    let mut response = (
+        status,
+        Json(ErrorResponse::new(code, message, Some(details))),
+    )
+        .into_response();
+    response
+        .headers_mut()
+        .insert("retry-after", HeaderValue::from(retry_after_secs));
+    response
+}
+
+/// The `503 Service Unavailable` response for a request the service can't
+/// take on right now, such as when the database pool is exhausted
+pub fn overloaded_response() -> Response {
+    retry_after_response(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "SERVICE_OVERLOADED",
+        "Service temporarily overloaded",
+        OVERLOADED_RETRY_AFTER_SECS,
+        serde_json::json!({}),
+    )
+}
+
+#[derive(Debug)]
+pub(crate) struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub(crate) fn full(limit: u32, now: Instant) -> Self {
+        Self {
+            tokens: limit as f64,
+            last_refill: now,
+        }
+    }
+
+    // Refill at `limit` tokens per second, capped at a one-second burst
+    fn refill(&mut self, limit: u32, now: Instant) {
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit as f64).min(limit as f64);
+        self.last_refill = now;
+    }
+
+    /// Refill, then take one token if available
+    pub(crate) fn try_take(&mut self, limit: u32, now: Instant) -> RateLimitStatus {
+        self.refill(limit, now);
+
+        let allowed = self.tokens >= 1.0;
+        if allowed {
+            self.tokens -= 1.0;
+        }
+        self.status(allowed, limit)
+    }
+
+    fn status(&self, allowed: bool, limit: u32) -> RateLimitStatus {
+        bucket_status(self.tokens, allowed, limit)
+    }
+}
+
+// Status of a bucket holding `tokens` after a check
+fn bucket_status(tokens: f64, allowed: bool, limit: u32) -> RateLimitStatus {
+    let retry_after_secs = if allowed {
+        0
+    } else if limit == 0 {
+        1
+    } else {
+        (((1.0 - tokens) / limit as f64).ceil() as u64).max(1)
+    };
+
+    RateLimitStatus {
+        allowed,
+        limit,
+        remaining: tokens.max(0.0).floor() as u32,
+        retry_after_secs,
+    }
+}
+
+/// Where rate limit buckets are kept. The in-memory store limits each instance
+/// separately; a shared store such as Redis gives every instance one budget.
+#[async_trait]
+pub trait RateLimitStore: std::fmt::Debug + Send + Sync {
+    /// Take one token for the identifier if available
+    async fn acquire(
+        &self,
+        identifier: &str,
+        limit_per_sec: u32,
+    ) -> anyhow::Result<RateLimitStatus>;
+
+    /// Current state for the identifier without consuming a token
+    async fn peek(&self, identifier: &str, limit_per_sec: u32) -> anyhow::Result<RateLimitStatus>;
+
+    /// Record `identifier` for `ttl`. Returns false if it is already recorded,
+    /// e.g. a request signature that was seen before.
+    async fn record_once(&self, identifier: &str, ttl: Duration) -> anyhow::Result<bool>;
+
+    /// Drop buckets idle for `max_idle`; stores that expire keys themselves ignore this
+    fn cleanup(&self, _max_idle: Duration) {}
+}
+
+/// Build the store selected by `config`
+pub async fn rate_limit_store(config: &RateLimitConfig) -> anyhow::Result<Arc<dyn RateLimitStore>> {
+    match config.backend {
+        RateLimitBackend::Memory => Ok(Arc::new(RateLimiter::new())),
+        RateLimitBackend::Redis => {
+            let url = config.redis_url.as_deref().ok_or_else(|| {
+                anyhow::anyhow!("REDIS_URL is required for the redis rate limit backend")
+            })?;
+            let store = RedisRateLimitStore::connect(url).await?;
+            info!("Rate limits are shared through Redis");
+            Ok(Arc::new(store))
+        }
+    }
+}
+
+/// Token-bucket rate limiter keyed by an identifier such as an API key id
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+    // Identifiers passed to `record_once` and when they expire
+    recorded: Mutex<HashMap<String, Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take one token for the identifier if available
+    pub fn try_acquire(&self, identifier: &str, limit_per_sec: u32) -> RateLimitStatus {
+        self.try_acquire_at(identifier, limit_per_sec, Instant::now())
+    }
+
+    fn try_acquire_at(&self, identifier: &str, limit_per_sec: u32, now: Instant) -> RateLimitStatus {
+        let mut buckets = self.buckets.lock().unwrap();
+        buckets
+            .entry(identifier.to_string())
+            .or_insert_with(|| TokenBucket::full(limit_per_sec, now))
+            .try_take(limit_per_sec, now)
+    }
+
+    /// Current state for the identifier without consuming a token
+    pub fn status(&self, identifier: &str, limit_per_sec: u32) -> RateLimitStatus {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        match buckets.get_mut(identifier) {
+            Some(bucket) => {
+                bucket.refill(limit_per_sec, now);
+                bucket.status(true, limit_per_sec)
+            }
+            None => TokenBucket::full(limit_per_sec, now).status(true, limit_per_sec),
+        }
+    }
+
+    /// Record `identifier` for `ttl`; false if it is already recorded
+    pub fn record_once(&self, identifier: &str, ttl: Duration) -> bool {
+        self.record_once_at(identifier, ttl, Instant::now())
+    }
+
+    fn record_once_at(&self, identifier: &str, ttl: Duration, now: Instant) -> bool {
+        let mut recorded = self.recorded.lock().unwrap();
+        if recorded
+            .get(identifier)
+            .is_some_and(|expires_at| *expires_at > now)
+        {
+            return false;
+        }
+        recorded.insert(identifier.to_string(), now + ttl);
+        true
+    }
+
+    /// Drop buckets that have not been touched for `max_idle`, and expired records
+    pub fn cleanup(&self, max_idle: Duration) {
+        let now = Instant::now();
+        self.buckets
+            .lock()
+            .unwrap()
+            .retain(|_, bucket| now.duration_since(bucket.last_refill) < max_idle);
+        self.recorded
+            .lock()
+            .unwrap()
+            .retain(|_, expires_at| *expires_at > now);
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RateLimiter {
+    async fn acquire(
+        &self,
+        identifier: &str,
+        limit_per_sec: u32,
+    ) -> anyhow::Result<RateLimitStatus> {
+        Ok(self.try_acquire(identifier, limit_per_sec))
+    }
+
+    async fn peek(
+        &self,
+        identifier: &str,
+        limit_per_sec: u32,
+    ) -> anyhow::Result<RateLimitStatus> {
+        Ok(self.status(identifier, limit_per_sec))
+    }
+
+    async fn record_once(&self, identifier: &str, ttl: Duration) -> anyhow::Result<bool> {
+        Ok(RateLimiter::record_once(self, identifier, ttl))
+    }
+
+    fn cleanup(&self, max_idle: Duration) {
+        RateLimiter::cleanup(self, max_idle);
+    }
+}
+
+/// Prefix of the Redis keys holding rate limit buckets
+pub const REDIS_KEY_PREFIX: &str = "ratelimit:";
+
+/// Prefix of the Redis keys set by `record_once`
+pub const REDIS_RECORD_KEY_PREFIX: &str = "recorded:";
+
+// Seconds an idle bucket is kept in Redis; a full refill takes at most one second
+const REDIS_BUCKET_TTL_SECS: u64 = 60;
+
+// The same token bucket as `RateLimiter`, refilled from the Redis server clock so
+// instances with skewed clocks agree. Returns {allowed, tokens as a string}.
+const TOKEN_BUCKET_SCRIPT: &str = r#"
+local limit = tonumber(ARGV[1])
+local consume = ARGV[2] == "1"
+local time = redis.call("TIME")
+local now = tonumber(time[1]) + tonumber(time[2]) / 1000000
+
+local bucket = redis.call("HMGET", KEYS[1], "tokens", "ts")
+local tokens = tonumber(bucket[1]) or limit
+local last = tonumber(bucket[2]) or now
+tokens = math.min(limit, tokens + math.max(0, now - last) * limit)
+
+local allowed = 1
+if consume then
+    if tokens >= 1 then
+        tokens = tokens - 1
+    else
+        allowed = 0
+    end
+    redis.call("HSET", KEYS[1], "tokens", tostring(tokens), "ts", tostring(now))
+    redis.call("EXPIRE", KEYS[1], ARGV[3])
+end
+return {allowed, tostring(tokens)}
+"#;
+
+/// Token buckets kept in Redis so every instance shares one budget per key
+#[derive(Clone)]
+pub struct RedisRateLimitStore {
+    connection: redis::aio::ConnectionManager,
+    script: Arc<redis::Script>,
+}
+
+impl std::fmt::Debug for RedisRateLimitStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisRateLimitStore").finish_non_exhaustive()
+    }
+}
+
+impl RedisRateLimitStore {
+    /// Connect to Redis; the connection is re-established automatically if it drops
+    pub async fn connect(url: &str) -> anyhow::Result<Self> {
+        let client = redis::Client::open(url)?;
+        let connection = redis::aio::ConnectionManager::new(client).await?;
+        Ok(Self {
+            connection,
+            script: Arc::new(redis::Script::new(TOKEN_BUCKET_SCRIPT)),
+        })
+    }
+
+    async fn run(
+        &self,
+        identifier: &str,
+        limit_per_sec: u32,
+        consume: bool,
+    ) -> anyhow::Result<RateLimitStatus> {
+        let mut connection = self.connection.clone();
+        let (allowed, tokens): (i64, String) = self
+            .script
+            .key(format!("{}{}", REDIS_KEY_PREFIX, identifier))
+            .arg(limit_per_sec)
+            .arg(if consume { "1" } else { "0" })
+            .arg(REDIS_BUCKET_TTL_SECS)
+            .invoke_async(&mut connection)
+            .await?;
+
+        Ok(bucket_status(tokens.parse()?, allowed == 1, limit_per_sec))
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    async fn acquire(
+        &self,
+        identifier: &str,
+        limit_per_sec: u32,
+    ) -> anyhow::Result<RateLimitStatus> {
+        self.run(identifier, limit_per_sec, true).await
+    }
+
+    async fn peek(
+        &self,
+        identifier: &str,
+        limit_per_sec: u32,
+    ) -> anyhow::Result<RateLimitStatus> {
+        self.run(identifier, limit_per_sec, false).await
+    }
+
+    async fn record_once(&self, identifier: &str, ttl: Duration) -> anyhow::Result<bool> {
+        let mut connection = self.connection.clone();
+        // Only the first SET NX of a key replies OK; the rest get nil
+        let reply: Option<String> = redis::cmd("SET")
+            .arg(format!("{}{}", REDIS_RECORD_KEY_PREFIX, identifier))
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl.as_secs().max(1))
+            .query_async(&mut connection)
+            .await?;
+        Ok(reply.is_some())
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref RATE_LIMIT_FAIL_OPEN: Counter = Counter::new(
+        "realtime_rate_limit_fail_open_total",
+        "Requests let through unchecked because the rate limit store was unavailable"
+    )
+    .expect("valid metric definition");
+}
+
+/// Counter of requests admitted without a rate limit check
+pub fn rate_limit_fail_open_counter() -> &'static Counter {
+    &RATE_LIMIT_FAIL_OPEN
+}
+
+/// Take a token from `store`, letting the request through if the store is
+/// unreachable so a Redis outage doesn't take the API down with it. Each such
+/// request is counted so the outage can be alerted on.
+pub async fn acquire_or_allow(
+    store: &dyn RateLimitStore,
+    identifier: &str,
+    limit_per_sec: u32,
+) -> RateLimitStatus {
+    match store.acquire(identifier, limit_per_sec).await {
+        Ok(status) => status,
+        Err(e) => {
+            RATE_LIMIT_FAIL_OPEN.inc();
+            error!("Rate limit store unavailable, allowing request: {}", e);
+            RateLimitStatus {
+                allowed: true,
+                limit: limit_per_sec,
+                remaining: limit_per_sec,
+                retry_after_secs: 0,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_allows_burst_up_to_limit() {
+        let limiter = RateLimiter::new();
+        let now = Instant::now();
+
+        for expected_remaining in (0..3).rev() {
+            let status = limiter.try_acquire_at("key_1", 3, now);
+            assert!(status.allowed);
+            assert_eq!(status.remaining, expected_remaining);
+        }
+
+        let status = limiter.try_acquire_at("key_1", 3, now);
+        assert!(!status.allowed);
+        assert_eq!(status.remaining, 0);
+        assert_eq!(status.retry_after_secs, 1);
+    }
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let limiter = RateLimiter::new();
+        let now = Instant::now();
+
+        for _ in 0..2 {
+            assert!(limiter.try_acquire_at("key_1", 2, now).allowed);
+        }
+        assert!(!limiter.try_acquire_at("key_1", 2, now).allowed);
+
+        // Half a second at 2/sec refills one token
+        let later = now + Duration::from_millis(500);
+        assert!(limiter.try_acquire_at("key_1", 2, later).allowed);
+        assert!(!limiter.try_acquire_at("key_1", 2, later).allowed);
+    }
+
+    #[test]
+    fn test_keys_are_limited_independently() {
+        let limiter = RateLimiter::new();
+        let now = Instant::now();
+
+        assert!(limiter.try_acquire_at("key_1", 1, now).allowed);
+        assert!(!limiter.try_acquire_at("key_1", 1, now).allowed);
+        assert!(limiter.try_acquire_at("key_2", 1, now).allowed);
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_shares_buckets_with_limiter() {
+        let store: Arc<dyn RateLimitStore> = Arc::new(RateLimiter::new());
+
+        assert!(store.acquire("key_1", 1).await.unwrap().allowed);
+        assert!(!store.acquire("key_1", 1).await.unwrap().allowed);
+        assert_eq!(store.peek("key_1", 1).await.unwrap().remaining, 0);
+        assert_eq!(store.peek("key_2", 1).await.unwrap().remaining, 1);
+    }
+
+    #[test]
+    fn test_record_once_rejects_repeats_until_expiry() {
+        let limiter = RateLimiter::new();
+        let now = Instant::now();
+        let ttl = Duration::from_secs(600);
+
+        assert!(limiter.record_once_at("key_1:sig", ttl, now));
+        assert!(!limiter.record_once_at("key_1:sig", ttl, now + Duration::from_secs(1)));
+        assert!(limiter.record_once_at("key_1:other", ttl, now));
+        assert!(limiter.record_once_at("key_1:sig", ttl, now + ttl));
+    }
+
+    #[derive(Debug)]
+    struct UnavailableStore;
+
+    #[async_trait]
+    impl RateLimitStore for UnavailableStore {
+        async fn acquire(
+            &self,
+            _identifier: &str,
+            _limit_per_sec: u32,
+        ) -> anyhow::Result<RateLimitStatus> {
+            Err(anyhow::anyhow!("connection refused"))
+        }
+
+        async fn peek(
+            &self,
+            _identifier: &str,
+            _limit_per_sec: u32,
+        ) -> anyhow::Result<RateLimitStatus> {
+            Err(anyhow::anyhow!("connection refused"))
+        }
+
+        async fn record_once(&self, _identifier: &str, _ttl: Duration) -> anyhow::Result<bool> {
+            Err(anyhow::anyhow!("connection refused"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unavailable_store_fails_open_and_is_counted() {
+        let before = rate_limit_fail_open_counter().get();
+
+        let status = acquire_or_allow(&UnavailableStore, "key_1", 5).await;
+
+        assert!(status.allowed);
+        assert_eq!(status.remaining, 5);
+        assert!(rate_limit_fail_open_counter().get() >= before + 1.0);
+    }
+
+    #[test]
+    fn test_throttled_response_carries_headers() {
+        let status = RateLimitStatus {
+            allowed: false,
+            limit: 10,
+            remaining: 0,
+            retry_after_secs: 1,
+        };
+
+        let response = status.into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()["x-ratelimit-limit"], "10");
+        assert_eq!(response.headers()["x-ratelimit-remaining"], "0");
+        assert_eq!(response.headers()["retry-after"], "1");
+    }
+}
@@ -0,0 +1,201 @@
+use serde_json::Value;
+
+use crate::config::RedactionConfig;
+use crate::models::topic_allowed;
+
+/// Replacement for a masked payload value
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Object keys masked in every payload unless configured otherwise
+pub const DEFAULT_REDACTED_KEYS: &[&str] = &["password", "token", "secret", "authorization"];
+
+/// Shortest number or boolean masked inside messages; shorter ones are too
+/// common in message text to replace safely and too small to identify anyone
+const MIN_MASKED_SCALAR_LEN: usize = 4;
+
+/// Masks sensitive values in event payloads before they are logged or quoted
+/// in error responses. Stored and delivered events are never changed.
+#[derive(Debug, Clone)]
+pub struct PayloadRedactor {
+    /// Lowercase key fragments; any object key containing one is masked
+    keys: Vec<String>,
+    /// Topic patterns and the JSON pointers masked in their payloads
+    topic_paths: Vec<(String, Vec<String>)>,
+}
+
+impl Default for PayloadRedactor {
+    fn default() -> Self {
+        Self::new(&RedactionConfig::default())
+    }
+}
+
+impl PayloadRedactor {
+    pub fn new(config: &RedactionConfig) -> Self {
+        Self {
+            keys: config.keys.iter().map(|key| key.to_lowercase()).collect(),
+            topic_paths: config
+                .topic_paths
+                .iter()
+                .map(|(pattern, paths)| (pattern.clone(), paths.clone()))
+                .collect(),
+        }
+    }
+
+    /// A copy of `payload` that is safe to log
+    pub fn redact(&self, topic: &str, payload: &Value) -> Value {
+        let mut redacted = payload.clone();
+        self.mask(topic, &mut redacted);
+        redacted
+    }
+
+    /// `message` with every value masked in `payload` replaced, for error
+    /// text that may quote payload values, such as schema violations
+    pub fn redact_message(&self, topic: &str, payload: &Value, message: &str) -> String {
+        let mut masked = payload.clone();
+        let mut secrets = Vec::new();
+        self.mask_collecting(topic, &mut masked, &mut secrets);
+
+        // Longest first so a value isn't partly replaced by one it contains
+        secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+        secrets
+            .iter()
+            .fold(message.to_string(), |message, secret| message.replace(secret, REDACTED))
+    }
+
+    fn mask(&self, topic: &str, payload: &mut Value) {
+        self.mask_collecting(topic, payload, &mut Vec::new());
+    }
+
+    // Mask configured paths and keys, collecting the text forms of what was
+    // masked so it can be scrubbed from messages too
+    fn mask_collecting(&self, topic: &str, payload: &mut Value, secrets: &mut Vec<String>) {
+        for (pattern, paths) in &self.topic_paths {
+            if !topic_allowed(std::slice::from_ref(pattern), topic) {
+                continue;
+            }
+            for path in paths {
+                if let Some(value) = payload.pointer_mut(path) {
+                    collect_secrets(value, secrets);
+                    *value = Value::String(REDACTED.to_string());
+                }
+            }
+        }
+        self.mask_keys(payload, secrets);
+    }
+
+    fn mask_keys(&self, value: &mut Value, secrets: &mut Vec<String>) {
+        match value {
+            Value::Object(object) => {
+                for (key, value) in object.iter_mut() {
+                    if self.is_sensitive_key(key) {
+                        collect_secrets(value, secrets);
+                        *value = Value::String(REDACTED.to_string());
+                    } else {
+                        self.mask_keys(value, secrets);
+                    }
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.mask_keys(item, secrets);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn is_sensitive_key(&self, key: &str) -> bool {
+        let key = key.to_lowercase();
+        self.keys.iter().any(|sensitive| key.contains(sensitive.as_str()))
+    }
+}
+
+// The ways a masked value can show up in message text: its JSON form and,
+// for strings, the bare text, recursing into objects and arrays
+fn collect_secrets(value: &Value, secrets: &mut Vec<String>) {
+    match value {
+        Value::Null => {}
+        Value::String(text) => {
+            if !text.is_empty() {
+                secrets.push(value.to_string());
+                secrets.push(text.clone());
+            }
+        }
+        Value::Number(_) | Value::Bool(_) => {
+            let text = value.to_string();
+            if text.len() >= MIN_MASKED_SCALAR_LEN {
+                secrets.push(text);
+            }
+        }
+        Value::Array(items) => {
+            secrets.push(value.to_string());
+            items.iter().for_each(|item| collect_secrets(item, secrets));
+        }
+        Value::Object(object) => {
+            secrets.push(value.to_string());
+            object.values().for_each(|item| collect_secrets(item, secrets));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn redactor() -> PayloadRedactor {
+        PayloadRedactor::new(&RedactionConfig {
+            topic_paths: HashMap::from([(
+                "users.>".to_string(),
+                vec!["/email".to_string(), "/profile/ssn".to_string()],
+            )]),
+            ..RedactionConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_default_keys_are_masked_anywhere() {
+        let payload = json!({
+            "user": "u1",
+            "password": "hunter22",
+            "nested": {"Access_Token": "abc123", "items": [{"client_secret": {"k": "v"}}]},
+            "headers": {"Authorization": "Bearer xyz"}
+        });
+
+        let redacted = PayloadRedactor::default().redact("orders.created", &payload);
+        assert_eq!(
+            redacted,
+            json!({
+                "user": "u1",
+                "password": REDACTED,
+                "nested": {"Access_Token": REDACTED, "items": [{"client_secret": REDACTED}]},
+                "headers": {"Authorization": REDACTED}
+            })
+        );
+    }
+
+    #[test]
+    fn test_topic_paths_apply_only_to_matching_topics() {
+        let payload = json!({"email": "ada@example.com", "profile": {"ssn": "123-45-6789", "age": 36}});
+
+        let redacted = redactor().redact("users.created", &payload);
+        assert_eq!(
+            redacted,
+            json!({"email": REDACTED, "profile": {"ssn": REDACTED, "age": 36}})
+        );
+        assert_eq!(redactor().redact("orders.created", &payload), payload);
+    }
+
+    #[test]
+    fn test_messages_never_quote_masked_values() {
+        let payload = json!({"email": "ada@example.com", "password": "hunter22", "count": 3});
+        let message = r#"/email: "ada@example.com" is not a "uuid"; /password: hunter22 too short; /count: 3 is not a string"#;
+
+        let redacted = redactor().redact_message("users.created", &payload, message);
+        assert!(!redacted.contains("ada@example.com"), "{}", redacted);
+        assert!(!redacted.contains("hunter22"), "{}", redacted);
+        assert!(redacted.contains("/count: 3 is not a string"), "{}", redacted);
+        assert!(redacted.contains(REDACTED));
+    }
+}
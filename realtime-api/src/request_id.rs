@@ -0,0 +1,155 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use std::future::Future;
+use tracing::Instrument;
+use uuid::Uuid;
+
+/// Header a request id is read from and echoed back in
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest client-supplied request id that is accepted as-is
+pub const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Correlation id of an HTTP request, stored as a request extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: String;
+}
+
+/// The id of the request being handled on this task, if any
+pub fn current_request_id() -> Option<String> {
+    CURRENT_REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Run `future` with `id` as the current request id. Used to carry the id
+/// into work that outlives the request, such as an upgraded WebSocket.
+pub async fn with_request_id<F: Future>(id: String, future: F) -> F::Output {
+    CURRENT_REQUEST_ID.scope(id, future).await
+}
+
+/// A client-supplied id, if it's short printable ASCII; anything else is
+/// replaced so it can't be used to inject into logs or headers
+fn incoming_request_id(request: &Request) -> Option<String> {
+    let value = request.headers().get(&REQUEST_ID_HEADER)?.to_str().ok()?.trim();
+    let valid = !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LEN
+        && value.bytes().all(|b| b.is_ascii_graphic());
+    valid.then(|| value.to_string())
+}
+
+/// Take the request's `X-Request-Id` or generate one, make it available to
+/// handlers as a `RequestId` extension, through `current_request_id` and on
+/// the request's tracing span, and echo it in the response header
+pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
+    let id = incoming_request_id(&request).unwrap_or_else(|| Uuid::new_v4().to_string());
+    request.extensions_mut().insert(RequestId(id.clone()));
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %request.method(),
+        path = %request.uri().path(),
+    );
+    let mut response = with_request_id(id.clone(), next.run(request).instrument(span)).await;
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER.clone(), value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_error::ApiError;
+    use axum::body::Body;
+    use axum::http::StatusCode;
+    use axum::routing::get;
+    use axum::{middleware, Extension, Router};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/echo",
+                get(|Extension(RequestId(id)): Extension<RequestId>| async move { id }),
+            )
+            .route(
+                "/fail",
+                get(|| async { ApiError::not_found("NOT_FOUND", "nothing here") }),
+            )
+            .layer(middleware::from_fn(request_id_middleware))
+    }
+
+    async fn send(uri: &str, request_id: Option<&str>) -> Response {
+        let mut request = Request::builder().uri(uri);
+        if let Some(id) = request_id {
+            request = request.header(&REQUEST_ID_HEADER, id);
+        }
+        app()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    fn header(response: &Response) -> String {
+        response.headers()[&REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    async fn body_json(response: Response) -> serde_json::Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn incoming_id_round_trips_into_error_body() {
+        let response = send("/fail", Some("req-abc-123")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(header(&response), "req-abc-123");
+
+        let body = body_json(response).await;
+        assert_eq!(body["error"]["request_id"], "req-abc-123");
+    }
+
+    #[tokio::test]
+    async fn generated_id_matches_header_extension_and_error_body() {
+        let response = send("/echo", None).await;
+        let id = header(&response);
+        assert!(Uuid::parse_str(&id).is_ok());
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, id.as_bytes());
+
+        let response = send("/fail", None).await;
+        let id = header(&response);
+        assert_eq!(body_json(response).await["error"]["request_id"], id);
+    }
+
+    #[tokio::test]
+    async fn unusable_incoming_id_is_replaced() {
+        let too_long = "a".repeat(MAX_REQUEST_ID_LEN + 1);
+        for bad in ["", "has space", too_long.as_str()] {
+            let response = send("/echo", Some(bad)).await;
+            assert!(Uuid::parse_str(&header(&response)).is_ok(), "{:?}", bad);
+        }
+    }
+
+    #[tokio::test]
+    async fn current_request_id_is_scoped() {
+        assert_eq!(current_request_id(), None);
+        let inside = with_request_id("scoped".to_string(), async { current_request_id() }).await;
+        assert_eq!(inside.as_deref(), Some("scoped"));
+    }
+}
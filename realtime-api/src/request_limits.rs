@@ -0,0 +1,115 @@
+use std::time::Duration;
+use tower_http::limit::RequestBodyLimitLayer;
+use tower_http::timeout::TimeoutLayer;
+
+use crate::config::HttpConfig;
+
+/// Reject request bodies over `max_request_body_bytes` with `413`. Axum's own
+/// 2MB extractor limit has to be lifted with `DefaultBodyLimit::disable` for
+/// larger configured limits to take effect.
+pub fn body_limit_layer(config: &HttpConfig) -> RequestBodyLimitLayer {
+    RequestBodyLimitLayer::new(config.max_request_body_bytes)
+}
+
+/// Answer requests that haven't produced a response within
+/// `request_timeout_secs` with `408`. Only for routes that return promptly;
+/// WebSocket and SSE routes must not be wrapped in it.
+pub fn request_timeout_layer(config: &HttpConfig) -> TimeoutLayer {
+    TimeoutLayer::new(Duration::from_secs(config.request_timeout_secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::extract::DefaultBodyLimit;
+    use axum::http::{header, Request, StatusCode};
+    use axum::routing::{get, post};
+    use axum::Router;
+    use tower::ServiceExt;
+
+    fn config() -> HttpConfig {
+        HttpConfig {
+            max_request_body_bytes: 1024,
+            request_timeout_secs: 1,
+        }
+    }
+
+    fn app(config: &HttpConfig) -> Router {
+        Router::new()
+            .route("/echo", post(|body: String| async move { body }))
+            .route("/fast", get(|| async { "ok" }))
+            .route(
+                "/slow",
+                get(|| async {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    "too late"
+                }),
+            )
+            .layer(request_timeout_layer(config))
+            .layer(DefaultBodyLimit::disable())
+            .layer(body_limit_layer(config))
+    }
+
+    fn upload(size: usize) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/echo")
+            .header(header::CONTENT_LENGTH, size)
+            .body(Body::from(vec![b'a'; size]))
+            .unwrap()
+    }
+
+    async fn status(request: Request<Body>) -> StatusCode {
+        app(&config()).oneshot(request).await.unwrap().status()
+    }
+
+    fn get_request(uri: &str) -> Request<Body> {
+        Request::builder().uri(uri).body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn oversized_body_is_rejected() {
+        assert_eq!(status(upload(1024)).await, StatusCode::OK);
+        assert_eq!(status(upload(1025)).await, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn oversized_body_without_content_length_is_rejected() {
+        let request = Request::builder()
+            .method("POST")
+            .uri("/echo")
+            .body(Body::from(vec![b'a'; 4096]))
+            .unwrap();
+        assert_eq!(status(request).await, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn limit_above_axum_default_is_honoured() {
+        let config = HttpConfig {
+            max_request_body_bytes: 4 * 1024 * 1024,
+            ..config()
+        };
+        let size = 3 * 1024 * 1024;
+        let request = Request::builder()
+            .method("POST")
+            .uri("/echo")
+            .header(header::CONTENT_LENGTH, size)
+            .body(Body::from(vec![b'a'; size]))
+            .unwrap();
+        let response = app(&config).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn slow_handler_times_out() {
+        assert_eq!(status(get_request("/fast")).await, StatusCode::OK);
+
+        let started = std::time::Instant::now();
+        assert_eq!(
+            status(get_request("/slow")).await,
+            StatusCode::REQUEST_TIMEOUT
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,253 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use hmac::{Hmac, Mac};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// Authorization scheme of signed requests:
+/// `Authorization: HMAC keyId=<api key id>,signature=<hex>,ts=<unix seconds>`
+pub const HMAC_SCHEME: &str = "HMAC";
+
+/// How far a signed request's timestamp may be from the server clock, in seconds
+pub const MAX_SIGNATURE_SKEW_SECS: i64 = 300;
+
+/// Prefix of generated request signing secrets
+pub const SIGNING_SECRET_PREFIX: &str = "rtsk_";
+
+/// Prefix of signing secrets encrypted for storage
+pub const SEALED_SECRET_PREFIX: &str = "v1:";
+
+// Mixed into the key derivation so the cipher key differs from any other use
+// of the same configured secret, e.g. as the JWT secret
+const CIPHER_KEY_CONTEXT: &[u8] = b"realtime-api signing secret encryption\n";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Parsed `Authorization: HMAC ...` header
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HmacAuthorization {
+    /// Id of the API key whose signing secret made the signature
+    pub key_id: String,
+    /// Hex HMAC-SHA256 of the request
+    pub signature: String,
+    /// Unix seconds the request was signed at
+    pub timestamp: i64,
+}
+
+impl HmacAuthorization {
+    /// Parse an Authorization header value; None unless it uses the HMAC
+    /// scheme with all of `keyId`, `signature` and `ts`
+    pub fn parse(header: &str) -> Option<Self> {
+        let params = header.strip_prefix(HMAC_SCHEME)?.strip_prefix(' ')?;
+
+        let (mut key_id, mut signature, mut timestamp) = (None, None, None);
+        for param in params.split(',') {
+            let (name, value) = param.trim().split_once('=')?;
+            match name {
+                "keyId" => key_id = Some(value.to_string()),
+                "signature" => signature = Some(value.to_string()),
+                "ts" => timestamp = Some(value.parse().ok()?),
+                _ => return None,
+            }
+        }
+
+        Some(Self {
+            key_id: key_id.filter(|id| !id.is_empty())?,
+            signature: signature?,
+            timestamp: timestamp?,
+        })
+    }
+
+    /// Whether the signing time is within `MAX_SIGNATURE_SKEW_SECS` of `now`
+    pub fn is_fresh(&self, now: i64) -> bool {
+        (now - self.timestamp).abs() <= MAX_SIGNATURE_SKEW_SECS
+    }
+}
+
+// Method, path (with any query string) and timestamp on their own lines,
+// followed by the raw body
+fn signing_mac(secret: &str, method: &str, path: &str, timestamp: i64, body: &[u8]) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}\n{}\n{}\n", method.to_ascii_uppercase(), path, timestamp).as_bytes());
+    mac.update(body);
+    mac
+}
+
+/// Hex signature of a request, as a client computes it
+pub fn sign_request(secret: &str, method: &str, path: &str, timestamp: i64, body: &[u8]) -> String {
+    hex::encode(signing_mac(secret, method, path, timestamp, body).finalize().into_bytes())
+}
+
+/// Check a request's signature in constant time
+pub fn verify_request(
+    secret: &str,
+    method: &str,
+    path: &str,
+    timestamp: i64,
+    body: &[u8],
+    signature: &str,
+) -> bool {
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    signing_mac(secret, method, path, timestamp, body)
+        .verify_slice(&signature)
+        .is_ok()
+}
+
+/// Generate a random secret for signing requests with an API key
+pub fn generate_signing_secret() -> String {
+    use rand::Rng;
+    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    const SECRET_LENGTH: usize = 48;
+
+    let mut rng = rand::thread_rng();
+    let secret: String = (0..SECRET_LENGTH)
+        .map(|_| CHARSET[rng.gen_range(0..CHARSET.len())] as char)
+        .collect();
+
+    format!("{}{}", SIGNING_SECRET_PREFIX, secret)
+}
+
+/// Encrypts API key and webhook signing secrets for storage. A signing secret
+/// has to be recoverable to recompute signatures, so unlike the key itself it
+/// can't be hashed; it is kept AES-256-GCM encrypted under a server-side key,
+/// bound to the id of the key or webhook it belongs to.
+#[derive(Clone)]
+pub struct SigningSecretCipher {
+    key: LessSafeKey,
+}
+
+impl SigningSecretCipher {
+    /// Cipher whose key is derived from `secret`
+    pub fn new(secret: &str) -> Self {
+        let digest = Sha256::new()
+            .chain_update(CIPHER_KEY_CONTEXT)
+            .chain_update(secret.as_bytes())
+            .finalize();
+        let key = UnboundKey::new(&AES_256_GCM, &digest).expect("SHA-256 digests are AES-256 keys");
+        Self {
+            key: LessSafeKey::new(key),
+        }
+    }
+
+    /// Encrypt the signing secret of the API key or webhook `key_id`
+    pub fn seal(&self, key_id: &str, secret: &str) -> String {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .expect("system randomness is available");
+
+        let mut sealed = secret.as_bytes().to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(key_id.as_bytes()),
+                &mut sealed,
+            )
+            .expect("signing secrets are far below the AES-GCM size limit");
+
+        let mut stored = nonce.to_vec();
+        stored.extend_from_slice(&sealed);
+        format!("{}{}", SEALED_SECRET_PREFIX, STANDARD.encode(stored))
+    }
+
+    /// Decrypt a signing secret sealed for `key_id`; None if it was sealed
+    /// under another key, for another API key or webhook, or has been
+    /// tampered with
+    pub fn open(&self, key_id: &str, stored: &str) -> Option<String> {
+        let stored = STANDARD
+            .decode(stored.strip_prefix(SEALED_SECRET_PREFIX)?)
+            .ok()?;
+        if stored.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, sealed) = stored.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).ok()?;
+
+        let mut sealed = sealed.to_vec();
+        let secret = self
+            .key
+            .open_in_place(nonce, Aad::from(key_id.as_bytes()), &mut sealed)
+            .ok()?;
+        String::from_utf8(secret.to_vec()).ok()
+    }
+}
+
+impl fmt::Debug for SigningSecretCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningSecretCipher").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_authorization_header() {
+        let parsed = HmacAuthorization::parse("HMAC keyId=key-1, signature=abcd,ts=1700000000")
+            .expect("Header should parse");
+        assert_eq!(parsed.key_id, "key-1");
+        assert_eq!(parsed.signature, "abcd");
+        assert_eq!(parsed.timestamp, 1_700_000_000);
+
+        for invalid in [
+            "Bearer token",
+            "HMAC keyId=key-1,signature=abcd",
+            "HMAC keyId=key-1,signature=abcd,ts=soon",
+            "HMAC keyId=,signature=abcd,ts=1",
+            "HMAC keyId=key-1,signature=abcd,ts=1,extra=1",
+        ] {
+            assert!(HmacAuthorization::parse(invalid).is_none(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_signature_covers_method_path_timestamp_and_body() {
+        let secret = generate_signing_secret();
+        let signature = sign_request(&secret, "POST", "/events", 1_700_000_000, b"{}");
+        assert!(verify_request(&secret, "post", "/events", 1_700_000_000, b"{}", &signature));
+
+        assert!(!verify_request(&secret, "PUT", "/events", 1_700_000_000, b"{}", &signature));
+        assert!(!verify_request(&secret, "POST", "/events/batch", 1_700_000_000, b"{}", &signature));
+        assert!(!verify_request(&secret, "POST", "/events", 1_700_000_001, b"{}", &signature));
+        assert!(!verify_request(&secret, "POST", "/events", 1_700_000_000, b"[]", &signature));
+        assert!(!verify_request("other", "POST", "/events", 1_700_000_000, b"{}", &signature));
+        assert!(!verify_request(&secret, "POST", "/events", 1_700_000_000, b"{}", "not hex"));
+    }
+
+    #[test]
+    fn test_timestamp_skew_window() {
+        let authorization = HmacAuthorization {
+            key_id: "key-1".to_string(),
+            signature: String::new(),
+            timestamp: 1_000_000,
+        };
+        assert!(authorization.is_fresh(1_000_000 + MAX_SIGNATURE_SKEW_SECS));
+        assert!(authorization.is_fresh(1_000_000 - MAX_SIGNATURE_SKEW_SECS));
+        assert!(!authorization.is_fresh(1_000_000 + MAX_SIGNATURE_SKEW_SECS + 1));
+    }
+
+    #[test]
+    fn test_sealed_secret_opens_only_for_its_key() {
+        let cipher = SigningSecretCipher::new("server secret");
+        let secret = generate_signing_secret();
+        let sealed = cipher.seal("key-1", &secret);
+
+        assert!(!sealed.contains(&secret));
+        assert_ne!(sealed, cipher.seal("key-1", &secret));
+        assert_eq!(cipher.open("key-1", &sealed), Some(secret.clone()));
+
+        assert_eq!(cipher.open("key-2", &sealed), None);
+        let other_cipher = SigningSecretCipher::new("other secret");
+        assert_eq!(other_cipher.open("key-1", &sealed), None);
+        assert_eq!(cipher.open("key-1", &secret), None);
+
+        let mut tampered = sealed.clone();
+        tampered.replace_range(sealed.len() - 4.., "AAAA");
+        assert_eq!(cipher.open("key-1", &tampered), None);
+    }
+}
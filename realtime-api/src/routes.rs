@@ -1,29 +1,42 @@
 use axum::{
-    extract::{ws::WebSocketUpgrade, Extension, Query, State},
+    extract::{ConnectInfo, DefaultBodyLimit, Extension, Query, State},
     middleware,
     response::Response,
-    routing::{delete, get, post},
+    routing::{delete, get, patch, post},
     Router,
 };
 use serde::Deserialize;
+use std::net::SocketAddr;
 use tower::ServiceBuilder;
-use tower_http::{
-    cors::{Any, CorsLayer},
-    trace::TraceLayer,
-};
+use tower_http::trace::TraceLayer;
+use utoipa::IntoParams;
 
 use crate::api::{
     create_api_key, create_tenant, get_usage_limits, get_usage_report, handle_stripe_webhook,
     health_check, publish_event, revoke_api_key, suspend_tenant, unsuspend_tenant, AppState,
-    update_user_role, list_tenant_users, deactivate_user, metrics_handler,
+    update_user_role, list_tenant_users, deactivate_user, metrics_handler, get_sla_summary,
+    list_events, onboard_tenant, register_topic_schema, set_tenant_log_level,
+    clear_tenant_log_level, get_api_key_usage, get_topic_schema, replay_events,
+    publish_events_batch, get_event, list_dead_letters, replay_dead_letter, list_project_api_keys,
+    liveness_check, readiness_check, update_project, update_tenant_plan, create_webhook,
+    list_webhooks, get_webhook, update_webhook, delete_webhook, list_audit_logs, export_usage,
+    archive_project, rotate_api_key, get_tenant,
 };
 use crate::auth::{api_key_auth_middleware, AuthContext};
+use crate::cloudevents::DeliveryFormat;
+use crate::cors::cors_layer;
 use crate::graphql::{
     create_schema, graphql_handler, graphql_playground, graphql_subscription_handler, ApiSchema,
 };
 use crate::models::Permission;
+use crate::mtls::ClientCertificate;
+use crate::openapi::swagger_ui;
 use crate::rbac::{RbacMiddleware, require_permission};
-use crate::sse::sse_handler;
+use crate::request_id::{request_id_middleware, with_request_id, RequestId};
+use crate::request_limits::{body_limit_layer, request_timeout_layer};
+use crate::sse::{sse_handler, sse_subscribe_handler};
+use crate::ws_compression::WebSocketUpgrade;
+use crate::ws_framing::SUPPORTED_SUBPROTOCOLS;
 
 /// Create the main application router with all endpoints
 pub fn create_router(state: AppState) -> Router {
@@ -41,27 +54,77 @@ pub fn create_router(state: AppState) -> Router {
         state.database.clone(),
         state.event_service.clone(),
         state.auth_service.clone(),
+        state.max_page_size,
+        &state.graphql,
     );
 
-    Router::new()
-        // Public endpoints (no authentication required)
+    // Prometheus scrape endpoint, only mounted when a metrics endpoint is configured
+    let metrics_router = if state.metrics_enabled {
+        Router::new().route("/metrics", get(metrics_handler))
+    } else {
+        Router::new()
+    };
+
+    // Stripe authenticates webhooks with a signature rather than an API key
+    let webhooks_router = Router::new()
+        .route("/webhooks/stripe", post(handle_stripe_webhook))
+        .route("/billing/stripe-webhook", post(handle_stripe_webhook));
+
+    // Probes are unauthenticated so load balancers and Kubernetes can call them
+    let health_router = Router::new()
         .route("/health", get(health_check))
-        .route("/metrics", get(metrics_handler))
-        .route("/billing/stripe-webhook", post(handle_stripe_webhook))
+        .route("/health/live", get(liveness_check))
+        .route("/health/ready", get(readiness_check));
+
+    // Streaming connections stay open, so they're kept out of the request
+    // timeout, and authenticate in their handlers: WebSocket clients may pass
+    // `?access_token=` and GraphQL subscriptions use `connection_init`
+    let streaming_router = Router::new()
+        .route("/ws", get(websocket_handler))
+        .route("/sse", get(sse_handler).post(sse_subscribe_handler))
+        .route("/graphql/ws", get(graphql_subscription_handler));
+
+    Router::new()
         // GraphQL playground (development only - should be disabled in production)
         .route("/graphql/playground", get(graphql_playground))
-        // WebSocket endpoint (authentication handled in the handler)
-        .route("/ws", get(websocket_handler))
-        // SSE endpoint (authentication handled in the handler)
-        .route("/sse", get(sse_handler))
         // Protected endpoints (require authentication)
         // TODO: Fix axum version conflicts for GraphQL routes
         // .route("/graphql", post(graphql_handler_with_auth))
-        // .route("/graphql/ws", get(graphql_subscription_handler_with_auth))
-        .route("/events", post(publish_event))
+        .route("/events", post(publish_event).get(list_events))
+        .route("/events/batch", post(publish_events_batch))
+        .route("/events/replay", get(replay_events))
+        .route("/events/:event_id", get(get_event))
         .route("/admin/tenants", post(create_tenant))
+        .route("/admin/onboard", post(onboard_tenant))
         .route("/admin/api-keys", post(create_api_key))
         .route("/admin/api-keys/:key_id", delete(revoke_api_key))
+        .route("/admin/api-keys/:key_id/rotate", post(rotate_api_key))
+        .route("/admin/api-keys/:key_id/usage", get(get_api_key_usage))
+        .route(
+            "/admin/projects/:project_id",
+            patch(update_project).delete(archive_project),
+        )
+        .route("/admin/projects/:project_id/api-keys", get(list_project_api_keys))
+        .route("/admin/sla", get(get_sla_summary))
+        .route("/admin/audit", get(list_audit_logs))
+        .route("/admin/usage/export", get(export_usage))
+        .route("/admin/dlq", get(list_dead_letters))
+        .route("/admin/dlq/:id/replay", post(replay_dead_letter))
+        .route("/admin/webhooks", post(create_webhook).get(list_webhooks))
+        .route(
+            "/admin/webhooks/:webhook_id",
+            get(get_webhook).patch(update_webhook).delete(delete_webhook),
+        )
+        .route("/admin/topics/:topic/schema", axum::routing::put(register_topic_schema))
+        .route("/projects/:project_id/topics/:topic/schema", get(get_topic_schema))
+        .route("/admin/tenants/:tenant_id", get(get_tenant))
+        .route("/admin/tenants/:tenant_id/suspend", post(suspend_tenant))
+        .route("/admin/tenants/:tenant_id/unsuspend", post(unsuspend_tenant))
+        .route("/admin/tenants/:tenant_id/plan", patch(update_tenant_plan))
+        .route(
+            "/admin/tenants/:tenant_id/log-level",
+            axum::routing::put(set_tenant_log_level).delete(clear_tenant_log_level),
+        )
         .route("/billing/usage", get(get_usage_report))
         .route("/billing/limits", get(get_usage_limits))
         .route("/billing/suspend/:tenant_id", post(suspend_tenant))
@@ -91,21 +154,35 @@ pub fn create_router(state: AppState) -> Router {
                     require_permission(Permission::ManageUsers),
                 ))
         )
-        // Apply authentication middleware to protected routes (except playground and WebSocket)
+        // Apply authentication middleware to protected routes (except playground and streaming)
         .layer(middleware::from_fn_with_state(
             auth_service,
             api_key_auth_middleware,
         ))
-        // Apply global middleware
+        // Outside the auth layer so authentication, which reads the body of a
+        // signed request, is bounded by the timeout too
+        .layer(request_timeout_layer(&state.http))
+        .merge(streaming_router)
+        // Merged after the auth layer so scrapers don't need an API key
+        .merge(
+            Router::new()
+                .merge(metrics_router)
+                .merge(webhooks_router)
+                .merge(health_router)
+                // Spec at /openapi.json and Swagger UI at /docs, public like the probes
+                .merge(swagger_ui())
+                .layer(request_timeout_layer(&state.http)),
+        )
+        // Apply global middleware; CORS also covers the WebSocket and SSE routes.
+        // The body limit replaces axum's fixed 2MB extractor limit. The request
+        // id comes first so every response and log line below carries it.
         .layer(
             ServiceBuilder::new()
+                .layer(middleware::from_fn(request_id_middleware))
                 .layer(TraceLayer::new_for_http())
-                .layer(
-                    CorsLayer::new()
-                        .allow_origin(Any)
-                        .allow_methods(Any)
-                        .allow_headers(Any),
-                ),
+                .layer(cors_layer(&state.cors))
+                .layer(DefaultBodyLimit::disable())
+                .layer(body_limit_layer(&state.http)),
         )
         .with_state(state)
         .layer(Extension(schema))
@@ -121,49 +198,119 @@ pub fn create_websocket_router(state: AppState) -> Router {
 /// Create a router for Server-Sent Events
 pub fn create_sse_router(state: AppState) -> Router {
     Router::new()
-        .route("/sse", get(sse_handler))
+        .route("/sse", get(sse_handler).post(sse_subscribe_handler))
         .with_state(state)
 }
 
 /// WebSocket connection query parameters
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct WebSocketQuery {
     pub topics: Option<String>, // Comma-separated list of topics
+    /// API key or JWT for clients that can't set an `Authorization` header
+    pub access_token: Option<String>,
+    /// `native` (default) or `cloudevents`
+    #[serde(default)]
+    pub format: DeliveryFormat,
+    /// Stream sequence of the last event received before a reconnect
+    pub last_sequence: Option<u64>,
+    /// Id of the last event received before a reconnect
+    pub last_event_id: Option<String>,
+    /// Redeliver events the connection can't keep up with instead of dropping them
+    #[serde(default)]
+    pub acked: bool,
 }
 
 /// WebSocket handler with authentication and subscription management
+#[utoipa::path(
+    get,
+    path = "/ws",
+    tag = "streaming",
+    params(WebSocketQuery),
+    responses(
+        (status = 101, description = "Switching to the WebSocket protocol"),
+        (status = 403, description = "Key lacks the events:subscribe scope or the tenant is suspended"),
+    )
+)]
 async fn websocket_handler(
     State(state): State<AppState>,
     ws: WebSocketUpgrade,
     Query(params): Query<WebSocketQuery>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    request_id: Option<Extension<RequestId>>,
+    client_certificate: Option<Extension<ClientCertificate>>,
     headers: axum::http::HeaderMap,
 ) -> Result<Response, axum::http::StatusCode> {
-    use crate::auth::{extract_auth_header, AuthError};
-    use crate::websocket::{handle_websocket_connection, WebSocketConnectionParams};
+    use crate::auth::{extract_websocket_credential, AuthError, CredentialSource};
+    use crate::websocket::{
+        handle_websocket_connection, reject_websocket_connection, WebSocketConnectionParams,
+        UNAUTHORIZED_CLOSE_CODE, UNAUTHORIZED_CLOSE_REASON,
+    };
+    use crate::ws_resume::ResumeCursor;
+
+    // Extract authentication from headers, or the query string for browsers.
+    // Clients sending neither may authenticate with their TLS client certificate.
+    let credential = extract_websocket_credential(&headers, params.access_token.as_deref());
+    let source = match &credential {
+        Ok((_, source)) => *source,
+        Err(_) => CredentialSource::Header,
+    };
 
-    // Extract authentication from headers
-    let auth_value = match extract_auth_header(&headers) {
-        Ok(value) => value,
-        Err(_) => return Err(axum::http::StatusCode::UNAUTHORIZED),
+    // Browser clients only see close frames, so a bad query token is reported
+    // by completing the handshake and closing with 4401
+    let unauthorized = move |ws: WebSocketUpgrade| match source {
+        CredentialSource::Header => Err(axum::http::StatusCode::UNAUTHORIZED),
+        CredentialSource::Query => Ok(ws.on_upgrade(|socket| {
+            reject_websocket_connection(socket, UNAUTHORIZED_CLOSE_CODE, UNAUTHORIZED_CLOSE_REASON)
+        })),
     };
 
     // Validate authentication
-    let auth_context = match state.auth_service.validate_api_key(&auth_value).await {
-        Ok(context) => context,
-        Err(AuthError::InvalidApiKey) => {
-            // Try JWT validation as fallback
-            match state.auth_service.validate_jwt(&auth_value).await {
-                Ok(context) => context,
-                Err(_) => return Err(axum::http::StatusCode::UNAUTHORIZED),
+    let client_ip = state
+        .auth_service
+        .client_ip(&headers, connect_info.map(|ConnectInfo(addr)| addr));
+    let result = match (credential, client_certificate) {
+        (Ok((auth_value, _)), _) => {
+            match state
+                .auth_service
+                .validate_api_key_from(&auth_value, client_ip)
+                .await
+            {
+                // Try JWT validation as fallback
+                Err(AuthError::InvalidApiKey) => state
+                    .auth_service
+                    .validate_jwt(&auth_value)
+                    .await
+                    .map_err(|_| AuthError::InvalidJwt),
+                result => result,
             }
         }
-        Err(AuthError::RateLimitExceeded) => {
-            return Err(axum::http::StatusCode::TOO_MANY_REQUESTS);
+        (Err(_), Some(Extension(certificate))) => {
+            state
+                .auth_service
+                .validate_client_certificate(&certificate)
+                .await
         }
+        (Err(_), None) => return Err(axum::http::StatusCode::UNAUTHORIZED),
+    };
+    let auth_context = match result {
+        Ok(context) => context,
+        Err(AuthError::RateLimitExceeded(status)) => return Ok(status.into_response()),
         Err(AuthError::TenantSuspended) => {
             return Err(axum::http::StatusCode::FORBIDDEN);
         }
-        Err(_) => return Err(axum::http::StatusCode::UNAUTHORIZED),
+        Err(AuthError::Database(e)) if crate::circuit_breaker::circuit_open(&e).is_some() => {
+            tracing::warn!("Database unavailable during WebSocket authentication: {}", e);
+            return Ok(crate::circuit_breaker::circuit_open_response(&e));
+        }
+        Err(AuthError::Database(e)) if crate::db_retry::is_pool_exhausted(&e) => {
+            tracing::warn!(
+                "Database pool exhausted during WebSocket authentication: {}",
+                e
+            );
+            return Ok(crate::rate_limit::overloaded_response());
+        }
+        Err(_) => return unauthorized(ws),
     };
 
     // Check if the API key has subscribe permissions
@@ -182,16 +329,36 @@ async fn websocket_handler(
         .map(|t| t.split(',').map(|s| s.trim().to_string()).collect())
         .unwrap_or_else(Vec::new);
 
+    if let Err(message) = auth_context.check_subscribe_topics(&topics) {
+        tracing::warn!("Rejected WebSocket subscription: {}", message);
+        return Err(axum::http::StatusCode::FORBIDDEN);
+    }
+
     // Create connection parameters
     let connection_params = WebSocketConnectionParams {
         tenant_id: auth_context.tenant_id.clone(),
         project_id: auth_context.project_id.clone(),
         topics,
         auth_context,
+        format: params.format,
+        resume: ResumeCursor::from_params(params.last_sequence, params.last_event_id.as_deref()),
+        acked: params.acked,
     };
 
-    // Upgrade to WebSocket
-    Ok(ws.on_upgrade(move |socket| handle_websocket_connection(socket, connection_params, state)))
+    // Upgrade to WebSocket, agreeing on MessagePack framing and on
+    // permessage-deflate compression when the client offers them. The connection runs on its own task, so the request id is
+    // carried over for its logs.
+    let request_id = request_id
+        .map(|Extension(RequestId(id))| id)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    Ok(ws
+        .protocols(SUPPORTED_SUBPROTOCOLS)
+        .on_upgrade(move |socket| {
+            with_request_id(
+                request_id,
+                handle_websocket_connection(socket, connection_params, state),
+            )
+        }))
 }
 
 /// SSE handler (now implemented)
@@ -209,15 +376,6 @@ async fn graphql_handler_with_auth(
     graphql_handler(auth_context, axum::extract::State(schema), req).await
 }
 
-/// GraphQL subscription handler with authentication
-#[allow(dead_code)]
-async fn graphql_subscription_handler_with_auth(
-    Extension(auth_context): Extension<AuthContext>,
-    axum::extract::State(schema): axum::extract::State<ApiSchema>,
-    ws: WebSocketUpgrade,
-) -> axum::response::Response {
-    graphql_subscription_handler(auth_context, axum::extract::State(schema), ws).await
-}
 
 #[cfg(test)]
 mod tests {
@@ -1,20 +1,788 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use jsonschema::{Draft, JSONSchema};
+use prometheus::{CounterVec, Opts};
 /// Schema validation utilities for ensuring database schema correctness
 /// This module provides validation functions that can be used to verify
 /// database schema compliance without requiring an active database connection
-use std::collections::HashSet;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+use crate::database::Database;
+
+/// How long a topic's schema is served from cache before re-checking the store
+pub const DEFAULT_SCHEMA_SYNC_INTERVAL: Duration = Duration::from_secs(30);
+
+// Schema violations seen on publish, by whether the event was rejected or
+// published anyway
+lazy_static::lazy_static! {
+    static ref SCHEMA_VIOLATIONS: CounterVec = CounterVec::new(
+        Opts::new(
+            "realtime_schema_violations_total",
+            "Published payloads that violated their topic schema"
+        ),
+        &["action"]
+    )
+    .expect("valid metric definition");
+}
+
+/// Counter of schema violations, labelled `rejected` or `accepted`
+pub fn schema_violations_counter() -> &'static CounterVec {
+    &SCHEMA_VIOLATIONS
+}
+
+/// Count a schema violation; `accepted` when the event was published anyway
+pub fn record_schema_violation(accepted: bool) {
+    let action = if accepted { "accepted" } else { "rejected" };
+    SCHEMA_VIOLATIONS.with_label_values(&[action]).inc();
+}
+
+/// How violations of a topic schema are handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaMode {
+    /// Violations reject the event
+    Enforce,
+    /// Violations are reported but the event is accepted
+    Warn,
+}
+
+impl SchemaMode {
+    /// Name stored in the schema registry
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SchemaMode::Enforce => "enforce",
+            SchemaMode::Warn => "warn",
+        }
+    }
+
+    /// Parse a stored mode, treating anything unrecognised as `Enforce`
+    pub fn parse(mode: &str) -> Self {
+        match mode {
+            "warn" => SchemaMode::Warn,
+            _ => SchemaMode::Enforce,
+        }
+    }
+}
+
+/// How a project's publishes are held to their topic schemas
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationMode {
+    /// Each topic's schema mode applies; enforce-mode violations are rejected
+    #[default]
+    Strict,
+    /// Every violation is published, recorded on the event and counted
+    Advisory,
+    /// Payloads aren't checked against topic schemas
+    Off,
+}
+
+impl ValidationMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValidationMode::Strict => "strict",
+            ValidationMode::Advisory => "advisory",
+            ValidationMode::Off => "off",
+        }
+    }
+}
+
+/// Schema registered for a topic within a project
+#[derive(Debug, Clone)]
+pub struct TopicSchema {
+    pub version: u32,
+    pub schema: Value,
+    pub mode: SchemaMode,
+}
+
+/// Outcome of validating an event that passed all enforced checks
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaCheck {
+    /// The payload conforms to the topic's schema
+    Valid,
+    /// No schema is registered for the topic
+    Unregistered,
+    /// The payload violates a warn-mode schema
+    Warning(String),
+}
+
+/// Notification sent whenever a topic schema changes
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaInvalidation {
+    pub project_id: String,
+    pub topic: String,
+    pub version: u32,
+}
+
+/// A single schema violation at a JSON pointer within the payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// JSON pointer to the offending value, e.g. `/items/0/price`
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// A payload that does not conform to a topic schema
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Payload violates schema v{version}: {}", format_violations(.violations))]
+pub struct SchemaValidationError {
+    pub version: u32,
+    pub violations: Vec<SchemaViolation>,
+}
+
+fn format_violations(violations: &[SchemaViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Compatibility required between consecutive schema versions of a topic
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CompatibilityMode {
+    /// Every payload accepted by the old schema is accepted by the new one
+    Backward,
+    /// Every payload accepted by the new schema is accepted by the old one
+    Forward,
+    /// Both backward and forward
+    Full,
+    /// Any change is allowed
+    #[default]
+    None,
+}
+
+impl CompatibilityMode {
+    /// Check a schema update under this mode
+    pub fn check(&self, old: &Value, new: &Value) -> CompatibilityResult {
+        match self {
+            CompatibilityMode::Backward => SchemaValidator::check_compatibility(old, new),
+            CompatibilityMode::Forward => SchemaValidator::check_compatibility(new, old),
+            CompatibilityMode::Full => {
+                let mut result = SchemaValidator::check_compatibility(old, new);
+                for change in SchemaValidator::check_compatibility(new, old).breaking_changes {
+                    if !result.breaking_changes.contains(&change) {
+                        result.breaking_changes.push(change);
+                    }
+                }
+                result
+            }
+            CompatibilityMode::None => CompatibilityResult::default(),
+        }
+    }
+}
+
+/// A change between schema versions that rejects previously accepted payloads
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct BreakingChange {
+    /// JSON pointer into the schema, e.g. `/properties/items/items`
+    pub path: String,
+    pub message: String,
+}
+
+/// Breaking changes found when comparing two schema versions
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct CompatibilityResult {
+    pub breaking_changes: Vec<BreakingChange>,
+}
+
+impl CompatibilityResult {
+    pub fn is_compatible(&self) -> bool {
+        self.breaking_changes.is_empty()
+    }
+
+    fn breaking(&mut self, path: &str, message: String) {
+        self.breaking_changes.push(BreakingChange {
+            path: if path.is_empty() { "/".to_string() } else { path.to_string() },
+            message,
+        });
+    }
+}
+
+// Types a schema admits; `None` means unconstrained
+fn schema_types(schema: &Value) -> Option<HashSet<String>> {
+    match schema.get("type")? {
+        Value::String(t) => Some(HashSet::from([t.clone()])),
+        Value::Array(types) => Some(
+            types
+                .iter()
+                .filter_map(|t| t.as_str().map(str::to_string))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+// Bounds where a larger value in the new schema rejects more payloads
+const LOWER_BOUNDS: [&str; 5] = [
+    "minimum",
+    "exclusiveMinimum",
+    "minLength",
+    "minItems",
+    "minProperties",
+];
+// Bounds where a smaller value in the new schema rejects more payloads
+const UPPER_BOUNDS: [&str; 5] = [
+    "maximum",
+    "exclusiveMaximum",
+    "maxLength",
+    "maxItems",
+    "maxProperties",
+];
+
+// Record everything `new` rejects that `old` accepted at `path`
+fn compare_schemas(old: &Value, new: &Value, path: &str, result: &mut CompatibilityResult) {
+    // `true`/`{}` accept everything; `false` accepts nothing
+    if new == &Value::Bool(true) || old == &Value::Bool(false) {
+        return;
+    }
+    if new == &Value::Bool(false) {
+        result.breaking(path, "schema no longer accepts any value".to_string());
+        return;
+    }
+
+    if let Some(new_types) = schema_types(new) {
+        match schema_types(old) {
+            Some(old_types) => {
+                for old_type in &old_types {
+                    let kept = new_types.contains(old_type)
+                        || (old_type == "integer" && new_types.contains("number"));
+                    if !kept {
+                        result.breaking(path, format!("type `{}` is no longer accepted", old_type));
+                    }
+                }
+            }
+            None => result.breaking(path, "type constraint added".to_string()),
+        }
+    }
+
+    if let Some(Value::Array(new_values)) = new.get("enum") {
+        match old.get("enum") {
+            Some(Value::Array(old_values)) => {
+                for value in old_values.iter().filter(|v| !new_values.contains(v)) {
+                    result.breaking(path, format!("enum value {} was removed", value));
+                }
+            }
+            _ => result.breaking(path, "enum constraint added".to_string()),
+        }
+    }
+
+    if let Some(new_const) = new.get("const") {
+        if old.get("const") != Some(new_const) {
+            result.breaking(path, format!("value is now restricted to {}", new_const));
+        }
+    }
+
+    for bound in LOWER_BOUNDS {
+        if let Some(new_bound) = new.get(bound).and_then(Value::as_f64) {
+            match old.get(bound).and_then(Value::as_f64) {
+                Some(old_bound) if new_bound <= old_bound => {}
+                Some(old_bound) => result.breaking(
+                    path,
+                    format!("`{}` raised from {} to {}", bound, old_bound, new_bound),
+                ),
+                None => result.breaking(path, format!("`{}` of {} added", bound, new_bound)),
+            }
+        }
+    }
+    for bound in UPPER_BOUNDS {
+        if let Some(new_bound) = new.get(bound).and_then(Value::as_f64) {
+            match old.get(bound).and_then(Value::as_f64) {
+                Some(old_bound) if new_bound >= old_bound => {}
+                Some(old_bound) => result.breaking(
+                    path,
+                    format!("`{}` lowered from {} to {}", bound, old_bound, new_bound),
+                ),
+                None => result.breaking(path, format!("`{}` of {} added", bound, new_bound)),
+            }
+        }
+    }
+
+    let required = |schema: &Value| -> HashSet<String> {
+        schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|required| {
+                required
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let old_required = required(old);
+    let new_required = required(new);
+    if let Some(fields) = new.get("required").and_then(Value::as_array) {
+        for field in fields.iter().filter_map(Value::as_str) {
+            if !old_required.contains(field) {
+                result.breaking(path, format!("field `{}` is now required", field));
+            }
+        }
+    }
+
+    let empty = serde_json::Map::new();
+    let old_properties = old.get("properties").and_then(Value::as_object).unwrap_or(&empty);
+    let new_properties = new.get("properties").and_then(Value::as_object).unwrap_or(&empty);
+    let closed = |schema: &Value| schema.get("additionalProperties") == Some(&Value::Bool(false));
+    // What a schema accepts for properties it doesn't list
+    let unconstrained = Value::Bool(true);
+    let additional = |schema: &Value| match schema.get("additionalProperties") {
+        Some(additional @ Value::Object(_)) => Some(additional.clone()),
+        _ => None,
+    };
+
+    for (name, old_property) in old_properties {
+        let property_path = format!("{}/properties/{}", path, name);
+        match new_properties.get(name) {
+            Some(new_property) => {
+                compare_schemas(old_property, new_property, &property_path, result)
+            }
+            None if closed(new) => {
+                result.breaking(&property_path, format!("property `{}` was removed", name))
+            }
+            None => {}
+        }
+    }
+
+    // A property the old schema didn't list was checked against its
+    // `additionalProperties`. Optional additions to an open schema stay
+    // compatible; a newly required one must not narrow the values it allows.
+    if !closed(old) {
+        for (name, new_property) in new_properties {
+            if old_properties.contains_key(name) {
+                continue;
+            }
+            let property_path = format!("{}/properties/{}", path, name);
+            match additional(old) {
+                Some(old_property) => {
+                    compare_schemas(&old_property, new_property, &property_path, result)
+                }
+                None if new_required.contains(name) => {
+                    compare_schemas(&unconstrained, new_property, &property_path, result)
+                }
+                None => {}
+            }
+        }
+
+        // Unlisted properties must still be accepted as before
+        if let Some(new_additional) = additional(new) {
+            let old_additional = additional(old).unwrap_or_else(|| unconstrained.clone());
+            let additional_path = format!("{}/additionalProperties", path);
+            compare_schemas(&old_additional, &new_additional, &additional_path, result);
+        }
+    }
+
+    if closed(new) && !closed(old) {
+        result.breaking(path, "additional properties are no longer allowed".to_string());
+    }
+
+    if let Some(new_items) = new.get("items") {
+        let old_items = old.get("items").unwrap_or(&unconstrained);
+        compare_schemas(old_items, new_items, &format!("{}/items", path), result);
+    }
+}
+
+/// JSON Schema (draft 2020-12) compiled once for repeated validation
+#[derive(Debug)]
+pub struct CompiledSchema {
+    pub version: u32,
+    // Compilation errors are kept so every event on the topic reports them
+    validator: std::result::Result<JSONSchema, String>,
+}
+
+impl CompiledSchema {
+    fn compile(version: u32, schema: &Value) -> Self {
+        let validator = JSONSchema::options()
+            .with_draft(Draft::Draft202012)
+            .compile(schema)
+            .map_err(|e| e.to_string());
+
+        Self { version, validator }
+    }
+
+    fn validate(&self, payload: &Value) -> std::result::Result<(), SchemaValidationError> {
+        let validator = match &self.validator {
+            Ok(validator) => validator,
+            Err(e) => {
+                return Err(SchemaValidationError {
+                    version: self.version,
+                    violations: vec![SchemaViolation {
+                        path: "/".to_string(),
+                        message: format!("schema is invalid: {}", e),
+                    }],
+                })
+            }
+        };
+
+        validator.validate(payload).map_err(|errors| SchemaValidationError {
+            version: self.version,
+            violations: errors
+                .map(|e| {
+                    let path = e.instance_path.to_string();
+                    SchemaViolation {
+                        path: if path.is_empty() { "/".to_string() } else { path },
+                        message: e.to_string(),
+                    }
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Infer a schema requiring each non-null field of an object payload with its observed type
+pub fn infer_schema(payload: &Value) -> Value {
+    let object = match payload.as_object() {
+        Some(object) => object,
+        None => return serde_json::json!({ "type": json_type_name(payload) }),
+    };
+
+    let mut properties = serde_json::Map::new();
+    let mut required = Vec::new();
+    for (field, value) in object {
+        if value.is_null() {
+            continue;
+        }
+        properties.insert(
+            field.clone(),
+            serde_json::json!({ "type": json_type_name(value) }),
+        );
+        required.push(Value::String(field.clone()));
+    }
+
+    serde_json::json!({
+        "type": "object",
+        "required": required,
+        "properties": properties,
+    })
+}
+
+fn json_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::String(_) => "string",
+        Value::Number(_) => "number",
+        Value::Bool(_) => "boolean",
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::Null => "null",
+    }
+}
 
 /// Schema validator for event payloads and database operations
 #[derive(Debug, Clone)]
 pub struct SchemaValidator {
-    // For now, this is a simple validator
-    // In a real implementation, this would contain JSON schemas, etc.
+    // Active schema per (project, topic)
+    schemas: Arc<RwLock<HashMap<(String, String), TopicSchema>>>,
+    // Compiled schemas keyed by (project, topic, version)
+    cache: Arc<RwLock<HashMap<(String, String, u32), Arc<CompiledSchema>>>>,
+    invalidations: broadcast::Sender<SchemaInvalidation>,
+    // Durable registry that schemas are written to and loaded from, if configured
+    store: Option<Database>,
+    // When each (project, topic) was last checked against the store
+    synced_at: Arc<RwLock<HashMap<(String, String), Instant>>>,
+    sync_interval: Duration,
 }
 
 impl SchemaValidator {
     /// Create a new schema validator
     pub fn new() -> Self {
-        Self {}
+        let (invalidations, _) = broadcast::channel(100);
+        Self {
+            schemas: Arc::new(RwLock::new(HashMap::new())),
+            cache: Arc::new(RwLock::new(HashMap::new())),
+            invalidations,
+            store: None,
+            synced_at: Arc::new(RwLock::new(HashMap::new())),
+            sync_interval: DEFAULT_SCHEMA_SYNC_INTERVAL,
+        }
+    }
+
+    /// Back the validator with the database schema registry
+    pub fn with_store(mut self, database: Database) -> Self {
+        self.store = Some(database);
+        self
+    }
+
+    /// Set how long loaded schemas are trusted before re-checking the store
+    pub fn with_sync_interval(mut self, sync_interval: Duration) -> Self {
+        self.sync_interval = sync_interval;
+        self
+    }
+
+    /// Check that a document is itself a valid JSON Schema (draft 2020-12)
+    pub fn check_schema_document(schema: &Value) -> std::result::Result<(), String> {
+        if !schema.is_object() {
+            return Err("Schema must be a JSON object".to_string());
+        }
+        JSONSchema::options()
+            .with_draft(Draft::Draft202012)
+            .compile(schema)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Check that `new` is BACKWARD compatible with `old`: every payload the old
+    /// schema accepted is still accepted, so existing producers keep working
+    pub fn check_compatibility(old: &Value, new: &Value) -> CompatibilityResult {
+        let mut result = CompatibilityResult::default();
+        compare_schemas(old, new, "", &mut result);
+        result
+    }
+
+    /// Persist a new schema version for a topic and make it active. Without a
+    /// store the schema is only registered in memory.
+    pub async fn store_schema(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        topic: &str,
+        schema: Value,
+        mode: SchemaMode,
+    ) -> Result<u32> {
+        let store = match &self.store {
+            Some(store) => store,
+            None => return Ok(self.register_schema_with_mode(project_id, topic, schema, mode)),
+        };
+
+        let version = store
+            .create_topic_schema(tenant_id, project_id, topic, &schema, mode.as_str())
+            .await? as u32;
+        self.activate(project_id, topic, TopicSchema { version, schema, mode });
+        self.mark_synced(project_id, topic);
+        Ok(version)
+    }
+
+    /// Infer a schema from an event payload and persist it in warn mode as the
+    /// topic's first version. When concurrent first events race, the first
+    /// writer wins and the others adopt its schema; returns the active version.
+    pub async fn store_inferred_schema(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        topic: &str,
+        payload: &Value,
+    ) -> Result<u32> {
+        let store = match &self.store {
+            Some(store) => store,
+            None => return Ok(self.register_inferred_schema(project_id, topic, payload)),
+        };
+
+        let schema = infer_schema(payload);
+        let mode = SchemaMode::Warn;
+        let active = match store
+            .create_first_topic_schema(tenant_id, project_id, topic, &schema, mode.as_str())
+            .await?
+        {
+            Some(version) => TopicSchema {
+                version: version as u32,
+                schema,
+                mode,
+            },
+            None => {
+                let record = store
+                    .get_active_topic_schema(project_id, topic)
+                    .await?
+                    .context("Topic schema was deleted while it was being registered")?;
+                TopicSchema {
+                    version: record.version as u32,
+                    schema: record.json_schema,
+                    mode: SchemaMode::parse(&record.mode),
+                }
+            }
+        };
+
+        let version = active.version;
+        self.activate(project_id, topic, active);
+        self.mark_synced(project_id, topic);
+        Ok(version)
+    }
+
+    /// Remove a topic's schema from the store and the cache
+    pub async fn delete_schema(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        topic: &str,
+    ) -> Result<bool> {
+        let deleted = match &self.store {
+            Some(store) => store.delete_topic_schema(tenant_id, project_id, topic).await?,
+            None => false,
+        };
+        let key = (project_id.to_string(), topic.to_string());
+        let removed = self.schemas.write().unwrap().remove(&key);
+        self.cache
+            .write()
+            .unwrap()
+            .retain(|(p, t, _), _| !(p == project_id && t == topic));
+        self.mark_synced(project_id, topic);
+        Ok(deleted || removed.is_some())
+    }
+
+    /// Load the topic's active schema from the store unless the cached copy was
+    /// checked within the sync interval
+    pub async fn sync_topic(&self, project_id: &str, topic: &str) -> Result<()> {
+        let store = match &self.store {
+            Some(store) => store,
+            None => return Ok(()),
+        };
+
+        let key = (project_id.to_string(), topic.to_string());
+        let fresh = self
+            .synced_at
+            .read()
+            .unwrap()
+            .get(&key)
+            .is_some_and(|synced_at| synced_at.elapsed() < self.sync_interval);
+        if fresh {
+            return Ok(());
+        }
+
+        match store.get_active_topic_schema(project_id, topic).await? {
+            Some(record) => {
+                let version = record.version as u32;
+                if self.get_schema(project_id, topic).map(|s| s.version) != Some(version) {
+                    self.activate(
+                        project_id,
+                        topic,
+                        TopicSchema {
+                            version,
+                            schema: record.json_schema,
+                            mode: SchemaMode::parse(&record.mode),
+                        },
+                    );
+                }
+            }
+            None => {
+                // Deleted from the registry by another instance
+                self.schemas.write().unwrap().remove(&key);
+            }
+        }
+
+        self.synced_at.write().unwrap().insert(key, Instant::now());
+        Ok(())
+    }
+
+    // Record that the cached schema for a topic matches the store
+    fn mark_synced(&self, project_id: &str, topic: &str) {
+        self.synced_at
+            .write()
+            .unwrap()
+            .insert((project_id.to_string(), topic.to_string()), Instant::now());
+    }
+
+    // Make a schema the active one for a topic and drop older compilations
+    fn activate(&self, project_id: &str, topic: &str, schema: TopicSchema) {
+        let version = schema.version;
+        self.schemas
+            .write()
+            .unwrap()
+            .insert((project_id.to_string(), topic.to_string()), schema);
+        self.invalidate(project_id, topic, version);
+    }
+
+    /// Register a new schema version for a topic, invalidating any cached compilation
+    pub fn register_schema(&self, project_id: &str, topic: &str, schema: Value) -> u32 {
+        self.register_schema_with_mode(project_id, topic, schema, SchemaMode::Enforce)
+    }
+
+    /// Infer a schema from an event payload and register it in warn mode,
+    /// unless the topic already has a schema; returns the active version
+    pub fn register_inferred_schema(&self, project_id: &str, topic: &str, payload: &Value) -> u32 {
+        let key = (project_id.to_string(), topic.to_string());
+
+        {
+            let mut schemas = self.schemas.write().unwrap();
+            if let Some(existing) = schemas.get(&key) {
+                return existing.version;
+            }
+            schemas.insert(
+                key,
+                TopicSchema {
+                    version: 1,
+                    schema: infer_schema(payload),
+                    mode: SchemaMode::Warn,
+                },
+            );
+        }
+
+        self.invalidate(project_id, topic, 1);
+        1
+    }
+
+    fn register_schema_with_mode(
+        &self,
+        project_id: &str,
+        topic: &str,
+        schema: Value,
+        mode: SchemaMode,
+    ) -> u32 {
+        let key = (project_id.to_string(), topic.to_string());
+
+        let version = {
+            let mut schemas = self.schemas.write().unwrap();
+            let version = schemas.get(&key).map(|s| s.version + 1).unwrap_or(1);
+            schemas.insert(key, TopicSchema { version, schema, mode });
+            version
+        };
+
+        self.invalidate(project_id, topic, version);
+        version
+    }
+
+    /// Get the active schema for a topic
+    pub fn get_schema(&self, project_id: &str, topic: &str) -> Option<TopicSchema> {
+        self.schemas
+            .read()
+            .unwrap()
+            .get(&(project_id.to_string(), topic.to_string()))
+            .cloned()
+    }
+
+    /// Subscribe to schema invalidation notifications
+    pub fn subscribe_invalidations(&self) -> broadcast::Receiver<SchemaInvalidation> {
+        self.invalidations.subscribe()
+    }
+
+    /// Drop cached compilations older than `version` and notify subscribers
+    pub fn invalidate(&self, project_id: &str, topic: &str, version: u32) {
+        self.cache
+            .write()
+            .unwrap()
+            .retain(|(p, t, v), _| !(p == project_id && t == topic && *v < version));
+
+        // No receivers is fine; the cache above is already consistent
+        let _ = self.invalidations.send(SchemaInvalidation {
+            project_id: project_id.to_string(),
+            topic: topic.to_string(),
+            version,
+        });
+    }
+
+    /// Get the compiled form of the active schema, compiling it on first use
+    pub fn compiled_schema(&self, project_id: &str, topic: &str) -> Option<Arc<CompiledSchema>> {
+        let active = self.get_schema(project_id, topic)?;
+        let key = (project_id.to_string(), topic.to_string(), active.version);
+
+        if let Some(compiled) = self.cache.read().unwrap().get(&key) {
+            return Some(compiled.clone());
+        }
+
+        let compiled = Arc::new(CompiledSchema::compile(active.version, &active.schema));
+        self.cache.write().unwrap().insert(key, compiled.clone());
+        Some(compiled)
     }
 
     /// Validate an event payload against a topic schema
@@ -25,7 +793,7 @@ impl SchemaValidator {
         }
 
         // Validate topic format
-        validate_event_structure("", "", topic)
+        validate_topic_format(topic)
             .map_err(|e| anyhow::anyhow!("Topic validation failed: {}", e))?;
 
         // For now, just ensure the payload is a valid JSON object
@@ -37,6 +805,42 @@ impl SchemaValidator {
 
         Ok(())
     }
+
+    /// Validate an event payload against the schema registered for the project's topic.
+    /// Violations of warn-mode schemas are returned as `SchemaCheck::Warning` instead of an error.
+    pub fn validate_project_event(
+        &self,
+        project_id: &str,
+        topic: &str,
+        payload: &serde_json::Value,
+    ) -> Result<SchemaCheck> {
+        self.validate_event_payload(topic, payload)?;
+
+        let mode = match self.get_schema(project_id, topic) {
+            Some(active) => active.mode,
+            None => return Ok(SchemaCheck::Unregistered),
+        };
+
+        match (self.validate(project_id, topic, payload), mode) {
+            (Ok(()), _) => Ok(SchemaCheck::Valid),
+            (Err(e), SchemaMode::Warn) => Ok(SchemaCheck::Warning(e.to_string())),
+            (Err(e), SchemaMode::Enforce) => Err(e.into()),
+        }
+    }
+
+    /// Validate a payload against the topic's active schema, reporting every
+    /// violation with its JSON pointer. Topics without a schema always pass.
+    pub fn validate(
+        &self,
+        project_id: &str,
+        topic: &str,
+        payload: &Value,
+    ) -> std::result::Result<(), SchemaValidationError> {
+        match self.compiled_schema(project_id, topic) {
+            Some(compiled) => compiled.validate(payload),
+            None => Ok(()),
+        }
+    }
 }
 
 impl Default for SchemaValidator {
@@ -135,12 +939,15 @@ pub fn validate_event_structure(
         return Err("Project ID must be at least 8 characters".to_string());
     }
 
-    // Validate topic format
+    validate_topic_format(topic)
+}
+
+/// Validates topic naming (non-empty; alphanumeric, dots, underscores, hyphens)
+pub fn validate_topic_format(topic: &str) -> Result<(), String> {
     if topic.is_empty() {
         return Err("Topic cannot be empty".to_string());
     }
 
-    // Validate topic naming convention (alphanumeric, dots, underscores, hyphens)
     if !topic
         .chars()
         .all(|c| c.is_alphanumeric() || c == '.' || c == '_' || c == '-')
@@ -235,4 +1042,385 @@ mod tests {
         assert!(required_tables.contains("usage_records"));
         assert_eq!(required_tables.len(), 4);
     }
+
+    fn order_schema_v1() -> Value {
+        serde_json::json!({
+            "type": "object",
+            "required": ["order_id"],
+            "properties": {"order_id": {"type": "string"}}
+        })
+    }
+
+    fn order_schema_v2() -> Value {
+        serde_json::json!({
+            "type": "object",
+            "required": ["order_id", "amount"],
+            "properties": {
+                "order_id": {"type": "string"},
+                "amount": {"type": "number"}
+            }
+        })
+    }
+
+    #[test]
+    fn test_schema_update_applies_to_next_validation() {
+        let validator = SchemaValidator::new();
+        let payload = serde_json::json!({"order_id": "o-1"});
+
+        assert_eq!(validator.register_schema("project_456", "order.placed", order_schema_v1()), 1);
+        assert!(validator
+            .validate_project_event("project_456", "order.placed", &payload)
+            .is_ok());
+
+        // Updating the schema must take effect without rebuilding the validator
+        assert_eq!(validator.register_schema("project_456", "order.placed", order_schema_v2()), 2);
+        let err = validator
+            .validate_project_event("project_456", "order.placed", &payload)
+            .unwrap_err();
+        assert!(err.to_string().contains("amount"));
+        assert!(err.to_string().contains("v2"));
+
+        let payload = serde_json::json!({"order_id": "o-1", "amount": 10.5});
+        assert!(validator
+            .validate_project_event("project_456", "order.placed", &payload)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_schema_cache_is_versioned_and_invalidated() {
+        let validator = SchemaValidator::new();
+        let mut invalidations = validator.subscribe_invalidations();
+
+        validator.register_schema("project_456", "order.placed", order_schema_v1());
+        let v1 = validator.compiled_schema("project_456", "order.placed").unwrap();
+        let cached = validator.compiled_schema("project_456", "order.placed").unwrap();
+        assert!(Arc::ptr_eq(&v1, &cached));
+
+        validator.register_schema("project_456", "order.placed", order_schema_v2());
+        let v2 = validator.compiled_schema("project_456", "order.placed").unwrap();
+        assert_eq!(v2.version, 2);
+        assert!(!validator
+            .cache
+            .read()
+            .unwrap()
+            .contains_key(&("project_456".to_string(), "order.placed".to_string(), 1)));
+
+        assert_eq!(invalidations.try_recv().unwrap().version, 1);
+        assert_eq!(
+            invalidations.try_recv().unwrap(),
+            SchemaInvalidation {
+                project_id: "project_456".to_string(),
+                topic: "order.placed".to_string(),
+                version: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_violations_report_json_pointer_paths() {
+        let validator = SchemaValidator::new();
+        validator.register_schema(
+            "project_456",
+            "cart.updated",
+            serde_json::json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "properties": {
+                    "items": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {"price": {"type": "number"}},
+                            "required": ["price"]
+                        }
+                    }
+                }
+            }),
+        );
+
+        let payload = serde_json::json!({"items": [{"price": "free"}, {}]});
+        let err = validator
+            .validate("project_456", "cart.updated", &payload)
+            .unwrap_err();
+
+        assert_eq!(err.version, 1);
+        let paths: Vec<&str> = err.violations.iter().map(|v| v.path.as_str()).collect();
+        assert!(paths.contains(&"/items/0/price"));
+        assert!(paths.contains(&"/items/1"));
+        assert!(err.to_string().contains("/items/0/price: "));
+    }
+
+    #[test]
+    fn test_invalid_schema_rejects_events() {
+        let validator = SchemaValidator::new();
+        validator.register_schema(
+            "project_456",
+            "order.placed",
+            serde_json::json!({"type": "not-a-type"}),
+        );
+
+        let err = validator
+            .validate_project_event("project_456", "order.placed", &serde_json::json!({}))
+            .unwrap_err();
+        assert!(err.to_string().contains("schema is invalid"));
+    }
+
+    #[test]
+    fn test_schemas_are_scoped_per_project() {
+        let validator = SchemaValidator::new();
+        validator.register_schema("project_456", "order.placed", order_schema_v2());
+
+        let payload = serde_json::json!({"order_id": "o-1"});
+        assert!(validator
+            .validate_project_event("project_456", "order.placed", &payload)
+            .is_err());
+        assert!(validator
+            .validate_project_event("project_789", "order.placed", &payload)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_infer_schema_from_payload() {
+        let schema = infer_schema(&serde_json::json!({
+            "order_id": "o-1",
+            "amount": 10,
+            "paid": true,
+            "note": null
+        }));
+
+        assert_eq!(schema["properties"]["order_id"]["type"], "string");
+        assert_eq!(schema["properties"]["amount"]["type"], "number");
+        assert_eq!(schema["properties"]["paid"]["type"], "boolean");
+        assert!(schema["properties"].get("note").is_none());
+        assert_eq!(schema["required"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_inferred_schema_warns_instead_of_rejecting() {
+        let validator = SchemaValidator::new();
+        let first = serde_json::json!({"order_id": "o-1", "amount": 10});
+
+        assert_eq!(
+            validator
+                .validate_project_event("project_456", "order.placed", &first)
+                .unwrap(),
+            SchemaCheck::Unregistered
+        );
+        assert_eq!(validator.register_inferred_schema("project_456", "order.placed", &first), 1);
+        assert_eq!(
+            validator.get_schema("project_456", "order.placed").unwrap().mode,
+            SchemaMode::Warn
+        );
+
+        let conforming = serde_json::json!({"order_id": "o-2", "amount": 12.5});
+        assert_eq!(
+            validator
+                .validate_project_event("project_456", "order.placed", &conforming)
+                .unwrap(),
+            SchemaCheck::Valid
+        );
+
+        let violating = serde_json::json!({"order_id": 42});
+        match validator
+            .validate_project_event("project_456", "order.placed", &violating)
+            .unwrap()
+        {
+            SchemaCheck::Warning(message) => assert!(message.contains("amount")),
+            other => panic!("Expected a warning, got {:?}", other),
+        }
+
+        // A racing first event keeps the schema that was registered first
+        let racing = serde_json::json!({"sku": "s-1"});
+        assert_eq!(validator.register_inferred_schema("project_456", "order.placed", &racing), 1);
+        let schema = validator.get_schema("project_456", "order.placed").unwrap();
+        assert!(schema.schema["properties"].get("order_id").is_some());
+    }
+
+    #[test]
+    fn test_check_schema_document_rejects_invalid_schemas() {
+        let valid = serde_json::json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}}
+        });
+        assert!(SchemaValidator::check_schema_document(&valid).is_ok());
+
+        assert!(SchemaValidator::check_schema_document(&serde_json::json!("string")).is_err());
+        assert!(
+            SchemaValidator::check_schema_document(&serde_json::json!({"type": "not-a-type"}))
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_store_schema_without_store_registers_in_memory() {
+        let validator = SchemaValidator::new();
+        let schema = serde_json::json!({"type": "object"});
+
+        let version = validator
+            .store_schema("tenant_123", "project_456", "user.created", schema, SchemaMode::Enforce)
+            .await
+            .unwrap();
+        assert_eq!(version, 1);
+
+        validator.sync_topic("project_456", "user.created").await.unwrap();
+        assert_eq!(validator.get_schema("project_456", "user.created").unwrap().version, 1);
+    }
+
+    #[test]
+    fn test_compatibility_rejects_added_required_field() {
+        let old = serde_json::json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}},
+            "required": ["id"]
+        });
+        let new = serde_json::json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}, "email": {"type": "string"}},
+            "required": ["id", "email"]
+        });
+
+        let result = SchemaValidator::check_compatibility(&old, &new);
+        assert!(!result.is_compatible());
+        assert_eq!(result.breaking_changes[0].path, "/");
+        assert!(result.breaking_changes[0].message.contains("email"));
+
+        // Adding the field as optional is compatible
+        let optional = serde_json::json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}, "email": {"type": "string"}},
+            "required": ["id"]
+        });
+        assert!(SchemaValidator::check_compatibility(&old, &optional).is_compatible());
+    }
+
+    #[test]
+    fn test_compatibility_checks_constraints_of_added_properties() {
+        let old = serde_json::json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}},
+            "required": ["id"]
+        });
+
+        // A new required field also narrows the values old payloads may carry
+        let new = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "id": {"type": "string"},
+                "status": {"type": "string", "enum": ["open", "closed"]}
+            },
+            "required": ["id", "status"]
+        });
+        let result = SchemaValidator::check_compatibility(&old, &new);
+        let messages: Vec<(&str, &str)> = result
+            .breaking_changes
+            .iter()
+            .map(|c| (c.path.as_str(), c.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                ("/", "field `status` is now required"),
+                ("/properties/status", "type constraint added"),
+                ("/properties/status", "enum constraint added"),
+            ]
+        );
+
+        // Properties the old schema constrained through `additionalProperties`
+        // keep those constraints when they are listed
+        let old = serde_json::json!({
+            "type": "object",
+            "additionalProperties": {"type": "string"}
+        });
+        let listed = serde_json::json!({
+            "type": "object",
+            "properties": {"note": {"type": "string", "maxLength": 10}},
+            "additionalProperties": {"type": "string"}
+        });
+        let result = SchemaValidator::check_compatibility(&old, &listed);
+        assert_eq!(result.breaking_changes.len(), 1);
+        assert_eq!(result.breaking_changes[0].path, "/properties/note");
+
+        // Tightening `additionalProperties` or adding `items` is breaking too
+        let tightened = serde_json::json!({
+            "type": "object",
+            "additionalProperties": {"type": "integer"}
+        });
+        let result = SchemaValidator::check_compatibility(&old, &tightened);
+        assert_eq!(result.breaking_changes[0].path, "/additionalProperties");
+
+        let items = serde_json::json!({"type": "array", "items": {"type": "string"}});
+        let result =
+            SchemaValidator::check_compatibility(&serde_json::json!({"type": "array"}), &items);
+        assert_eq!(result.breaking_changes[0].path, "/items");
+    }
+
+    #[test]
+    fn test_compatibility_of_removed_property_depends_on_mode() {
+        let old = serde_json::json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}, "legacy": {"type": "string"}},
+            "required": ["id", "legacy"]
+        });
+        let new = serde_json::json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}},
+            "required": ["id"]
+        });
+
+        // Old payloads still carry the field, which the new schema ignores
+        assert!(CompatibilityMode::Backward.check(&old, &new).is_compatible());
+
+        // New producers may drop the field, which old consumers require
+        let forward = CompatibilityMode::Forward.check(&old, &new);
+        assert!(!forward.is_compatible());
+        assert!(forward.breaking_changes[0].message.contains("legacy"));
+        assert!(!CompatibilityMode::Full.check(&old, &new).is_compatible());
+
+        // Closing the object makes the removal break existing producers too
+        let closed = serde_json::json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}},
+            "required": ["id"],
+            "additionalProperties": false
+        });
+        let result = CompatibilityMode::Backward.check(&old, &closed);
+        assert!(result
+            .breaking_changes
+            .iter()
+            .any(|change| change.path == "/properties/legacy"));
+    }
+
+    #[test]
+    fn test_compatibility_rejects_type_changes() {
+        let old = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "amount": {"type": "integer"},
+                "status": {"enum": ["open", "closed"]}
+            }
+        });
+
+        // Widening integer to number is compatible
+        let widened = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "amount": {"type": "number"},
+                "status": {"enum": ["open", "closed", "pending"]}
+            }
+        });
+        assert!(SchemaValidator::check_compatibility(&old, &widened).is_compatible());
+
+        let changed = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "amount": {"type": "string"},
+                "status": {"enum": ["open"]}
+            }
+        });
+        let result = SchemaValidator::check_compatibility(&old, &changed);
+        let paths: Vec<&str> = result.breaking_changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["/properties/amount", "/properties/status"]);
+
+        assert!(CompatibilityMode::None.check(&old, &changed).is_compatible());
+    }
 }
@@ -0,0 +1,217 @@
+use std::env;
+use std::fmt;
+
+/// Secret store a `SecretRef` points into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretStore {
+    /// `vault://<path>#<key>`
+    Vault,
+    /// `awssm://<name>`, or `awssm://<name>#<key>` for a field of a JSON secret
+    AwsSecretsManager,
+}
+
+impl SecretStore {
+    fn scheme(self) -> &'static str {
+        match self {
+            Self::Vault => "vault",
+            Self::AwsSecretsManager => "awssm",
+        }
+    }
+}
+
+/// A config value naming a secret held elsewhere instead of the secret itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretRef {
+    pub store: SecretStore,
+    /// Vault secret path or Secrets Manager secret name
+    pub path: String,
+    /// Field within the secret
+    pub key: Option<String>,
+}
+
+impl SecretRef {
+    /// Parse `value` as a secret reference. Values without a secret store
+    /// scheme are literal secrets and give `None`.
+    pub fn parse(value: &str) -> Result<Option<Self>, SecretError> {
+        let Some((scheme, rest)) = value.split_once("://") else {
+            return Ok(None);
+        };
+        let store = match scheme {
+            "vault" => SecretStore::Vault,
+            "awssm" => SecretStore::AwsSecretsManager,
+            _ => return Ok(None),
+        };
+        let invalid = |reason| SecretError::InvalidReference {
+            reference: value.to_string(),
+            reason,
+        };
+
+        let (path, key) = match rest.split_once('#') {
+            Some((_, "")) => return Err(invalid("the key after # is empty")),
+            Some((path, key)) => (path, Some(key.to_string())),
+            None => (rest, None),
+        };
+        if path.is_empty() {
+            return Err(invalid("the secret path is empty"));
+        }
+        if store == SecretStore::Vault && key.is_none() {
+            return Err(invalid("vault references need a #key"));
+        }
+
+        Ok(Some(Self {
+            store,
+            path: path.to_string(),
+            key,
+        }))
+    }
+}
+
+impl fmt::Display for SecretRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}://{}", self.store.scheme(), self.path)?;
+        if let Some(key) = &self.key {
+            write!(f, "#{}", key)?;
+        }
+        Ok(())
+    }
+}
+
+/// Why a secret reference in the config couldn't be resolved
+#[derive(Debug, thiserror::Error)]
+pub enum SecretError {
+    #[error("Invalid secret reference {reference}: {reason}")]
+    InvalidReference {
+        reference: String,
+        reason: &'static str,
+    },
+    #[error("Secret {reference} for {field} was not found")]
+    NotFound {
+        field: &'static str,
+        reference: String,
+    },
+    #[error("Failed to fetch secret {reference} for {field}: {error}")]
+    Unavailable {
+        field: &'static str,
+        reference: String,
+        error: String,
+    },
+}
+
+/// Looks up the secrets config values reference. Called once while the config
+/// is loaded at startup, so implementations may block.
+pub trait SecretProvider: Send + Sync {
+    /// The secret `reference` points to, or `None` if it doesn't exist
+    fn fetch(&self, reference: &SecretRef) -> anyhow::Result<Option<String>>;
+}
+
+/// Resolves each reference from an environment variable named after it, for
+/// deployments where an agent or orchestrator injects secrets into the
+/// environment: `vault://secret/realtime#jwt_secret` is read from
+/// `VAULT_SECRET_REALTIME_JWT_SECRET`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvSecretProvider;
+
+impl EnvSecretProvider {
+    /// Environment variable holding the secret `reference` points to
+    pub fn variable_name(reference: &SecretRef) -> String {
+        let mut name = format!("{}_{}", reference.store.scheme(), reference.path);
+        if let Some(key) = &reference.key {
+            name.push('_');
+            name.push_str(key);
+        }
+        name.chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_uppercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+}
+
+impl SecretProvider for EnvSecretProvider {
+    fn fetch(&self, reference: &SecretRef) -> anyhow::Result<Option<String>> {
+        Ok(env::var(Self::variable_name(reference)).ok())
+    }
+}
+
+/// Replace `value` with the secret it references through `provider`; literal
+/// values are left as they are. `field` names the setting in errors.
+pub fn resolve_secret(
+    provider: &dyn SecretProvider,
+    field: &'static str,
+    value: &mut String,
+) -> Result<(), SecretError> {
+    let Some(reference) = SecretRef::parse(value)? else {
+        return Ok(());
+    };
+
+    match provider.fetch(&reference) {
+        Ok(Some(secret)) => {
+            *value = secret;
+            Ok(())
+        }
+        Ok(None) => Err(SecretError::NotFound {
+            field,
+            reference: reference.to_string(),
+        }),
+        Err(e) => Err(SecretError::Unavailable {
+            field,
+            reference: reference.to_string(),
+            error: e.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_references() {
+        assert_eq!(SecretRef::parse("plain-secret").unwrap(), None);
+        assert_eq!(
+            SecretRef::parse("postgresql://app:pw@db:5432/realtime").unwrap(),
+            None
+        );
+
+        let vault = SecretRef::parse("vault://secret/realtime#jwt_secret")
+            .unwrap()
+            .unwrap();
+        assert_eq!(vault.store, SecretStore::Vault);
+        assert_eq!(vault.path, "secret/realtime");
+        assert_eq!(vault.key.as_deref(), Some("jwt_secret"));
+        assert_eq!(vault.to_string(), "vault://secret/realtime#jwt_secret");
+
+        let aws = SecretRef::parse("awssm://prod/realtime-db")
+            .unwrap()
+            .unwrap();
+        assert_eq!(aws.store, SecretStore::AwsSecretsManager);
+        assert_eq!(aws.key, None);
+
+        assert!(SecretRef::parse("vault://secret/realtime").is_err());
+        assert!(SecretRef::parse("vault://#jwt_secret").is_err());
+        assert!(SecretRef::parse("awssm://prod/realtime-db#").is_err());
+    }
+
+    #[test]
+    fn test_env_provider_variable_name() {
+        let reference = SecretRef::parse("vault://secret/realtime#jwt-secret")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            EnvSecretProvider::variable_name(&reference),
+            "VAULT_SECRET_REALTIME_JWT_SECRET"
+        );
+
+        let reference = SecretRef::parse("awssm://prod/realtime-db")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            EnvSecretProvider::variable_name(&reference),
+            "AWSSM_PROD_REALTIME_DB"
+        );
+    }
+}
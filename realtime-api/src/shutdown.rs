@@ -0,0 +1,68 @@
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+use crate::event_service::EventService;
+use crate::sse::{sse_manager, SSEManager};
+use crate::websocket::{websocket_manager, WebSocketManager};
+
+/// Close code sent to streaming clients when the server shuts down (service restart)
+pub const SHUTDOWN_CLOSE_CODE: u16 = 1012;
+
+/// Close reason telling streaming clients to reconnect, resuming from their cursor
+pub const SHUTDOWN_CLOSE_REASON: &str = "server_restarting";
+
+/// Close every WebSocket and SSE connection with a reconnect hint.
+/// Returns the ids of the closed connections.
+pub fn close_streaming_connections(websockets: &WebSocketManager, sse: &SSEManager) -> Vec<String> {
+    let mut closed = websockets.close_all_connections(SHUTDOWN_CLOSE_CODE, SHUTDOWN_CLOSE_REASON);
+    closed.extend(sse.close_all_connections(SHUTDOWN_CLOSE_CODE, SHUTDOWN_CLOSE_REASON));
+    closed
+}
+
+/// Resolve once `signal` fires, after closing all streaming connections so
+/// they don't hold the server open. `started` is notified when draining begins.
+pub async fn on_shutdown_signal(signal: impl Future<Output = ()>, started: oneshot::Sender<()>) {
+    signal.await;
+
+    let closed = close_streaming_connections(websocket_manager(), sse_manager());
+    info!("Closed {} streaming connections for shutdown", closed.len());
+    let _ = started.send(());
+}
+
+/// Run `server` until it exits. Once shutdown has `started`, in-flight requests
+/// and buffered NATS publishes get `grace_period` in total to complete.
+pub async fn serve_with_grace_period<S>(
+    server: S,
+    started: oneshot::Receiver<()>,
+    grace_period: Duration,
+    event_service: &EventService,
+) -> std::io::Result<()>
+where
+    S: Future<Output = std::io::Result<()>> + Send + 'static,
+{
+    let mut server = tokio::spawn(server);
+
+    let deadline = tokio::select! {
+        result = &mut server => return result.map_err(std::io::Error::other)?,
+        Ok(()) = started => tokio::time::Instant::now() + grace_period,
+    };
+
+    match tokio::time::timeout_at(deadline, &mut server).await {
+        Ok(result) => result.map_err(std::io::Error::other)??,
+        Err(_) => {
+            warn!(
+                "Shutdown grace period of {:?} elapsed with requests in flight",
+                grace_period
+            );
+            server.abort();
+        }
+    }
+
+    let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+    if event_service.nats_client().flush_pending(remaining).await {
+        info!("Flushed pending NATS publishes");
+    }
+    Ok(())
+}
@@ -0,0 +1,270 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use crate::models::Event;
+use crate::websocket::topic_matches;
+
+/// Partition keys kept per tenant, project and topic unless configured otherwise
+pub const DEFAULT_MAX_KEYS_PER_TOPIC: usize = 10_000;
+
+/// Latest-state store for topics with `snapshot_on_subscribe` enabled.
+/// Keeps the most recent event per partition key so new subscribers can be
+/// brought up to date before live delivery starts. Each topic holds at most
+/// `max_keys_per_topic` keys; the least recently updated key is evicted first.
+#[derive(Debug)]
+pub struct SnapshotStore {
+    // topic -> payload field used as the partition key
+    key_fields: HashMap<String, String>,
+    max_keys_per_topic: usize,
+    // (tenant, project, topic) -> latest state of that topic
+    states: Mutex<HashMap<(String, String, String), TopicState>>,
+}
+
+/// Latest event per partition key of one topic, with the keys in update order
+#[derive(Debug, Default)]
+struct TopicState {
+    // partition key -> (update sequence, latest event)
+    latest: HashMap<String, (u64, Event)>,
+    // update sequence -> partition key, least recently updated first
+    recency: BTreeMap<u64, String>,
+    next_sequence: u64,
+}
+
+impl TopicState {
+    /// Store `event` as the latest state of `key`, evicting the least recently
+    /// updated keys beyond `max_keys`
+    fn insert(&mut self, key: String, event: Event, max_keys: usize) {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+
+        if let Some((previous, _)) = self.latest.insert(key.clone(), (sequence, event)) {
+            self.recency.remove(&previous);
+        }
+        self.recency.insert(sequence, key);
+
+        while self.latest.len() > max_keys {
+            let Some((_, evicted)) = self.recency.pop_first() else {
+                break;
+            };
+            self.latest.remove(&evicted);
+        }
+    }
+}
+
+impl Default for SnapshotStore {
+    fn default() -> Self {
+        Self::new(HashMap::new(), DEFAULT_MAX_KEYS_PER_TOPIC)
+    }
+}
+
+impl SnapshotStore {
+    /// Create a store for the given topic -> partition key field mapping,
+    /// keeping at most `max_keys_per_topic` keys per tenant, project and topic
+    pub fn new(key_fields: HashMap<String, String>, max_keys_per_topic: usize) -> Self {
+        Self {
+            key_fields,
+            max_keys_per_topic: max_keys_per_topic.max(1),
+            states: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Check if a topic delivers a snapshot on subscribe
+    pub fn is_enabled(&self, topic: &str) -> bool {
+        self.key_fields.contains_key(topic)
+    }
+
+    /// Extract the partition key of an event on a snapshot-enabled topic
+    fn partition_key(&self, event: &Event) -> Option<String> {
+        let field = self.key_fields.get(&event.topic)?;
+        match event.payload.get(field)? {
+            serde_json::Value::String(key) => Some(key.clone()),
+            serde_json::Value::Number(key) => Some(key.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Compact a published event into the topic's latest state
+    pub fn record(&self, event: &Event) {
+        let Some(key) = self.partition_key(event) else {
+            return;
+        };
+
+        let mut states = self.states.lock().unwrap();
+        states
+            .entry((
+                event.tenant_id.clone(),
+                event.project_id.clone(),
+                event.topic.clone(),
+            ))
+            .or_default()
+            .insert(key, event.clone(), self.max_keys_per_topic);
+    }
+
+    /// Latest event per partition key for every snapshot topic matching the
+    /// subscription (an empty subscription matches all topics), oldest first
+    pub fn snapshot(&self, tenant_id: &str, project_id: &str, topics: &[String]) -> Vec<Event> {
+        let states = self.states.lock().unwrap();
+        Self::collect(&states, tenant_id, project_id, topics)
+    }
+
+    /// Run `register` with the current snapshot while holding the store lock, so
+    /// no update can land between reading the snapshot and registering for live
+    /// delivery. An update racing the subscription may be seen twice, never missed.
+    pub fn deliver_snapshot<T>(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        topics: &[String],
+        register: impl FnOnce(Vec<Event>) -> T,
+    ) -> T {
+        let states = self.states.lock().unwrap();
+        let events = Self::collect(&states, tenant_id, project_id, topics);
+        register(events)
+    }
+
+    fn collect(
+        states: &HashMap<(String, String, String), TopicState>,
+        tenant_id: &str,
+        project_id: &str,
+        topics: &[String],
+    ) -> Vec<Event> {
+        let mut events: Vec<Event> = states
+            .iter()
+            .filter(|((tenant, project, topic), _)| {
+                tenant == tenant_id
+                    && project == project_id
+                    && topic_matches(topics, topic)
+            })
+            .flat_map(|(_, state)| state.latest.values().map(|(_, event)| event.clone()))
+            .collect();
+
+        events.sort_by(|a, b| a.published_at.cmp(&b.published_at));
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::websocket::{WebSocketConnection, WebSocketManager, WebSocketMessage};
+    use serde_json::json;
+    use tokio::sync::broadcast;
+
+    fn store() -> SnapshotStore {
+        let mut key_fields = HashMap::new();
+        key_fields.insert("presence".to_string(), "user_id".to_string());
+        SnapshotStore::new(key_fields, DEFAULT_MAX_KEYS_PER_TOPIC)
+    }
+
+    fn presence(user_id: &str, status: &str) -> Event {
+        Event::new(
+            "tenant_1".to_string(),
+            "project_1".to_string(),
+            "presence".to_string(),
+            json!({"user_id": user_id, "status": status}),
+        )
+    }
+
+    #[test]
+    fn test_snapshot_keeps_latest_state_per_key() {
+        let store = store();
+        store.record(&presence("alice", "online"));
+        store.record(&presence("bob", "online"));
+        store.record(&presence("alice", "away"));
+
+        let snapshot = store.snapshot("tenant_1", "project_1", &["presence".to_string()]);
+        assert_eq!(snapshot.len(), 2);
+
+        let alice = snapshot
+            .iter()
+            .find(|e| e.payload["user_id"] == "alice")
+            .unwrap();
+        assert_eq!(alice.payload["status"], "away");
+
+        // Other tenants never see this state
+        assert!(store.snapshot("tenant_2", "project_1", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_least_recently_updated_key_is_evicted_at_the_cap() {
+        let mut key_fields = HashMap::new();
+        key_fields.insert("presence".to_string(), "user_id".to_string());
+        let store = SnapshotStore::new(key_fields, 2);
+
+        store.record(&presence("alice", "online"));
+        store.record(&presence("bob", "online"));
+        // Updating alice makes bob the least recently updated key
+        store.record(&presence("alice", "away"));
+        store.record(&presence("carol", "online"));
+
+        let mut users: Vec<_> = store
+            .snapshot("tenant_1", "project_1", &[])
+            .into_iter()
+            .map(|event| event.payload["user_id"].as_str().unwrap().to_string())
+            .collect();
+        users.sort();
+        assert_eq!(users, vec!["alice", "carol"]);
+    }
+
+    #[test]
+    fn test_topics_without_snapshot_are_not_recorded() {
+        let store = store();
+        store.record(&Event::new(
+            "tenant_1".to_string(),
+            "project_1".to_string(),
+            "chat.message".to_string(),
+            json!({"user_id": "alice"}),
+        ));
+
+        assert!(!store.is_enabled("chat.message"));
+        assert!(store.snapshot("tenant_1", "project_1", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_new_subscriber_gets_snapshot_then_live_changes() {
+        let store = store();
+        let manager = WebSocketManager::new();
+        store.record(&presence("alice", "online"));
+        store.record(&presence("bob", "online"));
+
+        let (sender, mut receiver) = broadcast::channel(10);
+        let connection = WebSocketConnection {
+            id: "conn_1".to_string(),
+            tenant_id: "tenant_1".to_string(),
+            project_id: "project_1".to_string(),
+            subscribed_topics: vec!["presence".to_string()],
+            sender: sender.clone(),
+            created_at: chrono::Utc::now(),
+            acked: false,
+        };
+
+        store
+            .deliver_snapshot("tenant_1", "project_1", &connection.subscribed_topics.clone(), |events| {
+                for event in &events {
+                    let _ = sender.send(WebSocketMessage::from(event));
+                }
+                manager.add_connection(connection)
+            })
+            .unwrap();
+
+        // A live update after subscribing
+        let live = presence("alice", "offline");
+        store.record(&live);
+        for conn in manager.get_connections_for_event("tenant_1", "project_1", "presence") {
+            let _ = conn.sender.send(WebSocketMessage::from(&live));
+        }
+
+        let mut statuses = Vec::new();
+        while let Ok(WebSocketMessage::Event { payload, .. }) = receiver.try_recv() {
+            statuses.push(format!("{}:{}", payload["user_id"], payload["status"]));
+        }
+        assert_eq!(
+            statuses,
+            vec![
+                "\"alice\":\"online\"",
+                "\"bob\":\"online\"",
+                "\"alice\":\"offline\""
+            ]
+        );
+    }
+}
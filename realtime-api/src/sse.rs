@@ -1,30 +1,168 @@
 use anyhow::Result;
 use axum::{
-    extract::{Query, State},
+    extract::{ConnectInfo, Query, State},
     http::{HeaderMap, StatusCode},
+    Extension, Json,
     response::{
-        sse::{Event, KeepAlive, Sse},
+        sse::{Event, Sse},
         IntoResponse, Response,
     },
 };
 use futures_util::{stream::{self, Stream}, StreamExt};
+use prometheus::Gauge;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::convert::Infallible;
+use std::net::SocketAddr;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::sync::broadcast;
 use tracing::{debug, error, info, warn};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
-use crate::api::AppState;
+use crate::api::{AppState, ErrorResponse};
 use crate::auth::{extract_auth_header, AuthContext, AuthError};
-use crate::models::{Event as EventModel, Scope, UsageMetric, UsageRecord};
+use crate::backpressure::{
+    close_lagged_connections_enabled, delivery_buffer_capacity, next_delivery,
+    spawn_acked_delivery, Delivery, DEFAULT_DELIVERY_BUFFER_SIZE, EVENTS_DROPPED_CODE,
+    LAGGED_CLOSE_CODE, LAGGED_CLOSE_REASON,
+};
+use crate::circuit_breaker::{circuit_open, circuit_open_response};
+use crate::cloudevents::{DeliveryFormat, EventEncoding};
+use crate::db_retry::is_pool_exhausted;
+use crate::drain::{prune_closed_connections_enabled, record_closed_connection_pruned};
+use crate::idle::{
+    idle_timeout, record_idle_connection_closed, sse_keep_alive_interval, ActivityTracker,
+    DEFAULT_IDLE_TIMEOUT_SECS, IDLE_CLOSE_CODE, IDLE_CLOSE_REASON,
+};
+use crate::models::{Event as EventModel, Project, Scope, UsageMetric, UsageRecord};
+use crate::mtls::ClientCertificate;
+use crate::observability::sse_connections_gauge;
+use crate::nats::{EventCursor, ReplayRequest, SubscriptionConfig};
+use crate::ordering::ordering_verifier;
+use crate::project_archive::{PROJECT_ARCHIVED_CLOSE_CODE, PROJECT_ARCHIVED_CLOSE_REASON};
+use crate::rate_limit::overloaded_response;
+use crate::request_id::current_request_id;
+use crate::websocket::topic_matches;
 
 /// SSE connection query parameters
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SSEQuery {
     pub topics: Option<String>, // Comma-separated list of topics
+    /// `native` (default) or `cloudevents`
+    #[serde(default)]
+    pub format: DeliveryFormat,
+    /// Redeliver events the connection can't keep up with instead of dropping them
+    #[serde(default)]
+    pub acked: bool,
+}
+
+/// Body for establishing an SSE subscription via POST, for topic sets too large for a URL
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SSESubscribeRequest {
+    pub topics: Vec<String>,
+    /// `native` (default) or `cloudevents`
+    #[serde(default)]
+    pub format: DeliveryFormat,
+    /// Redeliver events the connection can't keep up with instead of dropping them
+    #[serde(default)]
+    pub acked: bool,
+}
+
+/// Maximum number of topics accepted in the `topics` query parameter
+pub const MAX_QUERY_TOPICS: usize = 50;
+/// Maximum total length of the decoded `topics` query parameter
+pub const MAX_QUERY_TOPICS_LENGTH: usize = 2048;
+/// Maximum number of topics accepted in a POST subscription body
+pub const MAX_BODY_TOPICS: usize = 1000;
+/// Maximum total length of all topics in a POST subscription body
+pub const MAX_BODY_TOPICS_LENGTH: usize = 64 * 1024;
+
+/// Bounds applied to a requested topic list
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TopicListLimits {
+    pub max_topics: usize,
+    pub max_total_length: usize,
+}
+
+impl TopicListLimits {
+    /// Limits for topics passed in the query string
+    pub const QUERY: TopicListLimits = TopicListLimits {
+        max_topics: MAX_QUERY_TOPICS,
+        max_total_length: MAX_QUERY_TOPICS_LENGTH,
+    };
+
+    /// Limits for topics passed in a POST body
+    pub const BODY: TopicListLimits = TopicListLimits {
+        max_topics: MAX_BODY_TOPICS,
+        max_total_length: MAX_BODY_TOPICS_LENGTH,
+    };
+
+    /// Check a topic list against these limits
+    pub fn check(&self, topics: &[String]) -> Result<(), String> {
+        if topics.len() > self.max_topics {
+            return Err(format!(
+                "{} topics requested; at most {} are allowed",
+                topics.len(),
+                self.max_topics
+            ));
+        }
+
+        let total_length: usize = topics.iter().map(String::len).sum();
+        if total_length > self.max_total_length {
+            return Err(format!(
+                "Topic list is {} characters; at most {} are allowed",
+                total_length, self.max_total_length
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse the comma-separated `topics` query parameter, enforcing the query limits
+pub fn parse_query_topics(raw: Option<&str>) -> Result<Vec<String>, String> {
+    let raw = match raw {
+        Some(raw) => raw,
+        None => return Ok(Vec::new()),
+    };
+
+    // Reject before splitting so huge inputs are never fully materialised
+    if raw.len() > MAX_QUERY_TOPICS_LENGTH + MAX_QUERY_TOPICS {
+        return Err(format!(
+            "Topic list is {} characters; at most {} are allowed",
+            raw.len(),
+            MAX_QUERY_TOPICS_LENGTH
+        ));
+    }
+
+    let topics: Vec<String> = raw
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    TopicListLimits::QUERY.check(&topics)?;
+    Ok(topics)
+}
+
+/// 400 response describing an oversized topic list and the limits that apply
+pub fn topic_list_error_response(message: &str, limits: TopicListLimits) -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(ErrorResponse::new(
+            "TOPIC_LIST_TOO_LARGE",
+            message,
+            Some(serde_json::json!({
+                "max_topics": limits.max_topics,
+                "max_total_length": limits.max_total_length,
+                "hint": "Use POST /sse with a JSON body for large topic sets",
+            })),
+        )),
+    )
+        .into_response()
 }
 
 /// SSE connection parameters
@@ -34,6 +172,13 @@ pub struct SSEConnectionParams {
     pub project_id: String,
     pub topics: Vec<String>,
     pub auth_context: AuthContext,
+    /// `Last-Event-ID` sent by a reconnecting client
+    pub last_event_id: Option<String>,
+    /// Encoding of delivered events
+    pub format: DeliveryFormat,
+    /// Deliver through an acking consumer, redelivering events the
+    /// connection's buffer has no room for instead of dropping them
+    pub acked: bool,
 }
 
 /// SSE message types
@@ -46,6 +191,22 @@ pub enum SSEMessage {
         topic: String,
         payload: serde_json::Value,
         published_at: String,
+        /// Stream sequence, present for live deliveries
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sequence: Option<u64>,
+        /// Ordering key the event was published with
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        partition_key: Option<String>,
+
+        /// Producer headers the event was published with
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        headers: HashMap<String, String>,
+        /// Producer metadata the event was published with
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        metadata: HashMap<String, String>,
+        /// Attributes the event was published with
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        attributes: HashMap<String, String>,
     },
     /// Connection acknowledgment
     Connected {
@@ -59,6 +220,44 @@ pub enum SSEMessage {
     Heartbeat {
         timestamp: String,
     },
+    /// Server-initiated close with a structured reason
+    Close {
+        code: u16,
+        reason: String,
+    },
+}
+
+impl From<&EventModel> for SSEMessage {
+    fn from(event: &EventModel) -> Self {
+        SSEMessage::Event {
+            id: event.id.clone(),
+            topic: event.topic.clone(),
+            payload: event.payload.clone(),
+            published_at: event.published_at.to_rfc3339(),
+            sequence: None,
+            partition_key: event.partition_key.clone(),
+            headers: event.headers.clone(),
+            metadata: event.metadata.clone(),
+            attributes: event.attributes.clone(),
+        }
+    }
+}
+
+impl SSEMessage {
+    /// Event delivery carrying its stream sequence
+    pub fn sequenced(event: &EventModel, sequence: Option<u64>) -> Self {
+        SSEMessage::Event {
+            id: event.id.clone(),
+            topic: event.topic.clone(),
+            payload: event.payload.clone(),
+            published_at: event.published_at.to_rfc3339(),
+            sequence,
+            partition_key: event.partition_key.clone(),
+            headers: event.headers.clone(),
+            metadata: event.metadata.clone(),
+            attributes: event.attributes.clone(),
+        }
+    }
 }
 
 /// SSE connection state
@@ -70,13 +269,17 @@ pub struct SSEConnection {
     pub subscribed_topics: Vec<String>,
     pub sender: broadcast::Sender<SSEMessage>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Fed by its own acking consumer rather than the live fan-out
+    pub acked: bool,
 }
 
 /// Global SSE connection manager
 #[derive(Debug, Clone)]
 pub struct SSEManager {
     connections: Arc<Mutex<HashMap<String, SSEConnection>>>,
-    connection_limits: Arc<Mutex<HashMap<String, i32>>>, // tenant_id -> limit
+    connection_limits: Arc<Mutex<HashMap<String, i32>>>, // project_id -> limit
+    // Tracks the number of connections; only the global manager reports one
+    active_gauge: Option<Gauge>,
 }
 
 impl Default for SSEManager {
@@ -90,6 +293,28 @@ impl SSEManager {
         Self {
             connections: Arc::new(Mutex::new(HashMap::new())),
             connection_limits: Arc::new(Mutex::new(HashMap::new())),
+            active_gauge: None,
+        }
+    }
+
+    /// Manager that keeps `gauge` equal to its number of connections
+    pub fn with_active_gauge(gauge: Gauge) -> Self {
+        Self {
+            active_gauge: Some(gauge),
+            ..Self::new()
+        }
+    }
+
+    /// Number of open connections
+    pub fn connection_count(&self) -> usize {
+        self.connections.lock().unwrap().len()
+    }
+
+    // Set the active gauge while the connections lock is held, so it can't
+    // be overwritten with a stale count
+    fn update_active_gauge(&self, connections: &HashMap<String, SSEConnection>) {
+        if let Some(gauge) = &self.active_gauge {
+            gauge.set(connections.len() as f64);
         }
     }
 
@@ -98,22 +323,23 @@ impl SSEManager {
         let mut connections = self.connections.lock().unwrap();
 
         // Check connection limits
-        let tenant_connection_count = connections
+        let project_connection_count = connections
             .values()
-            .filter(|conn| conn.tenant_id == connection.tenant_id)
+            .filter(|conn| conn.project_id == connection.project_id)
             .count();
 
         let limits = self.connection_limits.lock().unwrap();
-        let limit = limits.get(&connection.tenant_id).unwrap_or(&1000); // Default limit
+        let limit = limits.get(&connection.project_id).unwrap_or(&1000); // Default limit
 
-        if tenant_connection_count >= *limit as usize {
+        if project_connection_count >= *limit as usize {
             return Err(format!(
-                "SSE connection limit exceeded for tenant {}: {}/{}",
-                connection.tenant_id, tenant_connection_count, limit
+                "SSE connection limit exceeded for project {}: {}/{}",
+                connection.project_id, project_connection_count, limit
             ));
         }
 
         connections.insert(connection.id.clone(), connection);
+        self.update_active_gauge(&connections);
         Ok(())
     }
 
@@ -121,6 +347,7 @@ impl SSEManager {
     pub fn remove_connection(&self, connection_id: &str) {
         let mut connections = self.connections.lock().unwrap();
         connections.remove(connection_id);
+        self.update_active_gauge(&connections);
     }
 
     /// Get connections for a tenant/project/topic
@@ -134,10 +361,10 @@ impl SSEManager {
         connections
             .values()
             .filter(|conn| {
-                conn.tenant_id == tenant_id
+                !conn.acked
+                    && conn.tenant_id == tenant_id
                     && conn.project_id == project_id
-                    && (conn.subscribed_topics.is_empty()
-                        || conn.subscribed_topics.iter().any(|t| topic.starts_with(t)))
+                    && topic_matches(&conn.subscribed_topics, topic)
             })
             .cloned()
             .collect()
@@ -152,10 +379,64 @@ impl SSEManager {
             .count()
     }
 
-    /// Set connection limit for a tenant
-    pub fn set_connection_limit(&self, tenant_id: String, limit: i32) {
+    /// Get connection count for a project
+    pub fn get_project_connection_count(&self, project_id: &str) -> usize {
+        let connections = self.connections.lock().unwrap();
+        connections
+            .values()
+            .filter(|conn| conn.project_id == project_id)
+            .count()
+    }
+
+    /// Set connection limit for a project
+    pub fn set_connection_limit(&self, project_id: String, limit: i32) {
         let mut limits = self.connection_limits.lock().unwrap();
-        limits.insert(tenant_id, limit);
+        limits.insert(project_id, limit);
+    }
+
+    /// List (connection id, created_at) for every connection of a project
+    pub fn project_connections(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+    ) -> Vec<(String, chrono::DateTime<chrono::Utc>)> {
+        let connections = self.connections.lock().unwrap();
+        connections
+            .values()
+            .filter(|conn| conn.tenant_id == tenant_id && conn.project_id == project_id)
+            .map(|conn| (conn.id.clone(), conn.created_at))
+            .collect()
+    }
+
+    /// Close specific connections with a close code and reason
+    pub fn close_connections(&self, connection_ids: &[String], code: u16, reason: &str) {
+        let mut connections = self.connections.lock().unwrap();
+        for connection_id in connection_ids {
+            if let Some(conn) = connections.remove(connection_id) {
+                let _ = conn.sender.send(SSEMessage::Close {
+                    code,
+                    reason: reason.to_string(),
+                });
+            }
+        }
+        self.update_active_gauge(&connections);
+    }
+
+    /// Close every connection with a close code and reason, returning their ids
+    pub fn close_all_connections(&self, code: u16, reason: &str) -> Vec<String> {
+        let mut connections = self.connections.lock().unwrap();
+        let connection_ids = connections
+            .drain()
+            .map(|(id, conn)| {
+                let _ = conn.sender.send(SSEMessage::Close {
+                    code,
+                    reason: reason.to_string(),
+                });
+                id
+            })
+            .collect();
+        self.update_active_gauge(&connections);
+        connection_ids
     }
 
     /// Terminate all connections for a tenant (for suspension)
@@ -178,6 +459,7 @@ impl SSEManager {
 
         // Remove connections
         connections.retain(|_, conn| conn.tenant_id != tenant_id);
+        self.update_active_gauge(&connections);
 
         connection_ids
     }
@@ -185,38 +467,157 @@ impl SSEManager {
 
 // Global SSE manager instance
 lazy_static::lazy_static! {
-    static ref SSE_MANAGER: SSEManager = SSEManager::new();
+    static ref SSE_MANAGER: SSEManager =
+        SSEManager::with_active_gauge(sse_connections_gauge().clone());
+}
+
+/// Get the global SSE manager
+pub fn sse_manager() -> &'static SSEManager {
+    &SSE_MANAGER
 }
 
 /// SSE handler with authentication and subscription management
+#[utoipa::path(
+    get,
+    path = "/sse",
+    tag = "streaming",
+    params(
+        SSEQuery,
+        ("Last-Event-ID" = Option<String>, Header, description = "Resume after this event"),
+    ),
+    responses(
+        (status = 200, description = "Event stream", content_type = "text/event-stream", body = String),
+        (status = 400, description = "Topic list over the limits: TOPIC_LIST_TOO_LARGE", body = ErrorResponse),
+        (status = 403, description = "Key lacks the events:subscribe scope or the tenant is suspended"),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds: RATE_LIMIT_EXCEEDED", body = ErrorResponse),
+        (status = 503, description = "Service overloaded, retry after `Retry-After` seconds: SERVICE_OVERLOADED", body = ErrorResponse),
+    )
+)]
 pub async fn sse_handler(
     State(state): State<AppState>,
     Query(params): Query<SSEQuery>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    client_certificate: Option<Extension<ClientCertificate>>,
     headers: HeaderMap,
 ) -> Result<Response, StatusCode> {
-    // Extract authentication from headers
-    let auth_value = match extract_auth_header(&headers) {
-        Ok(value) => value,
-        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    let peer = connect_info.map(|ConnectInfo(addr)| addr);
+    let certificate = client_certificate.as_ref().map(|Extension(certificate)| certificate);
+    let auth_context = match authenticate_sse(&state, &headers, peer, certificate).await {
+        Ok(auth_context) => auth_context,
+        Err(rejection) => return Ok(rejection),
     };
 
-    // Validate authentication
-    let auth_context = match state.auth_service.validate_api_key(&auth_value).await {
-        Ok(context) => context,
-        Err(AuthError::InvalidApiKey) => {
-            // Try JWT validation as fallback
-            match state.auth_service.validate_jwt(&auth_value).await {
-                Ok(context) => context,
-                Err(_) => return Err(StatusCode::UNAUTHORIZED),
+    // Parse topics from query parameters
+    let topics = match parse_query_topics(params.topics.as_deref()) {
+        Ok(topics) => topics,
+        Err(message) => {
+            warn!("Rejected SSE topic list: {}", message);
+            return Ok(topic_list_error_response(&message, TopicListLimits::QUERY));
+        }
+    };
+
+    let last_event_id = last_event_id(&headers);
+    Ok(start_sse(state, auth_context, topics, last_event_id, params.format, params.acked).await)
+}
+
+/// POST /sse - Establish an SSE subscription with topics in the request body
+#[utoipa::path(
+    post,
+    path = "/sse",
+    tag = "streaming",
+    params(("Last-Event-ID" = Option<String>, Header, description = "Resume after this event")),
+    request_body = SSESubscribeRequest,
+    responses(
+        (status = 200, description = "Event stream", content_type = "text/event-stream", body = String),
+        (status = 400, description = "Topic list over the limits: TOPIC_LIST_TOO_LARGE", body = ErrorResponse),
+        (status = 403, description = "Key lacks the events:subscribe scope or the tenant is suspended"),
+        (status = 429, description = "Rate limit exceeded, retry after `Retry-After` seconds: RATE_LIMIT_EXCEEDED", body = ErrorResponse),
+        (status = 503, description = "Service overloaded, retry after `Retry-After` seconds: SERVICE_OVERLOADED", body = ErrorResponse),
+    )
+)]
+pub async fn sse_subscribe_handler(
+    State(state): State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    client_certificate: Option<Extension<ClientCertificate>>,
+    headers: HeaderMap,
+    Json(request): Json<SSESubscribeRequest>,
+) -> Result<Response, StatusCode> {
+    let peer = connect_info.map(|ConnectInfo(addr)| addr);
+    let certificate = client_certificate.as_ref().map(|Extension(certificate)| certificate);
+    let auth_context = match authenticate_sse(&state, &headers, peer, certificate).await {
+        Ok(auth_context) => auth_context,
+        Err(rejection) => return Ok(rejection),
+    };
+
+    let topics: Vec<String> = request
+        .topics
+        .into_iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    if let Err(message) = TopicListLimits::BODY.check(&topics) {
+        warn!("Rejected SSE topic list: {}", message);
+        return Ok(topic_list_error_response(&message, TopicListLimits::BODY));
+    }
+
+    let last_event_id = last_event_id(&headers);
+    Ok(start_sse(state, auth_context, topics, last_event_id, request.format, request.acked).await)
+}
+
+/// Authenticate an SSE request and require the subscribe scope. Requests
+/// without an `Authorization` header may authenticate with their TLS client
+/// certificate. Rejections are complete responses so throttled clients get a
+/// `Retry-After`.
+async fn authenticate_sse(
+    state: &AppState,
+    headers: &HeaderMap,
+    peer: Option<SocketAddr>,
+    client_certificate: Option<&ClientCertificate>,
+) -> Result<AuthContext, Response> {
+    // Validate the Authorization header, or else the client certificate
+    let result = match (extract_auth_header(headers), client_certificate) {
+        (Ok(auth_value), _) => {
+            match state
+                .auth_service
+                .validate_api_key_from(&auth_value, state.auth_service.client_ip(headers, peer))
+                .await
+            {
+                // Try JWT validation as fallback
+                Err(AuthError::InvalidApiKey) => state
+                    .auth_service
+                    .validate_jwt(&auth_value)
+                    .await
+                    .map_err(|_| AuthError::InvalidJwt),
+                result => result,
             }
         }
-        Err(AuthError::RateLimitExceeded) => {
-            return Err(StatusCode::TOO_MANY_REQUESTS);
+        (Err(_), Some(certificate)) => {
+            state
+                .auth_service
+                .validate_client_certificate(certificate)
+                .await
+        }
+        (Err(_), None) => return Err(StatusCode::UNAUTHORIZED.into_response()),
+    };
+
+    let auth_context = match result {
+        Ok(context) => context,
+        Err(AuthError::RateLimitExceeded(status)) => {
+            return Err(status.into_response());
         }
         Err(AuthError::TenantSuspended) => {
-            return Err(StatusCode::FORBIDDEN);
+            return Err(StatusCode::FORBIDDEN.into_response());
         }
-        Err(_) => return Err(StatusCode::UNAUTHORIZED),
+        Err(AuthError::Database(e)) if circuit_open(&e).is_some() => {
+            warn!("Database unavailable during SSE authentication: {}", e);
+            return Err(circuit_open_response(&e));
+        }
+        Err(AuthError::Database(e)) if is_pool_exhausted(&e) => {
+            warn!("Database pool exhausted during SSE authentication: {}", e);
+            return Err(overloaded_response());
+        }
+        Err(_) => return Err(StatusCode::UNAUTHORIZED.into_response()),
     };
 
     // Check if the API key has subscribe permissions
@@ -225,14 +626,183 @@ pub async fn sse_handler(
         .check_scope(&auth_context, &Scope::EventsSubscribe)
         .is_err()
     {
-        return Err(StatusCode::FORBIDDEN);
+        return Err(StatusCode::FORBIDDEN.into_response());
     }
 
-    // Parse topics from query parameters
-    let topics = params
-        .topics
-        .map(|t| t.split(',').map(|s| s.trim().to_string()).collect())
-        .unwrap_or_else(Vec::new);
+    Ok(auth_context)
+}
+
+/// The `Last-Event-ID` header a reconnecting client sent, if any
+fn last_event_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// Most missed events replayed to a reconnecting client before it must resync
+pub const MAX_RESUME_EVENTS: usize = 1000;
+
+/// Parse a `Last-Event-ID` into the stream sequence to resume after, given the
+/// first and last sequence the stream still retains
+pub fn resume_sequence(last_event_id: &str, first_sequence: u64, last_sequence: u64) -> Result<u64, String> {
+    let sequence: u64 = last_event_id
+        .parse()
+        .map_err(|_| format!("Unknown Last-Event-ID: {}", last_event_id))?;
+
+    if sequence > last_sequence {
+        return Err(format!("Unknown Last-Event-ID: {}", last_event_id));
+    }
+    if sequence.saturating_add(1) < first_sequence {
+        return Err(format!(
+            "Last-Event-ID {} is older than the retained event history",
+            last_event_id
+        ));
+    }
+
+    Ok(sequence)
+}
+
+/// Replay the events a reconnecting client missed after its `Last-Event-ID`,
+/// paired with their stream sequences
+async fn missed_events(
+    state: &AppState,
+    params: &SSEConnectionParams,
+    last_event_id: &str,
+) -> Result<Vec<(EventModel, u64)>, String> {
+    let (first_sequence, last_sequence) = state
+        .event_service
+        .stream_sequence_bounds(&params.tenant_id)
+        .await
+        .map_err(|e| format!("Failed to resume from Last-Event-ID: {}", e))?;
+    let resume_after = resume_sequence(last_event_id, first_sequence, last_sequence)?;
+
+    let request = ReplayRequest {
+        tenant_id: params.tenant_id.clone(),
+        project_id: params.project_id.clone(),
+        topic: None,
+        cursor: Some(EventCursor::at_sequence(resume_after + 1)),
+        from_timestamp: None,
+        limit: Some(MAX_RESUME_EVENTS + 1),
+        transform: None,
+    };
+    let missed: Vec<(EventModel, EventCursor)> = state
+        .event_service
+        .replay_with_cursors(request)
+        .await
+        .map_err(|e| format!("Failed to resume from Last-Event-ID: {}", e))?
+        .collect()
+        .await;
+
+    if missed.len() > MAX_RESUME_EVENTS {
+        return Err(format!(
+            "More than {} events were missed since Last-Event-ID {}",
+            MAX_RESUME_EVENTS, last_event_id
+        ));
+    }
+
+    Ok(missed
+        .into_iter()
+        .filter(|(event, _)| topic_matches(&params.topics, &event.topic))
+        .map(|(event, cursor)| (event, cursor.sequence))
+        .collect())
+}
+
+/// `data` of an event frame: the native event fields, or a CloudEvents
+/// envelope when the connection asked for one
+#[allow(clippy::too_many_arguments)]
+pub fn event_frame_data(
+    encoding: &EventEncoding,
+    id: &str,
+    topic: &str,
+    payload: &serde_json::Value,
+    published_at: &str,
+    sequence: Option<u64>,
+    partition_key: Option<&str>,
+    headers: &HashMap<String, String>,
+    metadata: &HashMap<String, String>,
+    attributes: &HashMap<String, String>,
+) -> serde_json::Value {
+    match encoding.cloud_event(id, topic, payload, published_at, sequence, partition_key) {
+        Some(cloud_event) => serde_json::json!(cloud_event.with_headers(headers)),
+        None => {
+            let mut data = serde_json::json!({
+                "id": id,
+                "topic": topic,
+                "payload": payload,
+                "published_at": published_at
+            });
+            if let Some(partition_key) = partition_key {
+                data["partition_key"] = serde_json::json!(partition_key);
+            }
+            if !headers.is_empty() {
+                data["headers"] = serde_json::json!(headers);
+            }
+            if !metadata.is_empty() {
+                data["metadata"] = serde_json::json!(metadata);
+            }
+            if !attributes.is_empty() {
+                data["attributes"] = serde_json::json!(attributes);
+            }
+            data
+        }
+    }
+}
+
+/// SSE frame for an event; the stream sequence, when known, is the frame id so
+/// a reconnecting client's `Last-Event-ID` can be resumed from
+#[allow(clippy::too_many_arguments)]
+fn event_frame(
+    encoding: &EventEncoding,
+    id: String,
+    topic: String,
+    payload: serde_json::Value,
+    published_at: String,
+    sequence: Option<u64>,
+    partition_key: Option<String>,
+    headers: HashMap<String, String>,
+    metadata: HashMap<String, String>,
+    attributes: HashMap<String, String>,
+) -> Option<Event> {
+    let event_data = event_frame_data(
+        encoding,
+        &id,
+        &topic,
+        &payload,
+        &published_at,
+        sequence,
+        partition_key.as_deref(),
+        &headers,
+        &metadata,
+        &attributes,
+    );
+
+    let data_str = serde_json::to_string(&event_data).ok()?;
+    let frame = Event::default().event("event").data(data_str);
+    Some(match sequence {
+        Some(sequence) => frame.id(sequence.to_string()),
+        None => frame,
+    })
+}
+
+/// Open the SSE stream for an authenticated subscription
+async fn start_sse(
+    state: AppState,
+    auth_context: AuthContext,
+    topics: Vec<String>,
+    last_event_id: Option<String>,
+    format: DeliveryFormat,
+    acked: bool,
+) -> Response {
+    if let Err(message) = auth_context.check_subscribe_topics(&topics) {
+        warn!("Rejected SSE subscription: {}", message);
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ErrorResponse::new("TOPIC_NOT_ALLOWED", &message, None)),
+        )
+            .into_response();
+    }
 
     // Create connection parameters
     let connection_params = SSEConnectionParams {
@@ -240,15 +810,62 @@ pub async fn sse_handler(
         project_id: auth_context.project_id.clone(),
         topics,
         auth_context,
+        last_event_id,
+        format,
+        acked,
     };
 
     // Create SSE stream
     let stream = create_sse_stream(connection_params, state).await;
 
-    // Return SSE response
-    Ok(Sse::new(stream)
-        .keep_alive(KeepAlive::new().interval(Duration::from_secs(30)))
-        .into_response())
+    // Keep-alives are heartbeat events sent by the idle watchdog, so they go
+    // through the stream and count as flushes
+    Sse::new(stream).into_response()
+}
+
+/// Watch an SSE connection for inactivity. Sends a heartbeat every keep-alive
+/// interval; once nothing has been flushed to the client for `timeout` the
+/// connection is removed from the manager and closed. Ends when the stream
+/// has gone away or the connection was removed by something else.
+pub fn spawn_idle_watchdog(
+    connection_id: String,
+    sender: broadcast::Sender<SSEMessage>,
+    activity: ActivityTracker,
+    timeout: Duration,
+) -> tokio::task::JoinHandle<()> {
+    let interval = sse_keep_alive_interval(timeout);
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+
+            // Closed by someone else; dropping our sender lets the stream end
+            if !SSE_MANAGER.connections.lock().unwrap().contains_key(&connection_id) {
+                break;
+            }
+
+            if activity.idle_for() >= timeout {
+                info!(
+                    "SSE connection {} idle for {:?}, closing",
+                    connection_id, timeout
+                );
+                SSE_MANAGER.remove_connection(&connection_id);
+                let _ = sender.send(SSEMessage::Close {
+                    code: IDLE_CLOSE_CODE,
+                    reason: IDLE_CLOSE_REASON.to_string(),
+                });
+                record_idle_connection_closed("sse");
+                break;
+            }
+
+            let heartbeat = SSEMessage::Heartbeat {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            };
+            if sender.send(heartbeat).is_err() {
+                // The stream has ended
+                break;
+            }
+        }
+    })
 }
 
 /// Create SSE stream for a connection
@@ -259,12 +876,46 @@ async fn create_sse_stream(
     let connection_id = Uuid::new_v4().to_string();
 
     info!(
+        correlation_id = current_request_id().as_deref(),
         "New SSE connection {} for tenant/project: {}/{}",
         connection_id, params.tenant_id, params.project_id
     );
 
-    // Create broadcast channel for this connection
-    let (sender, mut receiver) = broadcast::channel(1000);
+    let project = state
+        .database
+        .get_project_with_tenant(&params.tenant_id, &params.project_id)
+        .await
+        .ok()
+        .flatten();
+
+    // JWTs outlive their project, so the archive is checked here as well as at key lookup
+    if project.as_ref().is_some_and(Project::is_archived) {
+        warn!(
+            "Rejecting SSE connection {}: project {} is archived",
+            connection_id, params.project_id
+        );
+        let close_data = serde_json::json!({
+            "code": PROJECT_ARCHIVED_CLOSE_CODE,
+            "reason": PROJECT_ARCHIVED_CLOSE_REASON
+        });
+        return stream::once(async move {
+            Ok(Event::default().event("close").data(close_data.to_string()))
+        })
+        .boxed();
+    }
+
+    // Create broadcast channel for this connection, sized by the project's delivery buffer
+    let capacity = delivery_buffer_capacity(
+        project
+            .as_ref()
+            .map_or(DEFAULT_DELIVERY_BUFFER_SIZE, |p| p.limits.delivery_buffer_size),
+    );
+    let (sender, mut receiver) = broadcast::channel(capacity);
+    let idle_timeout = idle_timeout(
+        project
+            .as_ref()
+            .map_or(DEFAULT_IDLE_TIMEOUT_SECS, |p| p.limits.idle_timeout_secs),
+    );
 
     // Create connection object
     let connection = SSEConnection {
@@ -274,10 +925,28 @@ async fn create_sse_stream(
         subscribed_topics: params.topics.clone(),
         sender: sender.clone(),
         created_at: chrono::Utc::now(),
+        acked: params.acked,
     };
 
-    // Add connection to manager
-    if let Err(e) = SSE_MANAGER.add_connection(connection.clone()) {
+    // Acknowledge first, then queue the snapshot for stateful topics and
+    // register for live delivery
+    let _ = sender.send(SSEMessage::Connected {
+        connection_id: connection_id.clone(),
+    });
+
+    let registered = state.event_service.snapshots().deliver_snapshot(
+        &params.tenant_id,
+        &params.project_id,
+        &params.topics,
+        |snapshot| {
+            for event in &snapshot {
+                let _ = sender.send(SSEMessage::from(event));
+            }
+            SSE_MANAGER.add_connection(connection.clone())
+        },
+    );
+
+    if let Err(e) = registered {
         error!("Failed to add SSE connection: {}", e);
         return stream::once(async move {
             Ok(Event::default()
@@ -288,16 +957,25 @@ async fn create_sse_stream(
     }
 
     // Set connection limit based on project limits
-    if let Ok(Some(project)) = state
-        .database
-        .get_project_with_tenant(&params.tenant_id, &params.project_id)
-        .await
-    {
-        SSE_MANAGER.set_connection_limit(params.tenant_id.clone(), project.limits.max_connections);
+    if let Some(project) = &project {
+        SSE_MANAGER.set_connection_limit(params.project_id.clone(), project.limits.max_connections);
     }
 
-    // Subscribe to initial topics if provided
-    if !params.topics.is_empty() {
+    // Acked connections are fed by their own consumer, the rest by the live fan-out
+    if params.acked {
+        spawn_acked_delivery(
+            state.event_service.clone(),
+            SubscriptionConfig::acked(
+                &params.tenant_id,
+                &params.project_id,
+                params.topics.clone(),
+                format!("sse_{}", connection_id),
+            ),
+            sender.clone(),
+            capacity,
+            |event| SSEMessage::from(event),
+        );
+    } else if !params.topics.is_empty() {
         if let Err(e) = subscribe_to_topics(
             &state,
             &params.tenant_id,
@@ -321,36 +999,99 @@ async fn create_sse_stream(
             .and_hms_opt(0, 0, 0)
             .unwrap()
             .and_utc(),
-    );
+    )
+    .with_key_id(params.auth_context.api_key_id().map(str::to_string));
 
-    if let Err(e) = state.database.create_usage_record(&usage_record).await {
+    if let Err(e) = state.event_service.record_usage(&usage_record).await {
         warn!("Failed to track SSE usage: {}", e);
     }
 
-    // Send connection acknowledgment
-    let connected_msg = SSEMessage::Connected {
-        connection_id: connection_id.clone(),
+    // The connection is already registered, so live events published while the
+    // missed ones are replayed queue up behind them rather than being lost
+    let mut resume = match &params.last_event_id {
+        Some(last_event_id) => Some(missed_events(&state, &params, last_event_id).await),
+        None => None,
     };
-    let _ = sender.send(connected_msg);
+
+    let activity = ActivityTracker::new();
+    spawn_idle_watchdog(connection_id.clone(), sender, activity.clone(), idle_timeout);
 
     // Create the stream that converts broadcast messages to SSE events
     let connection_id_clone = connection_id.clone();
+    let encoding = EventEncoding::new(params.format, &params.tenant_id, &params.project_id);
     let stream = async_stream::stream! {
-        while let Ok(message) = receiver.recv().await {
-            match message {
-                SSEMessage::Event { id, topic, payload, published_at } => {
-                    let event_data = serde_json::json!({
-                        "id": id,
-                        "topic": topic,
-                        "payload": payload,
-                        "published_at": published_at
+        // Highest sequence sent during replay; live copies of those are skipped
+        let mut replayed_through = 0;
+        // Sequence of the last event sent, reported if the connection falls behind
+        let mut cursor = None;
+
+        loop {
+            // The stream only resumes once the previous frame has been taken
+            // for writing, so reaching here means the client is still reading
+            activity.touch();
+
+            let message = match next_delivery(&mut receiver, "sse", &connection_id_clone).await {
+                Delivery::Message(message) => message,
+                Delivery::Lagged(dropped) => {
+                    let lagged_data = serde_json::json!({
+                        "error": format!("{} events dropped because the connection fell behind", dropped),
+                        "code": EVENTS_DROPPED_CODE,
+                        "dropped": dropped,
+                        "cursor": cursor
                     });
-                    
-                    if let Ok(data_str) = serde_json::to_string(&event_data) {
+                    yield Ok(Event::default()
+                        .event("error")
+                        .data(lagged_data.to_string()));
+
+                    // Close so the client reconnects with Last-Event-ID and replays
+                    if close_lagged_connections_enabled() {
+                        let close_data = serde_json::json!({
+                            "code": LAGGED_CLOSE_CODE,
+                            "reason": LAGGED_CLOSE_REASON
+                        });
                         yield Ok(Event::default()
-                            .event("event")
-                            .id(id)
-                            .data(data_str));
+                            .event("close")
+                            .data(close_data.to_string()));
+                        break;
+                    }
+                    continue;
+                }
+                Delivery::Closed => break,
+            };
+
+            match message {
+                SSEMessage::Event {
+                    id,
+                    topic,
+                    payload,
+                    published_at,
+                    sequence,
+                    partition_key,
+                    headers,
+                    metadata,
+                    attributes,
+                } => {
+                    if let Some(sequence) = sequence {
+                        if sequence <= replayed_through {
+                            continue;
+                        }
+                        ordering_verifier().observe(&connection_id_clone, sequence);
+                        cursor = Some(sequence);
+                    }
+
+                    if let Some(frame) = event_frame(
+                        &encoding,
+                        id,
+                        topic,
+                        payload,
+                        published_at,
+                        sequence,
+                        partition_key,
+                        headers,
+                        metadata,
+                        attributes,
+                    ) {
+                        yield Ok(frame);
                     }
                 }
                 SSEMessage::Connected { connection_id } => {
@@ -364,6 +1105,42 @@ async fn create_sse_stream(
                             .event("connected")
                             .data(data_str));
                     }
+
+                    // Catch up on events missed since Last-Event-ID before live delivery
+                    match resume.take() {
+                        Some(Ok(missed)) => {
+                            for (event, sequence) in missed {
+                                replayed_through = sequence;
+                                cursor = Some(sequence);
+                                ordering_verifier().observe(&connection_id_clone, sequence);
+                                if let Some(frame) = event_frame(
+                                    &encoding,
+                                    event.id,
+                                    event.topic,
+                                    event.payload,
+                                    event.published_at.to_rfc3339(),
+                                    Some(sequence),
+                                    event.partition_key,
+                                    event.headers,
+                                    event.metadata,
+                                    event.attributes,
+                                ) {
+                                    yield Ok(frame);
+                                }
+                            }
+                        }
+                        Some(Err(reason)) => {
+                            warn!("SSE connection {} must resync: {}", connection_id_clone, reason);
+                            let resync_data = serde_json::json!({
+                                "error": reason,
+                                "code": "RESYNC_REQUIRED"
+                            });
+                            yield Ok(Event::default()
+                                .event("error")
+                                .data(resync_data.to_string()));
+                        }
+                        None => {}
+                    }
                 }
                 SSEMessage::Error { message } => {
                     let error_data = serde_json::json!({
@@ -388,12 +1165,26 @@ async fn create_sse_stream(
                             .data(data_str));
                     }
                 }
+                SSEMessage::Close { code, reason } => {
+                    let close_data = serde_json::json!({
+                        "code": code,
+                        "reason": reason
+                    });
+
+                    if let Ok(data_str) = serde_json::to_string(&close_data) {
+                        yield Ok(Event::default()
+                            .event("close")
+                            .data(data_str));
+                    }
+                    break;
+                }
             }
         }
         
         // Clean up connection when stream ends
         debug!("SSE stream ended for connection {}", connection_id_clone);
         SSE_MANAGER.remove_connection(&connection_id_clone);
+        ordering_verifier().forget(&connection_id_clone);
     };
 
     Box::pin(stream)
@@ -425,7 +1216,7 @@ async fn subscribe_to_topics(
 }
 
 /// Broadcast an event to all relevant SSE connections
-pub async fn broadcast_event_to_sse(event: &EventModel) -> Result<()> {
+pub async fn broadcast_event_to_sse(event: &EventModel, sequence: Option<u64>) -> Result<()> {
     let connections = SSE_MANAGER.get_connections_for_event(
         &event.tenant_id,
         &event.project_id,
@@ -437,23 +1228,25 @@ pub async fn broadcast_event_to_sse(event: &EventModel) -> Result<()> {
         return Ok(());
     }
 
-    let sse_message = SSEMessage::Event {
-        id: event.id.clone(),
-        topic: event.topic.clone(),
-        payload: event.payload.clone(),
-        published_at: event.published_at.to_rfc3339(),
-    };
+    let sse_message = SSEMessage::sequenced(event, sequence);
 
     let mut delivered_count = 0;
 
     for connection in connections {
-        if let Err(e) = connection.sender.send(sse_message.clone()) {
-            warn!(
-                "Failed to send event to SSE connection {}: {}",
-                connection.id, e
-            );
-        } else {
-            delivered_count += 1;
+        match connection.sender.send(sse_message.clone()) {
+            Ok(_) => delivered_count += 1,
+            Err(_) if prune_closed_connections_enabled() => {
+                // Every receiver is gone, so this client can never be reached again
+                SSE_MANAGER.remove_connection(&connection.id);
+                record_closed_connection_pruned("sse");
+                info!("Removed SSE connection {} with closed channel", connection.id);
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to send event to SSE connection {}: {}",
+                    connection.id, e
+                );
+            }
         }
     }
 
@@ -479,9 +1272,10 @@ pub fn get_sse_stats() -> HashMap<String, serde_json::Value> {
     let connections = SSE_MANAGER.connections.lock().unwrap();
     let mut stats = HashMap::new();
 
+    // The same count exported as `realtime_sse_connections_active`
     stats.insert(
         "total_connections".to_string(),
-        serde_json::Value::Number(connections.len().into()),
+        serde_json::Value::Number((sse_connections_gauge().get() as u64).into()),
     );
 
     // Count connections per tenant
@@ -532,7 +1326,7 @@ mod tests {
     #[test]
     fn test_sse_connection_limits() {
         let manager = SSEManager::new();
-        manager.set_connection_limit("tenant_1".to_string(), 2);
+        manager.set_connection_limit("project_1".to_string(), 2);
 
         let (sender, _) = broadcast::channel(100);
 
@@ -544,6 +1338,7 @@ mod tests {
             subscribed_topics: vec![],
             sender: sender.clone(),
             created_at: chrono::Utc::now(),
+            acked: false,
         };
 
         assert!(manager.add_connection(conn1).is_ok());
@@ -557,6 +1352,7 @@ mod tests {
             subscribed_topics: vec![],
             sender: sender.clone(),
             created_at: chrono::Utc::now(),
+            acked: false,
         };
 
         assert!(manager.add_connection(conn2).is_ok());
@@ -570,9 +1366,94 @@ mod tests {
             subscribed_topics: vec![],
             sender: sender.clone(),
             created_at: chrono::Utc::now(),
+            acked: false,
         };
 
         assert!(manager.add_connection(conn3).is_err());
         assert_eq!(manager.get_tenant_connection_count("tenant_1"), 2);
     }
-}
\ No newline at end of file
+
+    fn topics(count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("orders.region_{}.created", i)).collect()
+    }
+
+    #[test]
+    fn test_query_topics_parsed_and_trimmed() {
+        assert_eq!(parse_query_topics(None).unwrap(), Vec::<String>::new());
+        assert_eq!(
+            parse_query_topics(Some("a.b, c.d,,")).unwrap(),
+            vec!["a.b".to_string(), "c.d".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_query_topic_count_limit() {
+        let raw = (0..MAX_QUERY_TOPICS + 1)
+            .map(|i| format!("t{}", i))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let err = parse_query_topics(Some(&raw)).unwrap_err();
+        assert!(err.contains(&MAX_QUERY_TOPICS.to_string()));
+    }
+
+    #[test]
+    fn test_query_topic_length_limit() {
+        let raw = "x".repeat(MAX_QUERY_TOPICS_LENGTH + 1);
+        let err = parse_query_topics(Some(&raw)).unwrap_err();
+        assert!(err.contains(&MAX_QUERY_TOPICS_LENGTH.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_topic_list_yields_clear_400() {
+        let message = parse_query_topics(Some(&topics(200).join(","))).unwrap_err();
+        let response = topic_list_error_response(&message, TopicListLimits::QUERY);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], "TOPIC_LIST_TOO_LARGE");
+        assert_eq!(body["error"]["details"]["max_topics"], MAX_QUERY_TOPICS);
+        assert_eq!(
+            body["error"]["details"]["max_total_length"],
+            MAX_QUERY_TOPICS_LENGTH
+        );
+    }
+
+    #[test]
+    fn test_post_body_accepts_large_topic_set() {
+        let large = topics(500);
+        assert!(parse_query_topics(Some(&large.join(","))).is_err());
+        assert!(TopicListLimits::BODY.check(&large).is_ok());
+        assert!(TopicListLimits::BODY.check(&topics(MAX_BODY_TOPICS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_resume_sequence_within_retained_range() {
+        assert_eq!(resume_sequence("42", 10, 100), Ok(42));
+        // Resuming right before the first retained event loses nothing
+        assert_eq!(resume_sequence("9", 10, 100), Ok(9));
+        assert_eq!(resume_sequence("100", 10, 100), Ok(100));
+    }
+
+    #[test]
+    fn test_resume_sequence_rejects_unknown_or_expired_ids() {
+        // Not a stream sequence, e.g. an event id from before resume support
+        assert!(resume_sequence("3f2a9c1e-event-id", 10, 100).is_err());
+        // Ahead of anything the stream has stored
+        assert!(resume_sequence("101", 10, 100).is_err());
+        // Events after it have aged out of retention
+        assert!(resume_sequence("8", 10, 100).is_err());
+    }
+
+    #[test]
+    fn test_last_event_id_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(last_event_id(&headers), None);
+
+        headers.insert("last-event-id", " 17 ".parse().unwrap());
+        assert_eq!(last_event_id(&headers), Some("17".to_string()));
+    }
+}
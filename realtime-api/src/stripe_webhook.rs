@@ -0,0 +1,301 @@
+use anyhow::Result;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::Sha256;
+use tracing::{info, warn};
+
+use crate::database::Database;
+use crate::models::TenantStatus;
+
+/// Header carrying Stripe's webhook signature
+pub const STRIPE_SIGNATURE_HEADER: &str = "stripe-signature";
+
+/// Maximum age of a signed webhook before it is treated as a replay
+pub const SIGNATURE_TOLERANCE_SECS: i64 = 300;
+
+/// Default number of failed attempts on one invoice before the tenant is suspended
+pub const DEFAULT_SUSPEND_AFTER_FAILED_PAYMENTS: u32 = 3;
+
+/// Suspension reason recorded when an invoice keeps failing; a later
+/// `invoice.paid` lifts only suspensions with this reason
+pub const PAYMENT_FAILED_SUSPENSION_REASON: &str = "payment_failed";
+
+/// Suspension reason recorded when the customer's subscription is deleted
+pub const SUBSCRIPTION_DELETED_SUSPENSION_REASON: &str = "subscription_deleted";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Why a webhook signature was rejected
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SignatureError {
+    #[error("Signature header is malformed")]
+    Malformed,
+    #[error("Signature timestamp is outside the tolerance window")]
+    Expired,
+    #[error("No signature matches the payload")]
+    Mismatch,
+}
+
+/// Verify a `Stripe-Signature` header (`t=<unix>,v1=<hex>[,v1=<hex>...]`)
+/// against the raw request body
+pub fn verify_signature(
+    payload: &[u8],
+    header: &str,
+    secret: &str,
+    now: i64,
+) -> Result<(), SignatureError> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signatures.push(value),
+            _ => {}
+        }
+    }
+
+    let timestamp = timestamp.ok_or(SignatureError::Malformed)?;
+    if signatures.is_empty() {
+        return Err(SignatureError::Malformed);
+    }
+    if (now - timestamp).abs() > SIGNATURE_TOLERANCE_SECS {
+        return Err(SignatureError::Expired);
+    }
+
+    let mac = signed_payload_mac(payload, secret, timestamp);
+    let matched = signatures.iter().any(|signature| {
+        hex::decode(signature)
+            .map(|signature| mac.clone().verify_slice(&signature).is_ok())
+            .unwrap_or(false)
+    });
+
+    if matched {
+        Ok(())
+    } else {
+        Err(SignatureError::Mismatch)
+    }
+}
+
+/// Build the `Stripe-Signature` header Stripe would send for a payload
+pub fn sign_payload(payload: &[u8], secret: &str, timestamp: i64) -> String {
+    let signature = signed_payload_mac(payload, secret, timestamp).finalize().into_bytes();
+    format!("t={},v1={}", timestamp, hex::encode(signature))
+}
+
+// HMAC-SHA256 over `<timestamp>.<payload>`
+fn signed_payload_mac(payload: &[u8], secret: &str, timestamp: i64) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(payload);
+    mac
+}
+
+#[derive(Debug, Deserialize)]
+struct RawEvent {
+    id: String,
+    #[serde(rename = "type")]
+    event_type: String,
+    data: RawEventData,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawEventData {
+    object: Value,
+}
+
+/// Payment lifecycle events the platform acts on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StripeWebhookEvent {
+    /// `invoice.payment_failed`, with the number of attempts made on the invoice
+    PaymentFailed { customer_id: String, attempt_count: u32 },
+    /// `invoice.paid`
+    InvoicePaid { customer_id: String },
+    /// `customer.subscription.deleted`
+    SubscriptionDeleted { customer_id: String },
+    /// Any other event type, acknowledged without action
+    Ignored { event_type: String },
+}
+
+impl StripeWebhookEvent {
+    /// Parse a Stripe event body, returning the event id alongside it
+    pub fn parse(payload: &[u8]) -> Result<(String, Self)> {
+        let raw: RawEvent = serde_json::from_slice(payload)?;
+        let customer_id = || {
+            raw.data
+                .object
+                .get("customer")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| anyhow::anyhow!("Event {} has no customer", raw.id))
+        };
+
+        let event = match raw.event_type.as_str() {
+            "invoice.payment_failed" => StripeWebhookEvent::PaymentFailed {
+                customer_id: customer_id()?,
+                attempt_count: raw
+                    .data
+                    .object
+                    .get("attempt_count")
+                    .and_then(Value::as_u64)
+                    .unwrap_or(1) as u32,
+            },
+            "invoice.paid" => StripeWebhookEvent::InvoicePaid {
+                customer_id: customer_id()?,
+            },
+            "customer.subscription.deleted" => StripeWebhookEvent::SubscriptionDeleted {
+                customer_id: customer_id()?,
+            },
+            other => StripeWebhookEvent::Ignored {
+                event_type: other.to_string(),
+            },
+        };
+
+        Ok((raw.id, event))
+    }
+
+    /// The tenant status this event moves its customer's tenant to, if any
+    pub fn tenant_status(&self, suspend_after_failed_payments: u32) -> Option<TenantStatus> {
+        match self {
+            StripeWebhookEvent::PaymentFailed { attempt_count, .. }
+                if *attempt_count >= suspend_after_failed_payments =>
+            {
+                Some(TenantStatus::Suspended)
+            }
+            StripeWebhookEvent::PaymentFailed { .. } => Some(TenantStatus::PastDue),
+            StripeWebhookEvent::InvoicePaid { .. } => Some(TenantStatus::Active),
+            StripeWebhookEvent::SubscriptionDeleted { .. } => Some(TenantStatus::Suspended),
+            StripeWebhookEvent::Ignored { .. } => None,
+        }
+    }
+
+    /// Why the tenant is suspended, for events that suspend it
+    fn suspension_reason(&self) -> &'static str {
+        match self {
+            StripeWebhookEvent::SubscriptionDeleted { .. } => {
+                SUBSCRIPTION_DELETED_SUSPENSION_REASON
+            }
+            _ => PAYMENT_FAILED_SUSPENSION_REASON,
+        }
+    }
+
+    fn customer_id(&self) -> Option<&str> {
+        match self {
+            StripeWebhookEvent::PaymentFailed { customer_id, .. }
+            | StripeWebhookEvent::InvoicePaid { customer_id }
+            | StripeWebhookEvent::SubscriptionDeleted { customer_id } => Some(customer_id),
+            StripeWebhookEvent::Ignored { .. } => None,
+        }
+    }
+}
+
+/// Apply a webhook event to the customer's tenant. Suspension goes through the
+/// kill switch, terminating the tenant's WebSocket and SSE connections. A
+/// suspended tenant is only reactivated by `invoice.paid` when it was suspended
+/// for failed payments, and isn't moved to past due at all. Returns the updated
+/// tenant id and status, or `None` when the event needs no action, leaves the
+/// tenant as it is, or the customer is unknown.
+pub async fn apply_event(
+    database: &Database,
+    event: &StripeWebhookEvent,
+    suspend_after_failed_payments: u32,
+) -> Result<Option<(String, TenantStatus)>> {
+    let (customer_id, status) = match (
+        event.customer_id(),
+        event.tenant_status(suspend_after_failed_payments),
+    ) {
+        (Some(customer_id), Some(status)) => (customer_id, status),
+        _ => return Ok(None),
+    };
+
+    let tenant = match database.get_tenant_by_stripe_customer(customer_id).await? {
+        Some(tenant) => tenant,
+        None => {
+            warn!("Stripe webhook for unknown customer {}", customer_id);
+            return Ok(None);
+        }
+    };
+
+    if status == TenantStatus::Suspended {
+        let reason = event.suspension_reason();
+        crate::kill_switch::suspend_tenant(database, &tenant.id, reason, None).await?;
+    } else {
+        let lifts_reason = match event {
+            StripeWebhookEvent::InvoicePaid { .. } => Some(PAYMENT_FAILED_SUSPENSION_REASON),
+            _ => None,
+        };
+        if !database
+            .update_billing_status(&tenant.id, status.clone(), lifts_reason)
+            .await?
+        {
+            info!(
+                tenant_id = %tenant.id,
+                "Left suspended tenant as it is on Stripe webhook"
+            );
+            return Ok(None);
+        }
+    }
+    info!(
+        tenant_id = %tenant.id,
+        status = ?status,
+        "Updated tenant status from Stripe webhook"
+    );
+
+    Ok(Some((tenant.id, status)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "whsec_test_secret";
+
+    #[test]
+    fn test_signature_round_trips() {
+        let payload = br#"{"id":"evt_1"}"#;
+        let header = sign_payload(payload, SECRET, 1_700_000_000);
+
+        assert_eq!(verify_signature(payload, &header, SECRET, 1_700_000_010), Ok(()));
+        assert_eq!(
+            verify_signature(b"{\"id\":\"evt_2\"}", &header, SECRET, 1_700_000_010),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            verify_signature(payload, &header, "whsec_other", 1_700_000_010),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn test_stale_or_malformed_signatures_are_rejected() {
+        let payload = br#"{"id":"evt_1"}"#;
+        let header = sign_payload(payload, SECRET, 1_700_000_000);
+
+        let too_late = 1_700_000_000 + SIGNATURE_TOLERANCE_SECS + 1;
+        assert_eq!(
+            verify_signature(payload, &header, SECRET, too_late),
+            Err(SignatureError::Expired)
+        );
+        assert_eq!(
+            verify_signature(payload, "v1=abc", SECRET, 1_700_000_000),
+            Err(SignatureError::Malformed)
+        );
+        assert_eq!(
+            verify_signature(payload, "t=1700000000", SECRET, 1_700_000_000),
+            Err(SignatureError::Malformed)
+        );
+    }
+
+    #[test]
+    fn test_repeated_payment_failures_suspend() {
+        let failed = |attempt_count| StripeWebhookEvent::PaymentFailed {
+            customer_id: "cus_1".to_string(),
+            attempt_count,
+        };
+
+        assert_eq!(failed(1).tenant_status(3), Some(TenantStatus::PastDue));
+        assert_eq!(failed(3).tenant_status(3), Some(TenantStatus::Suspended));
+    }
+}
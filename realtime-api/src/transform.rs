@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::models::Event;
+
+/// A single payload transformation step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum TransformStep {
+    /// Rename a top-level payload field, if present
+    RenameField { from: String, to: String },
+    /// Set a top-level payload field, overwriting any existing value
+    SetField { field: String, value: Value },
+    /// Set a top-level payload field only when it is missing
+    DefaultField { field: String, value: Value },
+    /// Remove a top-level payload field
+    RemoveField { field: String },
+    /// Nest the whole payload under a key, e.g. to move into a `data` envelope
+    WrapPayload { key: String },
+}
+
+impl TransformStep {
+    fn apply(&self, payload: Value) -> Value {
+        if let TransformStep::WrapPayload { key } = self {
+            let mut wrapped = Map::new();
+            wrapped.insert(key.clone(), payload);
+            return Value::Object(wrapped);
+        }
+
+        // Field-level steps only apply to object payloads
+        let mut object = match payload {
+            Value::Object(object) => object,
+            other => return other,
+        };
+
+        match self {
+            TransformStep::RenameField { from, to } => {
+                if let Some(value) = object.remove(from) {
+                    object.insert(to.clone(), value);
+                }
+            }
+            TransformStep::SetField { field, value } => {
+                object.insert(field.clone(), value.clone());
+            }
+            TransformStep::DefaultField { field, value } => {
+                object.entry(field.clone()).or_insert_with(|| value.clone());
+            }
+            TransformStep::RemoveField { field } => {
+                object.remove(field);
+            }
+            TransformStep::WrapPayload { .. } => unreachable!(),
+        }
+
+        Value::Object(object)
+    }
+}
+
+/// An ordered pipeline of payload transformations applied to events
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransformPipeline {
+    pub steps: Vec<TransformStep>,
+}
+
+impl TransformPipeline {
+    /// Create a pipeline from a list of steps
+    pub fn new(steps: Vec<TransformStep>) -> Self {
+        Self { steps }
+    }
+
+    /// Whether the pipeline has no steps
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Return a transformed copy of the event; the input is never modified
+    pub fn apply(&self, event: &Event) -> Event {
+        let mut transformed = event.clone();
+        transformed.payload = self
+            .steps
+            .iter()
+            .fold(transformed.payload, |payload, step| step.apply(payload));
+        transformed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(payload: Value) -> Event {
+        Event::new(
+            "tenant_123".to_string(),
+            "project_456".to_string(),
+            "user.created".to_string(),
+            payload,
+        )
+    }
+
+    #[test]
+    fn test_pipeline_applies_steps_in_order() {
+        let pipeline = TransformPipeline::new(vec![
+            TransformStep::RenameField {
+                from: "user".to_string(),
+                to: "user_id".to_string(),
+            },
+            TransformStep::DefaultField {
+                field: "schema_version".to_string(),
+                value: json!(2),
+            },
+            TransformStep::RemoveField {
+                field: "legacy".to_string(),
+            },
+            TransformStep::WrapPayload {
+                key: "data".to_string(),
+            },
+        ]);
+
+        let original = event(json!({"user": "u1", "legacy": true}));
+        let transformed = pipeline.apply(&original);
+
+        assert_eq!(
+            transformed.payload,
+            json!({"data": {"user_id": "u1", "schema_version": 2}})
+        );
+        assert_eq!(transformed.id, original.id);
+        assert_eq!(original.payload, json!({"user": "u1", "legacy": true}));
+    }
+
+    #[test]
+    fn test_default_field_keeps_existing_value() {
+        let pipeline = TransformPipeline::new(vec![TransformStep::DefaultField {
+            field: "schema_version".to_string(),
+            value: json!(2),
+        }]);
+
+        let transformed = pipeline.apply(&event(json!({"schema_version": 3})));
+        assert_eq!(transformed.payload, json!({"schema_version": 3}));
+    }
+
+    #[test]
+    fn test_field_steps_skip_non_object_payloads() {
+        let pipeline = TransformPipeline::new(vec![TransformStep::SetField {
+            field: "a".to_string(),
+            value: json!(1),
+        }]);
+
+        let transformed = pipeline.apply(&event(json!([1, 2, 3])));
+        assert_eq!(transformed.payload, json!([1, 2, 3]));
+    }
+
+    #[test]
+    fn test_steps_deserialize_from_tagged_json() {
+        let pipeline: TransformPipeline = serde_json::from_value(json!({
+            "steps": [
+                {"op": "rename_field", "from": "a", "to": "b"},
+                {"op": "wrap_payload", "key": "data"}
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(pipeline.steps.len(), 2);
+        assert_eq!(
+            pipeline.steps[1],
+            TransformStep::WrapPayload {
+                key: "data".to_string()
+            }
+        );
+    }
+}
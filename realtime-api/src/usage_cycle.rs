@@ -0,0 +1,188 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Months, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use tracing::{info, warn};
+
+use crate::database::Database;
+
+/// Suspension reason recorded when a tenant exceeds its plan's usage; these
+/// suspensions are lifted when the next billing cycle starts
+pub const USAGE_LIMIT_SUSPENSION_REASON: &str = "usage_limit_exceeded";
+
+/// What a tenant's billing cycle is anchored to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageCycleAnchor {
+    /// Cycles start on the first of each month
+    #[default]
+    CalendarMonth,
+    /// Cycles start on the day of the month the tenant signed up
+    SignupAnniversary,
+}
+
+impl FromStr for UsageCycleAnchor {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "calendar_month" => Ok(Self::CalendarMonth),
+            "signup_anniversary" => Ok(Self::SignupAnniversary),
+            other => Err(anyhow::anyhow!("Unknown usage cycle anchor: {}", other)),
+        }
+    }
+}
+
+// Midnight UTC on `day` of the given month, clamped to the month's last day
+fn cycle_day(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1).expect("valid month");
+    let last_day = (first + Months::new(1))
+        .pred_opt()
+        .expect("valid date")
+        .day();
+    let date = first.with_day(day.min(last_day)).expect("day within month");
+    Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).expect("midnight is valid"))
+}
+
+impl UsageCycleAnchor {
+    /// Start of the cycle containing `now` for a tenant that signed up at `signup`
+    pub fn cycle_start(&self, signup: DateTime<Utc>, now: DateTime<Utc>) -> DateTime<Utc> {
+        let day = match self {
+            UsageCycleAnchor::CalendarMonth => 1,
+            UsageCycleAnchor::SignupAnniversary => signup.day(),
+        };
+        let this_month = cycle_day(now.year(), now.month(), day);
+        if this_month <= now {
+            return this_month;
+        }
+        let previous = now.date_naive() - Months::new(1);
+        cycle_day(previous.year(), previous.month(), day)
+    }
+
+    /// Start of the cycle after the one starting at `cycle_start`
+    pub fn next_cycle_start(
+        &self,
+        signup: DateTime<Utc>,
+        cycle_start: DateTime<Utc>,
+    ) -> DateTime<Utc> {
+        let day = match self {
+            UsageCycleAnchor::CalendarMonth => 1,
+            UsageCycleAnchor::SignupAnniversary => signup.day(),
+        };
+        let next = cycle_start.date_naive() + Months::new(1);
+        cycle_day(next.year(), next.month(), day)
+    }
+}
+
+/// Close every billing cycle that has ended by `now`: archive its usage totals
+/// into usage history, start a fresh window, and lift suspensions made solely
+/// for exceeding usage. Tenants without a cycle yet start one, archiving the
+/// usage recorded before it. Returns the number of cycles closed.
+pub async fn roll_over_due_cycles(
+    database: &Database,
+    anchor: UsageCycleAnchor,
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    let mut closed = 0;
+
+    for tenant in database.list_usage_cycles().await? {
+        let Some(mut cycle_start) = tenant.usage_cycle_start else {
+            let cycle_start = anchor.cycle_start(tenant.created_at, now);
+            let archived = database
+                .start_usage_cycle(&tenant.tenant_id, cycle_start)
+                .await?;
+            info!(
+                "Started usage cycle {} for tenant {} ({} totals archived)",
+                cycle_start, tenant.tenant_id, archived
+            );
+            continue;
+        };
+
+        let mut rolled_over = false;
+        loop {
+            let cycle_end = anchor.next_cycle_start(tenant.created_at, cycle_start);
+            if cycle_end > now {
+                break;
+            }
+            let archived = database
+                .roll_over_usage(&tenant.tenant_id, cycle_start, cycle_end)
+                .await?;
+            info!(
+                "Closed usage cycle {} for tenant {} ({} totals archived)",
+                cycle_start, tenant.tenant_id, archived
+            );
+            cycle_start = cycle_end;
+            rolled_over = true;
+            closed += 1;
+        }
+
+        if rolled_over && tenant.suspension_reason.as_deref() == Some(USAGE_LIMIT_SUSPENSION_REASON)
+        {
+            if let Err(e) = crate::kill_switch::unsuspend_tenant(
+                database,
+                &tenant.tenant_id,
+                "Usage reset for new billing cycle",
+                None,
+            )
+            .await
+            {
+                warn!(
+                    "Failed to lift usage suspension for tenant {}: {}",
+                    tenant.tenant_id, e
+                );
+            }
+        }
+    }
+
+    Ok(closed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_calendar_month_cycles() {
+        let anchor = UsageCycleAnchor::CalendarMonth;
+        let signup = at(2023, 7, 19);
+
+        assert_eq!(anchor.cycle_start(signup, at(2024, 3, 17)), at(2024, 3, 1));
+        assert_eq!(anchor.cycle_start(signup, at(2024, 3, 1)), at(2024, 3, 1));
+        assert_eq!(
+            anchor.next_cycle_start(signup, at(2024, 12, 1)),
+            at(2025, 1, 1)
+        );
+    }
+
+    #[test]
+    fn test_signup_anniversary_cycles() {
+        let anchor = UsageCycleAnchor::SignupAnniversary;
+        let signup = at(2023, 1, 31);
+
+        assert_eq!(anchor.cycle_start(signup, at(2024, 3, 17)), at(2024, 2, 29));
+        assert_eq!(anchor.cycle_start(signup, at(2024, 3, 31)), at(2024, 3, 31));
+        // Short months end the cycle on their last day
+        assert_eq!(
+            anchor.next_cycle_start(signup, at(2024, 1, 31)),
+            at(2024, 2, 29)
+        );
+        assert_eq!(
+            anchor.next_cycle_start(signup, at(2024, 2, 29)),
+            at(2024, 3, 31)
+        );
+    }
+
+    #[test]
+    fn test_anchor_parsing() {
+        assert_eq!(
+            "signup_anniversary".parse::<UsageCycleAnchor>().unwrap(),
+            UsageCycleAnchor::SignupAnniversary
+        );
+        assert_eq!(UsageCycleAnchor::default(), UsageCycleAnchor::CalendarMonth);
+        assert!("weekly".parse::<UsageCycleAnchor>().is_err());
+    }
+}
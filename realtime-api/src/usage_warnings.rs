@@ -0,0 +1,146 @@
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Topic of the event announcing that a tenant crossed a usage threshold
+pub const USAGE_WARNING_TOPIC: &str = "usage.warning";
+
+/// Percentages of the monthly event quota that warn when none are configured
+pub const DEFAULT_USAGE_WARNING_THRESHOLDS: [u32; 2] = [80, 95];
+
+/// Start of the monthly quota window containing `now`
+pub fn usage_window_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .expect("first of the month is a valid UTC time")
+}
+
+/// How long a tenant's usage total is trusted before it is read again; in
+/// between, the instance counts its own publishes on top of it
+pub const USAGE_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Soft-limit thresholds, with each tenant's usage in its current quota window
+/// cached so that publishes don't each total the tenant's usage records.
+///
+/// Fired warnings are persisted by the caller, one per tenant, metric,
+/// threshold and window, so each threshold warns once per window across all
+/// instances and restarts. The cache only remembers which are known to have
+/// fired, so they aren't checked again.
+#[derive(Debug, Default)]
+pub struct UsageWarnings {
+    /// Percentages of the quota, ascending
+    thresholds: Vec<u32>,
+    // tenant_id -> usage in its current window
+    usage: Mutex<HashMap<String, WindowUsage>>,
+}
+
+#[derive(Debug)]
+struct WindowUsage {
+    window_start: DateTime<Utc>,
+    used: i64,
+    read_at: Instant,
+    fired: Vec<u32>,
+}
+
+impl UsageWarnings {
+    pub fn new(mut thresholds: Vec<u32>) -> Self {
+        thresholds.sort_unstable();
+        thresholds.dedup();
+        Self {
+            thresholds,
+            usage: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count `published` new events towards a tenant's cached usage, returning
+    /// its window start and total. `None` when nothing was read within
+    /// [`USAGE_REFRESH_INTERVAL`] of `now`, so the caller must read the total
+    /// and pass it to [`UsageWarnings::refresh`].
+    pub fn count(
+        &self,
+        tenant_id: &str,
+        published: i64,
+        now: Instant,
+    ) -> Option<(DateTime<Utc>, i64)> {
+        let mut usage = self.usage.lock().unwrap();
+        let cached = usage.get_mut(tenant_id)?;
+        if now.saturating_duration_since(cached.read_at) >= USAGE_REFRESH_INTERVAL {
+            return None;
+        }
+        cached.used += published;
+        Some((cached.window_start, cached.used))
+    }
+
+    /// Cache a tenant's usage total as read at `now`. Moving to a new window
+    /// forgets the thresholds fired in the previous one.
+    pub fn refresh(&self, tenant_id: &str, window_start: DateTime<Utc>, used: i64, now: Instant) {
+        let mut usage = self.usage.lock().unwrap();
+        match usage.get_mut(tenant_id) {
+            Some(cached) => {
+                if cached.window_start != window_start {
+                    cached.window_start = window_start;
+                    cached.fired.clear();
+                }
+                cached.used = used;
+                cached.read_at = now;
+            }
+            None => {
+                usage.insert(
+                    tenant_id.to_string(),
+                    WindowUsage {
+                        window_start,
+                        used,
+                        read_at: now,
+                        fired: Vec::new(),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Thresholds that `used` out of `quota` has reached and that aren't known
+    /// to have fired in the tenant's current window
+    pub fn crossed(&self, tenant_id: &str, used: i64, quota: i64) -> Vec<u32> {
+        if quota <= 0 {
+            return Vec::new();
+        }
+
+        let usage = self.usage.lock().unwrap();
+        let fired = usage
+            .get(tenant_id)
+            .map(|cached| cached.fired.as_slice())
+            .unwrap_or_default();
+        self.thresholds
+            .iter()
+            .copied()
+            .filter(|threshold| {
+                used * 100 >= quota * i64::from(*threshold) && !fired.contains(threshold)
+            })
+            .collect()
+    }
+
+    /// Remember that a threshold fired in the tenant's current window, on this
+    /// or another instance
+    pub fn mark_fired(&self, tenant_id: &str, threshold: u32) {
+        if let Some(cached) = self.usage.lock().unwrap().get_mut(tenant_id) {
+            if !cached.fired.contains(&threshold) {
+                cached.fired.push(threshold);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_window_is_the_calendar_month() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 17, 12, 30, 0).unwrap();
+        assert_eq!(
+            usage_window_start(now),
+            Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()
+        );
+    }
+}
@@ -0,0 +1,499 @@
+use hmac::{Hmac, Mac};
+use prometheus::Counter;
+use sha2::Sha256;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Semaphore};
+use tracing::{info, warn};
+
+use crate::cloudevents::{CloudEvent, DeliveryFormat, CLOUDEVENTS_CONTENT_TYPE};
+use crate::config::WebhookConfig;
+use crate::database::Database;
+use crate::models::{Event, Webhook};
+use crate::nats::{DeadLetterKind, NatsClient};
+use crate::request_signing::SigningSecretCipher;
+
+/// Header carrying the HMAC-SHA256 of the request body, `sha256=<hex>`
+pub const SIGNATURE_HEADER: &str = "x-signature";
+
+/// Header naming the webhook a delivery was made for
+pub const WEBHOOK_ID_HEADER: &str = "x-webhook-id";
+
+/// Header carrying the delivered event's id, for receivers to deduplicate retries
+pub const EVENT_ID_HEADER: &str = "x-event-id";
+
+/// Published events waiting for webhook delivery before new ones are dropped
+pub const WEBHOOK_QUEUE_CAPACITY: usize = 10_000;
+
+/// Prefix of generated webhook secrets
+pub const WEBHOOK_SECRET_PREFIX: &str = "whsec_";
+
+type HmacSha256 = Hmac<Sha256>;
+
+lazy_static::lazy_static! {
+    static ref WEBHOOK_EVENTS_DROPPED: Counter = Counter::new(
+        "realtime_webhook_events_dropped_total",
+        "Published events not delivered to webhooks because the delivery queue was full"
+    )
+    .expect("valid metric definition");
+}
+
+/// Counter of events skipped for webhooks because the delivery queue was full
+pub fn webhook_events_dropped_counter() -> &'static Counter {
+    &WEBHOOK_EVENTS_DROPPED
+}
+
+/// Build the `X-Signature` header value for a request body
+pub fn sign_payload(payload: &[u8], secret: &str) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(payload);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Check an `X-Signature` header against a request body in constant time
+pub fn verify_signature(payload: &[u8], signature: &str, secret: &str) -> bool {
+    let Some(signature) = signature
+        .strip_prefix("sha256=")
+        .and_then(|hex_signature| hex::decode(hex_signature).ok())
+    else {
+        return false;
+    };
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(payload);
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Generate a random signing secret for a new webhook
+pub fn generate_secret() -> String {
+    use rand::Rng;
+    const CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    const SECRET_LENGTH: usize = 32;
+
+    let mut rng = rand::thread_rng();
+    let secret: String = (0..SECRET_LENGTH)
+        .map(|_| CHARSET[rng.gen_range(0..CHARSET.len())] as char)
+        .collect();
+
+    format!("{}{}", WEBHOOK_SECRET_PREFIX, secret)
+}
+
+/// Check that a webhook URL is an absolute http(s) URL with a host. Unless
+/// `allow_private` is set, hosts that name this machine or a private network
+/// are refused; names are checked again when a delivery resolves them.
+pub fn validate_webhook_url(url: &str, allow_private: bool) -> Result<(), String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid webhook URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err("Webhook URL must use http or https".to_string());
+    }
+    let Some(host) = parsed.host_str().filter(|host| !host.is_empty()) else {
+        return Err("Webhook URL must include a host".to_string());
+    };
+    if allow_private {
+        return Ok(());
+    }
+    // IPv6 hosts keep their brackets
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let private = match host.parse::<IpAddr>() {
+        Ok(ip) => is_private_address(ip),
+        Err(_) => {
+            let domain = host.trim_end_matches('.').to_ascii_lowercase();
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+    };
+    if private {
+        return Err("Webhook URL must not point at a loopback or private address".to_string());
+    }
+    Ok(())
+}
+
+/// Whether `ip` is loopback, private, link-local or otherwise not reachable
+/// on the public internet, so a webhook to it could probe internal services
+pub fn is_private_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || first == 0
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (first == 100 && (second & 0b1100_0000) == 64)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(mapped) = ip.to_ipv4_mapped() {
+                return is_private_address(IpAddr::V4(mapped));
+            }
+            let first = ip.segments()[0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // Unique local, fc00::/7
+                || (first & 0xfe00) == 0xfc00
+                // Link-local, fe80::/10
+                || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+/// Resolve a webhook host to its public addresses, so deliveries can be
+/// pinned to them and a name resolving to an internal address can't reach it
+async fn public_addresses(host: &str) -> Result<Vec<SocketAddr>, String> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, 0))
+        .await
+        .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+        .filter(|addr| !is_private_address(addr.ip()))
+        .collect();
+    if addrs.is_empty() {
+        return Err(format!(
+            "{} has no public address; webhooks can't target private networks",
+            host
+        ));
+    }
+    Ok(addrs)
+}
+
+/// Check a topic filter: dot-separated topic tokens, `*` for any one token
+/// and a trailing `>` for one or more
+pub fn validate_topic_filter(filter: &str) -> Result<(), String> {
+    let tokens: Vec<&str> = filter.split('.').collect();
+    for (index, token) in tokens.iter().enumerate() {
+        match *token {
+            "*" => {}
+            ">" if index == tokens.len() - 1 => {}
+            ">" => return Err("'>' may only be the last token of a topic filter".to_string()),
+            token => crate::schema_validator::validate_topic_format(token)
+                .map_err(|e| format!("Invalid topic filter: {}", e))?,
+        }
+    }
+    Ok(())
+}
+
+/// How often and how patiently a delivery is retried
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Wait after `attempt` (1-based) failed attempts: doubling from the
+    /// initial backoff, capped at the maximum
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+impl From<&WebhookConfig> for RetryPolicy {
+    fn from(config: &WebhookConfig) -> Self {
+        Self {
+            max_attempts: config.max_attempts.max(1),
+            initial_backoff: Duration::from_millis(config.initial_backoff_ms),
+            max_backoff: Duration::from_millis(config.max_backoff_ms),
+        }
+    }
+}
+
+/// Why a delivery was given up on
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WebhookDeliveryError {
+    #[error("Webhook responded with status {status} after {attempts} attempts")]
+    Status { status: u16, attempts: u32 },
+    #[error("Webhook request failed after {attempts} attempts: {message}")]
+    Request { message: String, attempts: u32 },
+}
+
+/// POSTs events to webhook endpoints, retrying failures with exponential backoff.
+/// Redirects aren't followed and, unless allowed, loopback and private
+/// addresses aren't connected to.
+#[derive(Debug, Clone)]
+pub struct WebhookDispatcher {
+    client: reqwest::Client,
+    policy: RetryPolicy,
+    timeout: Duration,
+    allow_private_addresses: bool,
+    max_concurrent_deliveries: usize,
+    /// Opens the webhook secrets, which are stored encrypted
+    secret_cipher: SigningSecretCipher,
+}
+
+impl WebhookDispatcher {
+    pub fn new(policy: RetryPolicy, timeout: Duration, secret_cipher: SigningSecretCipher) -> Self {
+        Self {
+            client: Self::client_builder(timeout)
+                .build()
+                .expect("valid webhook HTTP client"),
+            policy,
+            timeout,
+            allow_private_addresses: false,
+            max_concurrent_deliveries: WebhookConfig::default().max_concurrent_deliveries,
+            secret_cipher,
+        }
+    }
+
+    pub fn from_config(config: &WebhookConfig, secret_cipher: SigningSecretCipher) -> Self {
+        Self::new(
+            config.into(),
+            Duration::from_secs(config.timeout_secs),
+            secret_cipher,
+        )
+        .with_private_addresses(config.allow_private_addresses)
+        .with_max_concurrent_deliveries(config.max_concurrent_deliveries)
+    }
+
+    /// Let webhooks target loopback and private addresses, e.g. in development
+    pub fn with_private_addresses(mut self, allow: bool) -> Self {
+        self.allow_private_addresses = allow;
+        self
+    }
+
+    /// Deliveries the background worker runs at once
+    pub fn with_max_concurrent_deliveries(mut self, max: usize) -> Self {
+        self.max_concurrent_deliveries = max.max(1);
+        self
+    }
+
+    fn client_builder(timeout: Duration) -> reqwest::ClientBuilder {
+        reqwest::Client::builder()
+            .timeout(timeout)
+            .redirect(reqwest::redirect::Policy::none())
+    }
+
+    /// The client for one attempt at `url`. Unless private addresses are
+    /// allowed, a host name is resolved here and the client pinned to its
+    /// public addresses, so the name can't be re-pointed at an internal one
+    /// between the check and the connection.
+    async fn delivery_client(&self, url: &str) -> Result<reqwest::Client, String> {
+        validate_webhook_url(url, self.allow_private_addresses)?;
+        if self.allow_private_addresses {
+            return Ok(self.client.clone());
+        }
+        let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid webhook URL: {}", e))?;
+        // Address literals were checked above
+        let Some(domain) = parsed.domain() else {
+            return Ok(self.client.clone());
+        };
+        let addrs = public_addresses(domain).await?;
+        Self::client_builder(self.timeout)
+            .resolve_to_addrs(domain, &addrs)
+            .build()
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Whether webhooks may target loopback and private addresses
+    pub fn allows_private_addresses(&self) -> bool {
+        self.allow_private_addresses
+    }
+
+    /// Deliver `event` to `webhook` until it answers with a 2xx status or the
+    /// attempts run out. Returns the number of attempts made.
+    pub async fn deliver(
+        &self,
+        webhook: &Webhook,
+        event: &Event,
+    ) -> Result<u32, WebhookDeliveryError> {
+        let (body, content_type) = match webhook.format {
+            DeliveryFormat::Native => (serde_json::to_vec(event), "application/json"),
+            DeliveryFormat::CloudEvents => (
+                serde_json::to_vec(&CloudEvent::from(event)),
+                CLOUDEVENTS_CONTENT_TYPE,
+            ),
+        };
+        let body = body.map_err(|e| WebhookDeliveryError::Request {
+            message: e.to_string(),
+            attempts: 0,
+        })?;
+        let secret = self
+            .secret_cipher
+            .open(&webhook.id, &webhook.secret)
+            .ok_or_else(|| WebhookDeliveryError::Request {
+                message: "Webhook secret could not be decrypted".to_string(),
+                attempts: 0,
+            })?;
+        let signature = sign_payload(&body, &secret);
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let sent = match self.delivery_client(&webhook.url).await {
+                Ok(client) => client
+                    .post(&webhook.url)
+                    .header(reqwest::header::CONTENT_TYPE, content_type)
+                    .header(SIGNATURE_HEADER, &signature)
+                    .header(WEBHOOK_ID_HEADER, &webhook.id)
+                    .header(EVENT_ID_HEADER, &event.id)
+                    .body(body.clone())
+                    .send()
+                    .await
+                    .map_err(|e| e.to_string()),
+                Err(message) => Err(message),
+            };
+
+            let error = match sent {
+                Ok(response) if response.status().is_success() => return Ok(attempt),
+                Ok(response) => WebhookDeliveryError::Status {
+                    status: response.status().as_u16(),
+                    attempts: attempt,
+                },
+                Err(message) => WebhookDeliveryError::Request {
+                    message,
+                    attempts: attempt,
+                },
+            };
+
+            if attempt >= self.policy.max_attempts {
+                return Err(error);
+            }
+            let backoff = self.policy.backoff(attempt);
+            warn!(
+                "Webhook {} delivery of event {} failed ({}); retrying in {:?}",
+                webhook.id, event.id, error, backoff
+            );
+            tokio::time::sleep(backoff).await;
+        }
+    }
+}
+
+/// Start the worker that delivers published events to their project's
+/// webhooks. Events submitted on the returned queue are matched against each
+/// active webhook's topic filter; deliveries that exhaust their attempts are
+/// dead-lettered. At most the dispatcher's concurrency limit of deliveries run
+/// at once; beyond that the queue fills up.
+pub fn spawn_delivery_worker(
+    dispatcher: WebhookDispatcher,
+    database: Database,
+    nats_client: NatsClient,
+) -> mpsc::Sender<Event> {
+    let (sender, mut receiver) = mpsc::channel::<Event>(WEBHOOK_QUEUE_CAPACITY);
+    let deliveries = Arc::new(Semaphore::new(dispatcher.max_concurrent_deliveries));
+
+    tokio::spawn(async move {
+        while let Some(event) = receiver.recv().await {
+            let webhooks = match database
+                .list_webhooks(&event.tenant_id, &event.project_id, true)
+                .await
+            {
+                Ok(webhooks) => webhooks,
+                Err(e) => {
+                    warn!("Failed to load webhooks for event {}: {}", event.id, e);
+                    continue;
+                }
+            };
+
+            for webhook in webhooks.into_iter().filter(|w| w.matches(&event.topic)) {
+                let Ok(permit) = deliveries.clone().acquire_owned().await else {
+                    return;
+                };
+                let dispatcher = dispatcher.clone();
+                let nats_client = nats_client.clone();
+                let event = event.clone();
+                // Deliver concurrently so one slow endpoint doesn't hold up the others
+                tokio::spawn(async move {
+                    let _permit = permit;
+                    match dispatcher.deliver(&webhook, &event).await {
+                        Ok(attempts) => info!(
+                            "Delivered event {} to webhook {} in {} attempts",
+                            event.id, webhook.id, attempts
+                        ),
+                        Err(e) => {
+                            warn!(
+                                "Giving up on webhook {} for event {}: {}",
+                                webhook.id, event.id, e
+                            );
+                            let reason =
+                                format!("Webhook {} ({}): {}", webhook.id, webhook.url, e);
+                            if let Err(e) = nats_client
+                                .publish_targeted_dead_letter(
+                                    &event,
+                                    DeadLetterKind::Webhook,
+                                    Some(&webhook.id),
+                                    &reason,
+                                )
+                                .await
+                            {
+                                warn!("Failed to dead-letter event {}: {}", event.id, e);
+                            }
+                        }
+                    }
+                });
+            }
+        }
+    });
+
+    sender
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let policy = RetryPolicy {
+            max_attempts: 6,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+        assert_eq!(policy.backoff(40), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_validate_webhook_url_and_topic_filter() {
+        assert!(validate_webhook_url("https://hooks.example.com/events", false).is_ok());
+        assert!(validate_webhook_url("ftp://hooks.example.com", false).is_err());
+        assert!(validate_webhook_url("hooks.example.com/events", false).is_err());
+
+        assert!(validate_topic_filter(">").is_ok());
+        assert!(validate_topic_filter("orders.*").is_ok());
+        assert!(validate_topic_filter("orders.>").is_ok());
+        assert!(validate_topic_filter("orders.>.created").is_err());
+        assert!(validate_topic_filter("orders..created").is_err());
+        assert!(validate_topic_filter("orders created").is_err());
+    }
+
+    #[test]
+    fn test_private_webhook_hosts_are_refused() {
+        for url in [
+            "http://localhost:8080",
+            "http://api.localhost/hook",
+            "http://127.0.0.1/hook",
+            "http://10.1.2.3/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/hook",
+            "http://[::1]/hook",
+            "http://[fd00::1]/hook",
+            "http://[::ffff:192.168.0.1]/hook",
+        ] {
+            assert!(validate_webhook_url(url, false).is_err(), "{}", url);
+            assert!(validate_webhook_url(url, true).is_ok(), "{}", url);
+        }
+        assert!(validate_webhook_url("http://93.184.216.34/hook", false).is_ok());
+        assert!(validate_webhook_url("http://[2606:4700::1111]/hook", false).is_ok());
+    }
+
+    #[test]
+    fn test_generated_secrets_are_unique() {
+        let secret = generate_secret();
+        assert!(secret.starts_with(WEBHOOK_SECRET_PREFIX));
+        assert_eq!(secret.len(), WEBHOOK_SECRET_PREFIX.len() + 32);
+        assert_ne!(secret, generate_secret());
+    }
+}
@@ -1,6 +1,8 @@
 use anyhow::Result;
-use axum::extract::ws::{Message, WebSocket};
+use axum::extract::ws::{CloseFrame, Message};
+use dashmap::DashMap;
 use futures_util::{sink::SinkExt, stream::StreamExt};
+use prometheus::Gauge;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
@@ -10,7 +12,45 @@ use uuid::Uuid;
 
 use crate::api::AppState;
 use crate::auth::AuthContext;
-use crate::models::{Event, UsageMetric, UsageRecord};
+use crate::backpressure::{
+    close_lagged_connections_enabled, delivery_buffer_capacity, next_delivery,
+    spawn_acked_delivery, Delivery, DEFAULT_DELIVERY_BUFFER_SIZE, LAGGED_CLOSE_CODE,
+    LAGGED_CLOSE_REASON,
+};
+use crate::cloudevents::{CloudEvent, DeliveryFormat, EventEncoding};
+use crate::drain::{prune_closed_connections_enabled, record_closed_connection_pruned};
+use crate::idle::{
+    idle_timeout, record_idle_connection_closed, websocket_ping_interval, ActivityTracker,
+    DEFAULT_IDLE_TIMEOUT_SECS, IDLE_CLOSE_CODE, IDLE_CLOSE_REASON,
+};
+use crate::models::{topic_allowed, Event, Project, UsageMetric, UsageRecord};
+use crate::nats::SubscriptionConfig;
+use crate::observability::websocket_connections_gauge;
+use crate::ordering::ordering_verifier;
+use crate::project_archive::{PROJECT_ARCHIVED_CLOSE_CODE, PROJECT_ARCHIVED_CLOSE_REASON};
+use crate::request_id::current_request_id;
+use crate::ws_compression::WebSocket;
+use crate::ws_framing::WireFormat;
+use crate::ws_rate_limit::{
+    record_inbound_message_throttled, InboundRateLimiter, RATE_LIMITED_CLOSE_CODE,
+    RATE_LIMITED_CLOSE_REASON,
+};
+use crate::ws_resume::{missed_events, resync_message, ResumeCursor};
+
+/// Close code for a handshake whose `access_token` is invalid
+pub const UNAUTHORIZED_CLOSE_CODE: u16 = 4401;
+
+/// Close reason for a handshake whose `access_token` is invalid
+pub const UNAUTHORIZED_CLOSE_REASON: &str = "unauthorized";
+
+/// Distinct topics a connection may subscribe to when its project sets no limit
+pub const DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION: i32 = 100;
+
+/// Close code for a handshake requesting more topics than its project allows
+pub const SUBSCRIPTION_LIMIT_CLOSE_CODE: u16 = 1008;
+
+/// Close reason for a handshake requesting more topics than its project allows
+pub const SUBSCRIPTION_LIMIT_CLOSE_REASON: &str = "subscription_limit_exceeded";
 
 /// WebSocket connection parameters
 #[derive(Debug, Clone)]
@@ -19,6 +59,13 @@ pub struct WebSocketConnectionParams {
     pub project_id: String,
     pub topics: Vec<String>,
     pub auth_context: AuthContext,
+    /// Encoding of delivered events
+    pub format: DeliveryFormat,
+    /// Cursor a reconnecting client resumes delivery after
+    pub resume: Option<ResumeCursor>,
+    /// Deliver through an acking consumer, redelivering events the
+    /// connection's buffer has no room for instead of dropping them
+    pub acked: bool,
 }
 
 /// WebSocket message types
@@ -39,6 +86,22 @@ pub enum WebSocketMessage {
         topic: String,
         payload: serde_json::Value,
         published_at: String,
+        /// Stream sequence, present for live deliveries
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sequence: Option<u64>,
+        /// Ordering key the event was published with
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        partition_key: Option<String>,
+
+        /// Producer headers the event was published with
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        headers: HashMap<String, String>,
+        /// Producer metadata the event was published with
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        metadata: HashMap<String, String>,
+        /// Attributes the event was published with
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        attributes: HashMap<String, String>,
     },
     /// Connection acknowledgment
     Connected {
@@ -48,11 +111,55 @@ pub enum WebSocketMessage {
     Error {
         message: String,
     },
+    /// Server-initiated close with a structured reason
+    Close {
+        code: u16,
+        reason: String,
+    },
+    /// The connection fell behind and `dropped` events were lost; replay from `cursor`
+    Lagged {
+        dropped: u64,
+        /// Sequence of the last event delivered before the gap
+        cursor: Option<u64>,
+    },
     /// Ping/Pong for keepalive
     Ping,
     Pong,
 }
 
+impl From<&Event> for WebSocketMessage {
+    fn from(event: &Event) -> Self {
+        WebSocketMessage::Event {
+            id: event.id.clone(),
+            topic: event.topic.clone(),
+            payload: event.payload.clone(),
+            published_at: event.published_at.to_rfc3339(),
+            sequence: None,
+            partition_key: event.partition_key.clone(),
+            headers: event.headers.clone(),
+            metadata: event.metadata.clone(),
+            attributes: event.attributes.clone(),
+        }
+    }
+}
+
+impl WebSocketMessage {
+    /// Event delivery carrying its stream sequence
+    pub fn sequenced(event: &Event, sequence: Option<u64>) -> Self {
+        WebSocketMessage::Event {
+            id: event.id.clone(),
+            topic: event.topic.clone(),
+            payload: event.payload.clone(),
+            published_at: event.published_at.to_rfc3339(),
+            sequence,
+            partition_key: event.partition_key.clone(),
+            headers: event.headers.clone(),
+            metadata: event.metadata.clone(),
+            attributes: event.attributes.clone(),
+        }
+    }
+}
+
 /// WebSocket connection state
 #[derive(Debug, Clone)]
 pub struct WebSocketConnection {
@@ -62,13 +169,44 @@ pub struct WebSocketConnection {
     pub subscribed_topics: Vec<String>,
     pub sender: broadcast::Sender<WebSocketMessage>,
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// Fed by its own acking consumer rather than the live fan-out
+    pub acked: bool,
+}
+
+/// Check that subscribing to `topics` on top of `subscribed_topics` keeps a
+/// connection within `max_subscriptions` distinct topics
+pub fn check_subscription_limit(
+    subscribed_topics: &[String],
+    topics: &[String],
+    max_subscriptions: usize,
+) -> Result<(), String> {
+    let mut combined: Vec<&String> = subscribed_topics.iter().chain(topics).collect();
+    combined.sort();
+    combined.dedup();
+    if combined.len() > max_subscriptions {
+        return Err(format!(
+            "Subscription limit exceeded: {} topics requested, at most {} per connection",
+            combined.len(),
+            max_subscriptions
+        ));
+    }
+    Ok(())
+}
+
+/// Whether a topic matches a subscription's NATS-style subjects, token by token
+/// with `*` and `>` wildcards (empty subscriptions match every topic)
+pub fn topic_matches(subscribed_topics: &[String], topic: &str) -> bool {
+    topic_allowed(subscribed_topics, topic)
 }
 
 /// Global WebSocket connection manager
 #[derive(Debug, Clone)]
 pub struct WebSocketManager {
-    connections: Arc<Mutex<HashMap<String, WebSocketConnection>>>,
-    connection_limits: Arc<Mutex<HashMap<String, i32>>>, // tenant_id -> limit
+    // Sharded so broadcasts and lookups don't serialize on a single lock
+    connections: Arc<DashMap<String, WebSocketConnection>>,
+    connection_limits: Arc<Mutex<HashMap<String, i32>>>, // project_id -> limit
+    // Tracks the number of connections; only the global manager reports one
+    active_gauge: Option<Gauge>,
 }
 
 impl Default for WebSocketManager {
@@ -80,39 +218,64 @@ impl Default for WebSocketManager {
 impl WebSocketManager {
     pub fn new() -> Self {
         Self {
-            connections: Arc::new(Mutex::new(HashMap::new())),
+            connections: Arc::new(DashMap::new()),
             connection_limits: Arc::new(Mutex::new(HashMap::new())),
+            active_gauge: None,
+        }
+    }
+
+    /// Manager that keeps `gauge` equal to its number of connections
+    pub fn with_active_gauge(gauge: Gauge) -> Self {
+        Self {
+            active_gauge: Some(gauge),
+            ..Self::new()
+        }
+    }
+
+    /// Number of open connections
+    pub fn connection_count(&self) -> usize {
+        self.connections.len()
+    }
+
+    // Adjust the active gauge by the change in connection count
+    fn record_active_change(&self, delta: i64) {
+        if let Some(gauge) = &self.active_gauge {
+            gauge.add(delta as f64);
         }
     }
 
     /// Add a new connection
     pub fn add_connection(&self, connection: WebSocketConnection) -> Result<(), String> {
-        let mut connections = self.connections.lock().unwrap();
+        // Holding the limits lock serializes admissions so concurrent adds can't
+        // overshoot a project's limit; broadcasts never take this lock
+        let limits = self.connection_limits.lock().unwrap();
+        let limit = limits.get(&connection.project_id).unwrap_or(&1000); // Default limit
 
         // Check connection limits
-        let tenant_connection_count = connections
-            .values()
-            .filter(|conn| conn.tenant_id == connection.tenant_id)
-            .count();
-
-        let limits = self.connection_limits.lock().unwrap();
-        let limit = limits.get(&connection.tenant_id).unwrap_or(&1000); // Default limit
+        let project_connection_count = self.get_project_connection_count(&connection.project_id);
 
-        if tenant_connection_count >= *limit as usize {
+        if project_connection_count >= *limit as usize {
             return Err(format!(
-                "Connection limit exceeded for tenant {}: {}/{}",
-                connection.tenant_id, tenant_connection_count, limit
+                "Connection limit exceeded for project {}: {}/{}",
+                connection.project_id, project_connection_count, limit
             ));
         }
 
-        connections.insert(connection.id.clone(), connection);
+        if self
+            .connections
+            .insert(connection.id.clone(), connection)
+            .is_none()
+        {
+            self.record_active_change(1);
+        }
         Ok(())
     }
 
     /// Remove a connection
     pub fn remove_connection(&self, connection_id: &str) {
-        let mut connections = self.connections.lock().unwrap();
-        connections.remove(connection_id);
+        if self.connections.remove(connection_id).is_some() {
+            self.record_active_change(-1);
+        }
     }
 
     /// Get connections for a tenant/project/topic
@@ -122,54 +285,129 @@ impl WebSocketManager {
         project_id: &str,
         topic: &str,
     ) -> Vec<WebSocketConnection> {
-        let connections = self.connections.lock().unwrap();
-        connections
-            .values()
-            .filter(|conn| {
-                conn.tenant_id == tenant_id
-                    && conn.project_id == project_id
-                    && (conn.subscribed_topics.is_empty()
-                        || conn.subscribed_topics.iter().any(|t| topic.starts_with(t)))
-            })
-            .cloned()
-            .collect()
+        let mut matching = Vec::new();
+        self.for_each_connection_for_event(tenant_id, project_id, topic, |conn| {
+            matching.push(conn.clone())
+        });
+        matching
+    }
+
+    /// Visit every connection subscribed to a tenant/project/topic without
+    /// cloning it. Only one shard is read-locked at a time, so `f` must not
+    /// add or remove connections.
+    pub fn for_each_connection_for_event<F>(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+        topic: &str,
+        mut f: F,
+    ) where
+        F: FnMut(&WebSocketConnection),
+    {
+        for entry in self.connections.iter() {
+            let conn = entry.value();
+            if !conn.acked
+                && conn.tenant_id == tenant_id
+                && conn.project_id == project_id
+                && topic_matches(&conn.subscribed_topics, topic)
+            {
+                f(conn);
+            }
+        }
+    }
+
+    /// Check that a connection can subscribe to `topics` without holding more
+    /// than `max_subscriptions` distinct topics. Rejections leave the
+    /// connection's existing subscriptions untouched.
+    pub fn check_subscription_limit(
+        &self,
+        connection_id: &str,
+        topics: &[String],
+        max_subscriptions: usize,
+    ) -> Result<(), String> {
+        match self.connections.get(connection_id) {
+            Some(conn) => {
+                check_subscription_limit(&conn.subscribed_topics, topics, max_subscriptions)
+            }
+            None => check_subscription_limit(&[], topics, max_subscriptions),
+        }
     }
 
     /// Get connection count for a tenant
     pub fn get_tenant_connection_count(&self, tenant_id: &str) -> usize {
-        let connections = self.connections.lock().unwrap();
-        connections
-            .values()
-            .filter(|conn| conn.tenant_id == tenant_id)
+        self.connections
+            .iter()
+            .filter(|entry| entry.value().tenant_id == tenant_id)
             .count()
     }
 
-    /// Set connection limit for a tenant
-    pub fn set_connection_limit(&self, tenant_id: String, limit: i32) {
+    /// Get connection count for a project
+    pub fn get_project_connection_count(&self, project_id: &str) -> usize {
+        self.connections
+            .iter()
+            .filter(|entry| entry.value().project_id == project_id)
+            .count()
+    }
+
+    /// Set connection limit for a project
+    pub fn set_connection_limit(&self, project_id: String, limit: i32) {
         let mut limits = self.connection_limits.lock().unwrap();
-        limits.insert(tenant_id, limit);
+        limits.insert(project_id, limit);
     }
 
-    /// Terminate all connections for a tenant (for suspension)
-    pub fn terminate_tenant_connections(&self, tenant_id: &str) -> Vec<String> {
-        let mut connections = self.connections.lock().unwrap();
-        let connection_ids: Vec<String> = connections
+    /// List (connection id, created_at) for every connection of a project
+    pub fn project_connections(
+        &self,
+        tenant_id: &str,
+        project_id: &str,
+    ) -> Vec<(String, chrono::DateTime<chrono::Utc>)> {
+        self.connections
             .iter()
-            .filter(|(_, conn)| conn.tenant_id == tenant_id)
-            .map(|(id, _)| id.clone())
-            .collect();
-
-        // Send termination message to all tenant connections
-        for (_, conn) in connections.iter() {
-            if conn.tenant_id == tenant_id {
-                let _ = conn.sender.send(WebSocketMessage::Error {
-                    message: "Tenant suspended - connection terminated".to_string(),
+            .filter(|entry| {
+                let conn = entry.value();
+                conn.tenant_id == tenant_id && conn.project_id == project_id
+            })
+            .map(|entry| (entry.id.clone(), entry.created_at))
+            .collect()
+    }
+
+    /// Close specific connections with a close code and reason
+    pub fn close_connections(&self, connection_ids: &[String], code: u16, reason: &str) {
+        for connection_id in connection_ids {
+            if let Some((_, conn)) = self.connections.remove(connection_id) {
+                self.record_active_change(-1);
+                let _ = conn.sender.send(WebSocketMessage::Close {
+                    code,
+                    reason: reason.to_string(),
                 });
             }
         }
+    }
 
-        // Remove connections
-        connections.retain(|_, conn| conn.tenant_id != tenant_id);
+    /// Close every connection with a close code and reason, returning their ids
+    pub fn close_all_connections(&self, code: u16, reason: &str) -> Vec<String> {
+        let connection_ids: Vec<String> =
+            self.connections.iter().map(|entry| entry.key().clone()).collect();
+        self.close_connections(&connection_ids, code, reason);
+        connection_ids
+    }
+
+    /// Terminate all connections for a tenant (for suspension)
+    pub fn terminate_tenant_connections(&self, tenant_id: &str) -> Vec<String> {
+        let mut connection_ids = Vec::new();
+
+        // Send termination message to all tenant connections and remove them
+        self.connections.retain(|id, conn| {
+            if conn.tenant_id != tenant_id {
+                return true;
+            }
+            let _ = conn.sender.send(WebSocketMessage::Error {
+                message: "Tenant suspended - connection terminated".to_string(),
+            });
+            connection_ids.push(id.clone());
+            false
+        });
+        self.record_active_change(-(connection_ids.len() as i64));
 
         connection_ids
     }
@@ -177,7 +415,90 @@ impl WebSocketManager {
 
 // Global WebSocket manager instance
 lazy_static::lazy_static! {
-    static ref WEBSOCKET_MANAGER: WebSocketManager = WebSocketManager::new();
+    static ref WEBSOCKET_MANAGER: WebSocketManager =
+        WebSocketManager::with_active_gauge(websocket_connections_gauge().clone());
+}
+
+/// Get the global WebSocket manager
+pub fn websocket_manager() -> &'static WebSocketManager {
+    &WEBSOCKET_MANAGER
+}
+
+/// Next message to write to a connection, or `None` once its channel closes.
+/// Overflow of the delivery channel becomes a `Lagged` notification carrying
+/// the sequence of the last event delivered, which is tracked in `cursor`.
+pub async fn next_outgoing_message(
+    receiver: &mut broadcast::Receiver<WebSocketMessage>,
+    connection_id: &str,
+    cursor: &mut Option<u64>,
+) -> Option<WebSocketMessage> {
+    match next_delivery(receiver, "websocket", connection_id).await {
+        Delivery::Message(message) => {
+            if let WebSocketMessage::Event {
+                sequence: Some(sequence),
+                ..
+            } = &message
+            {
+                *cursor = Some(*sequence);
+            }
+            Some(message)
+        }
+        Delivery::Lagged(dropped) => Some(WebSocketMessage::Lagged {
+            dropped,
+            cursor: *cursor,
+        }),
+        Delivery::Closed => None,
+    }
+}
+
+// CloudEvents envelope for an event delivery on a connection that asked for one
+fn cloud_event_for(encoding: &EventEncoding, message: &WebSocketMessage) -> Option<CloudEvent> {
+    match message {
+        WebSocketMessage::Event {
+            id,
+            topic,
+            payload,
+            published_at,
+            sequence,
+            partition_key,
+            headers,
+            ..
+        } => encoding
+            .cloud_event(
+                id,
+                topic,
+                payload,
+                published_at,
+                *sequence,
+                partition_key.as_deref(),
+            )
+            .map(|cloud_event| cloud_event.with_headers(headers)),
+        _ => None,
+    }
+}
+
+/// JSON text frame for a message; event deliveries are wrapped in a
+/// CloudEvents envelope when the connection asked for one
+pub fn encode_message(
+    encoding: &EventEncoding,
+    message: &WebSocketMessage,
+) -> serde_json::Result<String> {
+    match cloud_event_for(encoding, message) {
+        Some(cloud_event) => serde_json::to_string(&cloud_event),
+        None => serde_json::to_string(message),
+    }
+}
+
+/// Frame for a message in the connection's negotiated framing
+pub fn encode_frame(
+    framing: WireFormat,
+    encoding: &EventEncoding,
+    message: &WebSocketMessage,
+) -> Result<Message> {
+    match cloud_event_for(encoding, message) {
+        Some(cloud_event) => framing.encode(&cloud_event),
+        None => framing.encode(message),
+    }
 }
 
 /// Handle a WebSocket connection
@@ -187,14 +508,69 @@ pub async fn handle_websocket_connection(
     state: AppState,
 ) {
     let connection_id = Uuid::new_v4().to_string();
+    // JSON text frames unless the client negotiated MessagePack
+    let framing = WireFormat::from_protocol(socket.protocol());
 
     info!(
-        "New WebSocket connection {} for tenant/project: {}/{}",
-        connection_id, params.tenant_id, params.project_id
+        correlation_id = current_request_id().as_deref(),
+        "New WebSocket connection {} for tenant/project: {}/{} ({:?} framing)",
+        connection_id, params.tenant_id, params.project_id, framing
     );
 
-    // Create broadcast channel for this connection
-    let (sender, mut receiver) = broadcast::channel(1000);
+    let project = state
+        .database
+        .get_project_with_tenant(&params.tenant_id, &params.project_id)
+        .await
+        .ok()
+        .flatten();
+
+    // JWTs outlive their project, so the archive is checked here as well as at key lookup
+    if project.as_ref().is_some_and(Project::is_archived) {
+        warn!(
+            "Rejecting WebSocket connection {}: project {} is archived",
+            connection_id, params.project_id
+        );
+        reject_websocket_connection(
+            socket,
+            PROJECT_ARCHIVED_CLOSE_CODE,
+            PROJECT_ARCHIVED_CLOSE_REASON,
+        )
+        .await;
+        return;
+    }
+
+    // Create broadcast channel for this connection, sized by the project's delivery buffer
+    let capacity = delivery_buffer_capacity(
+        project
+            .as_ref()
+            .map_or(DEFAULT_DELIVERY_BUFFER_SIZE, |p| p.limits.delivery_buffer_size),
+    );
+    let (sender, mut receiver) = broadcast::channel(capacity);
+    let idle_timeout = idle_timeout(
+        project
+            .as_ref()
+            .map_or(DEFAULT_IDLE_TIMEOUT_SECS, |p| p.limits.idle_timeout_secs),
+    );
+    let max_subscriptions = project
+        .as_ref()
+        .map_or(DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION, |p| {
+            p.limits.max_subscriptions_per_connection
+        })
+        .max(1) as usize;
+
+    if let Err(message) = check_subscription_limit(&[], &params.topics, max_subscriptions) {
+        warn!(
+            "Rejecting WebSocket connection {}: {}",
+            connection_id, message
+        );
+        reject_websocket_connection(
+            socket,
+            SUBSCRIPTION_LIMIT_CLOSE_CODE,
+            SUBSCRIPTION_LIMIT_CLOSE_REASON,
+        )
+        .await;
+        return;
+    }
 
     // Create connection object
     let connection = WebSocketConnection {
@@ -204,22 +580,31 @@ pub async fn handle_websocket_connection(
         subscribed_topics: params.topics.clone(),
         sender: sender.clone(),
         created_at: chrono::Utc::now(),
+        acked: params.acked,
     };
 
-    // Add connection to manager
-    if let Err(e) = WEBSOCKET_MANAGER.add_connection(connection.clone()) {
+    // Queue the snapshot for stateful topics, then register for live delivery
+    let registered = state.event_service.snapshots().deliver_snapshot(
+        &params.tenant_id,
+        &params.project_id,
+        &params.topics,
+        |snapshot| {
+            for event in &snapshot {
+                let _ = sender.send(WebSocketMessage::from(event));
+            }
+            WEBSOCKET_MANAGER.add_connection(connection.clone())
+        },
+    );
+
+    if let Err(e) = registered {
         error!("Failed to add WebSocket connection: {}", e);
         return;
     }
 
     // Set connection limit based on project limits
-    if let Ok(Some(project)) = state
-        .database
-        .get_project_with_tenant(&params.tenant_id, &params.project_id)
-        .await
-    {
+    if let Some(project) = &project {
         WEBSOCKET_MANAGER
-            .set_connection_limit(params.tenant_id.clone(), project.limits.max_connections);
+            .set_connection_limit(params.project_id.clone(), project.limits.max_connections);
     }
 
     // Split the socket into sender and receiver
@@ -230,27 +615,43 @@ pub async fn handle_websocket_connection(
         connection_id: connection_id.clone(),
     };
 
-    if let Ok(msg_json) = serde_json::to_string(&connected_msg) {
-        if let Err(e) = ws_sender.send(Message::Text(msg_json)).await {
+    if let Ok(frame) = framing.encode(&connected_msg) {
+        if let Err(e) = ws_sender.send(frame).await {
             error!("Failed to send connection acknowledgment: {}", e);
             WEBSOCKET_MANAGER.remove_connection(&connection_id);
             return;
         }
     }
 
-    // Subscribe to initial topics if provided
-    if !params.topics.is_empty() {
-        if let Err(e) = subscribe_to_topics(
-            &state,
-            &params.tenant_id,
-            &params.project_id,
-            &params.topics,
-        )
-        .await
-        {
-            warn!("Failed to subscribe to initial topics: {}", e);
+    // Acked connections are fed by their own consumer, the rest by the live fan-out
+    let acked_delivery = if params.acked {
+        Some(spawn_acked_delivery(
+            state.event_service.clone(),
+            SubscriptionConfig::acked(
+                &params.tenant_id,
+                &params.project_id,
+                params.topics.clone(),
+                format!("websocket_{}", connection_id),
+            ),
+            sender.clone(),
+            capacity,
+            |event| WebSocketMessage::from(event),
+        ))
+    } else {
+        if !params.topics.is_empty() {
+            if let Err(e) = subscribe_to_topics(
+                &state,
+                &params.tenant_id,
+                &params.project_id,
+                &params.topics,
+            )
+            .await
+            {
+                warn!("Failed to subscribe to initial topics: {}", e);
+            }
         }
-    }
+        None
+    };
 
     // Track WebSocket connection usage
     let usage_record = UsageRecord::new(
@@ -263,23 +664,119 @@ pub async fn handle_websocket_connection(
             .and_hms_opt(0, 0, 0)
             .unwrap()
             .and_utc(),
-    );
+    )
+    .with_key_id(params.auth_context.api_key_id().map(str::to_string));
 
-    if let Err(e) = state.database.create_usage_record(&usage_record).await {
+    if let Err(e) = state.event_service.record_usage(&usage_record).await {
         warn!("Failed to track WebSocket usage: {}", e);
     }
 
+    // The connection is already registered, so live events published while the
+    // missed ones are replayed queue up behind them rather than being lost
+    let (replay, replayed_through) = match &params.resume {
+        Some(resume) => match missed_events(&state, &params, resume).await {
+            Ok(missed) => {
+                let replayed_through = missed.last().map_or(0, |(_, sequence)| *sequence);
+                let replay: Vec<WebSocketMessage> = missed
+                    .iter()
+                    .map(|(event, sequence)| WebSocketMessage::sequenced(event, Some(*sequence)))
+                    .collect();
+                (replay, replayed_through)
+            }
+            Err(reason) => {
+                warn!(
+                    "WebSocket connection {} must resync: {}",
+                    connection_id, reason
+                );
+                (vec![resync_message(&reason)], 0)
+            }
+        },
+        None => (Vec::new(), 0),
+    };
+
+    // Frames received from the client, including pongs to the server's pings,
+    // and frames sent to it both count as activity
+    let activity = ActivityTracker::new();
+
     // Spawn task to handle outgoing messages
     let connection_id_clone = connection_id.clone();
-    let outgoing_task = tokio::spawn(async move {
-        while let Ok(message) = receiver.recv().await {
-            if let Ok(msg_json) = serde_json::to_string(&message) {
-                if let Err(e) = ws_sender.send(Message::Text(msg_json)).await {
+    let encoding = EventEncoding::new(params.format, &params.tenant_id, &params.project_id);
+    let outgoing_activity = activity.clone();
+    let mut outgoing_task = tokio::spawn(async move {
+        let mut cursor = None;
+        // Passive subscribers send nothing, so the server pings them instead
+        let ping_interval = websocket_ping_interval(idle_timeout);
+        let mut ping = tokio::time::interval_at(
+            tokio::time::Instant::now() + ping_interval,
+            ping_interval,
+        );
+        // Catch up on missed events before live delivery
+        let mut replay = replay.into_iter();
+        loop {
+            let (message, replayed) = if let Some(message) = replay.next() {
+                (message, true)
+            } else {
+                let next = next_outgoing_message(&mut receiver, &connection_id_clone, &mut cursor);
+                tokio::select! {
+                    message = next => match message {
+                        Some(message) => (message, false),
+                        None => break,
+                    },
+                    _ = ping.tick() => {
+                        if let Err(e) = ws_sender.send(Message::Ping(Vec::new())).await {
+                            debug!(
+                                "Failed to ping WebSocket connection {}: {}",
+                                connection_id_clone, e
+                            );
+                            break;
+                        }
+                        continue;
+                    }
+                }
+            };
+            if let WebSocketMessage::Event {
+                sequence: Some(sequence),
+                ..
+            } = &message
+            {
+                // Live copies of events already sent during replay
+                if !replayed && *sequence <= replayed_through {
+                    continue;
+                }
+                if replayed {
+                    cursor = Some(*sequence);
+                }
+                ordering_verifier().observe(&connection_id_clone, *sequence);
+            }
+            let lagged = matches!(message, WebSocketMessage::Lagged { .. });
+            if let WebSocketMessage::Close { code, reason } = message {
+                let _ = ws_sender
+                    .send(Message::Close(Some(CloseFrame {
+                        code,
+                        reason: reason.into(),
+                    })))
+                    .await;
+                break;
+            }
+            if let Ok(frame) = encode_frame(framing, &encoding, &message) {
+                if let Err(e) = ws_sender.send(frame).await {
                     error!("Failed to send WebSocket message: {}", e);
                     break;
                 }
+                outgoing_activity.touch();
+            }
+            // Close lagging connections so the client reconnects and replays from its cursor
+            if lagged && close_lagged_connections_enabled() {
+                let _ = ws_sender
+                    .send(Message::Close(Some(CloseFrame {
+                        code: LAGGED_CLOSE_CODE,
+                        reason: LAGGED_CLOSE_REASON.into(),
+                    })))
+                    .await;
+                break;
             }
         }
+        ordering_verifier().forget(&connection_id_clone);
         debug!(
             "Outgoing message task ended for connection {}",
             connection_id_clone
@@ -290,18 +787,73 @@ pub async fn handle_websocket_connection(
     let connection_id_clone = connection_id.clone();
     let state_clone = state.clone();
     let params_clone = params.clone();
+    let mut inbound_limiter = InboundRateLimiter::from_config();
 
-    while let Some(msg) = ws_receiver.next().await {
+    // Any inbound frame (message, ping or pong) counts as activity
+    loop {
+        let msg = match tokio::time::timeout(activity.remaining(idle_timeout), ws_receiver.next())
+            .await
+        {
+            Ok(Some(msg)) => {
+                activity.touch();
+                msg
+            }
+            Ok(None) => break,
+            // Frames went out in the meantime; wait out the rest
+            Err(_) if activity.idle_for() < idle_timeout => continue,
+            Err(_) => {
+                info!(
+                    "WebSocket connection {} idle for {:?}, closing",
+                    connection_id_clone, idle_timeout
+                );
+                WEBSOCKET_MANAGER.close_connections(
+                    &[connection_id_clone.clone()],
+                    IDLE_CLOSE_CODE,
+                    IDLE_CLOSE_REASON,
+                );
+                record_idle_connection_closed("websocket");
+                // Give the outgoing task a moment to flush the Close frame
+                let _ =
+                    tokio::time::timeout(std::time::Duration::from_secs(1), &mut outgoing_task)
+                        .await;
+                break;
+            }
+        };
         match msg {
-            Ok(Message::Text(text)) => {
-                if let Err(e) = handle_websocket_message(
-                    &text,
-                    &connection_id_clone,
-                    &params_clone,
-                    &state_clone,
-                )
-                .await
-                {
+            Ok(frame @ (Message::Text(_) | Message::Binary(_))) => {
+                match admit_inbound_message(&mut inbound_limiter, &connection_id_clone) {
+                    InboundAdmission::Admitted => {}
+                    InboundAdmission::Throttled => continue,
+                    InboundAdmission::Closed => {
+                        let _ = tokio::time::timeout(
+                            std::time::Duration::from_secs(1),
+                            &mut outgoing_task,
+                        )
+                        .await;
+                        break;
+                    }
+                }
+                let Some(decoded) = framing.decode::<WebSocketMessage>(&frame) else {
+                    debug!(
+                        "Ignoring frame outside {:?} framing from connection {}",
+                        framing, connection_id_clone
+                    );
+                    continue;
+                };
+                let handled = match decoded {
+                    Ok(ws_message) => {
+                        handle_websocket_message(
+                            ws_message,
+                            &connection_id_clone,
+                            &params_clone,
+                            &state_clone,
+                            max_subscriptions,
+                        )
+                        .await
+                    }
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = handled {
                     error!("Error handling WebSocket message: {}", e);
 
                     let error_msg = WebSocketMessage::Error {
@@ -326,13 +878,13 @@ pub async fn handle_websocket_connection(
                 let _ = sender.send(pong_msg);
             }
             Ok(Message::Pong(_)) => {
-                // Handle pong response
+                // Answer to a server ping; already counted as activity
                 debug!("Received pong from connection {}", connection_id_clone);
             }
             Ok(_) => {
-                // Handle other message types (binary, etc.)
+                // Raw frames aren't surfaced by the transport
                 debug!(
-                    "Received non-text message from connection {}",
+                    "Received unexpected frame from connection {}",
                     connection_id_clone
                 );
             }
@@ -350,49 +902,119 @@ pub async fn handle_websocket_connection(
     info!("Cleaning up WebSocket connection {}", connection_id);
     WEBSOCKET_MANAGER.remove_connection(&connection_id);
     outgoing_task.abort();
+    if let Some(acked_delivery) = acked_delivery {
+        acked_delivery.abort();
+    }
+}
+
+/// Outcome of charging an inbound message to a connection's rate limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InboundAdmission {
+    /// Within budget; handle the message
+    Admitted,
+    /// Over budget; the client was sent an error and the message is dropped
+    Throttled,
+    /// Over budget and the connection was closed
+    Closed,
+}
+
+/// Charge an inbound message to the connection's rate limit. Over budget, the
+/// client is sent an error and, if the limiter says so, the connection is closed.
+pub fn admit_inbound_message(
+    limiter: &mut InboundRateLimiter,
+    connection_id: &str,
+) -> InboundAdmission {
+    let status = limiter.try_acquire();
+    if status.allowed {
+        return InboundAdmission::Admitted;
+    }
+
+    record_inbound_message_throttled();
+    warn!(
+        "Connection {} exceeded {} inbound messages per second",
+        connection_id, status.limit
+    );
+    if let Some(conn) = WEBSOCKET_MANAGER.connections.get(connection_id) {
+        let _ = conn.sender.send(WebSocketMessage::Error {
+            message: format!(
+                "Rate limit of {} messages per second exceeded; retry in {}s",
+                status.limit, status.retry_after_secs
+            ),
+        });
+    }
+
+    if limiter.close_when_exceeded() {
+        WEBSOCKET_MANAGER.close_connections(
+            &[connection_id.to_string()],
+            RATE_LIMITED_CLOSE_CODE,
+            RATE_LIMITED_CLOSE_REASON,
+        );
+        InboundAdmission::Closed
+    } else {
+        InboundAdmission::Throttled
+    }
 }
 
 /// Handle incoming WebSocket messages
 async fn handle_websocket_message(
-    message: &str,
+    ws_message: WebSocketMessage,
     connection_id: &str,
     params: &WebSocketConnectionParams,
     state: &AppState,
+    max_subscriptions: usize,
 ) -> Result<()> {
-    let ws_message: WebSocketMessage = serde_json::from_str(message)?;
-
     match ws_message {
         WebSocketMessage::Subscribe { topics } => {
             info!(
                 "Connection {} subscribing to topics: {:?}",
                 connection_id, topics
             );
+            if params.acked {
+                anyhow::bail!("Acked connections choose their topics when they connect");
+            }
+            params
+                .auth_context
+                .check_subscribe_topics(&topics)
+                .map_err(|message| anyhow::anyhow!(message))?;
+            WEBSOCKET_MANAGER
+                .check_subscription_limit(connection_id, &topics, max_subscriptions)
+                .map_err(|message| anyhow::anyhow!(message))?;
             subscribe_to_topics(state, &params.tenant_id, &params.project_id, &topics).await?;
 
-            // Update connection's subscribed topics
-            let mut connections = WEBSOCKET_MANAGER.connections.lock().unwrap();
-            if let Some(conn) = connections.get_mut(connection_id) {
-                conn.subscribed_topics.extend(topics);
-                conn.subscribed_topics.sort();
-                conn.subscribed_topics.dedup();
-            }
+            // Send the snapshot for the new topics, then update the connection's subscribed topics
+            state.event_service.snapshots().deliver_snapshot(
+                &params.tenant_id,
+                &params.project_id,
+                &topics,
+                |snapshot| {
+                    if let Some(mut conn) = WEBSOCKET_MANAGER.connections.get_mut(connection_id) {
+                        for event in &snapshot {
+                            let _ = conn.sender.send(WebSocketMessage::from(event));
+                        }
+                        conn.subscribed_topics.extend(topics.iter().cloned());
+                        conn.subscribed_topics.sort();
+                        conn.subscribed_topics.dedup();
+                    }
+                },
+            );
         }
         WebSocketMessage::Unsubscribe { topics } => {
             info!(
                 "Connection {} unsubscribing from topics: {:?}",
                 connection_id, topics
             );
+            if params.acked {
+                anyhow::bail!("Acked connections choose their topics when they connect");
+            }
 
             // Update connection's subscribed topics
-            let mut connections = WEBSOCKET_MANAGER.connections.lock().unwrap();
-            if let Some(conn) = connections.get_mut(connection_id) {
+            if let Some(mut conn) = WEBSOCKET_MANAGER.connections.get_mut(connection_id) {
                 conn.subscribed_topics.retain(|t| !topics.contains(t));
             }
         }
         WebSocketMessage::Ping => {
             // Send pong response
-            let connections = WEBSOCKET_MANAGER.connections.lock().unwrap();
-            if let Some(conn) = connections.get(connection_id) {
+            if let Some(conn) = WEBSOCKET_MANAGER.connections.get(connection_id) {
                 let _ = conn.sender.send(WebSocketMessage::Pong);
             }
         }
@@ -433,36 +1055,49 @@ async fn subscribe_to_topics(
 }
 
 /// Broadcast an event to all relevant WebSocket connections
-pub async fn broadcast_event_to_websockets(event: &Event) -> Result<()> {
-    let connections = WEBSOCKET_MANAGER.get_connections_for_event(
+pub async fn broadcast_event_to_websockets(event: &Event, sequence: Option<u64>) -> Result<()> {
+    let ws_message = WebSocketMessage::sequenced(event, sequence);
+
+    let mut matched_count = 0;
+    let mut delivered_count = 0;
+    let mut closed_connections = Vec::new();
+
+    // Send through each connection's sender in place; closed connections are
+    // removed afterwards since the map can't be modified while it's being read
+    WEBSOCKET_MANAGER.for_each_connection_for_event(
         &event.tenant_id,
         &event.project_id,
         &event.topic,
+        |connection| {
+            matched_count += 1;
+            match connection.sender.send(ws_message.clone()) {
+                Ok(_) => delivered_count += 1,
+                Err(_) if prune_closed_connections_enabled() => {
+                    // Every receiver is gone, so this client can never be reached again
+                    closed_connections.push(connection.id.clone());
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to send event to WebSocket connection {}: {}",
+                        connection.id, e
+                    );
+                }
+            }
+        },
     );
 
-    if connections.is_empty() {
+    if matched_count == 0 {
         debug!("No WebSocket connections found for event {}", event.id);
         return Ok(());
     }
 
-    let ws_message = WebSocketMessage::Event {
-        id: event.id.clone(),
-        topic: event.topic.clone(),
-        payload: event.payload.clone(),
-        published_at: event.published_at.to_rfc3339(),
-    };
-
-    let mut delivered_count = 0;
-
-    for connection in connections {
-        if let Err(e) = connection.sender.send(ws_message.clone()) {
-            warn!(
-                "Failed to send event to WebSocket connection {}: {}",
-                connection.id, e
-            );
-        } else {
-            delivered_count += 1;
-        }
+    for connection_id in closed_connections {
+        WEBSOCKET_MANAGER.remove_connection(&connection_id);
+        record_closed_connection_pruned("websocket");
+        info!(
+            "Removed WebSocket connection {} with closed channel",
+            connection_id
+        );
     }
 
     info!(
@@ -473,6 +1108,17 @@ pub async fn broadcast_event_to_websockets(event: &Event) -> Result<()> {
     Ok(())
 }
 
+/// Close an upgraded socket straight away. Browsers can't read the status of a
+/// rejected handshake, so failures are reported through the close frame instead.
+pub async fn reject_websocket_connection(mut socket: WebSocket, code: u16, reason: &'static str) {
+    let _ = socket
+        .send(Message::Close(Some(CloseFrame {
+            code,
+            reason: reason.into(),
+        })))
+        .await;
+}
+
 /// Terminate all WebSocket connections for a suspended tenant
 pub async fn terminate_tenant_websocket_connections(tenant_id: &str) -> Vec<String> {
     info!(
@@ -484,17 +1130,18 @@ pub async fn terminate_tenant_websocket_connections(tenant_id: &str) -> Vec<Stri
 
 /// Get WebSocket connection statistics
 pub fn get_websocket_stats() -> HashMap<String, serde_json::Value> {
-    let connections = WEBSOCKET_MANAGER.connections.lock().unwrap();
+    let connections = &WEBSOCKET_MANAGER.connections;
     let mut stats = HashMap::new();
 
+    // The same count exported as `realtime_websocket_connections_active`
     stats.insert(
         "total_connections".to_string(),
-        serde_json::Value::Number(connections.len().into()),
+        serde_json::Value::Number((websocket_connections_gauge().get() as u64).into()),
     );
 
     // Count connections per tenant
     let mut tenant_counts: HashMap<String, usize> = HashMap::new();
-    for connection in connections.values() {
+    for connection in connections.iter() {
         *tenant_counts
             .entry(connection.tenant_id.clone())
             .or_insert(0) += 1;
@@ -537,10 +1184,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_topic_matching_uses_nats_wildcards() {
+        let subscribed = |topic: &str| vec![topic.to_string()];
+
+        assert!(topic_matches(&subscribed("order.*"), "order.created"));
+        assert!(!topic_matches(&subscribed("order.*"), "order.line.added"));
+        assert!(topic_matches(&subscribed("order.>"), "order.created"));
+        assert!(topic_matches(&subscribed("order.>"), "order.line.added"));
+        assert!(topic_matches(&subscribed("order"), "order"));
+        assert!(!topic_matches(&subscribed("order"), "orders.created"));
+        assert!(!topic_matches(&subscribed("order"), "order.created"));
+        assert!(topic_matches(&[], "anything"));
+    }
+
     #[test]
     fn test_connection_limits() {
         let manager = WebSocketManager::new();
-        manager.set_connection_limit("tenant_1".to_string(), 2);
+        manager.set_connection_limit("project_1".to_string(), 2);
 
         let (sender, _) = broadcast::channel(100);
 
@@ -552,6 +1213,7 @@ mod tests {
             subscribed_topics: vec![],
             sender: sender.clone(),
             created_at: chrono::Utc::now(),
+            acked: false,
         };
 
         assert!(manager.add_connection(conn1).is_ok());
@@ -565,6 +1227,7 @@ mod tests {
             subscribed_topics: vec![],
             sender: sender.clone(),
             created_at: chrono::Utc::now(),
+            acked: false,
         };
 
         assert!(manager.add_connection(conn2).is_ok());
@@ -578,9 +1241,173 @@ mod tests {
             subscribed_topics: vec![],
             sender: sender.clone(),
             created_at: chrono::Utc::now(),
+            acked: false,
         };
 
         assert!(manager.add_connection(conn3).is_err());
         assert_eq!(manager.get_tenant_connection_count("tenant_1"), 2);
+
+        // The limit is the project's; the tenant's other projects have their own
+        let other_project = WebSocketConnection {
+            id: "conn_4".to_string(),
+            tenant_id: "tenant_1".to_string(),
+            project_id: "project_2".to_string(),
+            subscribed_topics: vec![],
+            sender: sender.clone(),
+            created_at: chrono::Utc::now(),
+            acked: false,
+        };
+
+        assert!(manager.add_connection(other_project).is_ok());
+        assert_eq!(manager.get_project_connection_count("project_2"), 1);
+        assert_eq!(manager.get_tenant_connection_count("tenant_1"), 3);
+    }
+
+    #[tokio::test]
+    async fn test_overflow_delivers_lag_notification() {
+        let (sender, mut receiver) = broadcast::channel(4);
+        let event = Event::new(
+            "tenant".to_string(),
+            "project".to_string(),
+            "orders.created".to_string(),
+            serde_json::json!({}),
+        );
+
+        sender.send(WebSocketMessage::sequenced(&event, Some(1))).unwrap();
+        let mut cursor = None;
+        next_outgoing_message(&mut receiver, "conn", &mut cursor).await.unwrap();
+        assert_eq!(cursor, Some(1));
+
+        // Overflow the channel while the consumer isn't reading
+        for sequence in 2..=11 {
+            sender.send(WebSocketMessage::sequenced(&event, Some(sequence))).unwrap();
+        }
+
+        match next_outgoing_message(&mut receiver, "conn", &mut cursor).await {
+            Some(WebSocketMessage::Lagged { dropped, cursor }) => {
+                assert_eq!(dropped, 6);
+                assert_eq!(cursor, Some(1));
+            }
+            other => panic!("Expected a lag notification, got {:?}", other),
+        }
+
+        match next_outgoing_message(&mut receiver, "conn", &mut cursor).await {
+            Some(WebSocketMessage::Event { sequence, .. }) => assert_eq!(sequence, Some(8)),
+            other => panic!("Expected an event, got {:?}", other),
+        }
+        assert_eq!(cursor, Some(8));
+    }
+
+    #[tokio::test]
+    async fn test_closed_connection_removed_on_broadcast() {
+        let tenant_id = format!("tenant_{}", Uuid::new_v4());
+        let (sender, receiver) = broadcast::channel(100);
+        drop(receiver);
+
+        let connection = WebSocketConnection {
+            id: format!("conn_{}", Uuid::new_v4()),
+            tenant_id: tenant_id.clone(),
+            project_id: "project_1".to_string(),
+            subscribed_topics: vec![],
+            sender,
+            created_at: chrono::Utc::now(),
+            acked: false,
+        };
+        websocket_manager().add_connection(connection).unwrap();
+        assert_eq!(websocket_manager().get_tenant_connection_count(&tenant_id), 1);
+
+        let pruned = crate::drain::closed_connections_pruned_counter()
+            .with_label_values(&["websocket"]);
+        let pruned_before = pruned.get();
+
+        let event = Event::new(
+            tenant_id.clone(),
+            "project_1".to_string(),
+            "user.created".to_string(),
+            serde_json::json!({"id": 1}),
+        );
+        broadcast_event_to_websockets(&event, None).await.unwrap();
+
+        assert_eq!(websocket_manager().get_tenant_connection_count(&tenant_id), 0);
+        assert!(pruned.get() >= pruned_before + 1.0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_broadcast_to_thousands_of_connections() {
+        const CONNECTIONS: usize = 2000;
+        const BROADCASTS: usize = 8;
+
+        let tenant_id = format!("tenant_{}", Uuid::new_v4());
+        let project_id = format!("project_{}", Uuid::new_v4());
+        websocket_manager().set_connection_limit(project_id.clone(), CONNECTIONS as i32 + 100);
+
+        let mut receivers = Vec::with_capacity(CONNECTIONS);
+        for _ in 0..CONNECTIONS {
+            let (sender, receiver) = broadcast::channel(BROADCASTS * 2);
+            receivers.push(receiver);
+            websocket_manager()
+                .add_connection(WebSocketConnection {
+                    id: format!("conn_{}", Uuid::new_v4()),
+                    tenant_id: tenant_id.clone(),
+                    project_id: project_id.clone(),
+                    subscribed_topics: vec!["load.>".to_string()],
+                    sender,
+                    created_at: chrono::Utc::now(),
+                    acked: false,
+                })
+                .unwrap();
+        }
+
+        // Broadcast concurrently while other connections churn in and out
+        let mut tasks = Vec::new();
+        for i in 0..BROADCASTS {
+            let tenant_id = tenant_id.clone();
+            let project_id = project_id.clone();
+            tasks.push(tokio::spawn(async move {
+                let event = Event::new(
+                    tenant_id,
+                    project_id,
+                    "load.test".to_string(),
+                    serde_json::json!({"n": i}),
+                );
+                broadcast_event_to_websockets(&event, None).await.unwrap();
+            }));
+        }
+        let churn_tenant = tenant_id.clone();
+        tasks.push(tokio::spawn(async move {
+            for _ in 0..50 {
+                let (sender, _receiver) = broadcast::channel(1);
+                let id = format!("churn_{}", Uuid::new_v4());
+                websocket_manager()
+                    .add_connection(WebSocketConnection {
+                        id: id.clone(),
+                        tenant_id: churn_tenant.clone(),
+                        project_id: "project_2".to_string(),
+                        subscribed_topics: vec![],
+                        sender,
+                        created_at: chrono::Utc::now(),
+                        acked: false,
+                    })
+                    .unwrap();
+                websocket_manager().remove_connection(&id);
+            }
+        }));
+
+        tokio::time::timeout(
+            std::time::Duration::from_secs(10),
+            futures_util::future::join_all(tasks),
+        )
+        .await
+        .expect("broadcasts should not starve");
+
+        for receiver in receivers.iter_mut() {
+            let mut received = 0;
+            while receiver.try_recv().is_ok() {
+                received += 1;
+            }
+            assert_eq!(received, BROADCASTS);
+        }
+
+        websocket_manager().terminate_tenant_connections(&tenant_id);
     }
 }
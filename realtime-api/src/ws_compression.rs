@@ -0,0 +1,800 @@
+use async_trait::async_trait;
+use axum::body::Body;
+use axum::extract::ws::{CloseFrame, Message};
+use axum::extract::FromRequestParts;
+use axum::http::{header, request::Parts, HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use axum::response::Response;
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use futures_util::{Sink, SinkExt, Stream, StreamExt};
+use hyper_util::rt::TokioIo;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::frame::coding::{Data, OpCode};
+use tokio_tungstenite::tungstenite::protocol::frame::Frame;
+use tokio_tungstenite::tungstenite::protocol::{self as ts, Role};
+use tokio_tungstenite::WebSocketStream;
+use tracing::warn;
+
+// permessage-deflate (RFC 7692) for the `/ws` endpoint.
+//
+// axum's WebSocket can neither accept an extension during the upgrade nor set
+// RSV1 on frames, so `/ws` upgrades through the types here instead. Outgoing
+// messages at or above the compression threshold are sent as raw deflated
+// frames with RSV1 set; compressed client messages are inflated back into
+// plain frames before tungstenite reads them. Clients that don't offer the
+// extension get the same uncompressed framing as before.
+
+/// Extension negotiated through `Sec-WebSocket-Extensions`
+pub const PERMESSAGE_DEFLATE: &str = "permessage-deflate";
+
+/// Messages at least this large are compressed by default
+pub const DEFAULT_COMPRESSION_THRESHOLD_BYTES: usize = 1024;
+
+/// Deflate level used by default, from 0 (none) to 9 (best)
+pub const DEFAULT_COMPRESSION_LEVEL: u32 = 6;
+
+// Every sync-flushed deflate block ends with these bytes; RFC 7692 leaves
+// them off the wire
+const DEFLATE_TAIL: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+// Largest message inflated from a client, tungstenite's default message limit
+const MAX_MESSAGE_SIZE: usize = 64 << 20;
+
+// Compression settings applied to new connections
+lazy_static::lazy_static! {
+    static ref COMPRESSION_THRESHOLD_BYTES: AtomicUsize =
+        AtomicUsize::new(DEFAULT_COMPRESSION_THRESHOLD_BYTES);
+    static ref COMPRESSION_LEVEL: AtomicU32 = AtomicU32::new(DEFAULT_COMPRESSION_LEVEL);
+}
+
+/// Set the size from which messages are compressed and the deflate level
+pub fn set_compression(threshold_bytes: usize, level: u32) {
+    COMPRESSION_THRESHOLD_BYTES.store(threshold_bytes, Ordering::Relaxed);
+    COMPRESSION_LEVEL.store(level.min(9), Ordering::Relaxed);
+}
+
+/// permessage-deflate parameters agreed with a client
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeflateParams {
+    /// The client asked the server to reset its compression context after
+    /// every message
+    pub server_no_context_takeover: bool,
+}
+
+impl DeflateParams {
+    /// The first permessage-deflate offer in a `Sec-WebSocket-Extensions`
+    /// value that the server can accept
+    pub fn negotiate(offers: &str) -> Option<Self> {
+        offers.split(',').find_map(Self::accept)
+    }
+
+    fn accept(offer: &str) -> Option<Self> {
+        let mut parts = offer.split(';').map(str::trim);
+        if parts.next()? != PERMESSAGE_DEFLATE {
+            return None;
+        }
+
+        let mut params = Self::default();
+        let mut seen = Vec::new();
+        for part in parts {
+            let (name, value) = match part.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (part, None),
+            };
+            // A parameter repeated within an offer makes it invalid
+            if seen.contains(&name) {
+                return None;
+            }
+            seen.push(name);
+
+            match (name, value) {
+                ("server_no_context_takeover", None) => params.server_no_context_takeover = true,
+                // Inflating keeps the full window, whether or not the client resets its own
+                ("client_no_context_takeover", None) | ("client_max_window_bits", None) => {}
+                ("client_max_window_bits", Some(bits)) if valid_window_bits(bits) => {}
+                // Compression always uses the largest window
+                ("server_max_window_bits", Some("15")) => {}
+                _ => return None,
+            }
+        }
+        Some(params)
+    }
+
+    /// `Sec-WebSocket-Extensions` value accepting these parameters
+    pub fn response_header(&self) -> HeaderValue {
+        if self.server_no_context_takeover {
+            HeaderValue::from_static("permessage-deflate; server_no_context_takeover")
+        } else {
+            HeaderValue::from_static(PERMESSAGE_DEFLATE)
+        }
+    }
+}
+
+fn valid_window_bits(bits: &str) -> bool {
+    bits.parse::<u8>()
+        .is_ok_and(|bits| (8..=15).contains(&bits))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Compresses outgoing messages on a connection that negotiated permessage-deflate
+#[derive(Debug)]
+struct Deflater {
+    compress: Compress,
+    threshold_bytes: usize,
+    reset_after_message: bool,
+}
+
+impl Deflater {
+    fn new(params: DeflateParams) -> Self {
+        Self {
+            compress: Compress::new(
+                Compression::new(COMPRESSION_LEVEL.load(Ordering::Relaxed)),
+                false,
+            ),
+            threshold_bytes: COMPRESSION_THRESHOLD_BYTES.load(Ordering::Relaxed),
+            reset_after_message: params.server_no_context_takeover,
+        }
+    }
+
+    fn compress(&mut self, data: &[u8]) -> io::Result<Vec<u8>> {
+        let start = self.compress.total_in();
+        let mut output = Vec::with_capacity(data.len() / 2 + 64);
+        loop {
+            let consumed = (self.compress.total_in() - start) as usize;
+            if output.len() == output.capacity() {
+                output.reserve(output.capacity());
+            }
+            self.compress
+                .compress_vec(&data[consumed..], &mut output, FlushCompress::Sync)
+                .map_err(io::Error::other)?;
+            // The flush is complete once all input is taken with output space to spare
+            let consumed = (self.compress.total_in() - start) as usize;
+            if consumed == data.len() && output.len() < output.capacity() {
+                break;
+            }
+        }
+
+        if output.ends_with(&DEFLATE_TAIL) {
+            output.truncate(output.len() - DEFLATE_TAIL.len());
+        }
+        if self.reset_after_message {
+            self.compress.reset();
+        }
+        Ok(output)
+    }
+}
+
+/// Inflates compressed client messages. The window is kept across messages,
+/// which also reads clients that reset their context after each one.
+#[derive(Debug)]
+struct Inflater {
+    decompress: Decompress,
+}
+
+impl Inflater {
+    fn new() -> Self {
+        Self {
+            decompress: Decompress::new(false),
+        }
+    }
+
+    fn inflate(&mut self, mut data: Vec<u8>) -> io::Result<Vec<u8>> {
+        data.extend_from_slice(&DEFLATE_TAIL);
+        let start_in = self.decompress.total_in();
+        let mut output = Vec::with_capacity(data.len() * 2);
+        loop {
+            let consumed = (self.decompress.total_in() - start_in) as usize;
+            let produced = output.len();
+            if output.len() == output.capacity() {
+                output.reserve(output.capacity());
+            }
+            let status = self
+                .decompress
+                .decompress_vec(&data[consumed..], &mut output, FlushDecompress::Sync)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            if output.len() > MAX_MESSAGE_SIZE {
+                return Err(invalid_data("Inflated message is too large"));
+            }
+            if status == Status::StreamEnd {
+                // A final block ends the stream; the next message starts a new one
+                self.decompress.reset(false);
+                break;
+            }
+
+            let progressed = self.decompress.total_in() - start_in != consumed as u64
+                || output.len() != produced;
+            let consumed = (self.decompress.total_in() - start_in) as usize;
+            if (consumed == data.len() && output.len() < output.capacity()) || !progressed {
+                break;
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// Frames read from the client, rewritten so compressed messages arrive at
+/// tungstenite as plain ones
+#[derive(Debug)]
+struct FrameInflater {
+    inflater: Inflater,
+    /// Bytes read from the client that don't make up a whole frame yet
+    incoming: Vec<u8>,
+    /// Frames ready for tungstenite, from `ready_pos` on
+    ready: Vec<u8>,
+    ready_pos: usize,
+    /// Opcode and payload of a compressed message still arriving in fragments
+    fragmented: Option<(u8, Vec<u8>)>,
+    eof: bool,
+}
+
+impl FrameInflater {
+    fn new() -> Self {
+        Self {
+            inflater: Inflater::new(),
+            incoming: Vec::new(),
+            ready: Vec::new(),
+            ready_pos: 0,
+            fragmented: None,
+            eof: false,
+        }
+    }
+
+    /// Move the next whole frame from `incoming` to `ready`, inflating it if
+    /// compressed. Returns false when more bytes are needed.
+    fn next_frame(&mut self) -> io::Result<bool> {
+        let buf = &self.incoming;
+        if buf.len() < 2 {
+            return Ok(false);
+        }
+        let (first, second) = (buf[0], buf[1]);
+        let (length, mut header_len) = match second & 0x7f {
+            126 if buf.len() >= 4 => (u16::from_be_bytes([buf[2], buf[3]]) as u64, 4),
+            127 if buf.len() >= 10 => (u64::from_be_bytes(buf[2..10].try_into().unwrap()), 10),
+            126 | 127 => return Ok(false),
+            length => (length as u64, 2),
+        };
+        let mask_at = header_len;
+        let masked = second & 0x80 != 0;
+        if masked {
+            header_len += 4;
+        }
+        if length > MAX_MESSAGE_SIZE as u64 {
+            return Err(invalid_data("WebSocket frame is too large"));
+        }
+        let frame_len = header_len + length as usize;
+        if buf.len() < frame_len {
+            return Ok(false);
+        }
+
+        let is_final = first & 0x80 != 0;
+        let compressed = first & 0x40 != 0;
+        let opcode = first & 0x0f;
+        let is_control = opcode & 0x08 != 0;
+        if !is_control && self.fragmented.is_some() && (compressed || opcode != 0) {
+            return Err(invalid_data(
+                "Expected a continuation of a compressed message",
+            ));
+        }
+        if is_control || !(compressed || self.fragmented.is_some()) {
+            // Control frames and uncompressed messages pass through untouched
+            self.ready.extend(self.incoming.drain(..frame_len));
+            return Ok(true);
+        }
+        if compressed && opcode == 0 {
+            return Err(invalid_data("RSV1 is only set on a message's first frame"));
+        }
+
+        let mut payload = self.incoming[header_len..frame_len].to_vec();
+        if masked {
+            let mask = [
+                buf[mask_at],
+                buf[mask_at + 1],
+                buf[mask_at + 2],
+                buf[mask_at + 3],
+            ];
+            for (i, byte) in payload.iter_mut().enumerate() {
+                *byte ^= mask[i % 4];
+            }
+        }
+        self.incoming.drain(..frame_len);
+
+        let (opcode, message) = match self.fragmented.take() {
+            Some((opcode, mut message)) => {
+                message.extend_from_slice(&payload);
+                (opcode, message)
+            }
+            None => (opcode, payload),
+        };
+        if message.len() > MAX_MESSAGE_SIZE {
+            return Err(invalid_data("Compressed message is too large"));
+        }
+        if !is_final {
+            self.fragmented = Some((opcode, message));
+            return Ok(true);
+        }
+
+        let inflated = self.inflater.inflate(message)?;
+        push_client_frame(&mut self.ready, opcode, &inflated);
+        Ok(true)
+    }
+}
+
+/// Append `payload` as a single final client frame. Client frames must be
+/// masked; an all-zero key leaves the payload as it is.
+fn push_client_frame(out: &mut Vec<u8>, opcode: u8, payload: &[u8]) {
+    out.push(0x80 | opcode);
+    match payload.len() {
+        length @ 0..=125 => out.push(0x80 | length as u8),
+        length @ 126..=0xffff => {
+            out.push(0x80 | 126);
+            out.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            out.push(0x80 | 127);
+            out.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(payload);
+}
+
+/// Transport under a server WebSocket, inflating compressed client messages
+/// when permessage-deflate was negotiated and passing bytes through otherwise
+#[derive(Debug)]
+struct InflateStream<S> {
+    inner: S,
+    frames: Option<FrameInflater>,
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for InflateStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let Some(frames) = this.frames.as_mut() else {
+            return Pin::new(&mut this.inner).poll_read(cx, buf);
+        };
+
+        loop {
+            if frames.ready_pos < frames.ready.len() {
+                let ready = &frames.ready[frames.ready_pos..];
+                let n = ready.len().min(buf.remaining());
+                buf.put_slice(&ready[..n]);
+                frames.ready_pos += n;
+                if frames.ready_pos == frames.ready.len() {
+                    frames.ready.clear();
+                    frames.ready_pos = 0;
+                }
+                return Poll::Ready(Ok(()));
+            }
+            if frames.next_frame()? {
+                continue;
+            }
+            if frames.eof {
+                return Poll::Ready(Ok(()));
+            }
+
+            let mut chunk = [0u8; 8192];
+            let mut chunk = ReadBuf::new(&mut chunk);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut chunk))?;
+            if chunk.filled().is_empty() {
+                // The client hung up; hand over any partial frame as it is
+                frames.eof = true;
+                frames.ready = std::mem::take(&mut frames.incoming);
+            } else {
+                frames.incoming.extend_from_slice(chunk.filled());
+            }
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for InflateStream<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// A server WebSocket, used like axum's, that compresses outgoing messages
+/// when the client negotiated permessage-deflate
+#[derive(Debug)]
+pub struct WebSocket {
+    inner: WebSocketStream<InflateStream<TokioIo<hyper::upgrade::Upgraded>>>,
+    protocol: Option<HeaderValue>,
+    deflater: Option<Deflater>,
+}
+
+impl WebSocket {
+    /// Receive another message, or `None` once the stream has closed
+    pub async fn recv(&mut self) -> Option<Result<Message, axum::Error>> {
+        self.next().await
+    }
+
+    /// Send a message
+    pub async fn send(&mut self, message: Message) -> Result<(), axum::Error> {
+        SinkExt::send(self, message).await
+    }
+
+    /// Gracefully close this WebSocket
+    pub async fn close(mut self) -> Result<(), axum::Error> {
+        self.inner.close(None).await.map_err(axum::Error::new)
+    }
+
+    /// The subprotocol chosen during the upgrade, if any
+    pub fn protocol(&self) -> Option<&HeaderValue> {
+        self.protocol.as_ref()
+    }
+
+    /// Whether permessage-deflate was negotiated
+    pub fn is_compressed(&self) -> bool {
+        self.deflater.is_some()
+    }
+
+    fn should_compress(&self, length: usize) -> bool {
+        self.deflater
+            .as_ref()
+            .is_some_and(|deflater| length >= deflater.threshold_bytes)
+    }
+
+    fn compressed(&mut self, opcode: Data, data: &[u8]) -> Result<ts::Message, axum::Error> {
+        let deflater = self.deflater.as_mut().expect("compression was negotiated");
+        let payload = deflater.compress(data).map_err(axum::Error::new)?;
+        let mut frame = Frame::message(payload, OpCode::Data(opcode), true);
+        frame.header_mut().rsv1 = true;
+        Ok(ts::Message::Frame(frame))
+    }
+
+    fn encode(&mut self, message: Message) -> Result<ts::Message, axum::Error> {
+        match message {
+            Message::Text(text) if self.should_compress(text.len()) => {
+                self.compressed(Data::Text, text.as_bytes())
+            }
+            Message::Binary(data) if self.should_compress(data.len()) => {
+                self.compressed(Data::Binary, &data)
+            }
+            Message::Text(text) => Ok(ts::Message::Text(text)),
+            Message::Binary(data) => Ok(ts::Message::Binary(data)),
+            Message::Ping(data) => Ok(ts::Message::Ping(data)),
+            Message::Pong(data) => Ok(ts::Message::Pong(data)),
+            Message::Close(frame) => Ok(ts::Message::Close(frame.map(|frame| ts::CloseFrame {
+                code: frame.code.into(),
+                reason: frame.reason,
+            }))),
+        }
+    }
+}
+
+fn decode(message: ts::Message) -> Option<Message> {
+    match message {
+        ts::Message::Text(text) => Some(Message::Text(text)),
+        ts::Message::Binary(data) => Some(Message::Binary(data)),
+        ts::Message::Ping(data) => Some(Message::Ping(data)),
+        ts::Message::Pong(data) => Some(Message::Pong(data)),
+        ts::Message::Close(frame) => Some(Message::Close(frame.map(|frame| CloseFrame {
+            code: frame.code.into(),
+            reason: frame.reason,
+        }))),
+        // Only ever written, never read
+        ts::Message::Frame(_) => None,
+    }
+}
+
+impl Stream for WebSocket {
+    type Item = Result<Message, axum::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match ready!(self.inner.poll_next_unpin(cx)) {
+                Some(Ok(message)) => {
+                    if let Some(message) = decode(message) {
+                        return Poll::Ready(Some(Ok(message)));
+                    }
+                }
+                Some(Err(e)) => return Poll::Ready(Some(Err(axum::Error::new(e)))),
+                None => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl Sink<Message> for WebSocket {
+    type Error = axum::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner)
+            .poll_ready(cx)
+            .map_err(axum::Error::new)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
+        let message = self.encode(item)?;
+        Pin::new(&mut self.inner)
+            .start_send(message)
+            .map_err(axum::Error::new)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner)
+            .poll_flush(cx)
+            .map_err(axum::Error::new)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Pin::new(&mut self.inner)
+            .poll_close(cx)
+            .map_err(axum::Error::new)
+    }
+}
+
+/// Extractor for a WebSocket upgrade that negotiates permessage-deflate, in
+/// place of axum's `WebSocketUpgrade`
+#[derive(Debug)]
+pub struct WebSocketUpgrade {
+    sec_websocket_key: HeaderValue,
+    on_upgrade: hyper::upgrade::OnUpgrade,
+    /// Subprotocols the client offered
+    sec_websocket_protocol: Option<HeaderValue>,
+    /// Subprotocol chosen from the client's offer
+    protocol: Option<HeaderValue>,
+    deflate: Option<DeflateParams>,
+}
+
+impl WebSocketUpgrade {
+    /// Choose the first of `protocols`, most preferred first, that the client offered
+    pub fn protocols<I>(mut self, protocols: I) -> Self
+    where
+        I: IntoIterator<Item = &'static str>,
+    {
+        if let Some(offered) = self
+            .sec_websocket_protocol
+            .as_ref()
+            .and_then(|protocol| protocol.to_str().ok())
+        {
+            self.protocol = protocols
+                .into_iter()
+                .find(|protocol| offered.split(',').any(|offer| offer.trim() == *protocol))
+                .map(HeaderValue::from_static);
+        }
+        self
+    }
+
+    /// Answer the handshake and run `callback` with the socket once the
+    /// connection has been upgraded
+    #[must_use = "to set up the WebSocket connection, this response must be returned"]
+    pub fn on_upgrade<C, Fut>(self, callback: C) -> Response
+    where
+        C: FnOnce(WebSocket) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let protocol = self.protocol.clone();
+        let deflate = self.deflate;
+        let on_upgrade = self.on_upgrade;
+        tokio::spawn(async move {
+            let upgraded = match on_upgrade.await {
+                Ok(upgraded) => upgraded,
+                Err(e) => {
+                    warn!("WebSocket upgrade failed: {}", e);
+                    return;
+                }
+            };
+            let transport = InflateStream {
+                inner: TokioIo::new(upgraded),
+                frames: deflate.map(|_| FrameInflater::new()),
+            };
+            let socket = WebSocket {
+                inner: WebSocketStream::from_raw_socket(transport, Role::Server, None).await,
+                protocol,
+                deflater: deflate.map(Deflater::new),
+            };
+            callback(socket).await;
+        });
+
+        let mut response = Response::builder()
+            .status(StatusCode::SWITCHING_PROTOCOLS)
+            .header(header::CONNECTION, "upgrade")
+            .header(header::UPGRADE, "websocket")
+            .header(
+                header::SEC_WEBSOCKET_ACCEPT,
+                derive_accept_key(self.sec_websocket_key.as_bytes()),
+            );
+        if let Some(protocol) = self.protocol {
+            response = response.header(header::SEC_WEBSOCKET_PROTOCOL, protocol);
+        }
+        if let Some(deflate) = self.deflate {
+            response = response.header(header::SEC_WEBSOCKET_EXTENSIONS, deflate.response_header());
+        }
+        response.body(Body::empty()).unwrap()
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for WebSocketUpgrade
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if parts.method != Method::GET {
+            return Err((
+                StatusCode::METHOD_NOT_ALLOWED,
+                "Request method must be `GET`",
+            ));
+        }
+        if !header_contains(&parts.headers, header::CONNECTION, "upgrade") {
+            return Err((
+                StatusCode::UPGRADE_REQUIRED,
+                "Connection header did not include 'upgrade'",
+            ));
+        }
+        if !header_eq(&parts.headers, header::UPGRADE, "websocket") {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "`Upgrade` header did not include 'websocket'",
+            ));
+        }
+        if !header_eq(&parts.headers, header::SEC_WEBSOCKET_VERSION, "13") {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "`Sec-WebSocket-Version` header did not include '13'",
+            ));
+        }
+
+        let sec_websocket_key = parts
+            .headers
+            .get(header::SEC_WEBSOCKET_KEY)
+            .ok_or((
+                StatusCode::BAD_REQUEST,
+                "`Sec-WebSocket-Key` header missing",
+            ))?
+            .clone();
+        let on_upgrade = parts
+            .extensions
+            .remove::<hyper::upgrade::OnUpgrade>()
+            .ok_or((
+                StatusCode::UPGRADE_REQUIRED,
+                "WebSocket request couldn't be upgraded since no upgrade state was present",
+            ))?;
+        let deflate = parts
+            .headers
+            .get_all(header::SEC_WEBSOCKET_EXTENSIONS)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .find_map(DeflateParams::negotiate);
+
+        Ok(Self {
+            sec_websocket_key,
+            on_upgrade,
+            sec_websocket_protocol: parts.headers.get(header::SEC_WEBSOCKET_PROTOCOL).cloned(),
+            protocol: None,
+            deflate,
+        })
+    }
+}
+
+fn header_eq(headers: &HeaderMap, key: HeaderName, value: &str) -> bool {
+    headers
+        .get(&key)
+        .is_some_and(|header| header.as_bytes().eq_ignore_ascii_case(value.as_bytes()))
+}
+
+fn header_contains(headers: &HeaderMap, key: HeaderName, value: &str) -> bool {
+    headers
+        .get(&key)
+        .and_then(|header| header.to_str().ok())
+        .is_some_and(|header| header.to_ascii_lowercase().contains(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiates_first_acceptable_offer() {
+        assert_eq!(
+            DeflateParams::negotiate("permessage-deflate"),
+            Some(DeflateParams::default())
+        );
+        assert_eq!(
+            DeflateParams::negotiate(
+                "permessage-deflate; client_max_window_bits; server_no_context_takeover"
+            ),
+            Some(DeflateParams {
+                server_no_context_takeover: true
+            })
+        );
+        // A smaller server window can't be honoured, so the fallback offer is taken
+        assert_eq!(
+            DeflateParams::negotiate(
+                "permessage-deflate; server_max_window_bits=10, permessage-deflate"
+            ),
+            Some(DeflateParams::default())
+        );
+        assert_eq!(DeflateParams::negotiate("x-webkit-deflate-frame"), None);
+        assert_eq!(
+            DeflateParams::negotiate("permessage-deflate; unknown_parameter"),
+            None
+        );
+        assert_eq!(
+            DeflateParams::negotiate(
+                "permessage-deflate; server_no_context_takeover; server_no_context_takeover"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_compressed_messages_round_trip_across_context_takeover() {
+        let mut deflater = Deflater::new(DeflateParams::default());
+        let mut inflater = Inflater::new();
+
+        let message = br#"{"type":"Event","topic":"orders.created","payload":{"order_id":1}}"#;
+        let first = deflater.compress(message).unwrap();
+        let second = deflater.compress(message).unwrap();
+        assert!(!first.ends_with(&DEFLATE_TAIL));
+        // The second copy refers back to the first through the shared window
+        assert!(second.len() < first.len());
+
+        assert_eq!(inflater.inflate(first).unwrap(), message);
+        assert_eq!(inflater.inflate(second).unwrap(), message);
+    }
+
+    fn client_frame(first: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [1, 2, 3, 4];
+        let mut frame = vec![first, 0x80 | payload.len() as u8];
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        frame
+    }
+
+    #[test]
+    fn test_compressed_client_frames_are_rewritten_as_plain_frames() {
+        let mut deflater = Deflater::new(DeflateParams::default());
+        let compressed = deflater.compress(br#"{"type":"Ping"}"#).unwrap();
+        let (head, tail) = compressed.split_at(compressed.len() / 2);
+
+        let mut frames = FrameInflater::new();
+        // Fragmented: RSV1 and the opcode on the first frame only
+        frames.incoming.extend(client_frame(0x40 | 0x1, head));
+        frames.incoming.extend(client_frame(0x80, tail));
+        // An uncompressed ping passes through as it is
+        let ping = client_frame(0x80 | 0x9, b"hi");
+        frames.incoming.extend(&ping);
+
+        while frames.next_frame().unwrap() {}
+
+        let mut expected = Vec::new();
+        push_client_frame(&mut expected, 0x1, br#"{"type":"Ping"}"#);
+        expected.extend(&ping);
+        assert_eq!(frames.ready, expected);
+        assert!(frames.incoming.is_empty());
+    }
+
+    #[test]
+    fn test_rsv1_on_a_continuation_frame_is_rejected() {
+        let mut frames = FrameInflater::new();
+        frames.incoming.extend(client_frame(0x80 | 0x40, b"abc"));
+        assert!(frames.next_frame().is_err());
+    }
+}
@@ -0,0 +1,93 @@
+use anyhow::Result;
+use axum::extract::ws::Message;
+use axum::http::HeaderValue;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+// Message framing negotiated through `Sec-WebSocket-Protocol`.
+//
+// Clients that offer no subprotocol, or only `realtime.json`, exchange JSON
+// text frames. Offering `realtime.msgpack` switches both directions to
+// MessagePack binary frames with the same message shapes.
+
+/// Subprotocol selecting JSON text frames, the default
+pub const JSON_SUBPROTOCOL: &str = "realtime.json";
+
+/// Subprotocol selecting MessagePack binary frames
+pub const MSGPACK_SUBPROTOCOL: &str = "realtime.msgpack";
+
+/// Subprotocols the upgrade handler accepts, most preferred first
+pub const SUPPORTED_SUBPROTOCOLS: [&str; 2] = [MSGPACK_SUBPROTOCOL, JSON_SUBPROTOCOL];
+
+/// How messages are encoded on a WebSocket connection
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WireFormat {
+    /// JSON in text frames
+    #[default]
+    Json,
+    /// MessagePack in binary frames
+    MessagePack,
+}
+
+impl WireFormat {
+    /// Framing for the subprotocol chosen during the upgrade
+    pub fn from_protocol(protocol: Option<&HeaderValue>) -> Self {
+        match protocol.and_then(|protocol| protocol.to_str().ok()) {
+            Some(MSGPACK_SUBPROTOCOL) => WireFormat::MessagePack,
+            _ => WireFormat::Json,
+        }
+    }
+
+    /// Encode a message into a frame
+    pub fn encode<T: Serialize>(&self, message: &T) -> Result<Message> {
+        Ok(match self {
+            WireFormat::Json => Message::Text(serde_json::to_string(message)?),
+            // Named fields keep structs as maps, which internally tagged enums need
+            WireFormat::MessagePack => Message::Binary(rmp_serde::to_vec_named(message)?),
+        })
+    }
+
+    /// Decode a data frame. Returns `None` for frames that don't carry
+    /// messages in this framing (binary frames on a JSON connection and vice
+    /// versa, and control frames).
+    pub fn decode<T: DeserializeOwned>(&self, frame: &Message) -> Option<Result<T>> {
+        match (self, frame) {
+            (WireFormat::Json, Message::Text(text)) => {
+                Some(serde_json::from_str(text).map_err(Into::into))
+            }
+            (WireFormat::MessagePack, Message::Binary(bytes)) => {
+                Some(rmp_serde::from_slice(bytes).map_err(Into::into))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_framing_follows_negotiated_protocol() {
+        assert_eq!(WireFormat::from_protocol(None), WireFormat::Json);
+        assert_eq!(
+            WireFormat::from_protocol(Some(&HeaderValue::from_static(JSON_SUBPROTOCOL))),
+            WireFormat::Json
+        );
+        assert_eq!(
+            WireFormat::from_protocol(Some(&HeaderValue::from_static(MSGPACK_SUBPROTOCOL))),
+            WireFormat::MessagePack
+        );
+    }
+
+    #[test]
+    fn test_frames_of_the_other_framing_are_skipped() {
+        let text = Message::Text("{}".to_string());
+        let binary = Message::Binary(vec![0x80]);
+
+        assert!(WireFormat::Json.decode::<serde_json::Value>(&binary).is_none());
+        assert!(WireFormat::MessagePack.decode::<serde_json::Value>(&text).is_none());
+        assert!(WireFormat::Json.decode::<serde_json::Value>(&text).is_some());
+        assert!(WireFormat::MessagePack.decode::<serde_json::Value>(&binary).is_some());
+    }
+}
@@ -0,0 +1,80 @@
+use prometheus::Counter;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::Instant;
+
+use crate::rate_limit::{RateLimitStatus, TokenBucket};
+
+/// Inbound messages per second a WebSocket connection may send by default
+pub const DEFAULT_INBOUND_MESSAGES_PER_SEC: u32 = 20;
+
+/// Close code sent to a connection that exceeded its inbound message rate
+pub const RATE_LIMITED_CLOSE_CODE: u16 = 4429;
+
+/// Close reason sent to a connection that exceeded its inbound message rate
+pub const RATE_LIMITED_CLOSE_REASON: &str = "rate_limited";
+
+// Inbound message budget applied to new connections, whether exceeding it
+// closes the connection, and how many messages have been throttled
+lazy_static::lazy_static! {
+    static ref INBOUND_MESSAGES_PER_SEC: AtomicU32 =
+        AtomicU32::new(DEFAULT_INBOUND_MESSAGES_PER_SEC);
+    static ref CLOSE_RATE_LIMITED_CONNECTIONS: AtomicBool = AtomicBool::new(false);
+    static ref INBOUND_MESSAGES_THROTTLED: Counter = Counter::new(
+        "realtime_websocket_inbound_messages_throttled_total",
+        "WebSocket client messages rejected by the per-connection rate limit"
+    )
+    .expect("valid metric definition");
+}
+
+/// Set the inbound message budget and whether exceeding it closes the connection
+pub fn set_inbound_rate_limit(messages_per_sec: u32, close_when_exceeded: bool) {
+    INBOUND_MESSAGES_PER_SEC.store(messages_per_sec, Ordering::Relaxed);
+    CLOSE_RATE_LIMITED_CONNECTIONS.store(close_when_exceeded, Ordering::Relaxed);
+}
+
+/// Counter of inbound WebSocket messages rejected by the rate limit
+pub fn inbound_messages_throttled_counter() -> &'static Counter {
+    &INBOUND_MESSAGES_THROTTLED
+}
+
+/// Record that an inbound WebSocket message was throttled
+pub fn record_inbound_message_throttled() {
+    INBOUND_MESSAGES_THROTTLED.inc();
+}
+
+/// Token bucket for one connection's inbound messages, allowing a burst of
+/// one second's budget
+#[derive(Debug)]
+pub struct InboundRateLimiter {
+    bucket: TokenBucket,
+    messages_per_sec: u32,
+    close_when_exceeded: bool,
+}
+
+impl InboundRateLimiter {
+    pub fn new(messages_per_sec: u32, close_when_exceeded: bool) -> Self {
+        Self {
+            bucket: TokenBucket::full(messages_per_sec, Instant::now()),
+            messages_per_sec,
+            close_when_exceeded,
+        }
+    }
+
+    /// Limiter using the configured budget
+    pub fn from_config() -> Self {
+        Self::new(
+            INBOUND_MESSAGES_PER_SEC.load(Ordering::Relaxed),
+            CLOSE_RATE_LIMITED_CONNECTIONS.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Charge one inbound message
+    pub fn try_acquire(&mut self) -> RateLimitStatus {
+        self.bucket.try_take(self.messages_per_sec, Instant::now())
+    }
+
+    /// Whether a connection over budget is closed rather than just warned
+    pub fn close_when_exceeded(&self) -> bool {
+        self.close_when_exceeded
+    }
+}
@@ -0,0 +1,149 @@
+use futures_util::StreamExt;
+
+use crate::api::AppState;
+use crate::models::{topic_allowed, Event};
+use crate::nats::{EventCursor, ReplayRequest};
+use crate::sse::MAX_RESUME_EVENTS;
+use crate::websocket::{WebSocketConnectionParams, WebSocketMessage};
+
+/// Prefix of the error sent when missed events can't be replayed and the
+/// client has to fetch current state before subscribing again
+pub const RESYNC_REQUIRED_PREFIX: &str = "Resync required";
+
+/// Where a reconnecting WebSocket client picks up delivery
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResumeCursor {
+    /// After this stream sequence, as carried by delivered events
+    Sequence(u64),
+    /// After the event with this id
+    EventId(String),
+}
+
+impl ResumeCursor {
+    /// Cursor from the `last_sequence` and `last_event_id` connect parameters;
+    /// `last_sequence` wins when both are given. SSE frame ids are stream
+    /// sequences, so a numeric `last_event_id` resumes like `last_sequence`.
+    pub fn from_params(last_sequence: Option<u64>, last_event_id: Option<&str>) -> Option<Self> {
+        if let Some(sequence) = last_sequence {
+            return Some(Self::Sequence(sequence));
+        }
+
+        let last_event_id = last_event_id.map(str::trim).filter(|id| !id.is_empty())?;
+        Some(match last_event_id.parse() {
+            Ok(sequence) => Self::Sequence(sequence),
+            Err(_) => Self::EventId(last_event_id.to_string()),
+        })
+    }
+}
+
+/// Error message telling the client to resync because of `reason`
+pub fn resync_message(reason: &str) -> WebSocketMessage {
+    WebSocketMessage::Error {
+        message: format!("{}: {}", RESYNC_REQUIRED_PREFIX, reason),
+    }
+}
+
+/// Events a reconnecting client missed after `cursor` on its subscribed topics,
+/// oldest first and paired with their stream sequences. An error means the gap
+/// can't be replayed and the client must resync.
+pub async fn missed_events(
+    state: &AppState,
+    params: &WebSocketConnectionParams,
+    cursor: &ResumeCursor,
+) -> Result<Vec<(Event, u64)>, String> {
+    let (first_sequence, last_sequence) = state
+        .event_service
+        .stream_sequence_bounds(&params.tenant_id)
+        .await
+        .map_err(|e| format!("Failed to resume: {}", e))?;
+
+    let resume_after = match cursor {
+        ResumeCursor::Sequence(sequence) => *sequence,
+        ResumeCursor::EventId(event_id) => event_sequence(state, params, event_id).await?,
+    };
+    if resume_after > last_sequence {
+        return Err(format!("Unknown resume sequence: {}", resume_after));
+    }
+    if resume_after.saturating_add(1) < first_sequence {
+        return Err(format!(
+            "Sequence {} is older than the retained event history",
+            resume_after
+        ));
+    }
+
+    let missed: Vec<(Event, EventCursor)> = state
+        .event_service
+        .replay_with_cursors(replay_request(
+            params,
+            Some(EventCursor::at_sequence(resume_after + 1)),
+            None,
+        ))
+        .await
+        .map_err(|e| format!("Failed to resume: {}", e))?
+        .collect()
+        .await;
+
+    if missed.len() > MAX_RESUME_EVENTS {
+        return Err(format!(
+            "More than {} events were missed since sequence {}",
+            MAX_RESUME_EVENTS, resume_after
+        ));
+    }
+
+    Ok(missed
+        .into_iter()
+        .filter(|(event, _)| topic_allowed(&params.topics, &event.topic))
+        .map(|(event, cursor)| (event, cursor.sequence))
+        .collect())
+}
+
+// Stream sequence of one of the project's events, found by replaying from the
+// time it was published
+async fn event_sequence(
+    state: &AppState,
+    params: &WebSocketConnectionParams,
+    event_id: &str,
+) -> Result<u64, String> {
+    let event = state
+        .database
+        .get_event(&params.tenant_id, event_id)
+        .await
+        .map_err(|e| format!("Failed to resume: {}", e))?
+        .filter(|event| event.project_id == params.project_id)
+        .ok_or_else(|| format!("Unknown last_event_id: {}", event_id))?;
+
+    let replayed: Vec<(Event, EventCursor)> = state
+        .event_service
+        .replay_with_cursors(replay_request(params, None, Some(event.published_at)))
+        .await
+        .map_err(|e| format!("Failed to resume: {}", e))?
+        .collect()
+        .await;
+
+    replayed
+        .into_iter()
+        .find(|(replayed, _)| replayed.id == event.id)
+        .map(|(_, cursor)| cursor.sequence)
+        .ok_or_else(|| {
+            format!(
+                "Event {} is older than the retained event history",
+                event_id
+            )
+        })
+}
+
+fn replay_request(
+    params: &WebSocketConnectionParams,
+    cursor: Option<EventCursor>,
+    from_timestamp: Option<chrono::DateTime<chrono::Utc>>,
+) -> ReplayRequest {
+    ReplayRequest {
+        tenant_id: params.tenant_id.clone(),
+        project_id: params.project_id.clone(),
+        topic: None,
+        cursor,
+        from_timestamp,
+        limit: Some(MAX_RESUME_EVENTS + 1),
+        transform: None,
+    }
+}
@@ -0,0 +1,186 @@
+/// **Feature: realtime-saas-platform, Typed REST errors**
+///
+/// Every `ApiError` variant renders with its own status and code, and every
+/// rendered body carries a `request_id`. Failures caused by an exhausted
+/// database pool render as `503` with `Retry-After` instead of `500`.
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use realtime_api::api_error::{require_any_scope, require_scope, ApiError};
+use realtime_api::auth::{AuthContext, AuthError, AuthType};
+use realtime_api::models::Scope;
+use realtime_api::rate_limit::OVERLOADED_RETRY_AFTER_SECS;
+use serde_json::{json, Value};
+
+fn auth(scopes: Vec<Scope>) -> AuthContext {
+    AuthContext {
+        tenant_id: "tenant_1".to_string(),
+        project_id: "project_1".to_string(),
+        scopes,
+        rate_limit_per_sec: 100,
+        allowed_topics: vec![],
+        auth_type: AuthType::ApiKey {
+            key_id: "key_123".to_string(),
+        },
+        user_id: None,
+        user_role: None,
+    }
+}
+
+#[test]
+fn test_each_variant_maps_to_its_status_and_code() {
+    let insufficient_scope =
+        require_scope(&auth(vec![Scope::EventsPublish]), Scope::AdminWrite).unwrap_err();
+    let cases = [
+        (
+            insufficient_scope,
+            StatusCode::FORBIDDEN,
+            "INSUFFICIENT_SCOPE",
+        ),
+        (
+            ApiError::validation("INVALID_PLAN", "Unknown plan"),
+            StatusCode::BAD_REQUEST,
+            "INVALID_PLAN",
+        ),
+        (
+            ApiError::forbidden("TOPIC_NOT_ALLOWED", "Topic not allowed"),
+            StatusCode::FORBIDDEN,
+            "TOPIC_NOT_ALLOWED",
+        ),
+        (
+            ApiError::not_found("TENANT_NOT_FOUND", "Tenant not found"),
+            StatusCode::NOT_FOUND,
+            "TENANT_NOT_FOUND",
+        ),
+        (
+            ApiError::conflict("IDEMPOTENCY_KEY_IN_USE", "Key in use"),
+            StatusCode::CONFLICT,
+            "IDEMPOTENCY_KEY_IN_USE",
+        ),
+        (
+            ApiError::payload_too_large(2048, 1024),
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "PAYLOAD_TOO_LARGE",
+        ),
+        (
+            ApiError::unprocessable("VALIDATION_FAILED", "Schema mismatch"),
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "VALIDATION_FAILED",
+        ),
+        (
+            ApiError::internal("PUBLISH_FAILED", "Failed to publish event"),
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "PUBLISH_FAILED",
+        ),
+        (
+            ApiError::unavailable("WEBHOOK_NOT_CONFIGURED", "Not configured"),
+            StatusCode::SERVICE_UNAVAILABLE,
+            "WEBHOOK_NOT_CONFIGURED",
+        ),
+    ];
+
+    for (error, status, code) in cases {
+        assert_eq!(error.status(), status, "{}", code);
+        assert_eq!(error.code(), code);
+
+        let (rendered_status, body) = error.into_parts();
+        assert_eq!(rendered_status, status, "{}", code);
+        assert_eq!(body.error.code, code);
+        assert!(!body.error.request_id.is_empty(), "{}", code);
+    }
+}
+
+#[tokio::test]
+async fn test_response_body_carries_request_id() {
+    let response = ApiError::not_found("EVENT_NOT_FOUND", "Event not found")
+        .with_details(json!({"event_id": "evt_1"}))
+        .into_response();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["code"], "EVENT_NOT_FOUND");
+    assert_eq!(body["error"]["message"], "Event not found");
+    assert_eq!(body["error"]["details"]["event_id"], "evt_1");
+    assert!(body["error"]["request_id"]
+        .as_str()
+        .is_some_and(|id| !id.is_empty()));
+}
+
+#[test]
+fn test_insufficient_scope_lists_required_and_available_scopes() {
+    let auth = auth(vec![Scope::EventsPublish]);
+    assert!(require_any_scope(&auth, &[Scope::AdminRead, Scope::EventsPublish]).is_ok());
+
+    let (_, body) = require_any_scope(&auth, &[Scope::AdminRead, Scope::AdminWrite])
+        .unwrap_err()
+        .with_details(json!({"correlation_id": "abc"}))
+        .into_parts();
+    let details = body.error.details.clone().unwrap();
+    assert_eq!(
+        details["required_scopes"],
+        json!(["admin:read", "admin:write"])
+    );
+    assert_eq!(details["available_scopes"], json!(["events:publish"]));
+    assert_eq!(details["correlation_id"], "abc");
+    assert!(body.error.message.contains("admin:read"));
+}
+
+#[test]
+fn test_details_are_merged() {
+    let (_, body) = ApiError::payload_too_large(2048, 1024)
+        .with_details(json!({"correlation_id": "abc"}))
+        .into_parts();
+    assert_eq!(
+        body.error.details,
+        Some(json!({"size": 2048, "limit": 1024, "correlation_id": "abc"}))
+    );
+
+    let (_, body) = ApiError::validation("INVALID_BATCH_SIZE", "Too many events")
+        .with_details(json!({"size": 0}))
+        .with_details(json!({"max_batch_size": 100}))
+        .into_parts();
+    assert_eq!(
+        body.error.details,
+        Some(json!({"size": 0, "max_batch_size": 100}))
+    );
+}
+
+#[test]
+fn test_unexpected_failure_is_internal_with_the_error_in_details() {
+    let error = anyhow::anyhow!("connection reset");
+    let (status, body) = ApiError::from_error("EVENT_LIST_FAILED", "Failed to list events", error)
+        .with_details(json!({"correlation_id": "abc"}))
+        .into_parts();
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body.error.code, "EVENT_LIST_FAILED");
+    assert_eq!(
+        body.error.details,
+        Some(json!({"error": "connection reset", "correlation_id": "abc"}))
+    );
+}
+
+#[test]
+fn test_pool_exhaustion_is_overloaded_with_retry_after() {
+    let pool_timeout =
+        || anyhow::Error::new(sqlx::Error::PoolTimedOut).context("Failed to list events");
+    let errors = [
+        ApiError::from_error("EVENT_LIST_FAILED", "Failed to list events", pool_timeout()),
+        ApiError::from_error(
+            "API_KEY_ROTATION_FAILED",
+            "Failed to rotate API key",
+            AuthError::Database(pool_timeout()),
+        ),
+    ];
+
+    for error in errors {
+        assert_eq!(error.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(error.code(), "SERVICE_OVERLOADED");
+        let response = error.into_response();
+        assert_eq!(
+            response.headers()[header::RETRY_AFTER],
+            OVERLOADED_RETRY_AFTER_SECS.to_string()
+        );
+    }
+}
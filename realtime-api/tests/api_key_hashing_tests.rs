@@ -0,0 +1,89 @@
+/// **Feature: realtime-saas-platform, Argon2id API key hashing**
+///
+/// API keys are found by an indexed SHA-256 prefix and verified against a slow
+/// Argon2id hash. Keys stored with the legacy full SHA-256 digest keep working and
+/// are re-hashed on their next successful use.
+use realtime_api::auth::{AuthError, AuthService};
+use realtime_api::models::{ApiKey, Scope};
+use sha2::{Digest, Sha256};
+
+mod common;
+
+use common::{create_project, test_database};
+
+#[tokio::test]
+async fn test_new_key_is_found_by_prefix_and_verified_with_argon2() {
+    let database = test_database().await;
+    let (tenant, project) = create_project(&database, "Hashing Tenant").await;
+    let auth_service = AuthService::new(database.clone(), "test_secret".to_string());
+
+    let (raw_key, api_key) = auth_service
+        .create_api_key(
+            tenant.id.clone(),
+            project.id.clone(),
+            vec![Scope::EventsPublish],
+            100,
+            Vec::new(),
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+
+    let stored = database
+        .get_api_key_by_hash(&AuthService::hash_api_key_for_lookup(&raw_key))
+        .await
+        .unwrap()
+        .expect("Key should be found by its lookup prefix");
+    assert_eq!(stored.id, api_key.id);
+    assert!(stored.secret_hash.is_some());
+
+    let context = auth_service.validate_api_key(&raw_key).await.expect("Key should validate");
+    assert_eq!(context.tenant_id, tenant.id);
+
+    // A key that was never issued is rejected
+    let wrong_key = format!("{}x", raw_key);
+    assert!(matches!(
+        auth_service.validate_api_key(&wrong_key).await,
+        Err(AuthError::InvalidApiKey)
+    ));
+}
+
+#[tokio::test]
+async fn test_legacy_key_is_rehashed_on_use() {
+    let database = test_database().await;
+    let (tenant, project) = create_project(&database, "Hashing Tenant").await;
+    let auth_service = AuthService::new(database.clone(), "test_secret".to_string());
+
+    // Store a key the way it was stored before Argon2id: full SHA-256, no secret hash
+    let raw_key = AuthService::generate_api_key();
+    let legacy_hash = format!("{:x}", Sha256::digest(raw_key.as_bytes()));
+    let legacy_key = ApiKey::new(
+        tenant.id.clone(),
+        project.id.clone(),
+        legacy_hash.clone(),
+        vec![Scope::EventsPublish],
+        100,
+    );
+    database.create_api_key(&legacy_key).await.expect("Failed to create API key");
+
+    auth_service
+        .validate_api_key(&raw_key)
+        .await
+        .expect("Legacy key should still validate");
+
+    assert!(database.get_api_key_by_hash(&legacy_hash).await.unwrap().is_none());
+    let upgraded = database
+        .get_api_key_by_hash(&AuthService::hash_api_key_for_lookup(&raw_key))
+        .await
+        .unwrap()
+        .expect("Key should be stored under its lookup prefix after upgrade");
+    assert_eq!(upgraded.id, legacy_key.id);
+    let secret_hash = upgraded.secret_hash.expect("Upgraded key should have an Argon2id hash");
+    assert!(AuthService::verify_api_key(&raw_key, &secret_hash).unwrap());
+
+    // The upgraded key keeps validating through the Argon2id path
+    auth_service
+        .validate_api_key(&raw_key)
+        .await
+        .expect("Upgraded key should validate");
+}
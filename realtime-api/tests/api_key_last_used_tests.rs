@@ -0,0 +1,113 @@
+/// **Feature: realtime-saas-platform, API key last use**
+///
+/// Validating an API key records when and from where it was used, at most once
+/// per minute per key, so operators can find stale keys to rotate. Requests
+/// rejected by the rate limit record nothing.
+use realtime_api::auth::{AuthError, AuthService};
+use realtime_api::database::Database;
+use realtime_api::models::{ApiKey, BillingPlan, Project, Scope, Tenant};
+use realtime_api::rate_limit::{RateLimitStore, RateLimiter};
+use std::net::IpAddr;
+use std::sync::Arc;
+
+mod common;
+
+use common::test_database;
+
+async fn stored_key(database: &Database, project: &Project, key_id: &str) -> ApiKey {
+    database
+        .get_api_keys_for_project(&project.id, None)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|api_key| api_key.id == key_id)
+        .expect("Key should be stored")
+}
+
+#[tokio::test]
+async fn test_validation_records_last_use_at_most_once_per_minute() {
+    let database = test_database().await;
+    let tenant = Tenant::new("Last Used Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    database.create_project(&project).await.expect("Failed to create project");
+
+    let auth_service = AuthService::new(database.clone(), "test_secret".to_string());
+    let (raw_key, api_key) = auth_service
+        .create_api_key(
+            tenant.id.clone(),
+            project.id.clone(),
+            vec![Scope::EventsPublish],
+            100,
+            Vec::new(),
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+    assert!(stored_key(&database, &project, &api_key.id).await.last_used_at.is_none());
+
+    let first_ip: IpAddr = "203.0.113.7".parse().unwrap();
+    auth_service
+        .validate_api_key_from(&raw_key, Some(first_ip))
+        .await
+        .expect("Key should validate");
+
+    let first_use = stored_key(&database, &project, &api_key.id).await;
+    let last_used_at = first_use.last_used_at.expect("Use should be recorded");
+    assert_eq!(first_use.last_used_ip.as_deref(), Some("203.0.113.7"));
+
+    // Within the interval neither this instance nor another one writes again
+    let second_ip: IpAddr = "198.51.100.9".parse().unwrap();
+    auth_service
+        .validate_api_key_from(&raw_key, Some(second_ip))
+        .await
+        .expect("Key should validate");
+    AuthService::new(database.clone(), "test_secret".to_string())
+        .validate_api_key_from(&raw_key, Some(second_ip))
+        .await
+        .expect("Key should validate");
+
+    let second_use = stored_key(&database, &project, &api_key.id).await;
+    assert_eq!(second_use.last_used_at, Some(last_used_at));
+    assert_eq!(second_use.last_used_ip.as_deref(), Some("203.0.113.7"));
+
+    // A key used just now isn't stale; with a zero-day threshold everything is
+    assert!(!auth_service.is_stale_api_key(&second_use));
+    let strict = AuthService::new(database.clone(), "test_secret".to_string()).with_stale_key_days(0);
+    assert!(strict.is_stale_api_key(&second_use));
+}
+
+#[tokio::test]
+async fn test_rate_limited_validation_records_no_use() {
+    let database = test_database().await;
+    let tenant = Tenant::new("Throttled Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    database.create_project(&project).await.expect("Failed to create project");
+
+    let rate_limiter = Arc::new(RateLimiter::new());
+    let auth_service = AuthService::new(database.clone(), "test_secret".to_string())
+        .with_rate_limit_store(rate_limiter.clone());
+    let (raw_key, api_key) = auth_service
+        .create_api_key(
+            tenant.id.clone(),
+            project.id.clone(),
+            vec![Scope::EventsPublish],
+            1,
+            Vec::new(),
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+
+    // Another request has already taken this second's only token
+    rate_limiter.acquire(&api_key.id, 1).await.unwrap();
+    let result = auth_service
+        .validate_api_key_from(&raw_key, "203.0.113.7".parse().ok())
+        .await;
+    assert!(matches!(result, Err(AuthError::RateLimitExceeded(_))));
+
+    let stored = stored_key(&database, &project, &api_key.id).await;
+    assert!(stored.last_used_at.is_none());
+    assert!(stored.last_used_ip.is_none());
+}
@@ -0,0 +1,141 @@
+/// **Feature: realtime-saas-platform, API key listing**
+///
+/// `GET /admin/projects/{id}/api-keys` lists only the caller's tenant's keys,
+/// identified by a prefix/last-4 hint and never by the raw key or its hashes.
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Extension;
+use realtime_api::api::{list_project_api_keys, revoke_api_key, AppState};
+use realtime_api::auth::{AuthContext, AuthType};
+use realtime_api::models::{BillingPlan, Project, Scope, Tenant};
+
+mod common;
+
+use common::test_state;
+
+async fn create_project(state: &AppState) -> (Tenant, Project) {
+    let tenant = Tenant::new("API Key Listing Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    state.database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    state.database.create_project(&project).await.expect("Failed to create project");
+    (tenant, project)
+}
+
+fn admin(tenant: &Tenant, project: &Project, scopes: Vec<Scope>) -> AuthContext {
+    AuthContext {
+        tenant_id: tenant.id.clone(),
+        project_id: project.id.clone(),
+        scopes,
+        rate_limit_per_sec: 100,
+        allowed_topics: vec![],
+        auth_type: AuthType::ApiKey {
+            key_id: "admin_key".to_string(),
+        },
+        user_id: None,
+        user_role: None,
+    }
+}
+
+#[tokio::test]
+async fn test_lists_own_keys_without_secrets() {
+    let state = test_state().await;
+    let (tenant, project) = create_project(&state).await;
+
+    let (raw_key, api_key) = state
+        .auth_service
+        .create_api_key(
+            tenant.id.clone(),
+            project.id.clone(),
+            vec![Scope::EventsPublish],
+            100,
+            vec![],
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+
+    let keys = list_project_api_keys(
+        State(state.clone()),
+        Extension(admin(&tenant, &project, vec![Scope::AdminRead])),
+        Path(project.id.clone()),
+    )
+    .await
+    .expect("Keys should be listed");
+
+    assert_eq!(keys.len(), 1);
+    assert_eq!(keys[0].id, api_key.id);
+    assert_eq!(keys[0].scopes, vec!["events:publish".to_string()]);
+    assert_eq!(
+        keys[0].key_hint.as_deref(),
+        Some(format!("rtp_...{}", &raw_key[raw_key.len() - 4..]).as_str())
+    );
+
+    let body = serde_json::to_string(&keys.0).unwrap();
+    assert!(!body.contains(&raw_key));
+    assert!(!body.contains(&api_key.key_hash));
+
+    // Revoked keys stay listed as inactive
+    revoke_api_key(
+        State(state.clone()),
+        Extension(admin(&tenant, &project, vec![Scope::AdminWrite])),
+        Path(api_key.id.clone()),
+    )
+    .await
+    .expect("Key should be revoked");
+
+    let keys = list_project_api_keys(
+        State(state),
+        Extension(admin(&tenant, &project, vec![Scope::AdminRead])),
+        Path(project.id.clone()),
+    )
+    .await
+    .unwrap();
+    assert!(!keys[0].is_active);
+}
+
+#[tokio::test]
+async fn test_other_tenants_project_yields_404() {
+    let state = test_state().await;
+    let (tenant, project) = create_project(&state).await;
+    let (other_tenant, other_project) = create_project(&state).await;
+
+    state
+        .auth_service
+        .create_api_key(
+            other_tenant.id.clone(),
+            other_project.id.clone(),
+            vec![Scope::EventsPublish],
+            100,
+            vec![],
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+
+    let err = list_project_api_keys(
+        State(state),
+        Extension(admin(&tenant, &project, vec![Scope::AdminRead])),
+        Path(other_project.id.clone()),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(err.status(), StatusCode::NOT_FOUND);
+    assert_eq!(err.code(), "PROJECT_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn test_listing_requires_admin_read() {
+    let state = test_state().await;
+    let (tenant, project) = create_project(&state).await;
+
+    let err = list_project_api_keys(
+        State(state),
+        Extension(admin(&tenant, &project, vec![Scope::EventsPublish])),
+        Path(project.id.clone()),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(err.status(), StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,166 @@
+/// **Feature: realtime-saas-platform, API key rotation**
+///
+/// Rotating an API key issues a new secret for the same key while the old
+/// secret keeps working for a grace period, after which only the new one does.
+/// Only one previous secret is kept, so rotating again within the grace period
+/// cuts off the oldest secret at once.
+use realtime_api::auth::{AuthError, AuthService, AuthType};
+use realtime_api::database::Database;
+use realtime_api::models::{BillingPlan, Project, Scope, Tenant};
+use std::time::Duration;
+
+mod common;
+
+use common::test_database;
+
+async fn create_project(database: &Database, name: &str) -> Project {
+    let tenant = Tenant::new(name.to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    database.create_project(&project).await.expect("Failed to create project");
+    project
+}
+
+async fn create_key(auth_service: &AuthService, project: &Project) -> (String, String) {
+    let (raw_key, api_key) = auth_service
+        .create_api_key(
+            project.tenant_id.clone(),
+            project.id.clone(),
+            vec![Scope::EventsPublish],
+            100,
+            Vec::new(),
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+    (raw_key, api_key.id)
+}
+
+fn key_id_of(auth_type: &AuthType) -> &str {
+    match auth_type {
+        AuthType::ApiKey { key_id } => key_id,
+        other => panic!("Expected API key auth, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_both_secrets_validate_during_overlap() {
+    let database = test_database().await;
+    let project = create_project(&database, "Rotation Overlap Tenant").await;
+    let auth_service = AuthService::new(database.clone(), "test_secret".to_string())
+        .with_rotation_grace_period(Duration::from_secs(3600));
+    let (old_key, key_id) = create_key(&auth_service, &project).await;
+
+    let (new_key, rotated) = auth_service
+        .rotate_api_key(&project.tenant_id, &key_id)
+        .await
+        .expect("Rotation should succeed")
+        .expect("Key should exist");
+    assert_ne!(new_key, old_key);
+    assert_eq!(rotated.id, key_id);
+    assert_eq!(rotated.scopes, vec![Scope::EventsPublish]);
+    assert!(rotated.previous_expires_at.is_some());
+
+    for raw_key in [&old_key, &new_key] {
+        let context = auth_service
+            .validate_api_key(raw_key)
+            .await
+            .expect("Both secrets should validate during the grace period");
+        assert_eq!(key_id_of(&context.auth_type), key_id);
+        assert_eq!(context.project_id, project.id);
+    }
+}
+
+#[tokio::test]
+async fn test_old_secret_stops_working_after_grace_period() {
+    let database = test_database().await;
+    let project = create_project(&database, "Rotation Expiry Tenant").await;
+    let auth_service = AuthService::new(database.clone(), "test_secret".to_string())
+        .with_rotation_grace_period(Duration::from_secs(1));
+    let (old_key, key_id) = create_key(&auth_service, &project).await;
+
+    let (new_key, _) = auth_service
+        .rotate_api_key(&project.tenant_id, &key_id)
+        .await
+        .expect("Rotation should succeed")
+        .expect("Key should exist");
+    assert!(auth_service.validate_api_key(&old_key).await.is_ok());
+
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert!(matches!(
+        auth_service.validate_api_key(&old_key).await,
+        Err(AuthError::InvalidApiKey)
+    ));
+
+    // The sweeper forgets the expired secret; the new one is unaffected
+    assert!(database.clear_expired_api_key_secrets().await.unwrap() >= 1);
+    let stored = database
+        .get_api_keys_for_project(&project.id, None)
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|api_key| api_key.id == key_id)
+        .expect("Key should be stored");
+    assert!(stored.previous_key_hash.is_none());
+    assert!(stored.previous_secret_hash.is_none());
+    assert!(auth_service.validate_api_key(&new_key).await.is_ok());
+    assert!(auth_service.validate_api_key(&old_key).await.is_err());
+}
+
+#[tokio::test]
+async fn test_rotation_is_scoped_to_tenant_and_active_keys() {
+    let database = test_database().await;
+    let project = create_project(&database, "Rotation Scope Tenant").await;
+    let other = create_project(&database, "Rotation Other Tenant").await;
+    let auth_service = AuthService::new(database.clone(), "test_secret".to_string());
+    let (old_key, key_id) = create_key(&auth_service, &project).await;
+
+    let result = auth_service
+        .rotate_api_key(&other.tenant_id, &key_id)
+        .await
+        .expect("Rotation query should succeed");
+    assert!(result.is_none());
+    assert!(auth_service.validate_api_key(&old_key).await.is_ok());
+
+    auth_service
+        .revoke_api_key(&project.tenant_id, &key_id)
+        .await
+        .expect("Revocation should succeed");
+    let result = auth_service
+        .rotate_api_key(&project.tenant_id, &key_id)
+        .await
+        .expect("Rotation query should succeed");
+    assert!(result.is_none());
+}
+
+#[tokio::test]
+async fn test_second_rotation_cuts_off_the_oldest_secret() {
+    let database = test_database().await;
+    let project = create_project(&database, "Rotation Twice Tenant").await;
+    let auth_service = AuthService::new(database.clone(), "test_secret".to_string())
+        .with_rotation_grace_period(Duration::from_secs(3600));
+    let (oldest_key, key_id) = create_key(&auth_service, &project).await;
+
+    let (middle_key, first) = auth_service
+        .rotate_api_key(&project.tenant_id, &key_id)
+        .await
+        .expect("Rotation should succeed")
+        .expect("Key should exist");
+    let (newest_key, second) = auth_service
+        .rotate_api_key(&project.tenant_id, &key_id)
+        .await
+        .expect("Rotation should succeed")
+        .expect("Key should exist");
+    assert!(second.previous_expires_at >= first.previous_expires_at);
+
+    assert!(matches!(
+        auth_service.validate_api_key(&oldest_key).await,
+        Err(AuthError::InvalidApiKey)
+    ));
+    for raw_key in [&middle_key, &newest_key] {
+        assert!(
+            auth_service.validate_api_key(raw_key).await.is_ok(),
+            "The replaced and the new secret should both validate"
+        );
+    }
+}
@@ -692,3 +692,44 @@ mod authenticated_event_acceptance_properties {
         }
     }
 }
+
+/// **Feature: realtime-saas-platform, Page size ceiling**
+///
+/// List endpoints must never return more than `max_page_size` items, while
+/// honoring any requested limit that is within range.
+#[cfg(test)]
+mod page_size_properties {
+    use super::*;
+    use realtime_api::api::{clamp_page_size, DEFAULT_PAGE_SIZE};
+
+    proptest! {
+        #[test]
+        fn test_over_ceiling_limit_is_clamped(
+            max_page_size in 1i64..10_000,
+            excess in 1i64..1_000_000
+        ) {
+            prop_assert_eq!(
+                clamp_page_size(Some(max_page_size + excess), max_page_size),
+                max_page_size
+            );
+        }
+
+        #[test]
+        fn test_within_range_limit_is_honored(
+            max_page_size in 1i64..10_000,
+            fraction in 0.0f64..1.0
+        ) {
+            let requested = ((max_page_size as f64 * fraction) as i64).max(1);
+            prop_assert_eq!(clamp_page_size(Some(requested), max_page_size), requested);
+        }
+    }
+
+    #[test]
+    fn test_missing_or_invalid_limit_uses_default() {
+        assert_eq!(clamp_page_size(None, 1000), DEFAULT_PAGE_SIZE);
+        assert_eq!(clamp_page_size(Some(0), 1000), DEFAULT_PAGE_SIZE);
+        assert_eq!(clamp_page_size(Some(-5), 1000), DEFAULT_PAGE_SIZE);
+        // The default itself never exceeds a lower ceiling
+        assert_eq!(clamp_page_size(None, 10), 10);
+    }
+}
@@ -0,0 +1,267 @@
+/// **Feature: realtime-saas-platform, Admin audit log**
+///
+/// Every admin mutation writes exactly one audit entry attributed to the
+/// acting API key, and `GET /admin/audit` lists a tenant's entries filtered
+/// by action and time range.
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use realtime_api::api::{
+    create_api_key, create_tenant, list_audit_logs, revoke_api_key, suspend_tenant,
+    unsuspend_tenant, update_tenant_plan, AppState, AuditLogQuery, CreateApiKeyRequest,
+    CreateTenantRequest, UpdateTenantPlanRequest,
+};
+use realtime_api::audit;
+use realtime_api::auth::{AuthContext, AuthType};
+use realtime_api::kill_switch::{SUSPEND_AUDIT_ACTION, UNSUSPEND_AUDIT_ACTION};
+use realtime_api::models::{AuditLog, AuditQuery, BillingPlan, Project, Scope, Tenant};
+
+mod common;
+
+use common::test_state;
+
+const ACTOR_KEY_ID: &str = "audit_admin_key";
+
+async fn create_test_tenant(state: &AppState) -> (Tenant, Project) {
+    let tenant = Tenant::new("Audit Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    state.database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    state.database.create_project(&project).await.expect("Failed to create project");
+    (tenant, project)
+}
+
+fn admin(tenant: &Tenant, project: &Project) -> AuthContext {
+    AuthContext {
+        tenant_id: tenant.id.clone(),
+        project_id: project.id.clone(),
+        scopes: vec![Scope::AdminRead, Scope::AdminWrite],
+        rate_limit_per_sec: 100,
+        allowed_topics: vec![],
+        auth_type: AuthType::ApiKey {
+            key_id: ACTOR_KEY_ID.to_string(),
+        },
+        user_id: None,
+        user_role: None,
+    }
+}
+
+async fn audit_entries(state: &AppState, tenant_id: &str, action: &str) -> Vec<AuditLog> {
+    let query = AuditQuery {
+        action: Some(action.to_string()),
+        ..AuditQuery::default()
+    };
+    state
+        .database
+        .list_audit_logs(tenant_id, &query, 100)
+        .await
+        .expect("Failed to list audit log")
+}
+
+// Exactly one entry for `action`, attributed to the acting key
+async fn assert_audited_once(state: &AppState, tenant_id: &str, action: &str, target: &str) {
+    let entries = audit_entries(state, tenant_id, action).await;
+    assert_eq!(entries.len(), 1, "expected one {} entry", action);
+    assert_eq!(entries[0].actor_api_key_id.as_deref(), Some(ACTOR_KEY_ID));
+    assert_eq!(entries[0].target.as_deref(), Some(target));
+}
+
+fn audit_query(action: Option<&str>) -> Query<AuditLogQuery> {
+    Query(AuditLogQuery {
+        action: action.map(str::to_string),
+        from: None,
+        to: None,
+        limit: None,
+    })
+}
+
+#[tokio::test]
+async fn test_tenant_creation_is_audited() {
+    let state = test_state().await;
+    let (tenant, project) = create_test_tenant(&state).await;
+
+    let response = create_tenant(
+        State(state.clone()),
+        Extension(admin(&tenant, &project)),
+        Json(CreateTenantRequest {
+            name: "Audited Tenant".to_string(),
+            plan: "pro".to_string(),
+        }),
+    )
+    .await
+    .expect("Tenant should be created");
+
+    assert_audited_once(&state, &response.id, audit::TENANT_CREATED, &response.id).await;
+}
+
+#[tokio::test]
+async fn test_api_key_creation_and_revocation_are_audited() {
+    let state = test_state().await;
+    let (tenant, project) = create_test_tenant(&state).await;
+
+    let key = create_api_key(
+        State(state.clone()),
+        Extension(admin(&tenant, &project)),
+        Json(CreateApiKeyRequest {
+            name: "ci".to_string(),
+            scopes: vec!["events:publish".to_string()],
+            rate_limit_per_sec: None,
+            expires_in_days: None,
+            allowed_topics: vec![],
+        }),
+    )
+    .await
+    .expect("API key should be created");
+    assert_audited_once(&state, &tenant.id, audit::API_KEY_CREATED, &key.id).await;
+
+    let status = revoke_api_key(
+        State(state.clone()),
+        Extension(admin(&tenant, &project)),
+        Path(key.id.clone()),
+    )
+    .await
+    .expect("API key should be revoked");
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_audited_once(&state, &tenant.id, audit::API_KEY_REVOKED, &key.id).await;
+}
+
+#[tokio::test]
+async fn test_plan_change_and_suspension_are_audited() {
+    let mut state = test_state().await;
+    let (tenant, project) = create_test_tenant(&state).await;
+    // Plan changes need platform admin credentials
+    state.auth_service = state
+        .auth_service
+        .clone()
+        .with_platform_admin_tenant(Some(tenant.id.clone()));
+
+    update_tenant_plan(
+        Extension(admin(&tenant, &project)),
+        Path(tenant.id.clone()),
+        State(state.clone()),
+        Json(UpdateTenantPlanRequest {
+            plan: "pro".to_string(),
+        }),
+    )
+    .await
+    .expect("Plan should change");
+    assert_audited_once(&state, &tenant.id, audit::TENANT_PLAN_CHANGED, &tenant.id).await;
+
+    suspend_tenant(
+        Extension(admin(&tenant, &project)),
+        Path(tenant.id.clone()),
+        State(state.clone()),
+        None,
+    )
+    .await
+    .expect("Tenant should be suspended");
+    assert_audited_once(&state, &tenant.id, SUSPEND_AUDIT_ACTION, &tenant.id).await;
+
+    unsuspend_tenant(
+        Extension(admin(&tenant, &project)),
+        Path(tenant.id.clone()),
+        State(state.clone()),
+        None,
+    )
+    .await
+    .expect("Tenant should be unsuspended");
+    assert_audited_once(&state, &tenant.id, UNSUSPEND_AUDIT_ACTION, &tenant.id).await;
+
+    assert_eq!(
+        state.database.get_audit_actions(&tenant.id).await.unwrap(),
+        vec![
+            UNSUSPEND_AUDIT_ACTION.to_string(),
+            SUSPEND_AUDIT_ACTION.to_string(),
+            audit::TENANT_PLAN_CHANGED.to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_audit_listing_filters_by_action_and_time() {
+    let state = test_state().await;
+    let (tenant, project) = create_test_tenant(&state).await;
+    let (other_tenant, _) = create_test_tenant(&state).await;
+    let details = serde_json::json!({});
+
+    state
+        .database
+        .record_audit(
+            &tenant.id,
+            Some(ACTOR_KEY_ID),
+            audit::API_KEY_CREATED,
+            Some("key_1"),
+            &details,
+        )
+        .await
+        .unwrap();
+    let between = chrono::Utc::now();
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    state
+        .database
+        .record_audit(
+            &tenant.id,
+            Some(ACTOR_KEY_ID),
+            audit::API_KEY_REVOKED,
+            Some("key_1"),
+            &details,
+        )
+        .await
+        .unwrap();
+    state
+        .database
+        .record_audit(&other_tenant.id, None, audit::API_KEY_CREATED, Some("key_2"), &details)
+        .await
+        .unwrap();
+
+    // Only the authenticated tenant's entries, newest first
+    let page = list_audit_logs(
+        State(state.clone()),
+        Extension(admin(&tenant, &project)),
+        audit_query(None),
+    )
+    .await
+    .expect("Audit log should list");
+    let actions: Vec<&str> = page.items.iter().map(|entry| entry.action.as_str()).collect();
+    assert_eq!(actions, vec![audit::API_KEY_REVOKED, audit::API_KEY_CREATED]);
+    assert!(page.items.iter().all(|entry| entry.tenant_id == tenant.id));
+
+    let page = list_audit_logs(
+        State(state.clone()),
+        Extension(admin(&tenant, &project)),
+        audit_query(Some(audit::API_KEY_CREATED)),
+    )
+    .await
+    .unwrap();
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(page.items[0].target.as_deref(), Some("key_1"));
+
+    let mut query = audit_query(None);
+    query.0.from = Some(between);
+    let page = list_audit_logs(State(state.clone()), Extension(admin(&tenant, &project)), query)
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(page.items[0].action, audit::API_KEY_REVOKED);
+
+    let mut query = audit_query(None);
+    query.0.to = Some(between);
+    let page = list_audit_logs(State(state.clone()), Extension(admin(&tenant, &project)), query)
+        .await
+        .unwrap();
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(page.items[0].action, audit::API_KEY_CREATED);
+}
+
+#[tokio::test]
+async fn test_audit_listing_requires_admin_read() {
+    let state = test_state().await;
+    let (tenant, project) = create_test_tenant(&state).await;
+    let mut auth = admin(&tenant, &project);
+    auth.scopes = vec![Scope::AdminWrite];
+
+    let err = list_audit_logs(State(state), Extension(auth), audit_query(None))
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), StatusCode::FORBIDDEN);
+    assert_eq!(err.code(), "INSUFFICIENT_SCOPE");
+}
@@ -0,0 +1,126 @@
+/// **Feature: realtime-saas-platform, Schema auto-registration**
+///
+/// For projects with `auto_register_schema_from_first_event`, the first event on a
+/// new topic defines a warn-mode schema that later events are validated against.
+use realtime_api::database::Database;
+use realtime_api::event_service::{EventService, PublishResult};
+use realtime_api::models::{Event, Project, Tenant};
+use realtime_api::schema_validator::{SchemaCheck, SchemaMode, SchemaValidator};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{create_project, test_database, test_event_service, test_nats_client};
+
+async fn setup(auto_register: bool) -> (EventService, Tenant, Project) {
+    let (database, tenant, project) = setup_project(auto_register).await;
+    let service = test_event_service(database).await;
+
+    (service, tenant, project)
+}
+
+async fn setup_project(auto_register: bool) -> (Database, Tenant, Project) {
+    let database = test_database().await;
+    let (tenant, mut project) = create_project(&database, "Schema Tenant").await;
+    project.settings.auto_register_schema_from_first_event = auto_register;
+    database
+        .update_project_settings(&tenant.id, &project.id, &project.settings)
+        .await
+        .expect("Failed to update project settings");
+
+    (database, tenant, project)
+}
+
+async fn publish(service: &EventService, tenant: &Tenant, project: &Project, payload: Value) -> PublishResult {
+    let event = Event::new(
+        tenant.id.clone(),
+        project.id.clone(),
+        "order.placed".to_string(),
+        payload,
+    );
+    service.publish_event(&event).await.expect("Publish should return a result")
+}
+
+#[tokio::test]
+async fn test_first_event_registers_schema_and_later_violations_warn() {
+    let (service, tenant, project) = setup(true).await;
+
+    let result = publish(&service, &tenant, &project, json!({"order_id": "o-1", "amount": 10})).await;
+    assert!(matches!(result, PublishResult::Success { .. }));
+
+    let schema = service
+        .schema_validator()
+        .get_schema(&project.id, "order.placed")
+        .expect("First event should register a schema");
+    assert_eq!(schema.version, 1);
+    assert_eq!(schema.mode, SchemaMode::Warn);
+
+    // A non-conforming event is still published, but flagged as a violation
+    let violating = json!({"order_id": 7});
+    let result = publish(&service, &tenant, &project, violating.clone()).await;
+    assert!(matches!(result, PublishResult::Success { .. }));
+    assert!(matches!(
+        service
+            .schema_validator()
+            .validate_project_event(&project.id, "order.placed", &violating)
+            .unwrap(),
+        SchemaCheck::Warning(_)
+    ));
+
+    // Later events do not replace the registered schema
+    assert_eq!(
+        service
+            .schema_validator()
+            .get_schema(&project.id, "order.placed")
+            .unwrap()
+            .version,
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_concurrent_first_events_register_one_schema() {
+    let (database, tenant, project) = setup_project(true).await;
+    let service = EventService::new(
+        database.clone(),
+        test_nats_client().await,
+        SchemaValidator::new().with_store(database.clone()),
+    );
+    let other = EventService::new(
+        database.clone(),
+        test_nats_client().await,
+        SchemaValidator::new().with_store(database),
+    );
+
+    // Two instances both see an unregistered topic; the first writer wins
+    let (first, second) = tokio::join!(
+        publish(&service, &tenant, &project, json!({"order_id": "o-1"})),
+        publish(&other, &tenant, &project, json!({"sku": "s-1"})),
+    );
+    assert!(matches!(first, PublishResult::Success { .. }));
+    assert!(matches!(second, PublishResult::Success { .. }));
+
+    let mine = service
+        .schema_validator()
+        .get_schema(&project.id, "order.placed")
+        .unwrap();
+    let theirs = other
+        .schema_validator()
+        .get_schema(&project.id, "order.placed")
+        .unwrap();
+    assert_eq!(mine.version, 1);
+    assert_eq!(theirs.version, 1);
+    assert_eq!(mine.schema, theirs.schema);
+}
+
+#[tokio::test]
+async fn test_no_schema_registered_without_flag() {
+    let (service, tenant, project) = setup(false).await;
+
+    let result = publish(&service, &tenant, &project, json!({"order_id": "o-1"})).await;
+    assert!(matches!(result, PublishResult::Success { .. }));
+    assert!(service
+        .schema_validator()
+        .get_schema(&project.id, "order.placed")
+        .is_none());
+}
@@ -0,0 +1,156 @@
+/// **Feature: realtime-saas-platform, Batch event publishing**
+///
+/// A batch publish validates and publishes each event on its own and reports a
+/// result per event, so one invalid payload does not fail the whole batch.
+use realtime_api::auth::{AuthContext, AuthType};
+use realtime_api::event_service::{BatchItem, BatchItemError, InvalidBatchSize, PublishResult};
+use realtime_api::models::{
+    BillingPlan, Event, MetadataLimitError, MetadataLimits, Project, Tenant,
+};
+use serde_json::json;
+use std::collections::HashMap;
+
+mod common;
+
+use common::{test_database, test_event_service};
+
+#[tokio::test]
+async fn test_batch_reports_partial_failures_per_event() {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone()).await;
+
+    let tenant = Tenant::new("Batch Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    database.create_project(&project).await.expect("Failed to create project");
+
+    event_service.schema_validator().register_schema(
+        &project.id,
+        "orders.created",
+        json!({
+            "type": "object",
+            "properties": { "order_id": { "type": "string" } },
+            "required": ["order_id"]
+        }),
+    );
+
+    let events: Vec<Event> = [
+        json!({ "order_id": "o-1" }),
+        json!({ "amount": 10 }),
+        json!({ "order_id": "o-2" }),
+    ]
+    .into_iter()
+    .map(|payload| {
+        Event::new(
+            tenant.id.clone(),
+            project.id.clone(),
+            "orders.created".to_string(),
+            payload,
+        )
+    })
+    .collect();
+
+    let results = event_service
+        .publish_batch(&events, None)
+        .await
+        .expect("Batch within the size limit should be accepted");
+    assert_eq!(results.len(), 3);
+
+    let first = match &results[0] {
+        Ok(PublishResult::Success { sequence }) => *sequence,
+        other => panic!("First event should publish, got {:?}", other),
+    };
+    assert!(matches!(results[1], Ok(PublishResult::ValidationFailed(_))));
+    let third = match &results[2] {
+        Ok(PublishResult::Success { sequence }) => *sequence,
+        other => panic!("Third event should publish, got {:?}", other),
+    };
+    assert!(third > first);
+}
+
+#[tokio::test]
+async fn test_batch_over_limit_is_rejected() {
+    let database = test_database().await;
+    let event_service = test_event_service(database).await.with_max_batch_size(2);
+
+    let events: Vec<Event> = (0..3)
+        .map(|i| {
+            Event::new(
+                "tenant_123".to_string(),
+                "project_456".to_string(),
+                "orders.created".to_string(),
+                json!({ "n": i }),
+            )
+        })
+        .collect();
+
+    assert!(event_service.publish_batch(&events, None).await.is_err());
+}
+
+#[tokio::test]
+async fn test_batch_items_share_one_set_of_checks() {
+    let database = test_database().await;
+    let event_service = test_event_service(database)
+        .await
+        .with_max_batch_size(2)
+        .with_metadata_limits(MetadataLimits {
+            max_metadata_keys: 1,
+            ..MetadataLimits::default()
+        });
+    let auth = AuthContext {
+        tenant_id: "tenant_123".to_string(),
+        project_id: "project_456".to_string(),
+        scopes: vec![],
+        rate_limit_per_sec: 100,
+        allowed_topics: vec!["orders.*".to_string()],
+        auth_type: AuthType::ApiKey {
+            key_id: "key_123".to_string(),
+        },
+        user_id: None,
+        user_role: None,
+    };
+
+    assert!(event_service.check_batch_size(2).is_ok());
+    for size in [0, 3] {
+        assert_eq!(
+            event_service.check_batch_size(size),
+            Err(InvalidBatchSize {
+                size,
+                max_batch_size: 2
+            })
+        );
+    }
+
+    let item = |topic: &str, metadata_keys: usize| BatchItem {
+        topic: topic.to_string(),
+        payload: json!({"order_id": "o-1"}),
+        metadata: (0..metadata_keys)
+            .map(|i| (format!("key_{}", i), "value".to_string()))
+            .collect::<HashMap<_, _>>(),
+        ..BatchItem::default()
+    };
+
+    let event = event_service
+        .prepare_batch_event(&auth, item("orders.created", 1))
+        .expect("Allowed item should build");
+    assert_eq!(event.tenant_id, "tenant_123");
+    assert_eq!(event.topic, "orders.created");
+
+    let error = event_service
+        .prepare_batch_event(&auth, item("users.created", 0))
+        .unwrap_err();
+    assert_eq!(
+        error,
+        BatchItemError::TopicNotAllowed("users.created".to_string())
+    );
+    let error = event_service
+        .prepare_batch_event(&auth, item("orders.created", 2))
+        .unwrap_err();
+    assert_eq!(
+        error,
+        BatchItemError::MetadataLimit(MetadataLimitError::TooManyMetadataKeys {
+            count: 2,
+            limit: 1
+        })
+    );
+}
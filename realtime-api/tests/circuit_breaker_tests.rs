@@ -0,0 +1,159 @@
+/// **Feature: realtime-saas-platform, Circuit breakers around NATS and Postgres**
+///
+/// Repeated failures open a breaker so later calls fail fast with `503` and
+/// `Retry-After` instead of waiting on the degraded dependency; once the open
+/// period passes a successful probe closes it again.
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use realtime_api::alerting::AlertingService;
+use realtime_api::api_error::ApiError;
+use realtime_api::circuit_breaker::{
+    circuit_breaker_state_gauge, circuit_open, BreakerState, CircuitBreaker,
+};
+use realtime_api::config::{CircuitBreakerConfig, LogFormat, ObservabilityConfig};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+const FAILURE_THRESHOLD: u32 = 3;
+
+fn breaker(dependency: &'static str, open_secs: u64) -> CircuitBreaker {
+    CircuitBreaker::new(
+        dependency,
+        &CircuitBreakerConfig {
+            failure_threshold: FAILURE_THRESHOLD,
+            open_secs,
+        },
+    )
+}
+
+fn alerting() -> AlertingService {
+    AlertingService::new(ObservabilityConfig {
+        tracing_endpoint: None,
+        metrics_endpoint: None,
+        service_name: "test-service".to_string(),
+        log_level: "info".to_string(),
+        enable_alerts: true,
+        alert_webhook_url: None,
+        log_format: LogFormat::default(),
+    })
+}
+
+fn any_error(_: &anyhow::Error) -> bool {
+    true
+}
+
+// Run one call through the breaker, counting whether it reached the dependency
+async fn call(
+    breaker: &CircuitBreaker,
+    attempts: &AtomicU32,
+    healthy: bool,
+) -> anyhow::Result<()> {
+    breaker
+        .call(
+            || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                if healthy {
+                    Ok(())
+                } else {
+                    Err(anyhow::anyhow!("connection timed out"))
+                }
+            },
+            any_error,
+        )
+        .await
+}
+
+fn gauge(dependency: &str) -> i64 {
+    circuit_breaker_state_gauge()
+        .with_label_values(&[dependency])
+        .get()
+}
+
+#[tokio::test]
+async fn test_repeated_failures_open_the_breaker() {
+    let alerting = alerting();
+    let breaker = breaker("trip_test", 30).with_alerting(alerting.clone());
+    let attempts = AtomicU32::new(0);
+
+    for _ in 0..FAILURE_THRESHOLD {
+        let error = call(&breaker, &attempts, false).await.unwrap_err();
+        assert!(circuit_open(&error).is_none());
+    }
+    assert_eq!(breaker.state(), BreakerState::Open);
+    assert_eq!(gauge("trip_test"), 2);
+
+    // Refused without reaching the dependency
+    let error = call(&breaker, &attempts, true).await.unwrap_err();
+    assert_eq!(attempts.load(Ordering::SeqCst), FAILURE_THRESHOLD);
+    let open = circuit_open(&error).expect("Call should be refused by the breaker");
+    assert_eq!(open.dependency, "trip_test");
+    assert!(open.retry_after <= Duration::from_secs(30));
+
+    let response = ApiError::circuit_open(open).into_response();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let retry_after: u64 = response.headers()["retry-after"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1..=30).contains(&retry_after));
+
+    // The alert is sent in the background
+    for _ in 0..50 {
+        if alerting.get_alert_count().await > 0 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(alerting.get_alert_count().await, 1);
+}
+
+#[tokio::test]
+async fn test_successful_probe_closes_the_breaker() {
+    let breaker = breaker("recovery_test", 1);
+    let attempts = AtomicU32::new(0);
+
+    for _ in 0..FAILURE_THRESHOLD {
+        call(&breaker, &attempts, false).await.unwrap_err();
+    }
+    assert_eq!(breaker.state(), BreakerState::Open);
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    call(&breaker, &attempts, true)
+        .await
+        .expect("Probe should reach the recovered dependency");
+    assert_eq!(breaker.state(), BreakerState::Closed);
+    assert_eq!(gauge("recovery_test"), 0);
+
+    call(&breaker, &attempts, true).await.unwrap();
+    assert_eq!(attempts.load(Ordering::SeqCst), FAILURE_THRESHOLD + 2);
+}
+
+#[tokio::test]
+async fn test_failed_probe_reopens_the_breaker() {
+    let alerting = alerting();
+    let breaker = breaker("probe_failure_test", 1).with_alerting(alerting.clone());
+    let attempts = AtomicU32::new(0);
+
+    for _ in 0..FAILURE_THRESHOLD {
+        call(&breaker, &attempts, false).await.unwrap_err();
+    }
+
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let error = call(&breaker, &attempts, false).await.unwrap_err();
+    assert!(circuit_open(&error).is_none(), "The probe should be attempted");
+    assert_eq!(breaker.state(), BreakerState::Open);
+
+    // A single failed probe is enough to fail fast for another period
+    let error = call(&breaker, &attempts, true).await.unwrap_err();
+    assert!(circuit_open(&error).is_some());
+    assert_eq!(attempts.load(Ordering::SeqCst), FAILURE_THRESHOLD + 1);
+
+    for _ in 0..50 {
+        if alerting.get_alert_count().await >= 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(alerting.get_alert_count().await, 2);
+}
@@ -0,0 +1,176 @@
+/// **Feature: realtime-saas-platform, mTLS client certificates**
+///
+/// A verified client certificate registered in `client_certs` authenticates as
+/// the tenant, project and scopes it was registered with, including on the
+/// `/ws` and `/sse` handshakes; any other certificate is rejected.
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use realtime_api::auth::{AuthError, AuthService, AuthType};
+use realtime_api::models::{ClientCert, Scope};
+use realtime_api::mtls::ClientCertificate;
+use realtime_api::routes::create_router;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+mod common;
+
+use common::{create_project, test_database, test_state};
+
+const ISSUER: &str = "CN=Realtime Test CA";
+
+/// A certificate identity no other test run will have registered
+fn presented_certificate(subject: &str) -> ClientCertificate {
+    ClientCertificate {
+        spki_sha256: format!("{:0>64}", Uuid::new_v4().simple()),
+        subject: format!("O=Acme, CN={}-{}", subject, Uuid::new_v4()),
+        issuer: ISSUER.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_registered_fingerprint_produces_auth_context() {
+    let database = test_database().await;
+    let (_, project) = create_project(&database, "mTLS Fingerprint Tenant").await;
+    let certificate = presented_certificate("billing-service");
+
+    let client_cert = ClientCert::for_fingerprint(
+        project.tenant_id.clone(),
+        project.id.clone(),
+        certificate.spki_sha256.to_uppercase(),
+        vec![Scope::EventsPublish, Scope::EventsSubscribe],
+        50,
+    )
+    .with_allowed_topics(vec!["billing.>".to_string()]);
+    database
+        .create_client_cert(&client_cert)
+        .await
+        .expect("Failed to register client certificate");
+
+    let auth_service = AuthService::new(database.clone(), "test_secret".to_string());
+    let context = auth_service
+        .validate_client_certificate(&certificate)
+        .await
+        .expect("Registered certificate should authenticate");
+
+    assert_eq!(context.tenant_id, project.tenant_id);
+    assert_eq!(context.project_id, project.id);
+    assert_eq!(context.scopes, vec![Scope::EventsPublish, Scope::EventsSubscribe]);
+    assert_eq!(context.rate_limit_per_sec, 50);
+    assert!(context.allows_topic("billing.invoices"));
+    assert!(!context.allows_topic("orders.created"));
+    assert!(context.api_key_id().is_none());
+    assert!(matches!(
+        context.auth_type,
+        AuthType::ClientCert { ref cert_id } if *cert_id == client_cert.id
+    ));
+}
+
+#[tokio::test]
+async fn test_registered_subject_produces_auth_context() {
+    let database = test_database().await;
+    let (_, project) = create_project(&database, "mTLS Subject Tenant").await;
+    let certificate = presented_certificate("reporting");
+
+    let client_cert = ClientCert::for_subject(
+        project.tenant_id.clone(),
+        project.id.clone(),
+        ISSUER.to_string(),
+        certificate.subject.clone(),
+        vec![Scope::EventsSubscribe],
+        100,
+    );
+    database
+        .create_client_cert(&client_cert)
+        .await
+        .expect("Failed to register client certificate");
+
+    let auth_service = AuthService::new(database.clone(), "test_secret".to_string());
+    let context = auth_service
+        .validate_client_certificate(&certificate)
+        .await
+        .expect("Registered subject should authenticate");
+    assert_eq!(context.project_id, project.id);
+    assert_eq!(context.scopes, vec![Scope::EventsSubscribe]);
+
+    // Another trusted CA can mint the same subject, but it isn't the one registered
+    let impostor = ClientCertificate {
+        issuer: "CN=Other Trusted CA".to_string(),
+        ..certificate
+    };
+    let result = auth_service.validate_client_certificate(&impostor).await;
+    assert!(matches!(result, Err(AuthError::UnknownClientCertificate)));
+}
+
+#[tokio::test]
+async fn test_unknown_certificate_is_rejected() {
+    let database = test_database().await;
+    let (_, project) = create_project(&database, "mTLS Unknown Tenant").await;
+    let registered = presented_certificate("billing-service");
+    database
+        .create_client_cert(&ClientCert::for_fingerprint(
+            project.tenant_id.clone(),
+            project.id.clone(),
+            registered.spki_sha256.clone(),
+            vec![Scope::EventsPublish],
+            100,
+        ))
+        .await
+        .expect("Failed to register client certificate");
+
+    let auth_service = AuthService::new(database.clone(), "test_secret".to_string());
+
+    // Same subject but a different key isn't enough when only the key is registered
+    let impostor = ClientCertificate {
+        subject: registered.subject.clone(),
+        ..presented_certificate("billing-service")
+    };
+    let result = auth_service.validate_client_certificate(&impostor).await;
+    assert!(matches!(result, Err(AuthError::UnknownClientCertificate)));
+
+    let unknown = presented_certificate("unknown");
+    let result = auth_service.validate_client_certificate(&unknown).await;
+    assert!(matches!(result, Err(AuthError::UnknownClientCertificate)));
+}
+
+#[tokio::test]
+async fn test_certificate_authenticates_sse_handshake() {
+    let state = test_state().await;
+    let (_, project) = create_project(&state.database, "mTLS SSE Tenant").await;
+    let certificate = presented_certificate("dashboard");
+    state
+        .database
+        .create_client_cert(&ClientCert::for_fingerprint(
+            project.tenant_id.clone(),
+            project.id.clone(),
+            certificate.spki_sha256.clone(),
+            vec![Scope::EventsSubscribe],
+            100,
+        ))
+        .await
+        .expect("Failed to register client certificate");
+    let router = create_router(state);
+
+    // The TLS listener attaches the verified certificate as an extension
+    let request = Request::builder()
+        .uri("/sse?topics=orders.created")
+        .extension(certificate)
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let request = Request::builder()
+        .uri("/sse?topics=orders.created")
+        .extension(presented_certificate("unregistered"))
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    let request = Request::builder()
+        .uri("/sse?topics=orders.created")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,250 @@
+/// **Feature: realtime-saas-platform, CloudEvents delivery format**
+///
+/// Subscribers that connect with `format=cloudevents` receive each event as a
+/// CloudEvents v1.0 JSON envelope instead of the native event JSON. Envelopes
+/// are checked against the CloudEvents JSON schema, which the fixture extends
+/// with the spec's naming rule for extension attributes.
+use jsonschema::{Draft, JSONSchema};
+use realtime_api::cloudevents::{
+    event_source, extension_name, CloudEvent, DeliveryFormat, EventEncoding,
+    CLOUDEVENTS_SPEC_VERSION,
+};
+use realtime_api::models::Event;
+use realtime_api::sse::event_frame_data;
+use realtime_api::websocket::{encode_message, WebSocketMessage};
+use serde_json::Value;
+
+fn cloudevents_schema() -> JSONSchema {
+    let path = format!(
+        "{}/tests/fixtures/cloudevents/cloudevents.json",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let schema: Value =
+        serde_json::from_str(&std::fs::read_to_string(path).expect("Failed to read fixture"))
+            .expect("Fixture is valid JSON");
+    JSONSchema::options()
+        .with_draft(Draft::Draft7)
+        .compile(&schema)
+        .expect("Fixture is a valid schema")
+}
+
+fn assert_valid_cloud_event(envelope: &Value) {
+    let schema = cloudevents_schema();
+    if let Err(errors) = schema.validate(envelope) {
+        let errors: Vec<String> = errors.map(|e| e.to_string()).collect();
+        panic!("Invalid CloudEvent {}: {:?}", envelope, errors);
+    }
+}
+
+fn event() -> Event {
+    Event::new(
+        "tenant_123".to_string(),
+        "project_456".to_string(),
+        "orders.created".to_string(),
+        serde_json::json!({"order_id": 42}),
+    )
+}
+
+fn assert_wraps(envelope: &Value, event: &Event) {
+    assert_eq!(envelope["specversion"], CLOUDEVENTS_SPEC_VERSION);
+    assert_eq!(envelope["id"], event.id.as_str());
+    assert_eq!(envelope["type"], "orders.created");
+    assert_eq!(envelope["source"], "/tenants/tenant_123/projects/project_456");
+    assert_eq!(envelope["time"], event.published_at.to_rfc3339().as_str());
+    assert_eq!(envelope["datacontenttype"], "application/json");
+    assert_eq!(envelope["data"], event.payload);
+    assert_eq!(envelope["tenantid"], "tenant_123");
+    assert_eq!(envelope["projectid"], "project_456");
+}
+
+fn cloudevents_encoding() -> EventEncoding {
+    EventEncoding::new(DeliveryFormat::CloudEvents, "tenant_123", "project_456")
+}
+
+#[test]
+fn test_delivery_format_parsing_defaults_to_native() {
+    assert_eq!(DeliveryFormat::default(), DeliveryFormat::Native);
+    assert_eq!("cloudevents".parse(), Ok(DeliveryFormat::CloudEvents));
+    assert_eq!("CloudEvents".parse(), Ok(DeliveryFormat::CloudEvents));
+    assert_eq!("native".parse(), Ok(DeliveryFormat::Native));
+    assert!("avro".parse::<DeliveryFormat>().is_err());
+
+    let format: DeliveryFormat = serde_json::from_str("\"cloudevents\"").unwrap();
+    assert_eq!(format, DeliveryFormat::CloudEvents);
+}
+
+#[test]
+fn test_event_converts_to_valid_cloud_event() {
+    let event = event();
+    let envelope = serde_json::to_value(CloudEvent::from(&event)).unwrap();
+
+    assert_valid_cloud_event(&envelope);
+    assert_wraps(&envelope, &event);
+    assert_eq!(event_source("tenant_123", "project_456"), envelope["source"]);
+    // No sequence is known outside a stream delivery
+    assert!(envelope.get("sequence").is_none());
+}
+
+#[test]
+fn test_websocket_event_is_wrapped_when_requested() {
+    let event = event();
+    let message = WebSocketMessage::sequenced(&event, Some(7));
+
+    let envelope: Value =
+        serde_json::from_str(&encode_message(&cloudevents_encoding(), &message).unwrap()).unwrap();
+    assert_valid_cloud_event(&envelope);
+    assert_wraps(&envelope, &event);
+    assert_eq!(envelope["sequence"], "7");
+
+    // Control messages keep their native shape
+    let connected = WebSocketMessage::Connected {
+        connection_id: "conn_1".to_string(),
+    };
+    let json: Value =
+        serde_json::from_str(&encode_message(&cloudevents_encoding(), &connected).unwrap())
+            .unwrap();
+    assert_eq!(json["type"], "Connected");
+}
+
+#[test]
+fn test_websocket_native_format_is_unchanged() {
+    let event = event();
+    let message = WebSocketMessage::sequenced(&event, Some(7));
+    let encoding = EventEncoding::new(DeliveryFormat::Native, "tenant_123", "project_456");
+
+    assert_eq!(
+        encode_message(&encoding, &message).unwrap(),
+        serde_json::to_string(&message).unwrap()
+    );
+}
+
+#[test]
+fn test_sse_event_is_wrapped_when_requested() {
+    let event = event();
+    let published_at = event.published_at.to_rfc3339();
+
+    let envelope = event_frame_data(
+        &cloudevents_encoding(),
+        &event.id,
+        &event.topic,
+        &event.payload,
+        &published_at,
+        Some(9),
+        None,
+        &event.headers,
+        &event.metadata,
+        &event.attributes,
+    );
+    assert_valid_cloud_event(&envelope);
+    assert_wraps(&envelope, &event);
+    assert_eq!(envelope["sequence"], "9");
+
+    let native = event_frame_data(
+        &EventEncoding::new(DeliveryFormat::Native, "tenant_123", "project_456"),
+        &event.id,
+        &event.topic,
+        &event.payload,
+        &published_at,
+        Some(9),
+        None,
+        &event.headers,
+        &event.metadata,
+        &event.attributes,
+    );
+    assert_eq!(
+        native,
+        serde_json::json!({
+            "id": event.id,
+            "topic": "orders.created",
+            "payload": {"order_id": 42},
+            "published_at": published_at
+        })
+    );
+}
+
+#[test]
+fn test_partition_key_is_carried_as_extension() {
+    let mut event = event();
+    event.partition_key = Some("order_42".to_string());
+
+    let envelope = serde_json::to_value(CloudEvent::from(&event)).unwrap();
+    assert_valid_cloud_event(&envelope);
+    assert_eq!(envelope["partitionkey"], "order_42");
+
+    let message = WebSocketMessage::sequenced(&event, Some(7));
+    let envelope: Value =
+        serde_json::from_str(&encode_message(&cloudevents_encoding(), &message).unwrap()).unwrap();
+    assert_valid_cloud_event(&envelope);
+    assert_eq!(envelope["partitionkey"], "order_42");
+
+    let envelope = event_frame_data(
+        &cloudevents_encoding(),
+        &event.id,
+        &event.topic,
+        &event.payload,
+        &event.published_at.to_rfc3339(),
+        Some(9),
+        event.partition_key.as_deref(),
+        &event.headers,
+        &event.metadata,
+        &event.attributes,
+    );
+    assert_valid_cloud_event(&envelope);
+    assert_eq!(envelope["partitionkey"], "order_42");
+}
+
+#[test]
+fn test_producer_headers_are_carried_as_extensions() {
+    let mut event = event();
+    for (name, value) in [
+        ("X-Trace-Id", "trace-1"),
+        ("schema_version", "3"),
+        // Can't replace the envelope's own attributes or be named validly
+        ("Source", "spoofed"),
+        ("-", "nameless"),
+        ("a-very-long-producer-header", "long"),
+    ] {
+        event.headers.insert(name.to_string(), value.to_string());
+    }
+
+    let expected = |envelope: &Value| {
+        assert_valid_cloud_event(envelope);
+        assert_wraps(envelope, &event);
+        assert_eq!(envelope["xtraceid"], "trace-1");
+        assert_eq!(envelope["schemaversion"], "3");
+        assert!(envelope.get("averylongproducerheader").is_none());
+    };
+
+    // Webhooks
+    expected(&serde_json::to_value(CloudEvent::from(&event)).unwrap());
+
+    // WebSocket
+    let message = WebSocketMessage::sequenced(&event, Some(7));
+    let envelope: Value =
+        serde_json::from_str(&encode_message(&cloudevents_encoding(), &message).unwrap()).unwrap();
+    expected(&envelope);
+
+    // SSE
+    expected(&event_frame_data(
+        &cloudevents_encoding(),
+        &event.id,
+        &event.topic,
+        &event.payload,
+        &event.published_at.to_rfc3339(),
+        Some(9),
+        None,
+        &event.headers,
+        &event.metadata,
+        &event.attributes,
+    ));
+}
+
+#[test]
+fn test_extension_names_follow_the_naming_rule() {
+    assert_eq!(extension_name("X-Trace-Id").as_deref(), Some("xtraceid"));
+    assert_eq!(extension_name("region").as_deref(), Some("region"));
+    assert_eq!(extension_name("Type"), None);
+    assert_eq!(extension_name("partition-key"), None);
+    assert_eq!(extension_name("_-_"), None);
+    assert_eq!(extension_name(&"a".repeat(21)), None);
+}
@@ -0,0 +1,116 @@
+/// **Feature: realtime-saas-platform, Active connection gauges**
+///
+/// `realtime_websocket_connections_active` and `realtime_sse_connections_active`
+/// follow the global connection managers as connections are added, removed,
+/// closed and terminated, and the stats helpers report the same totals.
+use realtime_api::observability::{sse_connections_gauge, websocket_connections_gauge, Metrics};
+use realtime_api::sse::{get_sse_stats, sse_manager, SSEConnection};
+use realtime_api::websocket::{get_websocket_stats, websocket_manager, WebSocketConnection};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+fn websocket(tenant_id: &str) -> WebSocketConnection {
+    let (sender, _) = broadcast::channel(8);
+    WebSocketConnection {
+        id: Uuid::new_v4().to_string(),
+        tenant_id: tenant_id.to_string(),
+        project_id: "project".to_string(),
+        subscribed_topics: vec![],
+        sender,
+        created_at: chrono::Utc::now(),
+        acked: false,
+    }
+}
+
+fn sse(tenant_id: &str) -> SSEConnection {
+    let (sender, _) = broadcast::channel(8);
+    SSEConnection {
+        id: Uuid::new_v4().to_string(),
+        tenant_id: tenant_id.to_string(),
+        project_id: "project".to_string(),
+        subscribed_topics: vec![],
+        sender,
+        created_at: chrono::Utc::now(),
+        acked: false,
+    }
+}
+
+fn assert_websocket_active(expected: usize) {
+    assert_eq!(websocket_connections_gauge().get() as usize, expected);
+    assert_eq!(websocket_manager().connection_count(), expected);
+    assert_eq!(get_websocket_stats()["total_connections"], expected);
+}
+
+fn assert_sse_active(expected: usize) {
+    assert_eq!(sse_connections_gauge().get() as usize, expected);
+    assert_eq!(sse_manager().connection_count(), expected);
+    assert_eq!(get_sse_stats()["total_connections"], expected);
+}
+
+#[test]
+fn test_websocket_gauge_follows_connections() {
+    let tenant_id = Uuid::new_v4().to_string();
+    let baseline = websocket_manager().connection_count();
+    assert_websocket_active(baseline);
+
+    let first = websocket(&tenant_id);
+    let second = websocket(&tenant_id);
+    let third = websocket(&tenant_id);
+    for connection in [&first, &second, &third] {
+        websocket_manager()
+            .add_connection(connection.clone())
+            .unwrap();
+    }
+    assert_websocket_active(baseline + 3);
+
+    websocket_manager().remove_connection(&first.id);
+    assert_websocket_active(baseline + 2);
+    // Removing an unknown connection changes nothing
+    websocket_manager().remove_connection(&first.id);
+    assert_websocket_active(baseline + 2);
+
+    websocket_manager().close_connections(&[second.id.clone()], 1000, "bye");
+    assert_websocket_active(baseline + 1);
+
+    let terminated = websocket_manager().terminate_tenant_connections(&tenant_id);
+    assert_eq!(terminated, vec![third.id.clone()]);
+    assert_websocket_active(baseline);
+}
+
+#[test]
+fn test_sse_gauge_follows_connections() {
+    let tenant_id = Uuid::new_v4().to_string();
+    let baseline = sse_manager().connection_count();
+    assert_sse_active(baseline);
+
+    let first = sse(&tenant_id);
+    let second = sse(&tenant_id);
+    let third = sse(&tenant_id);
+    for connection in [&first, &second, &third] {
+        sse_manager().add_connection(connection.clone()).unwrap();
+    }
+    assert_sse_active(baseline + 3);
+
+    sse_manager().remove_connection(&first.id);
+    assert_sse_active(baseline + 2);
+
+    sse_manager().close_connections(&[second.id.clone()], 1000, "bye");
+    assert_sse_active(baseline + 1);
+
+    let terminated = sse_manager().terminate_tenant_connections(&tenant_id);
+    assert_eq!(terminated, vec![third.id.clone()]);
+    assert_sse_active(baseline);
+}
+
+#[test]
+fn test_gauges_are_exported_by_metrics_registry() {
+    let metrics = Metrics::new().expect("Failed to create metrics");
+    let names: Vec<String> = metrics
+        .registry
+        .gather()
+        .iter()
+        .map(|family| family.get_name().to_string())
+        .collect();
+    assert!(names.contains(&"realtime_websocket_connections_active".to_string()));
+    assert!(names.contains(&"realtime_sse_connections_active".to_string()));
+}
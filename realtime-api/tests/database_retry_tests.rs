@@ -0,0 +1,120 @@
+/// **Feature: realtime-saas-platform, Database retries**
+///
+/// Reads that fail with a transient Postgres error (dropped connection,
+/// deadlock) are retried with backoff; permanent errors and pool timeouts
+/// surface at once.
+use realtime_api::config::DatabaseConfig;
+use realtime_api::db_retry::{is_transient, DbRetryPolicy};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+fn fast_policy(max_attempts: u32) -> DbRetryPolicy {
+    DbRetryPolicy {
+        max_attempts,
+        initial_backoff: Duration::from_millis(1),
+    }
+}
+
+fn connection_reset() -> sqlx::Error {
+    sqlx::Error::Io(std::io::Error::new(
+        std::io::ErrorKind::ConnectionReset,
+        "connection reset by peer",
+    ))
+}
+
+// Operation failing with `error` for its first `failures` calls
+async fn flaky(
+    calls: &AtomicU32,
+    failures: u32,
+    error: fn() -> sqlx::Error,
+) -> Result<&'static str, sqlx::Error> {
+    if calls.fetch_add(1, Ordering::SeqCst) < failures {
+        Err(error())
+    } else {
+        Ok("tenant")
+    }
+}
+
+#[tokio::test]
+async fn test_transient_error_succeeds_on_second_attempt() {
+    let calls = AtomicU32::new(0);
+    let result = fast_policy(3)
+        .run(|| flaky(&calls, 1, connection_reset))
+        .await;
+
+    assert_eq!(result.unwrap(), "tenant");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_dropped_connection_is_retried() {
+    let calls = AtomicU32::new(0);
+    let result = fast_policy(3)
+        .run(|| flaky(&calls, 2, connection_reset))
+        .await;
+
+    assert!(result.is_ok());
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_attempts() {
+    let calls = AtomicU32::new(0);
+    let result = fast_policy(3)
+        .run(|| flaky(&calls, u32::MAX, connection_reset))
+        .await;
+
+    assert!(matches!(result, Err(sqlx::Error::Io(_))));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_pool_timeout_is_not_retried() {
+    let calls = AtomicU32::new(0);
+    let result = fast_policy(3)
+        .run(|| flaky(&calls, 1, || sqlx::Error::PoolTimedOut))
+        .await;
+
+    assert!(matches!(result, Err(sqlx::Error::PoolTimedOut)));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_permanent_error_is_not_retried() {
+    let calls = AtomicU32::new(0);
+    let result = fast_policy(3)
+        .run(|| flaky(&calls, 1, || sqlx::Error::RowNotFound))
+        .await;
+
+    assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(!is_transient(&sqlx::Error::PoolClosed));
+}
+
+#[test]
+fn test_backoff_doubles_with_jitter() {
+    let policy = DbRetryPolicy {
+        max_attempts: 5,
+        initial_backoff: Duration::from_millis(100),
+    };
+    for _ in 0..20 {
+        let first = policy.backoff(1);
+        assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+        let third = policy.backoff(3);
+        assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+    }
+    // Capped however many attempts have failed
+    assert!(policy.backoff(30) <= Duration::from_secs(2));
+}
+
+#[test]
+fn test_policy_follows_config() {
+    let mut config = DatabaseConfig::new("postgresql://localhost/realtime_test");
+    assert_eq!(DbRetryPolicy::from(&config), DbRetryPolicy::default());
+
+    config.max_retry_attempts = 0;
+    config.retry_backoff_ms = 10;
+    let policy = DbRetryPolicy::from(&config);
+    assert_eq!(policy.max_attempts, 1);
+    assert_eq!(policy.initial_backoff, Duration::from_millis(10));
+}
@@ -0,0 +1,310 @@
+/// **Feature: realtime-saas-platform, Dead-letter stream**
+///
+/// Events that fail schema validation or are never acknowledged by JetStream are
+/// kept on `dlq.<tenant>.<project>` with the failure reason, can be listed per
+/// tenant, and can be replayed once the cause is fixed.
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::Router;
+use realtime_api::cloudevents::DeliveryFormat;
+use realtime_api::database::Database;
+use realtime_api::event_service::{DeadLetterReplay, EventService, PublishResult};
+use realtime_api::models::{Event, Webhook};
+use realtime_api::nats::DeadLetterKind;
+use realtime_api::request_signing::SigningSecretCipher;
+use realtime_api::schema_validator::{SchemaMode, SchemaValidator};
+use realtime_api::webhooks::{RetryPolicy, WebhookDispatcher};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+mod common;
+
+use common::{create_project, test_database, test_nats_client};
+
+async fn test_event_service(database: Database) -> EventService {
+    let validator = SchemaValidator::new()
+        .with_store(database.clone())
+        .with_sync_interval(Duration::ZERO);
+    EventService::new(database, test_nats_client().await, validator)
+}
+
+fn recently() -> chrono::DateTime<chrono::Utc> {
+    chrono::Utc::now() - chrono::Duration::minutes(1)
+}
+
+#[tokio::test]
+async fn test_validation_failure_is_dead_lettered_and_replayable() {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone()).await;
+    let (tenant, project) = create_project(&database, "DLQ Tenant").await;
+
+    let schemas = SchemaValidator::new().with_store(database.clone());
+    schemas
+        .store_schema(
+            &tenant.id,
+            &project.id,
+            "orders.created",
+            json!({"type": "object", "required": ["order_id"]}),
+            SchemaMode::Enforce,
+        )
+        .await
+        .unwrap();
+
+    let event = Event::new(
+        tenant.id.clone(),
+        project.id.clone(),
+        "orders.created".to_string(),
+        json!({"id": "o-1"}),
+    );
+    assert!(matches!(
+        event_service.publish_event(&event).await.unwrap(),
+        PublishResult::ValidationFailed(_)
+    ));
+
+    let dead_letters = event_service
+        .list_dead_letters(&tenant.id, Some(&project.id), recently(), 10)
+        .await
+        .unwrap();
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0].kind, DeadLetterKind::Validation);
+    assert_eq!(dead_letters[0].event.id, event.id);
+    assert!(dead_letters[0].reason.contains("validation failed"));
+
+    // Another tenant can't see or replay it
+    let (other_tenant, _) = create_project(&database, "DLQ Tenant").await;
+    assert!(event_service
+        .list_dead_letters(&other_tenant.id, None, recently(), 10)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(event_service
+        .replay_dead_letter(&other_tenant.id, dead_letters[0].id)
+        .await
+        .unwrap()
+        .is_none());
+
+    // Replaying before the fix keeps the dead letter and names the new one
+    let (_, result) = event_service
+        .replay_dead_letter(&tenant.id, dead_letters[0].id)
+        .await
+        .unwrap()
+        .expect("Dead letter should exist");
+    let DeadLetterReplay::Rejected {
+        result: PublishResult::ValidationFailed(_),
+        dead_letter_id: Some(new_id),
+    } = result
+    else {
+        panic!("Expected the replay to fail validation, got {:?}", result);
+    };
+    assert_ne!(new_id, dead_letters[0].id);
+    let ids: Vec<u64> = event_service
+        .list_dead_letters(&tenant.id, Some(&project.id), recently(), 10)
+        .await
+        .unwrap()
+        .iter()
+        .map(|dead_letter| dead_letter.id)
+        .collect();
+    assert_eq!(ids, vec![dead_letters[0].id, new_id]);
+
+    // Once the schema accepts the payload, the replay publishes it
+    schemas
+        .store_schema(
+            &tenant.id,
+            &project.id,
+            "orders.created",
+            json!({"type": "object", "required": ["id"]}),
+            SchemaMode::Enforce,
+        )
+        .await
+        .unwrap();
+
+    let (replayed, result) = event_service
+        .replay_dead_letter(&tenant.id, dead_letters[0].id)
+        .await
+        .unwrap()
+        .expect("Dead letter should exist");
+    assert_eq!(replayed.event.id, event.id);
+    assert!(matches!(
+        result,
+        DeadLetterReplay::Published(PublishResult::Success { .. })
+    ));
+
+    let remaining = event_service
+        .list_dead_letters(&tenant.id, Some(&project.id), recently(), 10)
+        .await
+        .unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].id, new_id);
+}
+
+#[tokio::test]
+async fn test_delivery_failure_is_dead_lettered_and_replayable() {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone()).await;
+    let (tenant, project) = create_project(&database, "DLQ Tenant").await;
+
+    let event = Event::new(
+        tenant.id.clone(),
+        project.id.clone(),
+        "orders.shipped".to_string(),
+        json!({"order_id": "o-2"}),
+    );
+    event_service
+        .dead_letter(&event, DeadLetterKind::Delivery, "JetStream publish timed out\nretrying")
+        .await;
+
+    let dead_letters = event_service
+        .list_dead_letters(&tenant.id, None, recently(), 10)
+        .await
+        .unwrap();
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0].kind, DeadLetterKind::Delivery);
+    assert_eq!(dead_letters[0].reason, "JetStream publish timed out retrying");
+
+    let (_, result) = event_service
+        .replay_dead_letter(&tenant.id, dead_letters[0].id)
+        .await
+        .unwrap()
+        .expect("Dead letter should exist");
+    assert!(matches!(
+        result,
+        DeadLetterReplay::Published(PublishResult::Success { .. })
+    ));
+
+    let stored = database.get_event(&tenant.id, &event.id).await.unwrap();
+    assert!(stored.is_some(), "Replayed event should be persisted");
+}
+
+/// Receiver answering `/ok/*` with 200 and `/fail/*` with 500, recording each path hit
+async fn webhook_receiver() -> (Arc<Mutex<Vec<String>>>, String) {
+    async fn receive(
+        State(hits): State<Arc<Mutex<Vec<String>>>>,
+        Path((outcome, name)): Path<(String, String)>,
+    ) -> StatusCode {
+        hits.lock().unwrap().push(name);
+        if outcome == "ok" {
+            StatusCode::OK
+        } else {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+
+    let hits = Arc::new(Mutex::new(Vec::new()));
+    let app = Router::new()
+        .route("/:outcome/:name", post(receive))
+        .with_state(hits.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (hits, url)
+}
+
+async fn register_webhook(
+    database: &Database,
+    cipher: &SigningSecretCipher,
+    event: &Event,
+    url: String,
+) -> Webhook {
+    let now = chrono::Utc::now();
+    let id = uuid::Uuid::new_v4().to_string();
+    let webhook = Webhook {
+        secret: cipher.seal(&id, "whsec_test_secret"),
+        id,
+        tenant_id: event.tenant_id.clone(),
+        project_id: event.project_id.clone(),
+        url,
+        topic_filter: ">".to_string(),
+        format: DeliveryFormat::Native,
+        active: true,
+        created_at: now,
+        updated_at: now,
+    };
+    database.create_webhook(&webhook).await.unwrap();
+    webhook
+}
+
+#[tokio::test]
+async fn test_webhook_dead_letter_is_redelivered_to_its_webhook_only() {
+    let database = test_database().await;
+    let nats_client = test_nats_client().await;
+    let cipher = SigningSecretCipher::new("test_secret");
+    let dispatcher = WebhookDispatcher::new(
+        RetryPolicy {
+            max_attempts: 1,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(10),
+        },
+        Duration::from_secs(5),
+        cipher.clone(),
+    )
+    .with_private_addresses(true);
+    let event_service = test_event_service(database.clone())
+        .await
+        .with_webhooks(dispatcher);
+    let (tenant, project) = create_project(&database, "DLQ Webhook Tenant").await;
+    let (hits, url) = webhook_receiver().await;
+
+    let event = Event::new(
+        tenant.id.clone(),
+        project.id.clone(),
+        "orders.paid".to_string(),
+        json!({"order_id": "o-3"}),
+    );
+    let failed = register_webhook(&database, &cipher, &event, format!("{}/ok/failed", url)).await;
+    register_webhook(&database, &cipher, &event, format!("{}/ok/healthy", url)).await;
+    let broken = register_webhook(&database, &cipher, &event, format!("{}/fail/broken", url)).await;
+
+    for webhook in [&failed, &broken] {
+        nats_client
+            .publish_targeted_dead_letter(
+                &event,
+                DeadLetterKind::Webhook,
+                Some(&webhook.id),
+                "Webhook responded with status 500",
+            )
+            .await
+            .unwrap();
+    }
+    let dead_letters = event_service
+        .list_dead_letters(&tenant.id, None, recently(), 10)
+        .await
+        .unwrap();
+    assert_eq!(dead_letters.len(), 2);
+    let id_for = |webhook: &Webhook| {
+        dead_letters
+            .iter()
+            .find(|d| d.target.as_deref() == Some(webhook.id.as_str()))
+            .expect("Dead letter should name its webhook")
+            .id
+    };
+
+    let (_, result) = event_service
+        .replay_dead_letter(&tenant.id, id_for(&failed))
+        .await
+        .unwrap()
+        .expect("Dead letter should exist");
+    assert!(matches!(result, DeadLetterReplay::Redelivered { attempts: 1 }));
+    assert_eq!(*hits.lock().unwrap(), vec!["failed".to_string()]);
+
+    // A webhook that still fails keeps its dead letter
+    let (_, result) = event_service
+        .replay_dead_letter(&tenant.id, id_for(&broken))
+        .await
+        .unwrap()
+        .expect("Dead letter should exist");
+    assert!(matches!(result, DeadLetterReplay::Failed(_)));
+
+    let remaining = event_service
+        .list_dead_letters(&tenant.id, None, recently(), 10)
+        .await
+        .unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].target.as_deref(), Some(broken.id.as_str()));
+    assert!(database
+        .get_event(&tenant.id, &event.id)
+        .await
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,65 @@
+/// **Feature: realtime-saas-platform, Content-based event deduplication**
+///
+/// Publishing an identical payload to a topic with a dedup window stores and
+/// delivers the first event only; the repeat gets the original event back.
+use realtime_api::event_service::PublishResult;
+use realtime_api::models::Event;
+use serde_json::json;
+use std::collections::HashMap;
+
+mod common;
+
+use common::{create_project, test_database, test_event_service};
+
+#[tokio::test]
+async fn test_identical_publishes_store_and_deliver_one_event() {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone())
+        .await
+        .with_dedup_windows(HashMap::from([("orders.created".to_string(), 60)]));
+    let (tenant, project) = create_project(&database, "Dedup Tenant").await;
+    let mut subscription = event_service
+        .subscribe_to_topics(&tenant.id, &project.id, vec!["orders.created".to_string()])
+        .await
+        .expect("Failed to subscribe");
+
+    let order_event = || {
+        Event::new(
+            tenant.id.clone(),
+            project.id.clone(),
+            "orders.created".to_string(),
+            json!({"order_id": "o-1"}),
+        )
+    };
+    let original = order_event();
+    let first_sequence = match event_service.publish_event(&original).await.unwrap() {
+        PublishResult::Success { sequence } => sequence,
+        other => panic!("Unexpected publish result: {:?}", other),
+    };
+    match event_service.publish_event(&order_event()).await.unwrap() {
+        PublishResult::Deduplicated {
+            original_event_id,
+            sequence,
+        } => {
+            assert_eq!(original_event_id, original.id);
+            assert_eq!(sequence, first_sequence);
+        }
+        other => panic!("Repeat should be deduplicated, got {:?}", other),
+    }
+
+    let stored = database
+        .get_events_for_tenant(&tenant.id, 10)
+        .await
+        .expect("Failed to list events");
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].id, original.id);
+
+    // Publishing fans out before it returns, so every delivery is already queued
+    let mut delivered = Vec::new();
+    while let Ok(event) = subscription.receiver.try_recv() {
+        if subscription.matches(&event) {
+            delivered.push(event.id);
+        }
+    }
+    assert_eq!(delivered, vec![original.id]);
+}
@@ -0,0 +1,36 @@
+/// **Feature: realtime-saas-platform, Single event fetch**
+///
+/// An event can be fetched by id only by its own tenant; other tenants see it as
+/// missing, the same as an id that was never stored.
+use realtime_api::models::Event;
+use serde_json::json;
+
+mod common;
+
+use common::{create_project, test_database};
+
+#[tokio::test]
+async fn test_event_is_only_visible_to_its_tenant() {
+    let database = test_database().await;
+    let (owner, project) = create_project(&database, "Fetch Tenant").await;
+    let (other, _) = create_project(&database, "Fetch Tenant").await;
+
+    let event = Event::new(
+        owner.id.clone(),
+        project.id.clone(),
+        "orders.created".to_string(),
+        json!({"order_id": "o-1"}),
+    );
+    database.create_event(&event).await.expect("Failed to store event");
+
+    let fetched = database
+        .get_event(&owner.id, &event.id)
+        .await
+        .unwrap()
+        .expect("Owner should see its event");
+    assert_eq!(fetched.id, event.id);
+    assert_eq!(fetched.payload, json!({"order_id": "o-1"}));
+
+    assert!(database.get_event(&other.id, &event.id).await.unwrap().is_none());
+    assert!(database.get_event(&owner.id, "missing-event").await.unwrap().is_none());
+}
@@ -0,0 +1,76 @@
+/// **Feature: realtime-saas-platform, Filtered event queries**
+///
+/// Topic and published-at filters on event listings are applied by the database,
+/// so clients only receive the events they asked for.
+use chrono::{Duration, Utc};
+use realtime_api::models::{BillingPlan, Event, Project, Tenant};
+use serde_json::json;
+
+mod common;
+
+use common::test_database;
+
+#[tokio::test]
+async fn test_topic_and_date_filters_narrow_results() {
+    let database = test_database().await;
+
+    let tenant = Tenant::new("Filter Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    database.create_project(&project).await.expect("Failed to create project");
+
+    // Two topics, each with one event last week and one today
+    let now = Utc::now();
+    let last_week = now - Duration::days(7);
+    for topic in ["orders.created", "billing.invoiced"] {
+        for published_at in [last_week, now] {
+            let mut event = Event::new(
+                tenant.id.clone(),
+                project.id.clone(),
+                topic.to_string(),
+                json!({ "topic": topic }),
+            );
+            event.published_at = published_at;
+            database.create_event(&event).await.expect("Failed to store event");
+        }
+    }
+
+    let all = database
+        .query_events(&tenant.id, None, None, None, 100)
+        .await
+        .unwrap();
+    assert_eq!(all.len(), 4);
+
+    let orders = database
+        .query_events(&tenant.id, Some("orders.created"), None, None, 100)
+        .await
+        .unwrap();
+    assert_eq!(orders.len(), 2);
+    assert!(orders.iter().all(|event| event.topic == "orders.created"));
+
+    let recent = database
+        .query_events(&tenant.id, None, Some(now - Duration::days(1)), None, 100)
+        .await
+        .unwrap();
+    assert_eq!(recent.len(), 2);
+
+    let old_billing = database
+        .query_events(
+            &tenant.id,
+            Some("billing.invoiced"),
+            None,
+            Some(now - Duration::days(1)),
+            100,
+        )
+        .await
+        .unwrap();
+    assert_eq!(old_billing.len(), 1);
+    assert_eq!(old_billing[0].topic, "billing.invoiced");
+    assert!(old_billing[0].published_at < now - Duration::days(1));
+
+    let other_tenant = database
+        .query_events("tenant_that_does_not_exist", Some("orders.created"), None, None, 100)
+        .await
+        .unwrap();
+    assert!(other_tenant.is_empty());
+}
@@ -0,0 +1,323 @@
+/// **Feature: realtime-saas-platform, Event headers**
+///
+/// Headers attached on publish travel as JetStream message headers next to the
+/// platform's `ce-*` headers, are stored with the event and come back on
+/// replay and in WebSocket, SSE and webhook deliveries. Producers can't set
+/// the platform's own headers. Event metadata and attributes are kept and
+/// delivered the same way.
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::{Extension, Json};
+use futures_util::StreamExt;
+use realtime_api::api::{publish_event, AppState, PublishEventRequest};
+use realtime_api::auth::{AuthContext, AuthType};
+use realtime_api::cloudevents::{DeliveryFormat, EventEncoding};
+use realtime_api::models::{
+    is_reserved_header, BillingPlan, Event, EventBuildError, Project, Scope, Tenant,
+    MAX_EVENT_HEADERS,
+};
+use realtime_api::nats::ReplayRequest;
+use realtime_api::sse::event_frame_data;
+use realtime_api::websocket::WebSocketMessage;
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::Duration;
+
+mod common;
+
+use common::test_state;
+
+async fn create_project(state: &AppState) -> (Tenant, Project) {
+    let tenant = Tenant::new(
+        "Event Headers Tenant".to_string(),
+        BillingPlan::Free {
+            monthly_events: 10000,
+        },
+    );
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    state
+        .database
+        .create_tenant(&tenant)
+        .await
+        .expect("Failed to create tenant");
+    state
+        .database
+        .create_project(&project)
+        .await
+        .expect("Failed to create project");
+    (tenant, project)
+}
+
+fn publisher(tenant: &Tenant, project: &Project) -> AuthContext {
+    AuthContext {
+        tenant_id: tenant.id.clone(),
+        project_id: project.id.clone(),
+        scopes: vec![Scope::EventsPublish],
+        rate_limit_per_sec: 100,
+        allowed_topics: vec![],
+        auth_type: AuthType::ApiKey {
+            key_id: "key_123".to_string(),
+        },
+        user_id: None,
+        user_role: None,
+    }
+}
+
+fn request(headers: serde_json::Value) -> PublishEventRequest {
+    serde_json::from_value(json!({
+        "topic": "orders.created",
+        "payload": {"order_id": 42},
+        "headers": headers
+    }))
+    .unwrap()
+}
+
+fn custom_headers() -> HashMap<String, String> {
+    HashMap::from([
+        ("trace-id".to_string(), "4bf92f3577b34da6".to_string()),
+        ("schema-version".to_string(), "3".to_string()),
+        ("source".to_string(), "checkout-service".to_string()),
+    ])
+}
+
+#[tokio::test]
+async fn test_headers_round_trip_from_publish_to_delivery() {
+    let state = test_state().await;
+    let (tenant, project) = create_project(&state).await;
+    let nats_client = state.event_service.nats_client().clone();
+
+    let mut messages = nats_client
+        .client()
+        .subscribe(format!("events.{}.{}.>", tenant.id, project.id))
+        .await
+        .expect("Failed to subscribe");
+
+    let Json(published) = publish_event(
+        State(state.clone()),
+        Extension(publisher(&tenant, &project)),
+        HeaderMap::new(),
+        Json(request(json!(custom_headers()))),
+    )
+    .await
+    .expect("Event should be published");
+
+    // Custom headers sit next to the CloudEvents headers on the stream message
+    let message = tokio::time::timeout(Duration::from_secs(5), messages.next())
+        .await
+        .expect("Timed out waiting for the stream message")
+        .expect("Subscription closed");
+    let message_headers = message.headers.expect("Message should carry headers");
+    let header = |name: &str| {
+        message_headers
+            .get(name)
+            .map(|value| value.as_str().to_string())
+    };
+    for (name, value) in custom_headers() {
+        assert_eq!(header(&name), Some(value));
+    }
+    assert_eq!(header("ce-id"), Some(published.event_id.clone()));
+    assert_eq!(header("ce-type").as_deref(), Some("orders.created"));
+    assert_eq!(header("ce-specversion").as_deref(), Some("1.0"));
+
+    let stored = state
+        .database
+        .get_event(&tenant.id, &published.event_id)
+        .await
+        .unwrap()
+        .expect("Event should be stored");
+    assert_eq!(stored.headers, custom_headers());
+
+    let replayed: Vec<_> = nats_client
+        .replay_with_cursors(ReplayRequest {
+            tenant_id: tenant.id.clone(),
+            project_id: project.id.clone(),
+            topic: None,
+            cursor: None,
+            from_timestamp: None,
+            limit: Some(10),
+            transform: None,
+        })
+        .await
+        .expect("Failed to replay")
+        .collect()
+        .await;
+    assert_eq!(replayed.len(), 1);
+    let (event, cursor) = &replayed[0];
+    assert_eq!(event.headers, custom_headers());
+
+    // WebSocket
+    let frame =
+        serde_json::to_value(WebSocketMessage::sequenced(event, Some(cursor.sequence))).unwrap();
+    assert_eq!(frame["headers"], json!(custom_headers()));
+    let restored: WebSocketMessage = serde_json::from_value(frame).unwrap();
+    match restored {
+        WebSocketMessage::Event { headers, .. } => assert_eq!(headers, custom_headers()),
+        other => panic!("Expected an event, got {:?}", other),
+    }
+
+    // SSE
+    let data = event_frame_data(
+        &EventEncoding::new(DeliveryFormat::Native, &tenant.id, &project.id),
+        &event.id,
+        &event.topic,
+        &event.payload,
+        &event.published_at.to_rfc3339(),
+        Some(cursor.sequence),
+        None,
+        &event.headers,
+        &event.metadata,
+        &event.attributes,
+    );
+    assert_eq!(data["headers"], json!(custom_headers()));
+
+    // Native webhook bodies are the serialized event
+    let body: Event = serde_json::from_slice(&serde_json::to_vec(event).unwrap()).unwrap();
+    assert_eq!(body.headers, custom_headers());
+}
+
+#[tokio::test]
+async fn test_metadata_and_attributes_are_stored_and_delivered() {
+    let state = test_state().await;
+    let (tenant, project) = create_project(&state).await;
+    let metadata = HashMap::from([("source".to_string(), "checkout-service".to_string())]);
+    let attributes = HashMap::from([("region".to_string(), "eu-west-1".to_string())]);
+
+    let request: PublishEventRequest = serde_json::from_value(json!({
+        "topic": "orders.created",
+        "payload": {"order_id": 42},
+        "metadata": metadata,
+        "attributes": attributes
+    }))
+    .unwrap();
+    let Json(published) = publish_event(
+        State(state.clone()),
+        Extension(publisher(&tenant, &project)),
+        HeaderMap::new(),
+        Json(request),
+    )
+    .await
+    .expect("Event should be published");
+
+    let stored = state
+        .database
+        .get_event(&tenant.id, &published.event_id)
+        .await
+        .unwrap()
+        .expect("Event should be stored");
+    assert_eq!(stored.metadata, metadata);
+    assert_eq!(stored.attributes, attributes);
+
+    let frame = serde_json::to_value(WebSocketMessage::sequenced(&stored, Some(1))).unwrap();
+    assert_eq!(frame["metadata"], json!(metadata));
+    assert_eq!(frame["attributes"], json!(attributes));
+
+    let data = event_frame_data(
+        &EventEncoding::new(DeliveryFormat::Native, &tenant.id, &project.id),
+        &stored.id,
+        &stored.topic,
+        &stored.payload,
+        &stored.published_at.to_rfc3339(),
+        Some(1),
+        None,
+        &stored.headers,
+        &stored.metadata,
+        &stored.attributes,
+    );
+    assert_eq!(data["metadata"], json!(metadata));
+    assert_eq!(data["attributes"], json!(attributes));
+}
+
+#[tokio::test]
+async fn test_reserved_headers_are_rejected_on_publish() {
+    let state = test_state().await;
+    let (tenant, project) = create_project(&state).await;
+
+    for reserved in ["ce-id", "CE-Source", "tenant_id", "Nats-Msg-Id"] {
+        let (status, Json(error)) = publish_event(
+            State(state.clone()),
+            Extension(publisher(&tenant, &project)),
+            HeaderMap::new(),
+            Json(request(json!({ reserved: "spoofed" }))),
+        )
+        .await
+        .expect_err("Reserved header should be rejected")
+        .into_parts();
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.error.code, "RESERVED_HEADER");
+        assert_eq!(error.error.details.unwrap()["header"], reserved);
+    }
+
+    let (status, Json(error)) = publish_event(
+        State(state.clone()),
+        Extension(publisher(&tenant, &project)),
+        HeaderMap::new(),
+        Json(request(json!({ "trace id": "with a space" }))),
+    )
+    .await
+    .expect_err("Invalid header name should be rejected")
+    .into_parts();
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(error.error.code, "INVALID_HEADER");
+}
+
+#[test]
+fn test_headers_are_validated() {
+    let build = |headers: HashMap<String, String>| {
+        Event::builder()
+            .tenant_id("tenant")
+            .project_id("project")
+            .topic("orders.created")
+            .headers(headers)
+            .build()
+    };
+    let one = |name: &str, value: &str| HashMap::from([(name.to_string(), value.to_string())]);
+
+    assert_eq!(build(custom_headers()).unwrap().headers, custom_headers());
+
+    assert!(is_reserved_header("ce-time"));
+    assert!(is_reserved_header("Partition_Key"));
+    assert!(!is_reserved_header("trace-id"));
+    assert!(matches!(
+        build(one("ce-type", "spoofed")),
+        Err(EventBuildError::ReservedHeader(name)) if name == "ce-type"
+    ));
+
+    for (name, value) in [
+        ("", "v"),
+        ("trace id", "v"),
+        ("trace:id", "v"),
+        ("trace-id", "a\r\nb"),
+    ] {
+        assert!(
+            matches!(
+                build(one(name, value)),
+                Err(EventBuildError::InvalidHeader(_))
+            ),
+            "{:?}: {:?}",
+            name,
+            value
+        );
+    }
+
+    let too_many = (0..=MAX_EVENT_HEADERS)
+        .map(|n| (format!("x-{}", n), "v".to_string()))
+        .collect();
+    assert!(matches!(
+        build(too_many),
+        Err(EventBuildError::InvalidHeader(_))
+    ));
+}
+
+#[test]
+fn test_headers_are_omitted_when_unset() {
+    let event = Event::new("t".into(), "p".into(), "x".into(), json!({}));
+    let serialized = serde_json::to_value(&event).unwrap();
+    assert!(serialized.get("headers").is_none());
+    let frame = serde_json::to_value(WebSocketMessage::from(&event)).unwrap();
+    assert!(frame.get("headers").is_none());
+
+    // Events stored before headers existed still deserialize
+    let restored: Event = serde_json::from_value(serialized).unwrap();
+    assert!(restored.headers.is_empty());
+}
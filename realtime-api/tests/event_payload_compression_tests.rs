@@ -0,0 +1,102 @@
+/// **Feature: realtime-saas-platform, Event payload compression at rest**
+///
+/// Payloads above the configured size are stored zstd-compressed and smaller
+/// ones as plain jsonb; either way reads return exactly what was published.
+use realtime_api::database::Database;
+use realtime_api::models::Event;
+use serde_json::json;
+use sqlx::Row;
+
+mod common;
+
+use common::{create_project, test_database, test_database_with};
+
+const COMPRESSION_THRESHOLD: usize = 256;
+
+async fn stored_compressed(database: &Database, event_id: &str) -> bool {
+    sqlx::query("SELECT payload_compressed FROM events WHERE id = $1")
+        .bind(event_id)
+        .fetch_one(database.pool())
+        .await
+        .expect("Event should be stored")
+        .get("payload_compressed")
+}
+
+#[tokio::test]
+async fn test_small_and_large_payloads_round_trip() {
+    let database = test_database_with(|config| {
+        config.payload_compression_threshold_bytes = COMPRESSION_THRESHOLD;
+    })
+    .await;
+    let (tenant, project) = create_project(&database, "Compression Tenant").await;
+
+    let small = Event::new(
+        tenant.id.clone(),
+        project.id.clone(),
+        "orders.created".to_string(),
+        json!({"order_id": "o-1"}),
+    );
+    let large = Event::new(
+        tenant.id.clone(),
+        project.id.clone(),
+        "orders.imported".to_string(),
+        json!({
+            "order_id": "o-2",
+            "lines": (0..100)
+                .map(|i| json!({"sku": format!("sku-{}", i), "quantity": i, "note": "ünïcode ✓"}))
+                .collect::<Vec<_>>(),
+        }),
+    );
+    assert!(serde_json::to_vec(&large.payload).unwrap().len() > COMPRESSION_THRESHOLD);
+
+    database.create_event(&small).await.expect("Failed to store small event");
+    database.create_event(&large).await.expect("Failed to store large event");
+
+    assert!(!stored_compressed(&database, &small.id).await);
+    assert!(stored_compressed(&database, &large.id).await);
+
+    for event in [&small, &large] {
+        let fetched = database
+            .get_event(&tenant.id, &event.id)
+            .await
+            .unwrap()
+            .expect("Event should be found");
+        assert_eq!(fetched.payload, event.payload);
+    }
+
+    let listed = database
+        .get_events_for_tenant(&tenant.id, 10)
+        .await
+        .expect("Failed to list events");
+    assert_eq!(listed.len(), 2);
+    for event in [&small, &large] {
+        let found = listed
+            .iter()
+            .find(|listed| listed.id == event.id)
+            .expect("Event should be listed");
+        assert_eq!(found.payload, event.payload);
+        assert_eq!(found.topic, event.topic);
+    }
+}
+
+#[tokio::test]
+async fn test_compression_disabled_stores_plain_jsonb() {
+    let database = test_database().await;
+    let (tenant, project) = create_project(&database, "Compression Tenant").await;
+
+    let event = Event::new(
+        tenant.id.clone(),
+        project.id.clone(),
+        "orders.imported".to_string(),
+        json!({"blob": "x".repeat(COMPRESSION_THRESHOLD * 4)}),
+    );
+    database.create_event(&event).await.expect("Failed to store event");
+
+    assert!(!stored_compressed(&database, &event.id).await);
+    let fetched = database
+        .get_event(&tenant.id, &event.id)
+        .await
+        .unwrap()
+        .expect("Event should be found");
+    assert_eq!(fetched.payload, event.payload);
+}
@@ -0,0 +1,79 @@
+/// **Feature: realtime-saas-platform, Graceful shutdown**
+///
+/// On shutdown every WebSocket and SSE connection receives a clean close with
+/// a reconnect hint and is removed, so it doesn't hold the server open.
+use realtime_api::shutdown::{
+    close_streaming_connections, on_shutdown_signal, SHUTDOWN_CLOSE_CODE, SHUTDOWN_CLOSE_REASON,
+};
+use realtime_api::sse::{sse_manager, SSEConnection, SSEMessage};
+use realtime_api::websocket::{websocket_manager, WebSocketConnection, WebSocketMessage};
+use tokio::sync::{broadcast, oneshot};
+use uuid::Uuid;
+
+fn open_websocket(tenant_id: &str) -> (String, broadcast::Receiver<WebSocketMessage>) {
+    let id = Uuid::new_v4().to_string();
+    let (sender, receiver) = broadcast::channel(8);
+    websocket_manager()
+        .add_connection(WebSocketConnection {
+            id: id.clone(),
+            tenant_id: tenant_id.to_string(),
+            project_id: "project".to_string(),
+            subscribed_topics: vec![],
+            sender,
+            created_at: chrono::Utc::now(),
+            acked: false,
+        })
+        .unwrap();
+    (id, receiver)
+}
+
+fn open_sse(tenant_id: &str) -> (String, broadcast::Receiver<SSEMessage>) {
+    let id = Uuid::new_v4().to_string();
+    let (sender, receiver) = broadcast::channel(8);
+    sse_manager()
+        .add_connection(SSEConnection {
+            id: id.clone(),
+            tenant_id: tenant_id.to_string(),
+            project_id: "project".to_string(),
+            subscribed_topics: vec![],
+            sender,
+            created_at: chrono::Utc::now(),
+            acked: false,
+        })
+        .unwrap();
+    (id, receiver)
+}
+
+#[tokio::test]
+async fn test_shutdown_closes_streaming_connections_cleanly() {
+    let tenant_id = Uuid::new_v4().to_string();
+    let (websocket_id, mut websocket) = open_websocket(&tenant_id);
+    let (sse_id, mut sse) = open_sse(&tenant_id);
+
+    let (started, started_rx) = oneshot::channel();
+    on_shutdown_signal(async {}, started).await;
+    started_rx.await.expect("Draining should be reported as started");
+
+    match websocket.recv().await.unwrap() {
+        WebSocketMessage::Close { code, reason } => {
+            assert_eq!(code, SHUTDOWN_CLOSE_CODE);
+            assert_eq!(reason, SHUTDOWN_CLOSE_REASON);
+        }
+        other => panic!("Expected a close frame, got {:?}", other),
+    }
+    match sse.recv().await.unwrap() {
+        SSEMessage::Close { code, reason } => {
+            assert_eq!(code, SHUTDOWN_CLOSE_CODE);
+            assert_eq!(reason, SHUTDOWN_CLOSE_REASON);
+        }
+        other => panic!("Expected a close event, got {:?}", other),
+    }
+
+    assert_eq!(websocket_manager().get_tenant_connection_count(&tenant_id), 0);
+    assert_eq!(sse_manager().get_tenant_connection_count(&tenant_id), 0);
+
+    // Closed connections aren't closed again by a repeated signal
+    let closed = close_streaming_connections(websocket_manager(), sse_manager());
+    assert!(!closed.contains(&websocket_id));
+    assert!(!closed.contains(&sse_id));
+}
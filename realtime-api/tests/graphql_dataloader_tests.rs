@@ -0,0 +1,119 @@
+/// **Feature: realtime-saas-platform, Batched GraphQL lookups**
+///
+/// Nested `tenants { projects { apiKeys } }` queries load each level with one
+/// batched database query instead of one query per parent.
+use async_graphql::Request;
+use realtime_api::auth::{AuthContext, AuthService, AuthType};
+use realtime_api::config::GraphQLConfig;
+use realtime_api::graphql::create_schema;
+use realtime_api::graphql_loaders::{loader_batches_counter, ApiKeysByProject, ProjectsByTenant};
+use realtime_api::models::{BillingPlan, Project, Scope, Tenant};
+
+mod common;
+
+use common::{test_database, test_event_service};
+
+fn batches(loader: &str) -> f64 {
+    loader_batches_counter().with_label_values(&[loader]).get()
+}
+
+#[tokio::test]
+async fn test_nested_query_batches_lookups() {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone()).await;
+
+    let tenant = Tenant::new("Loader Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    let mut key_ids = Vec::new();
+    for name in ["web", "mobile", "backend"] {
+        let project = Project::new(tenant.id.clone(), name.to_string());
+        database.create_project(&project).await.expect("Failed to create project");
+        for _ in 0..2 {
+            let (_, api_key) = AuthService::new_api_key(
+                tenant.id.clone(),
+                project.id.clone(),
+                vec![Scope::EventsPublish],
+                100,
+                None,
+            )
+            .expect("Failed to hash API key");
+            database.create_api_key(&api_key).await.expect("Failed to create API key");
+            key_ids.push(api_key.id);
+        }
+    }
+
+    let schema = create_schema(
+        database.clone(),
+        event_service,
+        AuthService::new(database.clone(), "test_secret".to_string()),
+        100,
+        &GraphQLConfig::default(),
+    );
+    let auth = AuthContext {
+        tenant_id: tenant.id.clone(),
+        project_id: String::new(),
+        scopes: vec![Scope::AdminRead],
+        rate_limit_per_sec: 100,
+        allowed_topics: vec![],
+        auth_type: AuthType::ApiKey { key_id: "loader_key".to_string() },
+        user_id: None,
+        user_role: None,
+    };
+
+    let projects_before = batches(ProjectsByTenant::NAME);
+    let api_keys_before = batches(ApiKeysByProject::NAME);
+
+    let query = "{ tenants { id projects { id apiKeys { id } } } }";
+    let response = schema.execute(Request::new(query).data(auth)).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let data = response.data.into_json().unwrap();
+    let projects = data["tenants"][0]["projects"].as_array().unwrap();
+    assert_eq!(projects.len(), 3);
+    let mut returned: Vec<String> = projects
+        .iter()
+        .flat_map(|project| project["apiKeys"].as_array().unwrap().clone())
+        .map(|key| key["id"].as_str().unwrap().to_string())
+        .collect();
+    returned.sort();
+    key_ids.sort();
+    assert_eq!(returned, key_ids);
+
+    // One query per level, however many parents
+    assert_eq!(batches(ProjectsByTenant::NAME) - projects_before, 1.0);
+    assert_eq!(batches(ApiKeysByProject::NAME) - api_keys_before, 1.0);
+}
+
+#[tokio::test]
+async fn test_nested_api_keys_require_admin_read() {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone()).await;
+
+    let tenant = Tenant::new("Loader Scope Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    database.create_project(&project).await.expect("Failed to create project");
+
+    let schema = create_schema(
+        database.clone(),
+        event_service,
+        AuthService::new(database.clone(), "test_secret".to_string()),
+        100,
+        &GraphQLConfig::default(),
+    );
+    let auth = AuthContext {
+        tenant_id: tenant.id.clone(),
+        project_id: project.id.clone(),
+        scopes: vec![Scope::EventsPublish],
+        rate_limit_per_sec: 100,
+        allowed_topics: vec![],
+        auth_type: AuthType::ApiKey { key_id: "publisher_key".to_string() },
+        user_id: None,
+        user_role: None,
+    };
+
+    let query = "{ projects { id apiKeys { id } } }";
+    let response = schema.execute(Request::new(query).data(auth)).await;
+    assert_eq!(response.errors.len(), 1);
+    assert_eq!(response.errors[0].message, "Forbidden");
+}
@@ -0,0 +1,113 @@
+/// **Feature: realtime-saas-platform, GraphQL list page sizes**
+///
+/// `apiKeys` and `usageRecords` return at most `limit` items, clamped to the
+/// configured maximum page size like the REST lists.
+use async_graphql::Request;
+use chrono::Utc;
+use realtime_api::auth::{AuthContext, AuthService, AuthType};
+use realtime_api::config::GraphQLConfig;
+use realtime_api::graphql::{create_schema, ApiSchema};
+use realtime_api::models::{Project, Scope, UsageMetric};
+
+mod common;
+
+use common::{create_project, test_database, test_event_service};
+
+const MAX_PAGE_SIZE: i64 = 3;
+
+async fn setup() -> (ApiSchema, Project) {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone()).await;
+    let (_, project) = create_project(&database, "GraphQL Page Size Tenant").await;
+
+    for hour in 0..5 {
+        let (_, api_key) = AuthService::new_api_key(
+            project.tenant_id.clone(),
+            project.id.clone(),
+            vec![Scope::EventsPublish],
+            100,
+            None,
+        )
+        .expect("Failed to hash API key");
+        database
+            .create_api_key(&api_key)
+            .await
+            .expect("Failed to create API key");
+        database
+            .increment_usage(
+                &project.tenant_id,
+                &project.id,
+                UsageMetric::EventsPublished,
+                1,
+                Utc::now() - chrono::Duration::hours(hour),
+                None,
+            )
+            .await
+            .expect("Failed to increment usage");
+    }
+
+    let schema = create_schema(
+        database.clone(),
+        event_service,
+        AuthService::new(database, "test_secret".to_string()),
+        MAX_PAGE_SIZE,
+        &GraphQLConfig::default(),
+    );
+    (schema, project)
+}
+
+fn auth(project: &Project) -> AuthContext {
+    AuthContext {
+        tenant_id: project.tenant_id.clone(),
+        project_id: project.id.clone(),
+        scopes: vec![Scope::AdminRead, Scope::BillingRead],
+        rate_limit_per_sec: 100,
+        allowed_topics: vec![],
+        auth_type: AuthType::ApiKey {
+            key_id: "reader_key".to_string(),
+        },
+        user_id: None,
+        user_role: None,
+    }
+}
+
+async fn count(schema: &ApiSchema, project: &Project, field: &str, limit: Option<i32>) -> usize {
+    let arguments = match limit {
+        Some(limit) => format!(r#"projectId: "{}", limit: {}"#, project.id, limit),
+        None => format!(r#"projectId: "{}""#, project.id),
+    };
+    let query = format!("{{ {}({}) {{ id }} }}", field, arguments);
+    let response = schema
+        .execute(Request::new(query).data(auth(project)))
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let data = response.data.into_json().unwrap();
+    data[field].as_array().unwrap().len()
+}
+
+#[tokio::test]
+async fn test_lists_are_clamped_to_the_maximum_page_size() {
+    let (schema, project) = setup().await;
+
+    for field in ["apiKeys", "usageRecords"] {
+        assert_eq!(
+            count(&schema, &project, field, Some(2)).await,
+            2,
+            "{}",
+            field
+        );
+        assert_eq!(
+            count(&schema, &project, field, Some(1000)).await,
+            MAX_PAGE_SIZE as usize,
+            "{}",
+            field
+        );
+        assert_eq!(
+            count(&schema, &project, field, None).await,
+            MAX_PAGE_SIZE as usize,
+            "{}",
+            field
+        );
+    }
+}
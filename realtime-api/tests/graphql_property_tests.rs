@@ -32,6 +32,7 @@ fn test_graphql_query_tenant_isolation() {
             project_id: "test_project".to_string(),
             scopes: vec![Scope::EventsSubscribe, Scope::AdminRead],
             rate_limit_per_sec: 100,
+            allowed_topics: vec![],
             auth_type: AuthType::ApiKey { key_id: "test_key".to_string() },
             user_id: None,
             user_role: None,
@@ -76,6 +77,7 @@ fn test_graphql_mutation_authentication() {
             project_id: project_id.clone(),
             scopes: scopes.clone(),
             rate_limit_per_sec: 100,
+            allowed_topics: vec![],
             auth_type: AuthType::ApiKey { key_id: "test_key".to_string() },
             user_id: None,
             user_role: None,
@@ -112,6 +114,7 @@ fn test_graphql_subscription_delivery() {
             project_id: project_id.clone(),
             scopes: vec![Scope::EventsSubscribe],
             rate_limit_per_sec: 100,
+            allowed_topics: vec![],
             auth_type: AuthType::ApiKey { key_id: "test_key".to_string() },
             user_id: None,
             user_role: None,
@@ -147,6 +150,7 @@ mod tests {
             project_id: "test_project".to_string(),
             scopes: vec![Scope::EventsPublish, Scope::EventsSubscribe],
             rate_limit_per_sec: 100,
+            allowed_topics: vec![],
             auth_type: AuthType::ApiKey {
                 key_id: "test_key".to_string(),
             },
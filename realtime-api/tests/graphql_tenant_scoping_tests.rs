@@ -0,0 +1,63 @@
+/// **Feature: realtime-saas-platform, GraphQL project tenant scoping**
+///
+/// Project-scoped GraphQL operations must never expose another tenant's project,
+/// even when the caller supplies its id directly.
+use async_graphql::Request;
+use realtime_api::auth::{AuthContext, AuthService, AuthType};
+use realtime_api::config::GraphQLConfig;
+use realtime_api::graphql::create_schema;
+use realtime_api::models::{BillingPlan, Project, Scope, Tenant};
+
+mod common;
+
+use common::{test_database, test_event_service};
+
+#[tokio::test]
+async fn test_api_keys_query_rejects_other_tenants_project() {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone()).await;
+
+    let owner = Tenant::new("Owner Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let owner_project = Project::new(owner.id.clone(), "default".to_string());
+    let intruder = Tenant::new("Intruder Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let intruder_project = Project::new(intruder.id.clone(), "default".to_string());
+    for (tenant, project) in [(&owner, &owner_project), (&intruder, &intruder_project)] {
+        database.create_tenant(tenant).await.expect("Failed to create tenant");
+        database.create_project(project).await.expect("Failed to create project");
+    }
+
+    let (_, owner_key) = AuthService::new_api_key(
+        owner.id.clone(),
+        owner_project.id.clone(),
+        vec![Scope::EventsPublish],
+        100,
+        None,
+    )
+    .expect("Failed to hash API key");
+    database.create_api_key(&owner_key).await.expect("Failed to create API key");
+
+    let schema = create_schema(
+        database.clone(),
+        event_service,
+        AuthService::new(database.clone(), "test_secret".to_string()),
+        100,
+        &GraphQLConfig::default(),
+    );
+    let intruder_auth = AuthContext {
+        tenant_id: intruder.id.clone(),
+        project_id: intruder_project.id.clone(),
+        scopes: vec![Scope::AdminRead],
+        rate_limit_per_sec: 100,
+        allowed_topics: vec![],
+        auth_type: AuthType::ApiKey { key_id: "intruder_key".to_string() },
+        user_id: None,
+        user_role: None,
+    };
+
+    let query = format!(r#"{{ apiKeys(projectId: "{}") {{ id }} }}"#, owner_project.id);
+    let response = schema.execute(Request::new(query).data(intruder_auth)).await;
+
+    assert_eq!(response.errors.len(), 1);
+    assert_eq!(response.errors[0].message, "Forbidden");
+    assert!(!response.data.to_string().contains(&owner_key.id));
+}
@@ -0,0 +1,276 @@
+/// **Feature: realtime-saas-platform, GraphQL subscriptions over WebSocket**
+///
+/// `/graphql/ws` speaks `graphql-transport-ws`: the client authenticates in
+/// `connection_init`, is acknowledged, and receives `eventStream` results as
+/// `next` messages. A bad credential closes the socket with `4401` and a client
+/// that never initialises is closed with `4408`. The project's subscription
+/// cap and idle timeout apply as on `/ws`.
+use axum::extract::ws::Message;
+use futures_util::sink;
+use realtime_api::api::AppState;
+use realtime_api::auth::AuthService;
+use realtime_api::config::GraphQLConfig;
+use realtime_api::database::Database;
+use realtime_api::event_service::{EventService, PublishResult};
+use realtime_api::graphql::{create_schema, ApiSchema};
+use realtime_api::graphql_ws::{serve_graphql_ws, INIT_TIMEOUT_CLOSE_CODE, SUBSCRIPTION_LIMIT_CODE};
+use realtime_api::idle::IDLE_CLOSE_CODE;
+use realtime_api::models::{Event, Project, Scope};
+use realtime_api::websocket::UNAUTHORIZED_CLOSE_CODE;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+mod common;
+
+use common::{create_project, test_state};
+
+const INIT_TIMEOUT: Duration = Duration::from_secs(2);
+
+struct Setup {
+    schema: ApiSchema,
+    auth_service: AuthService,
+    database: Database,
+    event_service: EventService,
+    project: Project,
+    raw_key: String,
+}
+
+async fn setup() -> Setup {
+    let AppState {
+        database,
+        event_service,
+        auth_service,
+        ..
+    } = test_state().await;
+    let (tenant, project) = create_project(&database, "GraphQL WS Tenant").await;
+
+    let (raw_key, _) = auth_service
+        .create_api_key(
+            tenant.id.clone(),
+            project.id.clone(),
+            vec![Scope::EventsSubscribe],
+            100,
+            vec![],
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+
+    let schema = create_schema(
+        database.clone(),
+        event_service.clone(),
+        auth_service.clone(),
+        100,
+        &GraphQLConfig::default(),
+    );
+    Setup {
+        schema,
+        auth_service,
+        database,
+        event_service,
+        project,
+        raw_key,
+    }
+}
+
+/// Client ends of a served connection: frames to send, and frames received
+fn connect(setup: &Setup) -> (mpsc::UnboundedSender<Message>, mpsc::UnboundedReceiver<Message>) {
+    let (client, incoming) = mpsc::unbounded_channel();
+    let (outgoing, server) = mpsc::unbounded_channel();
+    let outgoing = sink::unfold(outgoing, |outgoing, message: Message| async move {
+        outgoing.send(message).map(|()| outgoing)
+    });
+    tokio::spawn(serve_graphql_ws(
+        setup.schema.clone(),
+        setup.auth_service.clone(),
+        setup.database.clone(),
+        INIT_TIMEOUT,
+        UnboundedReceiverStream::new(incoming),
+        outgoing,
+    ));
+    (client, server)
+}
+
+/// Connect and complete the `connection_init` handshake
+async fn connect_acknowledged(setup: &Setup) -> (mpsc::UnboundedSender<Message>, mpsc::UnboundedReceiver<Message>) {
+    let (client, mut server) = connect(setup);
+    send(
+        &client,
+        json!({"type": "connection_init", "payload": {"authorization": format!("Bearer {}", setup.raw_key)}}),
+    );
+    let ack = receive(&mut server, Duration::from_secs(5)).await.expect("No connection_ack");
+    assert_eq!(json_frame(ack), json!({"type": "connection_ack"}));
+    (client, server)
+}
+
+async fn update_limits(setup: &Setup, max_subscriptions: i32, idle_timeout_secs: i32) {
+    let mut limits = setup.project.limits.clone();
+    limits.max_subscriptions_per_connection = max_subscriptions;
+    limits.idle_timeout_secs = idle_timeout_secs;
+    setup
+        .database
+        .update_project_limits(&setup.project.tenant_id, &setup.project.id, &limits)
+        .await
+        .expect("Failed to update project limits");
+}
+
+fn subscribe(client: &mpsc::UnboundedSender<Message>, id: &str) {
+    send(
+        client,
+        json!({
+            "type": "subscribe",
+            "id": id,
+            "payload": {"query": r#"subscription { eventStream(topics: ["orders.created"]) { id } }"#}
+        }),
+    );
+}
+
+fn send(client: &mpsc::UnboundedSender<Message>, message: Value) {
+    client
+        .send(Message::Text(message.to_string()))
+        .expect("Connection should still be open");
+}
+
+async fn receive(server: &mut mpsc::UnboundedReceiver<Message>, wait: Duration) -> Option<Message> {
+    tokio::time::timeout(wait, server.recv()).await.ok().flatten()
+}
+
+fn json_frame(message: Message) -> Value {
+    match message {
+        Message::Text(text) => serde_json::from_str(&text).unwrap(),
+        other => panic!("Expected a text frame, got {:?}", other),
+    }
+}
+
+fn close_code(message: Option<Message>) -> u16 {
+    match message {
+        Some(Message::Close(Some(frame))) => frame.code,
+        other => panic!("Expected a close frame, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_handshake_and_subscription_receive_events() {
+    let setup = setup().await;
+    let (client, mut server) = connect(&setup);
+
+    send(
+        &client,
+        json!({"type": "connection_init", "payload": {"authorization": format!("Bearer {}", setup.raw_key)}}),
+    );
+    let ack = receive(&mut server, Duration::from_secs(5)).await.expect("No connection_ack");
+    assert_eq!(json_frame(ack), json!({"type": "connection_ack"}));
+
+    send(&client, json!({"type": "ping"}));
+    let pong = receive(&mut server, Duration::from_secs(5)).await.expect("No pong");
+    assert_eq!(json_frame(pong), json!({"type": "pong"}));
+
+    send(
+        &client,
+        json!({
+            "type": "subscribe",
+            "id": "orders",
+            "payload": {"query": r#"subscription { eventStream(topics: ["orders.created"]) { id topic } }"#}
+        }),
+    );
+
+    // The subscription starts in the background, so publish until one arrives
+    let mut published = Vec::new();
+    let mut next = None;
+    for _ in 0..50 {
+        let event = Event::new(
+            setup.project.tenant_id.clone(),
+            setup.project.id.clone(),
+            "orders.created".to_string(),
+            json!({"order_id": published.len()}),
+        );
+        let result = setup.event_service.publish_event(&event).await.expect("Failed to publish");
+        assert!(matches!(result, PublishResult::Success { .. }), "{:?}", result);
+        published.push(event.id);
+
+        if let Some(message) = receive(&mut server, Duration::from_millis(100)).await {
+            next = Some(json_frame(message));
+            break;
+        }
+    }
+
+    let next = next.expect("No event reached the subscription");
+    assert_eq!(next["type"], "next");
+    assert_eq!(next["id"], "orders");
+    let event = &next["payload"]["data"]["eventStream"];
+    assert_eq!(event["topic"], "orders.created");
+    assert!(published.iter().any(|id| event["id"] == id.as_str()), "{}", next);
+
+    send(&client, json!({"type": "complete", "id": "orders"}));
+}
+
+#[tokio::test]
+async fn test_invalid_credential_closes_with_4401() {
+    let setup = setup().await;
+    let (client, mut server) = connect(&setup);
+
+    send(
+        &client,
+        json!({"type": "connection_init", "payload": {"authorization": "Bearer rtk_not_a_real_key"}}),
+    );
+    let close = receive(&mut server, Duration::from_secs(5)).await;
+    assert_eq!(close_code(close), UNAUTHORIZED_CLOSE_CODE);
+}
+
+#[tokio::test]
+async fn test_connection_without_init_times_out() {
+    let setup = setup().await;
+    let (_client, mut server) = connect(&setup);
+
+    let close = receive(&mut server, INIT_TIMEOUT + Duration::from_secs(2)).await;
+    assert_eq!(close_code(close), INIT_TIMEOUT_CLOSE_CODE);
+}
+
+#[tokio::test]
+async fn test_subscribe_over_the_project_cap_is_rejected() {
+    let setup = setup().await;
+    update_limits(&setup, 1, 300).await;
+    let (client, mut server) = connect_acknowledged(&setup).await;
+
+    subscribe(&client, "first");
+    subscribe(&client, "second");
+    let error = receive(&mut server, Duration::from_secs(5)).await.expect("No error for the second subscribe");
+    let error = json_frame(error);
+    assert_eq!(error["type"], "error");
+    assert_eq!(error["id"], "second");
+    assert_eq!(error["payload"][0]["extensions"]["code"], SUBSCRIPTION_LIMIT_CODE);
+
+    // Completing the running subscription frees its slot
+    send(&client, json!({"type": "complete", "id": "first"}));
+    subscribe(&client, "third");
+    assert!(receive(&mut server, Duration::from_millis(500)).await.is_none());
+}
+
+#[tokio::test]
+async fn test_server_pings_and_closes_idle_connections() {
+    let setup = setup().await;
+    update_limits(&setup, 100, 2).await;
+    let (client, mut server) = connect_acknowledged(&setup).await;
+
+    // Answering each ping keeps the connection open past the idle timeout
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    let mut pings = 0;
+    while let Ok(message) = tokio::time::timeout_at(deadline, server.recv()).await {
+        let message = json_frame(message.expect("Connection should stay open"));
+        assert_eq!(message["type"], "ping", "{}", message);
+        pings += 1;
+        send(&client, json!({"type": "pong"}));
+    }
+    assert!(pings >= 2, "Expected pings every half idle timeout, got {}", pings);
+
+    // Leaving the pings unanswered closes it
+    let close = loop {
+        match receive(&mut server, Duration::from_secs(5)).await {
+            Some(Message::Text(_)) => continue,
+            other => break other,
+        }
+    };
+    assert_eq!(close_code(close), IDLE_CLOSE_CODE);
+}
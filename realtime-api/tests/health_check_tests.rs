@@ -0,0 +1,44 @@
+/// **Feature: realtime-saas-platform, Health checks**
+///
+/// `/health` and `/health/ready` report whether the database and NATS are
+/// reachable; `/health/live` only reports that the process is up.
+use axum::extract::State;
+use axum::http::StatusCode;
+use realtime_api::api::{health_check, liveness_check, readiness_check, DependencyHealth};
+
+mod common;
+
+use common::test_state;
+
+#[tokio::test]
+async fn test_healthy_dependencies_report_ok() {
+    let state = test_state().await;
+    let expected = DependencyHealth {
+        database: "ok".to_string(),
+        nats: "ok".to_string(),
+    };
+
+    let (status, health) = health_check(State(state.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(health.0, expected);
+
+    let (status, health) = readiness_check(State(state)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        serde_json::to_value(&health.0).unwrap(),
+        serde_json::json!({ "database": "ok", "nats": "ok" })
+    );
+}
+
+#[tokio::test]
+async fn test_unreachable_database_fails_readiness_only() {
+    let state = test_state().await;
+    state.database.pool().close().await;
+
+    let (status, health) = readiness_check(State(state)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(health.database, "unavailable");
+    assert_eq!(health.nats, "ok");
+
+    assert_eq!(liveness_check().await.0["status"], "ok");
+}
@@ -0,0 +1,184 @@
+/// **Feature: realtime-saas-platform, Idempotent event publishing**
+///
+/// Publishing twice with the same idempotency key returns the first event's id and
+/// sequence instead of publishing again. Keys are scoped per tenant/project, so
+/// another tenant may reuse the same string. A claim whose publish never
+/// finished stops blocking the key once its lease runs out.
+use realtime_api::event_service::{PublishResult, DEFAULT_IDEMPOTENCY_CLAIM_LEASE};
+use realtime_api::models::{Event, Project, Tenant};
+use serde_json::json;
+use std::time::Duration;
+
+mod common;
+
+use common::{create_project, test_database, test_event_service};
+
+fn order_event(tenant: &Tenant, project: &Project) -> Event {
+    Event::new(
+        tenant.id.clone(),
+        project.id.clone(),
+        "orders.created".to_string(),
+        json!({"order_id": "o-1"}),
+    )
+}
+
+#[tokio::test]
+async fn test_same_key_twice_returns_original_event() {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone()).await;
+    let (tenant, project) = create_project(&database, "Idempotency Tenant").await;
+
+    let first = order_event(&tenant, &project);
+    let sequence = match event_service
+        .publish_event_idempotent(&first, None, Some("order-o-1"))
+        .await
+        .unwrap()
+    {
+        PublishResult::Success { sequence } => sequence,
+        other => panic!("Expected first publish to succeed, got {:?}", other),
+    };
+
+    // The retry carries a fresh event id, but the original is returned
+    let retry = order_event(&tenant, &project);
+    match event_service
+        .publish_event_idempotent(&retry, None, Some("order-o-1"))
+        .await
+        .unwrap()
+    {
+        PublishResult::Replayed {
+            event_id,
+            sequence: replayed_sequence,
+        } => {
+            assert_eq!(event_id, first.id);
+            assert_eq!(replayed_sequence, sequence);
+        }
+        other => panic!("Expected the original event back, got {:?}", other),
+    }
+    let stored = database.get_events_for_tenant(&tenant.id, 10).await.unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].id, first.id);
+
+    // A different key publishes normally
+    let other = order_event(&tenant, &project);
+    assert!(matches!(
+        event_service
+            .publish_event_idempotent(&other, None, Some("order-o-2"))
+            .await
+            .unwrap(),
+        PublishResult::Success { .. }
+    ));
+}
+
+#[tokio::test]
+async fn test_keys_are_scoped_per_tenant() {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone()).await;
+    let (tenant_a, project_a) = create_project(&database, "Idempotency Tenant").await;
+    let (tenant_b, project_b) = create_project(&database, "Idempotency Tenant").await;
+
+    for (tenant, project) in [(&tenant_a, &project_a), (&tenant_b, &project_b)] {
+        let event = order_event(tenant, project);
+        assert!(matches!(
+            event_service
+                .publish_event_idempotent(&event, None, Some("shared-key"))
+                .await
+                .unwrap(),
+            PublishResult::Success { .. }
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_invalid_key_is_rejected() {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone()).await;
+    let (tenant, project) = create_project(&database, "Idempotency Tenant").await;
+
+    let event = order_event(&tenant, &project);
+    let long_key = "k".repeat(256);
+    for key in ["", "has space", long_key.as_str()] {
+        assert!(matches!(
+            event_service
+                .publish_event_idempotent(&event, None, Some(key))
+                .await
+                .unwrap(),
+            PublishResult::ValidationFailed(_)
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_abandoned_claim_expires_after_its_lease() {
+    let database = test_database().await;
+    let (tenant, project) = create_project(&database, "Idempotency Tenant").await;
+    let ttl = Duration::from_secs(3600);
+    let claim = |event_id: &'static str, lease: Duration| {
+        let database = database.clone();
+        let (tenant_id, project_id) = (tenant.id.clone(), project.id.clone());
+        async move {
+            database
+                .claim_idempotency_key(&tenant_id, &project_id, "order-o-1", event_id, ttl, lease)
+                .await
+                .unwrap()
+        }
+    };
+
+    assert!(claim("event-1", DEFAULT_IDEMPOTENCY_CLAIM_LEASE)
+        .await
+        .is_none());
+
+    // Still in flight within the lease
+    let existing = claim("event-2", DEFAULT_IDEMPOTENCY_CLAIM_LEASE)
+        .await
+        .expect("Key should still be claimed");
+    assert_eq!(existing.event_id, "event-1");
+    assert_eq!(existing.sequence, None);
+
+    // Taken over once the lease has run out
+    assert!(claim("event-3", Duration::ZERO).await.is_none());
+}
+
+#[tokio::test]
+async fn test_retry_after_lost_outcome_returns_original_event() {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone()).await;
+    let (tenant, project) = create_project(&database, "Idempotency Tenant").await;
+
+    let first = order_event(&tenant, &project);
+    let sequence = match event_service
+        .publish_event_idempotent(&first, None, Some("order-o-1"))
+        .await
+        .unwrap()
+    {
+        PublishResult::Success { sequence } => sequence,
+        other => panic!("Expected first publish to succeed, got {:?}", other),
+    };
+
+    // The instance crashed after JetStream stored the event but before it
+    // recorded the outcome, and the claim's lease has since run out
+    sqlx::query(
+        "UPDATE idempotency_keys SET sequence = NULL, created_at = NOW() - INTERVAL '1 hour' WHERE tenant_id = $1",
+    )
+    .bind(&tenant.id)
+    .execute(database.pool())
+    .await
+    .unwrap();
+
+    for _ in 0..2 {
+        let retry = order_event(&tenant, &project);
+        match event_service
+            .publish_event_idempotent(&retry, None, Some("order-o-1"))
+            .await
+            .unwrap()
+        {
+            PublishResult::Replayed {
+                event_id,
+                sequence: replayed_sequence,
+            } => {
+                assert_eq!(event_id, first.id);
+                assert_eq!(replayed_sequence, sequence);
+            }
+            other => panic!("Expected the original event back, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+/// **Feature: realtime-saas-platform, Connection idle timeout**
+///
+/// SSE connections that flush nothing to the client within the project's idle
+/// timeout are closed with `idle_timeout` and removed from the manager; live
+/// connections get heartbeats instead.
+use realtime_api::idle::{
+    ActivityTracker, DEFAULT_IDLE_TIMEOUT_SECS, IDLE_CLOSE_CODE, IDLE_CLOSE_REASON,
+};
+use realtime_api::models::{ProjectLimits, ProjectLimitsUpdate};
+use realtime_api::sse::{spawn_idle_watchdog, sse_manager, SSEConnection, SSEMessage};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+fn open_sse(
+    tenant_id: &str,
+) -> (
+    String,
+    broadcast::Sender<SSEMessage>,
+    broadcast::Receiver<SSEMessage>,
+) {
+    let id = Uuid::new_v4().to_string();
+    let (sender, receiver) = broadcast::channel(8);
+    sse_manager()
+        .add_connection(SSEConnection {
+            id: id.clone(),
+            tenant_id: tenant_id.to_string(),
+            project_id: "project".to_string(),
+            subscribed_topics: vec![],
+            sender: sender.clone(),
+            created_at: chrono::Utc::now(),
+            acked: false,
+        })
+        .unwrap();
+    (id, sender, receiver)
+}
+
+#[tokio::test]
+async fn test_idle_sse_connection_is_reaped() {
+    let tenant_id = Uuid::new_v4().to_string();
+    let (id, sender, mut receiver) = open_sse(&tenant_id);
+
+    // Nothing touches the tracker, as when the client stops reading
+    let watchdog = spawn_idle_watchdog(
+        id,
+        sender,
+        ActivityTracker::new(),
+        Duration::from_millis(200),
+    );
+
+    let close = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match receiver.recv().await.unwrap() {
+                SSEMessage::Heartbeat { .. } => continue,
+                SSEMessage::Close { code, reason } => return (code, reason),
+                other => panic!("Unexpected message {:?}", other),
+            }
+        }
+    })
+    .await
+    .expect("Idle connection should be closed");
+    assert_eq!(close, (IDLE_CLOSE_CODE, IDLE_CLOSE_REASON.to_string()));
+
+    watchdog.await.unwrap();
+    assert_eq!(sse_manager().get_tenant_connection_count(&tenant_id), 0);
+}
+
+#[tokio::test]
+async fn test_active_sse_connection_gets_heartbeats() {
+    let tenant_id = Uuid::new_v4().to_string();
+    let (id, sender, mut receiver) = open_sse(&tenant_id);
+    let activity = ActivityTracker::new();
+    let watchdog = spawn_idle_watchdog(
+        id.clone(),
+        sender,
+        activity.clone(),
+        Duration::from_millis(200),
+    );
+
+    // Each heartbeat is flushed, as the stream would on every frame
+    for _ in 0..4 {
+        match receiver.recv().await.unwrap() {
+            SSEMessage::Heartbeat { .. } => activity.touch(),
+            other => panic!("Expected a heartbeat, got {:?}", other),
+        }
+    }
+    assert_eq!(sse_manager().get_tenant_connection_count(&tenant_id), 1);
+
+    // Closing the stream stops the watchdog
+    drop(receiver);
+    tokio::time::timeout(Duration::from_secs(5), watchdog)
+        .await
+        .expect("Watchdog should stop with the stream")
+        .unwrap();
+    sse_manager().remove_connection(&id);
+}
+
+#[test]
+fn test_idle_timeout_is_a_project_limit() {
+    assert_eq!(
+        ProjectLimits::default().idle_timeout_secs,
+        DEFAULT_IDLE_TIMEOUT_SECS
+    );
+
+    // Limits stored before the setting existed get the default
+    let stored: ProjectLimits = serde_json::from_value(serde_json::json!({
+        "max_connections": 100,
+        "max_events_per_sec": 10,
+        "max_payload_size": 1024
+    }))
+    .unwrap();
+    assert_eq!(stored.idle_timeout_secs, DEFAULT_IDLE_TIMEOUT_SECS);
+
+    let updated = ProjectLimitsUpdate {
+        idle_timeout_secs: Some(60),
+        ..ProjectLimitsUpdate::default()
+    }
+    .apply_to(&stored);
+    assert_eq!(updated.idle_timeout_secs, 60);
+    assert_eq!(updated.max_connections, 100);
+
+    let mut invalid = updated;
+    invalid.idle_timeout_secs = 0;
+    assert!(invalid.validate().is_err());
+}
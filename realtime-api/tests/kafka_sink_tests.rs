@@ -0,0 +1,150 @@
+/// **Feature: realtime-saas-platform, Kafka sink**
+///
+/// With the Kafka sink enabled, every event JetStream acknowledges is also
+/// written to the Kafka topic derived from its tenant, project and topic,
+/// keyed by its partition key so Kafka keeps the same ordering.
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::{Extension, Json};
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::message::Headers;
+use rdkafka::Message;
+use realtime_api::api::{publish_event, AppState, PublishEventRequest};
+use realtime_api::auth::{AuthContext, AuthType};
+use realtime_api::config::KafkaConfig;
+use realtime_api::kafka_sink::{kafka_topic, KafkaSink};
+use realtime_api::models::{BillingPlan, Event, Project, Scope, Tenant};
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::Duration;
+use testcontainers::clients::Cli;
+use testcontainers_modules::kafka::{Kafka, KAFKA_PORT};
+
+mod common;
+
+use common::{app_state, test_database, test_event_service};
+
+async fn test_state(kafka: &KafkaConfig) -> AppState {
+    let database = test_database().await;
+    let sink = KafkaSink::from_config(kafka).expect("Failed to create Kafka producer");
+    let event_service = test_event_service(database.clone())
+        .await
+        .with_kafka_sink(sink);
+    app_state(database, event_service)
+}
+
+#[tokio::test]
+async fn test_event_published_via_rest_lands_in_kafka() {
+    let docker = Cli::default();
+    let kafka = docker.run(Kafka::default());
+    let brokers = format!("127.0.0.1:{}", kafka.get_host_port_ipv4(KAFKA_PORT));
+    let config = KafkaConfig {
+        enabled: true,
+        brokers: brokers.clone(),
+        topic_template: "realtime.{tenant_id}.{project_id}".to_string(),
+        ..Default::default()
+    };
+    let state = test_state(&config).await;
+
+    let tenant = Tenant::new(
+        "Kafka Sink Tenant".to_string(),
+        BillingPlan::Free {
+            monthly_events: 10000,
+        },
+    );
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    state
+        .database
+        .create_tenant(&tenant)
+        .await
+        .expect("Failed to create tenant");
+    state
+        .database
+        .create_project(&project)
+        .await
+        .expect("Failed to create project");
+    let auth = AuthContext {
+        tenant_id: tenant.id.clone(),
+        project_id: project.id.clone(),
+        scopes: vec![Scope::EventsPublish],
+        rate_limit_per_sec: 100,
+        allowed_topics: vec![],
+        auth_type: AuthType::ApiKey {
+            key_id: "key_123".to_string(),
+        },
+        user_id: None,
+        user_role: None,
+    };
+
+    let request: PublishEventRequest = serde_json::from_value(json!({
+        "topic": "orders.created",
+        "payload": {"order_id": 42},
+        "partition_key": "customer-7",
+        "headers": {"trace-id": "4bf92f3577b34da6"}
+    }))
+    .unwrap();
+    let Json(published) = publish_event(
+        State(state.clone()),
+        Extension(auth),
+        HeaderMap::new(),
+        Json(request),
+    )
+    .await
+    .expect("Event should be published");
+
+    let consumer: StreamConsumer = ClientConfig::new()
+        .set("bootstrap.servers", &brokers)
+        .set("group.id", "kafka-sink-tests")
+        .set("auto.offset.reset", "earliest")
+        .set("allow.auto.create.topics", "true")
+        .create()
+        .expect("Failed to create Kafka consumer");
+    let kafka_topic = kafka_topic(
+        &config.topic_template,
+        &Event::new(
+            tenant.id.clone(),
+            project.id.clone(),
+            "orders.created".to_string(),
+            json!({}),
+        ),
+    );
+    consumer
+        .subscribe(&[kafka_topic.as_str()])
+        .expect("Failed to subscribe");
+
+    let message = tokio::time::timeout(Duration::from_secs(30), async {
+        loop {
+            // The topic may not exist yet when the consumer first polls
+            if let Ok(message) = consumer.recv().await {
+                return message.detach();
+            }
+        }
+    })
+    .await
+    .expect("Timed out waiting for the event in Kafka");
+
+    assert_eq!(message.key(), Some("customer-7".as_bytes()));
+    let event: Event = serde_json::from_slice(message.payload().expect("Message has a payload"))
+        .expect("Payload should be the native event");
+    assert_eq!(event.id, published.event_id);
+    assert_eq!(event.topic, "orders.created");
+    assert_eq!(event.payload, json!({"order_id": 42}));
+
+    let headers: HashMap<String, String> = message
+        .headers()
+        .expect("Message should carry headers")
+        .iter()
+        .map(|header| {
+            let value = header
+                .value
+                .map(String::from_utf8_lossy)
+                .unwrap_or_default();
+            (header.key.to_string(), value.into_owned())
+        })
+        .collect();
+    assert_eq!(headers["event_id"], published.event_id);
+    assert_eq!(headers["tenant_id"], tenant.id);
+    assert_eq!(headers["topic"], "orders.created");
+    assert_eq!(headers["trace-id"], "4bf92f3577b34da6");
+}
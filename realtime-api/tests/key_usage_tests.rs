@@ -0,0 +1,78 @@
+/// **Feature: realtime-saas-platform, Per-key usage accounting**
+///
+/// Traffic from different API keys in the same project must accrue separately so
+/// operators can attribute usage to a specific key.
+use realtime_api::auth::AuthService;
+use realtime_api::event_service::PublishResult;
+use realtime_api::models::{BillingPlan, Event, Project, Scope, Tenant, UsageMetric};
+use serde_json::json;
+
+mod common;
+
+use common::{test_database, test_event_service};
+
+#[tokio::test]
+async fn test_usage_accrues_separately_per_key() {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone()).await;
+
+    let tenant = Tenant::new("Key Usage Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    database.create_project(&project).await.expect("Failed to create project");
+
+    let mut key_ids = Vec::new();
+    for _ in 0..2 {
+        let (_, api_key) = AuthService::new_api_key(
+            tenant.id.clone(),
+            project.id.clone(),
+            vec![Scope::EventsPublish],
+            100,
+            None,
+        )
+        .expect("Failed to hash API key");
+        database.create_api_key(&api_key).await.expect("Failed to create API key");
+        key_ids.push(api_key.id);
+    }
+
+    // Key A publishes three events, key B publishes one
+    for (key_id, count) in [(&key_ids[0], 3), (&key_ids[1], 1)] {
+        for i in 0..count {
+            let event = Event::new(
+                tenant.id.clone(),
+                project.id.clone(),
+                "usage.test".to_string(),
+                json!({ "n": i, "key": key_id }),
+            );
+            let result = event_service
+                .publish_event_with_key(&event, Some(key_id))
+                .await
+                .expect("Publish should succeed");
+            assert!(matches!(result, PublishResult::Success { .. }));
+        }
+    }
+
+    let key_a = database
+        .get_usage_for_key(&tenant.id, &key_ids[0], UsageMetric::EventsPublished)
+        .await
+        .unwrap();
+    let key_b = database
+        .get_usage_for_key(&tenant.id, &key_ids[1], UsageMetric::EventsPublished)
+        .await
+        .unwrap();
+    let total = database
+        .get_usage_for_tenant(&tenant.id, UsageMetric::EventsPublished)
+        .await
+        .unwrap();
+
+    assert_eq!(key_a, 3);
+    assert_eq!(key_b, 1);
+    assert_eq!(total, 4);
+
+    // Another tenant cannot read this key's usage
+    let other_tenant = database
+        .get_usage_for_key("other-tenant", &key_ids[0], UsageMetric::EventsPublished)
+        .await
+        .unwrap();
+    assert_eq!(other_tenant, 0);
+}
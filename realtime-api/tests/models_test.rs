@@ -175,3 +175,151 @@ mod model_tests {
         println!("✅ Usage tracking logic test passed");
     }
 }
+
+#[cfg(test)]
+mod event_builder_tests {
+    use realtime_api::models::{Event, EventBuildError, DEFAULT_MAX_PAYLOAD_SIZE};
+    use serde_json::json;
+
+    #[test]
+    fn test_builder_accepts_valid_input() {
+        let event = Event::builder()
+            .tenant_id("tenant_123")
+            .project_id("project_456")
+            .topic("user.created")
+            .payload(json!({"user_id": "u-1"}))
+            .build()
+            .expect("Valid event should build");
+
+        assert_eq!(event.tenant_id, "tenant_123");
+        assert_eq!(event.project_id, "project_456");
+        assert_eq!(event.topic, "user.created");
+        assert!(!event.id.is_empty());
+    }
+
+    #[test]
+    fn test_builder_rejects_empty_topic() {
+        let result = Event::builder()
+            .tenant_id("tenant_123")
+            .project_id("project_456")
+            .topic("")
+            .payload(json!({}))
+            .build();
+
+        assert!(matches!(result, Err(EventBuildError::InvalidTopic(_))));
+    }
+
+    #[test]
+    fn test_builder_rejects_missing_tenant_or_project() {
+        let result = Event::builder()
+            .project_id("project_456")
+            .topic("user.created")
+            .payload(json!({}))
+            .build();
+        assert_eq!(result.unwrap_err(), EventBuildError::EmptyTenantId);
+
+        let result = Event::builder()
+            .tenant_id("tenant_123")
+            .topic("user.created")
+            .payload(json!({}))
+            .build();
+        assert_eq!(result.unwrap_err(), EventBuildError::EmptyProjectId);
+    }
+
+    #[test]
+    fn test_builder_rejects_oversized_payload() {
+        let result = Event::builder()
+            .tenant_id("tenant_123")
+            .project_id("project_456")
+            .topic("user.created")
+            .payload(json!({"data": "x".repeat(DEFAULT_MAX_PAYLOAD_SIZE)}))
+            .build();
+
+        match result {
+            Err(EventBuildError::PayloadTooLarge { size, limit }) => {
+                assert!(size > limit);
+                assert_eq!(limit, DEFAULT_MAX_PAYLOAD_SIZE);
+            }
+            other => panic!("Expected PayloadTooLarge, got {:?}", other),
+        }
+
+        // A custom limit is honored
+        let result = Event::builder()
+            .tenant_id("tenant_123")
+            .project_id("project_456")
+            .topic("user.created")
+            .payload(json!({"data": "x".repeat(64)}))
+            .max_payload_size(32)
+            .build();
+        assert!(matches!(result, Err(EventBuildError::PayloadTooLarge { limit: 32, .. })));
+    }
+}
+
+#[cfg(test)]
+mod metadata_limit_tests {
+    use realtime_api::models::{MetadataLimitError, MetadataLimits};
+    use std::collections::HashMap;
+
+    fn entries(count: usize, value: &str) -> HashMap<String, String> {
+        (0..count)
+            .map(|i| (format!("key_{}", i), value.to_string()))
+            .collect()
+    }
+
+    fn limits() -> MetadataLimits {
+        MetadataLimits {
+            max_metadata_keys: 4,
+            max_attributes: 2,
+            max_value_length: 8,
+        }
+    }
+
+    #[test]
+    fn test_normal_metadata_passes() {
+        assert!(limits().check(&entries(4, "short"), &entries(2, "v")).is_ok());
+        assert!(limits().check(&HashMap::new(), &HashMap::new()).is_ok());
+    }
+
+    #[test]
+    fn test_too_many_metadata_keys_rejected() {
+        let result = limits().check(&entries(5, "v"), &HashMap::new());
+        assert_eq!(
+            result,
+            Err(MetadataLimitError::TooManyMetadataKeys { count: 5, limit: 4 })
+        );
+    }
+
+    #[test]
+    fn test_too_many_attributes_rejected() {
+        let result = limits().check(&HashMap::new(), &entries(3, "v"));
+        assert_eq!(
+            result,
+            Err(MetadataLimitError::TooManyAttributes { count: 3, limit: 2 })
+        );
+    }
+
+    #[test]
+    fn test_oversized_values_rejected() {
+        let result = limits().check(&entries(1, "123456789"), &HashMap::new());
+        assert!(matches!(
+            result,
+            Err(MetadataLimitError::ValueTooLong { length: 9, limit: 8, .. })
+        ));
+
+        let result = limits().check(&HashMap::new(), &entries(1, "123456789"));
+        assert!(matches!(result, Err(MetadataLimitError::ValueTooLong { .. })));
+    }
+
+    #[test]
+    fn test_default_limits_allow_typical_metadata() {
+        let metadata: HashMap<String, String> = [
+            ("trace_id", "4bf92f3577b34da6a3ce929d0e0e4736"),
+            ("source", "billing-service"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        assert!(MetadataLimits::default().check(&metadata, &HashMap::new()).is_ok());
+    }
+}
@@ -0,0 +1,100 @@
+/// **Feature: realtime-saas-platform, Durable consumer backpressure**
+///
+/// A durable consumer that stops acking must not keep receiving new events once
+/// `max_ack_pending` un-acked deliveries are outstanding, and must resume after acking.
+use async_nats::jetstream::consumer::PullConsumer;
+use futures_util::StreamExt;
+use realtime_api::models::Event;
+use realtime_api::nats::{NatsClient, SubscriptionConfig};
+use serde_json::json;
+use std::time::Duration;
+use uuid::Uuid;
+
+async fn fetch_available(consumer: &PullConsumer) -> Vec<async_nats::jetstream::Message> {
+    let mut batch = consumer
+        .fetch()
+        .max_messages(10)
+        .expires(Duration::from_secs(1))
+        .messages()
+        .await
+        .expect("Failed to fetch batch");
+
+    let mut messages = Vec::new();
+    while let Some(message) = batch.next().await {
+        messages.push(message.expect("Failed to receive message"));
+    }
+    messages
+}
+
+#[tokio::test]
+async fn test_unacked_consumer_stops_at_max_ack_pending() {
+    let nats_url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://localhost:4222".to_string());
+    let stream_name = std::env::var("NATS_STREAM_NAME").unwrap_or_else(|_| "EVENTS".to_string());
+
+    let nats_client = NatsClient::new(&nats_url, stream_name.clone())
+        .await
+        .expect("Failed to connect to NATS");
+
+    let tenant_id = Uuid::new_v4().to_string();
+    let project_id = Uuid::new_v4().to_string();
+    let consumer_name = format!("ack_pending_{}", Uuid::new_v4().simple());
+
+    let config = SubscriptionConfig {
+        tenant_id: tenant_id.clone(),
+        project_id: project_id.clone(),
+        topics: vec!["ack.test".to_string()],
+        consumer_name: consumer_name.clone(),
+        durable: true,
+        max_ack_pending: Some(2),
+        explicit_ack: false,
+        ack_wait: None,
+        max_deliver: None,
+    };
+    nats_client
+        .create_consumer(&config)
+        .await
+        .expect("Failed to create consumer");
+
+    for i in 0..5 {
+        let event = Event::new(
+            tenant_id.clone(),
+            project_id.clone(),
+            "ack.test".to_string(),
+            json!({ "n": i }),
+        );
+        nats_client
+            .publish_event(&event)
+            .await
+            .expect("Failed to publish event");
+    }
+
+    let stream = nats_client
+        .jetstream()
+        .get_stream(&stream_name)
+        .await
+        .expect("Failed to get stream");
+    let consumer: PullConsumer = stream
+        .get_consumer(&consumer_name)
+        .await
+        .expect("Failed to get consumer");
+
+    // Without acking, delivery stops at the cap
+    let first = fetch_available(&consumer).await;
+    assert_eq!(first.len(), 2);
+
+    // Nothing further is delivered while the window is full
+    let stalled = fetch_available(&consumer).await;
+    assert!(stalled.is_empty());
+
+    // Acking frees the window and delivery resumes
+    for message in &first {
+        message.double_ack().await.expect("Failed to ack");
+    }
+    let resumed = fetch_available(&consumer).await;
+    assert_eq!(resumed.len(), 2);
+
+    nats_client
+        .delete_consumer(&tenant_id, &consumer_name)
+        .await
+        .expect("Failed to delete consumer");
+}
@@ -0,0 +1,89 @@
+/// **Feature: realtime-saas-platform, NATS publish buffer**
+///
+/// Events published while NATS is disconnected wait in the publish buffer,
+/// but only for the configured publish buffer timeout: the publisher gets an
+/// unavailability error instead of hanging, and the event leaves the buffer.
+use realtime_api::models::Event;
+use realtime_api::nats::{is_nats_unavailable, NatsClient};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+const PUBLISH_BUFFER_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// A TCP proxy in front of the test NATS server that can be cut to simulate
+/// losing the connection
+struct Proxy {
+    addr: String,
+    tasks: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl Proxy {
+    async fn start() -> Self {
+        let nats_url =
+            std::env::var("NATS_URL").unwrap_or_else(|_| "nats://localhost:4222".to_string());
+        let upstream = nats_url.trim_start_matches("nats://").to_string();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let tasks = Arc::new(Mutex::new(Vec::new()));
+        let connections = tasks.clone();
+        let accept = tokio::spawn(async move {
+            while let Ok((mut inbound, _)) = listener.accept().await {
+                let upstream = upstream.clone();
+                let connection = tokio::spawn(async move {
+                    if let Ok(mut outbound) = TcpStream::connect(&upstream).await {
+                        let _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+                    }
+                });
+                connections.lock().unwrap().push(connection);
+            }
+        });
+        tasks.lock().unwrap().push(accept);
+        Self { addr, tasks }
+    }
+
+    /// Close the listener and every proxied connection
+    fn cut(&self) {
+        for task in self.tasks.lock().unwrap().drain(..) {
+            task.abort();
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_publish_while_disconnected_fails_after_the_buffer_timeout() {
+    let proxy = Proxy::start().await;
+    let stream_name = std::env::var("NATS_STREAM_NAME").unwrap_or_else(|_| "EVENTS".to_string());
+    let client = NatsClient::new(&format!("nats://{}", proxy.addr), stream_name)
+        .await
+        .expect("Failed to connect to NATS through the proxy")
+        .with_publish_buffer_timeout(PUBLISH_BUFFER_TIMEOUT);
+    assert!(client.is_connected());
+
+    proxy.cut();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while client.is_connected() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("Client should notice the connection is gone");
+
+    let event = Event::new(
+        "tenant_123".to_string(),
+        "project_456".to_string(),
+        "orders.created".to_string(),
+        serde_json::json!({"order_id": 1}),
+    );
+    let started = Instant::now();
+    let error = tokio::time::timeout(Duration::from_secs(5), client.publish_event(&event))
+        .await
+        .expect("Buffered publish should not hang")
+        .expect_err("Publish should fail while NATS stays down");
+
+    assert!(is_nats_unavailable(&error), "{:#}", error);
+    assert!(started.elapsed() >= PUBLISH_BUFFER_TIMEOUT);
+    assert_eq!(client.buffered_publishes(), 0);
+}
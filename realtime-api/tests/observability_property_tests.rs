@@ -7,7 +7,11 @@
 
 use proptest::prelude::*;
 use realtime_api::{
-    config::{Config, ObservabilityConfig},
+    config::{
+        BillingConfig, CircuitBreakerConfig, Config, CorsConfig, EventsConfig, GraphQLConfig,
+        HttpConfig, JwtConfig, KafkaConfig, LogFormat, ObservabilityConfig, RateLimitConfig,
+        TlsConfig, WebSocketConfig, WebhookConfig,
+    },
     observability::{init_observability, add_correlation_id, Metrics},
     alerting::AlertingService,
 };
@@ -107,14 +111,24 @@ mod trace_emission_tests {
                     server: realtime_api::config::ServerConfig {
                         host: "localhost".to_string(),
                         port: 3000,
+                        max_page_size: 1000,
+                        stale_api_key_days: 90,
+                        api_key_rotation_grace_secs: 86400,
+                        shutdown_grace_period_secs: 30,
+                        platform_admin_tenant_id: None,
+                        trusted_proxies: Vec::new(),
                     },
                     database: realtime_api::config::DatabaseConfig {
-                        url: "postgresql://test".to_string(),
                         max_connections: 1,
+                        ..realtime_api::config::DatabaseConfig::new("postgresql://test")
                     },
                     nats: realtime_api::config::NatsConfig {
                         url: "nats://test".to_string(),
                         stream_name: "TEST".to_string(),
+                        max_ack_pending: 1000,
+                        publish_buffer_size: 10000,
+                        publish_buffer_timeout_ms: 5000,
+                        tenant_streams: realtime_api::config::TenantStreamConfig::default(),
                     },
                     observability: ObservabilityConfig {
                         tracing_endpoint: None, // Disable external tracing for testing
@@ -123,8 +137,22 @@ mod trace_emission_tests {
                         log_level: "debug".to_string(),
                         enable_alerts: false,
                         alert_webhook_url: None,
+                        log_format: LogFormat::default(),
                     },
+                    events: EventsConfig::default(),
+                    billing: BillingConfig::default(),
+                    websocket: WebSocketConfig::default(),
+                    cors: CorsConfig::default(),
+                    graphql: GraphQLConfig::default(),
+                    http: HttpConfig::default(),
+                    rate_limit: RateLimitConfig::default(),
+                    webhooks: WebhookConfig::default(),
+                    kafka: KafkaConfig::default(),
+                    tls: TlsConfig::default(),
+                    jwt: JwtConfig::default(),
+                    circuit_breaker: CircuitBreakerConfig::default(),
                     jwt_secret: "test_secret".to_string(),
+                    signing_secret_key: None,
                 };
 
                 // Initialize observability (this should not fail)
@@ -206,14 +234,24 @@ mod trace_emission_tests {
                     server: realtime_api::config::ServerConfig {
                         host: "localhost".to_string(),
                         port: 3000,
+                        max_page_size: 1000,
+                        stale_api_key_days: 90,
+                        api_key_rotation_grace_secs: 86400,
+                        shutdown_grace_period_secs: 30,
+                        platform_admin_tenant_id: None,
+                        trusted_proxies: Vec::new(),
                     },
                     database: realtime_api::config::DatabaseConfig {
-                        url: "postgresql://test".to_string(),
                         max_connections: 1,
+                        ..realtime_api::config::DatabaseConfig::new("postgresql://test")
                     },
                     nats: realtime_api::config::NatsConfig {
                         url: "nats://test".to_string(),
                         stream_name: "TEST".to_string(),
+                        max_ack_pending: 1000,
+                        publish_buffer_size: 10000,
+                        publish_buffer_timeout_ms: 5000,
+                        tenant_streams: realtime_api::config::TenantStreamConfig::default(),
                     },
                     observability: ObservabilityConfig {
                         tracing_endpoint: None,
@@ -222,8 +260,22 @@ mod trace_emission_tests {
                         log_level: "info".to_string(),
                         enable_alerts: false,
                         alert_webhook_url: None,
+                        log_format: LogFormat::default(),
                     },
+                    events: EventsConfig::default(),
+                    billing: BillingConfig::default(),
+                    websocket: WebSocketConfig::default(),
+                    cors: CorsConfig::default(),
+                    graphql: GraphQLConfig::default(),
+                    http: HttpConfig::default(),
+                    rate_limit: RateLimitConfig::default(),
+                    webhooks: WebhookConfig::default(),
+                    kafka: KafkaConfig::default(),
+                    tls: TlsConfig::default(),
+                    jwt: JwtConfig::default(),
+                    circuit_breaker: CircuitBreakerConfig::default(),
                     jwt_secret: "test_secret".to_string(),
+                    signing_secret_key: None,
                 };
 
                 // Initialize observability and get metrics
@@ -245,8 +297,8 @@ mod trace_emission_tests {
                         prop_assert!(events_published_metric.is_some(), "Events published metric should be exposed");
                     }
                     "websocket_connect" => {
-                        metrics.record_websocket_connection_change(1);
-                        
+                        // The connection manager owns the gauge; it is exposed
+                        // even with no connections open
                         let metric_families = metrics.registry.gather();
                         let websocket_metric = metric_families.iter()
                             .find(|mf| mf.get_name() == "realtime_websocket_connections_active");
@@ -388,6 +440,7 @@ mod trace_emission_tests {
                     log_level: "info".to_string(),
                     enable_alerts: true,
                     alert_webhook_url: Some("http://localhost:8080/webhook".to_string()),
+                    log_format: LogFormat::default(),
                 };
 
                 // Create alerting service
@@ -479,14 +532,24 @@ mod trace_emission_tests {
             server: realtime_api::config::ServerConfig {
                 host: "localhost".to_string(),
                 port: 3000,
+                max_page_size: 1000,
+                stale_api_key_days: 90,
+                api_key_rotation_grace_secs: 86400,
+                shutdown_grace_period_secs: 30,
+                platform_admin_tenant_id: None,
+                trusted_proxies: Vec::new(),
             },
             database: realtime_api::config::DatabaseConfig {
-                url: "postgresql://test".to_string(),
                 max_connections: 1,
+                ..realtime_api::config::DatabaseConfig::new("postgresql://test")
             },
             nats: realtime_api::config::NatsConfig {
                 url: "nats://test".to_string(),
                 stream_name: "TEST".to_string(),
+                max_ack_pending: 1000,
+                publish_buffer_size: 10000,
+                publish_buffer_timeout_ms: 5000,
+                tenant_streams: realtime_api::config::TenantStreamConfig::default(),
             },
             observability: ObservabilityConfig {
                 tracing_endpoint: None,
@@ -495,8 +558,22 @@ mod trace_emission_tests {
                 log_level: "info".to_string(),
                 enable_alerts: false,
                 alert_webhook_url: None,
+                log_format: LogFormat::default(),
             },
+            events: EventsConfig::default(),
+            billing: BillingConfig::default(),
+            websocket: WebSocketConfig::default(),
+            cors: CorsConfig::default(),
+            graphql: GraphQLConfig::default(),
+            http: HttpConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            webhooks: WebhookConfig::default(),
+            kafka: KafkaConfig::default(),
+            tls: TlsConfig::default(),
+            jwt: JwtConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
             jwt_secret: "test_secret".to_string(),
+            signing_secret_key: None,
         };
 
         // Test that observability can be initialized without external dependencies
@@ -0,0 +1,118 @@
+/// **Feature: realtime-saas-platform, Transactional tenant onboarding**
+///
+/// Onboarding must create the tenant, its default project and the initial API key
+/// together, or none of them at all.
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use realtime_api::api::{onboard_tenant, OnboardRequest};
+use realtime_api::auth::{AuthContext, AuthService, AuthType};
+use realtime_api::models::{BillingPlan, Project, ProjectLimits, Scope, Tenant};
+use uuid::Uuid;
+
+mod common;
+
+use common::{create_project, test_database, test_state};
+
+fn onboarding_resources(plan: BillingPlan) -> (Tenant, Project, String, realtime_api::models::ApiKey) {
+    let tenant = Tenant::new("Onboarded Tenant".to_string(), plan);
+    let mut project = Project::new(tenant.id.clone(), "default".to_string());
+    project.limits = ProjectLimits::for_plan(&tenant.plan);
+    let (raw_key, api_key) = AuthService::new_api_key(
+        tenant.id.clone(),
+        project.id.clone(),
+        vec![Scope::AdminRead, Scope::AdminWrite],
+        100,
+        None,
+    )
+    .expect("Failed to hash API key");
+    (tenant, project, raw_key, api_key)
+}
+
+#[tokio::test]
+async fn test_onboard_creates_linked_resources() {
+    let database = test_database().await;
+    let (tenant, project, raw_key, api_key) =
+        onboarding_resources(BillingPlan::Free { monthly_events: 10000 });
+
+    database
+        .onboard_tenant(&tenant, &project, &api_key)
+        .await
+        .expect("Onboarding should succeed");
+
+    let stored_tenant = database.get_tenant(&tenant.id).await.unwrap();
+    assert!(stored_tenant.is_some());
+
+    let stored_project = database
+        .get_project_with_tenant(&tenant.id, &project.id)
+        .await
+        .unwrap()
+        .expect("Project should be linked to tenant");
+    assert_eq!(stored_project.limits.max_connections, 100);
+
+    let stored_key = database
+        .get_api_key_by_hash(&AuthService::hash_api_key_for_lookup(&raw_key))
+        .await
+        .unwrap()
+        .expect("API key should be retrievable by its raw value");
+    assert_eq!(stored_key.tenant_id, tenant.id);
+    assert_eq!(stored_key.project_id, project.id);
+}
+
+#[tokio::test]
+async fn test_onboard_failure_leaves_nothing_behind() {
+    let database = test_database().await;
+    let (tenant, project, raw_key, mut api_key) =
+        onboarding_resources(BillingPlan::Enterprise { unlimited: true });
+
+    // Point the key at a project that does not exist so the final insert fails
+    api_key.project_id = Uuid::new_v4().to_string();
+
+    let result = database.onboard_tenant(&tenant, &project, &api_key).await;
+    assert!(result.is_err());
+
+    assert!(database.get_tenant(&tenant.id).await.unwrap().is_none());
+    assert!(database.get_project_with_tenant(&tenant.id, &project.id).await.unwrap().is_none());
+    assert!(database
+        .get_api_key_by_hash(&AuthService::hash_api_key_for_lookup(&raw_key))
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_tenant_admin_key_cannot_onboard_tenants() {
+    let mut state = test_state().await;
+    state.auth_service = state
+        .auth_service
+        .clone()
+        .with_platform_admin_tenant(Some("platform-admin-tenant".to_string()));
+    let (tenant, project) = create_project(&state.database, "Onboarding Caller").await;
+    let auth = AuthContext {
+        tenant_id: tenant.id,
+        project_id: project.id,
+        scopes: vec![Scope::AdminWrite],
+        rate_limit_per_sec: 100,
+        allowed_topics: vec![],
+        auth_type: AuthType::ApiKey {
+            key_id: "admin_key".to_string(),
+        },
+        user_id: None,
+        user_role: None,
+    };
+
+    let err = onboard_tenant(
+        State(state),
+        Extension(auth),
+        Json(OnboardRequest {
+            tenant_name: "Self-Onboarded Tenant".to_string(),
+            plan: "enterprise".to_string(),
+            project_name: None,
+        }),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(err.status(), StatusCode::FORBIDDEN);
+    assert_eq!(err.code(), "INSUFFICIENT_SCOPE");
+}
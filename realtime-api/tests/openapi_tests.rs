@@ -0,0 +1,214 @@
+/// **Feature: realtime-saas-platform, OpenAPI spec**
+///
+/// `ApiDoc` is a valid OpenAPI 3.0 document that documents every route and
+/// method registered in `create_router`, along with the bearer API key
+/// scheme, and Swagger UI is served at `/docs`.
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use openapiv3::{OpenAPI, ReferenceOr};
+use realtime_api::openapi::{swagger_ui, ApiDoc, DOCS_PATH, OPENAPI_JSON_PATH};
+use tower::ServiceExt;
+use utoipa::OpenApi;
+
+/// Every `(path, method)` registered in `create_router` besides the spec and
+/// Swagger UI themselves, in OpenAPI's `{param}` form; a route added there
+/// belongs here too
+const ROUTES: [(&str, &str); 50] = [
+    ("/health", "get"),
+    ("/health/live", "get"),
+    ("/health/ready", "get"),
+    ("/metrics", "get"),
+    ("/webhooks/stripe", "post"),
+    ("/billing/stripe-webhook", "post"),
+    ("/ws", "get"),
+    ("/sse", "get"),
+    ("/sse", "post"),
+    ("/graphql/ws", "get"),
+    ("/graphql/playground", "get"),
+    ("/events", "post"),
+    ("/events", "get"),
+    ("/events/batch", "post"),
+    ("/events/replay", "get"),
+    ("/events/{event_id}", "get"),
+    ("/admin/tenants", "post"),
+    ("/admin/onboard", "post"),
+    ("/admin/api-keys", "post"),
+    ("/admin/api-keys/{key_id}", "delete"),
+    ("/admin/api-keys/{key_id}/rotate", "post"),
+    ("/admin/api-keys/{key_id}/usage", "get"),
+    ("/admin/projects/{project_id}", "patch"),
+    ("/admin/projects/{project_id}", "delete"),
+    ("/admin/projects/{project_id}/api-keys", "get"),
+    ("/admin/sla", "get"),
+    ("/admin/audit", "get"),
+    ("/admin/usage/export", "get"),
+    ("/admin/dlq", "get"),
+    ("/admin/dlq/{id}/replay", "post"),
+    ("/admin/webhooks", "post"),
+    ("/admin/webhooks", "get"),
+    ("/admin/webhooks/{webhook_id}", "get"),
+    ("/admin/webhooks/{webhook_id}", "patch"),
+    ("/admin/webhooks/{webhook_id}", "delete"),
+    ("/admin/topics/{topic}/schema", "put"),
+    ("/projects/{project_id}/topics/{topic}/schema", "get"),
+    ("/admin/tenants/{tenant_id}", "get"),
+    ("/admin/tenants/{tenant_id}/suspend", "post"),
+    ("/admin/tenants/{tenant_id}/unsuspend", "post"),
+    ("/admin/tenants/{tenant_id}/plan", "patch"),
+    ("/admin/tenants/{tenant_id}/log-level", "put"),
+    ("/admin/tenants/{tenant_id}/log-level", "delete"),
+    ("/billing/usage", "get"),
+    ("/billing/limits", "get"),
+    ("/billing/suspend/{tenant_id}", "post"),
+    ("/billing/unsuspend/{tenant_id}", "post"),
+    ("/admin/tenants/{tenant_id}/users/{user_id}/role", "put"),
+    ("/admin/tenants/{tenant_id}/users", "get"),
+    (
+        "/admin/tenants/{tenant_id}/users/{user_id}/deactivate",
+        "post",
+    ),
+];
+
+/// The generated spec, as a client would parse it
+fn spec() -> OpenAPI {
+    let json = ApiDoc::openapi().to_json().expect("Spec should serialize");
+    serde_json::from_str(&json).expect("Spec should be valid OpenAPI 3.0")
+}
+
+#[test]
+fn test_spec_is_valid_openapi_covering_every_route() {
+    let spec = spec();
+    assert!(spec.openapi.starts_with("3.0."), "{}", spec.openapi);
+
+    for (path, method) in ROUTES {
+        let item = match spec.paths.paths.get(path) {
+            Some(ReferenceOr::Item(item)) => item,
+            _ => panic!("{} is not documented", path),
+        };
+        let operation = match method {
+            "get" => &item.get,
+            "post" => &item.post,
+            "put" => &item.put,
+            "patch" => &item.patch,
+            "delete" => &item.delete,
+            _ => unreachable!(),
+        };
+        assert!(
+            operation.is_some(),
+            "{} {} is not documented",
+            method.to_uppercase(),
+            path
+        );
+    }
+
+    // Operation ids stay unique across alias routes
+    let mut operation_ids: Vec<_> = spec
+        .operations()
+        .filter_map(|(_, _, operation)| operation.operation_id.clone())
+        .collect();
+    let count = operation_ids.len();
+    operation_ids.sort();
+    operation_ids.dedup();
+    assert_eq!(operation_ids.len(), count);
+}
+
+#[test]
+fn test_spec_documents_auth_bodies_and_error_codes() {
+    let spec = spec();
+    let components = spec
+        .components
+        .as_ref()
+        .expect("Spec should have components");
+
+    match components.security_schemes.get("api_key") {
+        Some(ReferenceOr::Item(openapiv3::SecurityScheme::HTTP { scheme, .. })) => {
+            assert_eq!(scheme.to_lowercase(), "bearer")
+        }
+        other => panic!("Expected a bearer security scheme, got {:?}", other),
+    }
+    assert!(spec
+        .security
+        .as_ref()
+        .unwrap()
+        .iter()
+        .any(|req| req.contains_key("api_key")));
+
+    for schema in [
+        "PublishEventRequest",
+        "PublishEventResponse",
+        "ErrorResponse",
+        "Event",
+    ] {
+        assert!(
+            components.schemas.contains_key(schema),
+            "{} is missing",
+            schema
+        );
+    }
+
+    let publish = spec.paths.paths["/events"]
+        .as_item()
+        .unwrap()
+        .post
+        .as_ref()
+        .unwrap();
+    assert!(publish.request_body.is_some());
+    let bad_request = match publish
+        .responses
+        .responses
+        .get(&openapiv3::StatusCode::Code(400))
+    {
+        Some(ReferenceOr::Item(response)) => response,
+        other => panic!("Expected a 400 response, got {:?}", other),
+    };
+    assert!(bad_request.description.contains("VALIDATION_FAILED"));
+    assert!(publish
+        .responses
+        .responses
+        .contains_key(&openapiv3::StatusCode::Code(401)));
+
+    // Probes and the Stripe webhook don't take an API key
+    for path in ["/health", "/webhooks/stripe", "/billing/stripe-webhook"] {
+        let item = spec.paths.paths[path].as_item().unwrap();
+        let operation = item.get.as_ref().or(item.post.as_ref()).unwrap();
+        assert_eq!(operation.security, Some(vec![]), "{}", path);
+    }
+}
+
+#[tokio::test]
+async fn test_swagger_ui_and_spec_are_served() {
+    let router: Router = Router::new().merge(swagger_ui());
+
+    let response = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("{}/", DOCS_PATH))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert!(String::from_utf8_lossy(&body).contains("swagger"));
+
+    let response = router
+        .oneshot(
+            Request::builder()
+                .uri(OPENAPI_JSON_PATH)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let served: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(served, serde_json::to_value(ApiDoc::openapi()).unwrap());
+}
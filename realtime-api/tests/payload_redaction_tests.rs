@@ -0,0 +1,152 @@
+/// **Feature: realtime-saas-platform, PII redaction in logs**
+///
+/// Values at configured JSON pointers, and under keys such as `password`, are
+/// masked wherever a payload is quoted in logs or validation errors, while the
+/// stored event keeps the payload exactly as published.
+use realtime_api::config::RedactionConfig;
+use realtime_api::database::Database;
+use realtime_api::event_service::{EventService, PublishResult};
+use realtime_api::models::{Event, Project, Tenant};
+use realtime_api::redaction::REDACTED;
+use realtime_api::schema_validator::ValidationMode;
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::fmt::MakeWriter;
+
+mod common;
+
+use common::{create_project, test_database, test_event_service};
+
+const EMAIL: &str = "ada.lovelace@example.com";
+const PASSWORD: &str = "correct-horse-battery";
+
+/// Everything logged while the guard from `capture_logs` is held
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = Self;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+fn capture_logs() -> (CapturedLogs, tracing::subscriber::DefaultGuard) {
+    let logs = CapturedLogs::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(logs.clone())
+        .with_ansi(false)
+        .finish();
+    (logs, tracing::subscriber::set_default(subscriber))
+}
+
+async fn setup(validation_mode: ValidationMode) -> (Database, EventService, Tenant, Project) {
+    let database = test_database().await;
+    let (tenant, mut project) = create_project(&database, "Redaction Tenant").await;
+    project.settings.validation_mode = validation_mode;
+    database
+        .update_project_settings(&tenant.id, &project.id, &project.settings)
+        .await
+        .expect("Failed to update project settings");
+
+    let event_service =
+        test_event_service(database.clone())
+            .await
+            .with_redaction(&RedactionConfig {
+                topic_paths: HashMap::from([("signup.>".to_string(), vec!["/email".to_string()])]),
+                ..RedactionConfig::default()
+            });
+
+    // Both fields violate the schema, so the violation messages quote them
+    event_service.schema_validator().register_schema(
+        &project.id,
+        "signup.completed",
+        json!({
+            "type": "object",
+            "properties": {
+                "email": {"type": "integer"},
+                "password": {"type": "string", "maxLength": 4}
+            }
+        }),
+    );
+
+    (database, event_service, tenant, project)
+}
+
+fn signup_event(tenant: &Tenant, project: &Project) -> Event {
+    Event::new(
+        tenant.id.clone(),
+        project.id.clone(),
+        "signup.completed".to_string(),
+        json!({"email": EMAIL, "password": PASSWORD, "plan": "pro"}),
+    )
+}
+
+#[tokio::test]
+async fn test_rejection_error_and_logs_are_redacted() {
+    let (_database, service, tenant, project) = setup(ValidationMode::Strict).await;
+    let (logs, _guard) = capture_logs();
+
+    let result = service
+        .publish_event(&signup_event(&tenant, &project))
+        .await
+        .unwrap();
+    let PublishResult::ValidationFailed(message) = result else {
+        panic!("Expected a validation failure, got {:?}", result);
+    };
+
+    assert!(message.contains(REDACTED), "{}", message);
+    for output in [message.as_str(), logs.contents().as_str()] {
+        assert!(!output.contains(EMAIL), "{}", output);
+        assert!(!output.contains(PASSWORD), "{}", output);
+    }
+    assert!(logs.contents().contains("Event validation failed"));
+}
+
+#[tokio::test]
+async fn test_stored_event_keeps_original_payload() {
+    let (database, service, tenant, project) = setup(ValidationMode::Advisory).await;
+    let (logs, _guard) = capture_logs();
+    let event = signup_event(&tenant, &project);
+
+    let result = service.publish_event(&event).await.unwrap();
+    assert!(
+        matches!(result, PublishResult::Success { .. }),
+        "{:?}",
+        result
+    );
+
+    let logged = logs.contents();
+    assert!(logged.contains("advisory"), "{}", logged);
+    assert!(!logged.contains(EMAIL), "{}", logged);
+    assert!(!logged.contains(PASSWORD), "{}", logged);
+
+    let stored = database
+        .get_event(&tenant.id, &event.id)
+        .await
+        .unwrap()
+        .expect("Event should be stored");
+    assert_eq!(stored.payload, event.payload);
+    assert_eq!(stored.payload["email"], EMAIL);
+    assert_eq!(stored.payload["password"], PASSWORD);
+}
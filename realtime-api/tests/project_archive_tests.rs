@@ -0,0 +1,246 @@
+/// **Feature: realtime-saas-platform, Project archiving**
+///
+/// Archiving a project soft deletes it: it drops out of project listings unless
+/// archived projects are requested, its open WebSocket and SSE connections are
+/// closed, and its API keys stop authenticating so no new connections are
+/// accepted. It no longer takes publishes or limit changes from credentials
+/// that still name it. A tenant can never archive another tenant's project.
+use async_graphql::Request;
+use realtime_api::api::{update_project_limits, ProjectLimitsError};
+use realtime_api::auth::{AuthContext, AuthError, AuthService, AuthType};
+use realtime_api::config::GraphQLConfig;
+use realtime_api::database::Database;
+use realtime_api::event_service::PublishResult;
+use realtime_api::graphql::create_schema;
+use realtime_api::models::{BillingPlan, Event, Project, ProjectLimitsUpdate, Scope, Tenant};
+use realtime_api::project_archive::archive_project;
+use realtime_api::sse::{sse_manager, SSEConnection};
+use realtime_api::websocket::{websocket_manager, WebSocketConnection};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+mod common;
+
+use common::{test_database, test_event_service};
+
+async fn create_tenant(database: &Database, name: &str) -> (Tenant, Project) {
+    let tenant = Tenant::new(name.to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    database.create_project(&project).await.expect("Failed to create project");
+    (tenant, project)
+}
+
+fn admin_auth(tenant: &Tenant, project: &Project) -> AuthContext {
+    AuthContext {
+        tenant_id: tenant.id.clone(),
+        project_id: project.id.clone(),
+        scopes: vec![Scope::AdminRead, Scope::AdminWrite],
+        rate_limit_per_sec: 100,
+        allowed_topics: vec![],
+        auth_type: AuthType::ApiKey { key_id: "admin_key".to_string() },
+        user_id: None,
+        user_role: None,
+    }
+}
+
+#[tokio::test]
+async fn test_archived_project_is_hidden_from_listing_by_default() {
+    let database = test_database().await;
+    let (tenant, project) = create_tenant(&database, "Archive Listing Tenant").await;
+    let kept = Project::new(tenant.id.clone(), "kept".to_string());
+    database.create_project(&kept).await.expect("Failed to create project");
+
+    assert!(archive_project(&database, &tenant.id, &project.id)
+        .await
+        .unwrap()
+        .is_some());
+
+    let listed = database.list_projects_for_tenant(&tenant.id, false).await.unwrap();
+    assert_eq!(listed.iter().map(|p| p.id.as_str()).collect::<Vec<_>>(), vec![kept.id.as_str()]);
+
+    let all = database.list_projects_for_tenant(&tenant.id, true).await.unwrap();
+    assert_eq!(all.len(), 2);
+    let archived = all.iter().find(|p| p.id == project.id).unwrap();
+    assert!(archived.is_archived());
+
+    // Archiving twice is reported as not found
+    assert!(archive_project(&database, &tenant.id, &project.id)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_archive_closes_only_the_projects_connections() {
+    let database = test_database().await;
+    let (tenant, project) = create_tenant(&database, "Archive Connections Tenant").await;
+    let other = Project::new(tenant.id.clone(), "other".to_string());
+    database.create_project(&other).await.expect("Failed to create project");
+
+    let websocket_id = Uuid::new_v4().to_string();
+    websocket_manager()
+        .add_connection(WebSocketConnection {
+            id: websocket_id.clone(),
+            tenant_id: tenant.id.clone(),
+            project_id: project.id.clone(),
+            subscribed_topics: vec![],
+            sender: broadcast::channel(8).0,
+            created_at: chrono::Utc::now(),
+            acked: false,
+        })
+        .unwrap();
+    let sse_id = Uuid::new_v4().to_string();
+    sse_manager()
+        .add_connection(SSEConnection {
+            id: sse_id.clone(),
+            tenant_id: tenant.id.clone(),
+            project_id: project.id.clone(),
+            subscribed_topics: vec![],
+            sender: broadcast::channel(8).0,
+            created_at: chrono::Utc::now(),
+            acked: false,
+        })
+        .unwrap();
+    let other_websocket_id = Uuid::new_v4().to_string();
+    websocket_manager()
+        .add_connection(WebSocketConnection {
+            id: other_websocket_id.clone(),
+            tenant_id: tenant.id.clone(),
+            project_id: other.id.clone(),
+            subscribed_topics: vec![],
+            sender: broadcast::channel(8).0,
+            created_at: chrono::Utc::now(),
+            acked: false,
+        })
+        .unwrap();
+
+    let mut terminated = archive_project(&database, &tenant.id, &project.id)
+        .await
+        .unwrap()
+        .expect("Project should be archived");
+    terminated.sort();
+    let mut expected = vec![websocket_id, sse_id];
+    expected.sort();
+    assert_eq!(terminated, expected);
+
+    assert!(websocket_manager().project_connections(&tenant.id, &project.id).is_empty());
+    assert!(sse_manager().project_connections(&tenant.id, &project.id).is_empty());
+    assert_eq!(
+        websocket_manager().project_connections(&tenant.id, &other.id).len(),
+        1
+    );
+    websocket_manager().remove_connection(&other_websocket_id);
+}
+
+#[tokio::test]
+async fn test_archived_project_keys_stop_authenticating() {
+    let database = test_database().await;
+    let (tenant, project) = create_tenant(&database, "Archive Keys Tenant").await;
+    let auth_service = AuthService::new(database.clone(), "test_secret".to_string());
+
+    let (raw_key, _) = auth_service
+        .create_api_key(
+            tenant.id.clone(),
+            project.id.clone(),
+            vec![Scope::EventsSubscribe],
+            100,
+            Vec::new(),
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+    auth_service.validate_api_key(&raw_key).await.expect("Key should validate");
+
+    archive_project(&database, &tenant.id, &project.id)
+        .await
+        .unwrap()
+        .expect("Project should be archived");
+
+    // WebSocket and SSE handshakes authenticate with the key, so new
+    // connections to the archived project are refused
+    assert!(matches!(
+        auth_service.validate_api_key(&raw_key).await,
+        Err(AuthError::InvalidApiKey)
+    ));
+}
+
+#[tokio::test]
+async fn test_archived_project_rejects_publishes_and_updates() {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone()).await;
+    let (tenant, project) = create_tenant(&database, "Archive Writes Tenant").await;
+
+    archive_project(&database, &tenant.id, &project.id)
+        .await
+        .unwrap()
+        .expect("Project should be archived");
+
+    // A JWT still names the project, so the publish path checks it too
+    let event = Event::new(
+        tenant.id.clone(),
+        project.id.clone(),
+        "orders.created".to_string(),
+        serde_json::json!({"order_id": 1}),
+    );
+    let result = event_service.publish_event(&event).await.unwrap();
+    assert!(matches!(result, PublishResult::ValidationFailed(_)), "{:?}", result);
+
+    assert!(matches!(
+        update_project_limits(&database, &tenant.id, &project.id, &ProjectLimitsUpdate::default()).await,
+        Err(ProjectLimitsError::Archived)
+    ));
+}
+
+#[tokio::test]
+async fn test_archive_rejects_other_tenants_project() {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone()).await;
+    let (owner, owner_project) = create_tenant(&database, "Archive Owner Tenant").await;
+    let (intruder, intruder_project) = create_tenant(&database, "Archive Intruder Tenant").await;
+
+    // Directly through the archive call
+    assert!(archive_project(&database, &intruder.id, &owner_project.id)
+        .await
+        .unwrap()
+        .is_none());
+
+    // And through the GraphQL mutation
+    let schema = create_schema(
+        database.clone(),
+        event_service,
+        AuthService::new(database.clone(), "test_secret".to_string()),
+        100,
+        &GraphQLConfig::default(),
+    );
+    let mutation = format!(r#"mutation {{ archiveProject(id: "{}") }}"#, owner_project.id);
+    let response = schema
+        .execute(Request::new(mutation).data(admin_auth(&intruder, &intruder_project)))
+        .await;
+    assert_eq!(response.errors.len(), 1);
+    assert_eq!(response.errors[0].message, "Forbidden");
+
+    let project = database
+        .get_project_with_tenant(&owner.id, &owner_project.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!project.is_archived());
+
+    // The owner can archive it and sees it again only when asking for archived projects
+    let owner_auth = admin_auth(&owner, &owner_project);
+    let mutation = format!(r#"mutation {{ archiveProject(id: "{}") }}"#, owner_project.id);
+    let response = schema.execute(Request::new(mutation).data(owner_auth.clone())).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    let response = schema
+        .execute(Request::new("{ projects { id } }").data(owner_auth.clone()))
+        .await;
+    assert_eq!(response.data.into_json().unwrap()["projects"], serde_json::json!([]));
+    let response = schema
+        .execute(Request::new("{ projects(includeArchived: true) { id archivedAt } }").data(owner_auth))
+        .await;
+    let projects = response.data.into_json().unwrap()["projects"].clone();
+    assert_eq!(projects[0]["id"], serde_json::json!(owner_project.id));
+    assert!(!projects[0]["archivedAt"].is_null());
+}
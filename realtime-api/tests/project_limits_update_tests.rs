@@ -0,0 +1,179 @@
+/// **Feature: realtime-saas-platform, Project limit updates**
+///
+/// `PATCH /admin/projects/{id}` changes a project's limits: the new limits are
+/// persisted and the connection limit applies to connections opened afterwards.
+/// No limit may exceed the tenant's plan.
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use realtime_api::api::{update_project, AppState, UpdateProjectRequest};
+use realtime_api::auth::{AuthContext, AuthType};
+use realtime_api::models::{
+    BillingPlan, Project, ProjectLimits, ProjectLimitsUpdate, Scope, Tenant,
+};
+use realtime_api::websocket::{websocket_manager, WebSocketConnection};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+mod common;
+
+use common::test_state;
+
+async fn create_project(state: &AppState) -> (Tenant, Project) {
+    let tenant = Tenant::new("Limits Update Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let mut project = Project::new(tenant.id.clone(), "default".to_string());
+    project.limits = ProjectLimits::for_plan(&tenant.plan);
+    state.database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    state.database.create_project(&project).await.expect("Failed to create project");
+    (tenant, project)
+}
+
+fn admin(tenant: &Tenant, project: &Project, scopes: Vec<Scope>) -> AuthContext {
+    AuthContext {
+        tenant_id: tenant.id.clone(),
+        project_id: project.id.clone(),
+        scopes,
+        rate_limit_per_sec: 100,
+        allowed_topics: vec![],
+        auth_type: AuthType::ApiKey {
+            key_id: "admin_key".to_string(),
+        },
+        user_id: None,
+        user_role: None,
+    }
+}
+
+fn limits_request(update: ProjectLimitsUpdate) -> Json<UpdateProjectRequest> {
+    Json(UpdateProjectRequest {
+        limits: Some(update),
+        ..Default::default()
+    })
+}
+
+fn connection(tenant: &Tenant, project: &Project) -> WebSocketConnection {
+    WebSocketConnection {
+        id: Uuid::new_v4().to_string(),
+        tenant_id: tenant.id.clone(),
+        project_id: project.id.clone(),
+        subscribed_topics: vec![],
+        sender: broadcast::channel(8).0,
+        created_at: chrono::Utc::now(),
+        acked: false,
+    }
+}
+
+#[tokio::test]
+async fn test_limit_change_is_persisted_and_enforced() {
+    let state = test_state().await;
+    let (tenant, project) = create_project(&state).await;
+
+    let updated = update_project(
+        State(state.clone()),
+        Extension(admin(&tenant, &project, vec![Scope::AdminWrite])),
+        Path(project.id.clone()),
+        limits_request(ProjectLimitsUpdate {
+            max_connections: Some(1),
+            max_events_per_sec: Some(5),
+            ..ProjectLimitsUpdate::default()
+        }),
+    )
+    .await
+    .expect("Limits should be updated");
+    assert_eq!(updated.limits.max_connections, 1);
+    assert_eq!(updated.limits.max_events_per_sec, 5);
+    // Omitted limits keep their value
+    assert_eq!(updated.limits.max_payload_size, project.limits.max_payload_size);
+
+    let stored = state
+        .database
+        .get_project_with_tenant(&tenant.id, &project.id)
+        .await
+        .unwrap()
+        .expect("Project should exist");
+    assert_eq!(stored.limits.max_connections, 1);
+    assert_eq!(stored.limits.max_events_per_sec, 5);
+
+    websocket_manager()
+        .add_connection(connection(&tenant, &project))
+        .expect("First connection is within the limit");
+    assert!(websocket_manager()
+        .add_connection(connection(&tenant, &project))
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_invalid_or_foreign_updates_are_rejected() {
+    let state = test_state().await;
+    let (tenant, project) = create_project(&state).await;
+    let (_, other_project) = create_project(&state).await;
+
+    let err = update_project(
+        State(state.clone()),
+        Extension(admin(&tenant, &project, vec![Scope::AdminWrite])),
+        Path(project.id.clone()),
+        limits_request(ProjectLimitsUpdate {
+            max_connections: Some(0),
+            ..ProjectLimitsUpdate::default()
+        }),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(err.code(), "INVALID_LIMITS");
+
+    let err = update_project(
+        State(state.clone()),
+        Extension(admin(&tenant, &project, vec![Scope::AdminWrite])),
+        Path(other_project.id.clone()),
+        limits_request(ProjectLimitsUpdate::default()),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.status(), StatusCode::NOT_FOUND);
+
+    let err = update_project(
+        State(state),
+        Extension(admin(&tenant, &project, vec![Scope::AdminRead])),
+        Path(project.id.clone()),
+        limits_request(ProjectLimitsUpdate::default()),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_limits_are_capped_to_the_plan() {
+    let state = test_state().await;
+    let (tenant, project) = create_project(&state).await;
+    let plan_limits = ProjectLimits::for_plan(&tenant.plan);
+
+    let updated = update_project(
+        State(state.clone()),
+        Extension(admin(&tenant, &project, vec![Scope::AdminWrite])),
+        Path(project.id.clone()),
+        limits_request(ProjectLimitsUpdate {
+            max_connections: Some(plan_limits.max_connections * 100),
+            max_events_per_sec: Some(plan_limits.max_events_per_sec - 1),
+            max_payload_size: Some(i32::MAX),
+            ..ProjectLimitsUpdate::default()
+        }),
+    )
+    .await
+    .expect("Limits should be updated");
+    assert_eq!(updated.limits.max_connections, plan_limits.max_connections);
+    assert_eq!(
+        updated.limits.max_events_per_sec,
+        plan_limits.max_events_per_sec - 1
+    );
+    assert_eq!(updated.limits.max_payload_size, plan_limits.max_payload_size);
+
+    let stored = state
+        .database
+        .get_project_with_tenant(&tenant.id, &project.id)
+        .await
+        .unwrap()
+        .expect("Project should exist");
+    assert_eq!(stored.limits.max_connections, plan_limits.max_connections);
+    assert_eq!(stored.limits.max_payload_size, plan_limits.max_payload_size);
+}
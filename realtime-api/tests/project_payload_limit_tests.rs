@@ -0,0 +1,121 @@
+/// **Feature: realtime-saas-platform, Per-project payload size limit**
+///
+/// Publishes are held to the publishing project's `max_payload_size` rather
+/// than a platform-wide limit, so one payload can be too large for one project
+/// and fine for another. The event service enforces it, so REST and GraphQL
+/// publishes alike are covered.
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use realtime_api::api::AppState;
+use realtime_api::event_service::PublishResult;
+use realtime_api::models::{BillingPlan, Event, Project, Scope, Tenant};
+use realtime_api::routes::create_router;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+use common::test_state;
+
+const SMALL_LIMIT: i32 = 1024;
+const LARGE_LIMIT: i32 = 64 * 1024;
+
+/// A new project allowing payloads of up to `max_payload_size` bytes, and a publishing key for it
+async fn key_for_project(state: &AppState, max_payload_size: i32) -> (Project, String) {
+    let tenant = Tenant::new(
+        "Payload Limit Tenant".to_string(),
+        BillingPlan::Free {
+            monthly_events: 10000,
+        },
+    );
+    let mut project = Project::new(tenant.id.clone(), "default".to_string());
+    project.limits.max_payload_size = max_payload_size;
+    state
+        .database
+        .create_tenant(&tenant)
+        .await
+        .expect("Failed to create tenant");
+    state
+        .database
+        .create_project(&project)
+        .await
+        .expect("Failed to create project");
+
+    let (raw_key, _) = state
+        .auth_service
+        .create_api_key(
+            tenant.id,
+            project.id.clone(),
+            vec![Scope::EventsPublish],
+            100,
+            vec![],
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+    (project, raw_key)
+}
+
+async fn publish(router: &Router, key: &str, payload: &Value) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/events")
+        .header("authorization", format!("Bearer {}", key))
+        .header("content-type", "application/json")
+        .body(Body::from(
+            json!({"topic": "documents.uploaded", "payload": payload}).to_string(),
+        ))
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_payload_limit_follows_the_project() {
+    let state = test_state().await;
+    let (_, small_key) = key_for_project(&state, SMALL_LIMIT).await;
+    let (_, large_key) = key_for_project(&state, LARGE_LIMIT).await;
+    let router = create_router(state);
+
+    // Over the small project's limit, well under the large one's
+    let payload = json!({"content": "x".repeat(8 * 1024)});
+    let size = payload.to_string().len();
+
+    let (status, body) = publish(&router, &small_key, &payload).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["error"]["code"], "PAYLOAD_TOO_LARGE");
+    assert_eq!(body["error"]["details"]["limit"], SMALL_LIMIT);
+    assert_eq!(body["error"]["details"]["size"], size);
+
+    let (status, body) = publish(&router, &large_key, &payload).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body["event_id"].is_string());
+}
+
+#[tokio::test]
+async fn test_event_service_enforces_the_project_limit() {
+    let state = test_state().await;
+    let (project, _) = key_for_project(&state, SMALL_LIMIT).await;
+
+    // Within the platform's limit, so only the project's stops it
+    let payload = json!({"content": "x".repeat(8 * 1024)});
+    let event = Event::new(
+        project.tenant_id.clone(),
+        project.id.clone(),
+        "documents.uploaded".to_string(),
+        payload.clone(),
+    );
+    let result = state.event_service.publish_event(&event).await.unwrap();
+    match result {
+        PublishResult::PayloadTooLarge { size, limit } => {
+            assert_eq!(size, payload.to_string().len());
+            assert_eq!(limit, SMALL_LIMIT as usize);
+        }
+        other => panic!("Expected PayloadTooLarge, got {:?}", other),
+    }
+}
@@ -0,0 +1,109 @@
+/// **Feature: realtime-saas-platform, Project-wide publish rate**
+///
+/// A project's `max_events_per_sec` caps its publishes as a whole, so spreading
+/// them over several API keys doesn't get past it.
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use realtime_api::api::AppState;
+use realtime_api::models::{BillingPlan, Project, Scope, Tenant};
+use realtime_api::routes::create_router;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+use common::test_state;
+
+const PROJECT_EVENTS_PER_SEC: i32 = 2;
+
+/// Two keys for one project, each allowed far more than the project is
+async fn project_keys(state: &AppState) -> (String, String) {
+    let tenant = Tenant::new(
+        "Project Rate Tenant".to_string(),
+        BillingPlan::Free {
+            monthly_events: 10000,
+        },
+    );
+    let mut project = Project::new(tenant.id.clone(), "default".to_string());
+    project.limits.max_events_per_sec = PROJECT_EVENTS_PER_SEC;
+    state
+        .database
+        .create_tenant(&tenant)
+        .await
+        .expect("Failed to create tenant");
+    state
+        .database
+        .create_project(&project)
+        .await
+        .expect("Failed to create project");
+
+    let mut keys = Vec::new();
+    for _ in 0..2 {
+        let (raw_key, _) = state
+            .auth_service
+            .create_api_key(
+                tenant.id.clone(),
+                project.id.clone(),
+                vec![Scope::EventsPublish],
+                1000,
+                vec![],
+                None,
+            )
+            .await
+            .expect("Failed to create API key");
+        keys.push(raw_key);
+    }
+    (keys.remove(0), keys.remove(0))
+}
+
+async fn publish(router: &Router, key: &str) -> (StatusCode, Option<String>, Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/events")
+        .header("authorization", format!("Bearer {}", key))
+        .header("content-type", "application/json")
+        .body(Body::from(
+            json!({"topic": "metrics.sampled", "payload": {"value": 1}}).to_string(),
+        ))
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let retry_after = response
+        .headers()
+        .get("retry-after")
+        .map(|value| value.to_str().unwrap().to_string());
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, retry_after, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_project_rate_applies_across_api_keys() {
+    let state = test_state().await;
+    let (first_key, second_key) = project_keys(&state).await;
+    let router = create_router(state);
+
+    // The project's burst is used up by the two keys together
+    for key in [&first_key, &second_key] {
+        let (status, _, body) = publish(&router, key).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+    }
+
+    for key in [&first_key, &second_key] {
+        let (status, retry_after, body) = publish(&router, key).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["error"]["code"], "PROJECT_RATE_LIMIT_EXCEEDED");
+        assert_eq!(body["error"]["details"]["limit"], PROJECT_EVENTS_PER_SEC);
+        let seconds = body["error"]["details"]["retry_after_seconds"]
+            .as_u64()
+            .expect("Body should carry retry_after_seconds");
+        assert_eq!(retry_after, Some(seconds.to_string()));
+    }
+
+    // Once the bucket refills the project can publish again
+    tokio::time::sleep(std::time::Duration::from_millis(600)).await;
+    let (status, _, body) = publish(&router, &first_key).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
@@ -0,0 +1,39 @@
+/// **Feature: realtime-saas-platform, Publish sequence numbers**
+///
+/// Publishing must return the JetStream stream sequence so clients can use it as
+/// a replay cursor.
+use realtime_api::event_service::PublishResult;
+use realtime_api::models::{BillingPlan, Event, Project, Tenant};
+use serde_json::json;
+
+mod common;
+
+use common::{test_database, test_event_service};
+
+#[tokio::test]
+async fn test_publish_returns_increasing_stream_sequence() {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone()).await;
+
+    let tenant = Tenant::new("Sequence Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    database.create_project(&project).await.expect("Failed to create project");
+
+    let mut sequences = Vec::new();
+    for i in 0..2 {
+        let event = Event::new(
+            tenant.id.clone(),
+            project.id.clone(),
+            "sequence.test".to_string(),
+            json!({ "n": i }),
+        );
+        match event_service.publish_event(&event).await.expect("Publish should succeed") {
+            PublishResult::Success { sequence } => sequences.push(sequence),
+            other => panic!("Unexpected publish result: {:?}", other),
+        }
+    }
+
+    assert!(sequences[0] > 0, "JetStream sequences start at 1");
+    assert!(sequences[1] > sequences[0]);
+}
@@ -0,0 +1,69 @@
+/// **Feature: realtime-saas-platform, Distributed rate limiting**
+///
+/// With the Redis store, every instance of the service draws from one token
+/// bucket per API key, so scaling out doesn't multiply a key's limit.
+use realtime_api::auth::{AuthError, AuthService};
+use realtime_api::database::Database;
+use realtime_api::models::{BillingPlan, Project, Scope, Tenant};
+use realtime_api::rate_limit::RedisRateLimitStore;
+use std::sync::Arc;
+use testcontainers::clients::Cli;
+use testcontainers_modules::redis::Redis;
+
+mod common;
+
+use common::test_database;
+
+// An instance of the service with its own Redis connection
+async fn instance(database: &Database, redis_url: &str) -> AuthService {
+    let store = RedisRateLimitStore::connect(redis_url)
+        .await
+        .expect("Failed to connect to Redis");
+    AuthService::new(database.clone(), "test_secret".to_string())
+        .with_rate_limit_store(Arc::new(store))
+}
+
+#[tokio::test]
+async fn test_instances_share_one_budget() {
+    let docker = Cli::default();
+    let redis = docker.run(Redis);
+    let redis_url = format!("redis://127.0.0.1:{}", redis.get_host_port_ipv4(6379));
+
+    let database = test_database().await;
+    let tenant = Tenant::new("Rate Limit Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    database.create_project(&project).await.expect("Failed to create project");
+
+    let first = instance(&database, &redis_url).await;
+    let second = instance(&database, &redis_url).await;
+    let (raw_key, _) = first
+        .create_api_key(
+            tenant.id.clone(),
+            project.id.clone(),
+            vec![Scope::EventsPublish],
+            4,
+            Vec::new(),
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+
+    // Four requests per second in total, split across both instances
+    for auth_service in [&first, &second, &first, &second] {
+        auth_service
+            .validate_api_key(&raw_key)
+            .await
+            .expect("Request within the shared budget");
+    }
+
+    for auth_service in [&first, &second] {
+        match auth_service.validate_api_key(&raw_key).await {
+            Err(AuthError::RateLimitExceeded(status)) => {
+                assert_eq!(status.limit, 4);
+                assert_eq!(status.remaining, 0);
+            }
+            other => panic!("Expected the shared budget to be spent, got {:?}", other.map(|_| ())),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+/// **Feature: realtime-saas-platform, Replay with server-side transformation**
+///
+/// Replaying with an upgrade transform must deliver transformed events while the
+/// events stored in the stream remain in their original format.
+use realtime_api::models::Event;
+use realtime_api::nats::{NatsClient, ReplayRequest};
+use realtime_api::transform::{TransformPipeline, TransformStep};
+use serde_json::json;
+use uuid::Uuid;
+
+fn replay_request(tenant_id: &str, project_id: &str, transform: Option<TransformPipeline>) -> ReplayRequest {
+    ReplayRequest {
+        tenant_id: tenant_id.to_string(),
+        project_id: project_id.to_string(),
+        topic: Some("order.created".to_string()),
+        cursor: None,
+        from_timestamp: None,
+        limit: Some(3),
+        transform,
+    }
+}
+
+#[tokio::test]
+async fn test_replay_with_upgrade_transform_leaves_stored_events_unchanged() {
+    let nats_url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://localhost:4222".to_string());
+    let stream_name = std::env::var("NATS_STREAM_NAME").unwrap_or_else(|_| "EVENTS".to_string());
+
+    let nats_client = NatsClient::new(&nats_url, stream_name)
+        .await
+        .expect("Failed to connect to NATS");
+
+    let tenant_id = Uuid::new_v4().to_string();
+    let project_id = Uuid::new_v4().to_string();
+
+    for i in 0..3 {
+        let event = Event::new(
+            tenant_id.clone(),
+            project_id.clone(),
+            "order.created".to_string(),
+            json!({ "order": i, "amount_cents": 100 * i }),
+        );
+        nats_client
+            .publish_event(&event)
+            .await
+            .expect("Failed to publish event");
+    }
+
+    // v1 -> v2: `order` became `order_id` and the payload moved under `data`
+    let upgrade = TransformPipeline::new(vec![
+        TransformStep::RenameField {
+            from: "order".to_string(),
+            to: "order_id".to_string(),
+        },
+        TransformStep::SetField {
+            field: "schema_version".to_string(),
+            value: json!(2),
+        },
+        TransformStep::WrapPayload {
+            key: "data".to_string(),
+        },
+    ]);
+
+    let transformed = nats_client
+        .replay_events(&replay_request(&tenant_id, &project_id, Some(upgrade)))
+        .await
+        .expect("Failed to replay with transform");
+
+    assert_eq!(transformed.len(), 3);
+    for (i, (event, _)) in transformed.iter().enumerate() {
+        assert_eq!(
+            event.payload,
+            json!({ "data": { "order_id": i, "amount_cents": 100 * i, "schema_version": 2 } })
+        );
+    }
+
+    // A plain replay still sees the original stored payloads
+    let originals = nats_client
+        .replay_events(&replay_request(&tenant_id, &project_id, None))
+        .await
+        .expect("Failed to replay without transform");
+
+    assert_eq!(originals.len(), 3);
+    for (i, ((original, original_cursor), (upgraded, upgraded_cursor))) in
+        originals.iter().zip(transformed.iter()).enumerate()
+    {
+        assert_eq!(original.payload, json!({ "order": i, "amount_cents": 100 * i }));
+        assert_eq!(original.id, upgraded.id);
+        assert_eq!(original_cursor.sequence, upgraded_cursor.sequence);
+    }
+}
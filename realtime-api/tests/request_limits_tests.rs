@@ -0,0 +1,190 @@
+/// **Feature: realtime-saas-platform, Request limits**
+///
+/// `create_router` rejects bodies over `max_request_body_bytes` on every
+/// route and answers REST requests, authentication included, with `408` once
+/// `request_timeout_secs` elapses. `/ws`, `/sse` and `/graphql/ws` stay open
+/// past the timeout.
+use axum::body::{Body, Bytes};
+use axum::http::{header, Request, StatusCode};
+use axum::Router;
+use futures_util::{SinkExt, StreamExt};
+use realtime_api::api::AppState;
+use realtime_api::config::HttpConfig;
+use realtime_api::graphql_ws::GRAPHQL_TRANSPORT_WS_PROTOCOL;
+use realtime_api::models::Scope;
+use realtime_api::request_signing::HMAC_SCHEME;
+use realtime_api::routes::create_router;
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+use tower::ServiceExt;
+
+mod common;
+
+use common::{create_project, test_state};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+async fn limited_state() -> AppState {
+    let mut state = test_state().await;
+    state.http = HttpConfig {
+        max_request_body_bytes: 1024,
+        request_timeout_secs: REQUEST_TIMEOUT.as_secs(),
+    };
+    state
+}
+
+/// A request whose body never finishes arriving
+fn stalled_request(method: &str, uri: &str) -> Request<Body> {
+    let body = futures_util::stream::pending::<Result<Bytes, std::io::Error>>();
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(
+            header::AUTHORIZATION,
+            format!("{} keyId=key_1,signature=abc,ts=0", HMAC_SCHEME),
+        )
+        .body(Body::from_stream(body))
+        .unwrap()
+}
+
+/// Serve the router on a local port and return a subscribe key
+async fn serve(state: AppState) -> (SocketAddr, String) {
+    let (tenant, project) = create_project(&state.database, "Request Limits Tenant").await;
+    let (raw_key, _) = state
+        .auth_service
+        .create_api_key(
+            tenant.id,
+            project.id,
+            vec![Scope::EventsSubscribe],
+            100,
+            vec![],
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = create_router(state);
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (addr, raw_key)
+}
+
+#[tokio::test]
+async fn test_router_rejects_oversized_bodies() {
+    let router: Router = create_router(limited_state().await);
+
+    for uri in ["/events", "/sse"] {
+        let request = Request::builder()
+            .method("POST")
+            .uri(uri)
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::CONTENT_LENGTH, 2048)
+            .body(Body::from(vec![b' '; 2048]))
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE, "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn test_authentication_runs_inside_the_request_timeout() {
+    let router = create_router(limited_state().await);
+
+    // A signed request is authenticated against its body, which never arrives
+    let response = tokio::time::timeout(
+        REQUEST_TIMEOUT * 5,
+        router.oneshot(stalled_request("POST", "/events")),
+    )
+    .await
+    .expect("Stalled authentication should time out")
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+}
+
+#[tokio::test]
+async fn test_sse_subscribe_is_exempt_from_the_request_timeout() {
+    let router = create_router(limited_state().await);
+
+    let pending = tokio::time::timeout(
+        REQUEST_TIMEOUT * 3,
+        router.oneshot(stalled_request("POST", "/sse")),
+    )
+    .await;
+    assert!(
+        pending.is_err(),
+        "/sse should not be answered with a timeout: {:?}",
+        pending.map(|response| response.map(|response| response.status()))
+    );
+}
+
+#[tokio::test]
+async fn test_websockets_stay_open_past_the_request_timeout() {
+    let (addr, key) = serve(limited_state().await).await;
+
+    let mut request = format!("ws://{}/ws?topics=orders.created", addr)
+        .into_client_request()
+        .unwrap();
+    request
+        .headers_mut()
+        .insert("authorization", format!("Bearer {}", key).parse().unwrap());
+    let (mut socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .expect("WebSocket handshake should succeed");
+
+    tokio::time::sleep(REQUEST_TIMEOUT * 2).await;
+    socket
+        .send(Message::Ping(b"still there".to_vec()))
+        .await
+        .expect("Connection should still be open");
+    let pong = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match socket.next().await {
+                Some(Ok(Message::Pong(payload))) => return payload,
+                Some(Ok(Message::Close(frame))) => panic!("Connection was closed: {:?}", frame),
+                Some(Ok(_)) => continue,
+                other => panic!("Expected a pong, got {:?}", other),
+            }
+        }
+    })
+    .await
+    .expect("No pong");
+    assert_eq!(pong, b"still there");
+
+    let mut request = format!("ws://{}/graphql/ws", addr)
+        .into_client_request()
+        .unwrap();
+    request.headers_mut().insert(
+        "sec-websocket-protocol",
+        GRAPHQL_TRANSPORT_WS_PROTOCOL.parse().unwrap(),
+    );
+    let (mut socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .expect("GraphQL WebSocket handshake should succeed");
+    let init =
+        json!({"type": "connection_init", "payload": {"authorization": format!("Bearer {}", key)}});
+    socket.send(Message::Text(init.to_string())).await.unwrap();
+
+    tokio::time::sleep(REQUEST_TIMEOUT * 2).await;
+    socket
+        .send(Message::Text(json!({"type": "ping"}).to_string()))
+        .await
+        .expect("Connection should still be open");
+    let mut received = Vec::new();
+    while let Ok(Some(Ok(Message::Text(text)))) =
+        tokio::time::timeout(Duration::from_secs(5), socket.next()).await
+    {
+        let message: Value = serde_json::from_str(&text).unwrap();
+        let done = message["type"] == "pong";
+        received.push(message["type"].clone());
+        if done {
+            break;
+        }
+    }
+    assert_eq!(received, vec![json!("connection_ack"), json!("pong")]);
+}
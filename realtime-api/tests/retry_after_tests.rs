@@ -0,0 +1,132 @@
+/// **Feature: realtime-saas-platform, Retry-After on throttling**
+///
+/// Throttled and overloaded requests, whether REST, SSE or a WebSocket
+/// upgrade, get a `Retry-After` header and an `ErrorResponse` body whose
+/// details carry `retry_after_seconds`, so clients can back off.
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use realtime_api::api::AppState;
+use realtime_api::models::{BillingPlan, Project, Scope, Tenant};
+use realtime_api::rate_limit::{overloaded_response, OVERLOADED_RETRY_AFTER_SECS};
+use realtime_api::routes::create_router;
+use serde_json::Value;
+use tower::ServiceExt;
+
+mod common;
+
+use common::test_state;
+
+/// A key allowed one request per second
+async fn throttled_key(state: &AppState) -> String {
+    let tenant = Tenant::new(
+        "Retry After Tenant".to_string(),
+        BillingPlan::Free {
+            monthly_events: 10000,
+        },
+    );
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    state
+        .database
+        .create_tenant(&tenant)
+        .await
+        .expect("Failed to create tenant");
+    state
+        .database
+        .create_project(&project)
+        .await
+        .expect("Failed to create project");
+
+    let (raw_key, _) = state
+        .auth_service
+        .create_api_key(
+            tenant.id,
+            project.id,
+            vec![Scope::EventsPublish, Scope::EventsSubscribe],
+            1,
+            vec![],
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+    raw_key
+}
+
+fn get(uri: &str, key: &str) -> Request<Body> {
+    Request::builder()
+        .uri(uri)
+        .header("authorization", format!("Bearer {}", key))
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Send `uri` twice with the same key and return the second response's
+/// `Retry-After` header and body
+async fn second_request(router: &Router, uri: &str, key: &str) -> (String, Value) {
+    let first = router.clone().oneshot(get(uri, key)).await.unwrap();
+    assert_ne!(first.status(), StatusCode::TOO_MANY_REQUESTS);
+    drop(first);
+
+    let response = router.clone().oneshot(get(uri, key)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after = response
+        .headers()
+        .get("retry-after")
+        .expect("Throttled response should carry Retry-After")
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (retry_after, serde_json::from_slice(&body).unwrap())
+}
+
+fn assert_retry_after_body(retry_after: &str, body: &Value) {
+    assert_eq!(body["error"]["code"], "RATE_LIMIT_EXCEEDED");
+    let seconds = body["error"]["details"]["retry_after_seconds"]
+        .as_u64()
+        .expect("Body should carry retry_after_seconds");
+    assert!(seconds >= 1);
+    assert_eq!(retry_after, seconds.to_string());
+}
+
+#[tokio::test]
+async fn test_rate_limited_rest_request_carries_retry_after() {
+    let state = test_state().await;
+    let key = throttled_key(&state).await;
+    let router = create_router(state);
+
+    let (retry_after, body) = second_request(&router, "/events", &key).await;
+    assert_retry_after_body(&retry_after, &body);
+}
+
+#[tokio::test]
+async fn test_rate_limited_sse_request_carries_retry_after() {
+    let state = test_state().await;
+    let key = throttled_key(&state).await;
+    let router = create_router(state);
+
+    let (retry_after, body) = second_request(&router, "/sse?topics=orders.created", &key).await;
+    assert_retry_after_body(&retry_after, &body);
+}
+
+#[tokio::test]
+async fn test_overloaded_response_carries_retry_after() {
+    let response = overloaded_response();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(
+        response.headers()["retry-after"],
+        OVERLOADED_RETRY_AFTER_SECS.to_string().as_str()
+    );
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["code"], "SERVICE_OVERLOADED");
+    assert_eq!(
+        body["error"]["details"]["retry_after_seconds"],
+        OVERLOADED_RETRY_AFTER_SECS
+    );
+}
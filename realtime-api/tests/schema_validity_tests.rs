@@ -0,0 +1,131 @@
+/// **Feature: realtime-saas-platform, Recorded schema validity**
+///
+/// Publishing records whether the payload conformed to the topic schema, and
+/// which version it was checked against, even when a warn-mode schema lets a
+/// violating event through. GraphQL exposes both on the stored event.
+use async_graphql::Request;
+use realtime_api::auth::{AuthContext, AuthService, AuthType};
+use realtime_api::config::GraphQLConfig;
+use realtime_api::event_service::{EventService, PublishResult};
+use realtime_api::graphql::{create_schema, ApiSchema};
+use realtime_api::models::{Event, Project, Scope, Tenant};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::{create_project, test_database, test_event_service};
+
+async fn publish(
+    service: &EventService,
+    tenant: &Tenant,
+    project: &Project,
+    topic: &str,
+    payload: Value,
+) -> String {
+    let event = Event::new(
+        tenant.id.clone(),
+        project.id.clone(),
+        topic.to_string(),
+        payload,
+    );
+    let result = service
+        .publish_event(&event)
+        .await
+        .expect("Publish should return a result");
+    assert!(
+        matches!(result, PublishResult::Success { .. }),
+        "{:?}",
+        result
+    );
+    event.id
+}
+
+async fn query_event(schema: &ApiSchema, auth: &AuthContext, id: &str) -> Value {
+    let query = format!(
+        r#"{{ event(id: "{}") {{ id schemaValid schemaVersion }} }}"#,
+        id
+    );
+    let response = schema.execute(Request::new(query).data(auth.clone())).await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    response.data.into_json().unwrap()["event"].clone()
+}
+
+#[tokio::test]
+async fn test_schema_validity_is_recorded_in_advisory_mode() {
+    let database = test_database().await;
+    let service = test_event_service(database.clone()).await;
+    let (tenant, project) = create_project(&database, "Schema Validity Tenant").await;
+
+    // A warn-mode schema reports violations without rejecting the event
+    let version = service.schema_validator().register_inferred_schema(
+        &project.id,
+        "order.placed",
+        &json!({"order_id": "o-1", "amount": 10}),
+    );
+
+    let conforming = publish(
+        &service,
+        &tenant,
+        &project,
+        "order.placed",
+        json!({"order_id": "o-2", "amount": 25}),
+    )
+    .await;
+    let violating = publish(
+        &service,
+        &tenant,
+        &project,
+        "order.placed",
+        json!({"order_id": 7}),
+    )
+    .await;
+    let unchecked = publish(
+        &service,
+        &tenant,
+        &project,
+        "order.shipped",
+        json!({"order_id": "o-2"}),
+    )
+    .await;
+
+    let stored = database
+        .get_event(&tenant.id, &violating)
+        .await
+        .unwrap()
+        .expect("Violating event should still be stored");
+    assert_eq!(stored.schema_valid, Some(false));
+    assert_eq!(stored.schema_version, Some(version as i32));
+
+    let schema = create_schema(
+        database.clone(),
+        service,
+        AuthService::new(database, "test_secret".to_string()),
+        100,
+        &GraphQLConfig::default(),
+    );
+    let auth = AuthContext {
+        tenant_id: tenant.id.clone(),
+        project_id: project.id.clone(),
+        scopes: vec![Scope::EventsSubscribe],
+        rate_limit_per_sec: 100,
+        allowed_topics: vec![],
+        auth_type: AuthType::ApiKey {
+            key_id: "key_123".to_string(),
+        },
+        user_id: None,
+        user_role: None,
+    };
+
+    let event = query_event(&schema, &auth, &conforming).await;
+    assert_eq!(event["schemaValid"], json!(true));
+    assert_eq!(event["schemaVersion"], json!(version));
+
+    let event = query_event(&schema, &auth, &violating).await;
+    assert_eq!(event["schemaValid"], json!(false));
+    assert_eq!(event["schemaVersion"], json!(version));
+
+    // Topics without a schema have nothing to violate
+    let event = query_event(&schema, &auth, &unchecked).await;
+    assert_eq!(event["schemaValid"], json!(true));
+    assert_eq!(event["schemaVersion"], Value::Null);
+}
@@ -0,0 +1,291 @@
+/// **Feature: realtime-saas-platform, HMAC request signing**
+///
+/// A request signed with an API key's signing secret authenticates with that
+/// key's scopes; a tampered body, a timestamp outside the skew window or a
+/// replay of an already accepted request is rejected.
+use chrono::Utc;
+use realtime_api::auth::{AuthError, AuthService, AuthType};
+use realtime_api::database::Database;
+use realtime_api::models::{ApiKey, BillingPlan, Project, Scope, Tenant};
+use realtime_api::request_signing::{sign_request, HmacAuthorization, MAX_SIGNATURE_SKEW_SECS};
+use std::time::Duration;
+
+mod common;
+
+use common::test_database;
+
+async fn create_signing_key(database: &Database, name: &str) -> (AuthService, ApiKey) {
+    let tenant = Tenant::new(name.to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    database.create_project(&project).await.expect("Failed to create project");
+
+    let auth_service = AuthService::new(database.clone(), "test_secret".to_string());
+    let (_, api_key) = auth_service
+        .create_api_key(
+            tenant.id.clone(),
+            project.id.clone(),
+            vec![Scope::EventsPublish],
+            100,
+            vec!["orders.>".to_string()],
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+    (auth_service, api_key)
+}
+
+fn signed(
+    auth_service: &AuthService,
+    api_key: &ApiKey,
+    method: &str,
+    path: &str,
+    timestamp: i64,
+    body: &[u8],
+) -> HmacAuthorization {
+    let secret = auth_service
+        .signing_secret(api_key)
+        .expect("New keys have a signing secret");
+    signed_with(&secret, &api_key.id, method, path, timestamp, body)
+}
+
+fn signed_with(
+    secret: &str,
+    key_id: &str,
+    method: &str,
+    path: &str,
+    timestamp: i64,
+    body: &[u8],
+) -> HmacAuthorization {
+    let header = format!(
+        "HMAC keyId={},signature={},ts={}",
+        key_id,
+        sign_request(secret, method, path, timestamp, body),
+        timestamp
+    );
+    HmacAuthorization::parse(&header).expect("Header should parse")
+}
+
+#[tokio::test]
+async fn test_valid_signature_uses_key_scopes() {
+    let database = test_database().await;
+    let (auth_service, api_key) = create_signing_key(&database, "Signed Request Tenant").await;
+    let body = br#"{"topic":"orders.created","payload":{}}"#;
+
+    let authorization = signed(
+        &auth_service,
+        &api_key,
+        "POST",
+        "/events",
+        Utc::now().timestamp(),
+        body,
+    );
+    let context = auth_service
+        .validate_signed_request(&authorization, "POST", "/events", body, None)
+        .await
+        .expect("Signed request should authenticate");
+
+    assert_eq!(context.tenant_id, api_key.tenant_id);
+    assert_eq!(context.project_id, api_key.project_id);
+    assert_eq!(context.scopes, vec![Scope::EventsPublish]);
+    assert!(context.allows_topic("orders.created"));
+    assert!(!context.allows_topic("billing.invoices"));
+    assert!(matches!(
+        context.auth_type,
+        AuthType::ApiKey { ref key_id } if *key_id == api_key.id
+    ));
+}
+
+#[tokio::test]
+async fn test_tampered_body_is_rejected() {
+    let database = test_database().await;
+    let (auth_service, api_key) = create_signing_key(&database, "Tampered Request Tenant").await;
+
+    let authorization = signed(
+        &auth_service,
+        &api_key,
+        "POST",
+        "/events",
+        Utc::now().timestamp(),
+        br#"{"topic":"orders.created","payload":{"amount":1}}"#,
+    );
+    let result = auth_service
+        .validate_signed_request(
+            &authorization,
+            "POST",
+            "/events",
+            br#"{"topic":"orders.created","payload":{"amount":1000}}"#,
+            None,
+        )
+        .await;
+    assert!(matches!(result, Err(AuthError::InvalidSignature)));
+
+    // Signing with another key's id doesn't verify either
+    let (_, other_key) = create_signing_key(&database, "Other Signing Tenant").await;
+    let mut authorization = signed(
+        &auth_service,
+        &api_key,
+        "GET",
+        "/events",
+        Utc::now().timestamp(),
+        b"",
+    );
+    authorization.key_id = other_key.id.clone();
+    let result = auth_service
+        .validate_signed_request(&authorization, "GET", "/events", b"", None)
+        .await;
+    assert!(matches!(result, Err(AuthError::InvalidSignature)));
+}
+
+#[tokio::test]
+async fn test_expired_timestamp_is_rejected() {
+    let database = test_database().await;
+    let (auth_service, api_key) = create_signing_key(&database, "Stale Signature Tenant").await;
+
+    for timestamp in [
+        Utc::now().timestamp() - MAX_SIGNATURE_SKEW_SECS - 60,
+        Utc::now().timestamp() + MAX_SIGNATURE_SKEW_SECS + 60,
+    ] {
+        // Correctly signed, but too old (or too far ahead) to accept
+        let authorization = signed(&auth_service, &api_key, "GET", "/events", timestamp, b"");
+        let result = auth_service
+            .validate_signed_request(&authorization, "GET", "/events", b"", None)
+            .await;
+        assert!(matches!(result, Err(AuthError::ExpiredSignature)));
+    }
+}
+
+#[tokio::test]
+async fn test_replayed_request_is_rejected() {
+    let database = test_database().await;
+    let (auth_service, api_key) = create_signing_key(&database, "Replayed Request Tenant").await;
+    let body = br#"{"topic":"orders.created","payload":{}}"#;
+
+    let authorization = signed(
+        &auth_service,
+        &api_key,
+        "POST",
+        "/events",
+        Utc::now().timestamp(),
+        body,
+    );
+    auth_service
+        .validate_signed_request(&authorization, "POST", "/events", body, None)
+        .await
+        .expect("Signed request should authenticate");
+
+    // The captured request is still fresh, but has been used
+    let result = auth_service
+        .validate_signed_request(&authorization, "POST", "/events", body, None)
+        .await;
+    assert!(matches!(result, Err(AuthError::ReplayedSignature)));
+}
+
+#[tokio::test]
+async fn test_signing_secret_is_stored_encrypted() {
+    let database = test_database().await;
+    let (auth_service, api_key) = create_signing_key(&database, "Encrypted Secret Tenant").await;
+    let secret = auth_service
+        .signing_secret(&api_key)
+        .expect("New keys have a signing secret");
+
+    let stored = database
+        .get_signing_api_key(&api_key.id)
+        .await
+        .expect("Failed to load API key")
+        .expect("Key should exist");
+    let stored_secret = stored
+        .signing_secret
+        .expect("Key should have a signing secret");
+    assert!(!stored_secret.contains(&secret));
+    assert_eq!(auth_service.signing_secret(&stored), Some(secret));
+
+    // Without the encryption key the stored secret is useless
+    let other_service = AuthService::new(database.clone(), "another_secret".to_string());
+    assert_eq!(other_service.signing_secret(&stored), None);
+    let authorization = signed(
+        &auth_service,
+        &api_key,
+        "GET",
+        "/events",
+        Utc::now().timestamp(),
+        b"",
+    );
+    let result = other_service
+        .validate_signed_request(&authorization, "GET", "/events", b"", None)
+        .await;
+    assert!(matches!(result, Err(AuthError::InvalidSignature)));
+}
+
+#[tokio::test]
+async fn test_rotation_replaces_signing_secret() {
+    let database = test_database().await;
+    let (auth_service, api_key) = create_signing_key(&database, "Rotated Secret Tenant").await;
+    let old_secret = auth_service
+        .signing_secret(&api_key)
+        .expect("New keys have a signing secret");
+
+    let (_, rotated) = auth_service
+        .rotate_api_key(&api_key.tenant_id, &api_key.id)
+        .await
+        .expect("Failed to rotate API key")
+        .expect("Key should exist");
+    let new_secret = auth_service
+        .signing_secret(&rotated)
+        .expect("Rotated keys have a signing secret");
+    assert_ne!(new_secret, old_secret);
+
+    // Both secrets work during the grace period
+    for secret in [&new_secret, &old_secret] {
+        let authorization = signed_with(
+            secret,
+            &api_key.id,
+            "GET",
+            "/events",
+            Utc::now().timestamp(),
+            b"",
+        );
+        auth_service
+            .validate_signed_request(&authorization, "GET", "/events", b"", None)
+            .await
+            .expect("Signed request should authenticate");
+    }
+
+    // Without a grace period only the newest secret is accepted
+    let no_grace = auth_service
+        .clone()
+        .with_rotation_grace_period(Duration::ZERO);
+    let (_, rotated) = no_grace
+        .rotate_api_key(&api_key.tenant_id, &api_key.id)
+        .await
+        .expect("Failed to rotate API key")
+        .expect("Key should exist");
+    let newest_secret = no_grace
+        .signing_secret(&rotated)
+        .expect("Rotated keys have a signing secret");
+    let authorization = signed_with(
+        &newest_secret,
+        &api_key.id,
+        "GET",
+        "/events",
+        Utc::now().timestamp(),
+        b"",
+    );
+    no_grace
+        .validate_signed_request(&authorization, "GET", "/events", b"", None)
+        .await
+        .expect("Signed request should authenticate");
+
+    let authorization = signed_with(
+        &new_secret,
+        &api_key.id,
+        "GET",
+        "/events",
+        Utc::now().timestamp(),
+        b"",
+    );
+    let result = no_grace
+        .validate_signed_request(&authorization, "GET", "/events", b"", None)
+        .await;
+    assert!(matches!(result, Err(AuthError::InvalidSignature)));
+}
@@ -0,0 +1,132 @@
+/// **Feature: realtime-saas-platform, Streaming endpoint authentication**
+///
+/// `/ws` and `/sse` authenticate in their handlers rather than behind the API
+/// key middleware, so a browser WebSocket can pass its key as `?access_token=`
+/// and header clients are validated once.
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use realtime_api::api::AppState;
+use realtime_api::models::Scope;
+use realtime_api::routes::create_router;
+use tower::ServiceExt;
+
+mod common;
+
+use common::{create_project, test_state};
+
+/// State and a key allowed to subscribe
+async fn subscriber() -> (AppState, String) {
+    let state = test_state().await;
+    let (tenant, project) = create_project(&state.database, "Streaming Tenant").await;
+    let (raw_key, _) = state
+        .auth_service
+        .create_api_key(
+            tenant.id,
+            project.id,
+            vec![Scope::EventsSubscribe],
+            1000,
+            vec![],
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+    (state, raw_key)
+}
+
+/// A WebSocket handshake request. `oneshot` has no connection to upgrade, so
+/// the request carries an upgrade that never completes; the handshake
+/// response is all these tests look at.
+fn websocket_request(uri: &str, authorization: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder()
+        .uri(uri)
+        .header("connection", "upgrade")
+        .header("upgrade", "websocket")
+        .header("sec-websocket-version", "13")
+        .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==");
+    if let Some(authorization) = authorization {
+        builder = builder.header("authorization", authorization);
+    }
+    let mut request = builder.body(Body::empty()).unwrap();
+    let on_upgrade = hyper::upgrade::on(&mut Request::new(()));
+    request.extensions_mut().insert(on_upgrade);
+    request
+}
+
+async fn status(router: &Router, request: Request<Body>) -> StatusCode {
+    router.clone().oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn test_websocket_accepts_query_access_token() {
+    let (state, key) = subscriber().await;
+    let router = create_router(state);
+
+    let uri = format!("/ws?topics=orders.created&access_token={}", key);
+    assert_eq!(
+        status(&router, websocket_request(&uri, None)).await,
+        StatusCode::SWITCHING_PROTOCOLS
+    );
+}
+
+#[tokio::test]
+async fn test_websocket_accepts_authorization_header() {
+    let (state, key) = subscriber().await;
+    let router = create_router(state);
+
+    let authorization = format!("Bearer {}", key);
+    assert_eq!(
+        status(&router, websocket_request("/ws", Some(&authorization))).await,
+        StatusCode::SWITCHING_PROTOCOLS
+    );
+}
+
+#[tokio::test]
+async fn test_websocket_rejects_bad_credentials() {
+    let (state, _) = subscriber().await;
+    let router = create_router(state);
+
+    // A bad header is refused before the upgrade
+    assert_eq!(
+        status(
+            &router,
+            websocket_request("/ws", Some("Bearer rk_not_a_key"))
+        )
+        .await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        status(&router, websocket_request("/ws", None)).await,
+        StatusCode::UNAUTHORIZED
+    );
+    // A bad query token completes the handshake so the browser sees the 4401 close
+    assert_eq!(
+        status(
+            &router,
+            websocket_request("/ws?access_token=rk_not_a_key", None)
+        )
+        .await,
+        StatusCode::SWITCHING_PROTOCOLS
+    );
+}
+
+#[tokio::test]
+async fn test_sse_authenticates_header_in_handler() {
+    let (state, key) = subscriber().await;
+    let router = create_router(state);
+
+    let request = Request::builder()
+        .uri("/sse?topics=orders.created")
+        .header("authorization", format!("Bearer {}", key))
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+    let request = Request::builder()
+        .uri("/sse?topics=orders.created")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(status(&router, request).await, StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,142 @@
+/// **Feature: realtime-saas-platform, Stripe payment webhooks**
+///
+/// Signed Stripe webhooks move the customer's tenant through the payment lifecycle:
+/// a failed invoice makes it past due, repeated failures or a cancelled
+/// subscription suspend it, and a paid invoice reactivates it unless it was
+/// suspended for something other than failed payments.
+use realtime_api::config::BillingConfig;
+use realtime_api::database::Database;
+use realtime_api::kill_switch::suspend_tenant;
+use realtime_api::models::{BillingPlan, Tenant, TenantStatus};
+use realtime_api::stripe_webhook::{
+    apply_event, sign_payload, verify_signature, SignatureError, StripeWebhookEvent,
+    DEFAULT_SUSPEND_AFTER_FAILED_PAYMENTS,
+};
+
+mod common;
+
+use common::test_database;
+
+const SECRET: &str = "whsec_fixture_secret";
+
+fn fixture(name: &str, customer_id: &str) -> Vec<u8> {
+    let path = format!("{}/tests/fixtures/stripe/{}.json", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(path)
+        .expect("Failed to read fixture")
+        .replace("{{CUSTOMER_ID}}", customer_id)
+        .into_bytes()
+}
+
+async fn tenant_status(database: &Database, tenant_id: &str) -> TenantStatus {
+    database.get_tenant(tenant_id).await.unwrap().unwrap().status
+}
+
+#[test]
+fn test_fixtures_parse_into_lifecycle_events() {
+    let (event_id, event) =
+        StripeWebhookEvent::parse(&fixture("invoice_payment_failed", "cus_1")).unwrap();
+    assert_eq!(event_id, "evt_1OdRmK2eZvKYlo2CnW1cBL8M");
+    assert_eq!(
+        event,
+        StripeWebhookEvent::PaymentFailed {
+            customer_id: "cus_1".to_string(),
+            attempt_count: 1,
+        }
+    );
+
+    let (_, event) = StripeWebhookEvent::parse(&fixture("invoice_paid", "cus_1")).unwrap();
+    assert_eq!(
+        event,
+        StripeWebhookEvent::InvoicePaid {
+            customer_id: "cus_1".to_string()
+        }
+    );
+
+    let (_, event) =
+        StripeWebhookEvent::parse(&fixture("customer_subscription_deleted", "cus_1")).unwrap();
+    assert_eq!(event.tenant_status(3), Some(TenantStatus::Suspended));
+}
+
+#[test]
+fn test_fixture_signatures_verify() {
+    let payload = fixture("invoice_payment_failed", "cus_1");
+    let now = chrono::Utc::now().timestamp();
+    let header = sign_payload(&payload, SECRET, now);
+
+    assert_eq!(verify_signature(&payload, &header, SECRET, now), Ok(()));
+
+    // Any change to the body invalidates the signature
+    let tampered = fixture("invoice_payment_failed", "cus_2");
+    assert_eq!(
+        verify_signature(&tampered, &header, SECRET, now),
+        Err(SignatureError::Mismatch)
+    );
+}
+
+#[tokio::test]
+async fn test_payment_lifecycle_updates_tenant_status() {
+    let database = test_database().await;
+
+    let customer_id = format!("cus_{}", uuid::Uuid::new_v4().simple());
+    let mut tenant = Tenant::new("Stripe Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    tenant.stripe_customer_id = Some(customer_id.clone());
+    database.create_tenant(&tenant).await.expect("Failed to create tenant");
+
+    // First failure: past due
+    let (_, failed) =
+        StripeWebhookEvent::parse(&fixture("invoice_payment_failed", &customer_id)).unwrap();
+    apply_event(&database, &failed, 3).await.unwrap();
+    assert_eq!(tenant_status(&database, &tenant.id).await, TenantStatus::PastDue);
+
+    // Paid: active again
+    let (_, paid) = StripeWebhookEvent::parse(&fixture("invoice_paid", &customer_id)).unwrap();
+    apply_event(&database, &paid, 3).await.unwrap();
+    assert_eq!(tenant_status(&database, &tenant.id).await, TenantStatus::Active);
+
+    // Third failed attempt on an invoice: suspended
+    let repeated = StripeWebhookEvent::PaymentFailed {
+        customer_id: customer_id.clone(),
+        attempt_count: 3,
+    };
+    let applied = apply_event(&database, &repeated, 3).await.unwrap();
+    assert_eq!(applied, Some((tenant.id.clone(), TenantStatus::Suspended)));
+    assert_eq!(tenant_status(&database, &tenant.id).await, TenantStatus::Suspended);
+
+    // Paying the invoice lifts the payment suspension
+    apply_event(&database, &paid, 3).await.unwrap();
+    assert_eq!(tenant_status(&database, &tenant.id).await, TenantStatus::Active);
+
+    // Unknown customers are acknowledged without changes
+    let unknown = StripeWebhookEvent::InvoicePaid {
+        customer_id: "cus_unknown".to_string(),
+    };
+    assert_eq!(apply_event(&database, &unknown, 3).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_paid_invoice_keeps_admin_suspension() {
+    let database = test_database().await;
+
+    let customer_id = format!("cus_{}", uuid::Uuid::new_v4().simple());
+    let mut tenant = Tenant::new("Suspended Stripe Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    tenant.stripe_customer_id = Some(customer_id.clone());
+    database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    suspend_tenant(&database, &tenant.id, "Abuse report", None)
+        .await
+        .expect("Failed to suspend tenant");
+
+    let (_, paid) = StripeWebhookEvent::parse(&fixture("invoice_paid", &customer_id)).unwrap();
+    assert_eq!(apply_event(&database, &paid, 3).await.unwrap(), None);
+    let (_, failed) =
+        StripeWebhookEvent::parse(&fixture("invoice_payment_failed", &customer_id)).unwrap();
+    assert_eq!(apply_event(&database, &failed, 3).await.unwrap(), None);
+    assert_eq!(tenant_status(&database, &tenant.id).await, TenantStatus::Suspended);
+}
+
+#[test]
+fn test_billing_config_defaults_to_the_failed_payment_limit() {
+    assert_eq!(
+        BillingConfig::default().suspend_after_failed_payments,
+        DEFAULT_SUSPEND_AFTER_FAILED_PAYMENTS
+    );
+}
@@ -0,0 +1,108 @@
+/// **Feature: realtime-saas-platform, Connection subscription limits**
+///
+/// A WebSocket connection may hold at most the project's
+/// `max_subscriptions_per_connection` distinct topics. Subscribes past the cap
+/// are rejected with an error while the topics already subscribed keep
+/// receiving events.
+use realtime_api::models::{ProjectLimits, ProjectLimitsUpdate};
+use realtime_api::websocket::{
+    check_subscription_limit, websocket_manager, WebSocketConnection, WebSocketMessage,
+    DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION,
+};
+use serde_json::json;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+fn topics(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+fn open_websocket(
+    tenant_id: &str,
+    subscribed: &[&str],
+) -> (String, broadcast::Receiver<WebSocketMessage>) {
+    let id = Uuid::new_v4().to_string();
+    let (sender, receiver) = broadcast::channel(64);
+    websocket_manager()
+        .add_connection(WebSocketConnection {
+            id: id.clone(),
+            tenant_id: tenant_id.to_string(),
+            project_id: "project".to_string(),
+            subscribed_topics: topics(subscribed),
+            sender,
+            created_at: chrono::Utc::now(),
+            acked: false,
+        })
+        .unwrap();
+    (id, receiver)
+}
+
+#[test]
+fn test_subscribing_past_the_limit_is_rejected() {
+    let tenant_id = Uuid::new_v4().to_string();
+    let (id, _receiver) = open_websocket(&tenant_id, &["orders.created", "orders.updated"]);
+    let manager = websocket_manager();
+
+    // Topics already held don't count twice
+    assert!(manager
+        .check_subscription_limit(&id, &topics(&["orders.created", "orders.shipped"]), 3)
+        .is_ok());
+
+    let message = manager
+        .check_subscription_limit(&id, &topics(&["orders.shipped", "orders.cancelled"]), 3)
+        .unwrap_err();
+    assert!(
+        message.contains("Subscription limit exceeded"),
+        "{}",
+        message
+    );
+
+    // The rejected subscribe leaves the existing subscriptions delivering
+    for topic in ["orders.created", "orders.updated"] {
+        let connections = manager.get_connections_for_event(&tenant_id, "project", topic);
+        assert_eq!(connections.len(), 1, "{}", topic);
+        assert_eq!(connections[0].id, id);
+    }
+    assert!(manager
+        .get_connections_for_event(&tenant_id, "project", "orders.cancelled")
+        .is_empty());
+
+    websocket_manager().remove_connection(&id);
+}
+
+#[test]
+fn test_initial_topics_count_towards_the_limit() {
+    assert!(check_subscription_limit(&[], &topics(&["a", "b", "a"]), 2).is_ok());
+    assert!(check_subscription_limit(&[], &topics(&["a", "b", "c"]), 2).is_err());
+}
+
+#[test]
+fn test_limit_is_configured_per_project() {
+    assert_eq!(
+        ProjectLimits::default().max_subscriptions_per_connection,
+        DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION
+    );
+
+    // Limits stored before the setting existed get the default
+    let stored: ProjectLimits = serde_json::from_value(json!({
+        "max_connections": 100,
+        "max_events_per_sec": 10,
+        "max_payload_size": 1024
+    }))
+    .unwrap();
+    assert_eq!(
+        stored.max_subscriptions_per_connection,
+        DEFAULT_MAX_SUBSCRIPTIONS_PER_CONNECTION
+    );
+
+    let updated = ProjectLimitsUpdate {
+        max_subscriptions_per_connection: Some(5),
+        ..ProjectLimitsUpdate::default()
+    }
+    .apply_to(&stored);
+    assert_eq!(updated.max_subscriptions_per_connection, 5);
+
+    let mut invalid = updated;
+    invalid.max_subscriptions_per_connection = 0;
+    assert!(invalid.validate().is_err());
+}
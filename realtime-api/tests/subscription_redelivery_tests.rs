@@ -0,0 +1,207 @@
+/// **Feature: realtime-saas-platform, Acked subscription delivery**
+///
+/// An acking subscription's event is acked only after it reaches the
+/// subscriber. A failed send is redelivered, and an event that fails on every
+/// allowed delivery is dead-lettered, and replaying it redelivers the event to
+/// that subscription alone. Acked WebSocket and SSE connections redeliver
+/// events their full buffer has no room for.
+use realtime_api::backpressure::spawn_acked_delivery;
+use realtime_api::event_service::{DeadLetterReplay, EventService, PublishResult, SendError};
+use realtime_api::models::{Event, Project, Tenant};
+use realtime_api::nats::{DeadLetterKind, NatsClient, SubscriptionConfig};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+mod common;
+
+use common::{create_project, test_database, test_event_service, test_nats_client};
+
+// Long enough for the nacked first delivery to come back
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(3);
+
+async fn setup() -> (EventService, NatsClient, Tenant, Project) {
+    let database = test_database().await;
+    let (tenant, project) = create_project(&database, "Redelivery Tenant").await;
+    let event_service = test_event_service(database).await;
+    (event_service, test_nats_client().await, tenant, project)
+}
+
+fn subscription(project: &Project, max_deliver: i64) -> SubscriptionConfig {
+    SubscriptionConfig {
+        tenant_id: project.tenant_id.clone(),
+        project_id: project.id.clone(),
+        topics: vec!["orders.created".to_string()],
+        consumer_name: format!("redelivery_{}", project.id),
+        durable: false,
+        max_ack_pending: None,
+        explicit_ack: true,
+        ack_wait: Some(Duration::from_secs(10)),
+        max_deliver: Some(max_deliver),
+    }
+}
+
+// Create the consumer and publish one event for it to deliver
+async fn publish_to(
+    service: &EventService,
+    nats_client: &NatsClient,
+    config: &SubscriptionConfig,
+) -> Event {
+    nats_client.create_consumer(config).await.expect("Failed to create consumer");
+
+    let event = Event::new(
+        config.tenant_id.clone(),
+        config.project_id.clone(),
+        "orders.created".to_string(),
+        json!({"order_id": "o-1"}),
+    );
+    let result = service.publish_event(&event).await.expect("Failed to publish");
+    assert!(matches!(result, PublishResult::Success { .. }), "{:?}", result);
+    event
+}
+
+#[tokio::test]
+async fn test_failed_send_is_redelivered() {
+    let (service, nats_client, _tenant, project) = setup().await;
+    let config = subscription(&project, 3);
+    let event = publish_to(&service, &nats_client, &config).await;
+
+    let attempts = Arc::new(Mutex::new(Vec::new()));
+    let recorded = attempts.clone();
+    let delivery = service.deliver_subscription(&config, move |event| {
+        let recorded = recorded.clone();
+        async move {
+            let mut attempts = recorded.lock().unwrap();
+            attempts.push(event.id.clone());
+            if attempts.len() == 1 {
+                Err(SendError::Transient("outbound buffer full".to_string()))
+            } else {
+                Ok(())
+            }
+        }
+    });
+    // Delivery runs until the subscriber disconnects, which this one never does
+    let _ = tokio::time::timeout(DELIVERY_TIMEOUT, delivery).await;
+
+    // Delivered again after the failed attempt, then acked and not repeated
+    assert_eq!(*attempts.lock().unwrap(), vec![event.id.clone(), event.id.clone()]);
+    let dead_letters = service
+        .list_dead_letters(&project.tenant_id, Some(&project.id), event.published_at, 10)
+        .await
+        .expect("Failed to list dead letters");
+    assert!(dead_letters.is_empty());
+}
+
+#[tokio::test]
+async fn test_event_is_dead_lettered_after_max_deliveries() {
+    let (service, nats_client, _tenant, project) = setup().await;
+    let config = subscription(&project, 2);
+    let event = publish_to(&service, &nats_client, &config).await;
+
+    let attempts = Arc::new(Mutex::new(0));
+    let counted = attempts.clone();
+    let delivery = service.deliver_subscription(&config, move |_| {
+        *counted.lock().unwrap() += 1;
+        async { Err(SendError::Transient("socket write timed out".to_string())) }
+    });
+    let _ = tokio::time::timeout(DELIVERY_TIMEOUT, delivery).await;
+
+    assert_eq!(*attempts.lock().unwrap(), 2);
+    let dead_letters = service
+        .list_dead_letters(&project.tenant_id, Some(&project.id), event.published_at, 10)
+        .await
+        .expect("Failed to list dead letters");
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0].kind, DeadLetterKind::Subscription);
+    assert_eq!(dead_letters[0].event.id, event.id);
+    assert_eq!(dead_letters[0].reason, "socket write timed out");
+}
+
+// Run a subscription for a while, recording the ids of the events it delivers
+async fn delivered_ids(
+    service: &EventService,
+    config: &SubscriptionConfig,
+    succeed: bool,
+) -> Vec<String> {
+    let delivered = Arc::new(Mutex::new(Vec::new()));
+    let recorded = delivered.clone();
+    let delivery = service.deliver_subscription(config, move |event| {
+        recorded.lock().unwrap().push(event.id);
+        async move {
+            if succeed {
+                Ok(())
+            } else {
+                Err(SendError::Transient("socket write timed out".to_string()))
+            }
+        }
+    });
+    let _ = tokio::time::timeout(DELIVERY_TIMEOUT, delivery).await;
+    let ids = delivered.lock().unwrap().clone();
+    ids
+}
+
+#[tokio::test]
+async fn test_replayed_dead_letter_reaches_only_its_subscription() {
+    let (service, nats_client, _tenant, project) = setup().await;
+    let failing = subscription(&project, 1);
+    let mut healthy = subscription(&project, 1);
+    healthy.consumer_name = format!("healthy_{}", project.id);
+    nats_client.create_consumer(&healthy).await.expect("Failed to create consumer");
+    let event = publish_to(&service, &nats_client, &failing).await;
+
+    assert_eq!(delivered_ids(&service, &healthy, true).await, vec![event.id.clone()]);
+    assert_eq!(delivered_ids(&service, &failing, false).await, vec![event.id.clone()]);
+
+    let dead_letters = service
+        .list_dead_letters(&project.tenant_id, Some(&project.id), event.published_at, 10)
+        .await
+        .expect("Failed to list dead letters");
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0].target.as_deref(), Some(failing.consumer_name.as_str()));
+
+    let (_, result) = service
+        .replay_dead_letter(&project.tenant_id, dead_letters[0].id)
+        .await
+        .expect("Failed to replay dead letter")
+        .expect("Dead letter should exist");
+    assert!(matches!(result, DeadLetterReplay::Requeued { .. }), "{:?}", result);
+
+    // Only the subscription that failed sees the event again
+    assert_eq!(delivered_ids(&service, &failing, true).await, vec![event.id.clone()]);
+    assert!(delivered_ids(&service, &healthy, true).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_acked_connection_redelivers_when_buffer_is_full() {
+    let (service, nats_client, _tenant, project) = setup().await;
+    let config = subscription(&project, 5);
+    let first = publish_to(&service, &nats_client, &config).await;
+    let second = Event::new(
+        project.tenant_id.clone(),
+        project.id.clone(),
+        "orders.created".to_string(),
+        json!({"order_id": "o-2"}),
+    );
+    service.publish_event(&second).await.expect("Failed to publish");
+
+    // Room for one event: the second is nacked rather than overwriting the first
+    let (sender, mut receiver) = broadcast::channel(1);
+    let delivery = spawn_acked_delivery(service.clone(), config, sender, 1, |event| {
+        event.id.clone()
+    });
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let received = tokio::time::timeout(DELIVERY_TIMEOUT, receiver.recv())
+        .await
+        .expect("First event should be delivered")
+        .expect("Buffer should not have overflowed");
+    assert_eq!(received, first.id);
+    let received = tokio::time::timeout(DELIVERY_TIMEOUT * 2, receiver.recv())
+        .await
+        .expect("Second event should be redelivered")
+        .expect("Buffer should not have overflowed");
+    assert_eq!(received, second.id);
+
+    delivery.abort();
+}
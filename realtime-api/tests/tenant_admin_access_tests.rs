@@ -0,0 +1,182 @@
+/// **Feature: realtime-saas-platform, Tenant admin access**
+///
+/// A tenant's admin:write keys may suspend and reactivate only their own
+/// tenant; only keys of the configured platform admin tenant manage others.
+/// The platform-wide SLA summary and tenant log level overrides are reserved
+/// for platform admin keys.
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use realtime_api::api::AppState;
+use realtime_api::models::{Scope, Tenant, TenantStatus};
+use realtime_api::routes::create_router;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+mod common;
+
+use common::{create_project, test_state};
+
+/// A tenant with an admin:write key
+async fn tenant_with_key(state: &AppState, name: &str) -> (Tenant, String) {
+    let (tenant, project) = create_project(&state.database, name).await;
+    let (raw_key, _) = state
+        .auth_service
+        .create_api_key(
+            tenant.id.clone(),
+            project.id.clone(),
+            vec![Scope::AdminRead, Scope::AdminWrite],
+            1000,
+            vec![],
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+    (tenant, raw_key)
+}
+
+async fn post(router: &Router, key: &str, uri: &str) -> (StatusCode, Value) {
+    send(router, "POST", key, uri, None).await
+}
+
+async fn send(
+    router: &Router,
+    method: &str,
+    key: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("authorization", format!("Bearer {}", key));
+    let request = match body {
+        Some(body) => request
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string())),
+        None => request.body(Body::empty()),
+    }
+    .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+async fn tenant_status(state: &AppState, tenant_id: &str) -> TenantStatus {
+    state
+        .database
+        .get_tenant(tenant_id)
+        .await
+        .expect("Failed to load tenant")
+        .expect("Tenant should exist")
+        .status
+}
+
+#[tokio::test]
+async fn test_admin_cannot_suspend_another_tenant() {
+    let state = test_state().await;
+    let (_, key) = tenant_with_key(&state, "Hostile Tenant").await;
+    let (victim, _) = tenant_with_key(&state, "Victim Tenant").await;
+    let router = create_router(state.clone());
+
+    for uri in [
+        format!("/admin/tenants/{}/suspend", victim.id),
+        format!("/billing/suspend/{}", victim.id),
+    ] {
+        let (status, body) = post(&router, &key, &uri).await;
+        assert_eq!(status, StatusCode::FORBIDDEN, "{}", uri);
+        assert_eq!(body["error"]["code"], "TENANT_ACCESS_DENIED");
+    }
+    assert_ne!(
+        tenant_status(&state, &victim.id).await,
+        TenantStatus::Suspended
+    );
+}
+
+#[tokio::test]
+async fn test_platform_admin_suspends_any_tenant() {
+    let mut state = test_state().await;
+    let (platform, key) = tenant_with_key(&state, "Platform Tenant").await;
+    let (customer, _) = tenant_with_key(&state, "Customer Tenant").await;
+    state.auth_service = state
+        .auth_service
+        .clone()
+        .with_platform_admin_tenant(Some(platform.id.clone()));
+    let router = create_router(state.clone());
+
+    let uri = format!("/admin/tenants/{}/suspend", customer.id);
+    let (status, body) = post(&router, &key, &uri).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        tenant_status(&state, &customer.id).await,
+        TenantStatus::Suspended
+    );
+
+    let uri = format!("/admin/tenants/{}/unsuspend", customer.id);
+    let (status, body) = post(&router, &key, &uri).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        tenant_status(&state, &customer.id).await,
+        TenantStatus::Active
+    );
+}
+
+#[tokio::test]
+async fn test_only_platform_admin_reads_sla_summary() {
+    let mut state = test_state().await;
+    let (platform, platform_key) = tenant_with_key(&state, "Platform Tenant").await;
+    let (_, key) = tenant_with_key(&state, "Customer Tenant").await;
+    state.auth_service = state
+        .auth_service
+        .clone()
+        .with_platform_admin_tenant(Some(platform.id.clone()));
+    let router = create_router(state);
+
+    let (status, body) = send(&router, "GET", &key, "/admin/sla", None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{}", body);
+    assert_eq!(body["error"]["code"], "PLATFORM_ADMIN_REQUIRED");
+
+    let (status, body) = send(&router, "GET", &platform_key, "/admin/sla", None).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
+
+#[tokio::test]
+async fn test_admin_cannot_change_another_tenants_log_level() {
+    let mut state = test_state().await;
+    let (platform, _) = tenant_with_key(&state, "Platform Tenant").await;
+    let (_, key) = tenant_with_key(&state, "Hostile Tenant").await;
+    let (victim, _) = tenant_with_key(&state, "Victim Tenant").await;
+    state.auth_service = state
+        .auth_service
+        .clone()
+        .with_platform_admin_tenant(Some(platform.id.clone()));
+    let router = create_router(state);
+
+    let uri = format!("/admin/tenants/{}/log-level", victim.id);
+    let (status, body) = send(&router, "PUT", &key, &uri, Some(json!({"level": "trace"}))).await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{}", body);
+    assert_eq!(body["error"]["code"], "PLATFORM_ADMIN_REQUIRED");
+
+    let (status, body) = send(&router, "DELETE", &key, &uri, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{}", body);
+    assert_eq!(body["error"]["code"], "PLATFORM_ADMIN_REQUIRED");
+}
+
+#[tokio::test]
+async fn test_log_level_override_for_unknown_tenant_is_not_found() {
+    let mut state = test_state().await;
+    let (platform, key) = tenant_with_key(&state, "Platform Tenant").await;
+    state.auth_service = state
+        .auth_service
+        .clone()
+        .with_platform_admin_tenant(Some(platform.id.clone()));
+    let router = create_router(state);
+
+    let uri = format!("/admin/tenants/{}/log-level", uuid::Uuid::new_v4());
+    let (status, body) = send(&router, "PUT", &key, &uri, Some(json!({"level": "debug"}))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{}", body);
+    assert_eq!(body["error"]["code"], "TENANT_NOT_FOUND");
+}
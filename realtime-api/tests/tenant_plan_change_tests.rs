@@ -0,0 +1,242 @@
+/// **Feature: realtime-saas-platform, Tenant plan changes**
+///
+/// `PATCH /admin/tenants/{id}/plan` lets the platform admin upgrade or downgrade
+/// a tenant, moving its projects to the new plan's limits. A downgrade below
+/// this cycle's usage is reported as a warning and never suspends the tenant.
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use realtime_api::api::{update_tenant_plan, AppState, UpdateTenantPlanRequest};
+use realtime_api::auth::{AuthContext, AuthType};
+use realtime_api::models::{BillingPlan, Project, ProjectLimits, Scope, Tenant, UsageMetric};
+
+mod common;
+
+use common::test_state;
+
+async fn create_tenant(state: &AppState, plan: BillingPlan) -> (Tenant, Project) {
+    let tenant = Tenant::new("Plan Change Tenant".to_string(), plan);
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    state.database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    state.database.create_project(&project).await.expect("Failed to create project");
+    (tenant, project)
+}
+
+const PLATFORM_TENANT_ID: &str = "platform-admin-tenant";
+
+/// State in which `platform_admin()` credentials manage every tenant
+async fn platform_state() -> AppState {
+    let mut state = test_state().await;
+    state.auth_service = state
+        .auth_service
+        .clone()
+        .with_platform_admin_tenant(Some(PLATFORM_TENANT_ID.to_string()));
+    state
+}
+
+fn platform_admin() -> AuthContext {
+    admin(PLATFORM_TENANT_ID, "platform-admin-project")
+}
+
+fn admin(tenant_id: &str, project_id: &str) -> AuthContext {
+    AuthContext {
+        tenant_id: tenant_id.to_string(),
+        project_id: project_id.to_string(),
+        scopes: vec![Scope::AdminWrite],
+        rate_limit_per_sec: 100,
+        allowed_topics: vec![],
+        auth_type: AuthType::ApiKey {
+            key_id: "admin_key".to_string(),
+        },
+        user_id: None,
+        user_role: None,
+    }
+}
+
+fn plan_request(plan: &str) -> Json<UpdateTenantPlanRequest> {
+    Json(UpdateTenantPlanRequest { plan: plan.to_string() })
+}
+
+#[tokio::test]
+async fn test_free_to_pro_upgrade() {
+    let state = platform_state().await;
+    let (tenant, _) = create_tenant(&state, BillingPlan::Free { monthly_events: 10000 }).await;
+
+    let response = update_tenant_plan(
+        Extension(platform_admin()),
+        Path(tenant.id.clone()),
+        State(state.clone()),
+        plan_request("pro"),
+    )
+    .await
+    .expect("Plan should change");
+
+    assert!(matches!(response.plan, BillingPlan::Pro { monthly_events: 100000, .. }));
+    assert!(response.warnings.is_empty());
+
+    let stored = state.database.get_tenant(&tenant.id).await.unwrap().unwrap();
+    assert!(matches!(stored.plan, BillingPlan::Pro { .. }));
+}
+
+#[tokio::test]
+async fn test_pro_to_free_downgrade_over_limit_warns_without_suspending() {
+    let state = platform_state().await;
+    let (tenant, project) = create_tenant(
+        &state,
+        BillingPlan::Pro {
+            monthly_events: 100000,
+            price_per_event: 0.001,
+        },
+    )
+    .await;
+    state
+        .database
+        .increment_usage(
+            &tenant.id,
+            &project.id,
+            UsageMetric::EventsPublished,
+            25000,
+            chrono::Utc::now(),
+            None,
+        )
+        .await
+        .expect("Failed to record usage");
+
+    let response = update_tenant_plan(
+        Extension(platform_admin()),
+        Path(tenant.id.clone()),
+        State(state.clone()),
+        plan_request("free"),
+    )
+    .await
+    .expect("Plan should change");
+
+    assert!(matches!(response.plan, BillingPlan::Free { monthly_events: 10000 }));
+    assert_eq!(response.warnings.len(), 1);
+    assert_eq!(response.warnings[0].code, "USAGE_EXCEEDS_PLAN");
+    assert_eq!(response.warnings[0].usage, 25000);
+    assert_eq!(response.warnings[0].limit, 10000);
+
+    let stored = state.database.get_tenant(&tenant.id).await.unwrap().unwrap();
+    assert!(matches!(stored.plan, BillingPlan::Free { .. }));
+    assert_eq!(stored.status, tenant.status);
+}
+
+#[tokio::test]
+async fn test_unknown_plan_is_rejected() {
+    let state = platform_state().await;
+    let (tenant, _) = create_tenant(&state, BillingPlan::Free { monthly_events: 10000 }).await;
+
+    let err = update_tenant_plan(
+        Extension(platform_admin()),
+        Path(tenant.id.clone()),
+        State(state),
+        plan_request("platinum"),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(err.code(), "INVALID_PLAN");
+}
+
+#[tokio::test]
+async fn test_tenant_admin_cannot_change_own_plan() {
+    let state = platform_state().await;
+    let (tenant, project) = create_tenant(&state, BillingPlan::Free { monthly_events: 10000 }).await;
+
+    let err = update_tenant_plan(
+        Extension(admin(&tenant.id, &project.id)),
+        Path(tenant.id.clone()),
+        State(state.clone()),
+        plan_request("enterprise"),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(err.status(), StatusCode::FORBIDDEN);
+    assert_eq!(err.code(), "PLATFORM_ADMIN_REQUIRED");
+    let stored = state.database.get_tenant(&tenant.id).await.unwrap().unwrap();
+    assert!(matches!(stored.plan, BillingPlan::Free { .. }));
+}
+
+#[tokio::test]
+async fn test_downgrade_moves_projects_to_new_plan_limits() {
+    let state = platform_state().await;
+    let pro = BillingPlan::Pro {
+        monthly_events: 100000,
+        price_per_event: 0.001,
+    };
+    let (tenant, project) = create_tenant(&state, pro.clone()).await;
+    let mut customized = ProjectLimits::for_plan(&pro);
+    customized.max_events_per_sec = 5;
+    customized.max_connections = 500;
+    state
+        .database
+        .update_project_limits(&tenant.id, &project.id, &customized)
+        .await
+        .expect("Failed to set limits");
+
+    update_tenant_plan(
+        Extension(platform_admin()),
+        Path(tenant.id.clone()),
+        State(state.clone()),
+        plan_request("free"),
+    )
+    .await
+    .expect("Plan should change");
+
+    let free = ProjectLimits::for_plan(&BillingPlan::Free { monthly_events: 10000 });
+    let stored = state
+        .database
+        .get_project_with_tenant(&tenant.id, &project.id)
+        .await
+        .unwrap()
+        .unwrap();
+    // Defaults follow the plan, lowered limits are kept and raised ones capped
+    assert_eq!(stored.limits.max_payload_size, free.max_payload_size);
+    assert_eq!(stored.limits.max_events_per_sec, 5);
+    assert_eq!(stored.limits.max_connections, free.max_connections);
+}
+
+#[tokio::test]
+async fn test_downgrade_only_counts_usage_of_current_cycle() {
+    let state = platform_state().await;
+    let (tenant, project) = create_tenant(
+        &state,
+        BillingPlan::Pro {
+            monthly_events: 100000,
+            price_per_event: 0.001,
+        },
+    )
+    .await;
+    let now = chrono::Utc::now();
+    state
+        .database
+        .increment_usage(
+            &tenant.id,
+            &project.id,
+            UsageMetric::EventsPublished,
+            25000,
+            now - chrono::Duration::days(40),
+            None,
+        )
+        .await
+        .expect("Failed to record usage");
+    state
+        .database
+        .start_usage_cycle(&tenant.id, now - chrono::Duration::days(1))
+        .await
+        .expect("Failed to start usage cycle");
+
+    let response = update_tenant_plan(
+        Extension(platform_admin()),
+        Path(tenant.id.clone()),
+        State(state),
+        plan_request("free"),
+    )
+    .await
+    .expect("Plan should change");
+
+    assert!(response.warnings.is_empty());
+}
@@ -0,0 +1,130 @@
+/// **Feature: realtime-saas-platform, Tenant read endpoint**
+///
+/// `GET /admin/tenants/:id` lets a tenant's admin keys read its plan and
+/// status, such as to see it has been suspended, but never another tenant's.
+/// It is the one endpoint a suspended or past-due tenant can still call.
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use axum::Router;
+use realtime_api::api::AppState;
+use realtime_api::models::{BillingPlan, Project, Scope, Tenant, TenantStatus};
+use realtime_api::routes::create_router;
+use serde_json::Value;
+use tower::ServiceExt;
+
+mod common;
+
+use common::test_state;
+
+/// A tenant with an admin:read key
+async fn tenant_with_key(state: &AppState, name: &str) -> (Tenant, String) {
+    let mut tenant = Tenant::new(
+        name.to_string(),
+        BillingPlan::Free {
+            monthly_events: 10000,
+        },
+    );
+    tenant.stripe_customer_id = Some(format!("cus_{}", &tenant.id[..8]));
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    state
+        .database
+        .create_tenant(&tenant)
+        .await
+        .expect("Failed to create tenant");
+    state
+        .database
+        .create_project(&project)
+        .await
+        .expect("Failed to create project");
+
+    let (raw_key, _) = state
+        .auth_service
+        .create_api_key(
+            tenant.id.clone(),
+            project.id.clone(),
+            vec![Scope::AdminRead],
+            1000,
+            vec![],
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+    (tenant, raw_key)
+}
+
+async fn get_tenant(router: &Router, key: &str, tenant_id: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("GET")
+        .uri(format!("/admin/tenants/{}", tenant_id))
+        .header("authorization", format!("Bearer {}", key))
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_admin_reads_own_tenant() {
+    let state = test_state().await;
+    let (tenant, key) = tenant_with_key(&state, "Readable Tenant").await;
+    let router = create_router(state);
+
+    let (status, body) = get_tenant(&router, &key, &tenant.id).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["id"], tenant.id);
+    assert_eq!(body["name"], "Readable Tenant");
+    assert_eq!(body["status"], "trial");
+    assert_eq!(
+        body["stripe_customer_id"],
+        tenant.stripe_customer_id.clone().unwrap()
+    );
+    assert_eq!(body["plan"], serde_json::to_value(&tenant.plan).unwrap());
+}
+
+#[tokio::test]
+async fn test_other_tenant_is_forbidden() {
+    let state = test_state().await;
+    let (_, key) = tenant_with_key(&state, "Curious Tenant").await;
+    let (other, _) = tenant_with_key(&state, "Private Tenant").await;
+    let router = create_router(state);
+
+    let (status, body) = get_tenant(&router, &key, &other.id).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["error"]["code"], "TENANT_ACCESS_DENIED");
+    assert!(body.get("name").is_none());
+}
+
+#[tokio::test]
+async fn test_inactive_tenant_reads_own_status() {
+    let state = test_state().await;
+    let router = create_router(state.clone());
+
+    for (status, expected) in [
+        (TenantStatus::Suspended, "suspended"),
+        (TenantStatus::PastDue, "past_due"),
+    ] {
+        let (tenant, key) = tenant_with_key(&state, "Lapsed Tenant").await;
+        state
+            .database
+            .update_tenant_status(&tenant.id, status)
+            .await
+            .expect("Failed to update tenant status");
+
+        let (code, body) = get_tenant(&router, &key, &tenant.id).await;
+        assert_eq!(code, StatusCode::OK, "{}", body);
+        assert_eq!(body["status"], expected);
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("/billing/usage")
+            .header("authorization", format!("Bearer {}", key))
+            .body(Body::empty())
+            .unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN, "{}", expected);
+    }
+}
@@ -0,0 +1,237 @@
+/// **Feature: realtime-saas-platform, Per-tenant JetStream streams**
+///
+/// With tenant streams enabled each tenant's events are stored in a stream of
+/// its own, created on its first publish, so one tenant's retention and storage
+/// limits never apply to another's events. Events published to the shared
+/// stream before tenant streams were enabled are still replayed, and durable
+/// subscriptions deliver what they had left there before moving over.
+use async_nats::jetstream::consumer::pull::Config as ConsumerConfig;
+use async_nats::jetstream::stream::RetentionPolicy;
+use futures_util::StreamExt;
+use realtime_api::config::{StreamRetention, TenantStreamConfig};
+use realtime_api::models::Event;
+use realtime_api::nats::{EventCursor, NatsClient, ReplayRequest, SubscriptionConfig};
+use serde_json::json;
+use std::time::Duration;
+use uuid::Uuid;
+
+mod common;
+
+use common::test_nats_client;
+
+async fn setup(defaults: &TenantStreamConfig) -> NatsClient {
+    test_nats_client().await.with_tenant_streams(defaults)
+}
+
+fn event(tenant_id: &str, project_id: &str, n: u32) -> Event {
+    Event::new(
+        tenant_id.to_string(),
+        project_id.to_string(),
+        "orders.created".to_string(),
+        json!({ "n": n }),
+    )
+}
+
+fn retention(max_age_secs: u64, max_bytes: i64) -> TenantStreamConfig {
+    TenantStreamConfig {
+        enabled: true,
+        max_age_secs,
+        max_bytes,
+        retention: StreamRetention::Limits,
+    }
+}
+
+fn replay_request(tenant_id: &str, project_id: &str) -> ReplayRequest {
+    ReplayRequest {
+        tenant_id: tenant_id.to_string(),
+        project_id: project_id.to_string(),
+        topic: None,
+        cursor: None,
+        from_timestamp: None,
+        limit: Some(10),
+        transform: None,
+    }
+}
+
+#[tokio::test]
+async fn test_tenants_events_land_in_separate_streams() {
+    let nats_client = setup(&retention(24 * 60 * 60, 1024 * 1024)).await;
+    let tenant_a = Uuid::new_v4().to_string();
+    let tenant_b = Uuid::new_v4().to_string();
+    let project_id = Uuid::new_v4().to_string();
+
+    nats_client
+        .ensure_tenant_stream(&tenant_a, &retention(60 * 60, 512 * 1024))
+        .await
+        .expect("Failed to create tenant stream");
+    nats_client
+        .ensure_tenant_stream(&tenant_b, &retention(7 * 24 * 60 * 60, 4 * 1024 * 1024))
+        .await
+        .expect("Failed to create tenant stream");
+
+    let mut events = Vec::new();
+    for tenant_id in [&tenant_a, &tenant_b] {
+        let event = Event::new(
+            tenant_id.clone(),
+            project_id.clone(),
+            "orders.created".to_string(),
+            json!({ "tenant": tenant_id }),
+        );
+        nats_client.publish_event(&event).await.expect("Failed to publish event");
+        events.push(event);
+    }
+
+    let mut a = nats_client
+        .jetstream()
+        .get_stream(nats_client.tenant_stream_name(&tenant_a))
+        .await
+        .expect("Tenant A should have its own stream");
+    let mut b = nats_client
+        .jetstream()
+        .get_stream(nats_client.tenant_stream_name(&tenant_b))
+        .await
+        .expect("Tenant B should have its own stream");
+    let a = a.info().await.expect("Failed to get stream info").clone();
+    let b = b.info().await.expect("Failed to get stream info").clone();
+
+    assert_ne!(a.config.name, b.config.name);
+    assert_eq!(a.state.messages, 1);
+    assert_eq!(b.state.messages, 1);
+
+    // Each stream keeps the retention it was created with
+    assert_eq!(a.config.max_age, Duration::from_secs(60 * 60));
+    assert_eq!(a.config.max_bytes, 512 * 1024);
+    assert_eq!(b.config.max_age, Duration::from_secs(7 * 24 * 60 * 60));
+    assert_eq!(b.config.max_bytes, 4 * 1024 * 1024);
+
+    // Replay reads each tenant's events from its own stream
+    for (tenant_id, event) in [&tenant_a, &tenant_b].into_iter().zip(&events) {
+        let replayed: Vec<Event> = nats_client
+            .replay(replay_request(tenant_id, &project_id))
+            .await
+            .expect("Failed to replay")
+            .collect()
+            .await;
+        let ids: Vec<&str> = replayed.iter().map(|event| event.id.as_str()).collect();
+        assert_eq!(ids, vec![event.id.as_str()]);
+    }
+}
+
+#[tokio::test]
+async fn test_tenant_stream_is_created_on_first_publish() {
+    let defaults = retention(2 * 60 * 60, 2 * 1024 * 1024);
+    let nats_client = setup(&defaults).await;
+    let tenant_id = Uuid::new_v4().to_string();
+    let stream_name = nats_client.tenant_stream_name(&tenant_id);
+
+    assert!(nats_client.jetstream().get_stream(&stream_name).await.is_err());
+
+    let event = Event::new(
+        tenant_id.clone(),
+        Uuid::new_v4().to_string(),
+        "orders.created".to_string(),
+        json!({ "n": 1 }),
+    );
+    nats_client.publish_event(&event).await.expect("Failed to publish event");
+
+    let mut stream = nats_client
+        .jetstream()
+        .get_stream(&stream_name)
+        .await
+        .expect("The first publish should create the tenant's stream");
+    let info = stream.info().await.expect("Failed to get stream info");
+    assert_eq!(info.state.messages, 1);
+    assert_eq!(info.config.max_age, Duration::from_secs(defaults.max_age_secs));
+    assert_eq!(info.config.max_bytes, defaults.max_bytes);
+    assert_eq!(info.config.retention, RetentionPolicy::Limits);
+    assert_eq!(info.config.subjects, vec![format!("tenant.{}.*.>", tenant_id)]);
+}
+
+#[tokio::test]
+async fn test_history_from_before_tenant_streams_is_still_replayed() {
+    // A client publishing every tenant's events to the shared stream
+    let shared = test_nats_client().await;
+    let nats_client = setup(&retention(60 * 60, 1024 * 1024)).await;
+    let tenant_id = Uuid::new_v4().to_string();
+    let project_id = Uuid::new_v4().to_string();
+
+    let before = event(&tenant_id, &project_id, 1);
+    let before_sequence = shared.publish_event(&before).await.expect("Failed to publish event");
+    let after = event(&tenant_id, &project_id, 2);
+    nats_client.publish_event(&after).await.expect("Failed to publish event");
+
+    let replayed: Vec<Event> = nats_client
+        .replay(replay_request(&tenant_id, &project_id))
+        .await
+        .expect("Failed to replay")
+        .collect()
+        .await;
+    let ids: Vec<&str> = replayed.iter().map(|event| event.id.as_str()).collect();
+    assert_eq!(ids, vec![before.id.as_str(), after.id.as_str()]);
+
+    // Resuming from a shared stream sequence picks up from there
+    let (_, last_sequence) = nats_client
+        .sequence_bounds(&tenant_id)
+        .await
+        .expect("Failed to get sequence bounds");
+    assert!(last_sequence >= before_sequence);
+    let mut request = replay_request(&tenant_id, &project_id);
+    request.cursor = Some(EventCursor::at_sequence(before_sequence));
+    let resumed: Vec<Event> = nats_client
+        .replay(request)
+        .await
+        .expect("Failed to replay")
+        .collect()
+        .await;
+    let ids: Vec<&str> = resumed.iter().map(|event| event.id.as_str()).collect();
+    assert_eq!(ids, vec![before.id.as_str(), after.id.as_str()]);
+}
+
+#[tokio::test]
+async fn test_durable_subscription_drains_its_shared_stream_consumer() {
+    let shared = test_nats_client().await;
+    let nats_client = setup(&retention(60 * 60, 1024 * 1024)).await;
+    let tenant_id = Uuid::new_v4().to_string();
+    let project_id = Uuid::new_v4().to_string();
+    let mut config = SubscriptionConfig::acked(
+        &tenant_id,
+        &project_id,
+        vec!["orders.created".to_string()],
+        format!("durable_{}", project_id),
+    );
+    config.durable = true;
+
+    shared.create_consumer(&config).await.expect("Failed to create consumer");
+    let before = event(&tenant_id, &project_id, 1);
+    shared.publish_event(&before).await.expect("Failed to publish event");
+    let after = event(&tenant_id, &project_id, 2);
+    nats_client.publish_event(&after).await.expect("Failed to publish event");
+
+    let deliveries = nats_client
+        .subscription_deliveries(&config)
+        .await
+        .expect("Failed to subscribe");
+    tokio::pin!(deliveries);
+    let mut delivered = Vec::new();
+    while delivered.len() < 2 {
+        let delivery = tokio::time::timeout(Duration::from_secs(5), deliveries.next())
+            .await
+            .expect("Events should be delivered")
+            .expect("Deliveries should not end")
+            .expect("Failed to receive delivery");
+        delivery.ack().await.expect("Failed to ack");
+        delivered.push(delivery.event.id);
+    }
+    assert_eq!(delivered, vec![before.id, after.id]);
+
+    // The drained consumer is gone from the shared stream
+    let stream = nats_client
+        .jetstream()
+        .get_stream(std::env::var("NATS_STREAM_NAME").unwrap_or_else(|_| "EVENTS".to_string()))
+        .await
+        .expect("Failed to get shared stream");
+    assert!(stream
+        .get_consumer::<ConsumerConfig>(&config.consumer_name)
+        .await
+        .is_err());
+}
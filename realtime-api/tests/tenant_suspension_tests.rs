@@ -1 +1,73 @@
+/// **Feature: realtime-saas-platform, Tenant kill switch**
+///
+/// Suspending a tenant marks it suspended, terminates its WebSocket and SSE
+/// connections in one call, and records an audit entry; unsuspending reactivates it.
+use realtime_api::kill_switch::{
+    suspend_tenant, unsuspend_tenant, SUSPEND_AUDIT_ACTION, UNSUSPEND_AUDIT_ACTION,
+};
+use realtime_api::models::{BillingPlan, Tenant, TenantStatus};
+use realtime_api::sse::{sse_manager, SSEConnection};
+use realtime_api::websocket::{websocket_manager, WebSocketConnection};
+use tokio::sync::broadcast;
+use uuid::Uuid;
 
+mod common;
+
+use common::test_database;
+
+#[tokio::test]
+async fn test_suspend_terminates_both_transports() {
+    let database = test_database().await;
+    let tenant = Tenant::new("Kill Switch Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    database.create_tenant(&tenant).await.expect("Failed to create tenant");
+
+    let websocket_id = Uuid::new_v4().to_string();
+    websocket_manager()
+        .add_connection(WebSocketConnection {
+            id: websocket_id.clone(),
+            tenant_id: tenant.id.clone(),
+            project_id: "project".to_string(),
+            subscribed_topics: vec![],
+            sender: broadcast::channel(8).0,
+            created_at: chrono::Utc::now(),
+            acked: false,
+        })
+        .unwrap();
+
+    let sse_id = Uuid::new_v4().to_string();
+    sse_manager()
+        .add_connection(SSEConnection {
+            id: sse_id.clone(),
+            tenant_id: tenant.id.clone(),
+            project_id: "project".to_string(),
+            subscribed_topics: vec![],
+            sender: broadcast::channel(8).0,
+            created_at: chrono::Utc::now(),
+            acked: false,
+        })
+        .unwrap();
+
+    let mut terminated = suspend_tenant(&database, &tenant.id, "Manual review", None)
+        .await
+        .unwrap();
+    terminated.sort();
+    let mut expected = vec![websocket_id, sse_id];
+    expected.sort();
+    assert_eq!(terminated, expected);
+
+    assert_eq!(websocket_manager().get_tenant_connection_count(&tenant.id), 0);
+    assert_eq!(sse_manager().get_tenant_connection_count(&tenant.id), 0);
+    let status = database.get_tenant(&tenant.id).await.unwrap().unwrap().status;
+    assert_eq!(status, TenantStatus::Suspended);
+
+    unsuspend_tenant(&database, &tenant.id, "Review complete", None)
+        .await
+        .unwrap();
+    let status = database.get_tenant(&tenant.id).await.unwrap().unwrap().status;
+    assert_eq!(status, TenantStatus::Active);
+
+    assert_eq!(
+        database.get_audit_actions(&tenant.id).await.unwrap(),
+        vec![UNSUSPEND_AUDIT_ACTION.to_string(), SUSPEND_AUDIT_ACTION.to_string()]
+    );
+}
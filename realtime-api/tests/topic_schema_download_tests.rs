@@ -0,0 +1,103 @@
+/// **Feature: realtime-saas-platform, Topic schema download**
+///
+/// `GET /projects/{id}/topics/{topic}/schema` returns the effective schema with its
+/// version for the caller's own projects, and 404 when no schema exists.
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Extension;
+use realtime_api::api::{get_topic_schema, AppState};
+use realtime_api::auth::{AuthContext, AuthType};
+use realtime_api::models::{BillingPlan, Project, Scope, Tenant};
+use realtime_api::schema_validator::SchemaMode;
+use serde_json::json;
+
+mod common;
+
+use common::test_state;
+
+async fn create_project(state: &AppState) -> (Tenant, Project) {
+    let tenant = Tenant::new("Schema Download Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    state.database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    state.database.create_project(&project).await.expect("Failed to create project");
+    (tenant, project)
+}
+
+fn subscriber(tenant: &Tenant, project: &Project) -> AuthContext {
+    AuthContext {
+        tenant_id: tenant.id.clone(),
+        project_id: project.id.clone(),
+        scopes: vec![Scope::EventsSubscribe],
+        rate_limit_per_sec: 100,
+        allowed_topics: vec![],
+        auth_type: AuthType::ApiKey {
+            key_id: "key_123".to_string(),
+        },
+        user_id: None,
+        user_role: None,
+    }
+}
+
+#[tokio::test]
+async fn test_registered_schema_is_returned_with_version() {
+    let state = test_state().await;
+    let (tenant, project) = create_project(&state).await;
+
+    let validator = state.event_service.schema_validator();
+    validator.register_schema(&project.id, "order.placed", json!({"type": "object"}));
+    let schema = json!({"type": "object", "required": ["order_id"]});
+    validator.register_schema(&project.id, "order.placed", schema.clone());
+
+    let response = get_topic_schema(
+        State(state.clone()),
+        Extension(subscriber(&tenant, &project)),
+        Path((project.id.clone(), "order.placed".to_string())),
+    )
+    .await
+    .expect("Schema should be returned");
+
+    assert_eq!(response.version, 2);
+    assert_eq!(response.mode, SchemaMode::Enforce);
+    assert_eq!(response.schema, schema);
+    assert_eq!(response.topic, "order.placed");
+}
+
+#[tokio::test]
+async fn test_unknown_topic_yields_404() {
+    let state = test_state().await;
+    let (tenant, project) = create_project(&state).await;
+
+    let err = get_topic_schema(
+        State(state),
+        Extension(subscriber(&tenant, &project)),
+        Path((project.id.clone(), "unknown.topic".to_string())),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(err.status(), StatusCode::NOT_FOUND);
+    assert_eq!(err.code(), "SCHEMA_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn test_other_tenants_project_yields_404() {
+    let state = test_state().await;
+    let (tenant, project) = create_project(&state).await;
+    let (_, other_project) = create_project(&state).await;
+
+    state
+        .event_service
+        .schema_validator()
+        .register_schema(&other_project.id, "order.placed", json!({"type": "object"}));
+
+    let err = get_topic_schema(
+        State(state),
+        Extension(subscriber(&tenant, &project)),
+        Path((other_project.id.clone(), "order.placed".to_string())),
+    )
+    .await
+    .unwrap_err();
+
+    assert_eq!(err.status(), StatusCode::NOT_FOUND);
+    assert_eq!(err.code(), "PROJECT_NOT_FOUND");
+}
@@ -0,0 +1,130 @@
+/// **Feature: realtime-saas-platform, Topic schema registry**
+///
+/// Schemas registered for a topic are persisted in `topic_schemas`, so a fresh
+/// validator backed by the same database picks up the active version, and a newer
+/// registration replaces the cached schema once the validator resyncs.
+use realtime_api::schema_validator::{SchemaMode, SchemaValidator};
+use serde_json::json;
+use std::time::Duration;
+
+mod common;
+
+use common::{create_project, test_database};
+
+#[tokio::test]
+async fn test_registered_schema_is_loaded_by_fresh_validator() {
+    let database = test_database().await;
+    let (tenant, project) = create_project(&database, "Registry Tenant").await;
+
+    let writer = SchemaValidator::new().with_store(database.clone());
+    let version = writer
+        .store_schema(
+            &tenant.id,
+            &project.id,
+            "orders.created",
+            json!({"type": "object", "required": ["order_id"]}),
+            SchemaMode::Enforce,
+        )
+        .await
+        .expect("Failed to store schema");
+    assert_eq!(version, 1);
+
+    let stored = database
+        .get_active_topic_schema(&project.id, "orders.created")
+        .await
+        .unwrap()
+        .expect("Schema should be persisted");
+    assert_eq!(stored.version, 1);
+    assert_eq!(stored.mode, "enforce");
+
+    // A validator that never saw the registration loads it from the store
+    let reader = SchemaValidator::new().with_store(database.clone());
+    assert!(reader.get_schema(&project.id, "orders.created").is_none());
+    reader.sync_topic(&project.id, "orders.created").await.unwrap();
+    let active = reader
+        .get_schema(&project.id, "orders.created")
+        .expect("Schema should be loaded from the store");
+    assert_eq!(active.version, 1);
+    assert!(reader
+        .validate_project_event(&project.id, "orders.created", &json!({}))
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_updated_schema_replaces_cached_version() {
+    let database = test_database().await;
+    let (tenant, project) = create_project(&database, "Registry Tenant").await;
+
+    let writer = SchemaValidator::new().with_store(database.clone());
+    let reader = SchemaValidator::new()
+        .with_store(database.clone())
+        .with_sync_interval(Duration::ZERO);
+
+    writer
+        .store_schema(
+            &tenant.id,
+            &project.id,
+            "users.updated",
+            json!({"type": "object", "required": ["user_id"]}),
+            SchemaMode::Enforce,
+        )
+        .await
+        .unwrap();
+    reader.sync_topic(&project.id, "users.updated").await.unwrap();
+    assert!(reader
+        .validate_project_event(&project.id, "users.updated", &json!({"email": "a@b.c"}))
+        .is_err());
+
+    let version = writer
+        .store_schema(
+            &tenant.id,
+            &project.id,
+            "users.updated",
+            json!({"type": "object", "required": ["email"]}),
+            SchemaMode::Enforce,
+        )
+        .await
+        .unwrap();
+    assert_eq!(version, 2);
+
+    reader.sync_topic(&project.id, "users.updated").await.unwrap();
+    assert_eq!(reader.get_schema(&project.id, "users.updated").unwrap().version, 2);
+    assert!(reader
+        .validate_project_event(&project.id, "users.updated", &json!({"email": "a@b.c"}))
+        .is_ok());
+
+    // Deleting the topic's schemas drops it from validators on their next sync
+    assert!(writer.delete_schema(&tenant.id, &project.id, "users.updated").await.unwrap());
+    reader.sync_topic(&project.id, "users.updated").await.unwrap();
+    assert!(reader.get_schema(&project.id, "users.updated").is_none());
+}
+
+#[tokio::test]
+async fn test_concurrent_registrations_get_distinct_versions() {
+    let database = test_database().await;
+    let (tenant, project) = create_project(&database, "Registry Tenant").await;
+
+    let registrations = (0..4).map(|i| {
+        let database = database.clone();
+        let (tenant_id, project_id) = (tenant.id.clone(), project.id.clone());
+        tokio::spawn(async move {
+            database
+                .create_topic_schema(
+                    &tenant_id,
+                    &project_id,
+                    "orders.created",
+                    &json!({"type": "object", "title": format!("v{}", i)}),
+                    "enforce",
+                )
+                .await
+        })
+    });
+
+    let mut versions = Vec::new();
+    for registration in registrations.collect::<Vec<_>>() {
+        let version = registration.await.unwrap();
+        versions.push(version.expect("Registration should not conflict"));
+    }
+    versions.sort();
+    assert_eq!(versions, vec![1, 2, 3, 4]);
+}
@@ -0,0 +1,76 @@
+/// **Feature: realtime-saas-platform, Usage aggregation**
+///
+/// Usage accrues into one row per tenant/project/metric/window (and API key), so
+/// per-connection and per-event tracking doesn't insert a row each time.
+use realtime_api::models::{BillingPlan, Project, Tenant, UsageMetric};
+
+mod common;
+
+use common::test_database;
+
+#[tokio::test]
+async fn test_concurrent_increments_accumulate_in_one_row() {
+    let database = test_database().await;
+    let tenant = Tenant::new("Usage Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    database.create_project(&project).await.expect("Failed to create project");
+
+    let window_start = chrono::Utc::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+        .and_utc();
+
+    let increments = (0..50).map(|_| {
+        let database = database.clone();
+        let (tenant_id, project_id) = (tenant.id.clone(), project.id.clone());
+        tokio::spawn(async move {
+            database
+                .increment_usage(
+                    &tenant_id,
+                    &project_id,
+                    UsageMetric::WebSocketMinutes,
+                    1,
+                    window_start,
+                    None,
+                )
+                .await
+        })
+    });
+    for increment in futures_util::future::join_all(increments).await {
+        increment.unwrap().expect("Failed to increment usage");
+    }
+
+    let records = database.get_usage_records(&project.id, None, None, None).await.unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].quantity, 50);
+    assert_eq!(records[0].window_start, window_start);
+
+    // A different window or key starts its own row
+    let next_window = window_start + chrono::Duration::days(1);
+    database
+        .increment_usage(&tenant.id, &project.id, UsageMetric::WebSocketMinutes, 2, next_window, None)
+        .await
+        .unwrap();
+    database
+        .increment_usage(
+            &tenant.id,
+            &project.id,
+            UsageMetric::WebSocketMinutes,
+            3,
+            window_start,
+            Some("key-1"),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(database.get_usage_records(&project.id, None, None, None).await.unwrap().len(), 3);
+    assert_eq!(
+        database
+            .get_usage_for_tenant(&tenant.id, UsageMetric::WebSocketMinutes)
+            .await
+            .unwrap(),
+        55
+    );
+}
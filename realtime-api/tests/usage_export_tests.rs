@@ -0,0 +1,191 @@
+/// **Feature: realtime-saas-platform, Raw usage export**
+///
+/// `GET /admin/usage/export` streams the tenant's raw usage records as CSV by
+/// default, or as JSON with `format=json`, and requires the billing read scope.
+use axum::extract::{Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::Response;
+use axum::Extension;
+use realtime_api::api::{
+    export_usage, AppState, UsageExportFormat, UsageExportQuery, USAGE_EXPORT_CSV_HEADER,
+};
+use realtime_api::auth::{AuthContext, AuthType};
+use realtime_api::models::{BillingPlan, Project, Scope, Tenant, UsageMetric};
+use realtime_api::usage_warnings::usage_window_start;
+
+mod common;
+
+use common::test_state;
+
+// A tenant with 42 events published this month
+async fn tenant_with_usage(state: &AppState) -> (Tenant, Project) {
+    let tenant = Tenant::new("Export Tenant".to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    state.database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    state.database.create_project(&project).await.expect("Failed to create project");
+    state
+        .database
+        .increment_usage(
+            &tenant.id,
+            &project.id,
+            UsageMetric::EventsPublished,
+            42,
+            usage_window_start(chrono::Utc::now()),
+            None,
+        )
+        .await
+        .expect("Failed to record usage");
+    (tenant, project)
+}
+
+fn auth(tenant: &Tenant, project: &Project, scopes: Vec<Scope>) -> AuthContext {
+    AuthContext {
+        tenant_id: tenant.id.clone(),
+        project_id: project.id.clone(),
+        scopes,
+        rate_limit_per_sec: 100,
+        allowed_topics: vec![],
+        auth_type: AuthType::ApiKey {
+            key_id: "export_key".to_string(),
+        },
+        user_id: None,
+        user_role: None,
+    }
+}
+
+fn export_query(format: UsageExportFormat) -> Query<UsageExportQuery> {
+    Query(UsageExportQuery {
+        from: None,
+        to: None,
+        format,
+    })
+}
+
+async fn body_text(response: Response) -> String {
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("Failed to read body");
+    String::from_utf8(bytes.to_vec()).expect("Export is UTF-8")
+}
+
+#[tokio::test]
+async fn test_csv_export_has_header_and_rows() {
+    let state = test_state().await;
+    let (tenant, project) = tenant_with_usage(&state).await;
+
+    let response = export_usage(
+        State(state),
+        Extension(auth(&tenant, &project, vec![Scope::BillingRead])),
+        export_query(UsageExportFormat::Csv),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        response.headers()[header::CONTENT_TYPE],
+        "text/csv; charset=utf-8"
+    );
+
+    let csv = body_text(response).await;
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some(USAGE_EXPORT_CSV_HEADER));
+    let row: Vec<&str> = lines.next().expect("one data row").split(',').collect();
+    assert_eq!(row[0], tenant.id);
+    assert_eq!(row[1], project.id);
+    assert_eq!(row[2], "events_published");
+    assert_eq!(row[3], "42");
+}
+
+#[tokio::test]
+async fn test_json_export_lists_records() {
+    let state = test_state().await;
+    let (tenant, project) = tenant_with_usage(&state).await;
+
+    let response = export_usage(
+        State(state),
+        Extension(auth(&tenant, &project, vec![Scope::BillingRead])),
+        export_query(UsageExportFormat::Json),
+    )
+    .await
+    .unwrap();
+
+    let rows: Vec<serde_json::Value> = serde_json::from_str(&body_text(response).await).unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["project_id"], project.id);
+    assert_eq!(rows[0]["metric"], "events_published");
+    assert_eq!(rows[0]["quantity"], 42);
+}
+
+#[tokio::test]
+async fn test_export_requires_billing_read() {
+    let state = test_state().await;
+    let (tenant, project) = tenant_with_usage(&state).await;
+
+    let err = export_usage(
+        State(state),
+        Extension(auth(&tenant, &project, vec![Scope::EventsPublish])),
+        export_query(UsageExportFormat::Csv),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_export_covers_every_project_of_the_tenant_only() {
+    let state = test_state().await;
+    let (tenant, project) = tenant_with_usage(&state).await;
+    let (other_tenant, _) = tenant_with_usage(&state).await;
+
+    // Usage of an archived project is still billed, so it is exported too
+    let archived = Project::new(tenant.id.clone(), "archived".to_string());
+    state.database.create_project(&archived).await.expect("Failed to create project");
+    state
+        .database
+        .increment_usage(
+            &tenant.id,
+            &archived.id,
+            UsageMetric::ApiRequests,
+            7,
+            usage_window_start(chrono::Utc::now()),
+            None,
+        )
+        .await
+        .expect("Failed to record usage");
+    state
+        .database
+        .archive_project(&tenant.id, &archived.id)
+        .await
+        .expect("Failed to archive project");
+
+    let response = export_usage(
+        State(state),
+        Extension(auth(&tenant, &project, vec![Scope::BillingRead])),
+        export_query(UsageExportFormat::Json),
+    )
+    .await
+    .unwrap();
+
+    let rows: Vec<serde_json::Value> = serde_json::from_str(&body_text(response).await).unwrap();
+    let mut exported: Vec<(String, i64)> = rows
+        .iter()
+        .map(|row| {
+            assert_ne!(row["tenant_id"], other_tenant.id);
+            (
+                row["project_id"].as_str().unwrap().to_string(),
+                row["quantity"].as_i64().unwrap(),
+            )
+        })
+        .collect();
+    exported.sort();
+    let mut expected = vec![(project.id.clone(), 42), (archived.id.clone(), 7)];
+    expected.sort();
+    assert_eq!(exported, expected);
+}
+
+#[test]
+fn test_export_format_defaults_to_csv() {
+    let query: UsageExportQuery = serde_json::from_str("{}").unwrap();
+    assert_eq!(query.format, UsageExportFormat::Csv);
+    let query: UsageExportQuery = serde_json::from_str(r#"{"format":"json"}"#).unwrap();
+    assert_eq!(query.format, UsageExportFormat::Json);
+}
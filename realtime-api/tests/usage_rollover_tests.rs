@@ -0,0 +1,285 @@
+/// **Feature: realtime-saas-platform, Billing cycle usage rollover**
+///
+/// When a tenant's billing cycle ends its usage totals are archived into usage
+/// history, the current window starts from zero while the raw records are kept,
+/// and suspensions made solely for exceeding usage are lifted while payment
+/// suspensions stay in place.
+use chrono::{DateTime, Months, Utc};
+use realtime_api::database::Database;
+use realtime_api::kill_switch::suspend_tenant;
+use realtime_api::models::{TenantStatus, UsageMetric};
+use realtime_api::usage_cycle::{
+    roll_over_due_cycles, UsageCycleAnchor, USAGE_LIMIT_SUSPENSION_REASON,
+};
+use realtime_api::usage_warnings::usage_window_start;
+use sqlx::Row;
+
+mod common;
+
+use common::{create_project, test_database};
+
+struct HistoryEntry {
+    project_id: String,
+    metric: String,
+    quantity: i64,
+    cycle_start: DateTime<Utc>,
+    cycle_end: DateTime<Utc>,
+}
+
+async fn usage_history(database: &Database, tenant_id: &str) -> Vec<HistoryEntry> {
+    sqlx::query(
+        "SELECT project_id, metric::text AS metric, quantity, cycle_start, cycle_end FROM usage_history WHERE tenant_id = $1 ORDER BY quantity",
+    )
+    .bind(tenant_id)
+    .fetch_all(database.pool())
+    .await
+    .expect("Failed to read usage history")
+    .iter()
+    .map(|row| HistoryEntry {
+        project_id: row.get("project_id"),
+        metric: row.get("metric"),
+        quantity: row.get("quantity"),
+        cycle_start: row.get("cycle_start"),
+        cycle_end: row.get("cycle_end"),
+    })
+    .collect()
+}
+
+async fn usage_record_count(database: &Database, tenant_id: &str) -> i64 {
+    sqlx::query("SELECT COUNT(*) AS count FROM usage_records WHERE tenant_id = $1")
+        .bind(tenant_id)
+        .fetch_one(database.pool())
+        .await
+        .expect("Failed to count usage records")
+        .get("count")
+}
+
+#[tokio::test]
+async fn test_rollover_archives_usage_and_zeroes_current_window() {
+    let database = test_database().await;
+    let (tenant, project) = create_project(&database, "Rollover Tenant").await;
+
+    let now = Utc::now();
+    let this_cycle = usage_window_start(now);
+    let last_cycle = this_cycle - Months::new(1);
+    database
+        .start_usage_cycle(&tenant.id, last_cycle)
+        .await
+        .unwrap();
+
+    // Two daily windows in the cycle that just ended
+    for (quantity, window_start) in [
+        (120, last_cycle),
+        (30, last_cycle + chrono::Duration::days(3)),
+    ] {
+        database
+            .increment_usage(
+                &tenant.id,
+                &project.id,
+                UsageMetric::EventsPublished,
+                quantity,
+                window_start,
+                None,
+            )
+            .await
+            .unwrap();
+    }
+    database
+        .increment_usage(
+            &tenant.id,
+            &project.id,
+            UsageMetric::WebSocketMinutes,
+            45,
+            last_cycle,
+            None,
+        )
+        .await
+        .unwrap();
+
+    roll_over_due_cycles(&database, UsageCycleAnchor::CalendarMonth, now)
+        .await
+        .unwrap();
+
+    for metric in [UsageMetric::EventsPublished, UsageMetric::WebSocketMinutes] {
+        assert_eq!(
+            database
+                .get_usage_for_tenant(&tenant.id, metric)
+                .await
+                .unwrap(),
+            0
+        );
+    }
+    assert_eq!(
+        database.get_usage_cycle_start(&tenant.id).await.unwrap(),
+        Some(this_cycle)
+    );
+
+    // Exports and reports still read the closed cycle's records
+    assert_eq!(usage_record_count(&database, &tenant.id).await, 3);
+
+    let history = usage_history(&database, &tenant.id).await;
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].metric, "web_socket_minutes");
+    assert_eq!(history[0].quantity, 45);
+    assert_eq!(history[1].metric, "events_published");
+    assert_eq!(history[1].quantity, 150);
+    for entry in &history {
+        assert_eq!(entry.project_id, project.id);
+        assert_eq!(entry.cycle_start, last_cycle);
+        assert_eq!(entry.cycle_end, this_cycle);
+    }
+
+    // Rolling over again within the same cycle changes nothing
+    roll_over_due_cycles(&database, UsageCycleAnchor::CalendarMonth, now)
+        .await
+        .unwrap();
+    assert_eq!(usage_history(&database, &tenant.id).await.len(), 2);
+}
+
+#[tokio::test]
+async fn test_rollover_keeps_usage_from_the_new_cycle() {
+    let database = test_database().await;
+    let (tenant, project) = create_project(&database, "Rollover Current Tenant").await;
+
+    let now = Utc::now();
+    let this_cycle = usage_window_start(now);
+    let last_cycle = this_cycle - Months::new(1);
+    database
+        .start_usage_cycle(&tenant.id, last_cycle)
+        .await
+        .unwrap();
+
+    database
+        .increment_usage(
+            &tenant.id,
+            &project.id,
+            UsageMetric::EventsPublished,
+            500,
+            last_cycle,
+            None,
+        )
+        .await
+        .unwrap();
+    database
+        .increment_usage(
+            &tenant.id,
+            &project.id,
+            UsageMetric::EventsPublished,
+            7,
+            this_cycle,
+            None,
+        )
+        .await
+        .unwrap();
+
+    roll_over_due_cycles(&database, UsageCycleAnchor::CalendarMonth, now)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        database
+            .get_usage_for_tenant(&tenant.id, UsageMetric::EventsPublished)
+            .await
+            .unwrap(),
+        7
+    );
+    let history = usage_history(&database, &tenant.id).await;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].quantity, 500);
+}
+
+#[tokio::test]
+async fn test_rollover_lifts_only_usage_suspensions() {
+    let database = test_database().await;
+    let (over_quota, _) = create_project(&database, "Over Quota Tenant").await;
+    let (unpaid, _) = create_project(&database, "Unpaid Tenant").await;
+
+    suspend_tenant(
+        &database,
+        &over_quota.id,
+        USAGE_LIMIT_SUSPENSION_REASON,
+        None,
+    )
+    .await
+    .unwrap();
+    suspend_tenant(&database, &unpaid.id, "Payment failed", None)
+        .await
+        .unwrap();
+
+    let now = Utc::now();
+    let last_cycle = usage_window_start(now) - Months::new(1);
+    for tenant in [&over_quota, &unpaid] {
+        database
+            .start_usage_cycle(&tenant.id, last_cycle)
+            .await
+            .unwrap();
+    }
+
+    roll_over_due_cycles(&database, UsageCycleAnchor::CalendarMonth, now)
+        .await
+        .unwrap();
+
+    let status = database
+        .get_tenant(&over_quota.id)
+        .await
+        .unwrap()
+        .unwrap()
+        .status;
+    assert_eq!(status, TenantStatus::Active);
+    let status = database
+        .get_tenant(&unpaid.id)
+        .await
+        .unwrap()
+        .unwrap()
+        .status;
+    assert_eq!(status, TenantStatus::Suspended);
+}
+
+#[tokio::test]
+async fn test_first_run_archives_usage_before_the_cycle() {
+    let database = test_database().await;
+    let (tenant, project) = create_project(&database, "New Cycle Tenant").await;
+
+    let now = Utc::now();
+    let this_cycle = usage_window_start(now);
+    for (quantity, window_start) in [(40, this_cycle - Months::new(1)), (12, this_cycle)] {
+        database
+            .increment_usage(
+                &tenant.id,
+                &project.id,
+                UsageMetric::EventsPublished,
+                quantity,
+                window_start,
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    roll_over_due_cycles(&database, UsageCycleAnchor::CalendarMonth, now)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        database.get_usage_cycle_start(&tenant.id).await.unwrap(),
+        Some(this_cycle)
+    );
+    assert_eq!(
+        database
+            .get_usage_for_tenant(&tenant.id, UsageMetric::EventsPublished)
+            .await
+            .unwrap(),
+        12
+    );
+    let history = usage_history(&database, &tenant.id).await;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].quantity, 40);
+    assert_eq!(history[0].cycle_end, this_cycle);
+    assert!(history[0].cycle_start <= this_cycle);
+
+    // Later runs don't archive the same usage again
+    roll_over_due_cycles(&database, UsageCycleAnchor::CalendarMonth, now)
+        .await
+        .unwrap();
+    assert_eq!(usage_history(&database, &tenant.id).await.len(), 1);
+}
@@ -0,0 +1,47 @@
+/// **Feature: realtime-saas-platform, Live usage updates**
+///
+/// Every stored usage record is announced to live subscribers of its tenant, which
+/// is what backs the `usageUpdates` GraphQL subscription.
+use futures_util::StreamExt;
+use realtime_api::models::{Project, Tenant, UsageMetric, UsageRecord};
+use std::time::Duration;
+
+mod common;
+
+use common::{create_project, test_database, test_event_service};
+
+fn usage(tenant: &Tenant, project: &Project, quantity: i64) -> UsageRecord {
+    UsageRecord::new(
+        tenant.id.clone(),
+        project.id.clone(),
+        UsageMetric::EventsPublished,
+        quantity,
+        chrono::Utc::now(),
+    )
+}
+
+#[tokio::test]
+async fn test_usage_updates_are_filtered_by_tenant() {
+    let database = test_database().await;
+    let event_service = test_event_service(database.clone()).await;
+    let (tenant_a, project_a) = create_project(&database, "Usage Stream Tenant").await;
+    let (tenant_b, project_b) = create_project(&database, "Usage Stream Tenant").await;
+
+    let mut updates = Box::pin(event_service.subscribe_usage(&tenant_a.id));
+
+    event_service
+        .record_usage(&usage(&tenant_b, &project_b, 5))
+        .await
+        .expect("Failed to record usage");
+    event_service
+        .record_usage(&usage(&tenant_a, &project_a, 3))
+        .await
+        .expect("Failed to record usage");
+
+    let update = tokio::time::timeout(Duration::from_secs(5), updates.next())
+        .await
+        .expect("Timed out waiting for usage update")
+        .expect("Usage stream ended");
+    assert_eq!(update.tenant_id, tenant_a.id);
+    assert_eq!(update.quantity, 3);
+}
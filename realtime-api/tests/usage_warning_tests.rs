@@ -0,0 +1,150 @@
+/// **Feature: realtime-saas-platform, Soft-limit usage warnings**
+///
+/// A tenant approaching its monthly event quota is warned once per window at
+/// each configured threshold; staying above a threshold doesn't warn again.
+/// Fired thresholds are recorded in the database, so other instances and
+/// restarts don't warn again either.
+use chrono::{TimeZone, Utc};
+use realtime_api::config::{Config, ConfigError, MIN_JWT_SECRET_LENGTH};
+use realtime_api::models::UsageMetric;
+use realtime_api::usage_warnings::{usage_window_start, UsageWarnings, USAGE_REFRESH_INTERVAL};
+use std::time::Instant;
+
+mod common;
+
+use common::{create_project, test_database};
+
+const QUOTA: i64 = 10_000;
+
+/// Count publishes on top of a cached total, marking whatever they cross as
+/// fired the way the event service does
+fn publish(warnings: &UsageWarnings, tenant_id: &str, published: i64, now: Instant) -> Vec<u32> {
+    let (_, used) = warnings
+        .count(tenant_id, published, now)
+        .expect("Usage should be cached");
+    let crossed = warnings.crossed(tenant_id, used, QUOTA);
+    for threshold in &crossed {
+        warnings.mark_fired(tenant_id, *threshold);
+    }
+    crossed
+}
+
+#[test]
+fn test_crossing_threshold_fires_once() {
+    let warnings = UsageWarnings::new(vec![80, 95]);
+    let window = usage_window_start(Utc::now());
+    let now = Instant::now();
+    warnings.refresh("tenant", window, 7_998, now);
+
+    assert!(publish(&warnings, "tenant", 1, now).is_empty());
+    assert_eq!(publish(&warnings, "tenant", 1, now), vec![80]);
+
+    // Staying above 80% doesn't refire
+    for published in [1, 499, 998] {
+        assert!(publish(&warnings, "tenant", published, now).is_empty());
+    }
+
+    assert_eq!(publish(&warnings, "tenant", 1, now), vec![95]);
+    assert!(publish(&warnings, "tenant", 500, now).is_empty());
+}
+
+#[test]
+fn test_thresholds_crossed_together_each_fire() {
+    let warnings = UsageWarnings::new(vec![95, 80]);
+    let window = usage_window_start(Utc::now());
+    let now = Instant::now();
+    warnings.refresh("tenant", window, 9_699, now);
+
+    assert_eq!(publish(&warnings, "tenant", 1, now), vec![80, 95]);
+    assert!(publish(&warnings, "tenant", 100, now).is_empty());
+}
+
+#[test]
+fn test_usage_is_reread_after_refresh_interval() {
+    let warnings = UsageWarnings::new(vec![80]);
+    let window = usage_window_start(Utc::now());
+    let now = Instant::now();
+
+    assert_eq!(warnings.count("tenant", 1, now), None);
+    warnings.refresh("tenant", window, 100, now);
+    assert_eq!(warnings.count("tenant", 1, now), Some((window, 101)));
+    assert_eq!(
+        warnings.count("tenant", 1, now + USAGE_REFRESH_INTERVAL),
+        None
+    );
+
+    // Other instances' publishes show up in the refreshed total
+    let later = now + USAGE_REFRESH_INTERVAL;
+    warnings.refresh("tenant", window, 8_000, later);
+    assert_eq!(warnings.count("tenant", 0, later), Some((window, 8_000)));
+}
+
+#[test]
+fn test_fired_thresholds_are_tracked_per_tenant_and_window() {
+    let warnings = UsageWarnings::new(vec![80]);
+    let march = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+    let april = Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap();
+    let now = Instant::now();
+    warnings.refresh("tenant_a", march, 8_000, now);
+    warnings.refresh("tenant_b", march, 8_000, now);
+
+    warnings.mark_fired("tenant_a", 80);
+    assert!(warnings.crossed("tenant_a", 9_000, QUOTA).is_empty());
+    assert_eq!(warnings.crossed("tenant_b", 8_000, QUOTA), vec![80]);
+
+    // A new window warns again
+    warnings.refresh("tenant_a", april, 8_000, now);
+    assert_eq!(warnings.crossed("tenant_a", 8_000, QUOTA), vec![80]);
+}
+
+#[test]
+fn test_unlimited_or_empty_quota_never_warns() {
+    let warnings = UsageWarnings::new(vec![80]);
+    warnings.refresh("tenant", usage_window_start(Utc::now()), 0, Instant::now());
+
+    assert!(warnings.crossed("tenant", 1_000_000, 0).is_empty());
+    assert!(UsageWarnings::new(vec![])
+        .crossed("tenant", QUOTA, QUOTA)
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_warning_is_recorded_once_per_window() {
+    let database = test_database().await;
+    let (tenant, _) = create_project(&database, "Usage Warning Tenant").await;
+    let march = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+    let april = Utc.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap();
+
+    let database = &database;
+    let tenant_id = tenant.id.as_str();
+    let record = move |threshold: u32, window_start| {
+        database.record_usage_warning(
+            tenant_id,
+            UsageMetric::EventsPublished,
+            threshold,
+            window_start,
+        )
+    };
+    assert!(record(80, march).await.unwrap());
+    // A second instance crossing the same threshold doesn't fire it
+    assert!(!record(80, march).await.unwrap());
+    assert!(record(95, march).await.unwrap());
+    assert!(record(80, april).await.unwrap());
+}
+
+#[test]
+fn test_thresholds_must_be_percentages() {
+    let mut config = Config::default();
+    config.jwt_secret = "x".repeat(MIN_JWT_SECRET_LENGTH);
+    assert_eq!(config.billing.usage_warning_thresholds, vec![80, 95]);
+    assert_eq!(config.validate(), Ok(()));
+
+    config.billing.usage_warning_thresholds = vec![0, 80, 150];
+    assert_eq!(
+        config.validate(),
+        Err(vec![
+            ConfigError::InvalidUsageWarningThreshold(0),
+            ConfigError::InvalidUsageWarningThreshold(150),
+        ])
+    );
+}
@@ -0,0 +1,129 @@
+/// **Feature: realtime-saas-platform, Schema validation modes**
+///
+/// A project's `validation_mode` decides what happens to a payload violating
+/// its topic schema: `strict` rejects it, `advisory` publishes it flagged as
+/// invalid and counts the violation, and `off` publishes it unchecked.
+use realtime_api::database::Database;
+use realtime_api::event_service::{EventService, PublishResult};
+use realtime_api::models::{Event, Project, ProjectSettings, Tenant};
+use realtime_api::schema_validator::{schema_violations_counter, ValidationMode};
+use serde_json::json;
+
+mod common;
+
+use common::{create_project, test_database, test_event_service};
+
+async fn setup(validation_mode: ValidationMode) -> (Database, EventService, Tenant, Project) {
+    let database = test_database().await;
+    let (tenant, mut project) = create_project(&database, "Validation Mode Tenant").await;
+    project.settings.validation_mode = validation_mode;
+    database
+        .update_project_settings(&tenant.id, &project.id, &project.settings)
+        .await
+        .expect("Failed to update project settings");
+
+    let event_service = test_event_service(database.clone()).await;
+
+    // An enforce-mode schema, which rejects violations unless the project says otherwise
+    event_service.schema_validator().register_schema(
+        &project.id,
+        "order.placed",
+        json!({
+            "type": "object",
+            "properties": {"order_id": {"type": "string"}},
+            "required": ["order_id"]
+        }),
+    );
+
+    (database, event_service, tenant, project)
+}
+
+fn violating_event(tenant: &Tenant, project: &Project) -> Event {
+    Event::new(
+        tenant.id.clone(),
+        project.id.clone(),
+        "order.placed".to_string(),
+        json!({"order_id": 7}),
+    )
+}
+
+fn accepted_violations() -> f64 {
+    schema_violations_counter()
+        .with_label_values(&["accepted"])
+        .get()
+}
+
+#[test]
+fn test_validation_mode_defaults_to_strict() {
+    assert_eq!(
+        ProjectSettings::default().validation_mode,
+        ValidationMode::Strict
+    );
+
+    // Projects stored before the setting existed stay strict
+    let settings: ProjectSettings =
+        serde_json::from_value(json!({"auto_register_schema_from_first_event": true})).unwrap();
+    assert_eq!(settings.validation_mode, ValidationMode::Strict);
+}
+
+#[tokio::test]
+async fn test_strict_mode_rejects_violations() {
+    let (database, service, tenant, project) = setup(ValidationMode::Strict).await;
+    let event = violating_event(&tenant, &project);
+
+    let result = service.publish_event(&event).await.unwrap();
+    assert!(
+        matches!(result, PublishResult::ValidationFailed(_)),
+        "{:?}",
+        result
+    );
+    assert!(database
+        .get_event(&tenant.id, &event.id)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_advisory_mode_publishes_and_flags_violations() {
+    let (database, service, tenant, project) = setup(ValidationMode::Advisory).await;
+    let event = violating_event(&tenant, &project);
+    let before = accepted_violations();
+
+    let result = service.publish_event(&event).await.unwrap();
+    assert!(
+        matches!(result, PublishResult::Success { .. }),
+        "{:?}",
+        result
+    );
+
+    let stored = database
+        .get_event(&tenant.id, &event.id)
+        .await
+        .unwrap()
+        .expect("Event should be stored");
+    assert_eq!(stored.schema_valid, Some(false));
+    assert_eq!(stored.schema_version, Some(1));
+    assert!(accepted_violations() > before);
+}
+
+#[tokio::test]
+async fn test_off_mode_skips_validation() {
+    let (database, service, tenant, project) = setup(ValidationMode::Off).await;
+    let event = violating_event(&tenant, &project);
+
+    let result = service.publish_event(&event).await.unwrap();
+    assert!(
+        matches!(result, PublishResult::Success { .. }),
+        "{:?}",
+        result
+    );
+
+    let stored = database
+        .get_event(&tenant.id, &event.id)
+        .await
+        .unwrap()
+        .expect("Event should be stored");
+    assert_eq!(stored.schema_valid, None);
+    assert_eq!(stored.schema_version, None);
+}
@@ -0,0 +1,339 @@
+/// **Feature: realtime-saas-platform, Outbound webhooks**
+///
+/// Events are POSTed to webhook endpoints with an HMAC-SHA256 `X-Signature`
+/// of the body; failed deliveries are retried with exponential backoff until
+/// the attempts run out. Webhook secrets are stored encrypted and opened for
+/// each delivery.
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use realtime_api::cloudevents::{CloudEvent, DeliveryFormat, CLOUDEVENTS_CONTENT_TYPE};
+use realtime_api::models::{Event, Webhook};
+use realtime_api::request_signing::{SigningSecretCipher, SEALED_SECRET_PREFIX};
+use realtime_api::webhooks::{
+    sign_payload, verify_signature, RetryPolicy, WebhookDeliveryError, WebhookDispatcher,
+    EVENT_ID_HEADER, SIGNATURE_HEADER, WEBHOOK_ID_HEADER,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+mod common;
+
+use common::{create_project, test_database};
+
+const SECRET: &str = "whsec_test_secret";
+
+/// Webhook receiver that fails its first `failures` requests
+#[derive(Default)]
+struct MockReceiver {
+    failures: AtomicUsize,
+    requests: Mutex<Vec<(HeaderMap, Bytes)>>,
+}
+
+async fn receive(
+    State(receiver): State<Arc<MockReceiver>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    receiver.requests.lock().unwrap().push((headers, body));
+    let failed = receiver
+        .failures
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+        .is_ok();
+    if failed {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    }
+}
+
+/// Start a receiver failing `failures` times; returns it and its URL
+async fn mock_receiver(failures: usize) -> (Arc<MockReceiver>, String) {
+    let receiver = Arc::new(MockReceiver {
+        failures: AtomicUsize::new(failures),
+        ..MockReceiver::default()
+    });
+    let app = Router::new()
+        .route("/hook", post(receive))
+        .with_state(receiver.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (receiver, url)
+}
+
+fn webhook(url: &str) -> Webhook {
+    let now = chrono::Utc::now();
+    Webhook {
+        id: "webhook_1".to_string(),
+        tenant_id: "tenant_123".to_string(),
+        project_id: "project_456".to_string(),
+        url: url.to_string(),
+        secret: cipher().seal("webhook_1", SECRET),
+        topic_filter: "orders.>".to_string(),
+        format: DeliveryFormat::Native,
+        active: true,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+fn event() -> Event {
+    Event::new(
+        "tenant_123".to_string(),
+        "project_456".to_string(),
+        "orders.created".to_string(),
+        serde_json::json!({"order_id": 42}),
+    )
+}
+
+/// The cipher the test webhooks' secrets are stored under
+fn cipher() -> SigningSecretCipher {
+    SigningSecretCipher::new("test_secret")
+}
+
+/// A dispatcher allowed to reach the mock receivers on 127.0.0.1
+fn dispatcher(max_attempts: u32) -> WebhookDispatcher {
+    WebhookDispatcher::new(
+        RetryPolicy {
+            max_attempts,
+            initial_backoff: Duration::from_millis(20),
+            max_backoff: Duration::from_millis(100),
+        },
+        Duration::from_secs(5),
+        cipher(),
+    )
+    .with_private_addresses(true)
+}
+
+#[test]
+fn test_signature_is_hmac_sha256_of_body() {
+    // HMAC-SHA256("key", "The quick brown fox jumps over the lazy dog")
+    let signature = sign_payload(b"The quick brown fox jumps over the lazy dog", "key");
+    assert_eq!(
+        signature,
+        "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+    );
+
+    let body = br#"{"id":"evt_1"}"#;
+    let signature = sign_payload(body, "secret");
+    assert!(verify_signature(body, &signature, "secret"));
+    assert!(!verify_signature(body, &signature, "other-secret"));
+    assert!(!verify_signature(br#"{"id":"evt_2"}"#, &signature, "secret"));
+    assert!(!verify_signature(body, "not-a-signature", "secret"));
+}
+
+#[test]
+fn test_webhook_matches_topic_filter_when_active() {
+    let mut webhook = webhook("http://localhost/hook");
+    assert!(webhook.matches("orders.created"));
+    assert!(webhook.matches("orders.eu.refunded"));
+    assert!(!webhook.matches("users.created"));
+
+    webhook.active = false;
+    assert!(!webhook.matches("orders.created"));
+}
+
+#[tokio::test]
+async fn test_delivery_is_signed_with_webhook_secret() {
+    let (receiver, url) = mock_receiver(0).await;
+    let webhook = webhook(&url);
+    let event = event();
+
+    let attempts = dispatcher(3).deliver(&webhook, &event).await.unwrap();
+    assert_eq!(attempts, 1);
+
+    let requests = receiver.requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    let (headers, body) = &requests[0];
+
+    let signature = headers[SIGNATURE_HEADER].to_str().unwrap();
+    assert_eq!(signature, sign_payload(body, SECRET));
+    assert!(verify_signature(body, signature, SECRET));
+    assert_eq!(headers[WEBHOOK_ID_HEADER], "webhook_1");
+    assert_eq!(headers[EVENT_ID_HEADER], event.id.as_str());
+    assert_eq!(headers["content-type"], "application/json");
+
+    let delivered: Event = serde_json::from_slice(body).unwrap();
+    assert_eq!(delivered.id, event.id);
+    assert_eq!(delivered.payload, event.payload);
+}
+
+#[tokio::test]
+async fn test_secret_sealed_for_another_webhook_is_not_used() {
+    let (receiver, url) = mock_receiver(0).await;
+    let mut webhook = webhook(&url);
+    webhook.secret = cipher().seal("webhook_2", SECRET);
+
+    let result = dispatcher(3).deliver(&webhook, &event()).await;
+    assert!(
+        matches!(
+            result,
+            Err(WebhookDeliveryError::Request { attempts: 0, .. })
+        ),
+        "{:?}",
+        result
+    );
+    assert!(receiver.requests.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_plaintext_secrets_are_sealed_in_place() {
+    let database = test_database().await;
+    let (tenant, project) = create_project(&database, "Webhook Secret Tenant").await;
+    let mut webhook = webhook("https://example.com/hook");
+    webhook.id = uuid::Uuid::new_v4().to_string();
+    webhook.tenant_id = tenant.id.clone();
+    webhook.project_id = project.id;
+    webhook.secret = SECRET.to_string();
+    database.create_webhook(&webhook).await.unwrap();
+
+    database
+        .seal_plaintext_webhook_secrets(&cipher())
+        .await
+        .unwrap();
+    let stored = database
+        .get_webhook(&tenant.id, &webhook.id)
+        .await
+        .unwrap()
+        .expect("Webhook should exist");
+    assert!(stored.secret.starts_with(SEALED_SECRET_PREFIX));
+    assert_eq!(
+        cipher().open(&webhook.id, &stored.secret).as_deref(),
+        Some(SECRET)
+    );
+
+    // Secrets already sealed are left alone
+    database
+        .seal_plaintext_webhook_secrets(&cipher())
+        .await
+        .unwrap();
+    let resealed = database
+        .get_webhook(&tenant.id, &webhook.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(resealed.secret, stored.secret);
+}
+
+#[tokio::test]
+async fn test_cloudevents_webhook_receives_envelope() {
+    let (receiver, url) = mock_receiver(0).await;
+    let mut webhook = webhook(&url);
+    webhook.format = DeliveryFormat::CloudEvents;
+    let event = event();
+
+    dispatcher(1).deliver(&webhook, &event).await.unwrap();
+
+    let requests = receiver.requests.lock().unwrap();
+    let (headers, body) = &requests[0];
+    assert_eq!(headers["content-type"], CLOUDEVENTS_CONTENT_TYPE);
+    assert!(verify_signature(body, headers[SIGNATURE_HEADER].to_str().unwrap(), SECRET));
+
+    let delivered: CloudEvent = serde_json::from_slice(body).unwrap();
+    assert_eq!(delivered, CloudEvent::from(&event));
+    assert_eq!(delivered.event_type, "orders.created");
+    assert_eq!(delivered.tenantid, "tenant_123");
+}
+
+#[tokio::test]
+async fn test_failed_deliveries_are_retried_with_backoff() {
+    let (receiver, url) = mock_receiver(2).await;
+
+    let started = Instant::now();
+    let attempts = dispatcher(5).deliver(&webhook(&url), &event()).await.unwrap();
+    assert_eq!(attempts, 3);
+    // Waits of 20ms then 40ms between the three attempts
+    assert!(started.elapsed() >= Duration::from_millis(60));
+
+    let requests = receiver.requests.lock().unwrap();
+    assert_eq!(requests.len(), 3);
+    // Every retry carries the same body and signature
+    assert!(requests.iter().all(|(headers, body)| {
+        body == &requests[0].1 && headers[SIGNATURE_HEADER] == requests[0].0[SIGNATURE_HEADER]
+    }));
+}
+
+#[tokio::test]
+async fn test_delivery_gives_up_after_max_attempts() {
+    let (receiver, url) = mock_receiver(usize::MAX).await;
+
+    let error = dispatcher(3)
+        .deliver(&webhook(&url), &event())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        WebhookDeliveryError::Status {
+            status: 503,
+            attempts: 3
+        }
+    );
+    assert_eq!(receiver.requests.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_unreachable_endpoint_is_retried_then_reported() {
+    // Bind and immediately release a port so nothing is listening on it
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    drop(listener);
+
+    let error = dispatcher(2)
+        .deliver(&webhook(&url), &event())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(error, WebhookDeliveryError::Request { attempts: 2, .. }),
+        "{:?}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn test_private_addresses_are_refused_by_default() {
+    let (receiver, url) = mock_receiver(0).await;
+    let dispatcher = dispatcher(1).with_private_addresses(false);
+
+    for url in [url, "http://localhost:9/hook".to_string()] {
+        let error = dispatcher.deliver(&webhook(&url), &event()).await.unwrap_err();
+        assert!(
+            matches!(error, WebhookDeliveryError::Request { attempts: 1, .. }),
+            "{:?}",
+            error
+        );
+    }
+    assert!(receiver.requests.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_redirects_are_not_followed() {
+    let (receiver, target) = mock_receiver(0).await;
+    let app = Router::new().route(
+        "/hook",
+        post(move || {
+            let target = target.clone();
+            async move { axum::response::Redirect::temporary(&target) }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let error = dispatcher(1)
+        .deliver(&webhook(&url), &event())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        WebhookDeliveryError::Status {
+            status: 307,
+            attempts: 1
+        }
+    );
+    assert!(receiver.requests.lock().unwrap().is_empty());
+}
@@ -0,0 +1,200 @@
+/// **Feature: realtime-saas-platform, WebSocket permessage-deflate**
+///
+/// Clients that offer permessage-deflate in `Sec-WebSocket-Extensions` get it
+/// accepted on `/ws`; messages from the compression threshold up travel as
+/// deflated frames with RSV1 set in both directions. Clients that don't offer
+/// it keep plain frames.
+use flate2::{Decompress, FlushDecompress};
+use futures_util::StreamExt;
+use realtime_api::api::AppState;
+use realtime_api::models::Scope;
+use realtime_api::routes::create_router;
+use realtime_api::ws_compression::set_compression;
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+
+mod common;
+
+use common::{create_project, test_state};
+
+const DEFLATE_TAIL: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// Serve the router on a local port and return a subscribe key
+async fn serve(state: AppState) -> (SocketAddr, String) {
+    // Compress every message, however small
+    set_compression(0, 6);
+
+    let (tenant, project) = create_project(&state.database, "WebSocket Compression Tenant").await;
+    let (raw_key, _) = state
+        .auth_service
+        .create_api_key(
+            tenant.id,
+            project.id,
+            vec![Scope::EventsSubscribe],
+            100,
+            vec![],
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = create_router(state);
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (addr, raw_key)
+}
+
+/// Read one server frame, returning its first header byte and payload
+async fn read_frame(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+    let mut header = [0u8; 2];
+    stream.read_exact(&mut header).await.unwrap();
+    assert_eq!(header[1] & 0x80, 0, "Server frames must not be masked");
+    let length = match header[1] & 0x7f {
+        126 => stream.read_u16().await.unwrap() as usize,
+        127 => stream.read_u64().await.unwrap() as usize,
+        length => length as usize,
+    };
+    let mut payload = vec![0u8; length];
+    stream.read_exact(&mut payload).await.unwrap();
+    (header[0], payload)
+}
+
+/// Read server frames up to the next data frame, skipping pings
+async fn read_data_frame(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+    loop {
+        let (first, payload) = read_frame(stream).await;
+        if first & 0x0f != 0x9 {
+            return (first, payload);
+        }
+    }
+}
+
+fn inflate(decompress: &mut Decompress, payload: &[u8]) -> String {
+    let mut input = payload.to_vec();
+    input.extend_from_slice(&DEFLATE_TAIL);
+    let mut output = Vec::with_capacity(4096);
+    decompress
+        .decompress_vec(&input, &mut output, FlushDecompress::Sync)
+        .expect("Server frame should inflate");
+    String::from_utf8(output).unwrap()
+}
+
+/// A masked, compressed text frame, as a client sends it
+fn compressed_text_frame(text: &str) -> Vec<u8> {
+    let mut compress = flate2::Compress::new(flate2::Compression::default(), false);
+    let mut compressed = Vec::with_capacity(256);
+    compress
+        .compress_vec(
+            text.as_bytes(),
+            &mut compressed,
+            flate2::FlushCompress::Sync,
+        )
+        .unwrap();
+    assert!(compressed.ends_with(&DEFLATE_TAIL));
+    compressed.truncate(compressed.len() - DEFLATE_TAIL.len());
+
+    let mask = [0x12, 0x34, 0x56, 0x78];
+    // FIN, RSV1 and the text opcode
+    let mut frame = vec![0xc1, 0x80 | compressed.len() as u8];
+    frame.extend_from_slice(&mask);
+    frame.extend(
+        compressed
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| byte ^ mask[i % 4]),
+    );
+    frame
+}
+
+#[tokio::test]
+async fn test_client_offering_deflate_gets_compressed_messages() {
+    let (addr, key) = serve(test_state().await).await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!(
+        "GET /ws?topics=orders.created HTTP/1.1\r\n\
+         Host: {}\r\n\
+         Connection: Upgrade\r\n\
+         Upgrade: websocket\r\n\
+         Sec-WebSocket-Version: 13\r\n\
+         Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+         Sec-WebSocket-Extensions: permessage-deflate; client_max_window_bits\r\n\
+         Authorization: Bearer {}\r\n\r\n",
+        addr, key
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        response.push(stream.read_u8().await.unwrap());
+    }
+    let response = String::from_utf8(response).unwrap().to_ascii_lowercase();
+    assert!(response.starts_with("http/1.1 101"), "{}", response);
+    assert!(
+        response.contains("sec-websocket-accept: s3pplmbitxaq9kygzzhzrbk+xoo="),
+        "{}",
+        response
+    );
+    assert!(
+        response.contains("sec-websocket-extensions: permessage-deflate\r\n"),
+        "{}",
+        response
+    );
+
+    // The server keeps its compression context, so one inflater reads every message
+    let mut decompress = Decompress::new(false);
+    let (first, payload) = read_data_frame(&mut stream).await;
+    assert_eq!(first, 0xc1, "Expected a final, compressed text frame");
+    let connected = inflate(&mut decompress, &payload);
+    assert!(connected.contains(r#""type":"Connected""#), "{}", connected);
+
+    // A compressed message from the client is understood and answered compressed
+    stream
+        .write_all(&compressed_text_frame(r#"{"type":"Ping"}"#))
+        .await
+        .unwrap();
+    loop {
+        let (first, payload) = read_data_frame(&mut stream).await;
+        assert_eq!(first, 0xc1, "Expected a final, compressed text frame");
+        if inflate(&mut decompress, &payload) == r#"{"type":"Pong"}"# {
+            break;
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_client_without_deflate_gets_plain_messages() {
+    let (addr, key) = serve(test_state().await).await;
+
+    let mut request = format!("ws://{}/ws?topics=orders.created", addr)
+        .into_client_request()
+        .unwrap();
+    request
+        .headers_mut()
+        .insert("authorization", format!("Bearer {}", key).parse().unwrap());
+    // tungstenite never offers extensions, and rejects frames with RSV1 set
+    let (mut socket, response) = tokio_tungstenite::connect_async(request)
+        .await
+        .expect("WebSocket handshake should succeed");
+    assert!(response.headers().get("sec-websocket-extensions").is_none());
+
+    loop {
+        match socket
+            .next()
+            .await
+            .expect("Connection should stay open")
+            .unwrap()
+        {
+            Message::Ping(_) => continue,
+            Message::Text(text) => {
+                assert!(text.contains(r#""type":"Connected""#), "{}", text);
+                break;
+            }
+            other => panic!("Expected the Connected message, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+/// **Feature: realtime-saas-platform, WebSocket idle timeout**
+///
+/// The server pings WebSocket clients every half idle timeout. A passive
+/// subscriber that answers the pings stays connected; one that answers
+/// nothing is closed with `idle_timeout` once the project's timeout elapses.
+use futures_util::StreamExt;
+use realtime_api::api::AppState;
+use realtime_api::idle::{IDLE_CLOSE_CODE, IDLE_CLOSE_REASON};
+use realtime_api::models::Scope;
+use realtime_api::routes::create_router;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+
+mod common;
+
+use common::{create_project, test_state};
+
+const IDLE_TIMEOUT_SECS: i32 = 2;
+
+/// Serve the router on a local port and return a subscribe key for a
+/// project with a short idle timeout
+async fn serve(state: AppState) -> (SocketAddr, String) {
+    let (tenant, mut project) = create_project(&state.database, "WebSocket Idle Tenant").await;
+    project.limits.idle_timeout_secs = IDLE_TIMEOUT_SECS;
+    state
+        .database
+        .update_project_limits(&tenant.id, &project.id, &project.limits)
+        .await
+        .expect("Failed to update project limits");
+    let (raw_key, _) = state
+        .auth_service
+        .create_api_key(
+            tenant.id,
+            project.id,
+            vec![Scope::EventsSubscribe],
+            100,
+            vec![],
+            None,
+        )
+        .await
+        .expect("Failed to create API key");
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = create_router(state);
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (addr, raw_key)
+}
+
+async fn connect(
+    addr: SocketAddr,
+    key: &str,
+) -> tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>> {
+    let mut request = format!("ws://{}/ws?topics=orders.created", addr)
+        .into_client_request()
+        .unwrap();
+    request
+        .headers_mut()
+        .insert("authorization", format!("Bearer {}", key).parse().unwrap());
+    let (socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .expect("WebSocket handshake should succeed");
+    socket
+}
+
+#[tokio::test]
+async fn test_passive_subscriber_answering_pings_stays_connected() {
+    let (addr, key) = serve(test_state().await).await;
+    let mut socket = connect(addr, &key).await;
+
+    // Reading answers each ping with a pong; nothing else is sent
+    let mut pings = 0;
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(IDLE_TIMEOUT_SECS as u64 * 2);
+    while let Ok(frame) = tokio::time::timeout_at(deadline, socket.next()).await {
+        match frame.expect("Connection should stay open").unwrap() {
+            Message::Ping(_) => pings += 1,
+            Message::Close(frame) => panic!("Passive subscriber was closed: {:?}", frame),
+            _ => {}
+        }
+    }
+    assert!(
+        pings >= 2,
+        "Expected pings every half idle timeout, got {}",
+        pings
+    );
+}
+
+#[tokio::test]
+async fn test_unresponsive_client_is_closed_when_idle() {
+    let (addr, key) = serve(test_state().await).await;
+    let mut socket = connect(addr, &key).await;
+
+    // Not reading means the pings go unanswered
+    tokio::time::sleep(Duration::from_secs(IDLE_TIMEOUT_SECS as u64 + 1)).await;
+
+    let close = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match socket.next().await {
+                Some(Ok(Message::Close(frame))) => return frame,
+                Some(Ok(_)) => continue,
+                other => panic!("Expected a close frame, got {:?}", other),
+            }
+        }
+    })
+    .await
+    .expect("Idle connection should be closed")
+    .expect("Close frame should carry a code");
+    assert_eq!(u16::from(close.code), IDLE_CLOSE_CODE);
+    assert_eq!(close.reason, IDLE_CLOSE_REASON);
+}
@@ -0,0 +1,123 @@
+/// **Feature: realtime-saas-platform, MessagePack WebSocket framing**
+///
+/// Clients negotiating the `realtime.msgpack` subprotocol exchange every
+/// `WebSocketMessage` as MessagePack in binary frames; JSON text frames stay
+/// the default.
+use axum::extract::ws::Message;
+use realtime_api::cloudevents::{CloudEvent, DeliveryFormat, EventEncoding};
+use realtime_api::models::Event;
+use realtime_api::websocket::{encode_frame, WebSocketMessage};
+use realtime_api::ws_framing::WireFormat;
+
+fn event() -> Event {
+    Event::new(
+        "tenant_123".to_string(),
+        "project_456".to_string(),
+        "sensors.temperature".to_string(),
+        serde_json::json!({"reading": 21.5, "samples": [1, 2, 3], "ok": true}),
+    )
+}
+
+fn every_variant() -> Vec<WebSocketMessage> {
+    let event = event();
+    vec![
+        WebSocketMessage::Subscribe {
+            topics: vec!["sensors.*".to_string(), "alerts.>".to_string()],
+        },
+        WebSocketMessage::Unsubscribe {
+            topics: vec!["alerts.>".to_string()],
+        },
+        WebSocketMessage::sequenced(&event, Some(42)),
+        WebSocketMessage::from(&event),
+        WebSocketMessage::Connected {
+            connection_id: "conn_1".to_string(),
+        },
+        WebSocketMessage::Error {
+            message: "Invalid topic".to_string(),
+        },
+        WebSocketMessage::Close {
+            code: 4001,
+            reason: "tenant suspended".to_string(),
+        },
+        WebSocketMessage::Lagged {
+            dropped: 17,
+            cursor: Some(41),
+        },
+        WebSocketMessage::Lagged {
+            dropped: 3,
+            cursor: None,
+        },
+        WebSocketMessage::Ping,
+        WebSocketMessage::Pong,
+    ]
+}
+
+// Messages don't implement PartialEq, so compare their JSON forms
+fn json(message: &WebSocketMessage) -> serde_json::Value {
+    serde_json::to_value(message).unwrap()
+}
+
+#[test]
+fn test_every_variant_round_trips_through_msgpack() {
+    for message in every_variant() {
+        let frame = WireFormat::MessagePack.encode(&message).unwrap();
+        assert!(matches!(frame, Message::Binary(_)), "{:?}", message);
+
+        let decoded: WebSocketMessage = WireFormat::MessagePack
+            .decode(&frame)
+            .expect("binary frames carry messages")
+            .unwrap();
+        assert_eq!(json(&decoded), json(&message));
+    }
+}
+
+#[test]
+fn test_json_framing_stays_the_default() {
+    assert_eq!(WireFormat::default(), WireFormat::Json);
+
+    for message in every_variant() {
+        let frame = WireFormat::Json.encode(&message).unwrap();
+        match &frame {
+            Message::Text(text) => assert_eq!(text, &serde_json::to_string(&message).unwrap()),
+            other => panic!("expected a text frame, got {:?}", other),
+        }
+
+        let decoded: WebSocketMessage = WireFormat::Json.decode(&frame).unwrap().unwrap();
+        assert_eq!(json(&decoded), json(&message));
+    }
+}
+
+#[test]
+fn test_msgpack_is_smaller_for_numeric_payloads() {
+    let message = WebSocketMessage::sequenced(&event(), Some(42));
+    let Message::Binary(msgpack) = WireFormat::MessagePack.encode(&message).unwrap() else {
+        panic!("expected a binary frame");
+    };
+    let Message::Text(json) = WireFormat::Json.encode(&message).unwrap() else {
+        panic!("expected a text frame");
+    };
+    assert!(msgpack.len() < json.len());
+}
+
+#[test]
+fn test_malformed_msgpack_is_an_error() {
+    let frame = Message::Binary(vec![0xc1, 0x00]);
+    assert!(WireFormat::MessagePack
+        .decode::<WebSocketMessage>(&frame)
+        .unwrap()
+        .is_err());
+}
+
+#[test]
+fn test_cloudevents_deliveries_use_negotiated_framing() {
+    let event = event();
+    let message = WebSocketMessage::sequenced(&event, Some(42));
+    let encoding = EventEncoding::new(DeliveryFormat::CloudEvents, "tenant_123", "project_456");
+
+    let frame = encode_frame(WireFormat::MessagePack, &encoding, &message).unwrap();
+    let Message::Binary(bytes) = frame else {
+        panic!("expected a binary frame");
+    };
+    let delivered: CloudEvent = rmp_serde::from_slice(&bytes).unwrap();
+    assert_eq!(delivered, CloudEvent::from(&event).with_sequence(Some(42)));
+}
@@ -0,0 +1,118 @@
+/// **Feature: realtime-saas-platform, WebSocket inbound rate limiting**
+///
+/// Each WebSocket connection may send a bounded number of messages per
+/// second. Messages over budget are rejected with an error, and connections
+/// can be configured to be closed instead.
+use realtime_api::websocket::{
+    admit_inbound_message, websocket_manager, InboundAdmission, WebSocketConnection,
+    WebSocketMessage,
+};
+use realtime_api::ws_rate_limit::{
+    InboundRateLimiter, RATE_LIMITED_CLOSE_CODE, RATE_LIMITED_CLOSE_REASON,
+};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+fn open_websocket(tenant_id: &str) -> (String, broadcast::Receiver<WebSocketMessage>) {
+    let id = Uuid::new_v4().to_string();
+    let (sender, receiver) = broadcast::channel(64);
+    websocket_manager()
+        .add_connection(WebSocketConnection {
+            id: id.clone(),
+            tenant_id: tenant_id.to_string(),
+            project_id: "project".to_string(),
+            subscribed_topics: vec![],
+            sender,
+            created_at: chrono::Utc::now(),
+            acked: false,
+        })
+        .unwrap();
+    (id, receiver)
+}
+
+#[test]
+fn test_subscribe_burst_is_throttled() {
+    let tenant_id = Uuid::new_v4().to_string();
+    let (id, mut receiver) = open_websocket(&tenant_id);
+    let mut limiter = InboundRateLimiter::new(5, false);
+
+    // A burst of subscribes, each charged as it arrives
+    let admissions: Vec<InboundAdmission> = (0..20)
+        .map(|_| admit_inbound_message(&mut limiter, &id))
+        .collect();
+
+    let admitted = admissions
+        .iter()
+        .filter(|a| **a == InboundAdmission::Admitted)
+        .count();
+    assert_eq!(admitted, 5);
+    assert!(admissions[5..]
+        .iter()
+        .all(|a| *a == InboundAdmission::Throttled));
+
+    match receiver.try_recv().unwrap() {
+        WebSocketMessage::Error { message } => {
+            assert!(message.contains("Rate limit"), "{}", message)
+        }
+        other => panic!("Expected an error, got {:?}", other),
+    }
+
+    // Throttled connections stay open
+    assert_eq!(
+        websocket_manager().get_tenant_connection_count(&tenant_id),
+        1
+    );
+    websocket_manager().remove_connection(&id);
+}
+
+#[test]
+fn test_connection_is_closed_when_configured() {
+    let tenant_id = Uuid::new_v4().to_string();
+    let (id, mut receiver) = open_websocket(&tenant_id);
+    let mut limiter = InboundRateLimiter::new(2, true);
+
+    assert_eq!(
+        admit_inbound_message(&mut limiter, &id),
+        InboundAdmission::Admitted
+    );
+    assert_eq!(
+        admit_inbound_message(&mut limiter, &id),
+        InboundAdmission::Admitted
+    );
+    assert_eq!(
+        admit_inbound_message(&mut limiter, &id),
+        InboundAdmission::Closed
+    );
+
+    assert!(matches!(
+        receiver.try_recv().unwrap(),
+        WebSocketMessage::Error { .. }
+    ));
+    match receiver.try_recv().unwrap() {
+        WebSocketMessage::Close { code, reason } => {
+            assert_eq!(code, RATE_LIMITED_CLOSE_CODE);
+            assert_eq!(reason, RATE_LIMITED_CLOSE_REASON);
+        }
+        other => panic!("Expected a close frame, got {:?}", other),
+    }
+    assert_eq!(
+        websocket_manager().get_tenant_connection_count(&tenant_id),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_budget_refills_over_time() {
+    let tenant_id = Uuid::new_v4().to_string();
+    let (id, _receiver) = open_websocket(&tenant_id);
+    let mut limiter = InboundRateLimiter::new(10, false);
+
+    while admit_inbound_message(&mut limiter, &id) == InboundAdmission::Admitted {}
+
+    tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+    assert_eq!(
+        admit_inbound_message(&mut limiter, &id),
+        InboundAdmission::Admitted
+    );
+    websocket_manager().remove_connection(&id);
+}
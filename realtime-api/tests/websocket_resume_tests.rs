@@ -0,0 +1,139 @@
+/// **Feature: realtime-saas-platform, WebSocket resume**
+///
+/// A WebSocket client reconnecting with `last_sequence` or `last_event_id` is
+/// first sent the events it missed on its topics, starting after the cursor
+/// event, and is told to resync when the cursor is outside the retained history.
+use realtime_api::api::AppState;
+use realtime_api::auth::{AuthContext, AuthType};
+use realtime_api::cloudevents::DeliveryFormat;
+use realtime_api::event_service::PublishResult;
+use realtime_api::models::{BillingPlan, Event, Project, Scope, Tenant};
+use realtime_api::websocket::{WebSocketConnectionParams, WebSocketMessage};
+use realtime_api::ws_resume::{missed_events, resync_message, ResumeCursor, RESYNC_REQUIRED_PREFIX};
+use serde_json::json;
+
+mod common;
+
+use common::test_state;
+
+async fn create_project(state: &AppState, name: &str) -> (Tenant, Project) {
+    let tenant = Tenant::new(name.to_string(), BillingPlan::Free { monthly_events: 10000 });
+    let project = Project::new(tenant.id.clone(), "default".to_string());
+    state.database.create_tenant(&tenant).await.expect("Failed to create tenant");
+    state.database.create_project(&project).await.expect("Failed to create project");
+    (tenant, project)
+}
+
+fn connection_params(project: &Project, topics: &[&str], resume: Option<ResumeCursor>) -> WebSocketConnectionParams {
+    WebSocketConnectionParams {
+        tenant_id: project.tenant_id.clone(),
+        project_id: project.id.clone(),
+        topics: topics.iter().map(|topic| topic.to_string()).collect(),
+        auth_context: AuthContext {
+            tenant_id: project.tenant_id.clone(),
+            project_id: project.id.clone(),
+            scopes: vec![Scope::EventsSubscribe],
+            rate_limit_per_sec: 100,
+            allowed_topics: vec![],
+            auth_type: AuthType::ApiKey { key_id: "subscriber_key".to_string() },
+            user_id: None,
+            user_role: None,
+        },
+        format: DeliveryFormat::default(),
+        resume,
+        acked: false,
+    }
+}
+
+async fn publish(state: &AppState, project: &Project, topic: &str, n: i32) -> (Event, u64) {
+    let event = Event::new(
+        project.tenant_id.clone(),
+        project.id.clone(),
+        topic.to_string(),
+        json!({ "n": n }),
+    );
+    match state.event_service.publish_event(&event).await.expect("Failed to publish event") {
+        PublishResult::Success { sequence } => (event, sequence),
+        other => panic!("Event was not published: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_reconnect_replays_events_missed_while_disconnected() {
+    let state = test_state().await;
+    let (_, project) = create_project(&state, "Resume Tenant").await;
+    let (_, other_project) = create_project(&state, "Resume Other Tenant").await;
+
+    // The last event the client received before dropping
+    let (last_received, last_sequence) = publish(&state, &project, "orders.created", 0).await;
+
+    // Published while the client is disconnected
+    let (first_missed, _) = publish(&state, &project, "orders.created", 1).await;
+    publish(&state, &project, "invoices.created", 2).await;
+    let (second_missed, _) = publish(&state, &project, "orders.updated", 3).await;
+    publish(&state, &other_project, "orders.created", 4).await;
+
+    for cursor in [
+        ResumeCursor::Sequence(last_sequence),
+        ResumeCursor::EventId(last_received.id.clone()),
+    ] {
+        let params = connection_params(&project, &["orders.*"], Some(cursor.clone()));
+        let missed = missed_events(&state, &params, &cursor)
+            .await
+            .expect("Missed events should be replayed");
+
+        // Only the subscribed topics of this project, after the cursor event
+        let ids: Vec<&str> = missed.iter().map(|(event, _)| event.id.as_str()).collect();
+        assert_eq!(ids, vec![first_missed.id.as_str(), second_missed.id.as_str()], "{:?}", cursor);
+        assert!(missed.iter().all(|(_, sequence)| *sequence > last_sequence));
+        assert!(missed.windows(2).all(|pair| pair[0].1 < pair[1].1));
+
+        let delivered = WebSocketMessage::sequenced(&missed[0].0, Some(missed[0].1));
+        assert!(matches!(
+            delivered,
+            WebSocketMessage::Event { sequence: Some(sequence), .. } if sequence == missed[0].1
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_cursor_outside_retained_history_requires_resync() {
+    let state = test_state().await;
+    let (_, project) = create_project(&state, "Resync Tenant").await;
+    let (_, last_sequence) = publish(&state, &project, "orders.created", 0).await;
+
+    for cursor in [
+        ResumeCursor::Sequence(last_sequence + 1_000_000),
+        ResumeCursor::EventId("never-published".to_string()),
+    ] {
+        let params = connection_params(&project, &[], Some(cursor.clone()));
+        let reason = missed_events(&state, &params, &cursor)
+            .await
+            .expect_err("Cursor should not be resumable");
+
+        match resync_message(&reason) {
+            WebSocketMessage::Error { message } => {
+                assert!(message.starts_with(RESYNC_REQUIRED_PREFIX), "{}", message)
+            }
+            other => panic!("Expected an error message, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_resume_cursor_from_connect_params() {
+    assert_eq!(ResumeCursor::from_params(None, None), None);
+    assert_eq!(ResumeCursor::from_params(None, Some("  ")), None);
+    assert_eq!(ResumeCursor::from_params(Some(7), None), Some(ResumeCursor::Sequence(7)));
+    // `last_sequence` wins over `last_event_id`
+    assert_eq!(
+        ResumeCursor::from_params(Some(7), Some("3f2a9c1e")),
+        Some(ResumeCursor::Sequence(7))
+    );
+    // SSE frame ids are stream sequences
+    assert_eq!(ResumeCursor::from_params(None, Some("42")), Some(ResumeCursor::Sequence(42)));
+    assert_eq!(
+        ResumeCursor::from_params(None, Some("3f2a9c1e")),
+        Some(ResumeCursor::EventId("3f2a9c1e".to_string()))
+    );
+}